@@ -149,6 +149,10 @@ pub struct Arguments {
 
     /// A list of drivers in the following format:
     /// `<NAME>|<URL>|<SUBMISSION_ADDRESS>|<FAIRNESS_THRESHOLD>`
+    ///
+    /// Drivers submitting settlements from multiple accounts list the
+    /// additional accounts after the primary one, separated by `+`:
+    /// `<NAME>|<URL>|<SUBMISSION_ADDRESS>+<ADDITIONAL_SUBMISSION_ADDRESS>`
     #[clap(long, env, use_value_delimiter = true)]
     pub drivers: Vec<Solver>,
 
@@ -474,6 +478,8 @@ pub struct Solver {
     pub name: String,
     pub url: Url,
     pub submission_account: Account,
+    /// Further accounts the driver submits settlements from.
+    pub additional_submission_accounts: Vec<Account>,
     pub fairness_threshold: Option<U256>,
     pub requested_timeout_on_problems: bool,
 }
@@ -518,10 +524,18 @@ impl FromStr for Solver {
         ensure!(parts.len() >= 3, "not enough arguments for external solver");
         let (name, url) = (parts[0], parts[1]);
         let url: Url = url.parse()?;
-        let submission_account = match Arn::from_str(parts[2]) {
-            Ok(value) => Account::Kms(value),
-            _ => Account::Address(H160::from_str(parts[2]).context("failed to parse submission")?),
-        };
+        let mut submission_accounts = parts[2]
+            .split('+')
+            .map(|account| {
+                Ok(match Arn::from_str(account) {
+                    Ok(value) => Account::Kms(value),
+                    _ => Account::Address(
+                        H160::from_str(account).context("failed to parse submission")?,
+                    ),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let submission_account = submission_accounts.remove(0);
 
         let mut fairness_threshold: Option<U256> = Default::default();
         let mut requested_timeout_on_problems = false;
@@ -547,6 +561,7 @@ impl FromStr for Solver {
             url,
             fairness_threshold,
             submission_account,
+            additional_submission_accounts: submission_accounts,
             requested_timeout_on_problems,
         })
     }
@@ -750,6 +765,7 @@ mod test {
             submission_account: Account::Address(H160::from_slice(&hex!(
                 "C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
             ))),
+            additional_submission_accounts: vec![],
         };
         assert_eq!(driver, expected);
     }
@@ -766,6 +782,29 @@ mod test {
             submission_account: Account::Kms(
                 Arn::from_str("arn:aws:kms:supersecretstuff").unwrap(),
             ),
+            additional_submission_accounts: vec![],
+        };
+        assert_eq!(driver, expected);
+    }
+
+    #[test]
+    fn parse_driver_additional_submission_accounts() {
+        let argument = "name1|http://localhost:8080|0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2+arn:aws:kms:supersecretstuff+0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB";
+        let driver = Solver::from_str(argument).unwrap();
+        let expected = Solver {
+            name: "name1".into(),
+            url: Url::parse("http://localhost:8080").unwrap(),
+            fairness_threshold: None,
+            requested_timeout_on_problems: false,
+            submission_account: Account::Address(H160::from_slice(&hex!(
+                "C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+            ))),
+            additional_submission_accounts: vec![
+                Account::Kms(Arn::from_str("arn:aws:kms:supersecretstuff").unwrap()),
+                Account::Address(H160::from_slice(&hex!(
+                    "DEf1CA1fb7FBcDC777520aa7f396b4E015F497aB"
+                ))),
+            ],
         };
         assert_eq!(driver, expected);
     }
@@ -780,6 +819,7 @@ mod test {
             submission_account: Account::Address(H160::from_slice(&hex!(
                 "C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
             ))),
+            additional_submission_accounts: vec![],
             fairness_threshold: Some(U256::exp10(18)),
             requested_timeout_on_problems: false,
         };
@@ -797,6 +837,7 @@ mod test {
             submission_account: Account::Address(H160::from_slice(&hex!(
                 "C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
             ))),
+            additional_submission_accounts: vec![],
             fairness_threshold: None,
             requested_timeout_on_problems: true,
         };
@@ -813,6 +854,7 @@ mod test {
             submission_account: Account::Address(H160::from_slice(&hex!(
                 "C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
             ))),
+            additional_submission_accounts: vec![],
             fairness_threshold: Some(U256::exp10(18)),
             requested_timeout_on_problems: true,
         };
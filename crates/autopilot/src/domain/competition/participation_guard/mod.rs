@@ -34,7 +34,12 @@ impl SolverParticipationGuard {
             db_based_validator_config,
             drivers
                 .into_iter()
-                .map(|driver| (driver.submission_address, driver.clone()))
+                .flat_map(|driver| {
+                    driver
+                        .submission_addresses()
+                        .map(|address| (address, driver.clone()))
+                        .collect::<Vec<_>>()
+                })
                 .collect(),
         );
         validators.push(Box::new(database_solver_participation_validator));
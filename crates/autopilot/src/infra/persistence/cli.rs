@@ -1,6 +1,6 @@
 //! Command line arguments for persistence.
 
-use anyhow::Result;
+use {anyhow::Result, std::path::PathBuf};
 
 #[derive(clap::Parser, Debug, Clone)]
 pub struct S3 {
@@ -14,9 +14,29 @@ pub struct S3 {
     /// Something like "staging/mainnet/"
     #[clap(long, env)]
     pub s3_instance_upload_filename_prefix: Option<String>,
+
+    /// Store the auction instances in this local directory instead of AWS S3.
+    /// Meant for development environments without AWS credentials. The
+    /// filename prefix is still applied if set.
+    #[clap(long, env, conflicts_with = "s3_instance_upload_bucket")]
+    pub instance_archive_directory: Option<PathBuf>,
 }
 
 impl S3 {
+    /// Creates the uploader for archiving auction instances if configured.
+    pub async fn into_uploader(self) -> Result<Option<s3::Uploader>> {
+        if let Some(directory) = self.instance_archive_directory {
+            return Ok(Some(s3::Uploader::local(
+                directory,
+                self.s3_instance_upload_filename_prefix.unwrap_or_default(),
+            )));
+        }
+        Ok(match self.into()? {
+            Some(config) => Some(s3::Uploader::new(config).await),
+            None => None,
+        })
+    }
+
     pub fn into(self) -> Result<Option<s3::Config>> {
         let s3_args = &[
             &self.s3_instance_upload_bucket,
@@ -48,14 +48,8 @@ pub struct Persistence {
 }
 
 impl Persistence {
-    pub async fn new(config: Option<s3::Config>, postgres: Arc<Postgres>) -> Self {
-        Self {
-            s3: match config {
-                Some(config) => Some(s3::Uploader::new(config).await),
-                None => None,
-            },
-            postgres,
-        }
+    pub fn new(s3: Option<s3::Uploader>, postgres: Arc<Postgres>) -> Self {
+        Self { s3, postgres }
     }
 
     /// There is always only one `current` auction.
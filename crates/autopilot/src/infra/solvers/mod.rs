@@ -22,6 +22,8 @@ pub struct Driver {
     // another driver solved with surplus exceeding this driver's surplus by `threshold`
     pub fairness_threshold: Option<eth::Ether>,
     pub submission_address: eth::Address,
+    /// Further addresses the driver submits settlements from.
+    pub additional_submission_addresses: Vec<eth::Address>,
     pub requested_timeout_on_problems: bool,
     client: Client,
 }
@@ -40,27 +42,20 @@ impl Driver {
         name: String,
         fairness_threshold: Option<eth::Ether>,
         submission_account: Account,
+        additional_submission_accounts: Vec<Account>,
         requested_timeout_on_problems: bool,
     ) -> Result<Self, Error> {
-        let submission_address = match submission_account {
-            Account::Kms(key_id) => {
-                let config = ethcontract::aws_config::load_from_env().await;
-                let account =
-                    ethcontract::transaction::kms::Account::new((&config).into(), &key_id.0)
-                        .await
-                        .map_err(|_| {
-                            tracing::error!(?name, ?key_id, "Unable to load KMS account");
-                            Error::UnableToLoadKmsAccount
-                        })?;
-                account.public_address()
-            }
-            Account::Address(address) => address,
-        };
+        let submission_address = Self::address(&name, submission_account).await?;
+        let mut additional_submission_addresses = Vec::new();
+        for account in additional_submission_accounts {
+            additional_submission_addresses.push(Self::address(&name, account).await?);
+        }
         tracing::info!(
             ?name,
             ?url,
             ?fairness_threshold,
             ?submission_address,
+            ?additional_submission_addresses,
             "Creating solver"
         );
 
@@ -72,11 +67,35 @@ impl Driver {
                 .timeout(RESPONSE_TIME_LIMIT)
                 .build()
                 .map_err(Error::FailedToBuildClient)?,
-            submission_address: submission_address.into(),
+            submission_address,
+            additional_submission_addresses,
             requested_timeout_on_problems,
         })
     }
 
+    async fn address(name: &str, account: Account) -> Result<eth::Address, Error> {
+        match account {
+            Account::Kms(key_id) => {
+                let config = ethcontract::aws_config::load_from_env().await;
+                let account =
+                    ethcontract::transaction::kms::Account::new((&config).into(), &key_id.0)
+                        .await
+                        .map_err(|_| {
+                            tracing::error!(?name, ?key_id, "Unable to load KMS account");
+                            Error::UnableToLoadKmsAccount
+                        })?;
+                Ok(account.public_address().into())
+            }
+            Account::Address(address) => Ok(address.into()),
+        }
+    }
+
+    /// All addresses the driver submits settlements from.
+    pub fn submission_addresses(&self) -> impl Iterator<Item = eth::Address> + '_ {
+        std::iter::once(self.submission_address)
+            .chain(self.additional_submission_addresses.iter().copied())
+    }
+
     pub async fn solve(&self, request: &solve::Request) -> Result<solve::Response> {
         self.request_response("solve", request, None).await
     }
@@ -34,6 +34,7 @@ use {
     shared::{
         account_balances,
         bad_token::{
+            TransferFee,
             cache::CachingDetector,
             instrumented::InstrumentedBadTokenDetectorExt,
             list_based::{ListBasedDetector, UnknownTokenStrategy},
@@ -242,6 +243,7 @@ pub async fn run(args: Arguments) {
             &web3,
             args.shared.gas_estimators.as_slice(),
             args.shared.blocknative_api_key.clone(),
+            &args.shared.gas_estimation,
         )
         .await
         .expect("failed to create gas price estimator"),
@@ -292,16 +294,23 @@ pub async fn run(args: Arguments) {
 
     let trace_call_detector = args.tracing_node_url.as_ref().map(|tracing_node_url| {
         CachingDetector::new(
-            Box::new(TraceCallDetector::new(
-                shared::ethrpc::web3(
-                    &args.shared.ethrpc,
-                    &http_factory,
-                    tracing_node_url,
-                    "trace",
+            Box::new(
+                TraceCallDetector::new(
+                    shared::ethrpc::web3(
+                        &args.shared.ethrpc,
+                        &http_factory,
+                        tracing_node_url,
+                        "trace",
+                    ),
+                    eth.contracts().settlement().address(),
+                    finder,
+                )
+                .with_max_transfer_fee(
+                    args.shared
+                        .token_max_transfer_fee_bps
+                        .and_then(TransferFee::from_bps),
                 ),
-                eth.contracts().settlement().address(),
-                finder,
-            )),
+            ),
             args.shared.token_quality_cache_expiry,
             args.shared.token_quality_cache_prefetch_time,
         )
@@ -348,6 +357,7 @@ pub async fn run(args: Arguments) {
             bad_token_detector: bad_token_detector.clone(),
             tokens: token_info_fetcher.clone(),
             code_fetcher: code_fetcher.clone(),
+            balance_override_storage: Some(Arc::new(db.pool.clone())),
         },
     )
     .await
@@ -386,8 +396,10 @@ pub async fn run(args: Arguments) {
     let (competition_updates_sender, competition_updates_receiver) =
         tokio::sync::mpsc::unbounded_channel();
 
-    let persistence =
-        infra::persistence::Persistence::new(args.s3.into().unwrap(), Arc::new(db.clone())).await;
+    let persistence = infra::persistence::Persistence::new(
+        args.s3.into_uploader().await.unwrap(),
+        Arc::new(db.clone()),
+    );
     let settlement_observer =
         crate::domain::settlement::Observer::new(eth.clone(), persistence.clone());
     let settlement_contract_start_index =
@@ -428,28 +440,31 @@ pub async fn run(args: Arguments) {
             .await;
     }
 
-    let quoter = Arc::new(OrderQuoter::new(
-        price_estimator,
-        native_price_estimator.clone(),
-        gas_price_estimator,
-        Arc::new(db.clone()),
-        order_quoting::Validity {
-            eip1271_onchain_quote: chrono::Duration::from_std(
-                args.order_quoting.eip1271_onchain_quote_validity,
-            )
-            .unwrap(),
-            presign_onchain_quote: chrono::Duration::from_std(
-                args.order_quoting.presign_onchain_quote_validity,
-            )
-            .unwrap(),
-            standard_quote: chrono::Duration::from_std(
-                args.order_quoting.standard_offchain_quote_validity,
-            )
-            .unwrap(),
-        },
-        balance_fetcher.clone(),
-        args.price_estimation.quote_verification,
-    ));
+    let quoter = Arc::new(
+        OrderQuoter::new(
+            price_estimator,
+            native_price_estimator.clone(),
+            gas_price_estimator,
+            Arc::new(db.clone()),
+            order_quoting::Validity {
+                eip1271_onchain_quote: chrono::Duration::from_std(
+                    args.order_quoting.eip1271_onchain_quote_validity,
+                )
+                .unwrap(),
+                presign_onchain_quote: chrono::Duration::from_std(
+                    args.order_quoting.presign_onchain_quote_validity,
+                )
+                .unwrap(),
+                standard_quote: chrono::Duration::from_std(
+                    args.order_quoting.standard_offchain_quote_validity,
+                )
+                .unwrap(),
+            },
+            balance_fetcher.clone(),
+            args.price_estimation.quote_verification,
+        )
+        .with_bad_token_detector(bad_token_detector.clone()),
+    );
 
     let solvable_orders_cache = SolvableOrdersCache::new(
         args.min_order_validity_period,
@@ -581,6 +596,7 @@ pub async fn run(args: Arguments) {
                 driver.name.clone(),
                 driver.fairness_threshold.map(Into::into),
                 driver.submission_account,
+                driver.additional_submission_accounts,
                 driver.requested_timeout_on_problems,
             )
             .await
@@ -634,6 +650,7 @@ async fn shadow_mode(args: Arguments) -> ! {
                 driver.name.clone(),
                 driver.fairness_threshold.map(Into::into),
                 driver.submission_account,
+                driver.additional_submission_accounts,
                 driver.requested_timeout_on_problems,
             )
             .await
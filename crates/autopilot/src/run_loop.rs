@@ -561,11 +561,14 @@ impl RunLoop {
 
         let mut counter = HashMap::new();
         solutions.retain(|participant| {
-            let submission_address = participant.driver().submission_address;
-            let is_solution_from_driver = participant.solution().solver() == submission_address;
-
-            // Filter out solutions that don't come from their corresponding submission
-            // address
+            let submission_address = participant.solution().solver();
+            let is_solution_from_driver = participant
+                .driver()
+                .submission_addresses()
+                .any(|address| address == submission_address);
+
+            // Filter out solutions that don't come from one of their corresponding
+            // submission addresses
             if !is_solution_from_driver {
                 tracing::warn!(
                     driver = participant.driver().name,
@@ -676,7 +676,7 @@ async fn find_unsupported_tokens(
                 let bad_token = bad_token.clone();
                 async move {
                     match bad_token.detect(token).await {
-                        Ok(quality) => (!quality.is_good()).then_some(token),
+                        Ok(quality) => (!quality.is_supported()).then_some(token),
                         Err(err) => {
                             tracing::warn!(
                                 ?token,
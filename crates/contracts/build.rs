@@ -1050,6 +1050,26 @@ fn main() {
                 },
             )
     });
+    generate_contract("BalancerV3BasePoolFactory");
+    generate_contract_with_config("BalancerV3Vault", |builder| {
+        // <https://docs.balancer.fi/developer-reference/contracts/deployment-addresses/mainnet.html>
+        builder
+            .add_network_str(MAINNET, "0xbA1333333333a1BA1108E8412f11850A5C319bA9")
+            .add_network_str(GNOSIS, "0xbA1333333333a1BA1108E8412f11850A5C319bA9")
+            .add_network_str(SEPOLIA, "0xbA1333333333a1BA1108E8412f11850A5C319bA9")
+            .add_network_str(ARBITRUM_ONE, "0xbA1333333333a1BA1108E8412f11850A5C319bA9")
+            .add_network_str(BASE, "0xbA1333333333a1BA1108E8412f11850A5C319bA9")
+            .add_network_str(AVALANCHE, "0xbA1333333333a1BA1108E8412f11850A5C319bA9")
+            .add_network_str(OPTIMISM, "0xbA1333333333a1BA1108E8412f11850A5C319bA9")
+    });
+    generate_contract_with_config("BalancerV3WeightedPoolFactory", |builder| {
+        builder.add_network_str(MAINNET, "0x201efd508c8DfE9DE1a13c2452863A78CB2a86Cc")
+    });
+    generate_contract_with_config("BalancerV3StablePoolFactoryV2", |builder| {
+        builder.add_network_str(MAINNET, "0xe42C2E153BB0A8899b59C73F5Ff941f9742F1197")
+    });
+    generate_contract("BalancerV3WeightedPool");
+    generate_contract("BalancerV3StablePool");
     generate_contract_with_config("BaoswapRouter", |builder| {
         builder.add_network_str(GNOSIS, "0x6093AeBAC87d62b1A5a4cEec91204e35020E38bE")
     });
@@ -1327,6 +1347,38 @@ fn main() {
         // Not available on Gnosis Chain
     });
     generate_contract("UniswapV3Pool");
+    generate_contract_with_config("UniswapV4PoolManager", |builder| {
+        // <https://docs.uniswap.org/contracts/v4/deployments>
+        builder
+            .add_network(
+                MAINNET,
+                Network {
+                    address: addr("0x000000000004444c5dc75cB358380D2e3dE08A90"),
+                    deployment_information: Some(DeploymentInformation::BlockNumber(21688329)),
+                },
+            )
+            .add_network(
+                ARBITRUM_ONE,
+                Network {
+                    address: addr("0x360E68faCcca8cA495c1B759Fd9EEe466db9FB32"),
+                    deployment_information: Some(DeploymentInformation::BlockNumber(297842872)),
+                },
+            )
+            .add_network(
+                BASE,
+                Network {
+                    address: addr("0x498581fF718922c3f8e6A244956aF099B2652b2b"),
+                    deployment_information: Some(DeploymentInformation::BlockNumber(25350988)),
+                },
+            )
+    });
+    generate_contract_with_config("UniswapV4UniversalRouter", |builder| {
+        // <https://docs.uniswap.org/contracts/v4/deployments>
+        builder
+            .add_network_str(MAINNET, "0x66a9893cC07D91D95644AEDD05D03f95e1dBA8Af")
+            .add_network_str(ARBITRUM_ONE, "0xA51afAFe0263b40EdaEf0Df8781eA9aa03E381a3")
+            .add_network_str(BASE, "0x6fF5693b99212Da76ad316178A184AB56D299b43")
+    });
     generate_contract_with_config("WETH9", |builder| {
         // Note: the WETH address must be consistent with the one used by the ETH-flow
         // contract
@@ -1466,6 +1518,22 @@ fn main() {
             )
     });
     generate_contract("CowAmmUniswapV2PriceOracle");
+    generate_contract_with_config("CurveRegistry", |builder| {
+        // <https://docs.curve.fi/registry/overview/>
+        builder
+            .add_network_str(MAINNET, "0x90E00ACe148ca3b23Ac1bC8C240C2a7Dd9c2d7f5")
+            .add_method_alias("get_A(address)", "get_amplification")
+    });
+    generate_contract_with_config("CurveCryptoRegistry", |builder| {
+        builder.add_network_str(MAINNET, "0x8F942C20D02bEfc377D41445793068908E2250D0")
+    });
+    generate_contract("CurveStableSwapPool");
+    generate_contract_with_config("CurveCryptoSwapPool", |builder| {
+        builder
+            .add_method_alias("A()", "amplification")
+            .add_method_alias("D()", "invariant")
+            .add_method_alias("price_scale(uint256)", "price_scale_at")
+    });
 
     // Support contracts used for trade and token simulations.
     generate_contract("Solver");
@@ -70,12 +70,22 @@ include_contracts! {
     BalancerV2WeightedPoolFactory;
     BalancerV2WeightedPoolFactoryV3;
     BalancerV2WeightedPoolFactoryV4;
+    BalancerV3BasePoolFactory;
     BalancerV3BatchRouter;
+    BalancerV3StablePool;
+    BalancerV3StablePoolFactoryV2;
+    BalancerV3Vault;
+    BalancerV3WeightedPool;
+    BalancerV3WeightedPoolFactory;
     BaoswapRouter;
     CowAmm;
     CowAmmConstantProductFactory;
     CowAmmLegacyHelper;
     CowAmmUniswapV2PriceOracle;
+    CurveCryptoRegistry;
+    CurveCryptoSwapPool;
+    CurveRegistry;
+    CurveStableSwapPool;
     CoWSwapEthFlow;
     CoWSwapOnchainOrders;
     CowProtocolToken;
@@ -109,6 +119,8 @@ include_contracts! {
     UniswapV2Router02;
     UniswapV3Pool;
     UniswapV3SwapRouter;
+    UniswapV4PoolManager;
+    UniswapV4UniversalRouter;
     WETH9;
 }
 
@@ -0,0 +1,103 @@
+use {
+    crate::{OrderUid, byte_array::ByteArray},
+    chrono::{DateTime, Utc},
+    sqlx::PgConnection,
+};
+
+/// A key identifying an API client and its request limits.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ApiKey {
+    pub name: String,
+    /// Keccak-256 hash of the secret key.
+    pub key_hash: ByteArray<32>,
+    /// `None` means unlimited.
+    pub quotes_per_minute: Option<i32>,
+    /// `None` means unlimited.
+    pub orders_per_minute: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn insert(ex: &mut PgConnection, key: &ApiKey) -> Result<(), sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO api_keys (name, key_hash, quotes_per_minute, orders_per_minute, created_at)
+VALUES ($1, $2, $3, $4, $5)
+    ;"#;
+    sqlx::query(QUERY)
+        .bind(&key.name)
+        .bind(key.key_hash)
+        .bind(key.quotes_per_minute)
+        .bind(key.orders_per_minute)
+        .bind(key.created_at)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+pub async fn fetch_all(ex: &mut PgConnection) -> Result<Vec<ApiKey>, sqlx::Error> {
+    const QUERY: &str = r#"SELECT * FROM api_keys ORDER BY name"#;
+    sqlx::query_as(QUERY).fetch_all(ex).await
+}
+
+/// Attributes an order to the API key that was used to create it.
+pub async fn insert_order(
+    ex: &mut PgConnection,
+    order_uid: &OrderUid,
+    api_key: &str,
+) -> Result<(), sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO order_api_keys (order_uid, api_key)
+VALUES ($1, $2)
+ON CONFLICT (order_uid) DO NOTHING
+    ;"#;
+    sqlx::query(QUERY)
+        .bind(order_uid)
+        .bind(api_key)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+/// Returns the name of the API key an order was created with.
+pub async fn fetch_order(
+    ex: &mut PgConnection,
+    order_uid: &OrderUid,
+) -> Result<Option<String>, sqlx::Error> {
+    const QUERY: &str = r#"SELECT api_key FROM order_api_keys WHERE order_uid = $1"#;
+    sqlx::query_scalar(QUERY)
+        .bind(order_uid)
+        .fetch_optional(ex)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, sqlx::Connection};
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let key = ApiKey {
+            name: "partner".to_string(),
+            key_hash: ByteArray([1; 32]),
+            quotes_per_minute: Some(600),
+            orders_per_minute: None,
+            created_at: DateTime::from_timestamp_millis(1_700_000_000_000).unwrap(),
+        };
+        insert(&mut db, &key).await.unwrap();
+        assert_eq!(fetch_all(&mut db).await.unwrap(), vec![key]);
+
+        let uid = ByteArray([2; 56]);
+        assert_eq!(fetch_order(&mut db, &uid).await.unwrap(), None);
+        insert_order(&mut db, &uid, "partner").await.unwrap();
+        // Attribution is only stored once.
+        insert_order(&mut db, &uid, "other").await.unwrap();
+        assert_eq!(
+            fetch_order(&mut db, &uid).await.unwrap(),
+            Some("partner".to_string())
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod api_keys;
 pub mod app_data;
 pub mod auction;
 pub mod auction_orders;
@@ -9,6 +10,7 @@ pub mod events;
 pub mod fee_policies;
 pub mod jit_orders;
 pub mod last_indexed_blocks;
+pub mod liquidity_snapshots;
 pub mod onchain_broadcasted_orders;
 pub mod onchain_invalidations;
 pub mod order_events;
@@ -23,6 +25,7 @@ pub mod settlement_scores;
 pub mod settlements;
 pub mod solver_competition;
 pub mod surplus_capturing_jit_order_owners;
+pub mod token_balance_overrides;
 pub mod trades;
 
 use {
@@ -52,6 +55,7 @@ pub type PgTransaction<'a> = sqlx::Transaction<'a, sqlx::Postgres>;
 
 /// The names of tables we use in the db.
 pub const TABLES: &[&str] = &[
+    "api_keys",
     "app_data",
     "auction_orders",
     "auctions",
@@ -63,8 +67,10 @@ pub const TABLES: &[&str] = &[
     "invalidations",
     "jit_orders",
     "last_indexed_blocks",
+    "liquidity_snapshots",
     "onchain_order_invalidations",
     "onchain_placed_orders",
+    "order_api_keys",
     "order_execution",
     "order_quotes",
     "orders",
@@ -79,6 +85,7 @@ pub const TABLES: &[&str] = &[
     "settlements",
     "solver_competitions",
     "surplus_capturing_jit_order_owners",
+    "token_balance_overrides",
     "trades",
 ];
 
@@ -0,0 +1,72 @@
+use {
+    chrono::{DateTime, Utc},
+    sqlx::PgConnection,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct LiquiditySnapshot {
+    pub source: String,
+    pub block_number: i64,
+    /// Serialized state of the liquidity source with a source specific
+    /// format.
+    pub data: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn upsert(ex: &mut PgConnection, row: &LiquiditySnapshot) -> Result<(), sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO liquidity_snapshots (source, block_number, data, created_at)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (source) DO UPDATE
+SET block_number = EXCLUDED.block_number, data = EXCLUDED.data, created_at = EXCLUDED.created_at
+    ;"#;
+    sqlx::query(QUERY)
+        .bind(&row.source)
+        .bind(row.block_number)
+        .bind(&row.data)
+        .bind(row.created_at)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+pub async fn fetch(
+    ex: &mut PgConnection,
+    source: &str,
+) -> Result<Option<LiquiditySnapshot>, sqlx::Error> {
+    const QUERY: &str = r#"SELECT * FROM liquidity_snapshots WHERE source = $1"#;
+    sqlx::query_as(QUERY).bind(source).fetch_optional(ex).await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, sqlx::Connection};
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        assert_eq!(fetch(&mut db, "uniswap-v3").await.unwrap(), None);
+
+        let mut row = LiquiditySnapshot {
+            source: "uniswap-v3".to_string(),
+            block_number: 42,
+            data: vec![1, 2, 3],
+            created_at: DateTime::from_timestamp_millis(1_700_000_000_000).unwrap(),
+        };
+        upsert(&mut db, &row).await.unwrap();
+        assert_eq!(
+            fetch(&mut db, "uniswap-v3").await.unwrap(),
+            Some(row.clone())
+        );
+
+        row.block_number = 43;
+        row.data = vec![4, 5];
+        upsert(&mut db, &row).await.unwrap();
+        assert_eq!(fetch(&mut db, "uniswap-v3").await.unwrap(), Some(row));
+        assert_eq!(fetch(&mut db, "balancer-v2").await.unwrap(), None);
+    }
+}
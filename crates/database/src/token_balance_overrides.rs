@@ -0,0 +1,85 @@
+use {
+    crate::{Address, byte_array::ByteArray},
+    chrono::{DateTime, Utc},
+    sqlx::PgConnection,
+};
+
+/// How the balances of a token are laid out in its storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "BalanceOverrideStrategy", rename_all = "snake_case")]
+pub enum Strategy {
+    SolidityMapping,
+    VyperMapping,
+    SoladyMapping,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct BalanceOverride {
+    pub token: Address,
+    pub strategy: Strategy,
+    /// Storage slot of the balances mapping. `None` for strategies that don't
+    /// need one.
+    pub slot: Option<ByteArray<32>>,
+    pub detected_at: DateTime<Utc>,
+}
+
+pub async fn upsert(ex: &mut PgConnection, row: &BalanceOverride) -> Result<(), sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO token_balance_overrides (token, strategy, slot, detected_at)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (token) DO UPDATE
+SET strategy = EXCLUDED.strategy, slot = EXCLUDED.slot, detected_at = EXCLUDED.detected_at
+    ;"#;
+    sqlx::query(QUERY)
+        .bind(row.token)
+        .bind(row.strategy)
+        .bind(row.slot)
+        .bind(row.detected_at)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+pub async fn fetch(
+    ex: &mut PgConnection,
+    token: &Address,
+) -> Result<Option<BalanceOverride>, sqlx::Error> {
+    const QUERY: &str = r#"SELECT * FROM token_balance_overrides WHERE token = $1"#;
+    sqlx::query_as(QUERY).bind(token).fetch_optional(ex).await
+}
+
+pub async fn fetch_all(ex: &mut PgConnection) -> Result<Vec<BalanceOverride>, sqlx::Error> {
+    const QUERY: &str = r#"SELECT * FROM token_balance_overrides ORDER BY token"#;
+    sqlx::query_as(QUERY).fetch_all(ex).await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, sqlx::Connection};
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let token = ByteArray([1; 20]);
+        assert_eq!(fetch(&mut db, &token).await.unwrap(), None);
+
+        let mut row = BalanceOverride {
+            token,
+            strategy: Strategy::SolidityMapping,
+            slot: Some(ByteArray([2; 32])),
+            detected_at: DateTime::from_timestamp_millis(1_700_000_000_000).unwrap(),
+        };
+        upsert(&mut db, &row).await.unwrap();
+        assert_eq!(fetch(&mut db, &token).await.unwrap(), Some(row.clone()));
+
+        row.strategy = Strategy::SoladyMapping;
+        row.slot = None;
+        upsert(&mut db, &row).await.unwrap();
+        assert_eq!(fetch(&mut db, &token).await.unwrap(), Some(row.clone()));
+        assert_eq!(fetch_all(&mut db).await.unwrap(), vec![row]);
+    }
+}
@@ -20,10 +20,13 @@ use {
     ethrpc::block_stream::{BlockRetrieving, CurrentBlockWatcher},
     shared::{
         http_solver::model::TokenAmount,
-        sources::balancer_v2::{
-            BalancerFactoryKind,
-            BalancerPoolFetcher,
-            pool_fetching::BalancerContracts,
+        sources::{
+            balancer_v2::{
+                BalancerFactoryKind,
+                BalancerPoolFetcher,
+                pool_fetching::{BalancerContracts, PoolDiscovery},
+            },
+            snapshots::Snapshots,
         },
         token_info::{CachedTokenInfoFetcher, TokenInfoFetcher},
     },
@@ -79,6 +82,7 @@ pub fn collector(
     block_stream: CurrentBlockWatcher,
     block_retriever: Arc<dyn BlockRetrieving>,
     config: &infra::liquidity::config::BalancerV2,
+    snapshots: Option<Arc<Snapshots>>,
 ) -> Box<dyn LiquidityCollecting> {
     let eth = Arc::new(eth.with_metric_label("balancerV2".into()));
     let reinit_interval = config.reinit_interval;
@@ -88,7 +92,17 @@ pub fn collector(
         let block_stream = block_stream.clone();
         let block_retriever = block_retriever.clone();
         let config = config.clone();
-        async move { init_liquidity(&eth, &block_stream, block_retriever.clone(), &config).await }
+        let snapshots = snapshots.clone();
+        async move {
+            init_liquidity(
+                &eth,
+                &block_stream,
+                block_retriever.clone(),
+                &config,
+                snapshots,
+            )
+            .await
+        }
     };
     const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
     Box::new(BackgroundInitLiquiditySource::new(
@@ -104,6 +118,7 @@ async fn init_liquidity(
     block_stream: &CurrentBlockWatcher,
     block_retriever: Arc<dyn BlockRetrieving>,
     config: &infra::liquidity::config::BalancerV2,
+    snapshots: Option<Arc<Snapshots>>,
 ) -> Result<impl LiquidityCollecting + use<>> {
     let web3 = boundary::web3(eth);
     let contracts = BalancerContracts {
@@ -180,7 +195,12 @@ async fn init_liquidity(
 
     let balancer_pool_fetcher = Arc::new(
         BalancerPoolFetcher::new(
-            &config.graph_url,
+            match &config.graph_url {
+                Some(graph_url) => PoolDiscovery::Subgraph(graph_url.clone()),
+                None => PoolDiscovery::Onchain {
+                    start_block: config.deployment_block,
+                },
+            },
             block_retriever.clone(),
             token_info_fetcher.clone(),
             boundary::liquidity::cache_config(),
@@ -189,6 +209,7 @@ async fn init_liquidity(
             web3.clone(),
             &contracts,
             config.pool_deny_list.clone(),
+            snapshots,
         )
         .await
         .context("failed to create balancer pool fetcher")?,
@@ -0,0 +1,174 @@
+use {
+    crate::{
+        boundary::{self, Result},
+        domain::{
+            eth,
+            liquidity::{
+                self,
+                balancer::{self, v3},
+            },
+        },
+        infra::{self, blockchain::Ethereum},
+    },
+    anyhow::Context,
+    contracts::{BalancerV3BatchRouter, Permit2},
+    ethrpc::block_stream::BlockRetrieving,
+    shared::{
+        http_solver::model::TokenAmount,
+        interaction::Interaction,
+        maintenance::ServiceMaintenance,
+        sources::balancer_v3::{BalancerV3PoolFetcher, FactoryKind, PoolKind},
+    },
+    solver::{
+        interactions::allowances::Allowances,
+        liquidity::{
+            BalancerV3PoolOrder,
+            balancer_v3::{BalancerV3Liquidity, BalancerV3SettlementHandler},
+        },
+        liquidity_collector::{BackgroundInitLiquiditySource, LiquidityCollecting},
+    },
+    std::sync::Arc,
+};
+
+pub fn to_domain(id: liquidity::Id, pool: BalancerV3PoolOrder) -> Result<liquidity::Liquidity> {
+    let handler = pool
+        .settlement_handling
+        .as_any()
+        .downcast_ref::<BalancerV3SettlementHandler>()
+        .context("unrecognized Balancer V3 settlement handler")?;
+    let gas = pool.pool.swap_gas();
+    let pool = pool.pool;
+
+    Ok(liquidity::Liquidity {
+        id,
+        gas: gas.into(),
+        kind: liquidity::Kind::BalancerV3(v3::Pool {
+            batch_router: handler.batch_router().address().into(),
+            permit2: handler.permit2().address().into(),
+            address: pool.address.into(),
+            reserves: pool
+                .reserves
+                .into_iter()
+                .map(|reserve| v3::Reserve {
+                    asset: eth::Asset {
+                        token: reserve.token.into(),
+                        amount: reserve.balance.into(),
+                    },
+                    scaling_factor: reserve.scaling_factor,
+                    rate: reserve.rate.as_uint256(),
+                })
+                .collect(),
+            fee: balancer::v2::Fee::from_raw(pool.swap_fee.as_uint256()),
+            hooks: pool.hooks.map(Into::into),
+            kind: match pool.kind {
+                PoolKind::Weighted(weights) => v3::Kind::Weighted(
+                    weights
+                        .into_iter()
+                        .map(|weight| weight.as_uint256())
+                        .collect(),
+                ),
+                PoolKind::Stable(amplification_parameter) => {
+                    v3::Kind::Stable(balancer::v2::stable::AmplificationParameter::new(
+                        amplification_parameter.factor(),
+                        amplification_parameter.precision(),
+                    )?)
+                }
+            },
+        }),
+    })
+}
+
+pub fn to_interactions(
+    pool: &v3::Pool,
+    input: &liquidity::MaxInput,
+    output: &liquidity::ExactOutput,
+) -> Vec<eth::Interaction> {
+    let web3 = ethrpc::dummy::web3();
+    let permit2 = Permit2::at(&web3, pool.permit2.into());
+    let handler = BalancerV3SettlementHandler::new(
+        pool.address.into(),
+        BalancerV3BatchRouter::at(&web3, pool.batch_router.into()),
+        permit2.clone(),
+        Allowances::empty(permit2.address()),
+    );
+
+    let (_, permit, swap) = handler.settle(
+        TokenAmount::new(input.0.token.into(), input.0.amount),
+        TokenAmount::new(output.0.token.into(), output.0.amount),
+    );
+
+    [permit.encode(), swap.encode()]
+        .into_iter()
+        .map(|(target, value, call_data)| eth::Interaction {
+            target: target.into(),
+            value: value.into(),
+            call_data: call_data.0.into(),
+        })
+        .collect()
+}
+
+pub fn collector(
+    eth: &Ethereum,
+    block_retriever: Arc<dyn BlockRetrieving>,
+    config: &infra::liquidity::config::BalancerV3,
+) -> Box<dyn LiquidityCollecting> {
+    let eth = Arc::new(eth.with_metric_label("balancerV3".into()));
+    let reinit_interval = config.reinit_interval;
+    let config = Arc::new(config.clone());
+    let init = move || {
+        let eth = eth.clone();
+        let block_retriever = block_retriever.clone();
+        let config = config.clone();
+        async move { init_liquidity(&eth, block_retriever, &config).await }
+    };
+    const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+    Box::new(BackgroundInitLiquiditySource::new(
+        "balancer-v3",
+        init,
+        TEN_MINUTES,
+        reinit_interval,
+    )) as Box<_>
+}
+
+async fn init_liquidity(
+    eth: &Ethereum,
+    block_retriever: Arc<dyn BlockRetrieving>,
+    config: &infra::liquidity::config::BalancerV3,
+) -> anyhow::Result<impl LiquidityCollecting + use<>> {
+    let web3 = boundary::web3(eth);
+    let factories = config
+        .weighted
+        .iter()
+        .map(|factory| (FactoryKind::Weighted, factory.0))
+        .chain(
+            config
+                .stable
+                .iter()
+                .map(|factory| (FactoryKind::Stable, factory.0)),
+        )
+        .collect::<Vec<_>>();
+
+    let pool_fetcher = Arc::new(
+        BalancerV3PoolFetcher::new(
+            web3.clone(),
+            block_retriever,
+            config.vault.0,
+            &factories,
+            config.pool_deny_list.iter().map(|pool| pool.0).collect(),
+        )
+        .await
+        .context("failed to initialise Balancer V3 liquidity")?,
+    );
+
+    let update_task = ServiceMaintenance::new(vec![pool_fetcher.clone()])
+        .run_maintenance_on_new_block(eth.current_block().clone());
+    tokio::task::spawn(update_task);
+
+    Ok(BalancerV3Liquidity::new(
+        web3.clone(),
+        pool_fetcher,
+        eth.contracts().settlement().clone(),
+        BalancerV3BatchRouter::at(&web3, config.batch_router.into()),
+        Permit2::at(&web3, config.permit2.into()),
+    ))
+}
@@ -0,0 +1,164 @@
+use {
+    crate::{
+        boundary::{self, Result},
+        domain::{
+            eth,
+            liquidity::{self, curve},
+        },
+        infra::{self, blockchain::Ethereum},
+    },
+    anyhow::Context,
+    contracts::{CurveCryptoSwapPool, CurveStableSwapPool},
+    shared::{
+        http_solver::model::TokenAmount,
+        interaction::Interaction,
+        sources::curve::{
+            pool_fetching::{
+                CRYPTO_SWAP_GAS_COST,
+                CurvePoolFetcher,
+                PoolState,
+                Registry,
+                STABLE_SWAP_GAS_COST,
+            },
+        },
+    },
+    solver::{
+        interactions::{CurvePoolContract, allowances::Allowances},
+        liquidity::{
+            CurvePoolOrder,
+            curve::{CurveLiquidity, CurveSettlementHandler},
+        },
+        liquidity_collector::{BackgroundInitLiquiditySource, LiquidityCollecting},
+    },
+    std::sync::Arc,
+};
+
+pub fn to_domain(id: liquidity::Id, pool: CurvePoolOrder) -> Result<liquidity::Liquidity> {
+    let pool = pool.pool;
+    let (gas, state) = match pool.state {
+        PoolState::Stable(state) => (
+            STABLE_SWAP_GAS_COST,
+            curve::State::Stable(curve::StableState {
+                balances: state.balances,
+                rates: state.rates,
+                amplification: state.amplification,
+                fee: state.fee,
+                offpeg_fee_multiplier: state.offpeg_fee_multiplier,
+            }),
+        ),
+        PoolState::Crypto(state) => (
+            CRYPTO_SWAP_GAS_COST,
+            curve::State::Crypto(curve::CryptoState {
+                balances: state.balances,
+                precisions: state.precisions,
+                price_scale: state.price_scale,
+                amplification: state.amplification,
+                gamma: state.gamma,
+                d: state.d,
+                mid_fee: state.mid_fee,
+                out_fee: state.out_fee,
+                fee_gamma: state.fee_gamma,
+            }),
+        ),
+    };
+
+    Ok(liquidity::Liquidity {
+        id,
+        gas: gas.into(),
+        kind: liquidity::Kind::Curve(curve::Pool {
+            address: pool.address.into(),
+            tokens: pool
+                .tokens
+                .into_iter()
+                .map(eth::TokenAddress::from)
+                .collect(),
+            state,
+        }),
+    })
+}
+
+pub fn to_interaction(
+    pool: &curve::Pool,
+    input: &liquidity::MaxInput,
+    output: &liquidity::ExactOutput,
+) -> eth::Interaction {
+    let web3 = ethrpc::dummy::web3();
+    let contract = match pool.state {
+        curve::State::Stable(_) => {
+            CurvePoolContract::Stable(CurveStableSwapPool::at(&web3, pool.address.into()))
+        }
+        curve::State::Crypto(_) => {
+            CurvePoolContract::Crypto(CurveCryptoSwapPool::at(&web3, pool.address.into()))
+        }
+    };
+    let handler = CurveSettlementHandler::new(
+        contract,
+        pool.tokens.iter().copied().map(Into::into).collect(),
+        Allowances::empty(pool.address.into()),
+    );
+
+    let (_, interaction) = handler
+        .settle(
+            TokenAmount::new(input.0.token.into(), input.0.amount),
+            TokenAmount::new(output.0.token.into(), output.0.amount),
+        )
+        .expect("swap tokens are verified by the domain");
+
+    let encoded = interaction.encode();
+    eth::Interaction {
+        target: eth::Address(encoded.0),
+        value: eth::Ether(encoded.1),
+        call_data: crate::util::Bytes(encoded.2.0),
+    }
+}
+
+pub fn collector(
+    eth: &Ethereum,
+    config: &infra::liquidity::config::Curve,
+) -> Box<dyn LiquidityCollecting> {
+    let eth = Arc::new(eth.with_metric_label("curve".into()));
+    let reinit_interval = config.reinit_interval;
+    let config = Arc::new(config.clone());
+    let init = move || {
+        let eth = eth.clone();
+        let config = config.clone();
+        async move { init_liquidity(&eth, &config).await }
+    };
+    const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+    Box::new(BackgroundInitLiquiditySource::new(
+        "curve",
+        init,
+        TEN_MINUTES,
+        reinit_interval,
+    )) as Box<_>
+}
+
+async fn init_liquidity(
+    eth: &Ethereum,
+    config: &infra::liquidity::config::Curve,
+) -> anyhow::Result<impl LiquidityCollecting + use<>> {
+    let web3 = boundary::web3(eth);
+    let registries = config
+        .stable_registries
+        .iter()
+        .map(|registry| Registry::Stable(registry.0))
+        .chain(
+            config
+                .crypto_registries
+                .iter()
+                .map(|registry| Registry::Crypto(registry.0)),
+        )
+        .collect::<Vec<_>>();
+
+    let pool_fetcher = Arc::new(
+        CurvePoolFetcher::new(web3.clone(), &registries)
+            .await
+            .context("failed to initialise Curve liquidity")?,
+    );
+
+    Ok(CurveLiquidity::new(
+        web3,
+        pool_fetcher,
+        eth.contracts().settlement().clone(),
+    ))
+}
@@ -27,6 +27,8 @@ use {
 };
 
 pub mod balancer;
+pub mod curve;
+pub mod snapshots;
 pub mod swapr;
 pub mod uniswap;
 pub mod zeroex;
@@ -66,6 +68,17 @@ impl Fetcher {
 
         let block_stream = eth.current_block();
         let block_retriever = blocks.retriever(boundary::web3(eth));
+        let snapshot_store = config
+            .snapshots
+            .as_ref()
+            .map(snapshots::store)
+            .transpose()?;
+        let source_snapshots = |source: String| {
+            snapshot_store
+                .clone()
+                .zip(config.snapshots.as_ref())
+                .map(|(store, config)| snapshots::snapshots(eth, store, config, &source))
+        };
 
         let uni_v2: Vec<_> = future::try_join_all(
             config
@@ -88,14 +101,45 @@ impl Fetcher {
             .balancer_v2
             .iter()
             .map(|config| {
-                balancer::v2::collector(eth, block_stream.clone(), block_retriever.clone(), config)
+                balancer::v2::collector(
+                    eth,
+                    block_stream.clone(),
+                    block_retriever.clone(),
+                    config,
+                    source_snapshots(format!("balancer-v2-{:?}", config.vault.0)),
+                )
             })
             .collect();
 
+        let bal_v3: Vec<_> = config
+            .balancer_v3
+            .iter()
+            .map(|config| balancer::v3::collector(eth, block_retriever.clone(), config))
+            .collect();
+
         let uni_v3: Vec<_> = config
             .uniswap_v3
             .iter()
-            .map(|config| uniswap::v3::collector(eth, block_retriever.clone(), config))
+            .map(|config| {
+                uniswap::v3::collector(
+                    eth,
+                    block_retriever.clone(),
+                    config,
+                    source_snapshots(format!("uniswap-v3-{:?}", config.router.0)),
+                )
+            })
+            .collect();
+
+        let uni_v4: Vec<_> = config
+            .uniswap_v4
+            .iter()
+            .map(|config| uniswap::v4::collector(eth, block_retriever.clone(), config))
+            .collect();
+
+        let curve: Vec<_> = config
+            .curve
+            .iter()
+            .map(|config| curve::collector(eth, config))
             .collect();
 
         let zeroex: Vec<_> = future::try_join_all(
@@ -121,7 +165,7 @@ impl Fetcher {
         Ok(Self {
             blocks: block_stream.clone(),
             inner: LiquidityCollector {
-                liquidity_sources: [uni_v2, swapr, bal_v2, uni_v3, zeroex]
+                liquidity_sources: [uni_v2, swapr, bal_v2, bal_v3, uni_v3, uni_v4, curve, zeroex]
                     .into_iter()
                     .flatten()
                     .collect(),
@@ -171,6 +215,9 @@ impl Fetcher {
                     Liquidity::BalancerStable(pool) => balancer::v2::stable::to_domain(id, pool),
                     Liquidity::LimitOrder(pool) => zeroex::to_domain(id, pool),
                     Liquidity::Concentrated(pool) => uniswap::v3::to_domain(id, pool),
+                    Liquidity::Curve(pool) => curve::to_domain(id, pool),
+                    Liquidity::BalancerV3(pool) => balancer::v3::to_domain(id, pool),
+                    Liquidity::UniswapV4(pool) => uniswap::v4::to_domain(id, pool),
                 }
                 // Ignore "bad" liquidity - this allows the driver to continue
                 // solving with the other good stuff.
@@ -0,0 +1,66 @@
+use {
+    crate::infra::{self, blockchain::Ethereum},
+    anyhow::Result,
+    database::liquidity_snapshots::LiquiditySnapshot,
+    shared::sources::snapshots::{self, FileSnapshotStore, SnapshotStoring, Snapshots},
+    sqlx::PgPool,
+    std::sync::Arc,
+};
+
+/// Creates the snapshot store for the specified configuration.
+pub fn store(config: &infra::liquidity::config::Snapshots) -> Result<Arc<dyn SnapshotStoring>> {
+    Ok(match &config.storage {
+        infra::liquidity::config::SnapshotStorage::File(path) => {
+            Arc::new(FileSnapshotStore::new(path.clone()))
+        }
+        infra::liquidity::config::SnapshotStorage::Postgres(url) => {
+            Arc::new(Postgres(PgPool::connect_lazy(url.as_str())?))
+        }
+    })
+}
+
+/// Returns the snapshots of a single liquidity source. The source name gets
+/// qualified with the chain so that drivers for different chains can share
+/// the same storage.
+pub fn snapshots(
+    eth: &Ethereum,
+    store: Arc<dyn SnapshotStoring>,
+    config: &infra::liquidity::config::Snapshots,
+    source: &str,
+) -> Arc<Snapshots> {
+    Arc::new(Snapshots::new(
+        store,
+        format!("{}-{source}", eth.chain().id()),
+        snapshots::Config {
+            interval: config.interval,
+            max_age: config.max_age,
+        },
+    ))
+}
+
+/// Stores snapshots in the `liquidity_snapshots` table.
+struct Postgres(PgPool);
+
+#[async_trait::async_trait]
+impl SnapshotStoring for Postgres {
+    async fn load(&self, source: &str) -> Result<Option<Vec<u8>>> {
+        let mut ex = self.0.acquire().await?;
+        let snapshot = database::liquidity_snapshots::fetch(&mut ex, source).await?;
+        Ok(snapshot.map(|snapshot| snapshot.data))
+    }
+
+    async fn save(&self, source: &str, block_number: u64, data: Vec<u8>) -> Result<()> {
+        let mut ex = self.0.acquire().await?;
+        database::liquidity_snapshots::upsert(
+            &mut ex,
+            &LiquiditySnapshot {
+                source: source.to_string(),
+                block_number: block_number.try_into()?,
+                data,
+                created_at: chrono::Utc::now(),
+            },
+        )
+        .await?;
+        Ok(())
+    }
+}
@@ -17,7 +17,7 @@ use {
         http_solver::model::TokenAmount,
         interaction::Interaction,
         maintenance::ServiceMaintenance,
-        sources::uniswap_v3::pool_fetching::UniswapV3PoolFetcher,
+        sources::{snapshots::Snapshots, uniswap_v3::pool_fetching::UniswapV3PoolFetcher},
     },
     solver::{
         interactions::allowances::Allowances,
@@ -104,6 +104,7 @@ pub fn collector(
     eth: &Ethereum,
     block_retriever: Arc<dyn BlockRetrieving>,
     config: &infra::liquidity::config::UniswapV3,
+    snapshots: Option<Arc<Snapshots>>,
 ) -> Box<dyn LiquidityCollecting> {
     let eth = Arc::new(eth.with_metric_label("uniswapV3".into()));
     let config = Arc::new(Clone::clone(config));
@@ -112,7 +113,8 @@ pub fn collector(
         let eth = eth.clone();
         let block_retriever = block_retriever.clone();
         let config = config.clone();
-        async move { init_liquidity(&eth, block_retriever.clone(), &config).await }
+        let snapshots = snapshots.clone();
+        async move { init_liquidity(&eth, block_retriever.clone(), &config, snapshots).await }
     };
     const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
     Box::new(BackgroundInitLiquiditySource::new(
@@ -127,6 +129,7 @@ async fn init_liquidity(
     eth: &Ethereum,
     block_retriever: Arc<dyn BlockRetrieving>,
     config: &infra::liquidity::config::UniswapV3,
+    snapshots: Option<Arc<Snapshots>>,
 ) -> anyhow::Result<impl LiquidityCollecting + use<>> {
     let web3 = boundary::web3(eth);
     let router = UniswapV3SwapRouter::at(&web3, config.router.0);
@@ -138,6 +141,7 @@ async fn init_liquidity(
             boundary::liquidity::http_client(),
             block_retriever,
             config.max_pools_to_initialize,
+            snapshots,
         )
         .await
         .context("failed to initialise UniswapV3 liquidity")?,
@@ -0,0 +1,165 @@
+use {
+    crate::{
+        boundary::{self, Result},
+        domain::{
+            eth,
+            liquidity::{
+                self,
+                uniswap::{
+                    v3::{Fee, Liquidity, LiquidityNet, SqrtPrice, Tick},
+                    v4,
+                },
+            },
+        },
+        infra::{self, blockchain::Ethereum},
+    },
+    anyhow::Context,
+    contracts::{Permit2, UniswapV4UniversalRouter},
+    ethrpc::block_stream::BlockRetrieving,
+    shared::{
+        http_solver::model::TokenAmount,
+        interaction::Interaction,
+        maintenance::ServiceMaintenance,
+        sources::uniswap_v4::pool_fetching::{PoolKey, UniswapV4PoolFetcher},
+    },
+    solver::{
+        interactions::allowances::Allowances,
+        liquidity::{
+            UniswapV4PoolOrder,
+            uniswap_v4::{UniswapV4Liquidity, UniswapV4SettlementHandler},
+        },
+        liquidity_collector::{BackgroundInitLiquiditySource, LiquidityCollecting},
+    },
+    std::{collections::BTreeMap, sync::Arc},
+};
+
+pub fn to_domain(id: liquidity::Id, pool: UniswapV4PoolOrder) -> Result<liquidity::Liquidity> {
+    let handler = pool
+        .settlement_handling
+        .as_any()
+        .downcast_ref::<UniswapV4SettlementHandler>()
+        .context("unrecognized Uniswap V4 settlement handler")?;
+    let pool = pool.pool;
+
+    Ok(liquidity::Liquidity {
+        id,
+        gas: eth::Gas(pool.gas_stats.mean_gas),
+        kind: liquidity::Kind::UniswapV4(v4::Pool {
+            router: handler.router().address().into(),
+            permit2: handler.permit2().address().into(),
+            id: pool.id,
+            key: v4::PoolKey {
+                tokens: liquidity::TokenPair::try_new(
+                    pool.key.currency0.into(),
+                    pool.key.currency1.into(),
+                )?,
+                fee: pool.key.fee,
+                tick_spacing: pool.key.tick_spacing,
+                hooks: pool.key.hooks.into(),
+            },
+            sqrt_price: SqrtPrice(pool.state.sqrt_price),
+            liquidity: Liquidity(pool.state.liquidity.as_u128()),
+            tick: Tick(pool.state.tick.try_into()?),
+            liquidity_net: pool
+                .state
+                .liquidity_net
+                .iter()
+                .map(|(key, value)| -> Result<_> {
+                    Ok((Tick(key.try_into()?), LiquidityNet(value.try_into()?)))
+                })
+                .collect::<Result<BTreeMap<_, _>>>()?,
+            fee: Fee(pool.state.fee),
+        }),
+    })
+}
+
+pub fn to_interactions(
+    pool: &v4::Pool,
+    input: &liquidity::MaxInput,
+    output: &liquidity::ExactOutput,
+) -> Vec<eth::Interaction> {
+    let web3 = ethrpc::dummy::web3();
+    let permit2 = Permit2::at(&web3, pool.permit2.into());
+    let (currency0, currency1) = pool.key.tokens.get();
+    let handler = UniswapV4SettlementHandler::new(
+        PoolKey {
+            currency0: currency0.into(),
+            currency1: currency1.into(),
+            fee: pool.key.fee,
+            tick_spacing: pool.key.tick_spacing,
+            hooks: pool.key.hooks.into(),
+        },
+        UniswapV4UniversalRouter::at(&web3, pool.router.into()),
+        permit2.clone(),
+        Allowances::empty(permit2.address()),
+    );
+
+    let (_, permit, swap) = handler.settle(
+        TokenAmount::new(input.0.token.into(), input.0.amount),
+        TokenAmount::new(output.0.token.into(), output.0.amount),
+    );
+
+    [permit.encode(), swap.encode()]
+        .into_iter()
+        .map(|(target, value, call_data)| eth::Interaction {
+            target: target.into(),
+            value: value.into(),
+            call_data: call_data.0.into(),
+        })
+        .collect()
+}
+
+pub fn collector(
+    eth: &Ethereum,
+    block_retriever: Arc<dyn BlockRetrieving>,
+    config: &infra::liquidity::config::UniswapV4,
+) -> Box<dyn LiquidityCollecting> {
+    let eth = Arc::new(eth.with_metric_label("uniswapV4".into()));
+    let reinit_interval = config.reinit_interval;
+    let config = Arc::new(config.clone());
+    let init = move || {
+        let eth = eth.clone();
+        let block_retriever = block_retriever.clone();
+        let config = config.clone();
+        async move { init_liquidity(&eth, block_retriever, &config).await }
+    };
+    const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+    Box::new(BackgroundInitLiquiditySource::new(
+        "uniswap-v4",
+        init,
+        TEN_MINUTES,
+        reinit_interval,
+    )) as Box<_>
+}
+
+async fn init_liquidity(
+    eth: &Ethereum,
+    block_retriever: Arc<dyn BlockRetrieving>,
+    config: &infra::liquidity::config::UniswapV4,
+) -> anyhow::Result<impl LiquidityCollecting + use<>> {
+    let web3 = boundary::web3(eth);
+
+    let pool_fetcher = Arc::new(
+        UniswapV4PoolFetcher::new(
+            web3.clone(),
+            block_retriever,
+            config.pool_manager.0,
+            config.deployment_block,
+            config.allowed_hooks.iter().map(|hooks| hooks.0).collect(),
+        )
+        .await
+        .context("failed to initialise Uniswap V4 liquidity")?,
+    );
+
+    let update_task = ServiceMaintenance::new(vec![pool_fetcher.clone()])
+        .run_maintenance_on_new_block(eth.current_block().clone());
+    tokio::task::spawn(update_task);
+
+    Ok(UniswapV4Liquidity::new(
+        web3.clone(),
+        pool_fetcher,
+        eth.contracts().settlement().clone(),
+        UniswapV4UniversalRouter::at(&web3, config.router.into()),
+        Permit2::at(&web3, config.permit2.into()),
+    ))
+}
@@ -2,7 +2,7 @@ use {
     super::{Order, order},
     crate::{
         domain::{
-            competition::{self, auction, sorting},
+            competition::{self, auction, bad_tokens, sorting},
             eth,
             liquidity,
             time,
@@ -11,8 +11,9 @@ use {
             self,
             Ethereum,
             blockchain,
-            config::file::OrderPriorityStrategy,
+            config::file::{OrderPriorityStrategy, PriorityKey},
             observe::{self, metrics},
+            solver,
         },
         util::{self, Bytes},
     },
@@ -43,6 +44,8 @@ pub struct Auction {
     gas_price: eth::GasPrice,
     deadline: time::Deadline,
     surplus_capturing_jit_order_owners: HashSet<eth::Address>,
+    /// See the [`Self::cow_amm_liquidity`] method.
+    cow_amm_liquidity: Vec<liquidity::cow_amm::Pool>,
 }
 
 impl Auction {
@@ -54,7 +57,7 @@ impl Auction {
         eth: &Ethereum,
         surplus_capturing_jit_order_owners: HashSet<eth::Address>,
     ) -> Result<Self, Error> {
-        let tokens = Tokens(tokens.map(|token| (token.address, token)).collect());
+        let tokens: Tokens = tokens.collect();
 
         // Ensure that tokens are included for each order.
         let weth = eth.contracts().weth_address();
@@ -77,6 +80,7 @@ impl Auction {
             gas_price: eth.gas_price(None).await?,
             deadline,
             surplus_capturing_jit_order_owners,
+            cow_amm_liquidity: Default::default(),
         })
     }
 
@@ -91,11 +95,24 @@ impl Auction {
         &self.orders
     }
 
+    /// The CoW AMMs that are not eligible for surplus and can be used as
+    /// liquidity instead. Only populated once the auction got prioritized.
+    pub fn cow_amm_liquidity(&self) -> &[liquidity::cow_amm::Pool] {
+        &self.cow_amm_liquidity
+    }
+
     /// The tokens used in the auction.
     pub fn tokens(&self) -> &Tokens {
         &self.tokens
     }
 
+    /// Records that the given token takes a fee on every transfer.
+    pub fn set_transfer_fee(&mut self, token: eth::TokenAddress, fee: bad_tokens::TransferFee) {
+        if let Some(token) = self.tokens.0.get_mut(&token) {
+            token.transfer_fee = Some(fee);
+        }
+    }
+
     /// Returns a collection of liquidity token pairs that are relevant to this
     /// auction.
     pub fn liquidity_pairs(&self) -> HashSet<liquidity::TokenPair> {
@@ -133,22 +150,143 @@ impl Auction {
     pub fn surplus_capturing_jit_order_owners(&self) -> &HashSet<eth::Address> {
         &self.surplus_capturing_jit_order_owners
     }
+
+    /// Removes the orders the solver doesn't want to see. Expects the orders
+    /// to be sorted by priority so that the most important orders are kept
+    /// when limiting the number of orders.
+    pub fn filter_orders(&mut self, filters: &OrderFilters, solver: &solver::Name) {
+        let filtered = filters.apply(&mut self.orders, &self.tokens);
+        for (reason, count) in filtered {
+            observe::orders_filtered(solver, reason, count);
+        }
+    }
+}
+
+/// Restrictions on the orders sent to a solver, for solvers that can't handle
+/// some orders and would waste their time rejecting them.
+#[derive(Debug, Clone, Default)]
+pub struct OrderFilters {
+    /// Removes orders selling less than this amount worth of the native
+    /// token. Orders without a native price for the sell token are removed as
+    /// well since their notional is unknown.
+    pub min_notional: Option<eth::Ether>,
+    /// If set, only orders trading exclusively these tokens are kept.
+    pub allowed_tokens: Option<HashSet<eth::TokenAddress>>,
+    /// Removes orders trading any of these tokens.
+    pub denied_tokens: HashSet<eth::TokenAddress>,
+    /// The maximum number of orders sent to the solver.
+    pub max_orders: Option<usize>,
+    /// Removes orders with pre- or post-interactions.
+    pub exclude_hooks: bool,
+    /// Removes orders with EIP-1271 signatures.
+    pub exclude_eip1271: bool,
+}
+
+impl OrderFilters {
+    /// Removes the filtered orders and returns how many orders were removed
+    /// for each reason.
+    fn apply(&self, orders: &mut Vec<Order>, tokens: &Tokens) -> HashMap<OrderFilterReason, usize> {
+        let mut filtered: HashMap<OrderFilterReason, usize> = HashMap::new();
+        orders.retain(|order| match self.check(order, tokens) {
+            Some(reason) => {
+                *filtered.entry(reason).or_default() += 1;
+                false
+            }
+            None => true,
+        });
+        if let Some(max_orders) = self.max_orders {
+            let excess = orders.len().saturating_sub(max_orders);
+            if excess > 0 {
+                orders.truncate(max_orders);
+                filtered.insert(OrderFilterReason::MaxOrders, excess);
+            }
+        }
+        filtered
+    }
+
+    /// Returns why the order should be removed, if it should be.
+    fn check(&self, order: &Order, tokens: &Tokens) -> Option<OrderFilterReason> {
+        let traded = [order.sell.token, order.buy.token];
+        if traded
+            .iter()
+            .any(|token| self.denied_tokens.contains(token))
+        {
+            return Some(OrderFilterReason::TokenDenied);
+        }
+        if self
+            .allowed_tokens
+            .as_ref()
+            .is_some_and(|allowed| !traded.iter().all(|token| allowed.contains(token)))
+        {
+            return Some(OrderFilterReason::TokenNotAllowed);
+        }
+        if self.exclude_hooks
+            && !(order.pre_interactions.is_empty() && order.post_interactions.is_empty())
+        {
+            return Some(OrderFilterReason::Hooks);
+        }
+        if self.exclude_eip1271
+            && matches!(order.signature.scheme, order::signature::Scheme::Eip1271)
+        {
+            return Some(OrderFilterReason::Eip1271);
+        }
+        if let Some(min_notional) = self.min_notional {
+            let sell = order.available().sell;
+            let notional = tokens
+                .get(sell.token)
+                .price
+                .map(|price| price.in_eth(sell.amount));
+            if notional.is_none_or(|notional| notional < min_notional) {
+                return Some(OrderFilterReason::MinNotional);
+            }
+        }
+        None
+    }
+}
+
+/// Why an order was removed by the [`OrderFilters`] of a solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderFilterReason {
+    TokenDenied,
+    TokenNotAllowed,
+    Hooks,
+    Eip1271,
+    MinNotional,
+    MaxOrders,
 }
 
+/// Prioritizes the orders of an auction for a solver. The data needed for that
+/// is fetched once per auction and shared by all processors created with
+/// [`AuctionProcessor::with_order_priority_strategies`], so that only sorting
+/// the orders and allocating the balances of their owners happens per solver.
 #[derive(Clone)]
-pub struct AuctionProcessor(Arc<Mutex<Inner>>);
+pub struct AuctionProcessor {
+    inner: Arc<Mutex<Inner>>,
+    /// Order sorting strategies should be in the same order as the
+    /// `order_priority_strategies` from the driver's config.
+    order_sorting_strategies: Vec<Arc<dyn sorting::SortingStrategy>>,
+    fill_history: sorting::FillHistory,
+}
 
 struct Inner {
     auction: auction::Id,
-    fut: Shared<BoxFuture<'static, Vec<Order>>>,
+    fut: Shared<BoxFuture<'static, Arc<Fetched>>>,
     eth: infra::Ethereum,
-    /// Order sorting strategies should be in the same order as the
-    /// `order_priority_strategies` from the driver's config.
-    order_sorting_strategies: Vec<Arc<dyn sorting::SortingStrategy>>,
     signature_validator: Arc<dyn SignatureValidating>,
     app_data_retriever: Option<order::app_data::AppDataRetriever>,
 }
 
+/// The data fetched to prioritize the orders of an auction, which doesn't
+/// depend on the solver.
+struct Fetched {
+    /// The orders of the auction including the orders of CoW AMMs.
+    orders: Vec<Order>,
+    /// The CoW AMMs that can be used as liquidity.
+    cow_amm_liquidity: Vec<liquidity::cow_amm::Pool>,
+    balances: Balances,
+    app_data_by_hash: HashMap<order::app_data::AppDataHash, app_data::ValidatedAppData>,
+}
+
 type BalanceGroup = (order::Trader, eth::TokenAddress, order::SellTokenBalance);
 type Balances = HashMap<BalanceGroup, order::SellAmount>;
 
@@ -157,22 +295,67 @@ impl AuctionProcessor {
     /// unfillable orders. Fetches full app data for each order and returns an
     /// auction with updated orders.
     pub async fn prioritize(&self, auction: Auction, solver: &eth::H160) -> Auction {
+        let fetched = self.fetch(&auction).await;
+        let tokens = auction.tokens().clone();
+        let solver = *solver;
+        let order_comparators = self.order_sorting_strategies.clone();
+        let settlement: eth::Address = self
+            .inner
+            .lock()
+            .unwrap()
+            .eth
+            .contracts()
+            .settlement()
+            .address()
+            .into();
+
+        // Use spawn_blocking() because sorting is CPU bound and we don't want to
+        // block the runtime for too long.
+        let (orders, cow_amm_liquidity) = tokio::task::spawn_blocking(move || {
+            let _timer = metrics::get()
+                .auction_preprocessing
+                .with_label_values(&["sorting"])
+                .start_timer();
+            let mut orders = fetched.orders.clone();
+            sorting::sort_orders(&mut orders, &tokens, &solver, &order_comparators);
+            let mut balances = fetched.balances.clone();
+            Self::update_orders(
+                &mut balances,
+                &fetched.app_data_by_hash,
+                &mut orders,
+                &settlement,
+            );
+            (orders, fetched.cow_amm_liquidity.clone())
+        })
+        .await
+        .expect(
+            "Either runtime was shut down before spawning the task or no OS threads are \
+             available; no sense in handling those errors",
+        );
+
         Auction {
-            orders: self.prioritize_orders(&auction, solver).await,
+            orders,
+            cow_amm_liquidity,
             ..auction
         }
     }
 
-    fn prioritize_orders(
-        &self,
-        auction: &Auction,
-        solver: &eth::H160,
-    ) -> Shared<BoxFuture<'static, Vec<Order>>> {
+    /// Returns a processor that shares the fetched data with this one but
+    /// sorts the orders with the given strategies.
+    pub fn with_order_priority_strategies(&self, strategies: Vec<OrderPriorityStrategy>) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            order_sorting_strategies: Self::sorting_strategies(strategies, &self.fill_history),
+            fill_history: self.fill_history.clone(),
+        }
+    }
+
+    fn fetch(&self, auction: &Auction) -> Shared<BoxFuture<'static, Arc<Fetched>>> {
         let new_id = auction
             .id()
             .expect("auctions used for quoting do not have to be prioritized");
 
-        let mut lock = self.0.lock().unwrap();
+        let mut lock = self.inner.lock().unwrap();
         let current_id = lock.auction;
         if new_id.0 < current_id.0 {
             tracing::error!(?current_id, ?new_id, "received an outdated auction");
@@ -189,8 +372,6 @@ impl AuctionProcessor {
         let signature_validator = lock.signature_validator.clone();
         let cow_amms = auction.surplus_capturing_jit_order_owners.clone();
         let mut orders = auction.orders.clone();
-        let solver = *solver;
-        let order_comparators = lock.order_sorting_strategies.clone();
         let app_data_retriever = lock.app_data_retriever.clone();
 
         // Use spawn_blocking() because a lot of CPU bound computations are happening
@@ -201,15 +382,16 @@ impl AuctionProcessor {
                 .with_label_values(&["total"])
                 .start_timer();
             let start = std::time::Instant::now();
-            {
+            let cow_amm_liquidity = {
                 let _timer = metrics::get()
                     .auction_preprocessing
-                    .with_label_values(&["cow_amm_orders_and_sorting"])
+                    .with_label_values(&["cow_amm_orders"])
                     .start_timer();
-                orders.extend(rt.block_on(Self::cow_amm_orders(&eth, &tokens, &cow_amms, signature_validator.as_ref())));
-                sorting::sort_orders(&mut orders, &tokens, &solver, &order_comparators);
-            }
-            let (mut balances, mut app_data_by_hash) =
+                let (cow_amm_orders, cow_amm_liquidity) = rt.block_on(Self::cow_amm_orders(&eth, &tokens, &cow_amms, signature_validator.as_ref()));
+                orders.extend(cow_amm_orders);
+                cow_amm_liquidity
+            };
+            let (balances, app_data_by_hash) =
                 rt.block_on(async {
                     tokio::join!(
                         Self::fetch_balances(&eth, &orders),
@@ -217,11 +399,13 @@ impl AuctionProcessor {
                     )
                 });
 
-            let settlement = eth.contracts().settlement().address().into();
-            Self::update_orders(&mut balances, &mut app_data_by_hash, &mut orders, &settlement);
-
             tracing::debug!(auction_id = new_id.0, time =? start.elapsed(), "auction preprocessing done");
-            orders
+            Arc::new(Fetched {
+                orders,
+                cow_amm_liquidity,
+                balances,
+                app_data_by_hash,
+            })
         })
             .map(|res| {
                 res.expect(
@@ -285,7 +469,7 @@ impl AuctionProcessor {
     /// and updates the fetched app data.
     fn update_orders(
         balances: &mut Balances,
-        app_data_by_hash: &mut HashMap<order::app_data::AppDataHash, app_data::ValidatedAppData>,
+        app_data_by_hash: &HashMap<order::app_data::AppDataHash, app_data::ValidatedAppData>,
         orders: &mut Vec<order::Order>,
         settlement: &eth::Address,
     ) {
@@ -426,22 +610,22 @@ impl AuctionProcessor {
         .collect()
     }
 
+    /// Generates the template orders of all CoW AMMs the auction provides
+    /// reference prices for. Orders of CoW AMMs that are eligible for
+    /// surplus get added to the auction directly while all other CoW AMMs
+    /// are offered to solvers as liquidity.
     async fn cow_amm_orders(
         eth: &Ethereum,
         tokens: &Tokens,
         eligible_for_surplus: &HashSet<eth::Address>,
         signature_validator: &dyn SignatureValidating,
-    ) -> Vec<Order> {
+    ) -> (Vec<Order>, Vec<liquidity::cow_amm::Pool>) {
         let cow_amms = eth.contracts().cow_amm_registry().amms().await;
         let domain_separator = eth.contracts().settlement_domain_separator();
         let domain_separator = model::DomainSeparator(domain_separator.0);
         let results: Vec<_> = futures::future::join_all(
             cow_amms
                 .into_iter()
-                // Only generate orders for cow amms the auction told us about.
-                // Otherwise the solver would expect the order to get surplus but
-                // the autopilot would actually not count it.
-                .filter(|amm| eligible_for_surplus.contains(&eth::Address(*amm.address())))
                 // Only generate orders where the auction provided the required
                 // reference prices. Otherwise there will be an error during the
                 // surplus calculation which will also result in 0 surplus for
@@ -465,89 +649,152 @@ impl AuctionProcessor {
         )
         .await;
 
-        // Convert results to domain format.
-        let domain_separator =
-            model::DomainSeparator(eth.contracts().settlement_domain_separator().0);
-        let orders: Vec<_> = results
-            .into_iter()
-            .filter_map(|(amm, result)| match result {
-                Ok(template) => Some(Order {
-                    uid: template.order.uid(&domain_separator, &amm).0.into(),
-                    receiver: template.order.receiver.map(|addr| addr.into()),
-                    created: u32::try_from(Utc::now().timestamp())
-                        .unwrap_or(u32::MIN)
-                        .into(),
-                    valid_to: template.order.valid_to.into(),
-                    buy: eth::Asset {
-                        amount: template.order.buy_amount.into(),
-                        token: template.order.buy_token.into(),
-                    },
-                    sell: eth::Asset {
-                        amount: template.order.sell_amount.into(),
-                        token: template.order.sell_token.into(),
-                    },
-                    kind: order::Kind::Limit,
-                    side: template.order.kind.into(),
-                    app_data: order::app_data::AppDataHash(Bytes(template.order.app_data.0)).into(),
-                    buy_token_balance: template.order.buy_token_balance.into(),
-                    sell_token_balance: template.order.sell_token_balance.into(),
-                    partial: match template.order.partially_fillable {
-                        true => order::Partial::Yes {
-                            available: match template.order.kind {
-                                OrderKind::Sell => order::TargetAmount(template.order.sell_amount),
-                                OrderKind::Buy => order::TargetAmount(template.order.buy_amount),
-                            },
+        let mut orders = Vec::new();
+        let mut pools = Vec::new();
+        for (amm, result) in results {
+            let template = match result {
+                Ok(template) => template,
+                Err(err) => {
+                    tracing::warn!(?err, ?amm, "failed to generate template order for cow amm");
+                    continue;
+                }
+            };
+            let Signature::Eip1271(signature) = template.signature else {
+                tracing::warn!(
+                    signature = ?template.signature,
+                    ?amm,
+                    "signature for cow amm order has incorrect scheme"
+                );
+                continue;
+            };
+            // Only generate orders for cow amms the auction told us about.
+            // Otherwise the solver would expect the order to get surplus but
+            // the autopilot would actually not count it. All other cow amms
+            // can still be used as liquidity.
+            if !eligible_for_surplus.contains(&eth::Address(amm)) {
+                pools.push(liquidity::cow_amm::Pool {
+                    address: amm.into(),
+                    order: liquidity::cow_amm::Order {
+                        sell: eth::Asset {
+                            amount: template.order.sell_amount.into(),
+                            token: template.order.sell_token.into(),
                         },
-                        false => order::Partial::No,
+                        buy: eth::Asset {
+                            amount: template.order.buy_amount.into(),
+                            token: template.order.buy_token.into(),
+                        },
+                        receiver: template.order.receiver.map(Into::into),
+                        valid_to: template.order.valid_to,
+                        app_data: order::app_data::AppDataHash(Bytes(template.order.app_data.0)),
+                        signature: Bytes(signature),
+                        pre_interactions: template
+                            .pre_interactions
+                            .into_iter()
+                            .map(Into::into)
+                            .collect(),
+                        post_interactions: template
+                            .post_interactions
+                            .into_iter()
+                            .map(Into::into)
+                            .collect(),
                     },
-                    pre_interactions: template
-                        .pre_interactions
-                        .into_iter()
-                        .map(Into::into)
-                        .collect(),
-                    post_interactions: template
-                        .post_interactions
-                        .into_iter()
-                        .map(Into::into)
-                        .collect(),
-                    signature: match template.signature {
-                        Signature::Eip1271(bytes) => order::Signature {
-                            scheme: order::signature::Scheme::Eip1271,
-                            data: Bytes(bytes),
-                            signer: amm.into(),
+                });
+                continue;
+            }
+            orders.push(Order {
+                uid: template.order.uid(&domain_separator, &amm).0.into(),
+                receiver: template.order.receiver.map(|addr| addr.into()),
+                created: u32::try_from(Utc::now().timestamp())
+                    .unwrap_or(u32::MIN)
+                    .into(),
+                valid_to: template.order.valid_to.into(),
+                buy: eth::Asset {
+                    amount: template.order.buy_amount.into(),
+                    token: template.order.buy_token.into(),
+                },
+                sell: eth::Asset {
+                    amount: template.order.sell_amount.into(),
+                    token: template.order.sell_token.into(),
+                },
+                kind: order::Kind::Limit,
+                side: template.order.kind.into(),
+                app_data: order::app_data::AppDataHash(Bytes(template.order.app_data.0)).into(),
+                buy_token_balance: template.order.buy_token_balance.into(),
+                sell_token_balance: template.order.sell_token_balance.into(),
+                partial: match template.order.partially_fillable {
+                    true => order::Partial::Yes {
+                        available: match template.order.kind {
+                            OrderKind::Sell => order::TargetAmount(template.order.sell_amount),
+                            OrderKind::Buy => order::TargetAmount(template.order.buy_amount),
                         },
-                        _ => {
-                            tracing::warn!(
-                                signature = ?template.signature,
-                                ?amm,
-                                "signature for cow amm order has incorrect scheme"
-                            );
-                            return None;
-                        }
                     },
-                    protocol_fees: vec![],
-                    quote: None,
-                }),
-                Err(err) => {
-                    tracing::warn!(?err, ?amm, "failed to generate template order for cow amm");
-                    None
-                }
-            })
-            .collect();
+                    false => order::Partial::No,
+                },
+                pre_interactions: template
+                    .pre_interactions
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                post_interactions: template
+                    .post_interactions
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                signature: order::Signature {
+                    scheme: order::signature::Scheme::Eip1271,
+                    data: Bytes(signature),
+                    signer: amm.into(),
+                },
+                protocol_fees: vec![],
+                quote: None,
+            });
+        }
 
         if !orders.is_empty() {
             tracing::debug!(?orders, "generated cow amm template orders");
         }
+        if !pools.is_empty() {
+            tracing::debug!(?pools, "generated cow amm liquidity");
+        }
 
-        orders
+        (orders, pools)
     }
 
     pub fn new(
         eth: &infra::Ethereum,
         order_priority_strategies: Vec<OrderPriorityStrategy>,
         app_data_retriever: Option<order::app_data::AppDataRetriever>,
+        fill_history: sorting::FillHistory,
     ) -> Self {
         let eth = eth.with_metric_label("auctionPreProcessing".into());
+        let order_sorting_strategies =
+            Self::sorting_strategies(order_priority_strategies, &fill_history);
+
+        let signature_validator = shared::signature_validator::validator(
+            eth.web3(),
+            Contracts {
+                settlement: eth.contracts().settlement().address(),
+                vault_relayer: eth.contracts().vault_relayer().0,
+            },
+        );
+
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                auction: Id(0),
+                fut: futures::future::pending().boxed().shared(),
+                eth,
+                signature_validator,
+                app_data_retriever,
+            })),
+            order_sorting_strategies,
+            fill_history,
+        }
+    }
+
+    fn sorting_strategies(
+        order_priority_strategies: Vec<OrderPriorityStrategy>,
+        fill_history: &sorting::FillHistory,
+    ) -> Vec<Arc<dyn sorting::SortingStrategy>> {
         let mut order_sorting_strategies = vec![];
 
         for strategy in order_priority_strategies {
@@ -563,26 +810,35 @@ impl AuctionProcessor {
                         max_order_age: max_order_age.map(|t| Duration::from_std(t).unwrap()),
                     })
                 }
+                OrderPriorityStrategy::FillProbability => Arc::new(sorting::FillProbability {
+                    history: fill_history.clone(),
+                }),
+                OrderPriorityStrategy::EstimatedSurplus => Arc::new(sorting::EstimatedSurplus),
+                OrderPriorityStrategy::Weighted { keys } => Arc::new(sorting::Weighted {
+                    keys: keys
+                        .into_iter()
+                        .map(|key| {
+                            let domain = match key.key {
+                                PriorityKey::ExternalPrice => sorting::WeightedKey::ExternalPrice,
+                                PriorityKey::EstimatedSurplus => {
+                                    sorting::WeightedKey::EstimatedSurplus
+                                }
+                                PriorityKey::FillProbability => {
+                                    sorting::WeightedKey::FillProbability
+                                }
+                                PriorityKey::OwnQuotes => sorting::WeightedKey::OwnQuotes,
+                                PriorityKey::OrderAge => sorting::WeightedKey::OrderAge,
+                            };
+                            (domain, key.weight)
+                        })
+                        .collect(),
+                    history: fill_history.clone(),
+                }),
             };
             order_sorting_strategies.push(comparator);
         }
 
-        let signature_validator = shared::signature_validator::validator(
-            eth.web3(),
-            Contracts {
-                settlement: eth.contracts().settlement().address(),
-                vault_relayer: eth.contracts().vault_relayer().0,
-            },
-        );
-
-        Self(Arc::new(Mutex::new(Inner {
-            auction: Id(0),
-            fut: futures::future::pending().boxed().shared(),
-            eth,
-            order_sorting_strategies,
-            signature_validator,
-            app_data_retriever,
-        })))
+        order_sorting_strategies
     }
 }
 
@@ -599,6 +855,7 @@ impl Tokens {
             price: None,
             available_balance: Default::default(),
             trusted: false,
+            transfer_fee: None,
         })
     }
 
@@ -607,6 +864,16 @@ impl Tokens {
     }
 }
 
+impl FromIterator<Token> for Tokens {
+    fn from_iter<I: IntoIterator<Item = Token>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|token| (token.address, token))
+                .collect(),
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub decimals: Option<u8>,
@@ -617,6 +884,9 @@ pub struct Token {
     pub available_balance: eth::U256,
     /// Is this token well-known and trusted by the protocol?
     pub trusted: bool,
+    /// The fee this token takes on every transfer. Only set for solvers that
+    /// support fee-on-transfer tokens.
+    pub transfer_fee: Option<bad_tokens::TransferFee>,
 }
 
 /// The price of a token in wei. This represents how much wei is needed to buy
@@ -731,3 +1001,253 @@ pub enum Error {
     #[error("blockchain error: {0:?}")]
     Blockchain(#[from] blockchain::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::competition::order::signature};
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::H160::repeat_byte(byte).into()
+    }
+
+    fn order(id: u8, sell: eth::TokenAddress, buy: eth::TokenAddress) -> Order {
+        Order {
+            uid: order::Uid(Bytes([id; order::UID_LEN])),
+            receiver: None,
+            created: util::Timestamp(100),
+            valid_to: util::Timestamp(u32::MAX),
+            buy: eth::Asset {
+                token: buy,
+                amount: eth::U256::exp10(18).into(),
+            },
+            sell: eth::Asset {
+                token: sell,
+                amount: eth::U256::exp10(18).into(),
+            },
+            side: order::Side::Sell,
+            kind: order::Kind::Limit,
+            app_data: Default::default(),
+            partial: order::Partial::No,
+            pre_interactions: Default::default(),
+            post_interactions: Default::default(),
+            sell_token_balance: order::SellTokenBalance::Erc20,
+            buy_token_balance: order::BuyTokenBalance::Erc20,
+            signature: order::Signature {
+                scheme: signature::Scheme::Eip712,
+                data: Default::default(),
+                signer: Default::default(),
+            },
+            protocol_fees: Default::default(),
+            quote: Default::default(),
+        }
+    }
+
+    /// Tokens where a whole unit of token `byte` is worth `byte` ETH.
+    fn tokens(bytes: &[u8]) -> Tokens {
+        bytes
+            .iter()
+            .map(|byte| Token {
+                decimals: Some(18),
+                symbol: None,
+                address: token(*byte),
+                price: Some(
+                    Price::try_new(eth::Ether(eth::U256::exp10(18) * eth::U256::from(*byte)))
+                        .unwrap(),
+                ),
+                available_balance: Default::default(),
+                trusted: false,
+                transfer_fee: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn checks_min_notional() {
+        let filters = OrderFilters {
+            min_notional: Some(eth::Ether(eth::U256::exp10(18) * eth::U256::from(2))),
+            ..Default::default()
+        };
+        let tokens = tokens(&[1, 2]);
+
+        assert_eq!(filters.check(&order(0, token(2), token(1)), &tokens), None);
+        assert_eq!(
+            filters.check(&order(0, token(1), token(2)), &tokens),
+            Some(OrderFilterReason::MinNotional),
+        );
+        // The notional of orders selling tokens without a price is unknown.
+        assert_eq!(
+            filters.check(&order(0, token(3), token(2)), &tokens),
+            Some(OrderFilterReason::MinNotional),
+        );
+    }
+
+    #[test]
+    fn checks_allowed_tokens() {
+        let filters = OrderFilters {
+            allowed_tokens: Some([token(1), token(2)].into()),
+            ..Default::default()
+        };
+        let tokens = tokens(&[]);
+
+        assert_eq!(filters.check(&order(0, token(1), token(2)), &tokens), None);
+        assert_eq!(
+            filters.check(&order(0, token(1), token(3)), &tokens),
+            Some(OrderFilterReason::TokenNotAllowed),
+        );
+        assert_eq!(
+            filters.check(&order(0, token(3), token(2)), &tokens),
+            Some(OrderFilterReason::TokenNotAllowed),
+        );
+    }
+
+    #[test]
+    fn checks_denied_tokens() {
+        let filters = OrderFilters {
+            denied_tokens: [token(3)].into(),
+            ..Default::default()
+        };
+        let tokens = tokens(&[]);
+
+        assert_eq!(filters.check(&order(0, token(1), token(2)), &tokens), None);
+        assert_eq!(
+            filters.check(&order(0, token(1), token(3)), &tokens),
+            Some(OrderFilterReason::TokenDenied),
+        );
+        assert_eq!(
+            filters.check(&order(0, token(3), token(2)), &tokens),
+            Some(OrderFilterReason::TokenDenied),
+        );
+    }
+
+    #[test]
+    fn checks_hooks() {
+        let filters = OrderFilters {
+            exclude_hooks: true,
+            ..Default::default()
+        };
+        let tokens = tokens(&[]);
+        let hook = eth::Interaction {
+            target: eth::Address(eth::H160::repeat_byte(0x42)),
+            value: eth::Ether(0.into()),
+            call_data: Default::default(),
+        };
+
+        let plain = order(0, token(1), token(2));
+        let mut pre = plain.clone();
+        pre.pre_interactions.push(hook.clone());
+        let mut post = plain.clone();
+        post.post_interactions.push(hook);
+
+        assert_eq!(filters.check(&plain, &tokens), None);
+        assert_eq!(filters.check(&pre, &tokens), Some(OrderFilterReason::Hooks));
+        assert_eq!(
+            filters.check(&post, &tokens),
+            Some(OrderFilterReason::Hooks)
+        );
+    }
+
+    #[test]
+    fn checks_eip1271() {
+        let filters = OrderFilters {
+            exclude_eip1271: true,
+            ..Default::default()
+        };
+        let tokens = tokens(&[]);
+
+        let plain = order(0, token(1), token(2));
+        let mut eip1271 = plain.clone();
+        eip1271.signature.scheme = signature::Scheme::Eip1271;
+
+        assert_eq!(filters.check(&plain, &tokens), None);
+        assert_eq!(
+            filters.check(&eip1271, &tokens),
+            Some(OrderFilterReason::Eip1271)
+        );
+    }
+
+    #[test]
+    fn keeps_highest_priority_orders_and_counts_reasons() {
+        let filters = OrderFilters {
+            denied_tokens: [token(3)].into(),
+            max_orders: Some(2),
+            ..Default::default()
+        };
+        let tokens = tokens(&[]);
+        let mut orders = vec![
+            order(0, token(1), token(3)),
+            order(1, token(1), token(2)),
+            order(2, token(3), token(2)),
+            order(3, token(2), token(1)),
+            order(4, token(1), token(2)),
+            order(5, token(2), token(1)),
+        ];
+
+        let filtered = filters.apply(&mut orders, &tokens);
+
+        assert_eq!(
+            orders.iter().map(|order| order.uid).collect::<Vec<_>>(),
+            [
+                order(1, token(1), token(2)).uid,
+                order(3, token(2), token(1)).uid
+            ],
+        );
+        assert_eq!(
+            filtered,
+            HashMap::from([
+                (OrderFilterReason::TokenDenied, 2),
+                (OrderFilterReason::MaxOrders, 2),
+            ]),
+        );
+    }
+
+    #[test]
+    fn does_not_count_orders_within_max_orders() {
+        let filters = OrderFilters {
+            max_orders: Some(2),
+            ..Default::default()
+        };
+        let mut orders = vec![order(0, token(1), token(2))];
+
+        let filtered = filters.apply(&mut orders, &tokens(&[]));
+
+        assert_eq!(orders.len(), 1);
+        assert!(filtered.is_empty());
+    }
+
+    #[test]
+    fn reports_filtered_orders_as_metrics() {
+        let filters = OrderFilters {
+            exclude_eip1271: true,
+            max_orders: Some(1),
+            ..Default::default()
+        };
+        let mut auction = Auction {
+            id: None,
+            orders: (0..4).map(|id| order(id, token(1), token(2))).collect(),
+            tokens: tokens(&[]),
+            gas_price: eth::GasPrice::new(
+                eth::U256::one().into(),
+                eth::U256::one().into(),
+                eth::U256::one().into(),
+            ),
+            deadline: Default::default(),
+            surplus_capturing_jit_order_owners: Default::default(),
+            cow_amm_liquidity: Default::default(),
+        };
+        auction.orders[0].signature.scheme = signature::Scheme::Eip1271;
+        let solver = solver::Name("order-filter-metrics".to_owned());
+        let count = |reason| {
+            metrics::get()
+                .filtered_orders
+                .with_label_values(&[solver.as_str(), reason])
+                .get()
+        };
+
+        auction.filter_orders(&filters, &solver);
+
+        assert_eq!(auction.orders.len(), 1);
+        assert_eq!(count("Eip1271"), 1);
+        assert_eq!(count("MaxOrders"), 2);
+        assert_eq!(count("TokenDenied"), 0);
+    }
+}
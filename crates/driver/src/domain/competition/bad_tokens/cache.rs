@@ -24,8 +24,15 @@ struct Inner {
 struct CacheEntry {
     /// when the decision on the token quality was made
     last_updated: Instant,
-    /// whether the token is supported or not
-    is_supported: bool,
+    /// the detected quality of the token
+    quality: Quality,
+}
+
+impl CacheEntry {
+    /// Entries are valid for less than `max_age` after they were last updated.
+    fn is_outdated(&self, now: Instant, max_age: Duration) -> bool {
+        now.duration_since(self.last_updated) >= max_age
+    }
 }
 
 impl Cache {
@@ -38,22 +45,28 @@ impl Cache {
         }))
     }
 
-    /// Updates whether or not a token should be considered supported.
-    pub fn update_quality(&self, token: eth::TokenAddress, is_supported: bool, now: Instant) {
+    /// Updates the quality of a token.
+    pub fn update_quality(&self, token: eth::TokenAddress, quality: Quality, now: Instant) {
         self.0
             .cache
             .entry(token)
             .and_modify(|token| {
-                if !is_supported || now.duration_since(token.last_updated) > self.0.max_age {
+                let more_restrictive = match (token.quality, quality) {
+                    (_, Quality::Unsupported) => true,
+                    (Quality::Supported, Quality::FeeOnTransfer(_)) => true,
+                    (Quality::FeeOnTransfer(old), Quality::FeeOnTransfer(new)) => new > old,
+                    _ => false,
+                };
+                if more_restrictive || token.is_outdated(now, self.0.max_age) {
                     // Only update the value if the cached value is outdated by now or
-                    // if the new value is "Unsupported". This means on conflicting updates
-                    // we err on the conservative side and assume a token is unsupported.
-                    token.is_supported = is_supported;
+                    // if the new value is more restrictive. This means on conflicting updates
+                    // we err on the conservative side and e.g. assume a token is unsupported.
+                    token.quality = quality;
                 }
                 token.last_updated = now;
             })
             .or_insert_with(|| CacheEntry {
-                is_supported,
+                quality,
                 last_updated: now,
             });
     }
@@ -62,7 +75,7 @@ impl Cache {
         let now = Instant::now();
         self.0
             .cache
-            .retain(|_, value| now.duration_since(value.last_updated) < self.0.max_age);
+            .retain(|_, value| !value.is_outdated(now, self.0.max_age));
     }
 
     /// Returns the quality of the token if the cached value has not expired
@@ -71,11 +84,61 @@ impl Cache {
         let Some(token) = self.0.cache.get(token) else {
             return Quality::Unknown;
         };
-        let still_valid = now.duration_since(token.last_updated) > self.0.max_age;
-        match (still_valid, token.is_supported) {
-            (false, _) => Quality::Unknown,
-            (true, true) => Quality::Supported,
-            (true, false) => Quality::Unsupported,
+        match token.is_outdated(now, self.0.max_age) {
+            true => Quality::Unknown,
+            false => token.quality,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::competition::bad_tokens::TransferFee};
+
+    #[test]
+    fn conflicting_updates_are_conservative() {
+        let cache = Cache::new(Duration::from_secs(60));
+        let token = eth::TokenAddress(eth::ContractAddress(eth::H160([1; 20])));
+        let fee = Quality::FeeOnTransfer(TransferFee::from_bps(10).unwrap());
+        let now = Instant::now();
+        let cached = |cache: &Cache| cache.0.cache.get(&token).unwrap().quality;
+
+        cache.update_quality(token, Quality::Supported, now);
+        assert_eq!(cached(&cache), Quality::Supported);
+
+        cache.update_quality(token, fee, now);
+        assert_eq!(cached(&cache), fee);
+
+        cache.update_quality(token, Quality::Supported, now);
+        assert_eq!(cached(&cache), fee);
+
+        cache.update_quality(token, Quality::Unsupported, now);
+        assert_eq!(cached(&cache), Quality::Unsupported);
+
+        // outdated entries get overwritten
+        let later = now + Duration::from_secs(61);
+        cache.update_quality(token, Quality::Supported, later);
+        assert_eq!(cached(&cache), Quality::Supported);
+    }
+
+    #[test]
+    fn entries_expire_after_max_age() {
+        let max_age = Duration::from_secs(60);
+        let cache = Cache::new(max_age);
+        let token = eth::TokenAddress(eth::ContractAddress(eth::H160([1; 20])));
+        let now = Instant::now();
+
+        cache.update_quality(token, Quality::Unsupported, now);
+        assert_eq!(cache.get_quality(&token, now), Quality::Unsupported);
+        assert_eq!(
+            cache.get_quality(&token, now + max_age - Duration::from_millis(1)),
+            Quality::Unsupported
+        );
+        assert_eq!(cache.get_quality(&token, now + max_age), Quality::Unknown);
+
+        // an update at exactly `max_age` replaces the expired entry even if it
+        // is less restrictive
+        cache.update_quality(token, Quality::Supported, now + max_age);
+        assert_eq!(cache.get_quality(&token, now + max_age), Quality::Supported);
+    }
+}
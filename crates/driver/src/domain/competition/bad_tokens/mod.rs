@@ -2,18 +2,28 @@ use {
     crate::domain::{competition::Auction, eth},
     futures::future::join_all,
     itertools::{Either, Itertools},
-    std::{collections::HashMap, fmt, time::Instant},
+    std::{
+        collections::{HashMap, HashSet},
+        fmt,
+        time::Instant,
+    },
 };
 
 pub mod cache;
 pub mod metrics;
 pub mod simulation;
 
+pub use shared::bad_token::TransferFee;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Quality {
     /// Solver is likely to produce working solutions when computing
     /// routes for this token.
     Supported,
+    /// Token works like a regular ERC20 token except that it takes a fee
+    /// on every transfer. Only solvers that explicitly opted in can handle
+    /// these tokens. For all others the token is [`Quality::Unsupported`].
+    FeeOnTransfer(TransferFee),
     /// Solver will likely produce failing solutions when computing
     /// routes for this token. This can have many reasons:
     /// * fees on transfer
@@ -36,6 +46,9 @@ pub struct Detector {
     hardcoded: HashMap<eth::TokenAddress, Quality>,
     simulation_detector: Option<simulation::Detector>,
     metrics: Option<metrics::Detector>,
+    /// Whether the solver is able to handle tokens that take a fee on
+    /// transfer.
+    fee_on_transfer_supported: bool,
 }
 
 impl Detector {
@@ -55,6 +68,13 @@ impl Detector {
         self
     }
 
+    /// Keeps orders trading tokens that take a fee on transfer and informs
+    /// the solver about the fee instead of dropping them.
+    pub fn with_fee_on_transfer_support(&mut self) -> &mut Self {
+        self.fee_on_transfer_supported = true;
+        self
+    }
+
     /// Enables detection of unsupported tokens based on heuristics.
     pub fn with_metrics_detector(&mut self, detector: metrics::Detector) -> &mut Self {
         self.metrics = Some(detector);
@@ -69,8 +89,6 @@ impl Detector {
             let sell = self.get_token_quality(order.sell.token, now);
             let buy = self.get_token_quality(order.buy.token, now);
             match (sell, buy) {
-                // both tokens supported => keep order
-                (Quality::Supported, Quality::Supported) => Either::Left(order),
                 // at least 1 token unsupported => drop order
                 (Quality::Unsupported, _) | (_, Quality::Unsupported) => Either::Right(order.uid),
                 // sell token quality is unknown => keep order if token is supported
@@ -79,14 +97,16 @@ impl Detector {
                         // we can't determine quality => assume order is good
                         return Either::Left(order);
                     };
-                    match detector.determine_sell_token_quality(&order, now).await {
-                        Quality::Supported => Either::Left(order),
+                    match self.restrict(detector.determine_sell_token_quality(&order, now).await) {
+                        Quality::Supported | Quality::FeeOnTransfer(_) => Either::Left(order),
                         _ => Either::Right(order.uid),
                     }
                 }
                 // buy token quality is unknown => keep order (because we can't
                 // determine quality and assume it's good)
                 (_, Quality::Unknown) => Either::Left(order),
+                // both tokens supported => keep order
+                _ => Either::Left(order),
             }
         });
         let (supported_orders, removed_uids): (Vec<_>, Vec<_>) = join_all(token_quality_checks)
@@ -99,6 +119,19 @@ impl Detector {
             tracing::debug!(orders = ?removed_uids, "ignored orders with unsupported tokens");
         }
 
+        if self.fee_on_transfer_supported {
+            let tokens: HashSet<_> = auction
+                .orders
+                .iter()
+                .flat_map(|order| [order.sell.token, order.buy.token])
+                .collect();
+            for token in tokens {
+                if let Quality::FeeOnTransfer(fee) = self.get_token_quality(token, now) {
+                    auction.set_transfer_fee(token, fee);
+                }
+            }
+        }
+
         if let Some(detector) = &self.simulation_detector {
             detector.evict_outdated_entries();
         }
@@ -123,14 +156,14 @@ impl Detector {
     fn get_token_quality(&self, token: eth::TokenAddress, now: Instant) -> Quality {
         match self.hardcoded.get(&token) {
             None | Some(Quality::Unknown) => (),
-            Some(quality) => return *quality,
+            Some(quality) => return self.restrict(*quality),
         }
 
-        if let Some(Quality::Unsupported) = self
+        let simulated = self
             .simulation_detector
             .as_ref()
-            .map(|d| d.get_quality(&token, now))
-        {
+            .map(|d| d.get_quality(&token, now));
+        if let Some(Quality::Unsupported) = simulated {
             return Quality::Unsupported;
         }
 
@@ -140,7 +173,18 @@ impl Detector {
             return Quality::Unsupported;
         }
 
-        Quality::Unknown
+        match simulated {
+            Some(quality @ Quality::FeeOnTransfer(_)) => self.restrict(quality),
+            _ => Quality::Unknown,
+        }
+    }
+
+    /// Fee-on-transfer tokens are unsupported unless the solver opted in.
+    fn restrict(&self, quality: Quality) -> Quality {
+        match quality {
+            Quality::FeeOnTransfer(_) if !self.fee_on_transfer_supported => Quality::Unsupported,
+            quality => quality,
+        }
     }
 }
 
@@ -3,7 +3,7 @@ use {
         domain::{
             competition::{
                 Order,
-                bad_tokens::{Quality, cache::Cache},
+                bad_tokens::{Quality, TransferFee, cache::Cache},
                 order,
             },
             eth,
@@ -36,9 +36,14 @@ struct Inner {
 }
 
 impl Detector {
-    pub fn new(max_age: Duration, eth: &infra::Ethereum) -> Self {
+    pub fn new(
+        max_age: Duration,
+        max_transfer_fee: Option<TransferFee>,
+        eth: &infra::Ethereum,
+    ) -> Self {
         let detector =
-            TraceCallDetectorRaw::new(eth.web3().clone(), eth.contracts().settlement().address());
+            TraceCallDetectorRaw::new(eth.web3().clone(), eth.contracts().settlement().address())
+                .with_max_transfer_fee(max_transfer_fee);
         Self(Arc::new(Inner {
             cache: Cache::new(max_age),
             detector,
@@ -95,16 +100,23 @@ impl Detector {
                         Ok(TokenQuality::Good) => {
                             inner
                                 .cache
-                                .update_quality(sell_token, true, now);
+                                .update_quality(sell_token, Quality::Supported, now);
                             Quality::Supported
                         }
+                        Ok(TokenQuality::FeeOnTransfer { fee }) => {
+                            tracing::debug!(?fee, token=?sell_token.0, "cache token as fee-on-transfer");
+                            inner
+                                .cache
+                                .update_quality(sell_token, Quality::FeeOnTransfer(fee), now);
+                            Quality::FeeOnTransfer(fee)
+                        }
                         Ok(TokenQuality::Bad { reason }) => {
                             tracing::debug!(reason, token=?sell_token.0, "cache token as unsupported");
                             // All solvers share the same cache for the simulation detector, so there is no need to specify the solver name here.
                             metrics::get().bad_tokens_detected.with_label_values(&["any", "simulation"]).inc();
                             inner
                                 .cache
-                                .update_quality(sell_token, false, now);
+                                .update_quality(sell_token, Quality::Unsupported, now);
                             Quality::Unsupported
                         }
                     }
@@ -0,0 +1,170 @@
+//! Search for the combinations of solutions that are most valuable to merge.
+
+use crate::domain::eth;
+
+/// Upper bound on the number of combinations explored by the search to keep
+/// its run time bounded regardless of how the candidates conflict.
+const MAX_SEARCH_STEPS: usize = 100_000;
+
+/// A solution that can potentially be merged with others.
+pub struct Candidate {
+    pub score: eth::U256,
+    pub orders: usize,
+}
+
+/// Returns up to `limit` combinations of at least two pairwise compatible
+/// candidates with the highest total score, best first. Combinations are
+/// returned as indices into `candidates` in ascending order.
+///
+/// Scores of merged solutions are approximated by the sum of the scores of
+/// the combined solutions, which holds as long as they don't share orders.
+pub fn best_combinations(
+    candidates: &[Candidate],
+    compatible: impl Fn(usize, usize) -> bool,
+    max_orders: usize,
+    limit: usize,
+) -> Vec<Vec<usize>> {
+    let mut search = Search {
+        candidates,
+        compatible: &compatible,
+        max_orders,
+        limit,
+        remaining_scores: remaining_scores(candidates),
+        best: Vec::new(),
+        steps: 0,
+    };
+    search.extend(&mut Vec::new(), 0, eth::U256::zero(), 0);
+
+    search.best.sort_by(|(a, _), (b, _)| b.cmp(a));
+    search
+        .best
+        .into_iter()
+        .map(|(_, combination)| combination)
+        .collect()
+}
+
+struct Search<'a, F> {
+    candidates: &'a [Candidate],
+    compatible: &'a F,
+    max_orders: usize,
+    limit: usize,
+    /// The total score of all candidates from an index onwards, used for
+    /// pruning combinations that can't beat the ones found so far.
+    remaining_scores: Vec<eth::U256>,
+    best: Vec<(eth::U256, Vec<usize>)>,
+    steps: usize,
+}
+
+impl<F> Search<'_, F>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    fn extend(
+        &mut self,
+        combination: &mut Vec<usize>,
+        next: usize,
+        score: eth::U256,
+        orders: usize,
+    ) {
+        for index in next..self.candidates.len() {
+            if self.steps >= MAX_SEARCH_STEPS {
+                return;
+            }
+            self.steps += 1;
+
+            if self.best.len() == self.limit
+                && score.saturating_add(self.remaining_scores[index]) <= self.worst_best()
+            {
+                return;
+            }
+
+            let candidate = &self.candidates[index];
+            if orders + candidate.orders > self.max_orders
+                || !combination
+                    .iter()
+                    .all(|&other| (self.compatible)(other, index))
+            {
+                continue;
+            }
+
+            combination.push(index);
+            let score = score.saturating_add(candidate.score);
+            if combination.len() > 1 {
+                self.record(score, combination);
+            }
+            self.extend(combination, index + 1, score, orders + candidate.orders);
+            combination.pop();
+        }
+    }
+
+    fn worst_best(&self) -> eth::U256 {
+        self.best
+            .iter()
+            .map(|(score, _)| *score)
+            .min()
+            .unwrap_or_default()
+    }
+
+    fn record(&mut self, score: eth::U256, combination: &[usize]) {
+        if self.best.len() < self.limit {
+            self.best.push((score, combination.to_vec()));
+            return;
+        }
+        if let Some(entry) = self
+            .best
+            .iter_mut()
+            .min_by_key(|(score, _)| *score)
+            .filter(|(worst, _)| *worst < score)
+        {
+            *entry = (score, combination.to_vec());
+        }
+    }
+}
+
+fn remaining_scores(candidates: &[Candidate]) -> Vec<eth::U256> {
+    let mut remaining = vec![eth::U256::zero(); candidates.len()];
+    let mut total = eth::U256::zero();
+    for (index, candidate) in candidates.iter().enumerate().rev() {
+        total = total.saturating_add(candidate.score);
+        remaining[index] = total;
+    }
+    remaining
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(scores: &[u64]) -> Vec<Candidate> {
+        scores
+            .iter()
+            .map(|&score| Candidate {
+                score: score.into(),
+                orders: 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prefers_best_total_score_over_first_fit() {
+        // A greedy merge starting with the best solution would only combine 0
+        // with 3 and miss that 1 and 2 together are worth more.
+        let conflicts = [(0, 1), (0, 2)];
+        let compatible = |a: usize, b: usize| !conflicts.contains(&(a.min(b), a.max(b)));
+
+        let best = best_combinations(&candidates(&[10, 8, 7, 1]), compatible, 10, 2);
+        assert_eq!(best, vec![vec![1, 2, 3], vec![1, 2]]);
+    }
+
+    #[test]
+    fn respects_order_limit() {
+        let best = best_combinations(&candidates(&[5, 4, 3]), |_, _| true, 2, 10);
+        assert_eq!(best, vec![vec![0, 1], vec![0, 2], vec![1, 2]]);
+    }
+
+    #[test]
+    fn requires_at_least_two_solutions() {
+        assert!(best_combinations(&candidates(&[5, 4]), |_, _| false, 10, 10).is_empty());
+        assert!(best_combinations(&candidates(&[5]), |_, _| true, 10, 10).is_empty());
+    }
+}
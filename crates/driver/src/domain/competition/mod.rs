@@ -5,15 +5,16 @@ use {
         time::{self, Remaining},
     },
     crate::{
-        domain::{competition::solution::Settlement, eth, time::DeadlineExceeded},
+        domain::{competition::solution::Settlement, eth, liquidity, time::DeadlineExceeded},
         infra::{
             self,
             Simulator,
             blockchain::Ethereum,
             notify,
             observe,
+            persistence,
             simulator::{RevertError, SimulatorError},
-            solver::{self, SolutionMerging, Solver},
+            solver::{self, SolutionMerging, Solver, accounts},
         },
         util::Bytes,
     },
@@ -27,15 +28,16 @@ use {
         time::Duration,
     },
     tap::TapFallible,
-    tokio::sync::{mpsc, oneshot},
+    tokio::sync::{Semaphore, mpsc, oneshot},
     tracing::Instrument,
 };
 
 pub mod auction;
 pub mod bad_tokens;
+mod merging;
 pub mod order;
 pub mod solution;
-mod sorting;
+pub mod sorting;
 
 pub use {
     auction::{Auction, AuctionProcessor},
@@ -60,6 +62,7 @@ pub struct Competition {
     /// Cached solutions with the most recent solutions at the front.
     pub settlements: Mutex<VecDeque<Settlement>>,
     pub bad_tokens: Arc<bad_tokens::Detector>,
+    fill_history: sorting::FillHistory,
     settle_queue: mpsc::Sender<SettleRequest>,
 }
 
@@ -71,6 +74,7 @@ impl Competition {
         simulator: Simulator,
         mempools: Mempools,
         bad_tokens: Arc<bad_tokens::Detector>,
+        fill_history: sorting::FillHistory,
     ) -> Arc<Self> {
         let (settle_sender, settle_receiver) = mpsc::channel(solver.settle_queue_size());
 
@@ -83,6 +87,7 @@ impl Competition {
             settlements: Default::default(),
             settle_queue: settle_sender,
             bad_tokens,
+            fill_history,
         });
 
         let competition_clone = Arc::clone(&competition);
@@ -97,19 +102,34 @@ impl Competition {
 
     /// Solve an auction as part of this competition.
     pub async fn solve(&self, auction: Auction) -> Result<Option<Solved>, Error> {
-        let auction = &self
+        let mut auction = self
             .bad_tokens
             .filter_unsupported_orders_in_auction(auction)
             .await;
+        auction.filter_orders(self.solver.order_filters(), self.solver.name());
+        let auction = &auction;
 
         let liquidity = match self.solver.liquidity() {
             solver::Liquidity::Fetch => {
-                self.liquidity
+                let mut fetched = self
+                    .liquidity
                     .fetch(
                         &auction.liquidity_pairs(),
                         infra::liquidity::AtBlock::Latest,
                     )
-                    .await
+                    .await;
+                let cow_amms = auction
+                    .cow_amm_liquidity()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pool)| liquidity::Liquidity {
+                        id: liquidity::Id(fetched.len() + i),
+                        gas: liquidity::cow_amm::GAS_PER_ORDER.into(),
+                        kind: liquidity::Kind::CowAmm(pool.clone()),
+                    })
+                    .collect_vec();
+                fetched.extend(cow_amms);
+                fetched
             }
             solver::Liquidity::Skip => Default::default(),
         };
@@ -127,11 +147,20 @@ impl Competition {
 
         observe::postprocessing(&solutions, auction.deadline().driver());
 
+        let persistence = self.solver.persistence();
+        let recorder = &persistence.competition_recorder();
+        let archive_record = || {
+            if let Some(id) = auction.id() {
+                persistence.archive_competition(id, recorder);
+            }
+        };
+
         // Discard solutions that don't have unique ID.
         let mut ids = HashSet::new();
         let solutions = solutions.into_iter().filter(|solution| {
             if !ids.insert(solution.id().clone()) {
                 observe::duplicated_solution_id(self.solver.name(), solution.id());
+                recorder.discarded(solution, "duplicated solution id");
                 notify::duplicated_solution_id(&self.solver, auction.id(), solution.id());
                 false
             } else {
@@ -143,6 +172,7 @@ impl Competition {
         let solutions = solutions.filter(|solution| {
             if solution.is_empty(auction.surplus_capturing_jit_order_owners()) {
                 observe::empty_solution(self.solver.name(), solution.id());
+                recorder.discarded(solution, "empty solution");
                 notify::empty_solution(&self.solver, auction.id(), solution.id().clone());
                 false
             } else {
@@ -150,12 +180,21 @@ impl Competition {
             }
         });
 
+        // Take out the flashloans hinted by the orders on behalf of the solver.
+        let solutions = solutions.map(|solution| match self.solver.wrap_flashloans() {
+            true => solution.with_hinted_flashloans(self.eth.contracts()),
+            false => solution,
+        });
+
         let all_solutions = match self.solver.solution_merging() {
             SolutionMerging::Allowed {
                 max_orders_per_merged_solution,
             } => merge(solutions, auction, max_orders_per_merged_solution),
             SolutionMerging::Forbidden => solutions.collect(),
         };
+        for solution in &all_solutions {
+            recorder.solution(solution);
+        }
 
         // Encode solutions into settlements (streamed).
         let encoded = all_solutions
@@ -182,8 +221,12 @@ impl Competition {
                         Some(solution)
                     }
                     // don't report on errors coming from solution merging
-                    Err(_err) if id.solutions().len() > 1 => None,
+                    Err(err) if id.solutions().len() > 1 => {
+                        recorder.encoding_failed(&id, &err);
+                        None
+                    }
                     Err(err) => {
+                        recorder.encoding_failed(&id, &err);
                         self.bad_tokens.encoding_failed(&token_pairs);
                         observe::encoding_failed(self.solver.name(), &id, &err);
                         notify::encoding_failed(&self.solver, auction.id(), &id, &err);
@@ -221,6 +264,7 @@ impl Competition {
                     settlement.score(
                         &auction.native_prices(),
                         auction.surplus_capturing_jit_order_owners(),
+                        auction.tokens(),
                     ),
                     settlement,
                 )
@@ -234,6 +278,7 @@ impl Competition {
                 result
                     .tap_err(|err| {
                         observe::scoring_failed(self.solver.name(), err);
+                        recorder.scoring_failed(settlement.solution(), err);
                         notify::scoring_failed(
                             &self.solver,
                             auction.id(),
@@ -249,6 +294,7 @@ impl Competition {
         // Observe the scores.
         for (score, settlement) in scores.iter() {
             observe::score(settlement, score);
+            recorder.scored(settlement.solution(), *score, settlement.gas.estimate);
         }
 
         // Pick the best-scoring settlement.
@@ -263,6 +309,7 @@ impl Competition {
                         trades: settlement.orders(),
                         prices: settlement.prices(),
                         gas: Some(settlement.gas.estimate),
+                        submission_address: self.solver.submission_address(settlement.submitter()),
                     },
                     settlement,
                 )
@@ -270,9 +317,11 @@ impl Competition {
             .unzip();
 
         let Some(settlement) = settlement else {
+            archive_record();
             // Don't wait for the deadline because we can't produce a solution anyway.
             return Ok(score);
         };
+        recorder.winner(settlement.solution());
         let solution_id = settlement.solution().get();
 
         {
@@ -297,6 +346,7 @@ impl Competition {
                         self.simulate_settlement(&settlement).await
                     {
                         observe::winner_voided(block, &err);
+                        recorder.voided(settlement.solution(), &err);
                         *score_ref = None;
                         self.settlements
                             .lock()
@@ -315,6 +365,15 @@ impl Competition {
             };
             let _ = tokio::time::timeout(remaining, simulate_on_new_blocks).await;
         }
+        archive_record();
+        if let Some(solved) = &score {
+            self.fill_history.proposed(
+                solved
+                    .trades
+                    .values()
+                    .map(|order| (order.sell.token, order.buy.token)),
+            );
+        }
 
         Ok(score)
     }
@@ -330,8 +389,19 @@ impl Competition {
             .unwrap()
             .iter()
             .find(|s| s.solution().get() == solution_id && s.auction_id == auction_id)
-            .cloned()
-            .ok_or(Error::SolutionNotAvailable)?;
+            .cloned();
+        self.solver.persistence().archive_settlement(
+            auction_id,
+            "reveal",
+            persistence::competition::Settlement {
+                solution_id,
+                tx_hash: None,
+                error: settlement
+                    .is_none()
+                    .then(|| format!("{:?}", Error::SolutionNotAvailable)),
+            },
+        );
+        let settlement = settlement.ok_or(Error::SolutionNotAvailable)?;
         Ok(Revealed {
             internalized_calldata: settlement
                 .transaction(settlement::Internalization::Enable)
@@ -386,38 +456,67 @@ impl Competition {
         self: Arc<Self>,
         mut settle_receiver: mpsc::Receiver<SettleRequest>,
     ) {
-        while let Some(request) = settle_receiver.recv().await {
-            let SettleRequest {
-                auction_id,
-                solution_id,
-                submission_deadline,
-                response_sender,
-                tracing_span,
-            } = request;
-            async {
-                if self.eth.current_block().borrow().number >= submission_deadline {
-                    if let Err(err) = response_sender.send(Err(DeadlineExceeded.into())) {
-                        tracing::error!(
-                            ?err,
-                            "settle deadline exceeded. unable to return a response"
-                        );
-                    }
-                    return;
+        // Settlements are submitted in parallel, at most one per solver
+        // account. Further requests wait in the queue until an account is
+        // done with its settlement.
+        let permits = Arc::new(Semaphore::new(self.solver.accounts().count()));
+        loop {
+            let permit = permits
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            let Some(request) = settle_receiver.recv().await else {
+                break;
+            };
+            let competition = Arc::clone(&self);
+            tokio::spawn(async move {
+                competition.handle_settle_request(request).await;
+                drop(permit);
+            });
+        }
+    }
+
+    async fn handle_settle_request(&self, request: SettleRequest) {
+        let SettleRequest {
+            auction_id,
+            solution_id,
+            submission_deadline,
+            response_sender,
+            tracing_span,
+        } = request;
+        async {
+            if self.eth.current_block().borrow().number >= submission_deadline {
+                if let Err(err) = response_sender.send(Err(DeadlineExceeded.into())) {
+                    tracing::error!(
+                        ?err,
+                        "settle deadline exceeded. unable to return a response"
+                    );
                 }
+                return;
+            }
 
-                observe::settling();
-                let result = self
-                    .process_settle_request(auction_id, solution_id, submission_deadline)
-                    .await;
-                observe::settled(self.solver.name(), &result);
+            observe::settling();
+            let result = self
+                .process_settle_request(auction_id, solution_id, submission_deadline)
+                .await;
+            observe::settled(self.solver.name(), &result);
+            self.solver.persistence().archive_settlement(
+                auction_id,
+                "settle",
+                persistence::competition::Settlement {
+                    solution_id,
+                    tx_hash: result.as_ref().ok().map(|settled| settled.tx_hash.0),
+                    error: result.as_ref().err().map(|err| format!("{err:?}")),
+                },
+            );
 
-                if let Err(err) = response_sender.send(result) {
-                    tracing::error!(?err, "Failed to send /settle response");
-                }
+            if let Err(err) = response_sender.send(result) {
+                tracing::error!(?err, "Failed to send /settle response");
             }
-            .instrument(tracing_span)
-            .await
         }
+        .instrument(tracing_span)
+        .await
     }
 
     async fn process_settle_request(
@@ -450,9 +549,31 @@ impl Competition {
             settlement.gas.price = gas_price;
         }
 
+        // Submit from the account the settlement was simulated with, since
+        // that is the one reported to the protocol.
+        let required = settlement.gas.required_balance();
+        let account = self
+            .solver
+            .accounts()
+            .acquire(&self.eth, settlement.submitter(), required)
+            .await
+            .map_err(|err| {
+                tracing::warn!(?err, "solver account unavailable to submit the settlement");
+                if matches!(err, accounts::Error::InsufficientBalance) {
+                    notify::solver_account_insufficient_balance(
+                        &self.solver,
+                        Some(settlement.auction_id),
+                        settlement.solution(),
+                        settlement.submitter(),
+                        required,
+                    );
+                }
+                Error::SubmissionError
+            })?;
+
         let executed = self
             .mempools
-            .execute(&self.solver, &settlement, submission_deadline)
+            .execute(&account, &settlement, submission_deadline)
             .await;
         notify::executed(
             &self.solver,
@@ -460,6 +581,14 @@ impl Competition {
             settlement.solution(),
             &executed,
         );
+        if executed.is_ok() {
+            self.fill_history.settled(
+                settlement
+                    .orders()
+                    .values()
+                    .map(|order| (order.sell.token, order.buy.token)),
+            );
+        }
 
         match executed {
             Err(_) => Err(Error::SubmissionError),
@@ -505,50 +634,97 @@ impl Competition {
     }
 }
 
-const MAX_SOLUTIONS_TO_MERGE: usize = 10;
+/// Number of best scoring solutions that are considered for merging.
+const MAX_SOLUTIONS_TO_MERGE: usize = 20;
+
+/// Number of merged solutions that get encoded (and thereby simulated) in
+/// addition to the individual solutions.
+const MAX_MERGED_SOLUTIONS: usize = 5;
 
-/// Creates a vector with all possible combinations of the given solutions.
-/// The result is sorted descending by score.
+/// Returns the given solutions together with the most valuable merged
+/// combinations of them, sorted descending by score.
+///
+/// Instead of merging greedily, all combinations of pairwise compatible
+/// solutions are searched for the ones with the highest combined score.
+/// Merged solutions are only offered if they also pass encoding, which
+/// simulates the merged settlement.
 fn merge(
     solutions: impl Iterator<Item = Solution>,
     auction: &Auction,
     max_orders_per_merged_solution: usize,
 ) -> Vec<Solution> {
-    let mut merged: Vec<Solution> = Vec::new();
-    // Limit the number of solutions to merge to avoid combinatorial explosion
-    // (2^MAX_SOLUTIONS).
-    for solution in solutions.take(MAX_SOLUTIONS_TO_MERGE) {
-        let mut extension = vec![];
-        for already_merged in merged.iter() {
-            match solution.merge(already_merged, max_orders_per_merged_solution) {
-                Ok(merged) => {
-                    observe::merged(&solution, already_merged, &merged);
-                    extension.push(merged);
+    let score = |solution: &Solution| {
+        solution
+            .scoring(
+                &auction.native_prices(),
+                auction.surplus_capturing_jit_order_owners(),
+                auction.tokens(),
+            )
+            .map(|score| score.0)
+            .unwrap_or_default()
+    };
+    let mut solutions = solutions
+        .map(|solution| (score(&solution), solution))
+        .collect_vec();
+    solutions.sort_by_key(|(score, _)| Reverse(*score));
+
+    // Limit the number of solutions to merge to bound the cost of the search
+    // and of checking the compatibility of all pairs.
+    let mergeable = &solutions[..solutions.len().min(MAX_SOLUTIONS_TO_MERGE)];
+    let mut compatible = HashSet::new();
+    for (i, (_, first)) in mergeable.iter().enumerate() {
+        for (j, (_, second)) in mergeable.iter().enumerate().skip(i + 1) {
+            match first.merge(second, max_orders_per_merged_solution) {
+                Ok(_) => {
+                    compatible.insert((i, j));
+                }
+                Err(err) => observe::not_merged(first, second, err),
+            }
+        }
+    }
+
+    let candidates = mergeable
+        .iter()
+        .map(|(score, solution)| merging::Candidate {
+            score: *score,
+            orders: solution.trades().len(),
+        })
+        .collect_vec();
+    let combinations = merging::best_combinations(
+        &candidates,
+        |i, j| compatible.contains(&(i.min(j), i.max(j))),
+        max_orders_per_merged_solution,
+        MAX_MERGED_SOLUTIONS,
+    );
+
+    // Pairwise compatibility doesn't guarantee that all solutions of a
+    // combination merge, so merged solutions that fail are skipped.
+    let mut merged = Vec::new();
+    for combination in combinations {
+        let mut parts = combination.iter().map(|&i| &mergeable[i].1);
+        let Some(first) = parts.next() else {
+            continue;
+        };
+        let result = parts.try_fold(first.clone(), |merged, part| {
+            match merged.merge(part, max_orders_per_merged_solution) {
+                Ok(result) => {
+                    observe::merged(&merged, part, &result);
+                    Some(result)
                 }
                 Err(err) => {
-                    observe::not_merged(&solution, already_merged, err);
+                    observe::not_merged(&merged, part, err);
+                    None
                 }
             }
+        });
+        if let Some(solution) = result {
+            merged.push((score(&solution), solution));
         }
-
-        // At least insert the current solution
-        extension.push(solution);
-        merged.extend(extension);
     }
 
-    // Sort merged solutions descending by score.
-    merged.sort_by_key(|solution| {
-        Reverse(
-            solution
-                .scoring(
-                    &auction.native_prices(),
-                    auction.surplus_capturing_jit_order_owners(),
-                )
-                .map(|score| score.0)
-                .unwrap_or_default(),
-        )
-    });
-    merged
+    let mut all = solutions.into_iter().chain(merged).collect_vec();
+    all.sort_by_key(|(score, _)| Reverse(*score));
+    all.into_iter().map(|(_, solution)| solution).collect()
 }
 
 /// Returns the aimed time limit for bringing the solution onchain, based on the
@@ -587,6 +763,9 @@ pub struct Solved {
     pub trades: HashMap<order::Uid, Amounts>,
     pub prices: HashMap<eth::TokenAddress, eth::TokenAmount>,
     pub gas: Option<eth::Gas>,
+    /// The address calling the settlement contract when settling the
+    /// solution.
+    pub submission_address: eth::Address,
 }
 
 #[derive(Debug)]
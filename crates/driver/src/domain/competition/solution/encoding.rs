@@ -10,7 +10,7 @@ use {
             liquidity,
         },
         infra::{self, solver::ManageNativeToken},
-        util::{Bytes, conv::u256::U256Ext},
+        util::Bytes,
     },
     allowance::Allowance,
     itertools::Itertools,
@@ -225,9 +225,10 @@ pub fn tx(
             );
 
             // Repayment amount needs to be increased by flash fee
-            let fee_amount =
-                (flashloan.amount.0 * flashloan_wrapper.fee_in_bps).ceil_div(&10_000.into());
-            let repayment_amount = flashloan.amount.0 + fee_amount;
+            let repayment_amount = flashloan_wrapper
+                .fee(flashloan.amount)
+                .and_then(|fee| flashloan.amount.0.checked_add(fee.0))
+                .ok_or(Math::Overflow)?;
 
             // Since the order receiver is expected to be the setttlement contract, we need
             // to transfer tokens from the settlement contract to the flashloan wrapper
@@ -269,12 +270,12 @@ pub fn tx(
             continue;
         }
 
-        interactions.push(match interaction {
-            competition::solution::Interaction::Custom(interaction) => eth::Interaction {
+        interactions.extend(match interaction {
+            competition::solution::Interaction::Custom(interaction) => vec![eth::Interaction {
                 value: interaction.value,
                 target: interaction.target.into(),
                 call_data: interaction.call_data.clone(),
-            },
+            }],
             competition::solution::Interaction::Liquidity(liquidity) => {
                 liquidity_interaction(liquidity, &slippage, contracts.settlement())?
             }
@@ -316,6 +317,12 @@ pub fn tx(
         (router.address().into(), calldata)
     };
 
+    // Route the call through the solver's executor contract if it has one
+    let (to, calldata) = match solution.solver().executor() {
+        Some(executor) => (executor.address, executor.wrap(to, calldata)),
+        None => (to, calldata),
+    };
+
     Ok(eth::Tx {
         from: solution.solver().address(),
         to,
@@ -329,7 +336,7 @@ pub fn liquidity_interaction(
     liquidity: &Liquidity,
     slippage: &slippage::Parameters,
     settlement: &contracts::GPv2Settlement,
-) -> Result<eth::Interaction, Error> {
+) -> Result<Vec<eth::Interaction>, Error> {
     let (input, output) = slippage.apply_to(&slippage::Interaction {
         input: liquidity.input,
         output: liquidity.output,
@@ -338,20 +345,41 @@ pub fn liquidity_interaction(
     match liquidity.liquidity.kind.clone() {
         liquidity::Kind::UniswapV2(pool) => pool
             .swap(&input, &output, &settlement.address().into())
+            .map(|interaction| vec![interaction])
             .ok(),
         liquidity::Kind::UniswapV3(pool) => pool
             .swap(&input, &output, &settlement.address().into())
+            .map(|interaction| vec![interaction])
             .ok(),
         liquidity::Kind::BalancerV2Stable(pool) => pool
             .swap(&input, &output, &settlement.address().into())
+            .map(|interaction| vec![interaction])
             .ok(),
         liquidity::Kind::BalancerV2Weighted(pool) => pool
             .swap(&input, &output, &settlement.address().into())
+            .map(|interaction| vec![interaction])
             .ok(),
         liquidity::Kind::Swapr(pool) => pool
             .swap(&input, &output, &settlement.address().into())
+            .map(|interaction| vec![interaction])
+            .ok(),
+        liquidity::Kind::Curve(pool) => pool
+            .swap(&input, &output)
+            .map(|interaction| vec![interaction])
+            .ok(),
+        // Balancer V3 swaps require a Permit2 allowance for the router in
+        // addition to the swap itself.
+        liquidity::Kind::BalancerV3(pool) => pool.swap(&input, &output).ok(),
+        // Uniswap V4 swaps go through the universal router which, like the
+        // Balancer V3 router, settles the input tokens with Permit2.
+        liquidity::Kind::UniswapV4(pool) => pool.swap(&input, &output).ok(),
+        liquidity::Kind::ZeroEx(limit_order) => limit_order
+            .to_interaction(&input)
+            .map(|interaction| vec![interaction])
             .ok(),
-        liquidity::Kind::ZeroEx(limit_order) => limit_order.to_interaction(&input).ok(),
+        // CoW AMMs can't be called through an interaction, solvers have to
+        // settle their JIT orders instead.
+        liquidity::Kind::CowAmm(_) => None,
     }
     .ok_or(Error::InvalidInteractionExecution(liquidity.clone()))
 }
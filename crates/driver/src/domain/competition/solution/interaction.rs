@@ -39,10 +39,20 @@ impl Interaction {
                 let address = match &interaction.liquidity.kind {
                     liquidity::Kind::UniswapV2(pool) => pool.router.into(),
                     liquidity::Kind::UniswapV3(pool) => pool.router.into(),
+                    // The Uniswap V4 universal router pulls the sell tokens
+                    // through Permit2.
+                    liquidity::Kind::UniswapV4(pool) => pool.permit2.into(),
                     liquidity::Kind::BalancerV2Stable(pool) => pool.vault.into(),
                     liquidity::Kind::BalancerV2Weighted(pool) => pool.vault.into(),
+                    // Balancer V3 routers pull the sell tokens through Permit2.
+                    liquidity::Kind::BalancerV3(pool) => pool.permit2.into(),
                     liquidity::Kind::Swapr(pool) => pool.base.router.into(),
+                    // Curve pools pull the sell tokens themselves.
+                    liquidity::Kind::Curve(pool) => pool.address.into(),
                     liquidity::Kind::ZeroEx(pool) => pool.zeroex.address().into(),
+                    // CoW AMMs only trade through their JIT orders which don't
+                    // need any allowances.
+                    liquidity::Kind::CowAmm(_) => return Vec::new(),
                 };
                 // As a gas optimization, we always approve the max amount possible. This
                 // minimizes the number of approvals necessary, and therefore
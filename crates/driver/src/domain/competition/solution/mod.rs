@@ -55,6 +55,10 @@ pub struct Solution {
     weth: eth::WethAddress,
     gas: Option<eth::Gas>,
     flashloans: Vec<Flashloan>,
+    /// Fees of the flashloans that the driver added based on the flashloan
+    /// hints of the settled orders. They are paid from the settlement
+    /// contract's buffers, so they reduce the score of the solution.
+    flashloan_fees: Vec<eth::Asset>,
 }
 
 impl Solution {
@@ -128,6 +132,7 @@ impl Solution {
             weth,
             gas,
             flashloans,
+            flashloan_fees: Vec::new(),
         };
 
         // Check that the solution includes clearing prices for all user trades.
@@ -198,6 +203,19 @@ impl Solution {
         &self.pre_interactions
     }
 
+    pub fn post_interactions(&self) -> &[eth::Interaction] {
+        &self.post_interactions
+    }
+
+    pub fn flashloans(&self) -> &[Flashloan] {
+        &self.flashloans
+    }
+
+    /// Fees of the flashloans the driver added based on order hints.
+    pub fn flashloan_fees(&self) -> &[eth::Asset] {
+        &self.flashloan_fees
+    }
+
     /// The solver which generated this solution.
     pub fn solver(&self) -> &Solver {
         &self.solver
@@ -225,6 +243,7 @@ impl Solution {
         &self,
         native_prices: &auction::Prices,
         surplus_capturing_jit_order_owners: &HashSet<eth::Address>,
+        tokens: &auction::Tokens,
     ) -> Result<eth::Ether, error::Scoring> {
         let mut trades = Vec::with_capacity(self.trades.len());
         for trade in self.trades().iter().filter(|trade| {
@@ -246,17 +265,51 @@ impl Solution {
                     .clearing_price(buy.token)
                     .ok_or(error::Scoring::InvalidClearingPrices)?,
             };
-            trades.push(scoring::Trade::new(
-                sell,
-                buy,
-                trade.side(),
-                executed,
-                trade.custom_prices(&uniform_prices)?,
-                trade.protocol_fees(),
-            ))
+            trades.push(
+                scoring::Trade::new(
+                    sell,
+                    buy,
+                    trade.side(),
+                    executed,
+                    trade.custom_prices(&uniform_prices)?,
+                    trade.protocol_fees(),
+                )
+                .with_buy_transfer_fee(tokens.get(buy.token).transfer_fee),
+            )
         }
 
-        scoring::compute_score(&trades, native_prices).map_err(error::Scoring::from)
+        let score = scoring::compute_score(&trades, native_prices)?;
+        net_score(score, &self.flashloan_fees, native_prices, &self.prices)
+    }
+
+    /// Wraps the solution into the flashloans hinted by the app data of its
+    /// orders, so that solvers don't need to build the flashloans themselves.
+    /// Flashloans the solver already took out for a borrower and token are
+    /// kept as they are.
+    pub fn with_hinted_flashloans(mut self, contracts: &blockchain::Contracts) -> Self {
+        let hinted = self
+            .user_trades()
+            .filter_map(|trade| {
+                let order = trade.order();
+                hinted_flashloan(
+                    order.app_data.flashloan()?,
+                    order.uid.owner(),
+                    contracts.flashloan_default_lender(),
+                )
+            })
+            .collect_vec();
+        add_hinted_flashloans(
+            &mut self.flashloans,
+            &mut self.flashloan_fees,
+            hinted,
+            |flashloan| {
+                contracts
+                    .get_flashloan_wrapper(&flashloan.lender)
+                    .map(|wrapper| wrapper.fee(flashloan.amount).ok_or(error::Math::Overflow))
+                    .transpose()
+            },
+        );
+        self
     }
 
     /// Approval interactions necessary for encoding the settlement.
@@ -369,6 +422,7 @@ impl Solution {
                 (None, None) => None,
             },
             flashloans: [self.flashloans.clone(), other.flashloans.clone()].concat(),
+            flashloan_fees: [self.flashloan_fees.clone(), other.flashloan_fees.clone()].concat(),
         })
     }
 
@@ -504,6 +558,7 @@ impl std::fmt::Debug for Solution {
             .field("solver", &self.solver.name())
             .field("gas", &self.gas)
             .field("flashloans", &self.flashloans)
+            .field("flashloan_fees", &self.flashloan_fees)
             .finish()
     }
 }
@@ -535,6 +590,103 @@ fn scaling_factor(first: &Prices, second: &Prices) -> Option<BigRational> {
     }
 }
 
+/// The flashloan hinted by an order's app data. Hints without a lender take
+/// out the flashloan from the default lender and hints without a borrower
+/// borrow for the order owner.
+fn hinted_flashloan(
+    hint: &app_data::Flashloan,
+    owner: eth::Address,
+    default_lender: Option<eth::ContractAddress>,
+) -> Option<Flashloan> {
+    Some(Flashloan {
+        lender: hint.lender.map(eth::ContractAddress).or(default_lender)?,
+        borrower: hint.borrower.map(eth::Address).unwrap_or(owner),
+        token: hint.token.into(),
+        amount: hint.amount.into(),
+    })
+}
+
+/// Adds the hinted flashloans that the solver didn't already take out for the
+/// same borrower and token. The fees of the added flashloans are tracked so
+/// that they can be deducted from the score. Hinted flashloans whose fee can't
+/// be computed are dropped.
+fn add_hinted_flashloans(
+    flashloans: &mut Vec<Flashloan>,
+    fees: &mut Vec<eth::Asset>,
+    hinted: Vec<Flashloan>,
+    fee: impl Fn(&Flashloan) -> Result<Option<eth::TokenAmount>, error::Math>,
+) {
+    for flashloan in hinted {
+        if flashloans.iter().any(|existing| {
+            existing.borrower == flashloan.borrower && existing.token == flashloan.token
+        }) {
+            continue;
+        }
+        let fee = match fee(&flashloan) {
+            Ok(fee) => fee,
+            Err(err) => {
+                tracing::warn!(?flashloan, ?err, "dropping hinted flashloan");
+                continue;
+            }
+        };
+        // Flashloans from lenders without a configured wrapper fail
+        // during encoding, so their fee doesn't matter.
+        if let Some(amount) = fee {
+            fees.push(eth::Asset {
+                token: flashloan.token,
+                amount,
+            });
+        }
+        flashloans.push(flashloan);
+    }
+}
+
+/// Deducts the flashloan fees paid by the settlement contract from the score
+/// of a solution.
+///
+/// Fees in tokens without a native price are converted with the solution's
+/// clearing prices into a token that has one. Fees that can't be converted
+/// either fail the scoring.
+fn net_score(
+    score: eth::Ether,
+    flashloan_fees: &[eth::Asset],
+    native_prices: &auction::Prices,
+    clearing_prices: &Prices,
+) -> Result<eth::Ether, error::Scoring> {
+    let fees = flashloan_fees
+        .iter()
+        .try_fold(eth::U256::zero(), |sum, fee| {
+            let value = flashloan_fee_in_eth(fee, native_prices, clearing_prices)
+                .ok_or(error::Scoring::MissingPrice(fee.token))?;
+            Ok::<_, error::Scoring>(sum.saturating_add(value.0))
+        })?;
+    score
+        .0
+        .checked_sub(fees)
+        .map(eth::Ether)
+        .ok_or(error::Scoring::NegativeScore(score, fees.into()))
+}
+
+fn flashloan_fee_in_eth(
+    fee: &eth::Asset,
+    native_prices: &auction::Prices,
+    clearing_prices: &Prices,
+) -> Option<eth::Ether> {
+    if let Some(price) = native_prices.get(&fee.token) {
+        return Some(price.in_eth(fee.amount));
+    }
+    // Uniform clearing prices value all traded tokens equally, so the fee is
+    // worth `fee * p(fee token) / p(token)` in any other token.
+    let fee_price = clearing_prices.get(&fee.token)?;
+    let (native_price, clearing_price) = clearing_prices
+        .iter()
+        .filter(|(_, price)| !price.is_zero())
+        .sorted_by_key(|(token, _)| **token)
+        .find_map(|(token, price)| Some((native_prices.get(token)?, price)))?;
+    let amount = fee.amount.0.checked_mul(*fee_price)? / *clearing_price;
+    Some(native_price.in_eth(amount.into()))
+}
+
 /// A unique reference to a specific solution which consists of 2 parts:
 /// 1. Globally unique (until driver restarts) ID used to communicate with the
 ///    protocol. Global uniquenes is enforced by the constructors.
@@ -621,8 +773,8 @@ pub mod error {
         FailingInternalization,
         #[error("Gas estimate of {0:?} exceeded the per settlement limit of {1:?}")]
         GasLimitExceeded(eth::Gas, eth::Gas),
-        #[error("insufficient solver account Ether balance, required {0:?}")]
-        SolverAccountInsufficientBalance(eth::Ether),
+        #[error("insufficient Ether balance in solver accounts {0:?}, required {1:?}")]
+        SolverAccountInsufficientBalance(Vec<eth::Address>, eth::Ether),
         #[error("attempted to merge settlements generated by different solvers")]
         DifferentSolvers,
         #[error("encoding error: {0:?}")]
@@ -659,6 +811,8 @@ pub mod error {
         CalculateCustomPrices(#[source] Trade),
         #[error("missing native price for token {0:?}")]
         MissingPrice(TokenAddress),
+        #[error("score {0:?} is lower than the flashloan fees {1:?}")]
+        NegativeScore(eth::Ether, eth::Ether),
     }
 
     impl From<scoring::Error> for Scoring {
@@ -705,4 +859,159 @@ mod tests {
         assert_eq!(fourth.get(), 3);
         assert_eq!(fourth.solutions(), &[222, 111]);
     }
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::H160([byte; 20]).into()
+    }
+
+    fn asset(token_byte: u8, amount: u64) -> eth::Asset {
+        eth::Asset {
+            token: token(token_byte),
+            amount: eth::U256::from(amount).into(),
+        }
+    }
+
+    fn native_price(wei: u64) -> auction::Price {
+        auction::Price::try_new(eth::U256::from(wei).into()).unwrap()
+    }
+
+    #[test]
+    fn hinted_flashloan_defaults() {
+        let owner = eth::Address(eth::H160([1; 20]));
+        let default_lender = eth::ContractAddress(eth::H160([2; 20]));
+        let hint = app_data::Flashloan {
+            lender: None,
+            borrower: None,
+            token: eth::H160([3; 20]),
+            amount: 100.into(),
+        };
+
+        assert_eq!(
+            hinted_flashloan(&hint, owner, Some(default_lender)),
+            Some(Flashloan {
+                lender: default_lender,
+                borrower: owner,
+                token: token(3),
+                amount: eth::U256::from(100).into(),
+            })
+        );
+        // Without a lender the flashloan can't be taken out.
+        assert_eq!(hinted_flashloan(&hint, owner, None), None);
+
+        let hint = app_data::Flashloan {
+            lender: Some(eth::H160([4; 20])),
+            borrower: Some(eth::H160([5; 20])),
+            ..hint
+        };
+        assert_eq!(
+            hinted_flashloan(&hint, owner, Some(default_lender)),
+            Some(Flashloan {
+                lender: eth::ContractAddress(eth::H160([4; 20])),
+                borrower: eth::Address(eth::H160([5; 20])),
+                token: token(3),
+                amount: eth::U256::from(100).into(),
+            })
+        );
+    }
+
+    #[test]
+    fn adds_hinted_flashloans_with_fees() {
+        let flashloan = |lender: u8, borrower: u8, token_byte: u8| Flashloan {
+            lender: eth::ContractAddress(eth::H160([lender; 20])),
+            borrower: eth::Address(eth::H160([borrower; 20])),
+            token: token(token_byte),
+            amount: eth::U256::from(10_000).into(),
+        };
+        let solver_flashloan = flashloan(1, 1, 1);
+        let mut flashloans = vec![solver_flashloan.clone()];
+        let mut fees = Vec::new();
+
+        add_hinted_flashloans(
+            &mut flashloans,
+            &mut fees,
+            vec![
+                // Already taken out by the solver.
+                flashloan(2, 1, 1),
+                flashloan(1, 1, 2),
+                // Hinted twice.
+                flashloan(1, 1, 2),
+                // No wrapper for the lender.
+                flashloan(3, 2, 1),
+                // The fee overflows.
+                flashloan(4, 2, 2),
+            ],
+            |flashloan| match flashloan.lender.0 {
+                eth::H160([3, ..]) => Ok(None),
+                eth::H160([4, ..]) => Err(error::Math::Overflow),
+                _ => Ok(Some(
+                    (flashloan.amount.0 * eth::U256::from(5) / eth::U256::from(10_000)).into(),
+                )),
+            },
+        );
+
+        assert_eq!(
+            flashloans,
+            vec![solver_flashloan, flashloan(1, 1, 2), flashloan(3, 2, 1)]
+        );
+        assert_eq!(fees, vec![asset(2, 5)]);
+    }
+
+    #[test]
+    fn deducts_flashloan_fees_from_score() {
+        let score = eth::Ether(eth::U256::from(1_000));
+        let native_prices = auction::Prices::from([
+            (token(1), native_price(2_000_000_000_000_000_000)),
+            (token(2), native_price(500_000_000_000_000_000)),
+        ]);
+        let clearing_prices = Prices::default();
+
+        assert_eq!(
+            net_score(score, &[], &native_prices, &clearing_prices).unwrap(),
+            score
+        );
+        assert_eq!(
+            net_score(
+                score,
+                &[asset(1, 100), asset(2, 200)],
+                &native_prices,
+                &clearing_prices
+            )
+            .unwrap(),
+            eth::Ether(eth::U256::from(700))
+        );
+    }
+
+    #[test]
+    fn values_flashloan_fees_without_native_price_through_clearing_prices() {
+        let score = eth::Ether(eth::U256::from(1_000));
+        let native_prices =
+            auction::Prices::from([(token(1), native_price(2_000_000_000_000_000_000))]);
+        // One unit of token 2 is worth four units of token 1.
+        let clearing_prices = Prices::from([
+            (token(1), eth::U256::from(10)),
+            (token(2), eth::U256::from(40)),
+        ]);
+
+        assert_eq!(
+            net_score(score, &[asset(2, 50)], &native_prices, &clearing_prices).unwrap(),
+            eth::Ether(eth::U256::from(600))
+        );
+        // Fees that can't be valued at all fail the scoring.
+        assert!(matches!(
+            net_score(score, &[asset(3, 50)], &native_prices, &clearing_prices),
+            Err(error::Scoring::MissingPrice(missing)) if missing == token(3)
+        ));
+    }
+
+    #[test]
+    fn flashloan_fees_exceeding_score_are_an_error() {
+        let score = eth::Ether(eth::U256::from(100));
+        let native_prices =
+            auction::Prices::from([(token(1), native_price(1_000_000_000_000_000_000))]);
+
+        assert!(matches!(
+            net_score(score, &[asset(1, 101)], &native_prices, &Prices::default()),
+            Err(error::Scoring::NegativeScore(..))
+        ));
+    }
 }
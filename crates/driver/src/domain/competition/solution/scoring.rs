@@ -17,6 +17,7 @@ use {
             competition::{
                 PriceLimits,
                 auction,
+                bad_tokens::TransferFee,
                 order::FeePolicy,
                 solution::{
                     error,
@@ -53,6 +54,8 @@ pub struct Trade {
     /// price vector and the necessary adjustements to incorporate fees.
     custom_price: CustomClearingPrices,
     policies: Vec<FeePolicy>,
+    /// The fee the buy token takes when it gets transferred to the receiver.
+    buy_transfer_fee: Option<TransferFee>,
 }
 
 impl Trade {
@@ -71,6 +74,16 @@ impl Trade {
             executed,
             custom_price,
             policies,
+            buy_transfer_fee: None,
+        }
+    }
+
+    /// Accounts for the fee the buy token takes on the transfer to the
+    /// receiver, which reduces the surplus the user actually gets.
+    pub fn with_buy_transfer_fee(self, buy_transfer_fee: Option<TransferFee>) -> Self {
+        Self {
+            buy_transfer_fee,
+            ..self
         }
     }
 
@@ -90,8 +103,7 @@ impl Trade {
             .ok_or(Error::MissingPrice(self.signed_buy.token))?;
 
         let surplus_in_surplus_token = self
-            .user_surplus()?
-            .0
+            .received_user_surplus()?
             .checked_add(self.fees()?.0)
             .ok_or(Error::Math(Math::Overflow))?;
 
@@ -311,6 +323,31 @@ impl Trade {
         Ok(self.surplus_over(limit_price)?)
     }
 
+    /// The user surplus that arrives at the receiver. The buy token's transfer
+    /// fee reduces the amount the receiver gets, which still has to satisfy
+    /// the order's limit price. For sell orders the surplus is reduced by the
+    /// fee itself, for buy orders by what the fee is worth in sell tokens at
+    /// the limit price.
+    fn received_user_surplus(&self) -> Result<eth::U256, Error> {
+        let surplus = self.user_surplus()?.0;
+        let Some(transfer_fee) = self.buy_transfer_fee else {
+            return Ok(surplus);
+        };
+        let bought = self.buy_amount()?.0;
+        let lost = bought
+            .checked_sub(transfer_fee.apply(bought).ok_or(Math::Overflow)?)
+            .ok_or(Math::Negative)?;
+        let lost = match self.side {
+            Side::Sell => lost,
+            Side::Buy => lost
+                .checked_mul(self.signed_sell.amount.0)
+                .ok_or(Math::Overflow)?
+                .checked_ceil_div(&self.signed_buy.amount.0)
+                .ok_or(Math::DivisionByZero)?,
+        };
+        Ok(surplus.checked_sub(lost).ok_or(Math::Negative)?)
+    }
+
     /// Protocol fee as a cut of surplus.
     fn fee(
         &self,
@@ -429,6 +466,7 @@ mod tests {
                 buy: 8050667745u128.into(),
             },
             policies: vec![],
+            buy_transfer_fee: None,
         };
 
         let native_prices: HashMap<_, _> = [
@@ -444,4 +482,99 @@ mod tests {
         let score = trade.score(&native_prices).unwrap();
         assert_eq!(score.0, 911.into());
     }
+
+    fn transfer_fee_trade(side: Side, custom_price: CustomClearingPrices) -> Trade {
+        Trade::new(
+            eth::Asset {
+                token: addr!("0000000000000000000000000000000000000001").into(),
+                amount: 1000u128.into(),
+            },
+            eth::Asset {
+                token: addr!("0000000000000000000000000000000000000002").into(),
+                amount: 1000u128.into(),
+            },
+            side,
+            order::TargetAmount(1000u128.into()),
+            custom_price,
+            vec![],
+        )
+    }
+
+    fn transfer_fee_native_prices() -> auction::Prices {
+        [(
+            addr!("0000000000000000000000000000000000000002").into(),
+            Price(eth::Ether(1000000000000000000u128.into())),
+        )]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn buy_transfer_fee_reduces_sell_order_score() {
+        let trade = transfer_fee_trade(
+            Side::Sell,
+            CustomClearingPrices {
+                sell: 1200u128.into(),
+                buy: 1000u128.into(),
+            },
+        );
+        let native_prices = transfer_fee_native_prices();
+
+        assert_eq!(trade.score(&native_prices).unwrap().0, 200.into());
+        // 1200 bought tokens, 1080 of which arrive for a limit of 1000.
+        let trade = trade.with_buy_transfer_fee(TransferFee::from_bps(1_000));
+        assert_eq!(trade.score(&native_prices).unwrap().0, 80.into());
+    }
+
+    #[test]
+    fn buy_transfer_fee_reduces_buy_order_score() {
+        let trade = transfer_fee_trade(
+            Side::Buy,
+            CustomClearingPrices {
+                sell: 1000u128.into(),
+                buy: 800u128.into(),
+            },
+        );
+        let native_prices = transfer_fee_native_prices();
+
+        assert_eq!(trade.score(&native_prices).unwrap().0, 200.into());
+        // 900 of the 1000 bought tokens arrive, which are worth 900 sell tokens
+        // at the limit price, 100 more than the 800 sold ones.
+        let trade = trade.with_buy_transfer_fee(TransferFee::from_bps(1_000));
+        assert_eq!(trade.score(&native_prices).unwrap().0, 100.into());
+    }
+
+    #[test]
+    fn buy_transfer_fee_below_limit_price_is_rejected() {
+        let native_prices = transfer_fee_native_prices();
+
+        // 1100 bought tokens, only 990 of which arrive for a limit of 1000.
+        let trade = transfer_fee_trade(
+            Side::Sell,
+            CustomClearingPrices {
+                sell: 1100u128.into(),
+                buy: 1000u128.into(),
+            },
+        )
+        .with_buy_transfer_fee(TransferFee::from_bps(1_000));
+        assert!(matches!(
+            trade.score(&native_prices),
+            Err(Error::Math(Math::Negative))
+        ));
+
+        // 900 of the 1000 bought tokens arrive, which are worth less than the
+        // 950 sold ones at the limit price.
+        let trade = transfer_fee_trade(
+            Side::Buy,
+            CustomClearingPrices {
+                sell: 1000u128.into(),
+                buy: 950u128.into(),
+            },
+        )
+        .with_buy_transfer_fee(TransferFee::from_bps(1_000));
+        assert!(matches!(
+            trade.score(&native_prices),
+            Err(Error::Math(Math::Negative))
+        ));
+    }
 }
@@ -29,7 +29,8 @@ use {
 ///
 /// - Simulation: the settlement has been simulated without reverting, including
 ///   the case where no interactions were internalized. Additionally the solver
-///   account is known to have sufficient Ether to execute the transaction.
+///   account submitting the settlement is known to have sufficient Ether to
+///   execute the transaction.
 /// - Internalization: internalized interactions only use trusted tokens.
 ///
 /// Publishing a settlement which violates these rules would result in slashing
@@ -44,6 +45,9 @@ pub struct Settlement {
     /// The gas parameters used by the settlement.
     pub gas: Gas,
     solution: Solution,
+    /// The solver account the settlement was simulated with and gets
+    /// submitted from.
+    submitter: eth::Address,
 }
 
 #[derive(Debug, Clone)]
@@ -57,6 +61,20 @@ struct SettlementTx {
 }
 
 impl SettlementTx {
+    fn with_sender(self, from: eth::Address) -> Self {
+        Self {
+            internalized: eth::Tx {
+                from,
+                ..self.internalized
+            },
+            uninternalized: eth::Tx {
+                from,
+                ..self.uninternalized
+            },
+            ..self
+        }
+    }
+
     fn with_access_list(self, access_list: eth::AccessList) -> Self {
         Self {
             internalized: self.internalized.set_access_list(access_list.clone()),
@@ -123,6 +141,79 @@ impl Settlement {
         eth: &Ethereum,
         simulator: &Simulator,
     ) -> Result<Self, Error> {
+        // Pick the solver account submitting the settlement. The accounts are
+        // tried in order of preference and each one is simulated as the sender,
+        // until one is found that can pay for the settlement. Accounts which
+        // can't even cover the requirement of a previous simulation are skipped.
+        let candidates = solution.solver().accounts().candidates();
+        let balances = try_join_all(candidates.iter().map(|account| eth.balance(*account))).await?;
+        let mut required = eth::Ether(0.into());
+        let mut simulated = None;
+        for (submitter, balance) in candidates.iter().zip(balances) {
+            if balance < required {
+                continue;
+            }
+            let transaction = transaction.clone().with_sender(*submitter);
+            let (partial_access_list, access_list, gas) =
+                Self::simulate_from(&solution, &transaction, eth, simulator).await?;
+            if balance >= gas.required_balance() {
+                simulated = Some((
+                    *submitter,
+                    transaction,
+                    partial_access_list,
+                    access_list,
+                    gas,
+                ));
+                break;
+            }
+            required = gas.required_balance();
+        }
+        let Some((submitter, transaction, partial_access_list, access_list, gas)) = simulated
+        else {
+            return Err(Error::SolverAccountInsufficientBalance(
+                candidates, required,
+            ));
+        };
+
+        // Is at least one interaction internalized?
+        if solution
+            .interactions()
+            .iter()
+            .any(|interaction| interaction.internalize())
+        {
+            // Some rules which are enforced by the settlement contract for non-internalized
+            // interactions are not enforced for internalized interactions (in order to save
+            // gas). However, publishing a settlement with interactions that violate
+            // these rules constitutes a punishable offense for the solver, even if
+            // the interactions are internalized. To ensure that this doesn't happen, check
+            // that the settlement simulates even when internalizations are disabled.
+            Self::simulate(
+                transaction.uninternalized.clone(),
+                &partial_access_list,
+                eth,
+                simulator,
+            )
+            .await?;
+        }
+
+        Ok(Self {
+            auction_id,
+            solution,
+            transaction: transaction.with_access_list(access_list),
+            gas,
+            submitter,
+        })
+    }
+
+    /// Simulates the settlement sent from the account set on the transaction
+    /// and returns the partial access list, the full access list and the gas
+    /// parameters.
+    async fn simulate_from(
+        solution: &Solution,
+        transaction: &SettlementTx,
+        eth: &Ethereum,
+        simulator: &Simulator,
+    ) -> Result<(eth::AccessList, eth::AccessList, Gas), Error> {
         // The settlement contract will fail if the receiver is a smart contract.
         // Because of this, if the receiver is a smart contract and we try to
         // estimate the access list, the access list estimation will also fail.
@@ -139,7 +230,7 @@ impl Settlement {
                 return Ok(Default::default());
             }
             let tx = eth::Tx {
-                from: solution.solver().address(),
+                from: transaction.internalized.from,
                 to: trade.order().receiver(),
                 value: 1.into(),
                 input: Default::default(),
@@ -162,41 +253,7 @@ impl Settlement {
         .await?;
         let price = eth.gas_price(None).await?;
         let gas = Gas::new(gas, eth.block_gas_limit(), price)?;
-
-        // Ensure that the solver has sufficient balance for the settlement to be mined.
-        if eth.balance(solution.solver().address()).await? < gas.required_balance() {
-            return Err(Error::SolverAccountInsufficientBalance(
-                gas.required_balance(),
-            ));
-        }
-
-        // Is at least one interaction internalized?
-        if solution
-            .interactions()
-            .iter()
-            .any(|interaction| interaction.internalize())
-        {
-            // Some rules which are enforced by the settlement contract for non-internalized
-            // interactions are not enforced for internalized interactions (in order to save
-            // gas). However, publishing a settlement with interactions that violate
-            // these rules constitutes a punishable offense for the solver, even if
-            // the interactions are internalized. To ensure that this doesn't happen, check
-            // that the settlement simulates even when internalizations are disabled.
-            Self::simulate(
-                transaction.uninternalized.clone(),
-                &partial_access_list,
-                eth,
-                simulator,
-            )
-            .await?;
-        }
-
-        Ok(Self {
-            auction_id,
-            solution,
-            transaction: transaction.with_access_list(access_list),
-            gas,
-        })
+        Ok((partial_access_list, access_list, gas))
     }
 
     /// Simulate executing this settlement on the blockchain. This process
@@ -231,6 +288,11 @@ impl Settlement {
         }
     }
 
+    /// The solver account submitting this settlement.
+    pub fn submitter(&self) -> eth::Address {
+        self.submitter
+    }
+
     /// Whether the settlement contains interactions that could possibly revert
     /// on chain
     pub fn may_revert(&self) -> bool {
@@ -242,9 +304,10 @@ impl Settlement {
         &self,
         prices: &auction::Prices,
         surplus_capturing_jit_order_owners: &HashSet<eth::Address>,
+        tokens: &auction::Tokens,
     ) -> Result<eth::Ether, solution::error::Scoring> {
         self.solution
-            .scoring(prices, surplus_capturing_jit_order_owners)
+            .scoring(prices, surplus_capturing_jit_order_owners, tokens)
     }
 
     /// The solution encoded in this settlement.
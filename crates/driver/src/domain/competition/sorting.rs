@@ -4,10 +4,14 @@ use {
             competition::{auction::Tokens, order},
             eth,
         },
-        util,
+        util::{self, conv::u256::U256Ext},
     },
     chrono::{Duration, Utc},
-    std::sync::Arc,
+    num::{ToPrimitive, Zero},
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    },
 };
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
@@ -68,6 +72,155 @@ impl SortingStrategy for OwnQuotes {
     }
 }
 
+/// Orders are sorted by how often proposed solutions trading the same token
+/// pair got settled before, with the most likely pairs coming first. See
+/// [`FillHistory`].
+pub struct FillProbability {
+    pub history: FillHistory,
+}
+impl SortingStrategy for FillProbability {
+    fn key(&self, order: &order::Order, _tokens: &Tokens, _solver: &eth::H160) -> SortingKey {
+        SortingKey::BigRational(rational(
+            self.history.probability(order.sell.token, order.buy.token),
+        ))
+    }
+}
+
+/// Orders are sorted by the surplus they would generate if they were executed
+/// at the native prices of the auction, with the largest surplus coming first.
+pub struct EstimatedSurplus;
+impl SortingStrategy for EstimatedSurplus {
+    fn key(&self, order: &order::Order, tokens: &Tokens, _solver: &eth::H160) -> SortingKey {
+        SortingKey::BigRational(estimated_surplus(order, tokens))
+    }
+}
+
+/// Orders are sorted by a weighted sum of several keys, with the largest sum
+/// coming first.
+pub struct Weighted {
+    pub keys: Vec<(WeightedKey, f64)>,
+    pub history: FillHistory,
+}
+
+pub enum WeightedKey {
+    /// See [`ExternalPrice`].
+    ExternalPrice,
+    /// The estimated surplus in units of the native token. See
+    /// [`EstimatedSurplus`].
+    EstimatedSurplus,
+    /// Between 0 and 1. See [`FillProbability`].
+    FillProbability,
+    /// 1 if the solver provided the winning quote for the order, 0 otherwise.
+    OwnQuotes,
+    /// The age of the order in minutes. Use a negative weight to prefer
+    /// recent orders.
+    OrderAge,
+}
+
+impl SortingStrategy for Weighted {
+    fn key(&self, order: &order::Order, tokens: &Tokens, solver: &eth::H160) -> SortingKey {
+        let value = |key: &WeightedKey| match key {
+            WeightedKey::ExternalPrice => order.likelihood(tokens).to_f64().unwrap_or_default(),
+            WeightedKey::EstimatedSurplus => (estimated_surplus(order, tokens)
+                / eth::U256::exp10(18).to_big_rational())
+            .to_f64()
+            .unwrap_or_default(),
+            WeightedKey::FillProbability => {
+                self.history.probability(order.sell.token, order.buy.token)
+            }
+            WeightedKey::OwnQuotes => {
+                match order.quote.as_ref().is_some_and(|q| &q.solver.0 == solver) {
+                    true => 1.,
+                    false => 0.,
+                }
+            }
+            WeightedKey::OrderAge => {
+                Utc::now()
+                    .timestamp()
+                    .saturating_sub(order.created.0.into()) as f64
+                    / 60.
+            }
+        };
+        SortingKey::BigRational(rational(
+            self.keys
+                .iter()
+                .map(|(key, weight)| weight * value(key))
+                .sum(),
+        ))
+    }
+}
+
+/// The difference between the native value of the sell and buy amounts of the
+/// order. Zero if any of the prices is missing.
+fn estimated_surplus(order: &order::Order, tokens: &Tokens) -> num::BigRational {
+    let available = order.available();
+    match (
+        tokens.get(available.sell.token).price,
+        tokens.get(available.buy.token).price,
+    ) {
+        (Some(sell_price), Some(buy_price)) => {
+            sell_price.in_eth(available.sell.amount).0.to_big_rational()
+                - buy_price.in_eth(available.buy.amount).0.to_big_rational()
+        }
+        _ => num::BigRational::zero(),
+    }
+}
+
+fn rational(value: f64) -> num::BigRational {
+    num::BigRational::from_float(value).unwrap_or_else(num::BigRational::zero)
+}
+
+/// How often the solutions proposed to the protocol by the solvers of the
+/// driver got settled, by the token pairs they traded. Solutions that lose
+/// the competition count as not filled just like the ones failing onchain,
+/// while the solutions of other drivers are never seen. Shared between all
+/// solvers of the driver.
+#[derive(Debug, Clone, Default)]
+pub struct FillHistory(Arc<Mutex<HashMap<(eth::TokenAddress, eth::TokenAddress), Fills>>>);
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Fills {
+    proposed: u64,
+    settled: u64,
+}
+
+impl FillHistory {
+    /// Records that a solution trading the given token pairs was proposed to
+    /// the protocol.
+    pub fn proposed(
+        &self,
+        pairs: impl IntoIterator<Item = (eth::TokenAddress, eth::TokenAddress)>,
+    ) {
+        let mut fills = self.0.lock().unwrap();
+        for pair in pairs {
+            fills.entry(pair).or_default().proposed += 1;
+        }
+    }
+
+    /// Records that a proposed solution trading the given token pairs was
+    /// settled onchain.
+    pub fn settled(&self, pairs: impl IntoIterator<Item = (eth::TokenAddress, eth::TokenAddress)>) {
+        let mut fills = self.0.lock().unwrap();
+        for pair in pairs {
+            fills.entry(pair).or_default().settled += 1;
+        }
+    }
+
+    /// The share of proposed solutions trading the pair that got settled.
+    /// Smoothed so that pairs without any history have a probability of 1/2.
+    pub fn probability(&self, sell: eth::TokenAddress, buy: eth::TokenAddress) -> f64 {
+        let fills = self
+            .0
+            .lock()
+            .unwrap()
+            .get(&(sell, buy))
+            .copied()
+            .unwrap_or_default();
+        let probability = (fills.settled + 1) as f64 / (fills.proposed + 2) as f64;
+        probability.min(1.)
+    }
+}
+
 /// Sort orders based on the provided comparators. Reverse ordering is used to
 /// ensure that the most important element comes first.
 pub fn sort_orders(
@@ -85,3 +238,168 @@ pub fn sort_orders(
         )
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::domain::competition::auction::{Price, Token},
+    };
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::H160::repeat_byte(byte).into()
+    }
+
+    /// Tokens where a whole unit of token `byte` is worth `byte` ETH.
+    fn tokens(bytes: &[u8]) -> Tokens {
+        bytes
+            .iter()
+            .map(|byte| Token {
+                decimals: Some(18),
+                symbol: None,
+                address: token(*byte),
+                price: Some(
+                    Price::try_new(eth::Ether(eth::U256::exp10(18) * eth::U256::from(*byte)))
+                        .unwrap(),
+                ),
+                available_balance: Default::default(),
+                trusted: false,
+                transfer_fee: None,
+            })
+            .collect()
+    }
+
+    /// A sell order of whole units of the tokens created at `created`.
+    fn order(id: u8, sell: (u8, u64), buy: (u8, u64), created: u32) -> order::Order {
+        let asset = |(token, units): (u8, u64)| eth::Asset {
+            token: self::token(token),
+            amount: (eth::U256::exp10(18) * eth::U256::from(units)).into(),
+        };
+        order::Order {
+            uid: order::Uid(util::Bytes([id; order::UID_LEN])),
+            receiver: None,
+            created: util::Timestamp(created),
+            valid_to: util::Timestamp(u32::MAX),
+            buy: asset(buy),
+            sell: asset(sell),
+            side: order::Side::Sell,
+            kind: order::Kind::Limit,
+            app_data: Default::default(),
+            partial: order::Partial::No,
+            pre_interactions: Default::default(),
+            post_interactions: Default::default(),
+            sell_token_balance: order::SellTokenBalance::Erc20,
+            buy_token_balance: order::BuyTokenBalance::Erc20,
+            signature: order::Signature {
+                scheme: order::signature::Scheme::Eip712,
+                data: Default::default(),
+                signer: Default::default(),
+            },
+            protocol_fees: Default::default(),
+            quote: Default::default(),
+        }
+    }
+
+    fn sorted(
+        mut orders: Vec<order::Order>,
+        tokens: &Tokens,
+        strategy: impl SortingStrategy + 'static,
+    ) -> Vec<order::Uid> {
+        let strategy: Arc<dyn SortingStrategy> = Arc::new(strategy);
+        sort_orders(&mut orders, tokens, &eth::H160::zero(), &[strategy]);
+        orders.into_iter().map(|order| order.uid).collect()
+    }
+
+    fn now() -> u32 {
+        Utc::now().timestamp().try_into().unwrap()
+    }
+
+    #[test]
+    fn fill_probability_is_smoothed() {
+        let history = FillHistory::default();
+        assert_eq!(history.probability(token(1), token(2)), 0.5);
+
+        history.proposed([(token(1), token(2))]);
+        history.settled([(token(1), token(2))]);
+        history.proposed([(token(1), token(2)), (token(2), token(1))]);
+        history.proposed([(token(1), token(2))]);
+        history.settled([(token(1), token(2))]);
+        assert_eq!(history.probability(token(1), token(2)), 0.6);
+        assert_eq!(history.probability(token(2), token(1)), 1. / 3.);
+    }
+
+    #[test]
+    fn sorts_by_estimated_surplus() {
+        let tokens = tokens(&[1, 2]);
+        // Sells 4 ETH worth of tokens for 2 ETH worth of tokens.
+        let large = order(0, (2, 2), (1, 2), now());
+        // Sells 2 ETH worth of tokens for 1 ETH worth of tokens.
+        let small = order(1, (1, 2), (1, 1), now());
+        // Sells 4 ETH worth of tokens for 6 ETH worth of tokens.
+        let negative = order(2, (1, 4), (2, 3), now());
+        // The surplus is unknown without a price for the sell token.
+        let unknown = order(3, (3, 100), (1, 1), now());
+
+        assert_eq!(
+            sorted(
+                vec![
+                    negative.clone(),
+                    unknown.clone(),
+                    small.clone(),
+                    large.clone()
+                ],
+                &tokens,
+                EstimatedSurplus,
+            ),
+            [large.uid, small.uid, unknown.uid, negative.uid],
+        );
+    }
+
+    #[test]
+    fn sorts_by_weighted_keys() {
+        let tokens = tokens(&[1, 2]);
+        let history = FillHistory::default();
+        for _ in 0..8 {
+            history.proposed([(token(1), token(2))]);
+            history.settled([(token(1), token(2))]);
+        }
+        // Surplus of 1 ETH, fill probability of 0.9 and 10 minutes old.
+        let likely = order(0, (1, 3), (2, 1), now() - 600);
+        // Surplus of 3 ETH, fill probability of 0.5 and just created.
+        let surplus = order(1, (2, 2), (1, 1), now());
+
+        let weighted = |keys: Vec<(WeightedKey, f64)>| Weighted {
+            keys,
+            history: history.clone(),
+        };
+        assert_eq!(
+            sorted(
+                vec![likely.clone(), surplus.clone()],
+                &tokens,
+                weighted(vec![(WeightedKey::EstimatedSurplus, 1.)]),
+            ),
+            [surplus.uid, likely.uid],
+        );
+        // 0.9 * 10 - 1 > 0.5 * 10 - 3
+        assert_eq!(
+            sorted(
+                vec![surplus.clone(), likely.clone()],
+                &tokens,
+                weighted(vec![
+                    (WeightedKey::FillProbability, 10.),
+                    (WeightedKey::EstimatedSurplus, -1.),
+                ]),
+            ),
+            [likely.uid, surplus.uid],
+        );
+        // Negative weights prefer recent orders.
+        assert_eq!(
+            sorted(
+                vec![likely.clone(), surplus.clone()],
+                &tokens,
+                weighted(vec![(WeightedKey::OrderAge, -1.)]),
+            ),
+            [surplus.uid, likely.uid],
+        );
+    }
+}
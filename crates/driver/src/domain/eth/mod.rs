@@ -422,7 +422,7 @@ impl From<CodeDigest> for [u8; 32] {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Flashloan {
     pub lender: ContractAddress,
     pub borrower: Address,
@@ -0,0 +1,77 @@
+use {
+    super::v2::{Fee, stable::AmplificationParameter},
+    crate::{
+        boundary,
+        domain::{eth, liquidity},
+    },
+};
+
+/// Liquidity data tied to a Balancer V3 pool [^1].
+///
+/// Unlike V2, V3 pools are swapped through a router which pulls the input
+/// tokens from the caller with Permit2. Token rates (for yield bearing
+/// tokens) and swap fees are applied by the vault, so both weighted and
+/// stable pools share the same representation.
+///
+/// [^1]: <https://docs.balancer.fi/concepts/vault/>
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub batch_router: eth::ContractAddress,
+    pub permit2: eth::ContractAddress,
+    pub address: eth::ContractAddress,
+    /// The pool tokens, in vault registration order.
+    pub reserves: Vec<Reserve>,
+    pub fee: Fee,
+    /// The hooks contract called on swaps, if any.
+    pub hooks: Option<eth::ContractAddress>,
+    pub kind: Kind,
+}
+
+/// A Balancer V3 pool token reserve.
+#[derive(Clone, Copy, Debug)]
+pub struct Reserve {
+    pub asset: eth::Asset,
+    /// The factor `10^(18 - decimals)` scaling raw amounts to 18 decimals.
+    pub scaling_factor: eth::U256,
+    /// The token rate, represented as `rate * 1e18`.
+    pub rate: eth::U256,
+}
+
+#[derive(Clone, Debug)]
+pub enum Kind {
+    /// A weighted pool with its normalized token weights, represented as
+    /// `weight * 1e18` and in token order.
+    Weighted(Vec<eth::U256>),
+    Stable(AmplificationParameter),
+}
+
+impl Pool {
+    /// Returns an iterator over the pool tokens.
+    pub fn tokens(&self) -> impl Iterator<Item = eth::TokenAddress> + '_ {
+        self.reserves.iter().map(|reserve| reserve.asset.token)
+    }
+
+    /// Encodes a pool swap as interactions. Returns `Err` if the swap
+    /// parameters are invalid for the pool, specifically if the input and
+    /// output tokens do not belong to the pool.
+    ///
+    /// The first interaction grants the batch router a Permit2 allowance for
+    /// the input tokens and the second executes the swap. The router always
+    /// sends the bought tokens to the caller.
+    pub fn swap(
+        &self,
+        input: &liquidity::MaxInput,
+        output: &liquidity::ExactOutput,
+    ) -> Result<Vec<eth::Interaction>, liquidity::InvalidSwap> {
+        let tokens_match = input.0.token != output.0.token
+            && self.tokens().any(|token| token == input.0.token)
+            && self.tokens().any(|token| token == output.0.token);
+        if !tokens_match {
+            return Err(liquidity::InvalidSwap);
+        }
+
+        Ok(boundary::liquidity::balancer::v3::to_interactions(
+            self, input, output,
+        ))
+    }
+}
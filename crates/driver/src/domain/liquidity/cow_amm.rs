@@ -0,0 +1,33 @@
+use crate::{
+    domain::{competition::order::app_data::AppDataHash, eth},
+    util::Bytes,
+};
+
+/// Rough estimate of the gas needed to settle the JIT order of a CoW AMM,
+/// including the pool's EIP-1271 signature verification.
+pub const GAS_PER_ORDER: u64 = 120_000;
+
+/// A CoW AMM that is not eligible for surplus in the auction. Instead of
+/// getting called through an interaction, it offers the order its helper
+/// contract computed to rebalance the pool to the auction's reference prices.
+/// The order is signed by the pool, so solvers can only settle it as-is as a
+/// JIT order.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub address: eth::Address,
+    pub order: Order,
+}
+
+/// The fill-or-kill sell order offered by a CoW AMM.
+#[derive(Clone, Debug)]
+pub struct Order {
+    pub sell: eth::Asset,
+    pub buy: eth::Asset,
+    pub receiver: Option<eth::Address>,
+    pub valid_to: u32,
+    pub app_data: AppDataHash,
+    /// The EIP-1271 signature of the order without the pool address.
+    pub signature: Bytes<Vec<u8>>,
+    pub pre_interactions: Vec<eth::Interaction>,
+    pub post_interactions: Vec<eth::Interaction>,
+}
@@ -0,0 +1,83 @@
+use crate::{
+    boundary,
+    domain::{eth, liquidity},
+};
+
+/// Liquidity data tied to a Curve pool.
+///
+/// Curve StableSwap pools [^1] are used for plain and lending pools of pegged
+/// assets, while CryptoSwap pools [^2] concentrate liquidity around a moving
+/// internal price for pairs of volatile assets.
+///
+/// [^1]: <https://classic.curve.fi/whitepaper>
+/// [^2]: <https://classic.curve.fi/files/crypto-pools-paper.pdf>
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub address: eth::ContractAddress,
+    /// The coins of the pool, in coin index order.
+    pub tokens: Vec<eth::TokenAddress>,
+    pub state: State,
+}
+
+#[derive(Clone, Debug)]
+pub enum State {
+    Stable(StableState),
+    Crypto(CryptoState),
+}
+
+/// The state of a plain or lending StableSwap pool.
+///
+/// Fees are relative to `1e10`, as in the pool contracts.
+#[derive(Clone, Debug)]
+pub struct StableState {
+    pub balances: Vec<eth::U256>,
+    /// The rates normalizing the balances to 18 decimals, scaled by `1e18`.
+    pub rates: Vec<eth::U256>,
+    pub amplification: eth::U256,
+    pub fee: eth::U256,
+    pub offpeg_fee_multiplier: Option<eth::U256>,
+}
+
+/// The state of a two coin crypto or tricrypto CryptoSwap pool.
+///
+/// Fees are relative to `1e10`, as in the pool contracts.
+#[derive(Clone, Debug)]
+pub struct CryptoState {
+    pub balances: Vec<eth::U256>,
+    /// The multipliers normalizing the balances to 18 decimals.
+    pub precisions: Vec<eth::U256>,
+    /// The prices of all coins but the first relative to the first, scaled
+    /// by `1e18`.
+    pub price_scale: Vec<eth::U256>,
+    pub amplification: eth::U256,
+    pub gamma: eth::U256,
+    pub d: eth::U256,
+    pub mid_fee: eth::U256,
+    pub out_fee: eth::U256,
+    pub fee_gamma: eth::U256,
+}
+
+impl Pool {
+    /// Encodes a pool swap as an interaction. Returns `Err` if the swap
+    /// parameters are invalid for the pool, specifically if the input and
+    /// output tokens do not belong to the pool.
+    ///
+    /// Curve pools always send the bought tokens to the caller, so there is
+    /// no receiver to specify.
+    pub fn swap(
+        &self,
+        input: &liquidity::MaxInput,
+        output: &liquidity::ExactOutput,
+    ) -> Result<eth::Interaction, liquidity::InvalidSwap> {
+        let tokens_match = input.0.token != output.0.token
+            && self.tokens.contains(&input.0.token)
+            && self.tokens.contains(&output.0.token);
+        if !tokens_match {
+            return Err(liquidity::InvalidSwap);
+        }
+
+        Ok(boundary::liquidity::curve::to_interaction(
+            self, input, output,
+        ))
+    }
+}
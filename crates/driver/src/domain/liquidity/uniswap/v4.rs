@@ -0,0 +1,70 @@
+use {
+    super::v3::{Fee, Liquidity, LiquidityNet, SqrtPrice, Tick},
+    crate::{
+        boundary,
+        domain::{
+            eth,
+            liquidity::{self, InvalidSwap},
+        },
+    },
+    derive_more::Debug,
+    std::collections::BTreeMap,
+};
+
+/// A Uniswap V4 concentrated liquidity pool [^1].
+///
+/// V4 pools share the V3 concentrated liquidity math, but all pools live in
+/// the singleton pool manager and are identified by their key. Swaps go
+/// through the universal router, which pulls the input tokens from the caller
+/// with Permit2.
+///
+/// [^1]: <https://docs.uniswap.org/contracts/v4/overview>
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub router: eth::ContractAddress,
+    pub permit2: eth::ContractAddress,
+    pub id: eth::H256,
+    pub key: PoolKey,
+    pub sqrt_price: SqrtPrice,
+    pub liquidity: Liquidity,
+    pub tick: Tick,
+    #[debug(ignore)]
+    pub liquidity_net: BTreeMap<Tick, LiquidityNet>,
+    /// The current swap fee, which for pools with dynamic fees can differ
+    /// from the fee of the pool key.
+    pub fee: Fee,
+}
+
+/// The key identifying a pool within the pool manager.
+#[derive(Clone, Copy, Debug)]
+pub struct PoolKey {
+    pub tokens: liquidity::TokenPair,
+    /// The LP fee in hundredths of a bip, or the dynamic fee flag.
+    pub fee: u32,
+    pub tick_spacing: i32,
+    /// The hooks contract of the pool, only allow-listed hooks are indexed.
+    pub hooks: eth::ContractAddress,
+}
+
+impl Pool {
+    /// Encodes a pool swap as interactions. Returns `Err` if the swap
+    /// parameters are invalid for the pool, specifically if the input and
+    /// output tokens don't correspond to the pool's token pair.
+    pub fn swap(
+        &self,
+        input: &liquidity::MaxInput,
+        output: &liquidity::ExactOutput,
+    ) -> Result<Vec<eth::Interaction>, InvalidSwap> {
+        let (token0, token1) = self.key.tokens.get();
+        let tokens_match = (input.0.token == token0 && output.0.token == token1)
+            || (input.0.token == token1 && output.0.token == token0);
+
+        if !tokens_match {
+            return Err(InvalidSwap);
+        }
+
+        Ok(boundary::liquidity::uniswap::v4::to_interactions(
+            self, input, output,
+        ))
+    }
+}
@@ -9,7 +9,7 @@ use {
             competition::solution::Settlement,
             eth::{TxId, TxStatus},
         },
-        infra::{self, Ethereum, observe, solver::Solver},
+        infra::{self, Ethereum, observe, solver::accounts},
     },
     anyhow::Context,
     ethrpc::block_stream::into_stream,
@@ -42,10 +42,10 @@ impl Mempools {
         }
     }
 
-    /// Publish a settlement to the mempools.
+    /// Publish a settlement to the mempools from the leased solver account.
     pub async fn execute(
         &self,
-        solver: &Solver,
+        account: &accounts::Lease,
         settlement: &Settlement,
         submission_deadline: BlockNo,
     ) -> Result<eth::TxId, Error> {
@@ -53,7 +53,7 @@ impl Mempools {
             select_ok(self.mempools.iter().cloned().map(|mempool| {
                 async move {
                     let result = self
-                        .submit(&mempool, solver, settlement, submission_deadline)
+                        .submit(&mempool, account, settlement, submission_deadline)
                         .instrument(tracing::info_span!("mempool", kind = mempool.to_string()))
                         .await;
                     observe::mempool_executed(&mempool, settlement, &result);
@@ -87,7 +87,7 @@ impl Mempools {
     async fn submit(
         &self,
         mempool: &infra::mempool::Mempool,
-        solver: &Solver,
+        account: &accounts::Lease,
         settlement: &Settlement,
         submission_deadline: BlockNo,
     ) -> Result<SubmissionSuccess, Error> {
@@ -100,7 +100,12 @@ impl Mempools {
             return Err(Error::Disabled);
         }
 
-        let tx = settlement.transaction(settlement::Internalization::Enable);
+        let tx = &eth::Tx {
+            from: account.address(),
+            ..settlement
+                .transaction(settlement::Internalization::Enable)
+                .clone()
+        };
 
         // Instantiate block stream and skip the current block before we submit the
         // settlement. This way we only run iterations in blocks that can potentially
@@ -130,7 +135,7 @@ impl Mempools {
             }
         }
 
-        let hash = mempool.submit(tx.clone(), settlement.gas, solver).await?;
+        let hash = mempool.submit(tx.clone(), settlement.gas, account).await?;
         let submitted_at_block = self.ethereum.current_block().borrow().number;
         tracing::debug!(?hash, current_block = ?submitted_at_block, "submitted tx to the mempool");
 
@@ -165,7 +170,7 @@ impl Mempools {
                         // Check if the current block reached the submission deadline block number
                         if block.number >= submission_deadline {
                             let cancellation_tx_hash = self
-                                .cancel(mempool, settlement.gas.price, solver, blocks_elapsed)
+                                .cancel(mempool, settlement.gas.price, account, blocks_elapsed)
                                 .await
                                 .context("cancellation tx due to deadline failed")?;
                             tracing::info!(
@@ -185,7 +190,7 @@ impl Mempools {
                         if let Err(err) = self.ethereum.estimate_gas(tx).await {
                             if err.is_revert() {
                                 let cancellation_tx_hash = self
-                                    .cancel(mempool, settlement.gas.price, solver, blocks_elapsed)
+                                    .cancel(mempool, settlement.gas.price, account, blocks_elapsed)
                                     .await
                                     .context("cancellation tx due to revert failed")?;
                                 tracing::info!(
@@ -238,12 +243,12 @@ impl Mempools {
         &self,
         mempool: &infra::mempool::Mempool,
         pending: eth::GasPrice,
-        solver: &Solver,
+        account: &accounts::Lease,
         blocks_elapsed: u64,
     ) -> Result<TxId, Error> {
         let cancellation = eth::Tx {
-            from: solver.address(),
-            to: solver.address(),
+            from: account.address(),
+            to: account.address(),
             value: 0.into(),
             input: Default::default(),
             access_list: Default::default(),
@@ -263,7 +268,7 @@ impl Mempools {
             "Cancelling transaction with adjusted gas price"
         );
 
-        mempool.submit(cancellation, gas, solver).await
+        mempool.submit(cancellation, gas, account).await
     }
 }
 
@@ -8,9 +8,9 @@ use {
         infra::{
             self,
             Ethereum,
-            Simulator,
             config::file::OrderPriorityStrategy,
             liquidity,
+            simulator::Simulators,
             solver::{Solver, Timeouts},
             tokens,
         },
@@ -29,7 +29,7 @@ const REQUEST_BODY_LIMIT: usize = 10 * 1024 * 1024;
 pub struct Api {
     pub solvers: Vec<Solver>,
     pub liquidity: liquidity::Fetcher,
-    pub simulator: Simulator,
+    pub simulators: Simulators,
     pub eth: Ethereum,
     pub mempools: Mempools,
     pub addr: SocketAddr,
@@ -56,10 +56,12 @@ impl Api {
         );
 
         let tokens = tokens::Fetcher::new(&self.eth);
+        let fill_history = domain::competition::sorting::FillHistory::default();
         let pre_processor = domain::competition::AuctionProcessor::new(
             &self.eth,
             order_priority_strategies,
             app_data_retriever,
+            fill_history.clone(),
         );
 
         // Add the metrics and healthz endpoints.
@@ -87,6 +89,10 @@ impl Api {
                 bad_tokens.with_simulation_detector(self.bad_token_detector.clone());
             }
 
+            if bad_token_config.enable_fee_on_transfer {
+                bad_tokens.with_fee_on_transfer_support();
+            }
+
             if bad_token_config.enable_metrics_strategy {
                 bad_tokens.with_metrics_detector(bad_tokens::metrics::Detector::new(
                     bad_token_config.metrics_strategy_failure_ratio,
@@ -97,6 +103,31 @@ impl Api {
                 ));
             }
 
+            let mut simulator = self
+                .simulators
+                .get(solver.simulator().unwrap_or(self.simulators.default_kind()))
+                .expect("solver simulators are validated when loading the config");
+            if let Some(comparison) = solver.simulation_comparison() {
+                let solver = solver.clone();
+                simulator.compare_with(
+                    self.simulators
+                        .get(comparison.with)
+                        .expect("solver simulators are validated when loading the config"),
+                    comparison,
+                    move |disagreement| {
+                        infra::observe::simulator_disagreement(solver.name(), disagreement);
+                        infra::notify::simulator_disagreement(&solver, disagreement);
+                    },
+                );
+            }
+
+            let pre_processor = match solver.order_priority_strategies() {
+                Some(strategies) => {
+                    pre_processor.with_order_priority_strategies(strategies.to_vec())
+                }
+                None => pre_processor.clone(),
+            };
+
             let router = router.with_state(State(Arc::new(Inner {
                 eth: self.eth.clone(),
                 solver: solver.clone(),
@@ -104,13 +135,14 @@ impl Api {
                     solver,
                     self.eth.clone(),
                     self.liquidity.clone(),
-                    self.simulator.clone(),
+                    simulator,
                     self.mempools.clone(),
                     Arc::new(bad_tokens),
+                    fill_history.clone(),
                 ),
                 liquidity: self.liquidity.clone(),
                 tokens: tokens.clone(),
-                pre_processor: pre_processor.clone(),
+                pre_processor,
             })));
             let path = format!("/{name}");
             infra::observe::mounting_solver(&name, &path);
@@ -1,7 +1,6 @@
 use {
     crate::{
         domain::{competition, competition::order, eth},
-        infra::Solver,
         util::serialize,
     },
     serde::Serialize,
@@ -10,10 +9,10 @@ use {
 };
 
 impl SolveResponse {
-    pub fn new(solved: Option<competition::Solved>, solver: &Solver) -> Self {
+    pub fn new(solved: Option<competition::Solved>) -> Self {
         let solutions = solved
             .into_iter()
-            .map(|solved| Solution::new(solved.id.get(), solved, solver))
+            .map(|solved| Solution::new(solved.id.get(), solved))
             .collect();
         Self { solutions }
     }
@@ -27,11 +26,11 @@ pub struct SolveResponse {
 }
 
 impl Solution {
-    pub fn new(solution_id: u64, solved: competition::Solved, solver: &Solver) -> Self {
+    pub fn new(solution_id: u64, solved: competition::Solved) -> Self {
         Self {
             solution_id,
             score: solved.score.0,
-            submission_address: solver.address().into(),
+            submission_address: solved.submission_address.into(),
             orders: solved
                 .trades
                 .into_iter()
@@ -41,10 +41,7 @@ async fn route(
         // have capacity again.
         competition.ensure_settle_queue_capacity()?;
         observe::solved(state.solver().name(), &result);
-        Ok(axum::Json(dto::SolveResponse::new(
-            result?,
-            &competition.solver,
-        )))
+        Ok(axum::Json(dto::SolveResponse::new(result?)))
     };
 
     handle_request
@@ -3,6 +3,7 @@ use {
         boundary,
         domain::eth,
         infra::{blockchain::Ethereum, config},
+        util::conv::u256::U256Ext,
     },
     chain::Chain,
     contracts::FlashLoanRouter,
@@ -42,6 +43,20 @@ pub struct FlashloanWrapperData {
     pub fee_in_bps: eth::U256,
 }
 
+impl FlashloanWrapperData {
+    /// The fee charged by the lender for borrowing the specified amount.
+    /// Returns `None` if the fee overflows.
+    pub fn fee(&self, amount: eth::TokenAmount) -> Option<eth::TokenAmount> {
+        Some(
+            amount
+                .0
+                .checked_mul(self.fee_in_bps)?
+                .ceil_div(&10_000.into())
+                .into(),
+        )
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Addresses {
     pub settlement: Option<eth::ContractAddress>,
@@ -15,6 +15,7 @@ use {
         GasPriceEstimating,
         nativegasestimator::{NativeGasEstimator, Params},
     },
+    shared::gas_price_estimation::fee_history::{self, FeeHistoryGasEstimator},
     std::{sync::Arc, time::Duration},
 };
 
@@ -55,6 +56,18 @@ impl GasPriceEstimator {
                 .map_err(Error::GasPrice)?,
             ),
             GasEstimatorType::Web3 => Arc::new(web3.clone()),
+            GasEstimatorType::FeeHistory {
+                blocks,
+                min_percentile,
+                max_percentile,
+            } => Arc::new(FeeHistoryGasEstimator::new(
+                web3.clone(),
+                fee_history::Params {
+                    blocks: *blocks,
+                    min_percentile: *min_percentile,
+                    max_percentile: *max_percentile,
+                },
+            )),
         };
         let additional_tip = mempools
             .iter()
@@ -237,6 +237,16 @@ impl Ethereum {
             .map_err(Into::into)
     }
 
+    /// Returns the nonce of the next transaction of the specified account,
+    /// taking pending transactions into account.
+    pub async fn nonce(&self, address: eth::Address) -> Result<eth::U256, Error> {
+        self.web3
+            .eth()
+            .transaction_count(address.into(), Some(web3::types::BlockNumber::Pending))
+            .await
+            .map_err(Into::into)
+    }
+
     /// Returns a [`token::Erc20`] for the specified address.
     pub fn erc20(&self, address: eth::TokenAddress) -> token::Erc20 {
         token::Erc20::new(self, address)
@@ -48,23 +48,52 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
         chain,
         "The configured chain ID does not match the connected Ethereum node"
     );
+    for solver in &config.solvers {
+        let kinds = solver.simulator.iter().chain(
+            solver
+                .simulation_comparison
+                .iter()
+                .map(|comparison| &comparison.with),
+        );
+        for kind in kinds {
+            let configured = match kind {
+                file::SimulatorKind::Ethereum | file::SimulatorKind::Revm => true,
+                file::SimulatorKind::Tenderly => config.tenderly.is_some(),
+                file::SimulatorKind::Enso => config.enso.is_some(),
+            };
+            assert!(
+                configured,
+                "solver {:?} uses the {kind:?} simulator which is not configured",
+                solver.name
+            );
+        }
+        if let Some(comparison) = &solver.simulation_comparison {
+            // Mirrors `Simulators::default_kind` for solvers that don't pick
+            // a simulator.
+            let simulator = solver
+                .simulator
+                .unwrap_or(match (&config.tenderly, &config.enso) {
+                    (Some(_), _) => file::SimulatorKind::Tenderly,
+                    (None, Some(_)) => file::SimulatorKind::Enso,
+                    (None, None) => file::SimulatorKind::Ethereum,
+                });
+            assert!(
+                comparison.with.to_domain() != simulator.to_domain(),
+                "solver {:?} compares the {simulator:?} simulator with itself",
+                solver.name
+            );
+        }
+    }
     infra::Config {
         solvers: join_all(config.solvers.into_iter().map(|solver_config| async move {
-            let account = match solver_config.account {
-                file::Account::PrivateKey(private_key) => ethcontract::Account::Offline(
-                    ethcontract::PrivateKey::from_raw(private_key.0).unwrap(),
-                    None,
-                ),
-                file::Account::Kms(key_id) => {
-                    let config = ethcontract::aws_config::load_from_env().await;
-                    let account =
-                        ethcontract::transaction::kms::Account::new((&config).into(), &key_id.0)
-                            .await
-                            .unwrap_or_else(|_| panic!("Unable to load KMS account {:?}", key_id));
-                    ethcontract::Account::Kms(account, None)
-                }
-                file::Account::Address(address) => ethcontract::Account::Local(address, None),
-            };
+            let account = load_account(solver_config.account).await;
+            let submission_accounts = join_all(
+                solver_config
+                    .submission_accounts
+                    .into_iter()
+                    .map(load_account),
+            )
+            .await;
             solver::Config {
                 endpoint: solver_config.endpoint,
                 name: solver_config.name.into(),
@@ -78,6 +107,7 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     solver::Liquidity::Fetch
                 },
                 account,
+                submission_accounts,
                 timeouts: solver::Timeouts {
                     http_delay: chrono::Duration::from_std(solver_config.timeouts.http_time_buffer)
                         .unwrap(),
@@ -98,6 +128,8 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     false => SolutionMerging::Forbidden,
                 },
                 s3: solver_config.s3.map(Into::into),
+                archive_directory: solver_config.archive_directory,
+                archive_competition: solver_config.archive_competition,
                 solver_native_token: solver_config.manage_native_token.to_domain(),
                 quote_tx_origin: solver_config.quote_tx_origin.map(eth::Address),
                 response_size_limit_max_bytes: solver_config.response_size_limit_max_bytes,
@@ -122,6 +154,9 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     enable_metrics_strategy: solver_config
                         .bad_token_detection
                         .enable_metrics_strategy,
+                    enable_fee_on_transfer: solver_config
+                        .bad_token_detection
+                        .enable_fee_on_transfer,
                     metrics_strategy_failure_ratio: solver_config
                         .bad_token_detection
                         .metrics_strategy_failure_ratio,
@@ -137,6 +172,20 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                 },
                 settle_queue_size: solver_config.settle_queue_size,
                 flashloans_enabled: config.flashloans_enabled,
+                wrap_flashloans: config.flashloans_enabled && solver_config.wrap_flashloans,
+                simulator: solver_config.simulator.map(|kind| kind.to_domain()),
+                simulation_comparison: solver_config
+                    .simulation_comparison
+                    .as_ref()
+                    .map(|comparison| comparison.to_domain()),
+                order_filters: solver_config.order_filters.to_domain(),
+                order_priority_strategies: solver_config.order_priority_strategies,
+                executor: solver_config.executor.map(|executor| {
+                    solver::Executor::try_new(eth::Address(executor.address), &executor.function)
+                        .unwrap_or_else(|err| {
+                            panic!("invalid executor function {:?}: {err:?}", executor.function)
+                        })
+                }),
             }
         }))
         .await,
@@ -241,6 +290,49 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     },
                 })
                 .collect(),
+            uniswap_v4: config
+                .liquidity
+                .uniswap_v4
+                .iter()
+                .cloned()
+                .map(|config| match config {
+                    file::UniswapV4Config::Preset {
+                        preset,
+                        allowed_hooks,
+                        reinit_interval,
+                    } => liquidity::config::UniswapV4 {
+                        allowed_hooks: allowed_hooks
+                            .into_iter()
+                            .map(eth::ContractAddress::from)
+                            .collect(),
+                        reinit_interval,
+                        ..match preset {
+                            file::UniswapV4Preset::UniswapV4 => {
+                                liquidity::config::UniswapV4::uniswap_v4(chain)
+                            }
+                        }
+                        .expect("no Uniswap V4 preset for current network")
+                    },
+                    file::UniswapV4Config::Manual {
+                        pool_manager,
+                        deployment_block,
+                        router,
+                        permit2,
+                        allowed_hooks,
+                        reinit_interval,
+                    } => liquidity::config::UniswapV4 {
+                        pool_manager: pool_manager.into(),
+                        deployment_block,
+                        router: router.into(),
+                        permit2: permit2.into(),
+                        allowed_hooks: allowed_hooks
+                            .into_iter()
+                            .map(eth::ContractAddress::from)
+                            .collect(),
+                        reinit_interval,
+                    },
+                })
+                .collect(),
             balancer_v2: config
                 .liquidity
                 .balancer_v2
@@ -257,13 +349,17 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                         reinit_interval,
                         ..match preset {
                             file::BalancerV2Preset::BalancerV2 => {
-                                liquidity::config::BalancerV2::balancer_v2(&graph_url, chain)
+                                liquidity::config::BalancerV2::balancer_v2(
+                                    graph_url.as_ref(),
+                                    chain,
+                                )
                             }
                         }
                         .expect("no Balancer V2 preset for current network")
                     },
                     file::BalancerV2Config::Manual {
                         vault,
+                        deployment_block,
                         weighted,
                         weighted_v3plus,
                         stable,
@@ -292,11 +388,101 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                             .map(eth::ContractAddress::from)
                             .collect(),
                         pool_deny_list: pool_deny_list.clone(),
+                        deployment_block: match (&graph_url, deployment_block) {
+                            (_, Some(block)) => block,
+                            // Only needed for discovering pools on-chain.
+                            (Some(_), None) => 0,
+                            (None, None) => panic!(
+                                "Balancer V2 vault {vault:?} needs a `deployment-block` to \
+                                 discover pools on-chain without a `graph-url`"
+                            ),
+                        },
                         graph_url,
                         reinit_interval,
                     },
                 })
                 .collect(),
+            balancer_v3: config
+                .liquidity
+                .balancer_v3
+                .iter()
+                .cloned()
+                .map(|config| match config {
+                    file::BalancerV3Config::Preset {
+                        preset,
+                        pool_deny_list,
+                        reinit_interval,
+                    } => liquidity::config::BalancerV3 {
+                        pool_deny_list: pool_deny_list
+                            .into_iter()
+                            .map(eth::ContractAddress::from)
+                            .collect(),
+                        reinit_interval,
+                        ..match preset {
+                            file::BalancerV3Preset::BalancerV3 => {
+                                liquidity::config::BalancerV3::balancer_v3(chain)
+                            }
+                        }
+                        .expect("no Balancer V3 preset for current network")
+                    },
+                    file::BalancerV3Config::Manual {
+                        vault,
+                        batch_router,
+                        permit2,
+                        weighted,
+                        stable,
+                        pool_deny_list,
+                        reinit_interval,
+                    } => liquidity::config::BalancerV3 {
+                        vault: vault.into(),
+                        batch_router: batch_router.into(),
+                        permit2: permit2.into(),
+                        weighted: weighted
+                            .into_iter()
+                            .map(eth::ContractAddress::from)
+                            .collect(),
+                        stable: stable.into_iter().map(eth::ContractAddress::from).collect(),
+                        pool_deny_list: pool_deny_list
+                            .into_iter()
+                            .map(eth::ContractAddress::from)
+                            .collect(),
+                        reinit_interval,
+                    },
+                })
+                .collect(),
+            curve: config
+                .liquidity
+                .curve
+                .iter()
+                .cloned()
+                .map(|config| match config {
+                    file::CurveConfig::Preset {
+                        preset,
+                        reinit_interval,
+                    } => liquidity::config::Curve {
+                        reinit_interval,
+                        ..match preset {
+                            file::CurvePreset::Curve => liquidity::config::Curve::curve(chain),
+                        }
+                        .expect("no Curve preset for current network")
+                    },
+                    file::CurveConfig::Manual {
+                        stable_registries,
+                        crypto_registries,
+                        reinit_interval,
+                    } => liquidity::config::Curve {
+                        stable_registries: stable_registries
+                            .into_iter()
+                            .map(eth::ContractAddress::from)
+                            .collect(),
+                        crypto_registries: crypto_registries
+                            .into_iter()
+                            .map(eth::ContractAddress::from)
+                            .collect(),
+                        reinit_interval,
+                    },
+                })
+                .collect(),
             zeroex: config
                 .liquidity
                 .zeroex
@@ -305,6 +491,21 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     api_key: config.api_key,
                     http_timeout: config.http_timeout,
                 }),
+            snapshots: config
+                .liquidity
+                .snapshots
+                .map(|config| liquidity::config::Snapshots {
+                    storage: match config.storage {
+                        file::LiquiditySnapshotStorage::File { path } => {
+                            liquidity::config::SnapshotStorage::File(path)
+                        }
+                        file::LiquiditySnapshotStorage::Postgres { database_url } => {
+                            liquidity::config::SnapshotStorage::Postgres(database_url)
+                        }
+                    },
+                    interval: config.interval,
+                    max_age: config.max_age,
+                }),
         },
         mempools: config
             .submission
@@ -354,23 +555,19 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                 },
             })
             .collect(),
-        simulator: match (config.tenderly, config.enso) {
-            (Some(config), None) => {
-                Some(simulator::Config::Tenderly(simulator::tenderly::Config {
-                    url: config.url,
-                    api_key: config.api_key,
-                    user: config.user,
-                    project: config.project,
-                    save: config.save,
-                    save_if_fails: config.save_if_fails,
-                }))
-            }
-            (None, Some(config)) => Some(simulator::Config::Enso(simulator::enso::Config {
+        simulator: simulator::Config {
+            tenderly: config.tenderly.map(|config| simulator::tenderly::Config {
+                url: config.url,
+                api_key: config.api_key,
+                user: config.user,
+                project: config.project,
+                save: config.save,
+                save_if_fails: config.save_if_fails,
+            }),
+            enso: config.enso.map(|config| simulator::enso::Config {
                 url: config.url,
                 network_block_interval: config.network_block_interval,
-            })),
-            (None, None) => None,
-            (Some(_), Some(_)) => panic!("Cannot configure both Tenderly and Enso"),
+            }),
         },
         contracts: blockchain::contracts::Addresses {
             settlement: config.contracts.gp_v2_settlement.map(Into::into),
@@ -411,6 +608,29 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
         order_priority_strategies: config.order_priority_strategies,
         archive_node_url: config.archive_node_url,
         simulation_bad_token_max_age: config.simulation_bad_token_max_age,
+        simulation_bad_token_max_transfer_fee: config
+            .simulation_bad_token_max_transfer_fee_bps
+            .map(|bps| {
+                bad_tokens::TransferFee::from_bps(bps)
+                    .expect("max transfer fee has to be below 10000 bps")
+            }),
         app_data_fetching: config.app_data_fetching,
     }
 }
+
+async fn load_account(account: file::Account) -> ethcontract::Account {
+    match account {
+        file::Account::PrivateKey(private_key) => ethcontract::Account::Offline(
+            ethcontract::PrivateKey::from_raw(private_key.0).unwrap(),
+            None,
+        ),
+        file::Account::Kms(key_id) => {
+            let config = ethcontract::aws_config::load_from_env().await;
+            let account = ethcontract::transaction::kms::Account::new((&config).into(), &key_id.0)
+                .await
+                .unwrap_or_else(|_| panic!("Unable to load KMS account {:?}", key_id));
+            ethcontract::Account::Kms(account, None)
+        }
+        file::Account::Address(address) => ethcontract::Account::Local(address, None),
+    }
+}
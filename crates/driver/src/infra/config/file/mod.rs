@@ -1,11 +1,16 @@
 pub use load::load;
 use {
-    crate::{domain::eth, infra, util::serialize},
+    crate::{
+        domain::{competition, eth},
+        infra,
+        util::serialize,
+    },
     reqwest::Url,
     serde::{Deserialize, Deserializer, Serialize},
     serde_with::serde_as,
+    shared::gas_price_estimation::fee_history,
     solver::solver::Arn,
-    std::{collections::HashMap, time::Duration},
+    std::{collections::HashMap, path::PathBuf, time::Duration},
 };
 
 mod load;
@@ -43,7 +48,8 @@ struct Config {
     #[serde(default)]
     contracts: ContractsConfig,
 
-    /// Use Tenderly for transaction simulation.
+    /// Use Tenderly for transaction simulation. If both Tenderly and Enso
+    /// are configured, Tenderly is used unless a solver picks otherwise.
     tenderly: Option<TenderlyConfig>,
 
     /// Use Enso for transaction simulation.
@@ -74,6 +80,13 @@ struct Config {
     )]
     simulation_bad_token_max_age: Duration,
 
+    /// Tokens that take a fee of at most this many basis points on transfers
+    /// get classified as fee-on-transfer tokens by the simulation based
+    /// logic instead of being unsupported. Solvers still have to opt into
+    /// receiving such tokens.
+    #[serde(default)]
+    simulation_bad_token_max_transfer_fee_bps: Option<u32>,
+
     /// Configuration for the app-data fetching.
     #[serde(default, flatten)]
     app_data_fetching: AppDataFetching,
@@ -184,6 +197,116 @@ impl ManageNativeToken {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum SimulatorKind {
+    Ethereum,
+    Tenderly,
+    Enso,
+    Revm,
+}
+
+impl SimulatorKind {
+    pub fn to_domain(self) -> infra::simulator::Kind {
+        match self {
+            Self::Ethereum => infra::simulator::Kind::Ethereum,
+            Self::Tenderly => infra::simulator::Kind::Tenderly,
+            Self::Enso => infra::simulator::Kind::Enso,
+            Self::Revm => infra::simulator::Kind::Revm,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct SimulationComparison {
+    /// The simulator to compare with.
+    with: SimulatorKind,
+
+    /// Relative difference of the simulated gas above which the simulators
+    /// are considered to disagree.
+    #[serde(default = "default_max_simulation_gas_deviation")]
+    max_gas_deviation: f64,
+
+    /// Use the stricter result of both simulators instead of the result of
+    /// the solver's simulator.
+    #[serde(default)]
+    prefer_stricter: bool,
+}
+
+impl SimulationComparison {
+    pub fn to_domain(&self) -> infra::simulator::Comparison {
+        infra::simulator::Comparison {
+            with: self.with.to_domain(),
+            max_gas_deviation: self.max_gas_deviation,
+            prefer_stricter: self.prefer_stricter,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Executor {
+    /// The address of the executor contract. It has to be an allow-listed
+    /// solver.
+    address: eth::H160,
+
+    /// The signature of the function wrapping the settlement call, like
+    /// `execute(address,bytes)`. The `address` parameter is optional and
+    /// receives the called contract, the `bytes` parameter receives the
+    /// calldata.
+    function: String,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct OrderFilters {
+    /// Remove orders selling less than this amount worth of the native token,
+    /// denominated in wei.
+    #[serde_as(as = "Option<serialize::U256>")]
+    min_notional: Option<eth::U256>,
+
+    /// Only send orders trading exclusively these tokens.
+    allowed_tokens: Option<Vec<eth::H160>>,
+
+    /// Remove orders trading any of these tokens.
+    #[serde(default)]
+    denied_tokens: Vec<eth::H160>,
+
+    /// Send at most this many of the highest priority orders.
+    max_orders: Option<usize>,
+
+    /// Remove orders with pre- or post-interactions.
+    #[serde(default)]
+    exclude_hooks: bool,
+
+    /// Remove orders with EIP-1271 signatures.
+    #[serde(default)]
+    exclude_eip1271: bool,
+}
+
+impl OrderFilters {
+    pub fn to_domain(&self) -> competition::auction::OrderFilters {
+        let token = |address: &eth::H160| eth::TokenAddress(eth::ContractAddress(*address));
+        competition::auction::OrderFilters {
+            min_notional: self.min_notional.map(eth::Ether),
+            allowed_tokens: self
+                .allowed_tokens
+                .as_ref()
+                .map(|tokens| tokens.iter().map(token).collect()),
+            denied_tokens: self.denied_tokens.iter().map(token).collect(),
+            max_orders: self.max_orders,
+            exclude_hooks: self.exclude_hooks,
+            exclude_eip1271: self.exclude_eip1271,
+        }
+    }
+}
+
+fn default_max_simulation_gas_deviation() -> f64 {
+    0.1
+}
+
 fn default_additional_tip_percentage() -> f64 {
     0.05
 }
@@ -240,6 +363,14 @@ struct SolverConfig {
     /// The account which should be used to sign settlements for this solver.
     account: Account,
 
+    /// Additional accounts for submitting settlements. Each account submits
+    /// at most one settlement at a time, so consecutive wins of the solver
+    /// can be settled in parallel. All accounts need to be allow-listed as
+    /// solvers in the settlement contract and configured as additional
+    /// submission addresses of the driver in the autopilot.
+    #[serde(default)]
+    submission_accounts: Vec<Account>,
+
     /// Timeout configuration for the solver.
     #[serde(default, flatten)]
     timeouts: Timeouts,
@@ -260,6 +391,40 @@ struct SolverConfig {
     #[serde(default)]
     merge_solutions: bool,
 
+    /// If enabled the driver wraps solutions into the flashloans hinted by
+    /// the app data of their orders, so the solver doesn't have to. Only
+    /// takes effect if flashloans are enabled.
+    #[serde(default)]
+    wrap_flashloans: bool,
+
+    /// Which simulator to use for this solver's settlements. Defaults to
+    /// Tenderly or Enso if configured and the Ethereum node otherwise.
+    #[serde(default)]
+    simulator: Option<SimulatorKind>,
+
+    /// Simulate the settlements with a second simulator and report when the
+    /// results disagree. Only the gas estimates and whether the settlements
+    /// revert are compared. Access lists are always generated by the solver's
+    /// simulator alone, since simulators legitimately differ in which
+    /// addresses they include.
+    #[serde(default)]
+    simulation_comparison: Option<SimulationComparison>,
+
+    /// Restrictions on the orders sent to the solver, for solvers that can't
+    /// handle some kinds of orders.
+    #[serde(default)]
+    order_filters: OrderFilters,
+
+    /// Order prioritization strategies for this solver, replacing the ones
+    /// configured for the whole driver.
+    #[serde(default, rename = "order-priority")]
+    order_priority_strategies: Option<Vec<OrderPriorityStrategy>>,
+
+    /// Route the settlements through this contract instead of calling the
+    /// settlement contract directly.
+    #[serde(default)]
+    executor: Option<Executor>,
+
     /// Maximum number of orders allowed to be contained in a merged solution.
     #[serde(default = "default_number_of_orders_per_merged_solution")]
     max_orders_per_merged_solution: usize,
@@ -269,6 +434,17 @@ struct SolverConfig {
     #[serde(default)]
     s3: Option<S3>,
 
+    /// Archive auctions to this local directory instead of S3. Meant for
+    /// development setups without AWS credentials. The `s3` prefix is still
+    /// applied if configured.
+    #[serde(default)]
+    archive_directory: Option<PathBuf>,
+
+    /// Whether to archive what happened to the solutions of each auction, in
+    /// addition to the auction itself. Requires `s3` or `archive-directory`.
+    #[serde(default)]
+    archive_competition: bool,
+
     /// Whether the native token is wrapped or not when sent to the solvers
     #[serde(default)]
     manage_native_token: ManageNativeToken,
@@ -462,13 +638,71 @@ struct LiquidityConfig {
     #[serde(default)]
     uniswap_v3: Vec<UniswapV3Config>,
 
+    /// Liquidity provided by a Uniswap V4 compatible pool manager.
+    #[serde(default)]
+    uniswap_v4: Vec<UniswapV4Config>,
+
     /// Liquidity provided by a Balancer V2 compatible contract.
     #[serde(default)]
     balancer_v2: Vec<BalancerV2Config>,
 
+    /// Liquidity provided by a Balancer V3 compatible contract.
+    #[serde(default)]
+    balancer_v3: Vec<BalancerV3Config>,
+
+    /// Liquidity provided by Curve pools.
+    #[serde(default)]
+    curve: Vec<CurveConfig>,
+
     /// Liquidity provided by 0x API.
     #[serde(default)]
     zeroex: Option<ZeroExConfig>,
+
+    /// Persist snapshots of the indexed liquidity state (Uniswap V3 pools and
+    /// ticks, registered Balancer V2 pools) and resume from them on restarts.
+    #[serde(default)]
+    snapshots: Option<LiquiditySnapshotsConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct LiquiditySnapshotsConfig {
+    /// Where the snapshots are stored.
+    storage: LiquiditySnapshotStorage,
+
+    /// How often a new snapshot is written.
+    #[serde(with = "humantime_serde", default = "default_snapshot_interval")]
+    interval: Duration,
+
+    /// Snapshots older than this number of blocks are ignored and liquidity
+    /// is indexed from scratch instead.
+    #[serde(default = "default_snapshot_max_age")]
+    max_age: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum LiquiditySnapshotStorage {
+    #[serde(rename_all = "kebab-case")]
+    File {
+        /// The directory the snapshot files are written to.
+        path: PathBuf,
+    },
+
+    #[serde(rename_all = "kebab-case")]
+    Postgres {
+        /// The URL of the Postgres database with the `liquidity_snapshots`
+        /// table.
+        database_url: Url,
+    },
+}
+
+fn default_snapshot_interval() -> Duration {
+    Duration::from_secs(10 * 60)
+}
+
+fn default_snapshot_max_age() -> u64 {
+    50_000
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -582,6 +816,56 @@ mod uniswap_v3 {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum UniswapV4Config {
+    #[serde(rename_all = "kebab-case")]
+    Preset {
+        preset: UniswapV4Preset,
+
+        /// Hooks contracts that are considered safe to trade through. Pools
+        /// with other hooks are ignored.
+        #[serde(default)]
+        allowed_hooks: Vec<eth::H160>,
+
+        /// How often the liquidity source should be reinitialized.
+        #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+        reinit_interval: Option<Duration>,
+    },
+
+    #[serde(rename_all = "kebab-case")]
+    Manual {
+        /// Address of the Uniswap V4 compatible pool manager contract.
+        pool_manager: eth::H160,
+
+        /// The block the pool manager was deployed in, from which its events
+        /// are indexed.
+        deployment_block: u64,
+
+        /// Address of the universal router contract used for swapping.
+        router: eth::H160,
+
+        /// Address of the Permit2 contract the universal router pulls tokens
+        /// with.
+        permit2: eth::H160,
+
+        /// Hooks contracts that are considered safe to trade through. Pools
+        /// with other hooks are ignored.
+        #[serde(default)]
+        allowed_hooks: Vec<eth::H160>,
+
+        /// How often the liquidity source should be reinitialized.
+        #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+        reinit_interval: Option<Duration>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+enum UniswapV4Preset {
+    UniswapV4,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(untagged, deny_unknown_fields)]
 enum BalancerV2Config {
@@ -593,8 +877,9 @@ enum BalancerV2Config {
         #[serde(default)]
         pool_deny_list: Vec<eth::H256>,
 
-        /// The URL used to connect to balancer v2 subgraph client.
-        graph_url: Url,
+        /// The URL used to connect to balancer v2 subgraph client. When
+        /// omitted, pools are discovered on-chain from the pool factory events.
+        graph_url: Option<Url>,
 
         /// How often the liquidity source should be reinitialized to get
         /// access to new pools.
@@ -607,6 +892,10 @@ enum BalancerV2Config {
         /// Addresses of Balancer V2 compatible vault contract.
         vault: eth::H160,
 
+        /// The block from which pool factory events are scanned. Required
+        /// when no `graph-url` is configured.
+        deployment_block: Option<u64>,
+
         /// The weighted pool factory contract addresses.
         #[serde(default)]
         weighted: Vec<eth::H160>,
@@ -634,8 +923,9 @@ enum BalancerV2Config {
         #[serde(default)]
         pool_deny_list: Vec<eth::H256>,
 
-        /// The URL used to connect to balancer v2 subgraph client.
-        graph_url: Url,
+        /// The URL used to connect to balancer v2 subgraph client. When
+        /// omitted, pools are discovered on-chain from the pool factory events.
+        graph_url: Option<Url>,
 
         /// How often the liquidity source should be reinitialized to get
         /// access to new pools.
@@ -650,6 +940,96 @@ enum BalancerV2Preset {
     BalancerV2,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum BalancerV3Config {
+    #[serde(rename_all = "kebab-case")]
+    Preset {
+        preset: BalancerV3Preset,
+
+        /// Deny listed Balancer V3 pools.
+        #[serde(default)]
+        pool_deny_list: Vec<eth::H160>,
+
+        /// How often the liquidity source should be reinitialized to get
+        /// access to new pools.
+        #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+        reinit_interval: Option<Duration>,
+    },
+
+    #[serde(rename_all = "kebab-case")]
+    Manual {
+        /// Address of the Balancer V3 compatible vault contract.
+        vault: eth::H160,
+
+        /// Address of the batch router contract used for swapping.
+        batch_router: eth::H160,
+
+        /// Address of the Permit2 contract the batch router pulls tokens
+        /// with.
+        permit2: eth::H160,
+
+        /// The weighted pool factory contract addresses.
+        #[serde(default)]
+        weighted: Vec<eth::H160>,
+
+        /// The stable pool factory contract addresses.
+        #[serde(default)]
+        stable: Vec<eth::H160>,
+
+        /// Deny listed Balancer V3 pools.
+        #[serde(default)]
+        pool_deny_list: Vec<eth::H160>,
+
+        /// How often the liquidity source should be reinitialized to get
+        /// access to new pools.
+        #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+        reinit_interval: Option<Duration>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+enum BalancerV3Preset {
+    BalancerV3,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum CurveConfig {
+    #[serde(rename_all = "kebab-case")]
+    Preset {
+        preset: CurvePreset,
+
+        /// How often the liquidity source should be reinitialized to get
+        /// access to new pools.
+        #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+        reinit_interval: Option<Duration>,
+    },
+
+    #[serde(rename_all = "kebab-case")]
+    Manual {
+        /// The registry contract addresses listing StableSwap pools.
+        #[serde(default)]
+        stable_registries: Vec<eth::H160>,
+
+        /// The registry contract addresses listing CryptoSwap pools.
+        #[serde(default)]
+        crypto_registries: Vec<eth::H160>,
+
+        /// How often the liquidity source should be reinitialized to get
+        /// access to new pools.
+        #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+        reinit_interval: Option<Duration>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+enum CurvePreset {
+    Curve,
+}
+
 fn default_reinit_interval() -> Option<Duration> {
     Some(Duration::from_secs(12 * 60 * 60))
 }
@@ -700,6 +1080,22 @@ pub enum GasEstimatorType {
         max_block_percentile: f64,
     },
     Web3,
+    /// Pays a percentile of the priority fees paid in recent blocks depending
+    /// on how urgently the settlement has to be included.
+    #[serde(rename_all = "kebab-case")]
+    FeeHistory {
+        // Number of recent blocks to sample priority fees from
+        #[serde(default = "default_fee_history_blocks")]
+        blocks: u64,
+        // Percentile of the sampled priority fees paid when there is plenty of
+        // time left until the submission deadline
+        #[serde(default = "default_fee_history_min_percentile")]
+        min_percentile: f64,
+        // Percentile of the sampled priority fees paid when the settlement has
+        // to be included in the next block
+        #[serde(default = "default_fee_history_max_percentile")]
+        max_percentile: f64,
+    },
 }
 
 impl Default for GasEstimatorType {
@@ -724,8 +1120,20 @@ fn default_max_block_percentile() -> f64 {
     60.
 }
 
+fn default_fee_history_blocks() -> u64 {
+    fee_history::Params::default().blocks
+}
+
+fn default_fee_history_min_percentile() -> f64 {
+    fee_history::Params::default().min_percentile
+}
+
+fn default_fee_history_max_percentile() -> f64 {
+    fee_history::Params::default().max_percentile
+}
+
 /// Defines various strategies to prioritize orders.
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case", tag = "strategy")]
 pub enum OrderPriorityStrategy {
     /// Strategy to prioritize orders based on external price.
@@ -751,6 +1159,40 @@ pub enum OrderPriorityStrategy {
         #[serde(with = "humantime_serde", default = "default_max_order_age")]
         max_order_age: Option<Duration>,
     },
+    /// Strategy to prioritize orders based on how often solutions trading the
+    /// same token pair that were proposed by the solvers of this driver got
+    /// settled.
+    FillProbability,
+    /// Strategy to prioritize orders based on the surplus they would generate
+    /// at the native prices of the auction.
+    EstimatedSurplus,
+    /// Strategy to prioritize orders based on a weighted sum of several keys.
+    Weighted { keys: Vec<WeightedPriorityKey> },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WeightedPriorityKey {
+    pub key: PriorityKey,
+    pub weight: f64,
+}
+
+/// Keys that can be combined by the weighted order prioritization strategy.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PriorityKey {
+    /// The likelihood of the order being filled based on external prices.
+    ExternalPrice,
+    /// The estimated surplus of the order in units of the native token.
+    EstimatedSurplus,
+    /// The share of proposed solutions trading the same token pair that got
+    /// settled.
+    FillProbability,
+    /// 1 if the solver provided the winning quote for the order, 0 otherwise.
+    OwnQuotes,
+    /// The age of the order in minutes. Use a negative weight to prioritize
+    /// recent orders.
+    OrderAge,
 }
 
 /// The default prioritization process first considers
@@ -795,6 +1237,11 @@ pub struct BadTokenDetectionConfig {
     #[serde(default, rename = "enable-metrics-bad-token-detection")]
     pub enable_metrics_strategy: bool,
 
+    /// Whether the solver is able to handle tokens that take a fee on
+    /// transfer. Such tokens are otherwise treated as unsupported.
+    #[serde(default, rename = "enable-fee-on-transfer-tokens")]
+    pub enable_fee_on_transfer: bool,
+
     /// The ratio of failures to attempts that qualifies a token as unsupported.
     #[serde(
         default = "default_metrics_bad_token_detector_failure_ratio",
@@ -1,6 +1,6 @@
 use {
     crate::{
-        domain::eth,
+        domain::{competition::bad_tokens, eth},
         infra::{
             blockchain,
             config::file::{AppDataFetching, GasEstimatorType, OrderPriorityStrategy},
@@ -23,12 +23,13 @@ pub struct Config {
     pub disable_gas_simulation: Option<eth::Gas>,
     pub solvers: Vec<solver::Config>,
     pub liquidity: liquidity::Config,
-    pub simulator: Option<simulator::Config>,
+    pub simulator: simulator::Config,
     pub gas_estimator: GasEstimatorType,
     pub mempools: Vec<mempool::Config>,
     pub contracts: blockchain::contracts::Addresses,
     pub order_priority_strategies: Vec<OrderPriorityStrategy>,
     pub archive_node_url: Option<Url>,
     pub simulation_bad_token_max_age: Duration,
+    pub simulation_bad_token_max_transfer_fee: Option<bad_tokens::TransferFee>,
     pub app_data_fetching: AppDataFetching,
 }
@@ -4,7 +4,7 @@ use {
     derive_more::Debug,
     hex_literal::hex,
     reqwest::Url,
-    std::{collections::HashSet, time::Duration},
+    std::{collections::HashSet, path::PathBuf, time::Duration},
 };
 
 /// Configuration options for liquidity fetching.
@@ -26,12 +26,45 @@ pub struct Config {
     /// for.
     pub uniswap_v3: Vec<UniswapV3>,
 
+    /// The collection of Uniswap V4 pool managers to fetch liquidity for.
+    pub uniswap_v4: Vec<UniswapV4>,
+
     /// The collection of Balancer V2 compatible exchanges to fetch liquidity
     /// for.
     pub balancer_v2: Vec<BalancerV2>,
 
+    /// The collection of Balancer V3 compatible exchanges to fetch liquidity
+    /// for.
+    pub balancer_v3: Vec<BalancerV3>,
+
+    /// The collection of Curve registries to fetch liquidity for.
+    pub curve: Vec<Curve>,
+
     /// 0x liquidity fetcher.
     pub zeroex: Option<ZeroEx>,
+
+    /// Persistent snapshots of the indexed liquidity state used to speed up
+    /// restarts.
+    pub snapshots: Option<Snapshots>,
+}
+
+/// Liquidity snapshot options.
+#[derive(Clone, Debug)]
+pub struct Snapshots {
+    /// Where the snapshots are stored.
+    pub storage: SnapshotStorage,
+    /// How often liquidity sources write a new snapshot.
+    pub interval: Duration,
+    /// The maximum age (in blocks) of a snapshot to still resume from it.
+    pub max_age: u64,
+}
+
+#[derive(Clone, Debug)]
+pub enum SnapshotStorage {
+    /// A directory on the local disk.
+    File(PathBuf),
+    /// A Postgres database.
+    Postgres(#[debug(ignore)] Url),
 }
 
 /// Uniswap V2 (and Uniswap V2 clone) liquidity fetching options.
@@ -167,6 +200,49 @@ impl UniswapV3 {
     }
 }
 
+/// Uniswap V4 liquidity fetching options.
+#[derive(Clone, Debug)]
+pub struct UniswapV4 {
+    /// The address of the singleton pool manager.
+    pub pool_manager: eth::ContractAddress,
+
+    /// The block the pool manager was deployed in. Pools are indexed from the
+    /// pool manager events starting at this block.
+    pub deployment_block: u64,
+
+    /// The address of the universal router used for swapping through pools.
+    pub router: eth::ContractAddress,
+
+    /// The address of the Permit2 contract the universal router uses for
+    /// pulling tokens.
+    pub permit2: eth::ContractAddress,
+
+    /// Hooks contracts that are considered safe to trade through.
+    ///
+    /// Hooks can arbitrarily change the outcome of swaps, so pools with hooks
+    /// that are not in this list are ignored.
+    pub allowed_hooks: Vec<eth::ContractAddress>,
+
+    /// How often the liquidity source should be re-initialized.
+    pub reinit_interval: Option<Duration>,
+}
+
+impl UniswapV4 {
+    /// Returns the liquidity configuration for Uniswap V4.
+    #[allow(clippy::self_named_constructors)]
+    pub fn uniswap_v4(chain: Chain) -> Option<Self> {
+        let pool_manager = contracts::UniswapV4PoolManager::raw_contract();
+        Some(Self {
+            pool_manager: deployment_address(pool_manager, chain)?,
+            deployment_block: contracts::deployment_block(pool_manager, chain.id()).ok()?,
+            router: deployment_address(contracts::UniswapV4UniversalRouter::raw_contract(), chain)?,
+            permit2: deployment_address(contracts::Permit2::raw_contract(), chain)?,
+            allowed_hooks: Vec::new(),
+            reinit_interval: None,
+        })
+    }
+}
+
 /// Balancer V2 liquidity fetching options.
 #[derive(Clone, Debug)]
 pub struct BalancerV2 {
@@ -195,8 +271,14 @@ pub struct BalancerV2 {
     /// ignored.
     pub pool_deny_list: Vec<eth::H256>,
 
-    /// The base URL used to connect to balancer v2 subgraph client.
-    pub graph_url: Url,
+    /// The base URL used to connect to balancer v2 subgraph client. When
+    /// unset, the existing pools are discovered on-chain by scanning the pool
+    /// factory events instead.
+    pub graph_url: Option<Url>,
+
+    /// The block from which pool factory events are scanned when discovering
+    /// pools on-chain.
+    pub deployment_block: u64,
 
     /// How often the liquidty source should be re-initialized to become
     /// aware of new pools.
@@ -206,7 +288,7 @@ pub struct BalancerV2 {
 impl BalancerV2 {
     /// Returns the liquidity configuration for Balancer V2.
     #[allow(clippy::self_named_constructors)]
-    pub fn balancer_v2(graph_url: &Url, chain: Chain) -> Option<Self> {
+    pub fn balancer_v2(graph_url: Option<&Url>, chain: Chain) -> Option<Self> {
         let factory_addresses =
             |contracts: &[&ethcontract::Contract]| -> Vec<eth::ContractAddress> {
                 contracts
@@ -216,8 +298,9 @@ impl BalancerV2 {
                     .collect()
             };
 
+        let vault = contracts::BalancerV2Vault::raw_contract();
         Some(Self {
-            vault: deployment_address(contracts::BalancerV2Vault::raw_contract(), chain)?,
+            vault: deployment_address(vault, chain)?,
             weighted: factory_addresses(&[
                 contracts::BalancerV2WeightedPoolFactory::raw_contract(),
                 contracts::BalancerV2WeightedPool2TokensFactory::raw_contract(),
@@ -239,7 +322,103 @@ impl BalancerV2 {
                 contracts::BalancerV2ComposableStablePoolFactoryV6::raw_contract(),
             ]),
             pool_deny_list: Vec::new(),
-            graph_url: graph_url.clone(),
+            graph_url: graph_url.cloned(),
+            deployment_block: contracts::deployment_block(vault, chain.id()).unwrap_or_default(),
+            reinit_interval: None,
+        })
+    }
+}
+
+/// Balancer V3 liquidity fetching options.
+#[derive(Clone, Debug)]
+pub struct BalancerV3 {
+    /// The address of the Balancer V3 vault.
+    pub vault: eth::ContractAddress,
+
+    /// The address of the batch router used for swapping through pools.
+    pub batch_router: eth::ContractAddress,
+
+    /// The address of the Permit2 contract the batch router uses for pulling
+    /// tokens.
+    pub permit2: eth::ContractAddress,
+
+    /// Weighted pool factory addresses.
+    pub weighted: Vec<eth::ContractAddress>,
+
+    /// Stable pool factory addresses.
+    pub stable: Vec<eth::ContractAddress>,
+
+    /// Deny listed Balancer V3 pools.
+    ///
+    /// Since pools allow for custom hooks, it is possible for pools to get
+    /// "bricked". This configuration allows those pools to be ignored.
+    pub pool_deny_list: Vec<eth::ContractAddress>,
+
+    /// How often the liquidity source should be re-initialized to become
+    /// aware of new pools.
+    pub reinit_interval: Option<Duration>,
+}
+
+impl BalancerV3 {
+    /// Returns the liquidity configuration for Balancer V3.
+    #[allow(clippy::self_named_constructors)]
+    pub fn balancer_v3(chain: Chain) -> Option<Self> {
+        let factory_addresses =
+            |contracts: &[&ethcontract::Contract]| -> Vec<eth::ContractAddress> {
+                contracts
+                    .iter()
+                    .copied()
+                    .filter_map(|c| deployment_address(c, chain))
+                    .collect()
+            };
+
+        Some(Self {
+            vault: deployment_address(contracts::BalancerV3Vault::raw_contract(), chain)?,
+            batch_router: deployment_address(
+                contracts::BalancerV3BatchRouter::raw_contract(),
+                chain,
+            )?,
+            permit2: deployment_address(contracts::Permit2::raw_contract(), chain)?,
+            weighted: factory_addresses(
+                &[contracts::BalancerV3WeightedPoolFactory::raw_contract()],
+            ),
+            stable: factory_addresses(&[contracts::BalancerV3StablePoolFactoryV2::raw_contract()]),
+            pool_deny_list: Vec::new(),
+            reinit_interval: None,
+        })
+    }
+}
+
+/// Curve liquidity fetching options.
+#[derive(Clone, Debug)]
+pub struct Curve {
+    /// The addresses of the registries listing plain and lending StableSwap
+    /// pools.
+    pub stable_registries: Vec<eth::ContractAddress>,
+
+    /// The addresses of the registries listing CryptoSwap pools.
+    pub crypto_registries: Vec<eth::ContractAddress>,
+
+    /// How often the liquidity source should be re-initialized to become
+    /// aware of new pools.
+    pub reinit_interval: Option<Duration>,
+}
+
+impl Curve {
+    /// Returns the liquidity configuration for Curve.
+    #[allow(clippy::self_named_constructors)]
+    pub fn curve(chain: Chain) -> Option<Self> {
+        Some(Self {
+            stable_registries: vec![deployment_address(
+                contracts::CurveRegistry::raw_contract(),
+                chain,
+            )?],
+            crypto_registries: deployment_address(
+                contracts::CurveCryptoRegistry::raw_contract(),
+                chain,
+            )
+            .into_iter()
+            .collect(),
             reinit_interval: None,
         })
     }
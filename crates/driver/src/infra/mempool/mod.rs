@@ -76,16 +76,17 @@ impl Mempool {
         Self { config, transport }
     }
 
-    /// Submits a transaction to the mempool. Returns optimistically as soon as
-    /// the transaction is pending.
+    /// Submits a transaction to the mempool from the leased solver account.
+    /// Returns optimistically as soon as the transaction is pending.
     pub async fn submit(
         &self,
         tx: eth::Tx,
         gas: competition::solution::settlement::Gas,
-        solver: &infra::Solver,
+        account: &infra::solver::accounts::Lease,
     ) -> Result<eth::TxId, mempools::Error> {
         ethcontract::transaction::TransactionBuilder::new(self.transport.clone())
-            .from(solver.account().clone())
+            .from(account.account().clone())
+            .nonce(account.nonce())
             .to(tx.to.into())
             .gas_price(ethcontract::GasPrice::Eip1559 {
                 max_fee_per_gas: gas.price.max().into(),
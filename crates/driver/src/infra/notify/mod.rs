@@ -41,6 +41,7 @@ pub fn scoring_failed(
             notification::Kind::ScoringFailed(ScoreKind::InvalidClearingPrices)
         }
         solution::error::Scoring::Math(_)
+        | solution::error::Scoring::NegativeScore(..)
         | solution::error::Scoring::CalculateCustomPrices(
             solution::error::Trade::Math(_) | solution::error::Trade::ProtocolFeeOnStaticOrder,
         ) => return,
@@ -65,8 +66,17 @@ pub fn encoding_failed(
         solution::Error::NonBufferableTokensUsed(tokens) => {
             notification::Kind::NonBufferableTokensUsed(tokens.clone())
         }
-        solution::Error::SolverAccountInsufficientBalance(required) => {
-            notification::Kind::SolverAccountInsufficientBalance(*required)
+        solution::Error::SolverAccountInsufficientBalance(accounts, required) => {
+            for account in accounts {
+                solver_account_insufficient_balance(
+                    solver,
+                    auction_id,
+                    solution_id,
+                    *account,
+                    *required,
+                );
+            }
+            return;
         }
         solution::Error::Blockchain(_) => return,
         solution::Error::Boundary(_) => return,
@@ -86,6 +96,20 @@ pub fn encoding_failed(
     solver.notify(auction_id, Some(solution_id.clone()), notification);
 }
 
+pub fn solver_account_insufficient_balance(
+    solver: &Solver,
+    auction_id: Option<auction::Id>,
+    solution_id: &solution::Id,
+    account: eth::Address,
+    required: eth::Ether,
+) {
+    solver.notify(
+        auction_id,
+        Some(solution_id.clone()),
+        notification::Kind::SolverAccountInsufficientBalance(account, required),
+    );
+}
+
 pub fn simulation_failed(
     solver: &Solver,
     auction_id: Option<auction::Id>,
@@ -140,3 +164,21 @@ pub fn duplicated_solution_id(
 pub fn postprocessing_timed_out(solver: &Solver, auction_id: Option<auction::Id>) {
     solver.notify(auction_id, None, notification::Kind::PostprocessingTimedOut);
 }
+
+pub fn simulator_disagreement(solver: &Solver, disagreement: &simulator::Disagreement) {
+    let reason = match &disagreement.kind {
+        simulator::DisagreementKind::Revert(kind) => format!("only {kind:?} simulation reverted"),
+        simulator::DisagreementKind::Gas { primary, secondary } => {
+            format!("simulated gas {primary} differs from {secondary}")
+        }
+    };
+    solver.notify(
+        None,
+        None,
+        notification::Kind::SimulatorDisagreement(
+            disagreement.block,
+            disagreement.tx.clone(),
+            reason,
+        ),
+    );
+}
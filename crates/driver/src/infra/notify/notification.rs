@@ -7,6 +7,7 @@ use {
     std::collections::BTreeSet,
 };
 
+type Account = eth::Address;
 type RequiredEther = Ether;
 type TokensUsed = BTreeSet<TokenAddress>;
 type TransactionHash = eth::TxId;
@@ -37,8 +38,9 @@ pub enum Kind {
     /// Solution aimed to internalize tokens that are not considered safe to
     /// keep in the settlement contract.
     NonBufferableTokensUsed(TokensUsed),
-    /// Solver don't have enough balance to submit the solution onchain.
-    SolverAccountInsufficientBalance(RequiredEther),
+    /// Solver account doesn't have enough balance to submit the solution
+    /// onchain.
+    SolverAccountInsufficientBalance(Account, RequiredEther),
     /// Result of winning solver trying to settle the transaction onchain.
     Settled(Settlement),
     /// Some aspect of the driver logic failed preventing the solution from
@@ -46,6 +48,9 @@ pub enum Kind {
     DriverError(String),
     /// On-chain solution postprocessing timed out.
     PostprocessingTimedOut,
+    /// The simulators used for the solver's settlements disagreed on the
+    /// outcome of a transaction.
+    SimulatorDisagreement(eth::BlockNo, Transaction, String),
     /// The solver has been banned for a specific reason.
     Banned {
         reason: BanReason,
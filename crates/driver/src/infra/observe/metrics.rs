@@ -23,6 +23,12 @@ pub struct Metrics {
     /// atempted and the error detection.
     #[metric(labels("mempool", "result"))]
     pub mempool_submission_results_blocks_passed: prometheus::IntCounterVec,
+    /// Disagreements between the simulators of a solver.
+    #[metric(labels("solver", "kind"))]
+    pub simulator_disagreements: prometheus::IntCounterVec,
+    /// Orders removed from the auction by the order filters of a solver.
+    #[metric(labels("solver", "reason"))]
+    pub filtered_orders: prometheus::IntCounterVec,
     /// How many tokens detected by specific solver and strategy.
     #[metric(labels("solver", "strategy"))]
     pub bad_tokens_detected: prometheus::IntCounterVec,
@@ -424,6 +424,28 @@ pub fn order_excluded_from_auction(
     tracing::trace!(uid=?order.uid, ?reason, "order excluded from auction");
 }
 
+/// Observe that the order filters of a solver removed orders from the
+/// auction.
+pub fn orders_filtered(
+    solver: &solver::Name,
+    reason: competition::auction::OrderFilterReason,
+    count: usize,
+) {
+    tracing::debug!(%solver, ?reason, count, "filtered orders from auction");
+    let reason = match reason {
+        competition::auction::OrderFilterReason::TokenDenied => "TokenDenied",
+        competition::auction::OrderFilterReason::TokenNotAllowed => "TokenNotAllowed",
+        competition::auction::OrderFilterReason::Hooks => "Hooks",
+        competition::auction::OrderFilterReason::Eip1271 => "Eip1271",
+        competition::auction::OrderFilterReason::MinNotional => "MinNotional",
+        competition::auction::OrderFilterReason::MaxOrders => "MaxOrders",
+    };
+    metrics::get()
+        .filtered_orders
+        .with_label_values(&[solver.as_str(), reason])
+        .inc_by(count as u64);
+}
+
 /// Observe that a settlement was simulated
 pub fn simulated(eth: &Ethereum, tx: &eth::Tx, gas: &Result<Gas, simulator::Error>) {
     let block: eth::BlockNo = eth.current_block().borrow().number.into();
@@ -432,3 +454,16 @@ pub fn simulated(eth: &Ethereum, tx: &eth::Tx, gas: &Result<Gas, simulator::Erro
         Err(err) => tracing::debug!(block = ?block, ?err, "simulated settlement"),
     }
 }
+
+/// Observe that two simulators disagreed on the outcome of a transaction.
+pub fn simulator_disagreement(solver: &solver::Name, disagreement: &simulator::Disagreement) {
+    tracing::warn!(%solver, ?disagreement, "simulators disagree");
+    let kind = match disagreement.kind {
+        simulator::DisagreementKind::Revert(_) => "Revert",
+        simulator::DisagreementKind::Gas { .. } => "Gas",
+    };
+    metrics::get()
+        .simulator_disagreements
+        .with_label_values(&[solver.as_str(), kind])
+        .inc();
+}
@@ -0,0 +1,375 @@
+//! Records of what happened to the solutions of a solver during an auction.
+//! They are archived so that it's possible to find out why a solution was
+//! discarded without correlating logs across services.
+
+use {
+    crate::{
+        domain::{
+            competition::{order, solution},
+            eth,
+        },
+        util::serialize,
+    },
+    serde::Serialize,
+    serde_with::serde_as,
+    std::{collections::HashMap, sync::Mutex},
+};
+
+/// Collects the record of a single `/solve` request. Does nothing if
+/// archiving competition records is disabled.
+#[derive(Debug, Default)]
+pub struct Recorder(Option<Mutex<Record>>);
+
+impl Recorder {
+    pub(super) fn enabled() -> Self {
+        Self(Some(Default::default()))
+    }
+
+    pub fn disabled() -> Self {
+        Self(None)
+    }
+
+    /// Records a post-processed solution, including solutions created by
+    /// merging.
+    pub fn solution(&self, solution: &solution::Solution) {
+        self.update(|record| {
+            record.solutions.push(Solution {
+                id: solution.id().get(),
+                merged_solutions: solution.id().solutions().to_vec(),
+                solution: Details::new(solution),
+                outcome: None,
+            })
+        });
+    }
+
+    /// Records a solution that was discarded before encoding.
+    pub fn discarded(&self, solution: &solution::Solution, reason: &str) {
+        self.solution(solution);
+        self.outcome(
+            solution.id(),
+            Outcome::Discarded {
+                reason: reason.to_owned(),
+            },
+        );
+    }
+
+    pub fn encoding_failed(&self, id: &solution::Id, err: &solution::Error) {
+        self.outcome(
+            id,
+            Outcome::EncodingFailed {
+                error: format!("{err:?}"),
+            },
+        );
+    }
+
+    pub fn scoring_failed(&self, id: &solution::Id, err: &solution::error::Scoring) {
+        self.outcome(
+            id,
+            Outcome::ScoringFailed {
+                error: format!("{err:?}"),
+            },
+        );
+    }
+
+    pub fn scored(&self, id: &solution::Id, score: eth::Ether, gas: eth::Gas) {
+        self.outcome(
+            id,
+            Outcome::Scored {
+                score: score.0,
+                gas: gas.0,
+            },
+        );
+    }
+
+    /// Records the solution proposed to the autopilot.
+    pub fn winner(&self, id: &solution::Id) {
+        self.update(|record| record.winner = Some(id.get()));
+    }
+
+    /// Records that the proposed solution started reverting before the
+    /// deadline.
+    pub fn voided(&self, id: &solution::Id, err: &impl std::fmt::Debug) {
+        self.outcome(
+            id,
+            Outcome::Voided {
+                error: format!("{err:?}"),
+            },
+        );
+    }
+
+    pub(super) fn take(&self) -> Option<Record> {
+        self.0
+            .as_ref()
+            .map(|record| std::mem::take(&mut *record.lock().unwrap()))
+    }
+
+    fn outcome(&self, id: &solution::Id, outcome: Outcome) {
+        self.update(|record| {
+            if let Some(solution) = record
+                .solutions
+                .iter_mut()
+                .rev()
+                .find(|solution| solution.id == id.get())
+            {
+                solution.outcome = Some(outcome);
+            }
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Record)) {
+        if let Some(record) = &self.0 {
+            f(&mut record.lock().unwrap());
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct Record {
+    solutions: Vec<Solution>,
+    winner: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Solution {
+    id: u64,
+    /// The IDs the solver assigned to the solutions making up this one.
+    merged_solutions: Vec<u64>,
+    /// The post-processed solution.
+    solution: Details,
+    /// What happened to the solution. Missing if post-processing timed out
+    /// before the solution was handled.
+    outcome: Option<Outcome>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Details {
+    trades: Vec<Trade>,
+    #[serde_as(as = "HashMap<_, serialize::U256>")]
+    clearing_prices: HashMap<eth::H160, eth::U256>,
+    pre_interactions: Vec<Call>,
+    interactions: Vec<Interaction>,
+    post_interactions: Vec<Call>,
+    #[serde_as(as = "Option<serialize::U256>")]
+    gas: Option<eth::U256>,
+    flashloans: Vec<Flashloan>,
+    flashloan_fees: Vec<Asset>,
+}
+
+impl Details {
+    fn new(solution: &solution::Solution) -> Self {
+        Self {
+            trades: solution
+                .trades()
+                .iter()
+                .map(|trade| Trade {
+                    kind: match trade {
+                        solution::Trade::Fulfillment(_) => TradeKind::Fulfillment,
+                        solution::Trade::Jit(_) => TradeKind::Jit,
+                    },
+                    order: trade.uid().into(),
+                    side: match trade.side() {
+                        order::Side::Buy => Side::Buy,
+                        order::Side::Sell => Side::Sell,
+                    },
+                    executed_amount: trade.executed().0,
+                    fee: trade.fee().0,
+                })
+                .collect(),
+            clearing_prices: solution
+                .clearing_prices()
+                .into_iter()
+                .map(|(token, price)| (token.into(), price))
+                .collect(),
+            pre_interactions: solution.pre_interactions().iter().map(Call::new).collect(),
+            interactions: solution
+                .interactions()
+                .iter()
+                .map(|interaction| match interaction {
+                    solution::Interaction::Custom(custom) => Interaction::Custom {
+                        target: custom.target.into(),
+                        value: custom.value.0,
+                        call_data: custom.call_data.0.clone(),
+                        inputs: custom.inputs.iter().map(Asset::new).collect(),
+                        outputs: custom.outputs.iter().map(Asset::new).collect(),
+                        internalize: custom.internalize,
+                    },
+                    solution::Interaction::Liquidity(liquidity) => Interaction::Liquidity {
+                        id: liquidity.liquidity.id.0,
+                        liquidity_kind: <&str>::from(&liquidity.liquidity.kind),
+                        input: Asset::new(&liquidity.input),
+                        output: Asset::new(&liquidity.output),
+                        internalize: liquidity.internalize,
+                    },
+                })
+                .collect(),
+            post_interactions: solution.post_interactions().iter().map(Call::new).collect(),
+            gas: solution.gas().map(|gas| gas.0),
+            flashloans: solution
+                .flashloans()
+                .iter()
+                .map(|flashloan| Flashloan {
+                    lender: flashloan.lender.into(),
+                    borrower: flashloan.borrower.into(),
+                    token: flashloan.token.into(),
+                    amount: flashloan.amount.0,
+                })
+                .collect(),
+            flashloan_fees: solution.flashloan_fees().iter().map(Asset::new).collect(),
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trade {
+    kind: TradeKind,
+    #[serde_as(as = "serialize::Hex")]
+    order: [u8; order::UID_LEN],
+    side: Side,
+    #[serde_as(as = "serialize::U256")]
+    executed_amount: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    fee: eth::U256,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum TradeKind {
+    Fulfillment,
+    Jit,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Side {
+    Buy,
+    Sell,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+enum Interaction {
+    Custom {
+        target: eth::H160,
+        #[serde_as(as = "serialize::U256")]
+        value: eth::U256,
+        #[serde_as(as = "serialize::Hex")]
+        call_data: Vec<u8>,
+        inputs: Vec<Asset>,
+        outputs: Vec<Asset>,
+        internalize: bool,
+    },
+    Liquidity {
+        id: usize,
+        liquidity_kind: &'static str,
+        input: Asset,
+        output: Asset,
+        internalize: bool,
+    },
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Call {
+    target: eth::H160,
+    #[serde_as(as = "serialize::U256")]
+    value: eth::U256,
+    #[serde_as(as = "serialize::Hex")]
+    call_data: Vec<u8>,
+}
+
+impl Call {
+    fn new(interaction: &eth::Interaction) -> Self {
+        Self {
+            target: interaction.target.into(),
+            value: interaction.value.0,
+            call_data: interaction.call_data.0.clone(),
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Flashloan {
+    lender: eth::H160,
+    borrower: eth::H160,
+    token: eth::H160,
+    #[serde_as(as = "serialize::U256")]
+    amount: eth::U256,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Asset {
+    token: eth::H160,
+    #[serde_as(as = "serialize::U256")]
+    amount: eth::U256,
+}
+
+impl Asset {
+    fn new(asset: &eth::Asset) -> Self {
+        Self {
+            token: asset.token.into(),
+            amount: asset.amount.0,
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+enum Outcome {
+    Discarded {
+        reason: String,
+    },
+    EncodingFailed {
+        error: String,
+    },
+    ScoringFailed {
+        error: String,
+    },
+    Scored {
+        #[serde_as(as = "serialize::U256")]
+        score: eth::U256,
+        #[serde_as(as = "serialize::U256")]
+        gas: eth::U256,
+    },
+    Voided {
+        error: String,
+    },
+}
+
+/// The outcome of revealing or settling a solution.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settlement {
+    pub solution_id: u64,
+    pub tx_hash: Option<eth::H256>,
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_recorder_records_nothing() {
+        let recorder = Recorder::disabled();
+        recorder.update(|record| record.winner = Some(1));
+        assert!(recorder.take().is_none());
+
+        let recorder = Recorder::enabled();
+        recorder.update(|record| record.winner = Some(1));
+        assert_eq!(recorder.take().unwrap().winner, Some(1));
+        assert_eq!(recorder.take().unwrap().winner, None);
+    }
+}
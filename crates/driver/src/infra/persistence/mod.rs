@@ -5,10 +5,13 @@ use {
     },
     serde::Serialize,
     serde_json::to_value,
-    std::sync::Arc,
     tracing::Instrument,
 };
 
+pub mod competition;
+
+pub use competition::Recorder;
+
 #[derive(Clone, Debug, Default)]
 pub struct S3 {
     /// Name of the AWS S3 bucket in which the auctions will be stored
@@ -39,41 +42,111 @@ impl From<S3> for s3::Config {
 
 #[derive(Clone, Debug)]
 pub struct Persistence {
-    s3: Option<Arc<s3::Uploader>>,
+    archive: Option<s3::Uploader>,
+    /// Whether competition records are archived in addition to the auctions.
+    competition: bool,
 }
 
 impl Persistence {
     pub async fn build(config: &Config) -> Self {
-        if let Some(s3) = &config.s3 {
-            Self {
-                s3: Some(Arc::new(s3::Uploader::new(s3.clone().into()).await)),
-            }
-        } else {
-            Self { s3: None }
+        let archive = match (&config.archive_directory, &config.s3) {
+            (Some(dir), s3) => Some(s3::Uploader::local(
+                dir.clone(),
+                s3.as_ref().map(|s3| s3.prefix.clone()).unwrap_or_default(),
+            )),
+            (None, Some(s3)) => Some(s3::Uploader::new(s3.clone().into()).await),
+            (None, None) => None,
+        };
+        Self {
+            archive,
+            competition: config.archive_competition,
         }
     }
 
     /// Saves the given auction with liquidity with fire and forget mentality
     /// (non-blocking operation)
     pub fn archive_auction(&self, auction_id: Id, body: impl Serialize) {
-        let Some(uploader) = self.s3.clone() else {
+        self.archive(auction_id.to_string(), "auction with liquidity", body);
+    }
+
+    /// Saves the raw response of the solver engine for the given auction.
+    pub fn archive_solver_response(&self, auction_id: Id, response: &str) {
+        if !self.competition {
+            return;
+        }
+        // Responses that aren't valid JSON are kept as they are to help
+        // debugging them.
+        let body = serde_json::from_str(response)
+            .unwrap_or_else(|_| serde_json::Value::String(response.to_owned()));
+        self.archive(
+            format!("competition/{auction_id}/solver-response"),
+            "solver response",
+            body,
+        );
+    }
+
+    /// Returns a recorder for what happens to the solutions of an auction.
+    /// The recorder does nothing if competition records aren't archived.
+    pub fn competition_recorder(&self) -> Recorder {
+        match self.competition && self.archive.is_some() {
+            true => Recorder::enabled(),
+            false => Recorder::disabled(),
+        }
+    }
+
+    /// Saves everything recorded so far about the solutions of the auction.
+    pub fn archive_competition(&self, auction_id: Id, recorder: &Recorder) {
+        if let Some(record) = recorder.take() {
+            self.archive(
+                format!("competition/{auction_id}/solve"),
+                "competition record",
+                record,
+            );
+        }
+    }
+
+    /// Saves the outcome of revealing or settling a solution. `stage` is
+    /// either "reveal" or "settle".
+    pub fn archive_settlement(
+        &self,
+        auction_id: Id,
+        stage: &'static str,
+        settlement: competition::Settlement,
+    ) {
+        if !self.competition {
+            return;
+        }
+        self.archive(
+            format!(
+                "competition/{auction_id}/{stage}-{}",
+                settlement.solution_id
+            ),
+            stage,
+            settlement,
+        );
+    }
+
+    /// Stores the object with fire and forget mentality (non-blocking
+    /// operation)
+    fn archive(&self, key: String, description: &'static str, body: impl Serialize) {
+        let Some(archive) = self.archive.clone() else {
             return;
         };
         let body = match to_value(body) {
             Ok(body) => body,
             Err(err) => {
-                tracing::error!(?err, "failed to parse the auction with liquidity to JSON");
+                tracing::error!(?err, description, "failed to serialize object to JSON");
                 return;
             }
         };
         tokio::spawn(
             async move {
-                match uploader.upload(auction_id.to_string(), body).await {
+                match archive.upload(key, body).await {
                     Ok(key) => {
-                        tracing::debug!(?key, "uploaded auction with liquidity to s3");
+                        tracing::debug!(?key, description, "archived object");
                     }
                     Err(err) => {
-                        tracing::warn!(?err, "failed to upload auction to s3");
+                        tracing::warn!(?err, description, "failed to archive object");
                     }
                 }
             }
@@ -2,11 +2,14 @@ use {
     crate::{
         domain::eth,
         infra::blockchain::{self, Ethereum},
+        util::conv::u256::U256Ext,
     },
     observe::future::Measure,
+    std::sync::Arc,
 };
 
 pub mod enso;
+pub mod revm;
 pub mod tenderly;
 
 /// Ethereum transaction simulator.
@@ -18,13 +21,123 @@ pub struct Simulator {
     /// If this is [`Some`], every gas estimate will return this fixed
     /// gas value.
     disable_gas: Option<eth::Gas>,
+    /// Another simulator that simulates the same transactions to detect
+    /// disagreements between the simulators.
+    compare: Option<Arc<Compare>>,
+}
+
+/// Configuration of the external transaction simulators. Transactions are
+/// simulated using the Ethereum RPC API unless a solver picks one of these.
+#[derive(Debug, Default)]
+pub struct Config {
+    pub tenderly: Option<tenderly::Config>,
+    pub enso: Option<enso::Config>,
+}
+
+/// The backends that can simulate transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Ethereum,
+    Tenderly,
+    Enso,
+    Revm,
+}
+
+/// All configured simulators, so that each solver can use its own.
+#[derive(Debug, Clone)]
+pub struct Simulators {
+    ethereum: Simulator,
+    revm: Simulator,
+    tenderly: Option<Simulator>,
+    enso: Option<Simulator>,
+}
+
+impl Simulators {
+    pub fn new(
+        ethereum: Simulator,
+        revm: Simulator,
+        tenderly: Option<Simulator>,
+        enso: Option<Simulator>,
+    ) -> Self {
+        Self {
+            ethereum,
+            revm,
+            tenderly,
+            enso,
+        }
+    }
+
+    /// The default simulator: Tenderly or Enso if configured, in that order
+    /// of preference, and the Ethereum RPC API otherwise.
+    pub fn default_kind(&self) -> Kind {
+        match (&self.tenderly, &self.enso) {
+            (Some(_), _) => Kind::Tenderly,
+            (None, Some(_)) => Kind::Enso,
+            (None, None) => Kind::Ethereum,
+        }
+    }
+
+    /// Returns the simulator of the given kind if it is configured. Loading
+    /// the config already ensures that solvers only use configured
+    /// simulators.
+    pub fn get(&self, kind: Kind) -> Option<Simulator> {
+        match kind {
+            Kind::Ethereum => Some(&self.ethereum),
+            Kind::Revm => Some(&self.revm),
+            Kind::Tenderly => self.tenderly.as_ref(),
+            Kind::Enso => self.enso.as_ref(),
+        }
+        .cloned()
+    }
+}
+
+/// Configures a second simulator to run on the same transactions as the
+/// primary one in order to detect simulator drift. Only gas simulations are
+/// compared, access lists always come from the primary simulator.
+#[derive(Debug, Clone, Copy)]
+pub struct Comparison {
+    /// The simulator to compare with.
+    pub with: Kind,
+    /// Relative difference of the simulated gas above which the simulators
+    /// are considered to disagree.
+    pub max_gas_deviation: f64,
+    /// Use the stricter result of both simulators, i.e. a revert over a
+    /// success and the higher gas estimate, instead of the primary result.
+    pub prefer_stricter: bool,
+}
+
+/// Two simulators disagreeing on the outcome of a transaction.
+#[derive(Debug)]
+pub struct Disagreement {
+    pub tx: eth::Tx,
+    pub block: eth::BlockNo,
+    pub kind: DisagreementKind,
 }
 
-/// Configuration of the transaction simulator.
 #[derive(Debug)]
-pub enum Config {
-    Tenderly(tenderly::Config),
-    Enso(enso::Config),
+pub enum DisagreementKind {
+    /// Only the given simulator reverted.
+    Revert(Kind),
+    /// Both simulators succeeded with diverging gas estimates.
+    Gas {
+        primary: eth::Gas,
+        secondary: eth::Gas,
+    },
+}
+
+struct Compare {
+    other: Simulator,
+    comparison: Comparison,
+    on_disagreement: Box<dyn Fn(&Disagreement) + Send + Sync>,
+}
+
+impl std::fmt::Debug for Compare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Compare")
+            .field("other", &self.other)
+            .field("comparison", &self.comparison)
+            .finish()
+    }
 }
 
 impl Simulator {
@@ -36,6 +149,7 @@ impl Simulator {
             eth,
             disable_access_lists: false,
             disable_gas: None,
+            compare: None,
         }
     }
 
@@ -47,6 +161,20 @@ impl Simulator {
             eth,
             disable_access_lists: false,
             disable_gas: None,
+            compare: None,
+        }
+    }
+
+    /// Simulate transactions in a local EVM that forks the state of the
+    /// Ethereum node.
+    pub fn revm(eth: Ethereum) -> Self {
+        let eth = eth.with_metric_label("revmSimulator".into());
+        Self {
+            inner: Inner::Revm(revm::Revm::new(&eth)),
+            eth,
+            disable_access_lists: false,
+            disable_gas: None,
+            compare: None,
         }
     }
 
@@ -63,6 +191,7 @@ impl Simulator {
             eth,
             disable_access_lists: false,
             disable_gas: None,
+            compare: None,
         }
     }
 
@@ -78,6 +207,30 @@ impl Simulator {
         self.disable_gas = Some(fixed_gas);
     }
 
+    /// Simulate every transaction with the `other` simulator as well and
+    /// report whenever both disagree.
+    pub fn compare_with(
+        &mut self,
+        other: Simulator,
+        comparison: Comparison,
+        on_disagreement: impl Fn(&Disagreement) + Send + Sync + 'static,
+    ) {
+        self.compare = Some(Arc::new(Compare {
+            other,
+            comparison,
+            on_disagreement: Box::new(on_disagreement),
+        }));
+    }
+
+    pub fn kind(&self) -> Kind {
+        match self.inner {
+            Inner::Tenderly(_) => Kind::Tenderly,
+            Inner::Ethereum => Kind::Ethereum,
+            Inner::Enso(_) => Kind::Enso,
+            Inner::Revm(_) => Kind::Revm,
+        }
+    }
+
     /// Simulate the access list needed by a transaction. If the transaction
     /// already has an access list, the returned access list will be a
     /// superset of the existing one.
@@ -104,6 +257,12 @@ impl Simulator {
                 .create_access_list(tx.clone())
                 .await
                 .map_err(with(tx.clone(), block))?,
+            Inner::Revm(revm) => {
+                revm.simulate(tx)
+                    .await
+                    .map_err(with(tx.clone(), block))?
+                    .access_list
+            }
         };
         Ok(tx.access_list.clone().merge(access_list))
     }
@@ -113,6 +272,16 @@ impl Simulator {
         if let Some(gas) = self.disable_gas {
             return Ok(gas);
         }
+        let Some(compare) = &self.compare else {
+            return self.simulate_gas(tx).await;
+        };
+        let (primary, secondary) =
+            futures::join!(self.simulate_gas(tx), compare.other.simulate_gas(tx));
+        let block = self.eth.current_block().borrow().number.into();
+        compare.reconcile(self.kind(), tx, block, primary, secondary)
+    }
+
+    async fn simulate_gas(&self, tx: &eth::Tx) -> Result<eth::Gas, Error> {
         let block = self.eth.current_block().borrow().number.into();
         Ok(match &self.inner {
             Inner::Tenderly(tenderly) => {
@@ -133,15 +302,97 @@ impl Simulator {
                 .measure("enso_simulate_gas")
                 .await
                 .map_err(with(tx.clone(), block))?,
+            Inner::Revm(revm) => {
+                revm.simulate(tx)
+                    .measure("revm_simulate_gas")
+                    .await
+                    .map_err(with(tx.clone(), block))?
+                    .gas
+            }
         })
     }
 }
 
+impl Compare {
+    /// Reports if the simulation results disagree and picks the result to
+    /// use.
+    fn reconcile(
+        &self,
+        primary_kind: Kind,
+        tx: &eth::Tx,
+        block: eth::BlockNo,
+        primary: Result<eth::Gas, Error>,
+        secondary: Result<eth::Gas, Error>,
+    ) -> Result<eth::Gas, Error> {
+        let (kind, result) =
+            self.comparison
+                .reconcile(primary_kind, self.other.kind(), primary, secondary);
+        if let Some(kind) = kind {
+            (self.on_disagreement)(&Disagreement {
+                tx: tx.clone(),
+                block,
+                kind,
+            });
+        }
+        result
+    }
+}
+
+impl Comparison {
+    /// Returns how the simulation results disagree, if they do, together with
+    /// the result to use.
+    fn reconcile(
+        &self,
+        primary_kind: Kind,
+        secondary_kind: Kind,
+        primary: Result<eth::Gas, Error>,
+        secondary: Result<eth::Gas, Error>,
+    ) -> (Option<DisagreementKind>, Result<eth::Gas, Error>) {
+        // Errors unrelated to the transaction say nothing about the
+        // simulators agreeing.
+        let kind = match (&primary, &secondary) {
+            (Ok(_), Err(Error::Revert(_))) => Some(DisagreementKind::Revert(secondary_kind)),
+            (Err(Error::Revert(_)), Ok(_)) => Some(DisagreementKind::Revert(primary_kind)),
+            (Ok(primary), Ok(secondary))
+                if deviates(*primary, *secondary, self.max_gas_deviation) =>
+            {
+                Some(DisagreementKind::Gas {
+                    primary: *primary,
+                    secondary: *secondary,
+                })
+            }
+            _ => None,
+        };
+
+        if !self.prefer_stricter {
+            return (kind, primary);
+        }
+        let result = match (primary, secondary) {
+            (Ok(primary), Ok(secondary)) => Ok(primary.max(secondary)),
+            (Ok(_), Err(err @ Error::Revert(_))) => Err(err),
+            (primary, _) => primary,
+        };
+        (kind, result)
+    }
+}
+
+/// Whether the gas estimates differ by more than the given fraction of the
+/// lower one.
+fn deviates(a: eth::Gas, b: eth::Gas, max_deviation: f64) -> bool {
+    let (low, high) = (a.min(b), a.max(b));
+    let tolerance = low
+        .0
+        .checked_mul_f64(max_deviation)
+        .unwrap_or(eth::U256::MAX);
+    high.0 - low.0 > tolerance
+}
+
 #[derive(Debug, Clone)]
 enum Inner {
     Tenderly(tenderly::Tenderly),
     Ethereum,
     Enso(enso::Enso),
+    Revm(revm::Revm),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -152,6 +403,8 @@ pub enum SimulatorError {
     Blockchain(#[from] blockchain::Error),
     #[error("enso error: {0:?}")]
     Enso(#[from] enso::Error),
+    #[error("revm error: {0:?}")]
+    Revm(#[from] revm::Error),
     #[error("the simulated gas {0} exceeded the gas limit {1} provided in the solution")]
     GasExceeded(eth::Gas, eth::Gas),
 }
@@ -194,6 +447,8 @@ where
             }
             SimulatorError::Enso(enso::Error::Http(_)) => None,
             SimulatorError::Enso(enso::Error::Revert(_)) => Some(tx),
+            SimulatorError::Revm(revm::Error::Other(_)) => None,
+            SimulatorError::Revm(revm::Error::Revert(_)) => Some(tx),
             SimulatorError::GasExceeded(..) => Some(tx),
         };
         match tx {
@@ -202,3 +457,125 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_deviation() {
+        let gas = |gas: u64| eth::Gas(gas.into());
+        assert!(!deviates(gas(100_000), gas(105_000), 0.1));
+        assert!(!deviates(gas(110_000), gas(100_000), 0.1));
+        assert!(deviates(gas(100_000), gas(111_000), 0.1));
+        assert!(deviates(gas(100_000), gas(100_001), 0.));
+    }
+
+    fn comparison(prefer_stricter: bool) -> Comparison {
+        Comparison {
+            with: Kind::Revm,
+            max_gas_deviation: 0.1,
+            prefer_stricter,
+        }
+    }
+
+    fn gas(gas: u64) -> Result<eth::Gas, Error> {
+        Ok(eth::Gas(gas.into()))
+    }
+
+    fn revert() -> Result<eth::Gas, Error> {
+        let tx = eth::Tx {
+            from: Default::default(),
+            to: Default::default(),
+            value: eth::Ether(0.into()),
+            input: Default::default(),
+            access_list: Default::default(),
+        };
+        Err(with(tx, eth::BlockNo(0))(revm::Error::Revert(
+            "reverted".into(),
+        )))
+    }
+
+    fn other_error() -> Result<eth::Gas, Error> {
+        Err(Error::Other(SimulatorError::Revm(revm::Error::Other(
+            anyhow::anyhow!("connection refused"),
+        ))))
+    }
+
+    fn reconcile(
+        prefer_stricter: bool,
+        primary: Result<eth::Gas, Error>,
+        secondary: Result<eth::Gas, Error>,
+    ) -> (Option<DisagreementKind>, Result<eth::Gas, Error>) {
+        comparison(prefer_stricter).reconcile(Kind::Ethereum, Kind::Revm, primary, secondary)
+    }
+
+    #[test]
+    fn reports_reverting_simulator() {
+        for prefer_stricter in [false, true] {
+            let (kind, result) = reconcile(prefer_stricter, gas(100_000), revert());
+            assert!(matches!(kind, Some(DisagreementKind::Revert(Kind::Revm))));
+            if prefer_stricter {
+                assert!(matches!(result, Err(Error::Revert(_))));
+            } else {
+                assert_eq!(result.unwrap(), eth::Gas(100_000.into()));
+            }
+
+            let (kind, result) = reconcile(prefer_stricter, revert(), gas(100_000));
+            assert!(matches!(
+                kind,
+                Some(DisagreementKind::Revert(Kind::Ethereum))
+            ));
+            assert!(matches!(result, Err(Error::Revert(_))));
+        }
+    }
+
+    #[test]
+    fn ignores_errors_unrelated_to_the_transaction() {
+        for prefer_stricter in [false, true] {
+            let (kind, result) = reconcile(prefer_stricter, gas(100_000), other_error());
+            assert!(kind.is_none());
+            assert_eq!(result.unwrap(), eth::Gas(100_000.into()));
+
+            let (kind, result) = reconcile(prefer_stricter, other_error(), gas(100_000));
+            assert!(kind.is_none());
+            assert!(matches!(result, Err(Error::Other(_))));
+
+            let (kind, result) = reconcile(prefer_stricter, revert(), other_error());
+            assert!(kind.is_none());
+            assert!(matches!(result, Err(Error::Revert(_))));
+        }
+    }
+
+    #[test]
+    fn reports_diverging_gas() {
+        for prefer_stricter in [false, true] {
+            let (kind, result) = reconcile(prefer_stricter, gas(100_000), gas(105_000));
+            assert!(kind.is_none());
+            let expected = if prefer_stricter { 105_000 } else { 100_000 };
+            assert_eq!(result.unwrap(), eth::Gas(expected.into()));
+
+            let (kind, result) = reconcile(prefer_stricter, gas(100_000), gas(200_000));
+            assert!(matches!(
+                kind,
+                Some(DisagreementKind::Gas { primary, secondary })
+                    if primary == eth::Gas(100_000.into())
+                        && secondary == eth::Gas(200_000.into())
+            ));
+            let expected = if prefer_stricter { 200_000 } else { 100_000 };
+            assert_eq!(result.unwrap(), eth::Gas(expected.into()));
+        }
+    }
+
+    #[test]
+    fn prefers_the_stricter_result() {
+        let (_, result) = reconcile(true, gas(200_000), gas(100_000));
+        assert_eq!(result.unwrap(), eth::Gas(200_000.into()));
+
+        let (_, result) = reconcile(true, revert(), revert());
+        assert!(matches!(result, Err(Error::Revert(_))));
+
+        let (_, result) = reconcile(true, other_error(), revert());
+        assert!(matches!(result, Err(Error::Other(_))));
+    }
+}
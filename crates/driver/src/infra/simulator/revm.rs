@@ -0,0 +1,61 @@
+use {
+    crate::{domain::eth, infra::blockchain::Ethereum},
+    ethrpc::block_stream::CurrentBlockWatcher,
+    shared::code_simulation::{SimulationError, evm::RevmSimulator},
+    std::sync::Arc,
+    thiserror::Error,
+};
+
+/// Simulates transactions in a local EVM that lazily forks the state of the
+/// Ethereum node.
+#[derive(Clone)]
+pub(super) struct Revm {
+    simulator: Arc<RevmSimulator>,
+    current_block: CurrentBlockWatcher,
+}
+
+pub(super) struct Simulation {
+    pub gas: eth::Gas,
+    pub access_list: eth::AccessList,
+}
+
+impl Revm {
+    pub(super) fn new(eth: &Ethereum) -> Self {
+        Self {
+            simulator: Arc::new(RevmSimulator::new(eth.web3().clone(), eth.chain().id())),
+            current_block: eth.current_block().clone(),
+        }
+    }
+
+    pub(super) async fn simulate(&self, tx: &eth::Tx) -> Result<Simulation, Error> {
+        // Simulating on the block the driver currently works with lets all
+        // simulations of that block share the fetched state.
+        let block = self.current_block.borrow().number;
+        let simulation = self
+            .simulator
+            .simulate_call(tx.clone().into(), Default::default(), Some(block))
+            .await
+            .map_err(Error::Other)?;
+        match simulation.result {
+            Ok(_) => Ok(Simulation {
+                gas: eth::Gas(simulation.gas_used.into()),
+                access_list: simulation.access_list.into(),
+            }),
+            Err(SimulationError::Revert(reason)) => Err(Error::Revert(reason.unwrap_or_default())),
+            Err(SimulationError::Other(err)) => Err(Error::Other(err)),
+        }
+    }
+}
+
+impl std::fmt::Debug for Revm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Revm").finish()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("revm tx simulation error")]
+pub enum Error {
+    Other(anyhow::Error),
+    Revert(String),
+}
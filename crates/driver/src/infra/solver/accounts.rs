@@ -0,0 +1,230 @@
+//! The accounts a solver submits its settlements from.
+
+use {
+    crate::{
+        domain::eth,
+        infra::blockchain::{self, Ethereum},
+    },
+    std::sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    tokio::sync::Notify,
+};
+
+/// A pool of accounts that a solver submits its settlements from.
+///
+/// Every account submits at most one settlement at a time. This way the
+/// settlements of auctions won back-to-back are submitted in parallel from
+/// different accounts instead of queueing behind each other's nonces.
+///
+/// The account submitting a settlement is already chosen when the solution
+/// gets encoded, so that it is simulated with the actual sender and reported
+/// to the protocol as the submission address.
+#[derive(Debug)]
+pub struct Accounts {
+    accounts: Vec<ethcontract::Account>,
+    /// Which of the accounts are currently reserved for a settlement.
+    busy: Mutex<Vec<bool>>,
+    released: Notify,
+    /// The account to prefer for the next settlement.
+    next: AtomicUsize,
+}
+
+impl Accounts {
+    /// Creates a pool from the solver's primary account followed by its
+    /// additional submission accounts.
+    pub fn new(primary: ethcontract::Account, additional: Vec<ethcontract::Account>) -> Self {
+        let accounts: Vec<_> = std::iter::once(primary).chain(additional).collect();
+        Self {
+            busy: Mutex::new(vec![false; accounts.len()]),
+            accounts,
+            released: Notify::new(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of accounts, i.e. how many settlements can be submitted in
+    /// parallel.
+    pub fn count(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// The addresses of all accounts in the order in which they should be
+    /// preferred for submitting the next settlement. Idle accounts come
+    /// first and the accounts are rotated between calls so that settlements
+    /// get spread across all of them.
+    pub fn candidates(&self) -> Vec<eth::Address> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % self.accounts.len();
+        let busy = self.busy.lock().unwrap().clone();
+        let mut indices: Vec<_> = (0..self.accounts.len())
+            .map(|offset| (start + offset) % self.accounts.len())
+            .collect();
+        // The sort is stable, so the rotation is kept within idle and busy
+        // accounts.
+        indices.sort_by_key(|index| busy[*index]);
+        indices
+            .into_iter()
+            .map(|index| self.accounts[index].address().into())
+            .collect()
+    }
+
+    /// Reserves the account with the given address for submitting a
+    /// settlement and ensures that it holds at least the `required`
+    /// balance. If the account is busy with another settlement, this waits
+    /// until it is released.
+    pub async fn acquire(
+        self: &Arc<Self>,
+        eth: &Ethereum,
+        address: eth::Address,
+        required: eth::Ether,
+    ) -> Result<Lease, Error> {
+        let index = self
+            .accounts
+            .iter()
+            .position(|account| eth::Address::from(account.address()) == address)
+            .ok_or(Error::UnknownAccount(address))?;
+        loop {
+            // Register for release notifications before checking the account
+            // so that a release in between is not missed.
+            let released = self.released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+
+            if self.reserve(index) {
+                break;
+            }
+            released.await;
+        }
+
+        match self.check(eth, address, required).await {
+            Ok(Some(nonce)) => Ok(Lease {
+                accounts: self.clone(),
+                index,
+                nonce,
+            }),
+            Ok(None) => {
+                self.release(index);
+                Err(Error::InsufficientBalance)
+            }
+            Err(err) => {
+                self.release(index);
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Returns the next nonce of the account if it holds the required balance.
+    async fn check(
+        &self,
+        eth: &Ethereum,
+        address: eth::Address,
+        required: eth::Ether,
+    ) -> Result<Option<eth::U256>, blockchain::Error> {
+        let balance = eth.balance(address).await?;
+        tracing::debug!(?address, ?balance, ?required, "solver account balance");
+        if balance < required {
+            return Ok(None);
+        }
+        eth.nonce(address).await.map(Some)
+    }
+
+    fn reserve(&self, index: usize) -> bool {
+        let mut busy = self.busy.lock().unwrap();
+        !std::mem::replace(&mut busy[index], true)
+    }
+
+    fn release(&self, index: usize) {
+        self.busy.lock().unwrap()[index] = false;
+        self.released.notify_waiters();
+    }
+}
+
+/// An account reserved for submitting a single settlement. The account is
+/// released when the lease is dropped.
+#[derive(Debug)]
+pub struct Lease {
+    accounts: Arc<Accounts>,
+    index: usize,
+    nonce: eth::U256,
+}
+
+impl Lease {
+    pub fn address(&self) -> eth::Address {
+        self.account().address().into()
+    }
+
+    /// The account used for signing the transactions.
+    pub fn account(&self) -> &ethcontract::Account {
+        &self.accounts.accounts[self.index]
+    }
+
+    /// The nonce of the settlement transaction. Cancellations reuse it so
+    /// that they replace the pending settlement.
+    pub fn nonce(&self) -> eth::U256 {
+        self.nonce
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        self.accounts.release(self.index);
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("blockchain error: {0:?}")]
+    Blockchain(#[from] blockchain::Error),
+    #[error("the solver account has insufficient balance")]
+    InsufficientBalance,
+    #[error("{0:?} is not a submission account of the solver")]
+    UnknownAccount(eth::Address),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserves_each_account_once() {
+        let accounts = Accounts::new(
+            ethcontract::Account::Local(Default::default(), None),
+            vec![ethcontract::Account::Local(eth::H160([1; 20]), None)],
+        );
+        assert_eq!(accounts.count(), 2);
+
+        assert!(accounts.reserve(0));
+        assert!(!accounts.reserve(0));
+        assert!(accounts.reserve(1));
+
+        accounts.release(0);
+        assert!(accounts.reserve(0));
+        assert!(!accounts.reserve(1));
+    }
+
+    #[test]
+    fn prefers_idle_accounts_in_rotation() {
+        let address = |byte| eth::Address::from(eth::H160([byte; 20]));
+        let accounts = Accounts::new(
+            ethcontract::Account::Local(address(0).0, None),
+            vec![
+                ethcontract::Account::Local(address(1).0, None),
+                ethcontract::Account::Local(address(2).0, None),
+            ],
+        );
+
+        assert_eq!(accounts.candidates(), [address(0), address(1), address(2)]);
+        assert_eq!(accounts.candidates(), [address(1), address(2), address(0)]);
+
+        // A busy primary account makes the settlement go to a secondary one.
+        assert!(accounts.reserve(0));
+        assert_eq!(accounts.candidates(), [address(2), address(1), address(0)]);
+        assert!(accounts.reserve(2));
+        assert_eq!(accounts.candidates(), [address(1), address(0), address(2)]);
+
+        accounts.release(0);
+        assert_eq!(accounts.candidates(), [address(1), address(0), address(2)]);
+    }
+}
@@ -38,6 +38,7 @@ pub fn new(
                     reference_price: token.price.map(Into::into),
                     available_balance: token.available_balance,
                     trusted: token.trusted,
+                    transfer_fee_bps: token.transfer_fee.map(|fee| fee.bps()),
                 },
             )
         })
@@ -50,15 +51,21 @@ pub fn new(
         .flat_map(|liquidity| match &liquidity.kind {
             liquidity::Kind::UniswapV2(pool) => pool.reserves.iter().map(|r| r.token).collect(),
             liquidity::Kind::UniswapV3(pool) => vec![pool.tokens.get().0, pool.tokens.get().1],
+            liquidity::Kind::UniswapV4(pool) => {
+                vec![pool.key.tokens.get().0, pool.key.tokens.get().1]
+            }
             liquidity::Kind::BalancerV2Stable(pool) => pool.reserves.tokens().collect(),
             liquidity::Kind::BalancerV2Weighted(pool) => pool.reserves.tokens().collect(),
+            liquidity::Kind::BalancerV3(pool) => pool.tokens().collect(),
             liquidity::Kind::Swapr(pool) => pool.base.reserves.iter().map(|r| r.token).collect(),
+            liquidity::Kind::Curve(pool) => pool.tokens.clone(),
             liquidity::Kind::ZeroEx(limit_order) => {
                 vec![
                     limit_order.order.maker_token.into(),
                     limit_order.order.taker_token.into(),
                 ]
             }
+            liquidity::Kind::CowAmm(pool) => vec![pool.order.sell.token, pool.order.buy.token],
         })
     {
         tokens.entry(token.into()).or_insert_with(Default::default);
@@ -224,6 +231,29 @@ pub fn new(
                         },
                     )
                 }
+                liquidity::Kind::UniswapV4(pool) => solvers_dto::auction::Liquidity::UniswapV4(
+                    solvers_dto::auction::UniswapV4Pool {
+                        id: liquidity.id.0.to_string(),
+                        pool_id: pool.id,
+                        router: pool.router.into(),
+                        gas_estimate: liquidity.gas.0,
+                        tokens: vec![
+                            pool.key.tokens.get().0.into(),
+                            pool.key.tokens.get().1.into(),
+                        ],
+                        sqrt_price: pool.sqrt_price.0,
+                        liquidity: pool.liquidity.0,
+                        tick: pool.tick.0,
+                        liquidity_net: pool
+                            .liquidity_net
+                            .iter()
+                            .map(|(key, value)| (key.0, value.0))
+                            .collect(),
+                        fee: rational_to_big_decimal(&pool.fee.0),
+                        tick_spacing: pool.key.tick_spacing,
+                        hooks: pool.key.hooks.into(),
+                    },
+                ),
                 liquidity::Kind::BalancerV2Stable(pool) => {
                     solvers_dto::auction::Liquidity::Stable(solvers_dto::auction::StablePool {
                         id: liquidity.id.0.to_string(),
@@ -283,6 +313,79 @@ pub fn new(
                         },
                     )
                 }
+                liquidity::Kind::BalancerV3(pool) => match &pool.kind {
+                    liquidity::balancer::v3::Kind::Weighted(weights) => {
+                        solvers_dto::auction::Liquidity::BalancerV3Weighted(
+                            solvers_dto::auction::BalancerV3WeightedPool {
+                                id: liquidity.id.0.to_string(),
+                                address: pool.address.into(),
+                                gas_estimate: liquidity.gas.into(),
+                                tokens: pool
+                                    .reserves
+                                    .iter()
+                                    .zip(weights)
+                                    .map(|(r, weight)| {
+                                        (
+                                            r.asset.token.into(),
+                                            solvers_dto::auction::BalancerV3WeightedReserve {
+                                                balance: r.asset.amount.into(),
+                                                scaling_factor: bigdecimal::BigDecimal::new(
+                                                    r.scaling_factor.to_big_int(),
+                                                    0,
+                                                ),
+                                                rate: bigdecimal::BigDecimal::new(
+                                                    r.rate.to_big_int(),
+                                                    18,
+                                                ),
+                                                weight: bigdecimal::BigDecimal::new(
+                                                    weight.to_big_int(),
+                                                    18,
+                                                ),
+                                            },
+                                        )
+                                    })
+                                    .collect(),
+                                fee: fee_to_decimal(pool.fee),
+                            },
+                        )
+                    }
+                    liquidity::balancer::v3::Kind::Stable(amplification_parameter) => {
+                        solvers_dto::auction::Liquidity::BalancerV3Stable(
+                            solvers_dto::auction::BalancerV3StablePool {
+                                id: liquidity.id.0.to_string(),
+                                address: pool.address.into(),
+                                gas_estimate: liquidity.gas.into(),
+                                tokens: pool
+                                    .reserves
+                                    .iter()
+                                    .map(|r| {
+                                        (
+                                            r.asset.token.into(),
+                                            solvers_dto::auction::BalancerV3StableReserve {
+                                                balance: r.asset.amount.into(),
+                                                scaling_factor: bigdecimal::BigDecimal::new(
+                                                    r.scaling_factor.to_big_int(),
+                                                    0,
+                                                ),
+                                                rate: bigdecimal::BigDecimal::new(
+                                                    r.rate.to_big_int(),
+                                                    18,
+                                                ),
+                                            },
+                                        )
+                                    })
+                                    .collect(),
+                                amplification_parameter: rational_to_big_decimal(
+                                    &num::BigRational::new(
+                                        amplification_parameter.factor().to_big_int(),
+                                        amplification_parameter.precision().to_big_int(),
+                                    ),
+                                ),
+                                fee: fee_to_decimal(pool.fee),
+                            },
+                        )
+                    }
+                },
                 liquidity::Kind::Swapr(pool) => solvers_dto::auction::Liquidity::ConstantProduct(
                     solvers_dto::auction::ConstantProductPool {
                         id: liquidity.id.0.to_string(),
@@ -305,6 +408,42 @@ pub fn new(
                         fee: bigdecimal::BigDecimal::new(pool.fee.bps().into(), 4),
                     },
                 ),
+                liquidity::Kind::Curve(pool) => match &pool.state {
+                    liquidity::curve::State::Stable(state) => {
+                        solvers_dto::auction::Liquidity::CurveStable(
+                            solvers_dto::auction::CurveStablePool {
+                                id: liquidity.id.0.to_string(),
+                                address: pool.address.into(),
+                                gas_estimate: liquidity.gas.into(),
+                                tokens: pool.tokens.iter().copied().map(Into::into).collect(),
+                                balances: state.balances.clone(),
+                                rates: state.rates.clone(),
+                                amplification_parameter: state.amplification,
+                                fee: state.fee,
+                                offpeg_fee_multiplier: state.offpeg_fee_multiplier,
+                            },
+                        )
+                    }
+                    liquidity::curve::State::Crypto(state) => {
+                        solvers_dto::auction::Liquidity::CurveCrypto(
+                            solvers_dto::auction::CurveCryptoPool {
+                                id: liquidity.id.0.to_string(),
+                                address: pool.address.into(),
+                                gas_estimate: liquidity.gas.into(),
+                                tokens: pool.tokens.iter().copied().map(Into::into).collect(),
+                                balances: state.balances.clone(),
+                                precisions: state.precisions.clone(),
+                                price_scale: state.price_scale.clone(),
+                                amplification_parameter: state.amplification,
+                                gamma: state.gamma,
+                                d: state.d,
+                                mid_fee: state.mid_fee,
+                                out_fee: state.out_fee,
+                                fee_gamma: state.fee_gamma,
+                            },
+                        )
+                    }
+                },
                 liquidity::Kind::ZeroEx(limit_order) => {
                     solvers_dto::auction::Liquidity::LimitOrder(
                         solvers_dto::auction::ForeignLimitOrder {
@@ -320,6 +459,37 @@ pub fn new(
                         },
                     )
                 }
+                liquidity::Kind::CowAmm(pool) => {
+                    solvers_dto::auction::Liquidity::CowAmm(solvers_dto::auction::CowAmmPool {
+                        id: liquidity.id.0.to_string(),
+                        address: pool.address.into(),
+                        gas_estimate: liquidity.gas.into(),
+                        order: solvers_dto::auction::CowAmmOrder {
+                            sell_token: pool.order.sell.token.into(),
+                            buy_token: pool.order.buy.token.into(),
+                            receiver: pool.order.receiver.map(Into::into),
+                            sell_amount: pool.order.sell.amount.into(),
+                            buy_amount: pool.order.buy.amount.into(),
+                            valid_to: pool.order.valid_to,
+                            app_data: AppDataHash(pool.order.app_data.0.into()),
+                            signature: pool.order.signature.0.clone(),
+                            pre_interactions: pool
+                                .order
+                                .pre_interactions
+                                .iter()
+                                .cloned()
+                                .map(interaction_from_domain)
+                                .collect(),
+                            post_interactions: pool
+                                .order
+                                .post_interactions
+                                .iter()
+                                .cloned()
+                                .map(interaction_from_domain)
+                                .collect(),
+                        },
+                    })
+                }
             })
             .collect(),
         tokens,
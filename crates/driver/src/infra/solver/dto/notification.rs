@@ -33,8 +33,9 @@ pub fn new(
                     tokens: tokens.into_iter().map(|token| token.0.0).collect(),
                 }
             }
-            notify::Kind::SolverAccountInsufficientBalance(required) => {
+            notify::Kind::SolverAccountInsufficientBalance(account, required) => {
                 solvers_dto::notification::Kind::SolverAccountInsufficientBalance {
+                    account: account.0,
                     required: required.0,
                 }
             }
@@ -58,6 +59,19 @@ pub fn new(
             notify::Kind::PostprocessingTimedOut => {
                 solvers_dto::notification::Kind::PostprocessingTimedOut
             }
+            notify::Kind::SimulatorDisagreement(block, tx, reason) => {
+                solvers_dto::notification::Kind::SimulatorDisagreement {
+                    block: block.0,
+                    tx: solvers_dto::notification::Tx {
+                        from: tx.from.into(),
+                        to: tx.to.into(),
+                        input: tx.input.into(),
+                        value: tx.value.into(),
+                        access_list: tx.access_list.into(),
+                    },
+                    reason,
+                }
+            }
             notify::Kind::Banned { reason, until } => solvers_dto::notification::Kind::Banned {
                 reason: match reason {
                     notify::BanReason::UnsettledConsecutiveAuctions => {
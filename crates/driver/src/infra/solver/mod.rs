@@ -13,21 +13,24 @@ use {
         },
         infra::{
             blockchain::Ethereum,
-            config::file::FeeHandler,
+            config::file::{FeeHandler, OrderPriorityStrategy},
             persistence::{Persistence, S3},
+            simulator,
         },
         util,
     },
+    accounts::Accounts,
     anyhow::Result,
     derive_more::{From, Into},
     num::BigRational,
     reqwest::header::HeaderName,
-    std::{collections::HashMap, time::Duration},
+    std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration},
     tap::TapFallible,
     thiserror::Error,
     tracing::Instrument,
 };
 
+pub mod accounts;
 pub mod dto;
 
 // TODO At some point I should be checking that the names are unique, I don't
@@ -93,6 +96,7 @@ pub struct Solver {
     config: Config,
     eth: Ethereum,
     persistence: Persistence,
+    accounts: Arc<Accounts>,
 }
 
 #[derive(Debug, Clone)]
@@ -106,6 +110,9 @@ pub struct Config {
     pub liquidity: Liquidity,
     /// The private key of this solver, used for settlement submission.
     pub account: ethcontract::Account,
+    /// Additional accounts used for settlement submission, allowing multiple
+    /// settlements to be submitted in parallel.
+    pub submission_accounts: Vec<ethcontract::Account>,
     /// How much time to spend for each step of the solving and competition.
     pub timeouts: Timeouts,
     /// HTTP headers that should be added to every request.
@@ -119,6 +126,10 @@ pub struct Config {
     /// S3 configuration for storing the auctions in the form they are sent to
     /// the solver engine
     pub s3: Option<S3>,
+    /// Local directory for storing the archives instead of S3.
+    pub archive_directory: Option<PathBuf>,
+    /// Whether to archive the competition records of every auction.
+    pub archive_competition: bool,
     /// Whether the native token is wrapped or not when sent to the solvers
     pub solver_native_token: ManageNativeToken,
     /// Which `tx.origin` is required to make quote verification pass.
@@ -129,6 +140,99 @@ pub struct Config {
     pub settle_queue_size: usize,
     /// Whether flashloan hints should be sent to the solver.
     pub flashloans_enabled: bool,
+    /// Whether the driver wraps solutions into the flashloans hinted by their
+    /// orders.
+    pub wrap_flashloans: bool,
+    /// The simulator for this solver's settlements. Uses the driver's
+    /// default simulator if not set.
+    pub simulator: Option<simulator::Kind>,
+    /// Compares the results of the simulator with another one.
+    pub simulation_comparison: Option<simulator::Comparison>,
+    /// Restrictions on the orders sent to the solver.
+    pub order_filters: auction::OrderFilters,
+    /// Prioritizes the orders sent to the solver. Uses the driver's
+    /// strategies if not set.
+    pub order_priority_strategies: Option<Vec<OrderPriorityStrategy>>,
+    /// Routes the settlements of the solver through this contract instead of
+    /// calling the settlement contract directly.
+    pub executor: Option<Executor>,
+}
+
+/// A solver-owned contract that settlements are routed through, for example
+/// to perform additional actions before and after settling. The contract
+/// has to be an allow-listed solver since it is the one calling the
+/// settlement contract.
+#[derive(Debug, Clone)]
+pub struct Executor {
+    pub address: eth::Address,
+    selector: [u8; 4],
+    params: Vec<ExecutorParam>,
+}
+
+/// The parameters of the executor function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExecutorParam {
+    /// The contract the executor should call, either the settlement contract
+    /// or the flashloan router.
+    Target,
+    /// The calldata the executor should forward.
+    Calldata,
+}
+
+impl Executor {
+    /// Creates an executor from the signature of the function the settlement
+    /// calls get wrapped in, like `execute(address,bytes)`. The function may
+    /// take an `address` that receives the target contract and has to take
+    /// `bytes` that receive the calldata for it.
+    pub fn try_new(address: eth::Address, signature: &str) -> Result<Self> {
+        let (name, params) = signature
+            .trim()
+            .strip_suffix(')')
+            .and_then(|signature| signature.split_once('('))
+            .ok_or_else(|| anyhow::anyhow!("malformed function signature"))?;
+        let params = params
+            .split(',')
+            .map(str::trim)
+            .filter(|param| !param.is_empty())
+            .map(|param| match param {
+                "address" => Ok(ExecutorParam::Target),
+                "bytes" => Ok(ExecutorParam::Calldata),
+                _ => Err(anyhow::anyhow!("unsupported parameter type {param}")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let count = |kind| params.iter().filter(|param| **param == kind).count();
+        anyhow::ensure!(
+            count(ExecutorParam::Calldata) == 1 && count(ExecutorParam::Target) <= 1,
+            "the function must take one bytes and at most one address parameter"
+        );
+        let types = params
+            .iter()
+            .map(|param| match param {
+                ExecutorParam::Target => ethabi::ParamType::Address,
+                ExecutorParam::Calldata => ethabi::ParamType::Bytes,
+            })
+            .collect::<Vec<_>>();
+        Ok(Self {
+            address,
+            selector: ethabi::short_signature(name.trim(), &types),
+            params,
+        })
+    }
+
+    /// The calldata for the executor to call `target` with `calldata`.
+    pub fn wrap(&self, target: eth::Address, calldata: Vec<u8>) -> Vec<u8> {
+        let tokens = self
+            .params
+            .iter()
+            .map(|param| match param {
+                ExecutorParam::Target => ethabi::Token::Address(target.0),
+                ExecutorParam::Calldata => ethabi::Token::Bytes(calldata.clone()),
+            })
+            .collect::<Vec<_>>();
+        let mut wrapped = self.selector.to_vec();
+        wrapped.extend(ethabi::encode(&tokens));
+        wrapped
+    }
 }
 
 impl Solver {
@@ -146,6 +250,10 @@ impl Solver {
         }
 
         let persistence = Persistence::build(&config).await;
+        let accounts = Arc::new(Accounts::new(
+            config.account.clone(),
+            config.submission_accounts.clone(),
+        ));
 
         Ok(Self {
             client: reqwest::ClientBuilder::new()
@@ -154,6 +262,7 @@ impl Solver {
             config,
             eth,
             persistence,
+            accounts,
         })
     }
 
@@ -189,6 +298,12 @@ impl Solver {
         self.config.account.clone()
     }
 
+    /// The accounts this solver submits settlements from, including its
+    /// primary [`Solver::account`].
+    pub fn accounts(&self) -> &Arc<Accounts> {
+        &self.accounts
+    }
+
     /// Timeout configuration for this solver.
     pub fn timeouts(&self) -> Timeouts {
         self.config.timeouts
@@ -203,6 +318,41 @@ impl Solver {
         self.config.merge_solutions
     }
 
+    pub fn simulator(&self) -> Option<simulator::Kind> {
+        self.config.simulator
+    }
+
+    pub fn simulation_comparison(&self) -> Option<simulator::Comparison> {
+        self.config.simulation_comparison
+    }
+
+    pub fn order_filters(&self) -> &auction::OrderFilters {
+        &self.config.order_filters
+    }
+
+    pub fn order_priority_strategies(&self) -> Option<&[OrderPriorityStrategy]> {
+        self.config.order_priority_strategies.as_deref()
+    }
+
+    pub fn executor(&self) -> Option<&Executor> {
+        self.config.executor.as_ref()
+    }
+
+    /// The address calling the settlement contract when submitting from the
+    /// given solver account. This is the executor if the solver has one and
+    /// the account itself otherwise.
+    pub fn submission_address(&self, account: eth::Address) -> eth::Address {
+        self.config
+            .executor
+            .as_ref()
+            .map(|executor| executor.address)
+            .unwrap_or(account)
+    }
+
+    pub fn wrap_flashloans(&self) -> bool {
+        self.config.wrap_flashloans
+    }
+
     pub fn solver_native_token(&self) -> ManageNativeToken {
         self.config.solver_native_token
     }
@@ -255,6 +405,9 @@ impl Solver {
         let res = util::http::send(self.config.response_size_limit_max_bytes, req).await;
         super::observe::solver_response(&url, res.as_deref());
         let res = res?;
+        if let Some(id) = auction.id() {
+            self.persistence.archive_solver_response(id, &res);
+        }
         let res: solvers_dto::solution::Solutions = serde_json::from_str(&res)
             .tap_err(|err| tracing::warn!(res, ?err, "failed to parse solver response"))?;
         let solutions = dto::Solutions::from(res).into_domain(
@@ -329,8 +482,44 @@ pub struct BadTokenDetection {
     pub tokens_supported: HashMap<eth::TokenAddress, bad_tokens::Quality>,
     pub enable_simulation_strategy: bool,
     pub enable_metrics_strategy: bool,
+    pub enable_fee_on_transfer: bool,
     pub metrics_strategy_failure_ratio: f64,
     pub metrics_strategy_required_measurements: u32,
     pub metrics_strategy_log_only: bool,
     pub metrics_strategy_token_freeze_time: Duration,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_calls_into_executor() {
+        let executor =
+            Executor::try_new(eth::H160([1; 20]).into(), "execute(address, bytes)").unwrap();
+        let calldata = executor.wrap(eth::H160([2; 20]).into(), vec![3; 4]);
+
+        assert_eq!(
+            calldata[..4],
+            ethabi::short_signature(
+                "execute",
+                &[ethabi::ParamType::Address, ethabi::ParamType::Bytes]
+            )
+        );
+        assert_eq!(
+            ethabi::decode(
+                &[ethabi::ParamType::Address, ethabi::ParamType::Bytes],
+                &calldata[4..]
+            )
+            .unwrap(),
+            vec![
+                ethabi::Token::Address(eth::H160([2; 20])),
+                ethabi::Token::Bytes(vec![3; 4])
+            ]
+        );
+
+        assert!(Executor::try_new(eth::H160([1; 20]).into(), "execute(bytes)").is_ok());
+        assert!(Executor::try_new(eth::H160([1; 20]).into(), "execute(address)").is_err());
+        assert!(Executor::try_new(eth::H160([1; 20]).into(), "execute(uint256,bytes)").is_err());
+    }
+}
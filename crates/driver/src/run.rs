@@ -11,7 +11,7 @@ use {
             cli,
             config,
             liquidity,
-            simulator::{self, Simulator},
+            simulator::{self, Simulator, Simulators},
             solver::Solver,
         },
     },
@@ -64,7 +64,7 @@ async fn run_with(args: cli::Args, addr_sender: Option<oneshot::Sender<SocketAdd
     let serve = Api {
         solvers: solvers(&config, &eth).await,
         liquidity: liquidity(&config, &eth).await,
-        simulator: simulator(&config, &eth),
+        simulators: simulators(&config, &eth),
         mempools: Mempools::try_new(
             config
                 .mempools
@@ -78,6 +78,7 @@ async fn run_with(args: cli::Args, addr_sender: Option<oneshot::Sender<SocketAdd
         .unwrap(),
         bad_token_detector: bad_tokens::simulation::Detector::new(
             config.simulation_bad_token_max_age,
+            config.simulation_bad_token_max_transfer_fee,
             &eth,
         ),
         eth,
@@ -107,9 +108,18 @@ async fn run_with(args: cli::Args, addr_sender: Option<oneshot::Sender<SocketAdd
     };
 }
 
-fn simulator(config: &infra::Config, eth: &Ethereum) -> Simulator {
-    let mut simulator = match &config.simulator {
-        Some(infra::simulator::Config::Tenderly(tenderly)) => Simulator::tenderly(
+fn simulators(config: &infra::Config, eth: &Ethereum) -> Simulators {
+    let configure = |mut simulator: Simulator| {
+        if config.disable_access_list_simulation {
+            simulator.disable_access_lists()
+        }
+        if let Some(gas) = config.disable_gas_simulation {
+            simulator.disable_gas(gas)
+        }
+        simulator
+    };
+    let tenderly = config.simulator.tenderly.as_ref().map(|tenderly| {
+        Simulator::tenderly(
             simulator::tenderly::Config {
                 url: tenderly.url.to_owned(),
                 api_key: tenderly.api_key.to_owned(),
@@ -119,23 +129,23 @@ fn simulator(config: &infra::Config, eth: &Ethereum) -> Simulator {
                 save_if_fails: tenderly.save_if_fails,
             },
             eth.to_owned(),
-        ),
-        Some(infra::simulator::Config::Enso(enso)) => Simulator::enso(
+        )
+    });
+    let enso = config.simulator.enso.as_ref().map(|enso| {
+        Simulator::enso(
             simulator::enso::Config {
                 url: enso.url.to_owned(),
                 network_block_interval: enso.network_block_interval.to_owned(),
             },
             eth.to_owned(),
-        ),
-        None => Simulator::ethereum(eth.to_owned()),
-    };
-    if config.disable_access_list_simulation {
-        simulator.disable_access_lists()
-    }
-    if let Some(gas) = config.disable_gas_simulation {
-        simulator.disable_gas(gas)
-    }
-    simulator
+        )
+    });
+    Simulators::new(
+        configure(Simulator::ethereum(eth.to_owned())),
+        configure(Simulator::revm(eth.to_owned())),
+        tenderly.map(configure),
+        enso.map(configure),
+    )
 }
 
 async fn ethrpc(args: &cli::Args) -> blockchain::Rpc {
@@ -1,6 +1,11 @@
 use {
     crate::{
-        infra::config::file::{FeeHandler, OrderPriorityStrategy},
+        infra::config::file::{
+            FeeHandler,
+            OrderPriorityStrategy,
+            PriorityKey,
+            WeightedPriorityKey,
+        },
         tests::{
             cases::EtherExt,
             setup::{Order, OrderQuote, ab_order, ab_pool, ab_solution, setup, test_solver},
@@ -242,6 +247,115 @@ async fn mixed_custom_sorting() {
     test.solve().await.ok();
 }
 
+/// Orders generating more surplus at the auction's native prices get
+/// prioritized no matter other factors.
+#[tokio::test]
+#[ignore]
+async fn estimated_surplus_sorting() {
+    let now = Utc::now().timestamp() as u32;
+    let solver = test_solver().fee_handler(FeeHandler::Driver);
+    let test = setup()
+        .solvers(vec![solver.clone()])
+        .pool(ab_pool())
+        .order_priority_strategy(OrderPriorityStrategy::EstimatedSurplus)
+        .order(ab_order().created(now - 20))
+        .order(
+            ab_order()
+                .rename("2")
+                .created(now - 10)
+                .reduce_amount("1e-2".ether().into_wei()),
+        )
+        .order(
+            ab_order()
+                .rename("3")
+                .created(now)
+                .reduce_amount("1e-1".ether().into_wei())
+                .quote(OrderQuote::default().solver(solver.address())),
+        )
+        .solution(ab_solution())
+        .done()
+        .await;
+
+    test.solve().await.ok();
+}
+
+/// Orders are sorted by the weighted sum of the configured keys. Without any
+/// settlement history all orders have the same fill probability, so the
+/// recent orders with own quotes come first.
+#[tokio::test]
+#[ignore]
+async fn weighted_sorting() {
+    let now = Utc::now().timestamp() as u32;
+    let solver = test_solver().fee_handler(FeeHandler::Driver);
+    let key = |key, weight| WeightedPriorityKey { key, weight };
+    let test = setup()
+        .solvers(vec![solver.clone()])
+        .pool(ab_pool())
+        .order_priority_strategy(OrderPriorityStrategy::Weighted {
+            keys: vec![
+                key(PriorityKey::OwnQuotes, 10.),
+                key(PriorityKey::OrderAge, -1.),
+                key(PriorityKey::FillProbability, 1.),
+            ],
+        })
+        .order(
+            ab_order()
+                .created(now - 60)
+                .reduce_amount("1e-1".ether().into_wei())
+                .quote(OrderQuote::default().solver(solver.address())),
+        )
+        .order(ab_order().rename("2").created(now))
+        .order(
+            ab_order()
+                .rename("3")
+                .created(now - 600)
+                .valid_to(u32::MAX - 1),
+        )
+        .solution(ab_solution())
+        .done()
+        .await;
+
+    test.solve().await.ok();
+}
+
+/// The order priority strategies configured for a solver replace the ones of
+/// the driver. Without any settlement history all orders have the same fill
+/// probability, so orders are sorted by external price even though the driver
+/// would prioritize the most recent orders.
+#[tokio::test]
+#[ignore]
+async fn solver_order_priority_sorting() {
+    let now = Utc::now().timestamp() as u32;
+    let solver = test_solver()
+        .fee_handler(FeeHandler::Driver)
+        .order_priority_strategy(OrderPriorityStrategy::FillProbability)
+        .order_priority_strategy(OrderPriorityStrategy::ExternalPrice);
+    let test = setup()
+        .solvers(vec![solver.clone()])
+        .pool(ab_pool())
+        .order_priority_strategy(OrderPriorityStrategy::CreationTimestamp {
+            max_order_age: None,
+        })
+        .order(ab_order().created(now - 20))
+        .order(
+            ab_order()
+                .rename("2")
+                .created(now - 10)
+                .reduce_amount("1e-2".ether().into_wei()),
+        )
+        .order(
+            ab_order()
+                .rename("3")
+                .created(now)
+                .reduce_amount("1e-1".ether().into_wei()),
+        )
+        .solution(ab_solution())
+        .done()
+        .await;
+
+    test.solve().await.ok();
+}
+
 /// If a user does not have enough tokens to settle all their orders filter out
 /// the least likely to settle ones that go over the user's budget.
 #[tokio::test]
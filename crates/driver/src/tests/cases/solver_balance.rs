@@ -49,3 +49,38 @@ async fn test_just_enough_funded() {
         .ok()
         .await;
 }
+
+/// Test that a settlement is simulated with and submitted from an additional
+/// submission account if the solver's primary account can't pay for it.
+#[tokio::test]
+#[ignore]
+async fn test_settles_from_submission_account() {
+    let submission_account = ethcontract::PrivateKey::from_slice(
+        hex::decode("f9a1f9f1c2b3d8e3b5bb33cbad6a2b09c3dd2a8b4ac14ec0e6f1bb9f7b8f0a56").unwrap(),
+    )
+    .unwrap();
+    let test = setup()
+        .pool(ab_pool())
+        .order(ab_order())
+        .solution(ab_solution())
+        .solvers(vec![
+            test_solver()
+                .name("secondary")
+                .balance(eth::U256::zero())
+                .submission_account(submission_account.clone(), eth::U256::exp10(18)),
+        ])
+        .done()
+        .await;
+
+    let solve = test.solve_with_solver("secondary").await.ok();
+    assert_eq!(
+        solve.submission_address(),
+        submission_account.public_address()
+    );
+    test.settle_with_solver("secondary", solve.id())
+        .await
+        .ok()
+        .await
+        .ab_order_executed(&test)
+        .await;
+}
@@ -365,6 +365,30 @@ impl Blockchain {
             .await
             .unwrap();
 
+            for (private_key, balance) in &config.submission_accounts {
+                wait_for(
+                    &web3,
+                    authenticator
+                        .add_solver(private_key.public_address())
+                        .from(main_trader_account.clone())
+                        .send(),
+                )
+                .await
+                .unwrap();
+                wait_for(
+                    &web3,
+                    web3.eth()
+                        .send_transaction(web3::types::TransactionRequest {
+                            from: primary_address(&web3).await,
+                            to: Some(private_key.public_address()),
+                            value: Some(*balance),
+                            ..Default::default()
+                        }),
+                )
+                .await
+                .unwrap();
+            }
+
             if !config.balance.is_zero() {
                 let trader_account = ethcontract::Account::Offline(
                     ethcontract::PrivateKey::from_slice(config.private_key.as_ref()).unwrap(),
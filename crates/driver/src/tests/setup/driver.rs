@@ -2,7 +2,7 @@ use {
     super::{Mempool, Partial, Solver, Test, blockchain::Blockchain},
     crate::{
         domain::competition::order,
-        infra::config::file::OrderPriorityStrategy,
+        infra::config::file::{OrderPriorityStrategy, PriorityKey},
         tests::{
             hex_address,
             setup::{blockchain::Trade, orderbook::Orderbook},
@@ -269,43 +269,12 @@ async fn create_config_file(
     }
 
     for strategy in &config.order_priority_strategies {
-        match strategy {
-            OrderPriorityStrategy::ExternalPrice => write!(
-                file,
-                r#"[[order-priority]]
-                strategy = "external-price"
-                "#,
-            )
-            .unwrap(),
-            OrderPriorityStrategy::CreationTimestamp { max_order_age } => {
-                let max_order_age = max_order_age
-                    .map(|age| format!("max-order-age = \"{:?}\"", age))
-                    .unwrap_or_else(|| "".to_string());
-                write!(
-                    file,
-                    r#"[[order-priority]]
-                    strategy = "creation-timestamp"
-                    {}
-                    "#,
-                    max_order_age,
-                )
-                .unwrap()
-            }
-            OrderPriorityStrategy::OwnQuotes { max_order_age } => {
-                let max_order_age = max_order_age
-                    .map(|age| format!("max-order-age = \"{:?}\"", age))
-                    .unwrap_or_else(|| "".to_string());
-                write!(
-                    file,
-                    r#"[[order-priority]]
-                    strategy = "own-quotes"
-                    {}
-                    "#,
-                    max_order_age,
-                )
-                .unwrap()
-            }
-        }
+        write!(
+            file,
+            "[[order-priority]]\n{}\n",
+            order_priority_fields(strategy).join("\n"),
+        )
+        .unwrap();
     }
 
     for (solver, addr) in solvers {
@@ -321,6 +290,8 @@ async fn create_config_file(
                http-time-buffer = "{}ms"
                fee-handler = {}
                merge-solutions = {}
+               submission-accounts = [{}]
+               {}
                "#,
             solver.name,
             addr,
@@ -335,8 +306,77 @@ async fn create_config_file(
             solver.timeouts.http_delay.num_milliseconds(),
             serde_json::to_string(&solver.fee_handler).unwrap(),
             solver.merge_solutions,
+            solver
+                .submission_accounts
+                .iter()
+                .map(|(private_key, _)| format!(
+                    "\"0x{}\"",
+                    hex::encode(private_key.secret_bytes())
+                ))
+                .collect::<Vec<_>>()
+                .join(", "),
+            match solver.order_priority_strategies.is_empty() {
+                true => String::new(),
+                false => format!(
+                    "order-priority = [{}]",
+                    solver
+                        .order_priority_strategies
+                        .iter()
+                        .map(|strategy| format!(
+                            "{{ {} }}",
+                            order_priority_fields(strategy).join(", ")
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
         )
         .unwrap();
     }
     file.into_temp_path()
 }
+
+/// The fields of the TOML table configuring the order priority strategy.
+fn order_priority_fields(strategy: &OrderPriorityStrategy) -> Vec<String> {
+    let with_max_order_age =
+        |strategy: &str, max_order_age: &Option<std::time::Duration>| -> Vec<String> {
+            std::iter::once(format!("strategy = \"{strategy}\""))
+                .chain(max_order_age.map(|age| format!("max-order-age = \"{:?}\"", age)))
+                .collect()
+        };
+    match strategy {
+        OrderPriorityStrategy::ExternalPrice => vec![r#"strategy = "external-price""#.to_owned()],
+        OrderPriorityStrategy::CreationTimestamp { max_order_age } => {
+            with_max_order_age("creation-timestamp", max_order_age)
+        }
+        OrderPriorityStrategy::OwnQuotes { max_order_age } => {
+            with_max_order_age("own-quotes", max_order_age)
+        }
+        OrderPriorityStrategy::FillProbability => {
+            vec![r#"strategy = "fill-probability""#.to_owned()]
+        }
+        OrderPriorityStrategy::EstimatedSurplus => {
+            vec![r#"strategy = "estimated-surplus""#.to_owned()]
+        }
+        OrderPriorityStrategy::Weighted { keys } => {
+            let keys = keys
+                .iter()
+                .map(|key| {
+                    let name = match key.key {
+                        PriorityKey::ExternalPrice => "external-price",
+                        PriorityKey::EstimatedSurplus => "estimated-surplus",
+                        PriorityKey::FillProbability => "fill-probability",
+                        PriorityKey::OwnQuotes => "own-quotes",
+                        PriorityKey::OrderAge => "order-age",
+                    };
+                    format!("{{ key = \"{name}\", weight = {:?} }}", key.weight)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            vec![
+                r#"strategy = "weighted""#.to_owned(),
+                format!("keys = [{keys}]"),
+            ]
+        }
+    }
+}
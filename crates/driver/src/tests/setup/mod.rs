@@ -357,6 +357,11 @@ pub struct Solver {
     /// Whether or not solver is allowed to combine multiple solutions into a
     /// new one.
     merge_solutions: bool,
+    /// Additional accounts for submitting settlements and the ETH balance
+    /// they are funded with.
+    submission_accounts: Vec<(ethcontract::PrivateKey, eth::U256)>,
+    /// Order priority strategies replacing the driver's ones for this solver.
+    order_priority_strategies: Vec<OrderPriorityStrategy>,
 }
 
 #[derive(Debug, Clone)]
@@ -384,6 +389,8 @@ pub fn test_solver() -> Solver {
         },
         fee_handler: FeeHandler::default(),
         merge_solutions: false,
+        submission_accounts: Vec::new(),
+        order_priority_strategies: Vec::new(),
     }
 }
 
@@ -422,6 +429,24 @@ impl Solver {
         self.merge_solutions = true;
         self
     }
+
+    /// Adds an additional account for submitting settlements which gets
+    /// funded with the given ETH balance.
+    pub fn submission_account(
+        mut self,
+        private_key: ethcontract::PrivateKey,
+        balance: eth::U256,
+    ) -> Self {
+        self.submission_accounts.push((private_key, balance));
+        self
+    }
+
+    /// Prioritizes the orders sent to this solver with the strategy instead
+    /// of the driver's strategies.
+    pub fn order_priority_strategy(mut self, strategy: OrderPriorityStrategy) -> Self {
+        self.order_priority_strategies.push(strategy);
+        self
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -1290,6 +1315,15 @@ impl SolveOk<'_> {
         eth::U256::from_dec_str(score).unwrap()
     }
 
+    /// Extracts the submission address from the response. Since response can
+    /// contain multiple solutions, it takes the address from the first
+    /// solution.
+    pub fn submission_address(&self) -> eth::H160 {
+        let solution = self.solution();
+        let address = solution.get("submissionAddress").unwrap().as_str().unwrap();
+        address.parse().unwrap()
+    }
+
     /// Ensures that `/solve` returns no solutions.
     pub fn empty(self) {
         assert!(self.solutions().is_empty());
@@ -119,6 +119,51 @@ impl<T: Transport> Debug<T> {
                 .execute("debug_traceTransaction", vec![hash, tracing_options]),
         )
     }
+
+    /// Executes the call and returns every executed opcode together with
+    /// the stack and memory at that time (default struct logger).
+    pub fn call_struct_logs(
+        &self,
+        call: CallRequest,
+        block: BlockId,
+    ) -> CallFuture<StructLogTrace, T::Out> {
+        let call = helpers::serialize(&call);
+        let block = helpers::serialize(&block);
+        let tracing_options = serde_json::json!({
+            "enableMemory": true,
+            "disableStorage": true,
+            "enableReturnData": false,
+        });
+        CallFuture::new(
+            self.transport()
+                .execute("debug_traceCall", vec![call, block, tracing_options]),
+        )
+    }
+}
+
+/// Result of the default struct logger.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogTrace {
+    #[serde(default)]
+    pub failed: bool,
+    #[serde(default)]
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// A single executed opcode of a [`StructLogTrace`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct StructLog {
+    /// Name of the opcode (e.g. `SLOAD`).
+    pub op: String,
+    /// Call depth starting at 1 for the outermost call.
+    pub depth: usize,
+    /// Stack before executing the opcode. The last item is the top.
+    #[serde(default)]
+    pub stack: Vec<U256>,
+    /// Memory before executing the opcode as hex encoded 32 byte words.
+    #[serde(default)]
+    pub memory: Vec<String>,
 }
 
 /// Taken from alloy::rpc::types::trace::geth::CallFrame
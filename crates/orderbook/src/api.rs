@@ -1,5 +1,11 @@
 use {
-    crate::{app_data, database::Postgres, orderbook::Orderbook, quoter::QuoteHandler},
+    crate::{
+        api_keys::{ApiKeyError, ApiKeys},
+        app_data,
+        database::Postgres,
+        orderbook::Orderbook,
+        quoter::QuoteHandler,
+    },
     anyhow::Result,
     serde::{Serialize, de::DeserializeOwned},
     shared::price_estimation::{PriceEstimationError, native::NativePriceEstimating},
@@ -16,6 +22,7 @@ use {
 
 mod cancel_order;
 mod cancel_orders;
+mod get_api_key_usage;
 mod get_app_data;
 mod get_auction;
 mod get_native_price;
@@ -23,6 +30,7 @@ mod get_order_by_uid;
 mod get_order_status;
 mod get_orders_by_tx;
 mod get_solver_competition;
+mod get_token_balance_override;
 mod get_token_metadata;
 mod get_total_surplus;
 mod get_trades;
@@ -37,6 +45,7 @@ pub fn handle_all_routes(
     orderbook: Arc<Orderbook>,
     quotes: Arc<QuoteHandler>,
     app_data: Arc<app_data::Registry>,
+    api_keys: Arc<ApiKeys>,
     native_price_estimator: Arc<dyn NativePriceEstimating>,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     // Note that we add a string with endpoint's name to all responses.
@@ -46,7 +55,7 @@ pub fn handle_all_routes(
     let routes = vec![
         (
             "v1/create_order",
-            box_filter(post_order::post_order(orderbook.clone())),
+            box_filter(post_order::post_order(orderbook.clone(), api_keys.clone())),
         ),
         (
             "v1/get_order",
@@ -76,7 +85,10 @@ pub fn handle_all_routes(
             "v1/get_orders_by_tx",
             box_filter(get_orders_by_tx::get_orders_by_tx(orderbook.clone())),
         ),
-        ("v1/post_quote", box_filter(post_quote::post_quote(quotes))),
+        (
+            "v1/post_quote",
+            box_filter(post_quote::post_quote(quotes, api_keys.clone())),
+        ),
         (
             "v1/auction",
             box_filter(get_auction::get_auction(orderbook.clone())),
@@ -110,7 +122,17 @@ pub fn handle_all_routes(
         ),
         (
             "v1/get_token_metadata",
-            box_filter(get_token_metadata::get_token_metadata(database)),
+            box_filter(get_token_metadata::get_token_metadata(database.clone())),
+        ),
+        (
+            "v1/get_token_balance_override",
+            box_filter(get_token_balance_override::get_token_balance_override(
+                database,
+            )),
+        ),
+        (
+            "v1/get_api_key_usage",
+            box_filter(get_api_key_usage::get(api_keys)),
         ),
     ];
 
@@ -254,6 +276,26 @@ pub async fn response_body(response: warp::hyper::Response<warp::hyper::Body>) -
     result
 }
 
+/// Extracts the optional API key identifying the client.
+pub fn api_key() -> impl Filter<Extract = (Option<String>,), Error = Rejection> + Clone {
+    warp::header::optional("X-API-Key")
+}
+
+impl IntoWarpReply for ApiKeyError {
+    fn into_warp_reply(self) -> ApiReply {
+        match self {
+            Self::Unknown => with_status(
+                error("UnknownApiKey", "The provided API key is not known"),
+                StatusCode::UNAUTHORIZED,
+            ),
+            Self::RateLimited(_) => with_status(
+                error("TooManyRequests", "Request limit of the API key exceeded"),
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+        }
+    }
+}
+
 const MAX_JSON_BODY_PAYLOAD: u64 = 1024 * 16;
 
 pub fn extract_payload<T: DeserializeOwned + Send>()
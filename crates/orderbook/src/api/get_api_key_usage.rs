@@ -0,0 +1,30 @@
+use {
+    crate::api_keys::ApiKeys,
+    std::{convert::Infallible, sync::Arc},
+    warp::{Filter, Rejection, hyper::StatusCode, reply},
+};
+
+fn request() -> impl Filter<Extract = (Option<String>,), Error = Rejection> + Clone {
+    warp::path!("v1" / "admin" / "api_keys")
+        .and(warp::get())
+        .and(warp::header::optional("X-Admin-Key"))
+}
+
+pub fn get(
+    api_keys: Arc<ApiKeys>,
+) -> impl Filter<Extract = (super::ApiReply,), Error = Rejection> + Clone {
+    request().and_then(move |admin_key: Option<String>| {
+        let api_keys = api_keys.clone();
+        async move {
+            let response = if api_keys.is_admin(admin_key.as_deref()) {
+                reply::with_status(reply::json(&api_keys.usage()), StatusCode::OK)
+            } else {
+                reply::with_status(
+                    super::error("Unauthorized", "Invalid admin key"),
+                    StatusCode::UNAUTHORIZED,
+                )
+            };
+            Result::<_, Infallible>::Ok(response)
+        }
+    })
+}
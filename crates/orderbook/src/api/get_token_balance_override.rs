@@ -0,0 +1,56 @@
+use {
+    crate::database::Postgres,
+    chrono::{DateTime, Utc},
+    hyper::StatusCode,
+    primitive_types::H160,
+    serde::Serialize,
+    shared::price_estimation::trade_verifier::balance_overrides::Strategy,
+    std::convert::Infallible,
+    warp::{Filter, Rejection, reply},
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BalanceOverride {
+    #[serde(flatten)]
+    strategy: Strategy,
+    detected_at: DateTime<Utc>,
+}
+
+fn request() -> impl Filter<Extract = (H160,), Error = Rejection> + Clone {
+    warp::path!("v1" / "token" / H160 / "balance_override").and(warp::get())
+}
+
+pub fn get_token_balance_override(
+    db: Postgres,
+) -> impl Filter<Extract = (super::ApiReply,), Error = Rejection> + Clone {
+    request().and_then(move |token: H160| {
+        let db = db.clone();
+        async move {
+            let result = db.token_balance_override(token).await.and_then(|row| {
+                row.map(|row| {
+                    Ok(BalanceOverride {
+                        strategy: Strategy::try_from(&row)?,
+                        detected_at: row.detected_at,
+                    })
+                })
+                .transpose()
+            });
+            let response = match result {
+                Ok(Some(balance_override)) => {
+                    reply::with_status(reply::json(&balance_override), StatusCode::OK)
+                }
+                Ok(None) => reply::with_status(
+                    super::error("NotFound", "No balance override detected for token"),
+                    StatusCode::NOT_FOUND,
+                ),
+                Err(err) => {
+                    tracing::error!(?err, ?token, "Failed to fetch token balance override");
+                    crate::api::internal_error_reply()
+                }
+            };
+
+            Result::<_, Infallible>::Ok(response)
+        }
+    })
+}
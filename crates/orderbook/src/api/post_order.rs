@@ -1,6 +1,7 @@
 use {
     crate::{
-        api::{ApiReply, IntoWarpReply, error, extract_payload},
+        api::{self, ApiReply, IntoWarpReply, error, extract_payload},
+        api_keys::{ApiKeys, Endpoint},
         orderbook::{AddOrderError, OrderReplacementError, Orderbook},
     },
     anyhow::Result,
@@ -304,26 +305,40 @@ pub fn create_order_response(
 
 pub fn post_order(
     orderbook: Arc<Orderbook>,
+    api_keys: Arc<ApiKeys>,
 ) -> impl Filter<Extract = (ApiReply,), Error = Rejection> + Clone {
-    create_order_request().and_then(move |order: OrderCreation| {
-        let orderbook = orderbook.clone();
-        async move {
-            let result = orderbook
-                .add_order(order.clone())
-                .await
-                .map(|(order_uid, quote_metadata)| {
-                    let quote_id = quote_metadata.as_ref().and_then(|q| q.id);
-                    let quote_solver = quote_metadata.as_ref().map(|q| q.solver);
-                    tracing::debug!(%order_uid, ?quote_id, ?quote_solver, "order created");
-                    (order_uid, quote_metadata.and_then(|quote| quote.id))
-                })
-                .inspect_err(|err| {
-                    tracing::debug!(?order, ?err, "error creating order");
-                });
+    create_order_request().and(api::api_key()).and_then(
+        move |order: OrderCreation, api_key: Option<String>| {
+            let orderbook = orderbook.clone();
+            let api_keys = api_keys.clone();
+            async move {
+                let client = match api_keys.authorize(api_key.as_deref(), Endpoint::Order) {
+                    Ok(client) => client,
+                    Err(err) => {
+                        tracing::debug!(%err, "rejected order request");
+                        return Result::<_, Infallible>::Ok(err.into_warp_reply());
+                    }
+                };
+                let result = orderbook
+                    .add_order(order.clone())
+                    .await
+                    .map(|(order_uid, quote_metadata)| {
+                        let quote_id = quote_metadata.as_ref().and_then(|q| q.id);
+                        let quote_solver = quote_metadata.as_ref().map(|q| q.solver);
+                        tracing::debug!(%order_uid, ?quote_id, ?quote_solver, "order created");
+                        (order_uid, quote_metadata.and_then(|quote| quote.id))
+                    })
+                    .inspect_err(|err| {
+                        tracing::debug!(?order, ?err, "error creating order");
+                    });
 
-            Result::<_, Infallible>::Ok(create_order_response(result))
-        }
-    })
+                if let (Ok((order_uid, _)), Some(client)) = (&result, &client) {
+                    api_keys.attribute_order(order_uid, client).await;
+                }
+                Result::<_, Infallible>::Ok(create_order_response(result))
+            }
+        },
+    )
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@ use {
     super::post_order::{AppDataValidationErrorWrapper, PartialValidationErrorWrapper},
     crate::{
         api::{self, ApiReply, IntoWarpReply, convert_json_response, error, rich_error},
+        api_keys::{ApiKeys, Endpoint},
         quoter::{OrderQuoteError, QuoteHandler},
     },
     anyhow::Result,
@@ -10,7 +11,13 @@ use {
     shared::order_quoting::CalculateQuoteError,
     std::{convert::Infallible, sync::Arc},
     thiserror::Error,
-    warp::{Filter, Rejection},
+    warp::{
+        Filter,
+        Rejection,
+        Reply,
+        http::HeaderValue,
+        reply::Response,
+    },
 };
 
 fn post_quote_request() -> impl Filter<Extract = (OrderQuoteRequest,), Error = Rejection> + Clone {
@@ -19,22 +26,44 @@ fn post_quote_request() -> impl Filter<Extract = (OrderQuoteRequest,), Error = R
         .and(api::extract_payload())
 }
 
+/// Response header indicating whether the quote was served from the quote
+/// cache (`hit`), served from the cache while being recomputed (`stale`) or
+/// freshly computed (`miss`). Only set if quote caching is enabled.
+const QUOTE_CACHE_HEADER: &str = "X-Quote-Cache";
+
 pub fn post_quote(
     quotes: Arc<QuoteHandler>,
-) -> impl Filter<Extract = (super::ApiReply,), Error = Rejection> + Clone {
-    post_quote_request().and_then(move |request: OrderQuoteRequest| {
-        let quotes = quotes.clone();
-        async move {
-            let result = quotes
-                .calculate_quote(&request)
-                .await
-                .map_err(OrderQuoteErrorWrapper);
-            if let Err(err) = &result {
-                tracing::warn!(%err, ?request, "post_quote error");
+    api_keys: Arc<ApiKeys>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    post_quote_request().and(api::api_key()).and_then(
+        move |request: OrderQuoteRequest, api_key: Option<String>| {
+            let quotes = quotes.clone();
+            let api_keys = api_keys.clone();
+            async move {
+                if let Err(err) = api_keys.authorize(api_key.as_deref(), Endpoint::Quote) {
+                    tracing::debug!(%err, "rejected quote request");
+                    return Result::<_, Infallible>::Ok(err.into_warp_reply().into_response());
+                }
+                let result = quotes
+                    .calculate_quote(&request)
+                    .await
+                    .map_err(OrderQuoteErrorWrapper);
+                if let Err(err) = &result {
+                    tracing::warn!(%err, ?request, "post_quote error");
+                }
+                let cache_status = result.as_ref().ok().and_then(|(_, status)| *status);
+                let mut response =
+                    convert_json_response(result.map(|(quote, _)| quote)).into_response();
+                if let Some(status) = cache_status {
+                    response.headers_mut().insert(
+                        QUOTE_CACHE_HEADER,
+                        HeaderValue::from_static(status.as_str()),
+                    );
+                }
+                Result::<_, Infallible>::Ok(response)
             }
-            Result::<_, Infallible>::Ok(convert_json_response(result))
-        }
-    })
+        },
+    )
 }
 
 #[derive(Debug, Error)]
@@ -0,0 +1,375 @@
+//! Optional API keys identifying clients of the orderbook API.
+//!
+//! Keys and their limits are stored in the database and get reloaded
+//! periodically. Every key has its own token bucket per limited endpoint so
+//! integration partners get guaranteed capacity regardless of the load other
+//! clients generate. Requests without a key are not limited.
+
+use {
+    crate::database::Postgres,
+    anyhow::Result,
+    ethcontract::{H256, web3::signing::keccak256},
+    model::order::OrderUid,
+    serde::Serialize,
+    shared::arguments::display_secret_option,
+    std::{
+        collections::HashMap,
+        fmt::{self, Display, Formatter},
+        sync::{Arc, Mutex, RwLock},
+        time::{Duration, Instant},
+    },
+    strum_macros::Display as StrumDisplay,
+    thiserror::Error,
+    tracing::Instrument,
+};
+
+/// API key configuration arguments.
+#[derive(clap::Parser)]
+#[group(skip)]
+pub struct Arguments {
+    /// Identify API clients by the `X-API-Key` header and apply the per-key
+    /// request limits stored in the database. Requests with unknown keys get
+    /// rejected, requests without a key are not limited.
+    #[clap(long, env, action = clap::ArgAction::Set, default_value = "false")]
+    pub api_keys: bool,
+
+    /// How often API keys and their limits get reloaded from the database.
+    #[clap(
+        long,
+        env,
+        default_value = "1m",
+        value_parser = humantime::parse_duration,
+    )]
+    pub api_keys_refresh_interval: Duration,
+
+    /// Secret that needs to be passed in the `X-Admin-Key` header to access
+    /// admin endpoints. Admin endpoints are disabled if unset.
+    #[clap(long, env)]
+    pub admin_api_key: Option<String>,
+}
+
+impl Display for Arguments {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Self {
+            api_keys,
+            api_keys_refresh_interval,
+            admin_api_key,
+        } = self;
+
+        writeln!(f, "api_keys: {}", api_keys)?;
+        writeln!(
+            f,
+            "api_keys_refresh_interval: {:?}",
+            api_keys_refresh_interval
+        )?;
+        display_secret_option(f, "admin_api_key", admin_api_key.as_ref())?;
+
+        Ok(())
+    }
+}
+
+/// Endpoints with per API key limits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, StrumDisplay)]
+#[strum(serialize_all = "snake_case")]
+pub enum Endpoint {
+    Quote,
+    Order,
+}
+
+#[derive(Debug, Error)]
+pub enum ApiKeyError {
+    #[error("unknown API key")]
+    Unknown,
+    #[error("request limit of API key {0} exceeded")]
+    RateLimited(String),
+}
+
+/// An API client identified by its key.
+#[derive(Debug)]
+pub struct Client {
+    pub name: String,
+    quotes: Limit,
+    orders: Limit,
+}
+
+impl Client {
+    /// Whether the client still has the same name and limits as the key.
+    fn is_configured_as(&self, key: &database::api_keys::ApiKey) -> bool {
+        self.name == key.name
+            && self.quotes.per_minute == key.quotes_per_minute
+            && self.orders.per_minute == key.orders_per_minute
+    }
+
+    fn limit(&self, endpoint: Endpoint) -> &Limit {
+        match endpoint {
+            Endpoint::Quote => &self.quotes,
+            Endpoint::Order => &self.orders,
+        }
+    }
+}
+
+/// Request limit of a client for a single endpoint.
+#[derive(Debug)]
+struct Limit {
+    /// The configured limit. `None` means unlimited.
+    per_minute: Option<i32>,
+    bucket: Option<Mutex<TokenBucket>>,
+}
+
+impl Limit {
+    fn per_minute(per_minute: Option<i32>) -> Self {
+        Self {
+            per_minute,
+            bucket: per_minute.map(|requests| {
+                let capacity = f64::from(requests.max(0));
+                Mutex::new(TokenBucket::new(capacity, capacity / 60.))
+            }),
+        }
+    }
+
+    fn try_acquire(&self) -> bool {
+        self.bucket
+            .as_ref()
+            .is_none_or(|bucket| bucket.lock().unwrap().try_acquire(Instant::now()))
+    }
+}
+
+/// A token bucket that allows bursts of up to `capacity` requests and refills
+/// continuously.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, refill_per_second: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_second,
+            tokens: capacity,
+            updated_at: Instant::now(),
+        }
+    }
+
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.updated_at = now;
+        if self.tokens < 1. {
+            return false;
+        }
+        self.tokens -= 1.;
+        true
+    }
+}
+
+/// Usage of an API key since the process started.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    pub name: String,
+    pub quotes_per_minute: Option<i32>,
+    pub orders_per_minute: Option<i32>,
+    pub quotes: EndpointUsage,
+    pub orders: EndpointUsage,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointUsage {
+    pub allowed: u64,
+    pub limited: u64,
+}
+
+pub struct ApiKeys {
+    db: Postgres,
+    enabled: bool,
+    admin_key: Option<String>,
+    /// Clients by the hash of their key.
+    clients: RwLock<HashMap<H256, Arc<Client>>>,
+}
+
+impl ApiKeys {
+    /// Creates the API key registry and keeps it up to date with the database
+    /// in a background task.
+    pub async fn new(db: Postgres, args: &Arguments) -> Result<Arc<Self>> {
+        let api_keys = Arc::new(Self {
+            db,
+            enabled: args.api_keys,
+            admin_key: args.admin_api_key.clone(),
+            clients: Default::default(),
+        });
+        if !api_keys.enabled {
+            return Ok(api_keys);
+        }
+
+        api_keys.refresh().await?;
+        let interval = args.api_keys_refresh_interval;
+        let task = {
+            let api_keys = api_keys.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+                    if let Err(err) = api_keys.refresh().await {
+                        tracing::warn!(?err, "failed to refresh API keys");
+                    }
+                }
+            }
+        };
+        tokio::task::spawn(task.instrument(tracing::info_span!("api_keys")));
+        Ok(api_keys)
+    }
+
+    /// Reloads the keys from the database. Clients whose limits did not change
+    /// keep their current token buckets.
+    async fn refresh(&self) -> Result<()> {
+        let keys = self.db.api_keys().await?;
+        let mut clients = self.clients.write().unwrap();
+        let mut existing = std::mem::take(&mut *clients);
+        for key in keys {
+            let hash = H256(key.key_hash.0);
+            let client = match existing.remove(&hash) {
+                Some(client) if client.is_configured_as(&key) => client,
+                _ => Arc::new(Client {
+                    name: key.name,
+                    quotes: Limit::per_minute(key.quotes_per_minute),
+                    orders: Limit::per_minute(key.orders_per_minute),
+                }),
+            };
+            clients.insert(hash, client);
+        }
+        tracing::debug!(keys = clients.len(), "refreshed API keys");
+        Ok(())
+    }
+
+    /// Identifies the client by its key and checks that it didn't exceed its
+    /// request limit for the endpoint. Returns `None` for anonymous requests.
+    pub fn authorize(
+        &self,
+        key: Option<&str>,
+        endpoint: Endpoint,
+    ) -> Result<Option<Arc<Client>>, ApiKeyError> {
+        let Some(key) = key.filter(|_| self.enabled) else {
+            return Ok(None);
+        };
+        let client = self
+            .clients
+            .read()
+            .unwrap()
+            .get(&H256(keccak256(key.as_bytes())))
+            .cloned()
+            .ok_or(ApiKeyError::Unknown)?;
+
+        let allowed = client.limit(endpoint).try_acquire();
+        let result = if allowed { "allowed" } else { "limited" };
+        Metrics::get()
+            .api_key_requests
+            .with_label_values(&[&client.name, &endpoint.to_string(), result])
+            .inc();
+
+        if !allowed {
+            return Err(ApiKeyError::RateLimited(client.name.clone()));
+        }
+        Ok(Some(client))
+    }
+
+    /// Attributes a newly created order to the client that created it.
+    pub async fn attribute_order(&self, order_uid: &OrderUid, client: &Client) {
+        if let Err(err) = self.db.insert_order_api_key(order_uid, &client.name).await {
+            tracing::warn!(?err, %order_uid, client = %client.name, "failed to attribute order");
+        }
+    }
+
+    /// Checks whether the admin key is configured and matches.
+    pub fn is_admin(&self, key: Option<&str>) -> bool {
+        self.admin_key.is_some() && self.admin_key.as_deref() == key
+    }
+
+    /// Returns the usage of all known API keys.
+    pub fn usage(&self) -> Vec<Usage> {
+        let metrics = Metrics::get();
+        let usage = |name: &str, endpoint: Endpoint| {
+            let count = |result: &str| {
+                metrics
+                    .api_key_requests
+                    .with_label_values(&[name, &endpoint.to_string(), result])
+                    .get()
+            };
+            EndpointUsage {
+                allowed: count("allowed"),
+                limited: count("limited"),
+            }
+        };
+
+        let mut usage: Vec<_> = self
+            .clients
+            .read()
+            .unwrap()
+            .values()
+            .map(|client| Usage {
+                name: client.name.clone(),
+                quotes_per_minute: client.quotes.per_minute,
+                orders_per_minute: client.orders.per_minute,
+                quotes: usage(&client.name, Endpoint::Quote),
+                orders: usage(&client.name, Endpoint::Order),
+            })
+            .collect();
+        usage.sort_by(|a, b| a.name.cmp(&b.name));
+        usage
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "orderbook")]
+struct Metrics {
+    /// Requests made with API keys by endpoint and whether they were allowed
+    /// or rate limited.
+    #[metric(labels("api_key", "endpoint", "result"))]
+    api_key_requests: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Self::instance(observe::metrics::get_storage_registry())
+            .expect("unexpected error getting metrics instance")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_refills() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket {
+            capacity: 2.,
+            refill_per_second: 1.,
+            tokens: 2.,
+            updated_at: start,
+        };
+
+        assert!(bucket.try_acquire(start));
+        assert!(bucket.try_acquire(start));
+        assert!(!bucket.try_acquire(start));
+
+        assert!(!bucket.try_acquire(start + Duration::from_millis(500)));
+        assert!(bucket.try_acquire(start + Duration::from_millis(1000)));
+
+        // Tokens never exceed the capacity.
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.try_acquire(later));
+        assert!(bucket.try_acquire(later));
+        assert!(!bucket.try_acquire(later));
+    }
+
+    #[test]
+    fn zero_limit_rejects_everything() {
+        let limit = Limit::per_minute(Some(0));
+        assert!(!limit.try_acquire());
+        assert!(Limit::per_minute(None).try_acquire());
+    }
+}
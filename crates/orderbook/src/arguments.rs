@@ -27,6 +27,12 @@ pub struct Arguments {
     #[clap(flatten)]
     pub price_estimation: price_estimation::Arguments,
 
+    #[clap(flatten)]
+    pub quote_cache: crate::quote_cache::Arguments,
+
+    #[clap(flatten)]
+    pub api_keys: crate::api_keys::Arguments,
+
     /// A tracing Ethereum node URL to connect to, allowing a separate node URL
     /// to be used exclusively for tracing calls.
     #[clap(long, env)]
@@ -152,6 +158,8 @@ impl std::fmt::Display for Arguments {
             http_client,
             token_owner_finder,
             price_estimation,
+            quote_cache,
+            api_keys,
             tracing_node_url,
             bind_address,
             min_order_validity_period,
@@ -180,6 +188,8 @@ impl std::fmt::Display for Arguments {
         write!(f, "{}", http_client)?;
         write!(f, "{}", token_owner_finder)?;
         write!(f, "{}", price_estimation)?;
+        write!(f, "{}", quote_cache)?;
+        write!(f, "{}", api_keys)?;
         display_option(f, "tracing_node_url", tracing_node_url)?;
         writeln!(f, "bind_address: {}", bind_address)?;
         let _intentionally_ignored = db_url;
@@ -0,0 +1,28 @@
+use {
+    anyhow::Result,
+    database::{api_keys::ApiKey, byte_array::ByteArray},
+    model::order::OrderUid,
+};
+
+impl super::Postgres {
+    pub async fn api_keys(&self) -> Result<Vec<ApiKey>> {
+        let _timer = super::Metrics::get()
+            .database_queries
+            .with_label_values(&["api_keys"])
+            .start_timer();
+
+        let mut ex = self.pool.acquire().await?;
+        Ok(database::api_keys::fetch_all(&mut ex).await?)
+    }
+
+    pub async fn insert_order_api_key(&self, order_uid: &OrderUid, api_key: &str) -> Result<()> {
+        let _timer = super::Metrics::get()
+            .database_queries
+            .with_label_values(&["insert_order_api_key"])
+            .start_timer();
+
+        let mut ex = self.pool.acquire().await?;
+        database::api_keys::insert_order(&mut ex, &ByteArray(order_uid.0), api_key).await?;
+        Ok(())
+    }
+}
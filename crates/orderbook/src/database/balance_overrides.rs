@@ -0,0 +1,21 @@
+use {
+    super::Postgres,
+    anyhow::Result,
+    database::byte_array::ByteArray,
+    primitive_types::H160,
+};
+
+impl Postgres {
+    pub async fn token_balance_override(
+        &self,
+        token: H160,
+    ) -> Result<Option<database::token_balance_overrides::BalanceOverride>> {
+        let _timer = super::Metrics::get()
+            .database_queries
+            .with_label_values(&["token_balance_override"])
+            .start_timer();
+
+        let mut ex = self.pool.acquire().await?;
+        Ok(database::token_balance_overrides::fetch(&mut ex, &ByteArray(token.0)).await?)
+    }
+}
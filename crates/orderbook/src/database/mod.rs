@@ -1,6 +1,8 @@
+mod api_keys;
 pub mod app_data;
 pub mod auction_prices;
 pub mod auctions;
+mod balance_overrides;
 mod fee_policies;
 pub mod orders;
 pub mod quotes;
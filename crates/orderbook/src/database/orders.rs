@@ -1181,6 +1181,8 @@ mod tests {
                         call_data: vec![3, 20],
                     }],
                     jit_orders: vec![],
+                    sell_token_transfer_fee_bps: None,
+                    buy_token_transfer_fee_bps: None,
                 }
                 .into(),
                 ..Default::default()
@@ -1,4 +1,5 @@
 pub mod api;
+mod api_keys;
 pub mod app_data;
 pub mod arguments;
 pub mod database;
@@ -6,6 +7,7 @@ pub mod dto;
 mod ipfs;
 mod ipfs_app_data;
 pub mod orderbook;
+mod quote_cache;
 mod quoter;
 pub mod run;
 pub mod solver_competition;
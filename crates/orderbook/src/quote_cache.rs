@@ -0,0 +1,585 @@
+//! Cache for quotes computed for the `POST /quote` endpoint.
+//!
+//! Price polling UIs generate a lot of identical quote requests. Instead of
+//! running a full price estimation competition for each of them, quotes are
+//! cached per block: pool states only change when a new block gets mined so a
+//! quote computed for the current block is served as is. Once newer blocks
+//! got mined the cached quote is still served for a configurable number of
+//! blocks but it gets recomputed in the background (stale-while-revalidate).
+
+use {
+    cached::{Cached, SizedCache},
+    chrono::{DateTime, Utc},
+    ethrpc::block_stream::CurrentBlockWatcher,
+    model::quote::{OrderQuoteSide, PriceQuality, QuoteSigningScheme, SellAmount},
+    primitive_types::{H160, U256, U512},
+    shared::{
+        order_quoting::{
+            CalculateQuoteError,
+            OrderQuoting,
+            Quote,
+            QuoteData,
+            QuoteParameters,
+            Validity,
+        },
+        price_estimation::Verification,
+    },
+    std::{
+        collections::HashSet,
+        fmt::{self, Display, Formatter},
+        num::{NonZeroU32, NonZeroUsize},
+        sync::{Arc, Mutex},
+    },
+    tracing::Instrument,
+};
+
+/// Quote cache configuration arguments.
+#[derive(clap::Parser)]
+#[group(skip)]
+pub struct Arguments {
+    /// Cache quotes across identical quote requests.
+    #[clap(long, env, action = clap::ArgAction::Set, default_value = "false")]
+    pub quote_cache: bool,
+
+    /// The maximum number of cached quotes before evicting the least recently
+    /// used ones.
+    #[clap(long, env, default_value = "10000")]
+    pub quote_cache_size: NonZeroUsize,
+
+    /// For how many blocks a cached quote gets served without recomputing it.
+    /// A value of 1 means quotes only get reused within the block they were
+    /// computed in.
+    #[clap(long, env, default_value = "1")]
+    pub quote_cache_max_age_blocks: u64,
+
+    /// For how many blocks after `quote_cache_max_age_blocks` a cached quote
+    /// may still be served while it gets recomputed in the background.
+    #[clap(long, env, default_value = "2")]
+    pub quote_cache_stale_blocks: u64,
+
+    /// Requested amounts get rounded down to this many significant decimal
+    /// digits when looking up cached quotes. Quotes for other amounts of the
+    /// same bucket get scaled linearly. If unset, only quotes for the exact
+    /// same amount are reused.
+    #[clap(long, env)]
+    pub quote_cache_amount_significant_digits: Option<NonZeroU32>,
+}
+
+impl Display for Arguments {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Self {
+            quote_cache,
+            quote_cache_size,
+            quote_cache_max_age_blocks,
+            quote_cache_stale_blocks,
+            quote_cache_amount_significant_digits,
+        } = self;
+
+        writeln!(f, "quote_cache: {}", quote_cache)?;
+        writeln!(f, "quote_cache_size: {}", quote_cache_size)?;
+        writeln!(
+            f,
+            "quote_cache_max_age_blocks: {}",
+            quote_cache_max_age_blocks
+        )?;
+        writeln!(f, "quote_cache_stale_blocks: {}", quote_cache_stale_blocks)?;
+        writeln!(
+            f,
+            "quote_cache_amount_significant_digits: {:?}",
+            quote_cache_amount_significant_digits
+        )?;
+
+        Ok(())
+    }
+}
+
+impl Arguments {
+    /// Creates a quote cache from the current configuration or `None` if
+    /// quote caching is disabled.
+    pub fn init(
+        &self,
+        validity: Validity,
+        block_stream: CurrentBlockWatcher,
+    ) -> Option<Arc<QuoteCache>> {
+        self.quote_cache.then(|| {
+            Arc::new(QuoteCache::new(
+                Config {
+                    size: self.quote_cache_size,
+                    max_age_blocks: self.quote_cache_max_age_blocks,
+                    stale_blocks: self.quote_cache_stale_blocks,
+                    amount_significant_digits: self.quote_cache_amount_significant_digits,
+                    validity,
+                },
+                block_stream,
+            ))
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub size: NonZeroUsize,
+    pub max_age_blocks: u64,
+    pub stale_blocks: u64,
+    pub amount_significant_digits: Option<NonZeroU32>,
+    /// How long served quotes are valid for.
+    pub validity: Validity,
+}
+
+/// How a quote request was answered by the cache.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CacheStatus {
+    /// The quote was computed for the current block.
+    Hit,
+    /// The quote was computed for an older block and is being recomputed in
+    /// the background.
+    Stale,
+    /// The quote was freshly computed.
+    Miss,
+}
+
+impl CacheStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hit => "hit",
+            Self::Stale => "stale",
+            Self::Miss => "miss",
+        }
+    }
+}
+
+pub struct QuoteCache {
+    config: Config,
+    block_stream: CurrentBlockWatcher,
+    entries: Mutex<SizedCache<Key, Entry>>,
+    /// Keys for which a background recomputation is currently in flight.
+    revalidating: Mutex<HashSet<Key>>,
+}
+
+/// Everything a quote depends on except for the exact in amount.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+struct Key {
+    sell_token: H160,
+    buy_token: H160,
+    side: Side,
+    amount_bucket: U256,
+    verification: Verification,
+    signing_scheme: QuoteSigningScheme,
+    additional_gas: u64,
+    price_quality: PriceQuality,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum Side {
+    SellBeforeFee,
+    SellAfterFee,
+    Buy,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    /// The quote data computed for `amount`.
+    data: QuoteData,
+    /// The exact in amount the quote was computed for.
+    amount: U256,
+    /// The block that was current when the quote computation started.
+    block: u64,
+}
+
+impl QuoteCache {
+    pub fn new(config: Config, block_stream: CurrentBlockWatcher) -> Self {
+        Self {
+            entries: Mutex::new(SizedCache::with_size(config.size.get())),
+            revalidating: Default::default(),
+            config,
+            block_stream,
+        }
+    }
+
+    /// Returns a cached quote for the parameters or computes and caches a new
+    /// one with the `quoter`.
+    pub async fn quote(
+        self: &Arc<Self>,
+        quoter: Arc<dyn OrderQuoting>,
+        parameters: QuoteParameters,
+        price_quality: PriceQuality,
+    ) -> Result<(Quote, CacheStatus), CalculateQuoteError> {
+        let key = self.key(&parameters, price_quality);
+        let block = self.block_stream.borrow().number;
+
+        let cached = self.entries.lock().unwrap().cache_get(&key).cloned();
+        if let Some((entry, status)) = cached.and_then(|entry| {
+            let status = self.status(entry.block, block)?;
+            Some((entry, status))
+        }) {
+            let expiration = self
+                .config
+                .validity
+                .expiration(&parameters.signing_scheme, Utc::now());
+            let quote = entry.quote(&parameters, expiration)?;
+            if status == CacheStatus::Stale {
+                self.revalidate(key, quoter, parameters);
+            }
+            Metrics::get()
+                .quote_cache
+                .with_label_values(&[status.as_str()])
+                .inc();
+            return Ok((quote, status));
+        }
+
+        Metrics::get()
+            .quote_cache
+            .with_label_values(&[CacheStatus::Miss.as_str()])
+            .inc();
+        let quote = quoter.calculate_quote(parameters.clone()).await?;
+        self.insert(key, &parameters, &quote, block);
+        Ok((quote, CacheStatus::Miss))
+    }
+
+    /// Returns how a quote computed at `computed_at` can be used in the
+    /// `current` block or `None` if it is too old to be served.
+    fn status(&self, computed_at: u64, current: u64) -> Option<CacheStatus> {
+        let age = current.saturating_sub(computed_at);
+        let max_stale_age = self
+            .config
+            .max_age_blocks
+            .saturating_add(self.config.stale_blocks);
+        if age < self.config.max_age_blocks {
+            Some(CacheStatus::Hit)
+        } else if age < max_stale_age {
+            Some(CacheStatus::Stale)
+        } else {
+            None
+        }
+    }
+
+    /// Recomputes the quote in a background task unless that is already
+    /// happening.
+    fn revalidate(
+        self: &Arc<Self>,
+        key: Key,
+        quoter: Arc<dyn OrderQuoting>,
+        parameters: QuoteParameters,
+    ) {
+        if !self.revalidating.lock().unwrap().insert(key.clone()) {
+            return;
+        }
+
+        let cache = self.clone();
+        let task = async move {
+            let block = cache.block_stream.borrow().number;
+            match quoter.calculate_quote(parameters.clone()).await {
+                Ok(quote) => cache.insert(key.clone(), &parameters, &quote, block),
+                Err(err) => tracing::debug!(?err, "failed to revalidate cached quote"),
+            }
+            cache.revalidating.lock().unwrap().remove(&key);
+        };
+        tokio::task::spawn(task.instrument(tracing::Span::current()));
+    }
+
+    fn insert(&self, key: Key, parameters: &QuoteParameters, quote: &Quote, block: u64) {
+        let entry = Entry {
+            data: quote.data.clone(),
+            amount: in_amount(&parameters.side),
+            block,
+        };
+        let mut entries = self.entries.lock().unwrap();
+        // Never replace a quote with one that was computed for an older block.
+        if entries
+            .cache_get(&key)
+            .is_some_and(|existing| existing.block > block)
+        {
+            return;
+        }
+        entries.cache_set(key, entry);
+    }
+
+    fn key(&self, parameters: &QuoteParameters, price_quality: PriceQuality) -> Key {
+        let side = match parameters.side {
+            OrderQuoteSide::Sell {
+                sell_amount: SellAmount::BeforeFee { .. },
+            } => Side::SellBeforeFee,
+            OrderQuoteSide::Sell {
+                sell_amount: SellAmount::AfterFee { .. },
+            } => Side::SellAfterFee,
+            OrderQuoteSide::Buy { .. } => Side::Buy,
+        };
+        Key {
+            sell_token: parameters.sell_token,
+            buy_token: parameters.buy_token,
+            side,
+            amount_bucket: round_amount(
+                in_amount(&parameters.side),
+                self.config.amount_significant_digits,
+            ),
+            verification: parameters.verification.clone(),
+            signing_scheme: parameters.signing_scheme,
+            additional_gas: parameters.additional_gas,
+            price_quality,
+        }
+    }
+}
+
+impl Entry {
+    /// Converts the cached quote data into a quote for the specified
+    /// parameters that expires at `expiration`, scaling it to the requested
+    /// amount if necessary.
+    fn quote(
+        &self,
+        parameters: &QuoteParameters,
+        expiration: DateTime<Utc>,
+    ) -> Result<Quote, CalculateQuoteError> {
+        let amount = in_amount(&parameters.side);
+        let mut data = self.data.clone();
+        data.expiration = expiration;
+        if amount != self.amount {
+            // Round in favour of the protocol, i.e. quote slightly less buy
+            // tokens for sell orders and slightly more sell tokens for buy
+            // orders.
+            match parameters.side {
+                OrderQuoteSide::Sell { .. } => {
+                    data.quoted_sell_amount = amount;
+                    data.quoted_buy_amount =
+                        mul_div(data.quoted_buy_amount, amount, self.amount, false)?;
+                }
+                OrderQuoteSide::Buy { .. } => {
+                    data.quoted_buy_amount = amount;
+                    data.quoted_sell_amount =
+                        mul_div(data.quoted_sell_amount, amount, self.amount, true)?;
+                }
+            }
+            // The verification only applies to the exact amount that was
+            // simulated.
+            data.verified = false;
+        }
+        Quote::from_data(data, parameters)
+    }
+}
+
+fn mul_div(value: U256, mul: U256, div: U256, round_up: bool) -> Result<U256, CalculateQuoteError> {
+    let product = value.full_mul(mul);
+    let div = U512::from(div);
+    let result = if round_up {
+        (product + div - U512::one()) / div
+    } else {
+        product / div
+    };
+    result
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("overflow scaling cached quote").into())
+}
+
+fn in_amount(side: &OrderQuoteSide) -> U256 {
+    match side {
+        OrderQuoteSide::Sell {
+            sell_amount:
+                SellAmount::BeforeFee { value: amount } | SellAmount::AfterFee { value: amount },
+        } => amount.get(),
+        OrderQuoteSide::Buy {
+            buy_amount_after_fee,
+        } => buy_amount_after_fee.get(),
+    }
+}
+
+/// Rounds the amount down to the specified number of significant decimal
+/// digits.
+fn round_amount(amount: U256, significant_digits: Option<NonZeroU32>) -> U256 {
+    let Some(significant_digits) = significant_digits else {
+        return amount;
+    };
+    let digits = amount.to_string().len();
+    let Some(truncated) = digits.checked_sub(significant_digits.get() as usize) else {
+        return amount;
+    };
+    let factor = U256::exp10(truncated);
+    amount / factor * factor
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "orderbook")]
+struct Metrics {
+    /// Quote requests by how they were answered by the quote cache.
+    #[metric(labels("result"))]
+    quote_cache: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Self::instance(observe::metrics::get_storage_registry())
+            .expect("unexpected error getting metrics instance")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        anyhow::Result,
+        ethrpc::block_stream::BlockInfo,
+        model::quote::QuoteId,
+        number::nonzero::U256 as NonZeroU256,
+        shared::order_quoting::{FindQuoteError, QuoteSearchParameters},
+        std::sync::atomic::{AtomicUsize, Ordering},
+        tokio::sync::watch,
+    };
+
+    /// Quotes 2 buy tokens per sell token and counts the computed quotes.
+    #[derive(Default)]
+    struct CountingQuoter(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl OrderQuoting for CountingQuoter {
+        async fn calculate_quote(
+            &self,
+            parameters: QuoteParameters,
+        ) -> Result<Quote, CalculateQuoteError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            let amount = in_amount(&parameters.side);
+            let data = QuoteData {
+                quoted_sell_amount: amount,
+                quoted_buy_amount: amount * 2,
+                verified: true,
+                ..Default::default()
+            };
+            Quote::from_data(data, &parameters)
+        }
+
+        async fn store_quote(&self, _: Quote) -> Result<Quote> {
+            unimplemented!()
+        }
+
+        async fn find_quote(
+            &self,
+            _: Option<QuoteId>,
+            _: QuoteSearchParameters,
+        ) -> Result<Quote, FindQuoteError> {
+            unimplemented!()
+        }
+    }
+
+    fn sell(amount: u64) -> QuoteParameters {
+        QuoteParameters {
+            side: OrderQuoteSide::Sell {
+                sell_amount: SellAmount::AfterFee {
+                    value: NonZeroU256::try_from(amount).unwrap(),
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    fn validity() -> Validity {
+        Validity {
+            eip1271_onchain_quote: chrono::Duration::seconds(600),
+            presign_onchain_quote: chrono::Duration::seconds(300),
+            standard_quote: chrono::Duration::seconds(60),
+        }
+    }
+
+    #[test]
+    fn rounds_amounts_to_significant_digits() {
+        let digits = NonZeroU32::new(3);
+        assert_eq!(round_amount(123_456.into(), digits), 123_000.into());
+        assert_eq!(round_amount(999.into(), digits), 999.into());
+        assert_eq!(round_amount(12.into(), digits), 12.into());
+        assert_eq!(round_amount(123_456.into(), None), 123_456.into());
+    }
+
+    #[tokio::test]
+    async fn serves_cached_quotes_per_block() {
+        let (blocks, block_stream) = watch::channel(BlockInfo::default());
+        let cache = Arc::new(QuoteCache::new(
+            Config {
+                size: NonZeroUsize::new(10).unwrap(),
+                max_age_blocks: 1,
+                stale_blocks: 1,
+                amount_significant_digits: NonZeroU32::new(3),
+                validity: validity(),
+            },
+            block_stream,
+        ));
+        let quoter = Arc::new(CountingQuoter::default());
+        let quote = |parameters| cache.quote(quoter.clone(), parameters, PriceQuality::Optimal);
+
+        let (quote1, status) = quote(sell(1_000_000)).await.unwrap();
+        assert_eq!(status, CacheStatus::Miss);
+        assert_eq!(quote1.buy_amount, 2_000_000.into());
+        assert!(quote1.data.verified);
+
+        // The exact same amount keeps the verification.
+        let (cached, status) = quote(sell(1_000_000)).await.unwrap();
+        assert_eq!(status, CacheStatus::Hit);
+        assert!(cached.data.verified);
+
+        // Different amount in the same bucket gets scaled.
+        let (quote2, status) = quote(sell(1_000_500)).await.unwrap();
+        assert_eq!(status, CacheStatus::Hit);
+        assert_eq!(quote2.sell_amount, 1_000_500.into());
+        assert_eq!(quote2.buy_amount, 2_001_000.into());
+        assert!(!quote2.data.verified);
+        assert_eq!(quoter.0.load(Ordering::SeqCst), 1);
+
+        // Other buckets are cache misses.
+        let (_, status) = quote(sell(2_000_000)).await.unwrap();
+        assert_eq!(status, CacheStatus::Miss);
+        assert_eq!(quoter.0.load(Ordering::SeqCst), 2);
+
+        // After a new block the quote is stale and gets revalidated.
+        blocks.send_modify(|block| block.number = 1);
+        let (_, status) = quote(sell(1_000_000)).await.unwrap();
+        assert_eq!(status, CacheStatus::Stale);
+        while quoter.0.load(Ordering::SeqCst) < 3 {
+            tokio::task::yield_now().await;
+        }
+        while !cache.revalidating.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        let (_, status) = quote(sell(1_000_000)).await.unwrap();
+        assert_eq!(status, CacheStatus::Hit);
+
+        // Quotes that are too old are recomputed.
+        blocks.send_modify(|block| block.number = 3);
+        let (_, status) = quote(sell(1_000_000)).await.unwrap();
+        assert_eq!(status, CacheStatus::Miss);
+        assert_eq!(quoter.0.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn cached_quotes_expire_relative_to_the_request() {
+        let (_blocks, block_stream) = watch::channel(BlockInfo::default());
+        let cache = QuoteCache::new(
+            Config {
+                size: NonZeroUsize::new(10).unwrap(),
+                max_age_blocks: 1,
+                stale_blocks: 1,
+                amount_significant_digits: None,
+                validity: validity(),
+            },
+            block_stream,
+        );
+        let quoter = Arc::new(CountingQuoter::default());
+        let presign = |amount| QuoteParameters {
+            signing_scheme: QuoteSigningScheme::PreSign {
+                onchain_order: true,
+            },
+            ..sell(amount)
+        };
+
+        let (_, status) = cache
+            .quote(quoter.clone(), presign(1_000), PriceQuality::Optimal)
+            .await
+            .unwrap();
+        assert_eq!(status, CacheStatus::Miss);
+
+        // The cached quote expires relative to when it is served and uses the
+        // validity of the requested signing scheme.
+        let before = Utc::now();
+        let (cached, status) = cache
+            .quote(quoter.clone(), presign(1_000), PriceQuality::Optimal)
+            .await
+            .unwrap();
+        assert_eq!(status, CacheStatus::Hit);
+        assert!(cached.data.expiration >= before + validity().presign_onchain_quote);
+        assert!(cached.data.expiration <= Utc::now() + validity().presign_onchain_quote);
+    }
+}
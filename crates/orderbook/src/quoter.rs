@@ -1,12 +1,15 @@
 use {
-    crate::app_data,
+    crate::{
+        app_data,
+        quote_cache::{CacheStatus, QuoteCache},
+    },
     chrono::{TimeZone, Utc},
     model::{
         order::OrderCreationAppData,
         quote::{OrderQuote, OrderQuoteRequest, OrderQuoteResponse, PriceQuality},
     },
     shared::{
-        order_quoting::{CalculateQuoteError, OrderQuoting, QuoteParameters},
+        order_quoting::{CalculateQuoteError, OrderQuoting, Quote, QuoteParameters},
         order_validation::{
             AppDataValidationError,
             OrderValidating,
@@ -26,6 +29,7 @@ pub struct QuoteHandler {
     optimal_quoter: Arc<dyn OrderQuoting>,
     fast_quoter: Arc<dyn OrderQuoting>,
     app_data: Arc<app_data::Registry>,
+    cache: Option<Arc<QuoteCache>>,
 }
 
 impl QuoteHandler {
@@ -39,6 +43,7 @@ impl QuoteHandler {
             optimal_quoter: quoter.clone(),
             fast_quoter: quoter,
             app_data,
+            cache: None,
         }
     }
 
@@ -46,13 +51,20 @@ impl QuoteHandler {
         self.fast_quoter = fast_quoter;
         self
     }
+
+    pub fn with_cache(mut self, cache: Option<Arc<QuoteCache>>) -> Self {
+        self.cache = cache;
+        self
+    }
 }
 
 impl QuoteHandler {
+    /// Computes a quote for the request. Also returns how the quote cache
+    /// answered the request if caching is enabled.
     pub async fn calculate_quote(
         &self,
         request: &OrderQuoteRequest,
-    ) -> Result<OrderQuoteResponse, OrderQuoteError> {
+    ) -> Result<(OrderQuoteResponse, Option<CacheStatus>), OrderQuoteError> {
         tracing::debug!(?request, "calculating quote");
 
         let full_app_data_override = match request.app_data {
@@ -84,21 +96,27 @@ impl QuoteHandler {
             additional_gas: app_data.inner.protocol.hooks.gas_limit(),
         };
 
-        let quote = match request.price_quality {
+        let (quote, cache_status) = match request.price_quality {
             PriceQuality::Optimal | PriceQuality::Verified => {
-                let quote = self.optimal_quoter.calculate_quote(params).await?;
-                self.optimal_quoter
+                let (quote, cache_status) = self
+                    .quote(&self.optimal_quoter, params, request.price_quality)
+                    .await?;
+                let quote = self
+                    .optimal_quoter
                     .store_quote(quote)
                     .await
-                    .map_err(CalculateQuoteError::Other)?
+                    .map_err(CalculateQuoteError::Other)?;
+                (quote, cache_status)
             }
             PriceQuality::Fast => {
-                let mut quote = self.fast_quoter.calculate_quote(params).await?;
+                let (mut quote, cache_status) = self
+                    .quote(&self.fast_quoter, params, request.price_quality)
+                    .await?;
                 // We maintain an API guarantee that fast quotes always have an expiry of zero,
                 // because they're not very accurate and can be considered to
                 // expire immediately.
                 quote.data.expiration = Utc.timestamp_millis_opt(0).unwrap();
-                quote
+                (quote, cache_status)
             }
         };
 
@@ -130,8 +148,25 @@ impl QuoteHandler {
             verified: quote.data.verified,
         };
 
-        tracing::debug!(?response, "finished computing quote");
-        Ok(response)
+        tracing::debug!(?response, ?cache_status, "finished computing quote");
+        Ok((response, cache_status))
+    }
+
+    async fn quote(
+        &self,
+        quoter: &Arc<dyn OrderQuoting>,
+        parameters: QuoteParameters,
+        price_quality: PriceQuality,
+    ) -> Result<(Quote, Option<CacheStatus>), CalculateQuoteError> {
+        match &self.cache {
+            Some(cache) => {
+                let (quote, status) = cache
+                    .quote(quoter.clone(), parameters, price_quality)
+                    .await?;
+                Ok((quote, Some(status)))
+            }
+            None => Ok((quoter.calculate_quote(parameters).await?, None)),
+        }
     }
 }
 
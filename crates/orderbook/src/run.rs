@@ -1,6 +1,7 @@
 use {
     crate::{
         api,
+        api_keys::ApiKeys,
         arguments::Arguments,
         database::Postgres,
         ipfs::Ipfs,
@@ -21,6 +22,7 @@ use {
     shared::{
         account_balances,
         bad_token::{
+            TransferFee,
             cache::CachingDetector,
             instrumented::InstrumentedBadTokenDetectorExt,
             list_based::{ListBasedDetector, UnknownTokenStrategy},
@@ -157,6 +159,7 @@ pub async fn run(args: Arguments) {
             &web3,
             args.shared.gas_estimators.as_slice(),
             args.shared.blocknative_api_key.clone(),
+            &args.shared.gas_estimation,
         )
         .await
         .expect("failed to create gas price estimator"),
@@ -212,16 +215,23 @@ pub async fn run(args: Arguments) {
 
     let trace_call_detector = args.tracing_node_url.as_ref().map(|tracing_node_url| {
         CachingDetector::new(
-            Box::new(TraceCallDetector::new(
-                shared::ethrpc::web3(
-                    &args.shared.ethrpc,
-                    &http_factory,
-                    tracing_node_url,
-                    "trace",
+            Box::new(
+                TraceCallDetector::new(
+                    shared::ethrpc::web3(
+                        &args.shared.ethrpc,
+                        &http_factory,
+                        tracing_node_url,
+                        "trace",
+                    ),
+                    settlement_contract.address(),
+                    finder,
+                )
+                .with_max_transfer_fee(
+                    args.shared
+                        .token_max_transfer_fee_bps
+                        .and_then(TransferFee::from_bps),
                 ),
-                settlement_contract.address(),
-                finder,
-            )),
+            ),
             args.shared.token_quality_cache_expiry,
             args.shared.token_quality_cache_prefetch_time,
         )
@@ -272,6 +282,7 @@ pub async fn run(args: Arguments) {
             bad_token_detector: bad_token_detector.clone(),
             tokens: token_info_fetcher.clone(),
             code_fetcher: code_fetcher.clone(),
+            balance_override_storage: Some(Arc::new(postgres.pool.clone())),
         },
     )
     .await
@@ -320,30 +331,34 @@ pub async fn run(args: Arguments) {
         max_limit: args.max_limit_order_validity_period,
     };
 
+    let quote_validity = order_quoting::Validity {
+        eip1271_onchain_quote: chrono::Duration::from_std(
+            args.order_quoting.eip1271_onchain_quote_validity,
+        )
+        .unwrap(),
+        presign_onchain_quote: chrono::Duration::from_std(
+            args.order_quoting.presign_onchain_quote_validity,
+        )
+        .unwrap(),
+        standard_quote: chrono::Duration::from_std(
+            args.order_quoting.standard_offchain_quote_validity,
+        )
+        .unwrap(),
+    };
     let create_quoter = |price_estimator: Arc<dyn PriceEstimating>,
                          verification: QuoteVerificationMode| {
-        Arc::new(OrderQuoter::new(
-            price_estimator,
-            native_price_estimator.clone(),
-            gas_price_estimator.clone(),
-            Arc::new(postgres.clone()),
-            order_quoting::Validity {
-                eip1271_onchain_quote: chrono::Duration::from_std(
-                    args.order_quoting.eip1271_onchain_quote_validity,
-                )
-                .unwrap(),
-                presign_onchain_quote: chrono::Duration::from_std(
-                    args.order_quoting.presign_onchain_quote_validity,
-                )
-                .unwrap(),
-                standard_quote: chrono::Duration::from_std(
-                    args.order_quoting.standard_offchain_quote_validity,
-                )
-                .unwrap(),
-            },
-            balance_fetcher.clone(),
-            verification,
-        ))
+        Arc::new(
+            OrderQuoter::new(
+                price_estimator,
+                native_price_estimator.clone(),
+                gas_price_estimator.clone(),
+                Arc::new(postgres.clone()),
+                quote_validity.clone(),
+                balance_fetcher.clone(),
+                verification,
+            )
+            .with_bad_token_detector(bad_token_detector.clone()),
+        )
     };
     let optimal_quoter = create_quoter(price_estimator, args.price_estimation.quote_verification);
     // Fast quoting is able to return early and if none of the produced quotes are
@@ -401,15 +416,21 @@ pub async fn run(args: Arguments) {
     check_database_connection(orderbook.as_ref()).await;
     let quotes = Arc::new(
         QuoteHandler::new(order_validator, optimal_quoter, app_data.clone())
-            .with_fast_quoter(fast_quoter),
+            .with_fast_quoter(fast_quoter)
+            .with_cache(args.quote_cache.init(quote_validity, current_block_stream.clone())),
     );
 
+    let api_keys = ApiKeys::new(postgres.clone(), &args.api_keys)
+        .await
+        .expect("failed to load API keys");
+
     let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel();
     let serve_api = serve_api(
         postgres,
         orderbook.clone(),
         quotes,
         app_data,
+        api_keys,
         args.bind_address,
         async {
             let _ = shutdown_receiver.await;
@@ -477,6 +498,7 @@ fn serve_api(
     orderbook: Arc<Orderbook>,
     quotes: Arc<QuoteHandler>,
     app_data: Arc<crate::app_data::Registry>,
+    api_keys: Arc<ApiKeys>,
     address: SocketAddr,
     shutdown_receiver: impl Future<Output = ()> + Send + 'static,
     native_price_estimator: Arc<dyn NativePriceEstimating>,
@@ -486,6 +508,7 @@ fn serve_api(
         orderbook,
         quotes,
         app_data,
+        api_keys,
         native_price_estimator,
     )
     .boxed();
@@ -1,7 +1,13 @@
 use {
     clap::Parser,
     ethcontract::H160,
-    shared::{arguments::display_option, ethrpc, http_client, logging_args_with_default_filter},
+    shared::{
+        arguments::display_option,
+        ethrpc,
+        gas_price_estimation::{self, GasEstimatorType},
+        http_client,
+        logging_args_with_default_filter,
+    },
     std::time::Duration,
     tracing::level_filters::LevelFilter,
     url::Url,
@@ -20,6 +26,22 @@ pub struct Arguments {
     #[clap(flatten)]
     pub logging: LoggingArguments,
 
+    #[clap(flatten)]
+    pub gas_estimation: gas_price_estimation::Arguments,
+
+    /// Which gas estimators to use for refund transactions. Multiple
+    /// estimators are used in sequence if a previous one fails unless they get
+    /// blended.
+    #[clap(
+        long,
+        env,
+        default_value = "Web3",
+        value_enum,
+        ignore_case = true,
+        use_value_delimiter = true
+    )]
+    pub gas_estimators: Vec<GasEstimatorType>,
+
     /// Minimum time in seconds an order must have been valid for
     /// to be eligible for refunding
     #[clap(
@@ -83,11 +105,15 @@ impl std::fmt::Display for Arguments {
             logging,
             db_url,
             refunder_pk,
+            gas_estimation,
+            gas_estimators,
         } = self;
 
         write!(f, "{}", http_client)?;
         write!(f, "{}", ethrpc)?;
         write!(f, "{}", logging)?;
+        write!(f, "{}", gas_estimation)?;
+        writeln!(f, "gas_estimators: {:?}", gas_estimators)?;
         writeln!(f, "min_validity_duration: {:?}", min_validity_duration)?;
         writeln!(f, "min_price_deviation_bps: {}", min_price_deviation_bps)?;
         let _intentionally_ignored = db_url;
@@ -63,6 +63,15 @@ pub async fn run(args: arguments::Arguments) {
         .iter()
         .map(|contract| CoWSwapEthFlow::at(&web3, *contract))
         .collect();
+    let gas_estimator = shared::gas_price_estimation::create_priority_estimator(
+        &http_factory,
+        &web3,
+        &args.gas_estimators,
+        None,
+        &args.gas_estimation,
+    )
+    .await
+    .expect("failed to create gas price estimator");
     let refunder_account = Account::Offline(args.refunder_pk.parse::<PrivateKey>().unwrap(), None);
     let mut refunder = RefundService::new(
         pg_pool,
@@ -71,7 +80,8 @@ pub async fn run(args: arguments::Arguments) {
         i64::try_from(args.min_validity_duration.as_secs()).unwrap_or(i64::MAX),
         args.min_price_deviation_bps,
         refunder_account,
-    );
+    )
+    .with_gas_estimator(Box::new(gas_estimator));
     loop {
         tracing::info!("Staring a new refunding loop");
         match refunder.try_to_refund_all_eligble_orders().await {
@@ -11,6 +11,7 @@ use {
     ethcontract::{Account, H160, H256},
     ethrpc::{Web3, block_stream::timestamp_of_current_block_in_seconds},
     futures::{StreamExt, stream},
+    gas_estimation::GasPriceEstimating,
     sqlx::PgPool,
     std::collections::HashMap,
 };
@@ -62,6 +63,13 @@ impl RefundService {
         }
     }
 
+    /// Uses the given gas estimator instead of the node's gas price
+    /// suggestion.
+    pub fn with_gas_estimator(mut self, gas_estimator: Box<dyn GasPriceEstimating>) -> Self {
+        self.submitter.gas_estimator = gas_estimator;
+        self
+    }
+
     pub async fn try_to_refund_all_eligble_orders(&mut self) -> Result<()> {
         let refundable_order_uids = self.get_refundable_ethflow_orders_from_db().await?;
 
@@ -19,12 +19,13 @@ use {
         U256,
         transaction::{ResolveCondition, confirm::ConfirmParams},
     },
-    gas_estimation::{GasPrice1559, GasPriceEstimating},
+    gas_estimation::{DEFAULT_GAS_LIMIT, GasPrice1559, GasPriceEstimating},
     shared::{
         conversions::into_gas_price,
         ethrpc::Web3,
         submitter_constants::{TX_ALREADY_KNOWN, TX_ALREADY_MINED},
     },
+    std::time::Duration,
 };
 
 // Max gas price used for submitting transactions
@@ -38,6 +39,12 @@ const GAS_PRICE_BUFFER_FACTOR: f64 = 1.3;
 // Starting priority fee that the refunder is willing to pay. (=2 Gwei)
 const START_PRIORITY_FEE_TIP: u64 = 2_000_000_000;
 
+// Number of blocks a submitted tx gets to be mined before it is resubmitted.
+const SUBMISSION_BLOCK_TIMEOUT: usize = 5;
+// Time the tx gets to be mined. Urgency aware gas estimators pay less when
+// there is more time.
+const SUBMISSION_TIME_LIMIT: Duration = Duration::from_secs(12 * SUBMISSION_BLOCK_TIMEOUT as u64);
+
 // In order to resubmit a new tx with the same nonce, the gas tip and
 // max_fee_per_gas needs to be increased by at least 10 percent.
 const GAS_PRICE_BUMP: f64 = 1.125;
@@ -68,11 +75,14 @@ impl Submitter {
         ethflow_contract: H160,
     ) -> Result<()> {
         let confirm_params = ConfirmParams {
-            block_timeout: Some(5),
+            block_timeout: Some(SUBMISSION_BLOCK_TIMEOUT),
             ..Default::default()
         };
         let resolve_conditions = ResolveCondition::Confirmed(confirm_params);
-        let gas_price_estimation = self.gas_estimator.estimate().await?;
+        let gas_price_estimation = self
+            .gas_estimator
+            .estimate_with_limits(DEFAULT_GAS_LIMIT, SUBMISSION_TIME_LIMIT)
+            .await?;
         let nonce = self.get_submission_nonce().await?;
         let gas_price = calculate_submission_gas_price(
             self.gas_parameters_of_last_tx,
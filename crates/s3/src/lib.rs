@@ -1,13 +1,16 @@
-//! Small abstraction over the AWS S3 SDK to upload arbitrary json object to S3.
+//! Small abstraction over object storages like AWS S3 to archive and read back
+//! arbitrary json objects.
 
 use {
     anyhow::{Context, Result, anyhow},
-    aws_sdk_s3::{Client, primitives::ByteStream},
-    flate2::{Compression, bufread::GzEncoder},
-    serde::Serialize,
-    std::io::Read,
+    serde::{Serialize, de::DeserializeOwned},
+    std::{path::PathBuf, sync::Arc},
 };
 
+pub mod storage;
+
+pub use storage::Storage;
+
 #[derive(Default)]
 pub struct Config {
     pub bucket: String,
@@ -17,42 +20,82 @@ pub struct Config {
 
 #[derive(Debug, Clone)]
 pub struct Uploader {
-    bucket: String,
     filename_prefix: String,
-    client: Client,
+    storage: Arc<dyn Storage>,
 }
 
 impl Uploader {
+    /// Stores objects in the configured AWS S3 bucket.
     pub async fn new(config: Config) -> Self {
-        let uploader = Self {
-            bucket: config.bucket,
-            filename_prefix: config.filename_prefix,
-            client: Client::new(&aws_config::from_env().load().await),
-        };
+        let uploader = Self::with_storage(
+            Arc::new(storage::S3::new(config.bucket).await),
+            config.filename_prefix,
+        );
         uploader.assert_credentials_are_usable().await;
         uploader
     }
 
-    /// Upload the bytes json encoded to the configured S3 bucket. Returns the
+    /// Stores objects as files in a local directory.
+    pub fn local(directory: PathBuf, filename_prefix: String) -> Self {
+        Self::with_storage(Arc::new(storage::Local::new(directory)), filename_prefix)
+    }
+
+    /// Keeps objects in memory.
+    pub fn in_memory(filename_prefix: String) -> Self {
+        Self::with_storage(Arc::new(storage::InMemory::default()), filename_prefix)
+    }
+
+    pub fn with_storage(storage: Arc<dyn Storage>, filename_prefix: String) -> Self {
+        Self {
+            filename_prefix,
+            storage,
+        }
+    }
+
+    /// Upload the bytes json encoded to the configured storage. Returns the
     /// key under which the file can be queried
     pub async fn upload(&self, id: String, content: impl Serialize) -> Result<String> {
         let bytes = serde_json::to_vec(&content)?;
-        let encoded = self.gzip(&bytes)?;
-        let key = std::path::Path::new(&self.filename_prefix)
+        let key = self.key(&id)?;
+        self.storage.put(&key, bytes).await?;
+        Ok(key)
+    }
+
+    /// Reads back the object uploaded under the given id, if any.
+    pub async fn download<T: DeserializeOwned>(&self, id: &str) -> Result<Option<T>> {
+        let key = self.key(id)?;
+        let Some(bytes) = self.storage.get(&key).await? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .with_context(|| format!("invalid json in {key}"))
+    }
+
+    /// Returns the ids of all uploaded objects in ascending order of their
+    /// keys.
+    pub async fn list(&self) -> Result<Vec<String>> {
+        let prefix = self.key("")?;
+        let prefix = prefix.strip_suffix(".json").unwrap_or(&prefix);
+        Ok(self
+            .storage
+            .list(prefix)
+            .await?
+            .into_iter()
+            .filter_map(|key| {
+                key.strip_prefix(prefix)?
+                    .strip_suffix(".json")
+                    .map(str::to_owned)
+            })
+            .collect())
+    }
+
+    fn key(&self, id: &str) -> Result<String> {
+        Ok(std::path::Path::new(&self.filename_prefix)
             .join(format!("{id}.json"))
             .to_str()
             .context(anyhow!("invalid path: {id}"))?
-            .to_string();
-        self.client
-            .put_object()
-            .bucket(self.bucket.clone())
-            .key(key.clone())
-            .body(ByteStream::new(encoded.into()))
-            .content_encoding("gzip")
-            .content_type("application/json")
-            .send()
-            .await?;
-        Ok(key)
+            .to_string())
     }
 
     /// Uploads a small test file to verify that the credentials loaded from the
@@ -73,19 +116,11 @@ impl Uploader {
             )
         });
     }
-
-    /// Compresses the input bytes using Gzip.
-    fn gzip(&self, bytes: &[u8]) -> Result<Vec<u8>> {
-        let mut encoder = GzEncoder::new(bytes, Compression::best());
-        let mut encoded: Vec<u8> = Vec::with_capacity(bytes.len());
-        encoder.read_to_end(&mut encoded).context("gzip encoding")?;
-        Ok(encoded)
-    }
 }
 
 #[cfg(test)]
 mod tests {
-    use {super::*, flate2::read::GzDecoder, serde_json::json};
+    use {super::*, flate2::read::GzDecoder, serde_json::json, std::io::Read};
 
     // This test requires AWS credentials to be set via env variables.
     // See https://docs.rs/aws-config/latest/aws_config/default_provider/credentials/struct.DefaultCredentialsChain.html
@@ -94,10 +129,7 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn real_upload() {
-        let config = Config {
-            bucket: std::env::var("BUCKET").unwrap(),
-            filename_prefix: "test/".to_string(),
-        };
+        let storage = Arc::new(storage::S3::new(std::env::var("BUCKET").unwrap()).await);
 
         // Upload a reasonable amount of data. This helps see the benefits of
         // compression.
@@ -107,16 +139,13 @@ mod tests {
         }))
         .unwrap();
 
-        let uploader = Uploader::new(config).await;
-        let key = uploader
-            .upload("test".to_string(), value.as_bytes())
-            .await
-            .unwrap();
+        let uploader = Uploader::with_storage(storage.clone(), "test/".to_string());
+        let key = uploader.upload("test".to_string(), &value).await.unwrap();
 
-        let get_object = uploader
+        let get_object = storage
             .client
             .get_object()
-            .bucket(uploader.bucket)
+            .bucket(storage.bucket.clone())
             .key(key)
             .send()
             .await
@@ -127,6 +156,40 @@ mod tests {
         let mut decoded = String::new();
         decoder.read_to_string(&mut decoded).unwrap();
 
-        assert_eq!(value, decoded);
+        assert_eq!(serde_json::to_string(&value).unwrap(), decoded);
+        assert_eq!(
+            uploader.download::<String>("test").await.unwrap(),
+            Some(value)
+        );
+    }
+
+    #[tokio::test]
+    async fn in_memory_round_trip() {
+        let uploader = Uploader::in_memory("staging/mainnet/".to_string());
+        uploader
+            .upload("2".to_string(), json!({"id": 2}))
+            .await
+            .unwrap();
+        uploader
+            .upload("1".to_string(), json!({"id": 1}))
+            .await
+            .unwrap();
+        uploader
+            .upload("competition/1/solve".to_string(), json!({}))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            uploader.download::<serde_json::Value>("1").await.unwrap(),
+            Some(json!({"id": 1}))
+        );
+        assert_eq!(
+            uploader.download::<serde_json::Value>("3").await.unwrap(),
+            None
+        );
+        assert_eq!(
+            uploader.list().await.unwrap(),
+            vec!["1", "2", "competition/1/solve"]
+        );
     }
 }
@@ -0,0 +1,266 @@
+//! Backends for storing objects by key.
+
+use {
+    anyhow::{Context, Result},
+    aws_sdk_s3::{Client, primitives::ByteStream},
+    flate2::{
+        Compression,
+        bufread::{GzDecoder, GzEncoder},
+    },
+    std::{
+        collections::BTreeMap,
+        io::Read,
+        path::{Path, PathBuf},
+        sync::Mutex,
+    },
+};
+
+/// Stores objects under `/` separated keys.
+#[async_trait::async_trait]
+pub trait Storage: Send + Sync + std::fmt::Debug {
+    /// Stores the bytes under the key, replacing any existing object.
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()>;
+
+    /// Returns the object stored under the key if there is one.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Returns the keys of all objects starting with the prefix in ascending
+    /// order.
+    async fn list(&self, prefix: &str) -> Result<Vec<String>>;
+}
+
+/// Stores objects gzip compressed in an AWS S3 bucket.
+#[derive(Debug, Clone)]
+pub struct S3 {
+    pub(crate) bucket: String,
+    pub(crate) client: Client,
+}
+
+impl S3 {
+    /// Connects to the bucket with the credentials loaded from the
+    /// environment.
+    pub async fn new(bucket: String) -> Self {
+        Self {
+            bucket,
+            client: Client::new(&aws_config::from_env().load().await),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for S3 {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        let encoded = gzip(&bytes)?;
+        self.client
+            .put_object()
+            .bucket(self.bucket.clone())
+            .key(key)
+            .body(ByteStream::new(encoded.into()))
+            .content_encoding("gzip")
+            .content_type("application/json")
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let object = match self
+            .client
+            .get_object()
+            .bucket(self.bucket.clone())
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(object) => object,
+            Err(err)
+                if err
+                    .as_service_error()
+                    .is_some_and(|err| err.is_no_such_key()) =>
+            {
+                return Ok(None);
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let gzipped = object.content_encoding() == Some("gzip");
+        let bytes = object.body.collect().await?.to_vec();
+        Ok(Some(match gzipped {
+            true => gunzip(&bytes)?,
+            false => bytes,
+        }))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let response = self
+                .client
+                .list_objects_v2()
+                .bucket(self.bucket.clone())
+                .prefix(prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await?;
+            keys.extend(
+                response
+                    .contents()
+                    .iter()
+                    .filter_map(|object| object.key().map(str::to_owned)),
+            );
+            match response.next_continuation_token() {
+                Some(token) => continuation_token = Some(token.to_owned()),
+                None => break,
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Stores objects as files in a local directory. Useful for development
+/// environments without AWS credentials.
+#[derive(Debug, Clone)]
+pub struct Local {
+    root: PathBuf,
+}
+
+impl Local {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for Local {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("creating {parent:?}"))?;
+        }
+        tokio::fs::write(&path, bytes)
+            .await
+            .with_context(|| format!("writing {path:?}"))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.root.join(key);
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("reading {path:?}")),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err).with_context(|| format!("listing {dir:?}")),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    dirs.push(path);
+                } else if let Some(key) =
+                    key_of(&self.root, &path).filter(|key: &String| key.starts_with(prefix))
+                {
+                    keys.push(key);
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// The `/` separated key of a file in the storage directory.
+fn key_of(root: &Path, path: &Path) -> Option<String> {
+    let components = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(components.join("/"))
+}
+
+/// Keeps objects in memory. Useful for tests.
+#[derive(Debug, Default)]
+pub struct InMemory {
+    objects: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+#[async_trait::async_trait]
+impl Storage for InMemory {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        self.objects.lock().unwrap().insert(key.to_owned(), bytes);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.objects.lock().unwrap().get(key).cloned())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        Ok(self
+            .objects
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Compresses the input bytes using Gzip.
+fn gzip(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(bytes, Compression::best());
+    let mut encoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    encoder.read_to_end(&mut encoded).context("gzip encoding")?;
+    Ok(encoded)
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = GzDecoder::new(bytes);
+    let mut decoded = Vec::new();
+    decoder.read_to_end(&mut decoded).context("gzip decoding")?;
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn local_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Local::new(dir.path().to_owned());
+
+        storage.put("a/1.json", b"1".to_vec()).await.unwrap();
+        storage.put("a/b/2.json", b"2".to_vec()).await.unwrap();
+        storage.put("c.json", b"3".to_vec()).await.unwrap();
+
+        assert_eq!(
+            storage.get("a/b/2.json").await.unwrap(),
+            Some(b"2".to_vec())
+        );
+        assert_eq!(storage.get("missing.json").await.unwrap(), None);
+        assert_eq!(
+            storage.list("a/").await.unwrap(),
+            vec!["a/1.json".to_string(), "a/b/2.json".to_string()]
+        );
+    }
+
+    #[test]
+    fn gzip_round_trip() {
+        let bytes = b"test file".repeat(10);
+        assert_eq!(gunzip(&gzip(&bytes).unwrap()).unwrap(), bytes);
+    }
+}
@@ -121,6 +121,9 @@ pub struct Arguments {
     #[clap(flatten)]
     pub logging: LoggingArguments,
 
+    #[clap(flatten)]
+    pub gas_estimation: crate::gas_price_estimation::Arguments,
+
     /// The Ethereum node URL to connect to.
     #[clap(long, env, default_value = "http://localhost:8545")]
     pub node_url: Url,
@@ -137,11 +140,12 @@ pub struct Arguments {
     pub chain_id: Option<u64>,
 
     /// Which gas estimators to use. Multiple estimators are used in sequence if
-    /// a previous one fails. Individual estimators support different
-    /// networks. `EthGasStation`: supports mainnet.
+    /// a previous one fails unless they get blended. Individual estimators
+    /// support different networks. `EthGasStation`: supports mainnet.
     /// `GasNow`: supports mainnet.
     /// `Web3`: supports every network.
     /// `Native`: supports every network.
+    /// `FeeHistory`: supports every network.
     #[clap(
         long,
         env,
@@ -261,6 +265,12 @@ pub struct Arguments {
         value_parser = humantime::parse_duration,
     )]
     pub token_quality_cache_prefetch_time: Duration,
+
+    /// Tokens that take a fee of at most this many basis points on every
+    /// transfer are supported instead of being classified as bad. By default
+    /// tokens that take any fee on transfer are bad.
+    #[clap(long, env, value_parser = clap::value_parser!(u32).range(0..10_000))]
+    pub token_max_transfer_fee_bps: Option<u32>,
 }
 
 pub fn display_secret_option<T>(
@@ -339,6 +349,7 @@ impl Display for Arguments {
             current_block,
             tenderly,
             logging,
+            gas_estimation,
             node_url,
             chain_id,
             simulation_node_url,
@@ -362,12 +373,14 @@ impl Display for Arguments {
             max_pools_to_initialize_cache,
             token_quality_cache_expiry,
             token_quality_cache_prefetch_time,
+            token_max_transfer_fee_bps,
         } = self;
 
         write!(f, "{}", ethrpc)?;
         write!(f, "{}", current_block)?;
         write!(f, "{}", tenderly)?;
         write!(f, "{}", logging)?;
+        write!(f, "{}", gas_estimation)?;
         writeln!(f, "node_url: {}", node_url)?;
         display_option(f, "chain_id", chain_id)?;
         display_option(f, "simulation_node_url", simulation_node_url)?;
@@ -443,6 +456,7 @@ impl Display for Arguments {
             "token_quality_cache_prefetch_time: {:?}",
             token_quality_cache_prefetch_time
         )?;
+        display_option(f, "token_max_transfer_fee_bps", token_max_transfer_fee_bps)?;
 
         Ok(())
     }
@@ -45,6 +45,7 @@ impl BadTokenDetecting for InstrumentedBadTokenDetector {
 
         let label = match &result {
             Ok(TokenQuality::Good) => "good",
+            Ok(TokenQuality::FeeOnTransfer { .. }) => "fee_on_transfer",
             // prometheus isn't very good for string based data so we simply log the bad
             // tokens/errors and get the information from Kibana when we need it.
             Err(err) => {
@@ -4,13 +4,25 @@ pub mod list_based;
 pub mod token_owner_finder;
 pub mod trace_call;
 
-use {anyhow::Result, primitive_types::H160};
+use {
+    crate::conversions::U256Ext,
+    anyhow::Result,
+    primitive_types::{H160, U256},
+};
 
 /// How well behaved a token is.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TokenQuality {
     Good,
-    Bad { reason: String },
+    /// The token works as expected except that it takes a fee on every
+    /// transfer. Only reported by detectors that opted into supporting
+    /// fee-on-transfer tokens.
+    FeeOnTransfer {
+        fee: TransferFee,
+    },
+    Bad {
+        reason: String,
+    },
 }
 
 impl TokenQuality {
@@ -18,6 +30,19 @@ impl TokenQuality {
         matches!(self, Self::Good { .. })
     }
 
+    /// Whether orders and quotes for the token can be accepted.
+    pub fn is_supported(&self) -> bool {
+        !matches!(self, Self::Bad { .. })
+    }
+
+    /// The fee the token takes on transfers (if any).
+    pub fn transfer_fee(&self) -> Option<TransferFee> {
+        match self {
+            Self::FeeOnTransfer { fee } => Some(*fee),
+            _ => None,
+        }
+    }
+
     pub fn bad(reason: impl ToString) -> Self {
         Self::Bad {
             reason: reason.to_string(),
@@ -25,6 +50,43 @@ impl TokenQuality {
     }
 }
 
+/// Fee a token takes on every transfer, measured in basis points of the
+/// transferred amount.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct TransferFee(u32);
+
+impl TransferFee {
+    /// Denominator of the fee rate.
+    pub const MAX_BPS: u32 = 10_000;
+
+    /// Creates a transfer fee from basis points. Returns `None` if the fee
+    /// would take the full transferred amount (or more).
+    pub fn from_bps(bps: u32) -> Option<Self> {
+        (bps < Self::MAX_BPS).then_some(Self(bps))
+    }
+
+    pub fn bps(&self) -> u32 {
+        self.0
+    }
+
+    /// The amount that arrives at the recipient when `amount` tokens get
+    /// transferred. Rounds in favour of the token (i.e. down).
+    pub fn apply(&self, amount: U256) -> Option<U256> {
+        let fee = amount
+            .checked_mul(self.0.into())?
+            .checked_ceil_div(&Self::MAX_BPS.into())?;
+        amount.checked_sub(fee)
+    }
+
+    /// The amount that has to be transferred for at least `amount` tokens to
+    /// arrive at the recipient.
+    pub fn gross_up(&self, amount: U256) -> Option<U256> {
+        amount
+            .checked_mul(Self::MAX_BPS.into())?
+            .checked_ceil_div(&(Self::MAX_BPS - self.0).into())
+    }
+}
+
 /// Detect how well behaved a token is.
 #[mockall::automock]
 #[async_trait::async_trait]
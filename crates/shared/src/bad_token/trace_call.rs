@@ -1,6 +1,6 @@
 use {
-    super::{BadTokenDetecting, TokenQuality, token_owner_finder::TokenOwnerFinding},
-    crate::{ethrpc::Web3, trace_many},
+    super::{BadTokenDetecting, TokenQuality, TransferFee, token_owner_finder::TokenOwnerFinding},
+    crate::{conversions::U256Ext, ethrpc::Web3, trace_many},
     anyhow::{Context, Result, bail, ensure},
     contracts::ERC20,
     ethcontract::{
@@ -25,7 +25,8 @@ use {
 /// Tokens are bad if:
 /// - we cannot find an amm pool of the token to one of the base tokens
 /// - transfer into the settlement contract or back out fails
-/// - a transfer loses total balance
+/// - a transfer loses total balance (unless fee-on-transfer tokens are
+///   explicitly allowed)
 pub struct TraceCallDetector {
     inner: TraceCallDetectorRaw,
     finder: Arc<dyn TokenOwnerFinding>,
@@ -48,6 +49,15 @@ impl TraceCallDetector {
         }
     }
 
+    /// Classifies tokens that take a fee of at most `max_fee` on transfers as
+    /// [`TokenQuality::FeeOnTransfer`] instead of bad.
+    pub fn with_max_transfer_fee(self, max_fee: Option<TransferFee>) -> Self {
+        Self {
+            inner: self.inner.with_max_transfer_fee(max_fee),
+            ..self
+        }
+    }
+
     async fn detect_impl(&self, token: H160) -> Result<TokenQuality> {
         // Arbitrary amount that is large enough that small relative fees should be
         // visible.
@@ -91,6 +101,9 @@ impl TraceCallDetector {
 pub struct TraceCallDetectorRaw {
     pub web3: Web3,
     pub settlement_contract: H160,
+    /// Highest transfer fee a token may take without being considered bad.
+    /// `None` means that tokens taking any fee are bad.
+    pub max_transfer_fee: Option<TransferFee>,
 }
 
 impl TraceCallDetectorRaw {
@@ -98,6 +111,14 @@ impl TraceCallDetectorRaw {
         Self {
             web3,
             settlement_contract: settlement,
+            max_transfer_fee: None,
+        }
+    }
+
+    pub fn with_max_transfer_fee(self, max_fee: Option<TransferFee>) -> Self {
+        Self {
+            max_transfer_fee: max_fee,
+            ..self
         }
     }
 
@@ -149,7 +170,7 @@ impl TraceCallDetectorRaw {
             }
         };
         let relevant_traces = &traces[pre_interactions.len()..];
-        Self::handle_response(relevant_traces, amount, take_from)
+        Self::handle_response(relevant_traces, amount, take_from, self.max_transfer_fee)
     }
 
     // For the out transfer we use an arbitrary address without balance to detect
@@ -161,6 +182,29 @@ impl TraceCallDetectorRaw {
             .public_address()
     }
 
+    /// The amount that gets transferred out of the settlement contract again.
+    /// If fee-on-transfer tokens are supported we only send out what is
+    /// guaranteed to have arrived in the settlement contract for any
+    /// acceptable fee.
+    fn transfer_out_amount(amount: U256, max_transfer_fee: Option<TransferFee>) -> U256 {
+        max_transfer_fee
+            .and_then(|fee| fee.apply(amount))
+            .unwrap_or(amount)
+    }
+
+    /// Returns the fee in basis points (rounded up) that got taken when
+    /// `sent` tokens were transferred but only `received` arrived.
+    /// Discrepancies of 1 wei are ignored because they may come from rounding
+    /// in tokens that track balances with "shares" (e.g. eUSD).
+    fn observed_fee_bps(sent: U256, received: U256) -> U256 {
+        if received.saturating_add(U256::one()) >= sent {
+            return U256::zero();
+        }
+        ((sent - received).saturating_mul(TransferFee::MAX_BPS.into()))
+            .checked_ceil_div(&sent)
+            .unwrap_or(U256::MAX)
+    }
+
     fn create_trace_request(&self, token: H160, amount: U256, take_from: H160) -> Vec<CallRequest> {
         let instance = ERC20::at(&self.web3, token);
 
@@ -180,7 +224,8 @@ impl TraceCallDetectorRaw {
         let tx = instance.balance_of(recipient).m.tx;
         requests.push(call_request(None, token, tx));
         // 4
-        let tx = instance.transfer(recipient, amount).tx;
+        let amount_out = Self::transfer_out_amount(amount, self.max_transfer_fee);
+        let tx = instance.transfer(recipient, amount_out).tx;
         requests.push(call_request(Some(self.settlement_contract), token, tx));
         // 5
         let tx = instance.balance_of(self.settlement_contract).m.tx;
@@ -200,8 +245,10 @@ impl TraceCallDetectorRaw {
         traces: &[BlockTrace],
         amount: U256,
         take_from: H160,
+        max_transfer_fee: Option<TransferFee>,
     ) -> Result<TokenQuality> {
         ensure!(traces.len() == 8, "unexpected number of traces");
+        let amount_out = Self::transfer_out_amount(amount, max_transfer_fee);
 
         let gas_in = match ensure_transaction_ok_and_get_gas(&traces[1])? {
             Ok(gas) => gas,
@@ -267,7 +314,11 @@ impl TraceCallDetectorRaw {
         // Allow for a small discrepancy (1 wei) in the balance after the transfer which
         // may come from rounding discrepancies in tokens that track balances
         // with "shares" (e.g. eUSD).
-        if balance_after_in < computed_balance_after_in.saturating_sub(U256::one()) {
+        let fee_in =
+            Self::observed_fee_bps(amount, balance_after_in.saturating_sub(balance_before_in));
+        if max_transfer_fee.is_some_and(|max| fee_in <= max.bps().into()) {
+            // Fee is acceptable and gets reported at the end.
+        } else if balance_after_in < computed_balance_after_in.saturating_sub(U256::one()) {
             return Ok(TokenQuality::bad(format!(
                 "Transferring {amount} into settlement contract was expected to result in a \
                  balance of {computed_balance_after_in} but actually resulted in \
@@ -275,28 +326,35 @@ impl TraceCallDetectorRaw {
                  transfer."
             )));
         }
-        if balance_after_out != balance_before_in {
+        // Without fee support everything that got transferred in gets
+        // transferred out again.
+        let expected_balance_after_out = match max_transfer_fee {
+            None => Some(balance_before_in),
+            Some(_) => balance_after_in.checked_sub(amount_out),
+        };
+        if expected_balance_after_out != Some(balance_after_out) {
             return Ok(TokenQuality::bad(format!(
-                "Transferring {amount} out of settlement contract was expected to result in the \
-                 original balance of {balance_before_in} but actually resulted in \
+                "Transferring {amount_out} out of settlement contract was expected to result in \
+                 a balance of {expected_balance_after_out:?} but actually resulted in \
                  {balance_after_out}."
             )));
         }
-        let computed_balance_recipient_after = match balance_recipient_before.checked_add(amount) {
-            Some(amount) => amount,
-            None => {
-                return Ok(TokenQuality::bad(format!(
-                    "Transferring {amount} into arbitrary recipient {arbitrary:?} would overflow \
-                     its balance."
-                )));
-            }
-        };
+        let computed_balance_recipient_after =
+            match balance_recipient_before.checked_add(amount_out) {
+                Some(amount) => amount,
+                None => {
+                    return Ok(TokenQuality::bad(format!(
+                        "Transferring {amount_out} into arbitrary recipient {arbitrary:?} would \
+                         overflow its balance."
+                    )));
+                }
+            };
         // Allow for a small discrepancy (1 wei) in the balance after the transfer
         // which may come from rounding discrepancies in tokens that track
         // balances with "shares" (e.g. eUSD).
         if computed_balance_recipient_after < balance_recipient_after.saturating_sub(U256::one()) {
             return Ok(TokenQuality::bad(format!(
-                "Transferring {amount} into arbitrary recipient {arbitrary:?} was expected to \
+                "Transferring {amount_out} into arbitrary recipient {arbitrary:?} was expected to \
                  result in a balance of {computed_balance_recipient_after} but actually resulted \
                  in {balance_recipient_after}. A common cause for this is that the token takes a \
                  fee on transfer."
@@ -310,7 +368,25 @@ impl TraceCallDetectorRaw {
         }
 
         let _gas_per_transfer = (gas_in + gas_out) / 2;
-        Ok(TokenQuality::Good)
+
+        let Some(max_transfer_fee) = max_transfer_fee else {
+            return Ok(TokenQuality::Good);
+        };
+        let fee_out = Self::observed_fee_bps(
+            amount_out,
+            balance_recipient_after.saturating_sub(balance_recipient_before),
+        );
+        let fee = cmp::max(fee_in, fee_out);
+        if fee.is_zero() {
+            return Ok(TokenQuality::Good);
+        }
+        match u32::try_from(fee).ok().and_then(TransferFee::from_bps) {
+            Some(fee) if fee <= max_transfer_fee => Ok(TokenQuality::FeeOnTransfer { fee }),
+            _ => Ok(TokenQuality::bad(format!(
+                "Token takes a fee of {fee} bps on transfers which exceeds the maximum of {} bps.",
+                max_transfer_fee.bps()
+            ))),
+        }
     }
 }
 
@@ -510,11 +586,90 @@ mod tests {
             },
         ];
 
-        let result = TraceCallDetectorRaw::handle_response(traces, 1.into(), H160::zero()).unwrap();
+        let result =
+            TraceCallDetectorRaw::handle_response(traces, 1.into(), H160::zero(), None).unwrap();
         let expected = TokenQuality::Good;
         assert_eq!(result, expected);
     }
 
+    fn balance_trace(balance: u64) -> BlockTrace {
+        BlockTrace {
+            output: encode_u256(balance.into()),
+            trace: None,
+            vm_trace: None,
+            state_diff: None,
+            transaction_hash: None,
+        }
+    }
+
+    fn call_trace() -> BlockTrace {
+        BlockTrace {
+            output: Default::default(),
+            trace: Some(vec![TransactionTrace {
+                trace_address: Vec::new(),
+                subtraces: 0,
+                action: Action::Call(Call {
+                    from: H160::zero(),
+                    to: H160::zero(),
+                    value: 0.into(),
+                    gas: 0.into(),
+                    input: Bytes(Vec::new()),
+                    call_type: CallType::None,
+                }),
+                action_type: ActionType::Call,
+                result: Some(Res::Call(CallResult {
+                    gas_used: 1.into(),
+                    output: Bytes(Vec::new()),
+                })),
+                error: None,
+            }]),
+            vm_trace: None,
+            state_diff: None,
+            transaction_hash: None,
+        }
+    }
+
+    #[test]
+    fn handle_response_fee_on_transfer() {
+        // Token takes 1% on every transfer. With a max fee of 2% we send
+        // 100_000 in (99_000 arrive) and 98_000 out again (97_020 arrive).
+        let traces = &[
+            balance_trace(0),
+            call_trace(),
+            balance_trace(99_000),
+            balance_trace(0),
+            call_trace(),
+            balance_trace(1_000),
+            balance_trace(97_020),
+            call_trace(),
+        ];
+        let handle = |max_fee| {
+            TraceCallDetectorRaw::handle_response(
+                traces,
+                100_000.into(),
+                H160::zero(),
+                TransferFee::from_bps(max_fee),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            handle(200),
+            TokenQuality::FeeOnTransfer {
+                fee: TransferFee::from_bps(100).unwrap()
+            }
+        );
+        // Same traces but the detector expects a smaller out amount so
+        // the balances no longer add up.
+        assert!(!handle(50).is_supported());
+        // Without fee support the token is bad.
+        assert!(
+            !TraceCallDetectorRaw::handle_response(traces, 100_000.into(), H160::zero(), None)
+                .unwrap()
+                .is_supported()
+        );
+    }
+
     #[test]
     fn arbitrary_recipient_() {
         println!("{:?}", TraceCallDetectorRaw::arbitrary_recipient());
@@ -14,6 +14,8 @@ use {
     web3::types::{BlockNumber, CallRequest},
 };
 
+pub mod evm;
+
 /// Simulate a call with state overrides.
 #[async_trait::async_trait]
 pub trait CodeSimulating: Send + Sync + 'static {
@@ -0,0 +1,534 @@
+//! In-process EVM simulation that lazily forks state from a node.
+//!
+//! Accounts, code and storage are fetched from the node the first time the
+//! EVM accesses them and cached per block, so repeated simulations on the same
+//! block only hit the node for state they haven't seen before.
+
+use {
+    super::{CodeSimulating, SimulationError},
+    anyhow::{Context, Result, anyhow},
+    chain::Chain,
+    ethrpc::{
+        Web3,
+        extensions::{StateOverride, StateOverrides},
+    },
+    futures::{Future, TryFutureExt},
+    revm::{
+        DatabaseRef,
+        Evm,
+        db::CacheDB,
+        primitives::{
+            AccountInfo,
+            Address,
+            B256,
+            Bytecode,
+            Bytes,
+            ExecutionResult,
+            KECCAK_EMPTY,
+            Output,
+            SpecId,
+            TxKind,
+            U256,
+        },
+    },
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::{Arc, Mutex},
+    },
+    web3::types::{AccessList, AccessListItem, BlockId, BlockNumber, CallRequest, H160, H256},
+};
+
+/// Number of most recent blocks for which the fetched state is kept.
+const CACHED_BLOCKS: usize = 3;
+
+/// Simulates calls in a local EVM on top of the state of the node.
+pub struct RevmSimulator {
+    web3: Web3,
+    chain_id: u64,
+    states: Mutex<VecDeque<(u64, Arc<BlockState>)>>,
+}
+
+/// The outcome of a simulated call.
+#[derive(Debug)]
+pub struct Simulation {
+    pub gas_used: u64,
+    /// All accounts and storage slots accessed by the call, except for the
+    /// caller, the called contract, the coinbase and precompiles.
+    pub access_list: AccessList,
+    pub result: Result<Vec<u8>, SimulationError>,
+}
+
+impl RevmSimulator {
+    pub fn new(web3: Web3, chain_id: u64) -> Self {
+        Self {
+            web3,
+            chain_id,
+            states: Default::default(),
+        }
+    }
+
+    /// Simulates the call at the end of the given block, or the latest block
+    /// if none is specified. Only errors unrelated to the call itself, like
+    /// node errors, are returned as [`Err`].
+    pub async fn simulate_call(
+        &self,
+        call: CallRequest,
+        overrides: StateOverrides,
+        block: Option<u64>,
+    ) -> Result<Simulation> {
+        let block = match block {
+            Some(block) => block,
+            None => self.web3.eth().block_number().await?.as_u64(),
+        };
+        let header = self
+            .web3
+            .eth()
+            .block(BlockId::Number(BlockNumber::Number(block.into())))
+            .await?
+            .with_context(|| format!("missing block {block}"))?;
+
+        let fork = Fork {
+            web3: self.web3.clone(),
+            block,
+            state: self.state(block),
+            runtime: tokio::runtime::Handle::current(),
+        };
+        let timestamp = header.timestamp.as_u64();
+        let env = BlockEnv {
+            chain_id: self.chain_id,
+            spec: spec_id(self.chain_id, block, timestamp),
+            number: block,
+            coinbase: address(header.author),
+            timestamp,
+            gas_limit: header.gas_limit,
+            basefee: header.base_fee_per_gas.unwrap_or_default(),
+            prevrandao: header.mix_hash.map(b256).unwrap_or_default(),
+        };
+        tokio::task::spawn_blocking(move || -> Result<Simulation> {
+            let mut db = CacheDB::new(fork);
+            for (address, state_override) in overrides {
+                apply_override(&mut db, self::address(address), state_override)?;
+            }
+            transact(db, env, call)
+        })
+        .await?
+    }
+
+    /// The cached state of the block. The state of the oldest block gets
+    /// evicted once too many blocks are cached.
+    fn state(&self, block: u64) -> Arc<BlockState> {
+        let mut states = self.states.lock().unwrap();
+        if let Some((_, state)) = states.iter().find(|(cached, _)| *cached == block) {
+            return state.clone();
+        }
+        let state = Arc::new(BlockState::default());
+        states.push_back((block, state.clone()));
+        if states.len() > CACHED_BLOCKS {
+            states.pop_front();
+        }
+        state
+    }
+}
+
+#[async_trait::async_trait]
+impl CodeSimulating for RevmSimulator {
+    async fn simulate(
+        &self,
+        call: CallRequest,
+        overrides: StateOverrides,
+        block: Option<u64>,
+    ) -> Result<Vec<u8>, SimulationError> {
+        self.simulate_call(call, overrides, block).await?.result
+    }
+}
+
+/// The environment of the block a call is simulated in.
+struct BlockEnv {
+    chain_id: u64,
+    spec: SpecId,
+    number: u64,
+    coinbase: Address,
+    timestamp: u64,
+    gas_limit: web3::types::U256,
+    basefee: web3::types::U256,
+    prevrandao: B256,
+}
+
+/// Executes the call on top of the database.
+fn transact<DB>(db: CacheDB<DB>, env: BlockEnv, call: CallRequest) -> Result<Simulation>
+where
+    DB: DatabaseRef,
+    DB::Error: std::fmt::Debug,
+{
+    let caller = address(call.from.unwrap_or_default());
+    let to = call.to.map(address);
+    let coinbase = env.coinbase;
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .with_spec_id(env.spec)
+        .modify_cfg_env(|cfg| {
+            cfg.chain_id = env.chain_id;
+            // Calls are simulated without paying for gas like `eth_call`
+            // does, while the `BASEFEE` opcode still sees the block's base fee.
+            cfg.disable_base_fee = true;
+        })
+        .modify_block_env(|block| {
+            block.number = U256::from(env.number);
+            block.coinbase = coinbase;
+            block.timestamp = U256::from(env.timestamp);
+            block.gas_limit = u256(env.gas_limit);
+            block.basefee = u256(env.basefee);
+            block.prevrandao = Some(env.prevrandao);
+        })
+        .modify_tx_env(|tx| {
+            tx.caller = caller;
+            tx.transact_to = match to {
+                Some(to) => TxKind::Call(to),
+                None => TxKind::Create,
+            };
+            tx.data = Bytes::from(call.data.unwrap_or_default().0);
+            tx.value = u256(call.value.unwrap_or_default());
+            tx.gas_limit = call.gas.unwrap_or(env.gas_limit).as_u64();
+            tx.gas_price = U256::ZERO;
+            tx.nonce = None;
+        })
+        .build();
+    let outcome = evm
+        .transact()
+        .map_err(|err| anyhow!("EVM error: {err:?}"))?;
+
+    // Like `eth_createAccessList`, the sender and the recipient are left out
+    // since they are always warm.
+    let access_list = outcome
+        .state
+        .iter()
+        .filter(|(address, _)| {
+            **address != caller
+                && Some(**address) != to
+                && **address != coinbase
+                && !is_precompile(address)
+        })
+        .map(|(address, account)| AccessListItem {
+            address: H160(address.0.0),
+            storage_keys: account
+                .storage
+                .keys()
+                .map(|slot| H256(slot.to_be_bytes::<32>()))
+                .collect(),
+        })
+        .collect();
+    let (gas_used, result) = match outcome.result {
+        ExecutionResult::Success {
+            gas_used, output, ..
+        } => {
+            let output = match output {
+                Output::Call(output) => output,
+                Output::Create(output, _) => output,
+            };
+            (gas_used, Ok(output.to_vec()))
+        }
+        ExecutionResult::Revert { gas_used, output } => (
+            gas_used,
+            Err(SimulationError::Revert(Some(format!(
+                "0x{}",
+                hex::encode(output)
+            )))),
+        ),
+        ExecutionResult::Halt { reason, gas_used } => (
+            gas_used,
+            Err(SimulationError::Revert(Some(format!("{reason:?}")))),
+        ),
+    };
+    Ok(Simulation {
+        gas_used,
+        access_list,
+        result,
+    })
+}
+
+/// The hard fork active at the given block. Chains whose hard fork schedule
+/// isn't known are simulated with Cancun rules, which all of them support.
+fn spec_id(chain_id: u64, block: u64, timestamp: u64) -> SpecId {
+    // Activation timestamps of Prague, Cancun and Shanghai and the spec
+    // before Shanghai.
+    let (prague, cancun, shanghai, before_shanghai) = match Chain::try_from(chain_id) {
+        Ok(Chain::Mainnet) => (
+            1_746_612_311,
+            1_710_338_135,
+            1_681_338_455,
+            match block {
+                15_537_394.. => SpecId::MERGE,
+                12_965_000.. => SpecId::LONDON,
+                _ => SpecId::BERLIN,
+            },
+        ),
+        Ok(Chain::Sepolia) => (1_741_159_776, 1_706_655_072, 1_677_557_088, SpecId::MERGE),
+        Ok(Chain::Gnosis) => (1_746_021_820, 1_710_181_820, 1_704_401_480, SpecId::MERGE),
+        // Both OP stack chains activated the hard forks at the same time.
+        Ok(Chain::Base | Chain::Optimism) => {
+            (1_746_806_401, 1_710_374_401, 1_704_992_401, SpecId::MERGE)
+        }
+        _ => return SpecId::CANCUN,
+    };
+    match timestamp {
+        t if t >= prague => SpecId::PRAGUE,
+        t if t >= cancun => SpecId::CANCUN,
+        t if t >= shanghai => SpecId::SHANGHAI,
+        _ => before_shanghai,
+    }
+}
+
+fn apply_override(
+    db: &mut CacheDB<Fork>,
+    address: Address,
+    state_override: StateOverride,
+) -> Result<()> {
+    let mut info = db.basic_ref(address)?.unwrap_or_default();
+    if let Some(balance) = state_override.balance {
+        info.balance = u256(balance);
+    }
+    if let Some(nonce) = state_override.nonce {
+        info.nonce = nonce.as_u64();
+    }
+    if let Some(code) = state_override.code {
+        let code = Bytecode::new_raw(Bytes::from(code.0));
+        info.code_hash = code.hash_slow();
+        info.code = Some(code);
+    }
+    db.insert_account_info(address, info);
+
+    let slots = |slots: HashMap<H256, H256>| {
+        slots
+            .into_iter()
+            .map(|(slot, value)| (U256::from_be_bytes(slot.0), U256::from_be_bytes(value.0)))
+            .collect::<revm::primitives::HashMap<_, _>>()
+    };
+    if let Some(state) = state_override.state {
+        db.replace_account_storage(address, slots(state))?;
+    }
+    for (slot, value) in state_override.state_diff.map(slots).unwrap_or_default() {
+        db.insert_account_storage(address, slot, value)?;
+    }
+    Ok(())
+}
+
+/// State fetched from the node for a specific block.
+#[derive(Default)]
+struct BlockState {
+    accounts: Mutex<HashMap<Address, AccountInfo>>,
+    storage: Mutex<HashMap<(Address, U256), U256>>,
+    block_hashes: Mutex<HashMap<u64, B256>>,
+}
+
+/// Database that forks the state of a block from the node.
+struct Fork {
+    web3: Web3,
+    block: u64,
+    state: Arc<BlockState>,
+    /// Handle to the runtime for fetching state from the node. The EVM runs on
+    /// a blocking thread, so the futures can be blocked on.
+    runtime: tokio::runtime::Handle,
+}
+
+impl Fork {
+    fn block_number(&self) -> Option<BlockNumber> {
+        Some(BlockNumber::Number(self.block.into()))
+    }
+
+    fn fetch<T>(&self, future: impl Future<Output = Result<T, web3::Error>>) -> Result<T> {
+        Ok(self.runtime.block_on(future)?)
+    }
+}
+
+impl DatabaseRef for Fork {
+    type Error = anyhow::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>> {
+        if let Some(info) = self.state.accounts.lock().unwrap().get(&address) {
+            return Ok(Some(info.clone()));
+        }
+        let h160 = H160(address.0.0);
+        let eth = self.web3.eth();
+        let (balance, nonce, code) = self.fetch(futures::future::try_join3(
+            eth.balance(h160, self.block_number()),
+            eth.transaction_count(h160, self.block_number()),
+            eth.code(h160, self.block_number()),
+        ))?;
+        let info = match code.0.is_empty() {
+            true => AccountInfo::new(u256(balance), nonce.as_u64(), KECCAK_EMPTY, Bytecode::new()),
+            false => {
+                let code = Bytecode::new_raw(Bytes::from(code.0));
+                AccountInfo::new(u256(balance), nonce.as_u64(), code.hash_slow(), code)
+            }
+        };
+        self.state
+            .accounts
+            .lock()
+            .unwrap()
+            .insert(address, info.clone());
+        Ok(Some(info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode> {
+        // Code is always fetched together with its account.
+        self.state
+            .accounts
+            .lock()
+            .unwrap()
+            .values()
+            .find(|info| info.code_hash == code_hash)
+            .and_then(|info| info.code.clone())
+            .with_context(|| format!("unknown code hash {code_hash}"))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256> {
+        if let Some(value) = self.state.storage.lock().unwrap().get(&(address, index)) {
+            return Ok(*value);
+        }
+        let value = self.fetch(self.web3.eth().storage(
+            H160(address.0.0),
+            web3::types::U256::from_big_endian(&index.to_be_bytes::<32>()),
+            self.block_number(),
+        ))?;
+        let value = U256::from_be_bytes(value.0);
+        self.state
+            .storage
+            .lock()
+            .unwrap()
+            .insert((address, index), value);
+        Ok(value)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256> {
+        let number = number.saturating_to::<u64>();
+        if let Some(hash) = self.state.block_hashes.lock().unwrap().get(&number) {
+            return Ok(*hash);
+        }
+        let block = self.fetch(
+            self.web3
+                .eth()
+                .block(BlockId::Number(BlockNumber::Number(number.into())))
+                .map_ok(|block| block.and_then(|block| block.hash)),
+        )?;
+        let hash = block.map(b256).unwrap_or_default();
+        self.state.block_hashes.lock().unwrap().insert(number, hash);
+        Ok(hash)
+    }
+}
+
+fn address(address: H160) -> Address {
+    Address::from(address.0)
+}
+
+fn u256(value: web3::types::U256) -> U256 {
+    let mut bytes = [0; 32];
+    value.to_big_endian(&mut bytes);
+    U256::from_be_bytes(bytes)
+}
+
+fn b256(value: H256) -> B256 {
+    B256::from(value.0)
+}
+
+/// Whether the address is one of the precompiled contracts, which
+/// `eth_createAccessList` excludes from access lists as well.
+fn is_precompile(address: &Address) -> bool {
+    let bytes = address.as_slice();
+    bytes[..19].iter().all(|byte| *byte == 0) && (1..=0x11).contains(&bytes[19])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_numbers() {
+        let value = web3::types::U256::from(1) << 200 | web3::types::U256::from(42);
+        let converted = u256(value);
+        assert_eq!(converted, (U256::from(1) << 200) | U256::from(42));
+    }
+
+    #[test]
+    fn detects_precompiles() {
+        assert!(is_precompile(&Address::with_last_byte(1)));
+        assert!(is_precompile(&Address::with_last_byte(0x0a)));
+        assert!(!is_precompile(&Address::ZERO));
+        assert!(!is_precompile(&Address::repeat_byte(1)));
+    }
+
+    #[test]
+    fn simulates_gas_and_access_list() {
+        let caller = Address::repeat_byte(0xca);
+        let coinbase = Address::repeat_byte(0xcb);
+        let a = Address::repeat_byte(0xaa);
+        let b = Address::repeat_byte(0xbb);
+
+        let mut db = CacheDB::new(revm::db::EmptyDB::default());
+        let mut deploy = |address: Address, code: Vec<u8>| {
+            let code = Bytecode::new_raw(Bytes::from(code));
+            db.insert_account_info(
+                address,
+                AccountInfo {
+                    code_hash: code.hash_slow(),
+                    code: Some(code),
+                    ..Default::default()
+                },
+            );
+        };
+        // PUSH1 0 (x5), PUSH20 b, GAS, CALL, POP, STOP
+        let mut code = [0x60, 0x00].repeat(5);
+        code.push(0x73);
+        code.extend_from_slice(b.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x50, 0x00]);
+        deploy(a, code);
+        // PUSH1 1, SLOAD, POP, STOP
+        deploy(b, vec![0x60, 0x01, 0x54, 0x50, 0x00]);
+
+        let env = BlockEnv {
+            chain_id: 1,
+            spec: SpecId::CANCUN,
+            number: 20_000_000,
+            coinbase,
+            timestamp: 1_717_000_000,
+            gas_limit: 30_000_000.into(),
+            // The call doesn't pay for gas even though the block has a base
+            // fee.
+            basefee: 1_000_000_000.into(),
+            prevrandao: B256::ZERO,
+        };
+        let call = CallRequest {
+            from: Some(H160(caller.0.0)),
+            to: Some(H160(a.0.0)),
+            ..Default::default()
+        };
+        let simulation = transact(db, env, call).unwrap();
+
+        assert_eq!(simulation.result.unwrap(), Vec::<u8>::new());
+        // intrinsic gas, pushes, `GAS`, cold `CALL`, callee with cold `SLOAD`,
+        // `POP`
+        assert_eq!(
+            simulation.gas_used,
+            21_000 + 5 * 3 + 3 + 2 + 2_600 + (3 + 2_100 + 2) + 2
+        );
+        assert_eq!(
+            simulation.access_list,
+            vec![AccessListItem {
+                address: H160(b.0.0),
+                storage_keys: vec![H256::from_low_u64_be(1)],
+            }]
+        );
+    }
+
+    #[test]
+    fn derives_spec_from_chain_and_block() {
+        assert_eq!(spec_id(1, 12_000_000, 1_600_000_000), SpecId::BERLIN);
+        assert_eq!(spec_id(1, 13_000_000, 1_630_000_000), SpecId::LONDON);
+        assert_eq!(spec_id(1, 16_000_000, 1_670_000_000), SpecId::MERGE);
+        assert_eq!(spec_id(1, 17_500_000, 1_690_000_000), SpecId::SHANGHAI);
+        assert_eq!(spec_id(1, 20_000_000, 1_717_000_000), SpecId::CANCUN);
+        assert_eq!(spec_id(1, 22_500_000, 1_748_000_000), SpecId::PRAGUE);
+        assert_eq!(spec_id(100, 33_000_000, 1_705_000_000), SpecId::SHANGHAI);
+        assert_eq!(spec_id(8453, 10_000_000, 1_704_000_000), SpecId::MERGE);
+        assert_eq!(spec_id(42161, 1, 0), SpecId::CANCUN);
+    }
+}
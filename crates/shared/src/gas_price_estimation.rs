@@ -1,5 +1,12 @@
+pub mod blended;
+pub mod fee_history;
+
 use {
-    crate::{ethrpc::Web3, http_client::HttpClientFactory},
+    self::{
+        blended::BlendedGasPriceEstimator,
+        fee_history::{FeeHistoryGasEstimator, Params},
+    },
+    crate::{arguments::display_option, ethrpc::Web3, http_client::HttpClientFactory},
     anyhow::{Context, Result, ensure},
     gas_estimation::{
         EthGasStation,
@@ -13,7 +20,10 @@ use {
     },
     reqwest::header::{self, HeaderMap, HeaderValue},
     serde::de::DeserializeOwned,
-    std::sync::{Arc, Mutex},
+    std::{
+        fmt::{self, Display, Formatter},
+        sync::{Arc, Mutex},
+    },
 };
 
 #[derive(Copy, Clone, Debug, clap::ValueEnum)]
@@ -24,6 +34,73 @@ pub enum GasEstimatorType {
     Web3,
     BlockNative,
     Native,
+    FeeHistory,
+}
+
+/// Arguments configuring how gas price estimators get combined and the
+/// `FeeHistory` estimator.
+#[derive(clap::Parser)]
+#[group(skip)]
+pub struct Arguments {
+    /// Query all configured gas estimators and use this percentile (0-100) of
+    /// their estimates instead of only falling back to the next estimator
+    /// when one fails.
+    #[clap(long, env)]
+    pub gas_estimators_blend_percentile: Option<f64>,
+
+    /// Number of recent blocks the `FeeHistory` estimator samples paid
+    /// priority fees from.
+    #[clap(long, env, default_value = "20")]
+    pub fee_history_blocks: u64,
+
+    /// Percentile of the sampled priority fees the `FeeHistory` estimator
+    /// pays for transactions that are not urgent.
+    #[clap(long, env, default_value = "25")]
+    pub fee_history_min_percentile: f64,
+
+    /// Percentile of the sampled priority fees the `FeeHistory` estimator
+    /// pays for transactions that have to be included in the next block.
+    #[clap(long, env, default_value = "75")]
+    pub fee_history_max_percentile: f64,
+}
+
+impl Arguments {
+    pub fn fee_history(&self) -> Params {
+        Params {
+            blocks: self.fee_history_blocks,
+            min_percentile: self.fee_history_min_percentile,
+            max_percentile: self.fee_history_max_percentile,
+        }
+    }
+}
+
+impl Display for Arguments {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Self {
+            gas_estimators_blend_percentile,
+            fee_history_blocks,
+            fee_history_min_percentile,
+            fee_history_max_percentile,
+        } = self;
+
+        display_option(
+            f,
+            "gas_estimators_blend_percentile",
+            gas_estimators_blend_percentile,
+        )?;
+        writeln!(f, "fee_history_blocks: {}", fee_history_blocks)?;
+        writeln!(
+            f,
+            "fee_history_min_percentile: {}",
+            fee_history_min_percentile
+        )?;
+        writeln!(
+            f,
+            "fee_history_max_percentile: {}",
+            fee_history_max_percentile
+        )?;
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
@@ -51,6 +128,7 @@ pub async fn create_priority_estimator(
     web3: &Web3,
     estimator_types: &[GasEstimatorType],
     blocknative_api_key: Option<String>,
+    args: &Arguments,
 ) -> Result<impl GasPriceEstimating + use<>> {
     let client = || Client(http_factory.create());
     let network_id = web3.eth().chain_id().await?.to_string();
@@ -98,15 +176,32 @@ pub async fn create_priority_estimator(
                     Err(err) => tracing::error!("nativegasestimator failed: {}", err),
                 }
             }
+            GasEstimatorType::FeeHistory => estimators.push(Box::new(FeeHistoryGasEstimator::new(
+                web3.clone(),
+                args.fee_history(),
+            ))),
         }
     }
     anyhow::ensure!(
         !estimators.is_empty(),
         "all gas estimators failed to initialize"
     );
+    if let Some(percentile) = args.gas_estimators_blend_percentile {
+        estimators = vec![Box::new(BlendedGasPriceEstimator::new(
+            estimators, percentile,
+        ))];
+    }
     Ok(PriorityGasPriceEstimating::new(estimators))
 }
 
+/// Returns the value at the given percentile (0-100) of the sorted values
+/// using the nearest rank.
+fn percentile(sorted: &[f64], percentile: f64) -> Option<f64> {
+    let max_index = sorted.len().checked_sub(1)?;
+    let index = (percentile.clamp(0., 100.) / 100. * max_index as f64).round() as usize;
+    sorted.get(index).copied()
+}
+
 fn is_mainnet(network_id: &str) -> bool {
     network_id == "1"
 }
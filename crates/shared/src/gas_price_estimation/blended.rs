@@ -0,0 +1,94 @@
+//! Gas price estimator that queries all configured sources and picks a
+//! percentile of their estimates instead of only using the first available
+//! one.
+
+use {
+    super::percentile,
+    anyhow::{Result, anyhow},
+    futures::future,
+    gas_estimation::{GasPrice1559, GasPriceEstimating},
+    std::time::Duration,
+};
+
+pub struct BlendedGasPriceEstimator {
+    estimators: Vec<Box<dyn GasPriceEstimating>>,
+    /// Percentile (0-100) of the individual estimates to use.
+    percentile: f64,
+}
+
+impl BlendedGasPriceEstimator {
+    pub fn new(estimators: Vec<Box<dyn GasPriceEstimating>>, percentile: f64) -> Self {
+        Self {
+            estimators,
+            percentile,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl GasPriceEstimating for BlendedGasPriceEstimator {
+    async fn estimate_with_limits(
+        &self,
+        gas_limit: f64,
+        time_limit: Duration,
+    ) -> Result<GasPrice1559> {
+        let estimates = future::join_all(
+            self.estimators
+                .iter()
+                .map(|estimator| estimator.estimate_with_limits(gas_limit, time_limit)),
+        )
+        .await
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, result)| {
+            result
+                .inspect_err(|err| tracing::warn!(?err, estimator = i, "gas estimator failed"))
+                .ok()
+        })
+        .collect::<Vec<_>>();
+        blend(&estimates, self.percentile).ok_or_else(|| anyhow!("all gas estimators failed"))
+    }
+}
+
+/// Selects the percentile of every component of the gas price individually.
+fn blend(estimates: &[GasPrice1559], p: f64) -> Option<GasPrice1559> {
+    let component = |f: fn(&GasPrice1559) -> f64| {
+        let mut values: Vec<_> = estimates.iter().map(f).collect();
+        values.sort_by(f64::total_cmp);
+        percentile(&values, p)
+    };
+    let max_priority_fee_per_gas = component(|gas| gas.max_priority_fee_per_gas)?;
+    Some(GasPrice1559 {
+        base_fee_per_gas: component(|gas| gas.base_fee_per_gas)?,
+        // the max fee must never be lower than the priority fee
+        max_fee_per_gas: component(|gas| gas.max_fee_per_gas)?.max(max_priority_fee_per_gas),
+        max_priority_fee_per_gas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gas_price(base: f64, max: f64, tip: f64) -> GasPrice1559 {
+        GasPrice1559 {
+            base_fee_per_gas: base,
+            max_fee_per_gas: max,
+            max_priority_fee_per_gas: tip,
+        }
+    }
+
+    #[test]
+    fn blends_components_individually() {
+        let estimates = [
+            gas_price(10., 30., 1.),
+            gas_price(12., 20., 5.),
+            gas_price(11., 25., 3.),
+        ];
+
+        assert_eq!(blend(&estimates, 50.), Some(gas_price(11., 25., 3.)));
+        assert_eq!(blend(&estimates, 100.), Some(gas_price(12., 30., 5.)));
+        assert_eq!(blend(&estimates, 0.), Some(gas_price(10., 20., 1.)));
+        assert_eq!(blend(&[], 50.), None);
+    }
+}
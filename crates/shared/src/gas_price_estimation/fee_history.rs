@@ -0,0 +1,262 @@
+//! Gas price estimation based on the priority fees that were actually paid in
+//! recent blocks (`eth_feeHistory`).
+//!
+//! Instead of a single suggestion the estimator builds the distribution of
+//! priority fees over the sampled blocks. The urgency of a transaction decides
+//! which percentile of that distribution gets paid, so transactions that can
+//! wait don't overpay during calm periods and urgent transactions keep up with
+//! spikes.
+
+use {
+    crate::ethrpc::Web3,
+    anyhow::{Context, Result},
+    gas_estimation::{DEFAULT_TIME_LIMIT, GasPrice1559, GasPriceEstimating},
+    std::time::Duration,
+    web3::types::{BlockNumber, FeeHistory},
+};
+
+/// Percentiles of the priority fees paid within each sampled block.
+const REWARD_PERCENTILES: [f64; 5] = [10., 25., 50., 75., 90.];
+
+/// Expected time between two blocks used to convert time limits into the
+/// number of blocks a transaction has to be included in.
+const BLOCK_TIME: Duration = Duration::from_secs(12);
+
+/// Maximum factor by which the base fee can increase from one block to the
+/// next one (EIP-1559).
+const MAX_BASE_FEE_INCREASE: f64 = 1.125;
+
+/// Maximum number of blocks of base fee increases the max fee covers. Long
+/// time limits would otherwise lead to absurdly high max fees (e.g. 200x after
+/// 45 blocks) while transactions usually get resubmitted with updated prices
+/// well before.
+const MAX_BASE_FEE_INCREASE_BLOCKS: u32 = 5;
+
+#[derive(Clone, Debug)]
+pub struct Params {
+    /// Number of recent blocks to sample priority fees from.
+    pub blocks: u64,
+    /// Percentile of the sampled priority fees paid by transactions that can
+    /// wait for [`DEFAULT_TIME_LIMIT`] or longer.
+    pub min_percentile: f64,
+    /// Percentile of the sampled priority fees paid by transactions that have
+    /// to be included in the next block.
+    pub max_percentile: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            blocks: 20,
+            min_percentile: 25.,
+            max_percentile: 75.,
+        }
+    }
+}
+
+impl Params {
+    /// Returns the urgency of a transaction that has to be included within
+    /// the time limit. The percentile is interpolated linearly between the
+    /// maximum percentile for an immediate inclusion and the minimum
+    /// percentile for [`DEFAULT_TIME_LIMIT`].
+    pub fn urgency(&self, time_limit: Duration) -> Urgency {
+        let relaxation = (time_limit.as_secs_f64() / DEFAULT_TIME_LIMIT.as_secs_f64()).min(1.);
+        let blocks = (time_limit.as_secs_f64() / BLOCK_TIME.as_secs_f64()).ceil();
+        Urgency {
+            percentile: self.max_percentile
+                - (self.max_percentile - self.min_percentile) * relaxation,
+            blocks: (blocks as u32).max(1),
+        }
+    }
+}
+
+/// How quickly a transaction needs to be included.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Urgency {
+    /// Percentile (0-100) of the recently paid priority fees to pay.
+    pub percentile: f64,
+    /// Number of blocks the transaction has to be included within. The max
+    /// fee covers the base fee increasing in every one of these blocks (up to
+    /// `MAX_BASE_FEE_INCREASE_BLOCKS`).
+    pub blocks: u32,
+}
+
+/// Priority fees paid in recent blocks together with the base fee of the next
+/// block.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeeDistribution {
+    /// Base fee of the block following the sampled ones.
+    pub base_fee: f64,
+    /// Sampled priority fees in ascending order.
+    pub priority_fees: Vec<f64>,
+}
+
+impl FeeDistribution {
+    /// Builds the distribution from a fee history response. Empty blocks are
+    /// ignored since they report a priority fee of zero for every percentile.
+    pub fn new(history: &FeeHistory) -> Self {
+        let base_fee = history
+            .base_fee_per_gas
+            .last()
+            .map(|fee| fee.to_f64_lossy())
+            .unwrap_or_default();
+        let mut priority_fees: Vec<_> = history
+            .reward
+            .iter()
+            .flatten()
+            .zip(&history.gas_used_ratio)
+            .filter(|(_, gas_used_ratio)| **gas_used_ratio > 0.)
+            .flat_map(|(rewards, _)| rewards.iter().map(|reward| reward.to_f64_lossy()))
+            .collect();
+        priority_fees.sort_by(f64::total_cmp);
+        Self {
+            base_fee,
+            priority_fees,
+        }
+    }
+
+    /// Returns the priority fee at the given percentile (0-100) of the
+    /// distribution or 0 if no priority fees were sampled.
+    pub fn priority_fee(&self, percentile: f64) -> f64 {
+        super::percentile(&self.priority_fees, percentile).unwrap_or_default()
+    }
+
+    /// Returns the gas price a transaction with the given urgency should pay.
+    pub fn gas_price(&self, urgency: Urgency) -> GasPrice1559 {
+        let priority_fee = self.priority_fee(urgency.percentile);
+        let blocks = urgency.blocks.min(MAX_BASE_FEE_INCREASE_BLOCKS);
+        let max_base_fee = self.base_fee * MAX_BASE_FEE_INCREASE.powi(blocks as i32);
+        GasPrice1559 {
+            base_fee_per_gas: self.base_fee,
+            max_fee_per_gas: max_base_fee + priority_fee,
+            max_priority_fee_per_gas: priority_fee,
+        }
+    }
+}
+
+pub struct FeeHistoryGasEstimator {
+    web3: Web3,
+    params: Params,
+}
+
+impl FeeHistoryGasEstimator {
+    pub fn new(web3: Web3, params: Params) -> Self {
+        Self { web3, params }
+    }
+
+    /// Fetches the distribution of priority fees paid in the most recent
+    /// blocks.
+    pub async fn distribution(&self) -> Result<FeeDistribution> {
+        let history = self
+            .web3
+            .eth()
+            .fee_history(
+                self.params.blocks.into(),
+                BlockNumber::Latest,
+                Some(REWARD_PERCENTILES.to_vec()),
+            )
+            .await
+            .context("eth_feeHistory")?;
+        Ok(FeeDistribution::new(&history))
+    }
+
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+}
+
+#[async_trait::async_trait]
+impl GasPriceEstimating for FeeHistoryGasEstimator {
+    async fn estimate_with_limits(&self, _: f64, time_limit: Duration) -> Result<GasPrice1559> {
+        let distribution = self.distribution().await?;
+        Ok(distribution.gas_price(self.params.urgency(time_limit)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, web3::types::U256};
+
+    #[test]
+    fn builds_distribution_from_non_empty_blocks() {
+        let history = FeeHistory {
+            oldest_block: BlockNumber::Number(1.into()),
+            base_fee_per_gas: vec![90.into(), 100.into(), 110.into()],
+            gas_used_ratio: vec![0.5, 0.],
+            reward: Some(vec![
+                vec![3.into(), 1.into(), 2.into()],
+                vec![U256::zero(), U256::zero(), U256::zero()],
+            ]),
+        };
+
+        let distribution = FeeDistribution::new(&history);
+        assert_eq!(
+            distribution,
+            FeeDistribution {
+                base_fee: 110.,
+                priority_fees: vec![1., 2., 3.],
+            }
+        );
+        assert_eq!(distribution.priority_fee(0.), 1.);
+        assert_eq!(distribution.priority_fee(50.), 2.);
+        assert_eq!(distribution.priority_fee(100.), 3.);
+        assert_eq!(FeeDistribution::default().priority_fee(50.), 0.);
+    }
+
+    #[test]
+    fn urgency_depends_on_time_limit() {
+        let params = Params {
+            blocks: 10,
+            min_percentile: 20.,
+            max_percentile: 80.,
+        };
+
+        assert_eq!(
+            params.urgency(Duration::ZERO),
+            Urgency {
+                percentile: 80.,
+                blocks: 1,
+            }
+        );
+        assert_eq!(params.urgency(DEFAULT_TIME_LIMIT / 2).percentile, 50.);
+        assert_eq!(params.urgency(Duration::from_secs(25)).blocks, 3);
+        assert_eq!(params.urgency(DEFAULT_TIME_LIMIT * 10).percentile, 20.);
+    }
+
+    #[test]
+    fn max_fee_covers_base_fee_increases() {
+        let distribution = FeeDistribution {
+            base_fee: 100.,
+            priority_fees: vec![1., 2., 3.],
+        };
+
+        let gas_price = distribution.gas_price(Urgency {
+            percentile: 100.,
+            blocks: 2,
+        });
+        assert_eq!(gas_price.base_fee_per_gas, 100.);
+        assert_eq!(gas_price.max_priority_fee_per_gas, 3.);
+        assert_eq!(gas_price.max_fee_per_gas, 100. * 1.125 * 1.125 + 3.);
+    }
+
+    #[test]
+    fn max_fee_is_bounded_for_long_time_limits() {
+        let distribution = FeeDistribution {
+            base_fee: 100.,
+            priority_fees: vec![],
+        };
+
+        let bounded = distribution.gas_price(Urgency {
+            percentile: 50.,
+            blocks: MAX_BASE_FEE_INCREASE_BLOCKS,
+        });
+        for blocks in [MAX_BASE_FEE_INCREASE_BLOCKS + 1, 1_000, u32::MAX] {
+            let gas_price = distribution.gas_price(Urgency {
+                percentile: 50.,
+                blocks,
+            });
+            assert_eq!(gas_price.max_fee_per_gas, bounded.max_fee_per_gas);
+        }
+        assert_eq!(bounded.max_fee_per_gas, 100. * 1.125_f64.powi(5));
+    }
+}
@@ -7,6 +7,7 @@ use {
     },
     crate::{
         account_balances::{BalanceFetching, Query},
+        bad_token::{BadTokenDetecting, TransferFee},
         db_order_conversions::order_kind_from,
         fee::FeeParameters,
         order_validation::PreOrderData,
@@ -105,6 +106,40 @@ impl Quote {
         }
     }
 
+    /// Creates a new `Quote` from quote data computed for the specified
+    /// parameters, accounting for additional costs and sell amounts before
+    /// fees.
+    pub fn from_data(
+        data: QuoteData,
+        parameters: &QuoteParameters,
+    ) -> Result<Self, CalculateQuoteError> {
+        let mut quote =
+            Quote::new(Default::default(), data).with_additional_cost(parameters.additional_cost());
+
+        // Make sure to scale the sell and buy amounts for quotes for sell
+        // amounts before fees.
+        if let OrderQuoteSide::Sell {
+            sell_amount:
+                SellAmount::BeforeFee {
+                    value: sell_amount_before_fee,
+                },
+        } = &parameters.side
+        {
+            let sell_amount =
+                Into::<U256>::into(*sell_amount_before_fee).saturating_sub(quote.fee_amount);
+            if sell_amount == U256::zero() {
+                // We want a sell_amount of at least 1!
+                return Err(CalculateQuoteError::SellAmountDoesNotCoverFee {
+                    fee_amount: quote.fee_amount,
+                });
+            }
+
+            quote = quote.with_scaled_sell_amount(sell_amount);
+        }
+
+        Ok(quote)
+    }
+
     /// Adjusts the quote fee to include arbitrary additional costs.
     pub fn with_additional_cost(mut self, additional_cost: u64) -> Self {
         // Be careful not to modify `self.data` as this represents the actual
@@ -375,12 +410,34 @@ impl Now for DateTime<Utc> {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct Validity {
     pub eip1271_onchain_quote: Duration,
     pub presign_onchain_quote: Duration,
     pub standard_quote: Duration,
 }
 
+impl Validity {
+    /// When a quote computed at `now` for orders with the given signing scheme
+    /// expires.
+    pub fn expiration(
+        &self,
+        signing_scheme: &QuoteSigningScheme,
+        now: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        match signing_scheme {
+            QuoteSigningScheme::Eip1271 {
+                onchain_order: true,
+                ..
+            } => now + self.eip1271_onchain_quote,
+            QuoteSigningScheme::PreSign {
+                onchain_order: true,
+            } => now + self.presign_onchain_quote,
+            _ => now + self.standard_quote,
+        }
+    }
+}
+
 #[cfg(test)]
 impl Default for Validity {
     fn default() -> Self {
@@ -402,6 +459,7 @@ pub struct OrderQuoter {
     validity: Validity,
     balance_fetcher: Arc<dyn BalanceFetching>,
     quote_verification: QuoteVerificationMode,
+    bad_token_detector: Option<Arc<dyn BadTokenDetecting>>,
 }
 
 impl OrderQuoter {
@@ -423,23 +481,38 @@ impl OrderQuoter {
             validity,
             balance_fetcher,
             quote_verification,
+            bad_token_detector: None,
         }
     }
 
+    /// Accounts for fees taken by fee-on-transfer tokens as reported by the
+    /// given detector when computing quotes.
+    pub fn with_bad_token_detector(self, detector: Arc<dyn BadTokenDetecting>) -> Self {
+        Self {
+            bad_token_detector: Some(detector),
+            ..self
+        }
+    }
+
+    /// Returns the transfer fee the given token takes (if any).
+    async fn transfer_fee(&self, token: H160) -> Result<Option<TransferFee>> {
+        let Some(detector) = &self.bad_token_detector else {
+            return Ok(None);
+        };
+        let quality = detector
+            .detect(token)
+            .await
+            .context("bad token detection")?;
+        Ok(quality.transfer_fee())
+    }
+
     async fn compute_quote_data(
         &self,
         parameters: &QuoteParameters,
     ) -> Result<QuoteData, CalculateQuoteError> {
-        let expiration = match parameters.signing_scheme {
-            QuoteSigningScheme::Eip1271 {
-                onchain_order: true,
-                ..
-            } => self.now.now() + self.validity.eip1271_onchain_quote,
-            QuoteSigningScheme::PreSign {
-                onchain_order: true,
-            } => self.now.now() + self.validity.presign_onchain_quote,
-            _ => self.now.now() + self.validity.standard_quote,
-        };
+        let expiration = self
+            .validity
+            .expiration(&parameters.signing_scheme, self.now.now());
 
         let trade_query = Arc::new(parameters.to_price_query());
         let (gas_estimate, trade_estimate, sell_token_price, _) = futures::try_join!(
@@ -462,6 +535,10 @@ impl OrderQuoter {
                 .estimate_native_price(parameters.buy_token)
                 .map_err(|err| (EstimatorKind::NativeBuy, err).into()),
         )?;
+        let (sell_token_fee, buy_token_fee) = futures::try_join!(
+            self.transfer_fee(parameters.sell_token),
+            self.transfer_fee(parameters.buy_token),
+        )?;
 
         let (quoted_sell_amount, quoted_buy_amount) = match &parameters.side {
             OrderQuoteSide::Sell {
@@ -474,6 +551,11 @@ impl OrderQuoter {
                 buy_amount_after_fee: buy_amount,
             } => (trade_estimate.out_amount, buy_amount.get()),
         };
+        let (quoted_sell_amount, quoted_buy_amount) = apply_transfer_fees(
+            &parameters.side,
+            (quoted_sell_amount, quoted_buy_amount),
+            [sell_token_fee, buy_token_fee],
+        )?;
         let fee_parameters = FeeParameters {
             gas_amount: trade_estimate.gas as _,
             gas_price: gas_estimate.effective_gas_price(),
@@ -499,6 +581,8 @@ impl OrderQuoter {
                 interactions: trade_estimate.execution.interactions,
                 pre_interactions: trade_estimate.execution.pre_interactions,
                 jit_orders: trade_estimate.execution.jit_orders,
+                sell_token_transfer_fee_bps: sell_token_fee.map(|fee| fee.bps()),
+                buy_token_transfer_fee_bps: buy_token_fee.map(|fee| fee.bps()),
             }
             .into(),
         };
@@ -570,29 +654,7 @@ impl OrderQuoting for OrderQuoter {
         parameters: QuoteParameters,
     ) -> Result<Quote, CalculateQuoteError> {
         let data = self.compute_quote_data(&parameters).await?;
-        let mut quote =
-            Quote::new(Default::default(), data).with_additional_cost(parameters.additional_cost());
-
-        // Make sure to scale the sell and buy amounts for quotes for sell
-        // amounts before fees.
-        if let OrderQuoteSide::Sell {
-            sell_amount:
-                SellAmount::BeforeFee {
-                    value: sell_amount_before_fee,
-                },
-        } = &parameters.side
-        {
-            let sell_amount =
-                Into::<U256>::into(*sell_amount_before_fee).saturating_sub(quote.fee_amount);
-            if sell_amount == U256::zero() {
-                // We want a sell_amount of at least 1!
-                return Err(CalculateQuoteError::SellAmountDoesNotCoverFee {
-                    fee_amount: quote.fee_amount,
-                });
-            }
-
-            quote = quote.with_scaled_sell_amount(sell_amount);
-        }
+        let quote = Quote::from_data(data, &parameters)?;
 
         tracing::debug!(?quote, "computed quote");
         Ok(quote)
@@ -748,6 +810,43 @@ pub struct QuoteMetadataV1 {
     pub pre_interactions: Vec<InteractionData>,
     /// Orders that were settled outside of the auction.
     pub jit_orders: Vec<dto::JitOrder>,
+    /// Fee in basis points the sell token takes on every transfer. Already
+    /// accounted for in the quoted amounts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sell_token_transfer_fee_bps: Option<u32>,
+    /// Fee in basis points the buy token takes on every transfer. Already
+    /// accounted for in the quoted amounts for the transfer into the
+    /// settlement contract. The receiver of the order additionally gets that
+    /// much less than the buy amount.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buy_token_transfer_fee_bps: Option<u32>,
+}
+
+/// Adjusts the quoted amounts for the fees taken by fee-on-transfer tokens.
+/// Only the amount of sell token left after its transfer fee arrives in the
+/// settlement contract, and the same goes for the buy token bought from the
+/// liquidity. Sell orders therefore get less buy token for their sell amount
+/// and buy orders need more sell token for their buy amount.
+fn apply_transfer_fees(
+    side: &OrderQuoteSide,
+    (mut sell_amount, mut buy_amount): (U256, U256),
+    fees: [Option<TransferFee>; 2],
+) -> Result<(U256, U256)> {
+    for fee in fees.into_iter().flatten() {
+        match side {
+            OrderQuoteSide::Sell { .. } => {
+                buy_amount = fee
+                    .apply(buy_amount)
+                    .context("buy amount after transfer fee")?;
+            }
+            OrderQuoteSide::Buy { .. } => {
+                sell_amount = fee
+                    .gross_up(sell_amount)
+                    .context("sell amount including transfer fee")?;
+            }
+        }
+    }
+    Ok((sell_amount, buy_amount))
 }
 
 #[cfg(test)]
@@ -756,6 +855,7 @@ mod tests {
         super::*,
         crate::{
             account_balances::MockBalanceFetching,
+            bad_token::{MockBadTokenDetecting, TokenQuality},
             gas_price_estimation::FakeGasPriceEstimator,
             price_estimation::{MockPriceEstimating, native::MockNativePriceEstimating},
         },
@@ -903,6 +1003,7 @@ mod tests {
             validity: super::Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         let quote = quoter.calculate_quote(parameters).await.unwrap();
@@ -936,6 +1037,101 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn compute_quote_with_fee_on_transfer_sell_token() {
+        let parameters = QuoteParameters {
+            sell_token: H160([1; 20]),
+            buy_token: H160([2; 20]),
+            side: OrderQuoteSide::Sell {
+                sell_amount: SellAmount::BeforeFee {
+                    value: NonZeroU256::try_from(1000).unwrap(),
+                },
+            },
+            verification: Default::default(),
+            signing_scheme: QuoteSigningScheme::Eip712,
+            additional_gas: 0,
+        };
+
+        let mut price_estimator = MockPriceEstimating::new();
+        price_estimator.expect_estimate().returning(|_| {
+            async {
+                Ok(price_estimation::Estimate {
+                    out_amount: 2000.into(),
+                    gas: 3,
+                    solver: H160([1; 20]),
+                    verified: false,
+                    execution: Default::default(),
+                })
+            }
+            .boxed()
+        });
+        let mut native_price_estimator = MockNativePriceEstimating::new();
+        native_price_estimator
+            .expect_estimate_native_price()
+            .returning(|_| async { Ok(0.2) }.boxed());
+        let mut bad_token_detector = MockBadTokenDetecting::new();
+        bad_token_detector.expect_detect().returning(|token| {
+            Ok(if token == H160([1; 20]) {
+                TokenQuality::FeeOnTransfer {
+                    fee: TransferFee::from_bps(100).unwrap(),
+                }
+            } else {
+                TokenQuality::Good
+            })
+        });
+
+        let quoter = OrderQuoter {
+            price_estimator: Arc::new(price_estimator),
+            native_price_estimator: Arc::new(native_price_estimator),
+            gas_estimator: Arc::new(FakeGasPriceEstimator::new(GasPrice1559 {
+                base_fee_per_gas: 1.5,
+                max_fee_per_gas: 3.0,
+                max_priority_fee_per_gas: 0.5,
+            })),
+            storage: Arc::new(MockQuoteStoring::new()),
+            now: Arc::new(Utc::now),
+            validity: super::Validity::default(),
+            quote_verification: QuoteVerificationMode::Unverified,
+            balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: Some(Arc::new(bad_token_detector)),
+        };
+
+        let quote = quoter.compute_quote_data(&parameters).await.unwrap();
+        // 1% of the sell amount gets lost on the way into the settlement
+        // contract so the buy amount shrinks accordingly.
+        assert_eq!(quote.quoted_sell_amount, 1000.into());
+        assert_eq!(quote.quoted_buy_amount, 1980.into());
+        let QuoteMetadata::V1(metadata) = quote.metadata;
+        assert_eq!(metadata.sell_token_transfer_fee_bps, Some(100));
+        assert_eq!(metadata.buy_token_transfer_fee_bps, None);
+    }
+
+    #[test]
+    fn transfer_fees_of_both_tokens_reduce_quotes() {
+        let fees = [TransferFee::from_bps(100), TransferFee::from_bps(1_000)];
+        let sell = OrderQuoteSide::Sell {
+            sell_amount: SellAmount::BeforeFee {
+                value: NonZeroU256::try_from(1000).unwrap(),
+            },
+        };
+        assert_eq!(
+            apply_transfer_fees(&sell, (1000.into(), 2000.into()), fees).unwrap(),
+            (1000.into(), 1782.into())
+        );
+        assert_eq!(
+            apply_transfer_fees(&sell, (1000.into(), 2000.into()), [None, fees[1]]).unwrap(),
+            (1000.into(), 1800.into())
+        );
+
+        let buy = OrderQuoteSide::Buy {
+            buy_amount_after_fee: NonZeroU256::try_from(2000).unwrap(),
+        };
+        assert_eq!(
+            apply_transfer_fees(&buy, (1000.into(), 2000.into()), fees).unwrap(),
+            (1124.into(), 2000.into())
+        );
+    }
+
     #[tokio::test]
     async fn compute_sell_after_fee_quote() {
         let now = Utc::now();
@@ -1041,6 +1237,7 @@ mod tests {
             validity: Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         let quote = quoter.calculate_quote(parameters).await.unwrap();
@@ -1174,6 +1371,7 @@ mod tests {
             validity: Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         let quote = quoter.calculate_quote(parameters).await.unwrap();
@@ -1271,6 +1469,7 @@ mod tests {
             validity: Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         assert!(matches!(
@@ -1343,6 +1542,7 @@ mod tests {
             validity: Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         assert!(matches!(
@@ -1397,12 +1597,17 @@ mod tests {
         let quoter = OrderQuoter {
             price_estimator: Arc::new(MockPriceEstimating::new()),
             native_price_estimator: Arc::new(MockNativePriceEstimating::new()),
-            gas_estimator: Arc::new(FakeGasPriceEstimator::default()),
+            gas_estimator: Arc::new(FakeGasPriceEstimator::new(GasPrice1559 {
+                base_fee_per_gas: 1.5,
+                max_fee_per_gas: 3.0,
+                max_priority_fee_per_gas: 0.5,
+            })),
             storage: Arc::new(storage),
             now: Arc::new(now),
             validity: Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         assert_eq!(
@@ -1479,12 +1684,17 @@ mod tests {
         let quoter = OrderQuoter {
             price_estimator: Arc::new(MockPriceEstimating::new()),
             native_price_estimator: Arc::new(MockNativePriceEstimating::new()),
-            gas_estimator: Arc::new(FakeGasPriceEstimator::default()),
+            gas_estimator: Arc::new(FakeGasPriceEstimator::new(GasPrice1559 {
+                base_fee_per_gas: 1.5,
+                max_fee_per_gas: 3.0,
+                max_priority_fee_per_gas: 0.5,
+            })),
             storage: Arc::new(storage),
             now: Arc::new(now),
             validity: Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         assert_eq!(
@@ -1563,12 +1773,17 @@ mod tests {
         let quoter = OrderQuoter {
             price_estimator: Arc::new(MockPriceEstimating::new()),
             native_price_estimator: Arc::new(MockNativePriceEstimating::new()),
-            gas_estimator: Arc::new(FakeGasPriceEstimator::default()),
+            gas_estimator: Arc::new(FakeGasPriceEstimator::new(GasPrice1559 {
+                base_fee_per_gas: 1.5,
+                max_fee_per_gas: 3.0,
+                max_priority_fee_per_gas: 0.5,
+            })),
             storage: Arc::new(storage),
             now: Arc::new(now),
             validity: Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         assert_eq!(
@@ -1635,12 +1850,17 @@ mod tests {
         let quoter = OrderQuoter {
             price_estimator: Arc::new(MockPriceEstimating::new()),
             native_price_estimator: Arc::new(MockNativePriceEstimating::new()),
-            gas_estimator: Arc::new(FakeGasPriceEstimator::default()),
+            gas_estimator: Arc::new(FakeGasPriceEstimator::new(GasPrice1559 {
+                base_fee_per_gas: 1.5,
+                max_fee_per_gas: 3.0,
+                max_priority_fee_per_gas: 0.5,
+            })),
             storage: Arc::new(storage),
             now: Arc::new(now),
             validity: Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         assert!(matches!(
@@ -1665,12 +1885,17 @@ mod tests {
         let quoter = OrderQuoter {
             price_estimator: Arc::new(MockPriceEstimating::new()),
             native_price_estimator: Arc::new(MockNativePriceEstimating::new()),
-            gas_estimator: Arc::new(FakeGasPriceEstimator::default()),
+            gas_estimator: Arc::new(FakeGasPriceEstimator::new(GasPrice1559 {
+                base_fee_per_gas: 1.5,
+                max_fee_per_gas: 3.0,
+                max_priority_fee_per_gas: 0.5,
+            })),
             storage: Arc::new(storage),
             now: Arc::new(Utc::now),
             validity: Validity::default(),
             quote_verification: QuoteVerificationMode::Unverified,
             balance_fetcher: mock_balance_fetcher(),
+            bad_token_detector: None,
         };
 
         assert!(matches!(
@@ -1732,6 +1957,8 @@ mod tests {
                 signature: vec![1; 16],
                 signing_scheme: model::signature::SigningScheme::Eip712,
             }],
+            sell_token_transfer_fee_bps: None,
+            buy_token_transfer_fee_bps: None,
         }
         .into();
         let v = serde_json::to_value(q).unwrap();
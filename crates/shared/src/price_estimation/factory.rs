@@ -9,7 +9,11 @@ use {
         native::{self, NativePriceEstimator},
         native_price_cache::CachingNativePriceEstimator,
         sanitized::SanitizedPriceEstimator,
-        trade_verifier::{TradeVerifier, TradeVerifying},
+        trade_verifier::{
+            TradeVerifier,
+            TradeVerifying,
+            balance_overrides::BalanceOverrideStoring,
+        },
     },
     crate::{
         arguments,
@@ -71,6 +75,7 @@ pub struct Components {
     pub bad_token_detector: Arc<dyn BadTokenDetecting>,
     pub tokens: Arc<dyn TokenInfoFetching>,
     pub code_fetcher: Arc<CachedCodeFetcher>,
+    pub balance_override_storage: Option<Arc<dyn BalanceOverrideStoring>>,
 }
 
 impl<'a> PriceEstimatorFactory<'a> {
@@ -107,6 +112,10 @@ impl<'a> PriceEstimatorFactory<'a> {
             .map(|t| TenderlyCodeSimulator::new(t, network.chain.id()));
 
         let simulator: Arc<dyn CodeSimulating> = match tenderly {
+            _ if args.local_code_simulation => Arc::new(code_simulation::evm::RevmSimulator::new(
+                web3.clone(),
+                network.chain.id(),
+            )),
             Some(tenderly) => Arc::new(code_simulation::Web3ThenTenderly::new(
                 web3.clone(),
                 tenderly,
@@ -114,7 +123,11 @@ impl<'a> PriceEstimatorFactory<'a> {
             None => Arc::new(web3.clone()),
         };
 
-        let balance_overrides = args.balance_overrides.init(simulator.clone());
+        let balance_overrides = args.balance_overrides.init(
+            simulator.clone(),
+            web3.clone(),
+            components.balance_override_storage.clone(),
+        );
 
         let verifier = TradeVerifier::new(
             web3,
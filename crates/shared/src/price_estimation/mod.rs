@@ -262,6 +262,11 @@ pub struct Arguments {
         value_parser = parse_tuple::<H160, H160>
     )]
     pub native_price_approximation_tokens: Vec<(H160, H160)>,
+
+    /// Verify quotes by simulating them in a local EVM that forks the state
+    /// of the simulation node instead of using `eth_call` on the node.
+    #[clap(long, env, action = clap::ArgAction::Set, default_value = "false")]
+    pub local_code_simulation: bool,
 }
 
 /// Custom Clap parser for tuple pair
@@ -359,6 +364,7 @@ impl Display for Arguments {
             quote_timeout,
             balance_overrides,
             native_price_approximation_tokens,
+            local_code_simulation,
         } = self;
 
         display_option(
@@ -442,6 +448,7 @@ impl Display for Arguments {
             "native_price_approximation_tokens: {:?}",
             native_price_approximation_tokens
         )?;
+        writeln!(f, "local_code_simulation: {}", local_code_simulation)?;
 
         Ok(())
     }
@@ -1,16 +1,19 @@
 mod detector;
+mod storage_trace;
 
 use {
     self::detector::{DetectionError, Detector},
-    crate::code_simulation::CodeSimulating,
+    crate::{code_simulation::CodeSimulating, ethrpc::Web3},
     anyhow::Context as _,
     cached::{Cached, SizedCache},
+    chrono::{DateTime, Utc},
+    database::byte_array::ByteArray,
     ethcontract::{Address, H256, U256},
     ethrpc::extensions::StateOverride,
     maplit::hashmap,
     std::{
         collections::HashMap,
-        fmt::{self, Display, Formatter},
+        fmt::{self, Debug, Display, Formatter},
         str::FromStr,
         sync::{Arc, Mutex},
     },
@@ -47,17 +50,32 @@ pub struct Arguments {
     /// balance override detection before evicting less used entries.
     #[clap(long, env, action = clap::ArgAction::Set, default_value = "1000")]
     pub quote_autodetect_token_balance_overrides_cache_size: usize,
+
+    /// Fall back to tracing the storage accesses of `balanceOf()` with
+    /// `debug_traceCall` for tokens where probing storage slots does not find
+    /// the balances mapping. Requires the simulation node to support the
+    /// `debug` namespace.
+    #[clap(long, env, action = clap::ArgAction::Set, default_value_t)]
+    pub quote_autodetect_token_balance_overrides_tracing: bool,
 }
 
 impl Arguments {
     /// Creates a balance overrides instance from the current configuration.
-    pub fn init(&self, simulator: Arc<dyn CodeSimulating>) -> Arc<dyn BalanceOverriding> {
+    /// Auto-detected strategies get persisted in `storage` if provided.
+    pub fn init(
+        &self,
+        simulator: Arc<dyn CodeSimulating>,
+        web3: Web3,
+        storage: Option<Arc<dyn BalanceOverrideStoring>>,
+    ) -> Arc<dyn BalanceOverriding> {
         Arc::new(BalanceOverrides {
             hardcoded: self.quote_token_balance_overrides.0.clone(),
             detector: self.quote_autodetect_token_balance_overrides.then(|| {
                 (
                     Detector::new(
                         simulator,
+                        self.quote_autodetect_token_balance_overrides_tracing
+                            .then_some(web3),
                         self.quote_autodetect_token_balance_overrides_probing_depth,
                     ),
                     Mutex::new(SizedCache::with_size(
@@ -65,6 +83,7 @@ impl Arguments {
                     )),
                 )
             }),
+            storage,
         })
     }
 }
@@ -76,6 +95,7 @@ impl Display for Arguments {
             quote_autodetect_token_balance_overrides,
             quote_autodetect_token_balance_overrides_probing_depth,
             quote_autodetect_token_balance_overrides_cache_size,
+            quote_autodetect_token_balance_overrides_tracing,
         } = self;
 
         writeln!(
@@ -98,6 +118,11 @@ impl Display for Arguments {
             "quote_autodetect_token_balance_overrides_cache_size: {:?}",
             quote_autodetect_token_balance_overrides_cache_size
         )?;
+        writeln!(
+            f,
+            "quote_autodetect_token_balance_overrides_tracing: {:?}",
+            quote_autodetect_token_balance_overrides_tracing
+        )?;
 
         Ok(())
     }
@@ -112,6 +137,7 @@ impl Display for TokenConfiguration {
         let format_entry =
             |f: &mut Formatter, (addr, strategy): (&Address, &Strategy)| match strategy {
                 Strategy::SolidityMapping { slot } => write!(f, "{addr:?}@{slot}"),
+                Strategy::VyperMapping { slot } => write!(f, "VyperMapping({addr:?}@{slot})"),
                 Strategy::SoladyMapping => write!(f, "SoladyMapping({addr:?})"),
             };
 
@@ -166,6 +192,33 @@ pub trait BalanceOverriding: Send + Sync + 'static {
     async fn state_override(&self, request: BalanceOverrideRequest) -> Option<StateOverride>;
 }
 
+/// Persistent storage for automatically detected balance override strategies
+/// so that detection only has to happen once per token across restarts and
+/// services.
+#[async_trait::async_trait]
+pub trait BalanceOverrideStoring: Send + Sync + 'static {
+    async fn fetch(&self, token: Address) -> anyhow::Result<Option<Strategy>>;
+    async fn save(&self, token: Address, strategy: &Strategy) -> anyhow::Result<()>;
+}
+
+#[async_trait::async_trait]
+impl BalanceOverrideStoring for sqlx::PgPool {
+    async fn fetch(&self, token: Address) -> anyhow::Result<Option<Strategy>> {
+        let mut ex = self.acquire().await?;
+        database::token_balance_overrides::fetch(&mut ex, &ByteArray(token.0))
+            .await?
+            .map(|row| Strategy::try_from(&row).context("invalid stored strategy"))
+            .transpose()
+    }
+
+    async fn save(&self, token: Address, strategy: &Strategy) -> anyhow::Result<()> {
+        let mut ex = self.acquire().await?;
+        database::token_balance_overrides::upsert(&mut ex, &strategy.to_db_row(token, Utc::now()))
+            .await?;
+        Ok(())
+    }
+}
+
 /// Parameters for computing a balance override request.
 pub struct BalanceOverrideRequest {
     /// The token for the override.
@@ -177,7 +230,8 @@ pub struct BalanceOverrideRequest {
 }
 
 /// Balance override strategy for a token.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
 pub enum Strategy {
     /// Balance override strategy for tokens whose balances are stored in a
     /// direct Solidity mapping from token holder to balance amount in the
@@ -187,6 +241,12 @@ pub enum Strategy {
     ///
     /// [^1]: <https://docs.soliditylang.org/en/latest/internals/layout_in_storage.html#mappings-and-dynamic-arrays>
     SolidityMapping { slot: U256 },
+    /// Balance override strategy for tokens whose balances are stored in a
+    /// Vyper `HashMap[address, uint256]`. Unlike Solidity, Vyper computes the
+    /// storage location as `keccak256(slot . key)` [^1].
+    ///
+    /// [^1]: <https://docs.vyperlang.org/en/stable/scoping-and-declarations.html#storage-layout>
+    VyperMapping { slot: U256 },
     /// Strategy computing storage slot for balances based on the Solady library
     /// [^1].
     ///
@@ -205,6 +265,12 @@ impl Strategy {
                 slot.to_big_endian(&mut buf[32..64]);
                 H256(signing::keccak256(&buf))
             }
+            Self::VyperMapping { slot } => {
+                let mut buf = [0; 64];
+                slot.to_big_endian(&mut buf[0..32]);
+                buf[44..64].copy_from_slice(holder.as_fixed_bytes());
+                H256(signing::keccak256(&buf))
+            }
             Self::SoladyMapping => {
                 let mut buf = [0; 32];
                 buf[0..20].copy_from_slice(holder.as_fixed_bytes());
@@ -221,12 +287,57 @@ impl Strategy {
 
         (key, value)
     }
+
+    /// Converts the strategy into its database representation.
+    pub fn to_db_row(
+        &self,
+        token: Address,
+        detected_at: DateTime<Utc>,
+    ) -> database::token_balance_overrides::BalanceOverride {
+        use database::token_balance_overrides::Strategy as DbStrategy;
+
+        let slot = |slot: &U256| {
+            let mut buf = [0; 32];
+            slot.to_big_endian(&mut buf);
+            Some(ByteArray(buf))
+        };
+        let (strategy, slot) = match self {
+            Self::SolidityMapping { slot: s } => (DbStrategy::SolidityMapping, slot(s)),
+            Self::VyperMapping { slot: s } => (DbStrategy::VyperMapping, slot(s)),
+            Self::SoladyMapping => (DbStrategy::SoladyMapping, None),
+        };
+        database::token_balance_overrides::BalanceOverride {
+            token: ByteArray(token.0),
+            strategy,
+            slot,
+            detected_at,
+        }
+    }
+}
+
+impl TryFrom<&database::token_balance_overrides::BalanceOverride> for Strategy {
+    type Error = anyhow::Error;
+
+    fn try_from(row: &database::token_balance_overrides::BalanceOverride) -> anyhow::Result<Self> {
+        use database::token_balance_overrides::Strategy as DbStrategy;
+
+        let slot = || {
+            row.slot
+                .map(|slot| U256::from_big_endian(&slot.0))
+                .context("missing balances mapping slot")
+        };
+        Ok(match row.strategy {
+            DbStrategy::SolidityMapping => Self::SolidityMapping { slot: slot()? },
+            DbStrategy::VyperMapping => Self::VyperMapping { slot: slot()? },
+            DbStrategy::SoladyMapping => Self::SoladyMapping,
+        })
+    }
 }
 
 type DetectorCache = Mutex<SizedCache<Address, Option<Strategy>>>;
 
 /// The default balance override provider.
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct BalanceOverrides {
     /// The configured balance override strategies for tokens.
     ///
@@ -237,6 +348,9 @@ pub struct BalanceOverrides {
     /// The balance override detector and its cache. Set to `None` if
     /// auto-detection is not enabled.
     detector: Option<(Detector, DetectorCache)>,
+    /// Persistent storage for auto-detected strategies. Consulted before
+    /// running the detector for tokens missing from the in-memory cache.
+    storage: Option<Arc<dyn BalanceOverrideStoring>>,
 }
 
 impl BalanceOverrides {
@@ -252,7 +366,19 @@ impl BalanceOverrides {
             }
         }
 
+        if let Some(strategy) = self.stored_strategy(token).await {
+            tracing::trace!(?token, "storage hit");
+            cache
+                .lock()
+                .unwrap()
+                .cache_set(token, Some(strategy.clone()));
+            return Some(strategy);
+        }
+
         let strategy = detector.detect(token).await;
+        if let Ok(strategy) = &strategy {
+            self.store_strategy(token, strategy).await;
+        }
 
         // Only cache when we successfully detect the token, or we can't find
         // it. Anything else is likely a temporary simulator (i.e. node) failure
@@ -271,6 +397,39 @@ impl BalanceOverrides {
 
         strategy.ok()
     }
+
+    async fn stored_strategy(&self, token: Address) -> Option<Strategy> {
+        match self.storage.as_ref()?.fetch(token).await {
+            Ok(strategy) => strategy,
+            Err(err) => {
+                tracing::warn!(
+                    ?token,
+                    ?err,
+                    "failed to fetch stored balance override strategy"
+                );
+                None
+            }
+        }
+    }
+
+    async fn store_strategy(&self, token: Address, strategy: &Strategy) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        if let Err(err) = storage.save(token, strategy).await {
+            tracing::warn!(?token, ?err, "failed to persist balance override strategy");
+        }
+    }
+}
+
+impl Debug for BalanceOverrides {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("BalanceOverrides")
+            .field("hardcoded", &self.hardcoded)
+            .field("detector", &self.detector)
+            .field("storage", &self.storage.is_some())
+            .finish()
+    }
 }
 
 #[async_trait::async_trait]
@@ -1,9 +1,12 @@
 use {
-    super::Strategy,
-    crate::code_simulation::{CodeSimulating, SimulationError},
+    super::{Strategy, storage_trace},
+    crate::{
+        code_simulation::{CodeSimulating, SimulationError},
+        ethrpc::Web3,
+    },
     contracts::{ERC20, dummy_contract},
     ethcontract::{Address, H256, U256},
-    ethrpc::extensions::StateOverride,
+    ethrpc::extensions::{DebugNamespace, StateOverride},
     maplit::hashmap,
     std::{
         collections::HashMap,
@@ -11,17 +14,24 @@ use {
         sync::Arc,
     },
     thiserror::Error,
-    web3::{signing::keccak256, types::CallRequest},
+    web3::{
+        signing::keccak256,
+        types::{BlockNumber, CallRequest},
+    },
 };
 
 /// A heuristic balance override detector based on `eth_call` simulations.
 ///
-/// This has the exact same node requirements as trade verification.
+/// This has the exact same node requirements as trade verification. When
+/// tracing is enabled, tokens whose balances can't be found by probing are
+/// additionally analyzed with `debug_traceCall`.
 #[derive(Clone)]
 pub struct Detector(Arc<Inner>);
 
 pub struct Inner {
     simulator: Arc<dyn CodeSimulating>,
+    /// node used for tracing storage accesses if enabled
+    tracer: Option<Web3>,
     /// address that we try to override the balances for
     holder: Address,
     /// all strategies used to detect successful balance overrides
@@ -39,8 +49,13 @@ impl std::ops::Deref for Detector {
 }
 
 impl Detector {
-    /// Creates a new balance override detector.
-    pub fn new(simulator: Arc<dyn CodeSimulating>, probing_depth: u8) -> Self {
+    /// Creates a new balance override detector. Pass a `tracer` to fall back
+    /// to storage tracing when probing does not find the balances mapping.
+    pub fn new(
+        simulator: Arc<dyn CodeSimulating>,
+        tracer: Option<Web3>,
+        probing_depth: u8,
+    ) -> Self {
         let holder = {
             // On a technical note, Ethereum public addresses are, for the most
             // part, generated by taking the 20 last bytes of a Keccak-256 hash (for
@@ -90,6 +105,7 @@ impl Detector {
 
         Self(Arc::new(Inner {
             simulator,
+            tracer,
             holder,
             strategies,
             state_overrides,
@@ -100,31 +116,87 @@ impl Detector {
     /// Returns an `Err` if it cannot detect the strategy or an internal
     /// simulation fails.
     pub async fn detect(&self, token: Address) -> Result<Strategy, DetectionError> {
-        let token = dummy_contract!(ERC20, token);
-        let call = CallRequest {
-            to: Some(token.address()),
-            data: token.methods().balance_of(self.holder).m.tx.data,
-            ..Default::default()
-        };
+        match self.detect_by_probing(token).await {
+            Err(DetectionError::NotFound) if self.tracer.is_some() => {
+                self.detect_by_tracing(token).await
+            }
+            result => result,
+        }
+    }
+
+    /// Overrides all probed storage slots at once and checks which one
+    /// changed the balance.
+    async fn detect_by_probing(&self, token: Address) -> Result<Strategy, DetectionError> {
         let overrides = hashmap! {
-            token.address() => StateOverride {
+            token => StateOverride {
                 state_diff: Some(self.state_overrides.clone()),
                 ..Default::default()
             },
         };
-
-        let output = self.simulator.simulate(call, overrides, None).await?;
-        let balance = (output.len() == 32)
-            .then(|| U256::from_big_endian(&output))
-            .ok_or(DetectionError::Decode)?;
+        let balance = self.balance_of(token, overrides).await?;
 
         self.strategies
             .iter()
             .find_map(|helper| (helper.balance == balance).then_some(helper.strategy.clone()))
             .ok_or(DetectionError::NotFound)
     }
+
+    /// Traces the storage slots read by `balanceOf()` and verifies every
+    /// strategy that could explain them until one works.
+    async fn detect_by_tracing(&self, token: Address) -> Result<Strategy, DetectionError> {
+        let tracer = self.tracer.as_ref().ok_or(DetectionError::NotFound)?;
+        let trace = tracer
+            .debug()
+            .call_struct_logs(self.balance_of_call(token), BlockNumber::Latest.into())
+            .await?;
+        if trace.failed {
+            return Err(DetectionError::NotFound);
+        }
+
+        for strategy in storage_trace::candidates(&trace, token, self.holder) {
+            let (key, value) = strategy.state_override(&self.holder, &VERIFICATION_BALANCE);
+            let overrides = hashmap! {
+                token => StateOverride {
+                    state_diff: Some(hashmap! { key => value }),
+                    ..Default::default()
+                },
+            };
+            if self.balance_of(token, overrides).await? == VERIFICATION_BALANCE {
+                return Ok(strategy);
+            }
+        }
+
+        Err(DetectionError::NotFound)
+    }
+
+    async fn balance_of(
+        &self,
+        token: Address,
+        overrides: HashMap<Address, StateOverride>,
+    ) -> Result<U256, DetectionError> {
+        let output = self
+            .simulator
+            .simulate(self.balance_of_call(token), overrides, None)
+            .await?;
+        (output.len() == 32)
+            .then(|| U256::from_big_endian(&output))
+            .ok_or(DetectionError::Decode)
+    }
+
+    fn balance_of_call(&self, token: Address) -> CallRequest {
+        let token = dummy_contract!(ERC20, token);
+        CallRequest {
+            to: Some(token.address()),
+            data: token.methods().balance_of(self.holder).m.tx.data,
+            ..Default::default()
+        }
+    }
 }
 
+/// Balance used to verify strategies derived from storage traces. Similar to
+/// the probing balances it is distinctive enough to avoid false positives.
+const VERIFICATION_BALANCE: U256 = U256([0x4d6f6f214d6f6f21, 0, 0, 0]);
+
 /// Contains all the information we need to determine which state override
 /// was successful.
 struct StrategyHelper {
@@ -158,6 +230,7 @@ impl Debug for Detector {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         f.debug_struct("Detector")
             .field("simulator", &format_args!("Arc<dyn CodeSimulating>"))
+            .field("tracing", &self.tracer.is_some())
             .finish()
     }
 }
@@ -171,6 +244,8 @@ pub enum DetectionError {
     Decode,
     #[error(transparent)]
     Simulation(#[from] SimulationError),
+    #[error("failed to trace storage accesses: {0}")]
+    Tracing(#[from] web3::Error),
 }
 
 #[cfg(test)]
@@ -183,7 +258,7 @@ mod tests {
     #[ignore]
     #[tokio::test]
     async fn detects_storage_slots_mainnet() {
-        let detector = Detector::new(Arc::new(Web3::new(create_env_test_transport())), 60);
+        let detector = Detector::new(Arc::new(Web3::new(create_env_test_transport())), None, 60);
 
         let storage = detector
             .detect(addr!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"))
@@ -215,7 +290,7 @@ mod tests {
     #[ignore]
     #[tokio::test]
     async fn detects_storage_slots_arbitrum() {
-        let detector = Detector::new(Arc::new(Web3::new(create_env_test_transport())), 60);
+        let detector = Detector::new(Arc::new(Web3::new(create_env_test_transport())), None, 60);
 
         // all bridged tokens on arbitrum require a ton of probing
         let storage = detector
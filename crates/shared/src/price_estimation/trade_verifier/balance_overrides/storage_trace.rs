@@ -0,0 +1,197 @@
+//! Derives balance override strategies from the storage slots a token reads
+//! when executing `balanceOf(holder)`.
+//!
+//! Mapping slots are computed by hashing the mapping key together with the
+//! slot of the mapping itself. By remembering the pre-images of all `KECCAK256`
+//! operations we can check which `SLOAD` reads a slot derived from the holder
+//! and recover the slot of the balances mapping. This works for arbitrary base
+//! slots, including nested mappings where the holder is the innermost key.
+
+use {
+    super::Strategy,
+    ethcontract::{Address, H256, U256},
+    ethrpc::extensions::{StructLog, StructLogTrace},
+    std::collections::HashMap,
+    web3::signing::keccak256,
+};
+
+/// Returns all strategies that could explain the storage reads of a
+/// `balanceOf(holder)` call on `token` in the order they were read.
+///
+/// Only reads in the storage context of the token are considered, which
+/// includes reads done by implementation contracts called via `DELEGATECALL`.
+pub fn candidates(trace: &StructLogTrace, token: Address, holder: Address) -> Vec<Strategy> {
+    let mut preimages = HashMap::<H256, Vec<u8>>::new();
+    let mut candidates = Vec::new();
+    // storage context (i.e. address) for each call depth
+    let mut contexts = vec![token];
+    // storage context of the call frame that is about to be entered
+    let mut next_context = None;
+
+    for log in &trace.struct_logs {
+        if log.depth > contexts.len() {
+            let current = *contexts.last().unwrap_or(&token);
+            contexts.push(next_context.take().unwrap_or(current));
+        } else {
+            contexts.truncate(log.depth.max(1));
+            next_context = None;
+        }
+        let context = *contexts.last().unwrap_or(&token);
+
+        match log.op.as_str() {
+            "CALL" | "STATICCALL" => next_context = stack_item(log, 1).map(to_address),
+            "DELEGATECALL" | "CALLCODE" => next_context = Some(context),
+            "KECCAK256" | "SHA3" => {
+                let input = stack_item(log, 0)
+                    .zip(stack_item(log, 1))
+                    .and_then(|(offset, size)| read_memory(&log.memory, offset, size));
+                if let Some(input) = input {
+                    preimages.insert(H256(keccak256(&input)), input);
+                }
+            }
+            "SLOAD" if context == token => {
+                let strategy = stack_item(log, 0)
+                    .and_then(|slot| preimages.get(&to_h256(slot)))
+                    .and_then(|preimage| strategy_for_preimage(preimage, holder));
+                if let Some(strategy) = strategy.filter(|s| !candidates.contains(s)) {
+                    candidates.push(strategy);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    candidates
+}
+
+/// Returns the strategy that computes a storage slot from the given pre-image
+/// if it is the key of a mapping indexed by `holder`.
+fn strategy_for_preimage(preimage: &[u8], holder: Address) -> Option<Strategy> {
+    if preimage.len() != 64 {
+        return None;
+    }
+    let mut key = [0; 32];
+    key[12..].copy_from_slice(holder.as_bytes());
+
+    let (first, second) = preimage.split_at(32);
+    if first == key {
+        Some(Strategy::SolidityMapping {
+            slot: U256::from_big_endian(second),
+        })
+    } else if second == key {
+        Some(Strategy::VyperMapping {
+            slot: U256::from_big_endian(first),
+        })
+    } else {
+        None
+    }
+}
+
+/// Returns the n-th item from the top of the stack.
+fn stack_item(log: &StructLog, n: usize) -> Option<U256> {
+    log.stack.iter().rev().nth(n).copied()
+}
+
+/// Reads `size` bytes at `offset` from the hex encoded memory words. Returns
+/// `None` for reads that can't be mapping keys or are out of bounds.
+fn read_memory(memory: &[String], offset: U256, size: U256) -> Option<Vec<u8>> {
+    if size > U256::from(64) || offset > U256::from(usize::MAX) {
+        return None;
+    }
+    let (offset, size) = (offset.as_usize(), size.as_usize());
+    let memory = memory
+        .iter()
+        .map(|word| hex::decode(word.trim_start_matches("0x")))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?
+        .concat();
+    memory
+        .get(offset..offset.checked_add(size)?)
+        .map(<[u8]>::to_vec)
+}
+
+fn to_h256(value: U256) -> H256 {
+    let mut buf = [0; 32];
+    value.to_big_endian(&mut buf);
+    H256(buf)
+}
+
+fn to_address(value: U256) -> Address {
+    Address::from_slice(&to_h256(value).as_bytes()[12..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(bytes: &[u8]) -> String {
+        let mut buf = [0; 32];
+        buf[32 - bytes.len()..].copy_from_slice(bytes);
+        hex::encode(buf)
+    }
+
+    fn log(op: &str, depth: usize, stack: Vec<U256>, memory: Vec<String>) -> StructLog {
+        StructLog {
+            op: op.to_string(),
+            depth,
+            stack,
+            memory,
+        }
+    }
+
+    /// Slot read when hashing the two memory words.
+    fn slot(memory: &[String]) -> U256 {
+        let preimage = read_memory(memory, 0.into(), 64.into()).unwrap();
+        U256::from_big_endian(&keccak256(&preimage))
+    }
+
+    #[test]
+    fn derives_mapping_slots() {
+        let token = addr!("1111111111111111111111111111111111111111");
+        let proxy_impl = addr!("2222222222222222222222222222222222222222");
+        let other = addr!("3333333333333333333333333333333333333333");
+        let holder = addr!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+
+        let solidity = vec![word(holder.as_bytes()), word(&[51])];
+        let vyper = vec![word(&[7]), word(holder.as_bytes())];
+        let unrelated = vec![word(&[1]), word(&[2])];
+
+        let trace = StructLogTrace {
+            failed: false,
+            struct_logs: vec![
+                // read by another contract's storage which doesn't count
+                log(
+                    "STATICCALL",
+                    1,
+                    vec![0.into(), U256::from(other.as_bytes()), 0.into()],
+                    vec![],
+                ),
+                log("KECCAK256", 2, vec![64.into(), 0.into()], vyper.clone()),
+                log("SLOAD", 2, vec![slot(&vyper)], vyper.clone()),
+                // delegate call to the implementation contract
+                log(
+                    "DELEGATECALL",
+                    1,
+                    vec![0.into(), U256::from(proxy_impl.as_bytes()), 0.into()],
+                    vec![],
+                ),
+                log("KECCAK256", 2, vec![64.into(), 0.into()], unrelated.clone()),
+                log("SLOAD", 2, vec![slot(&unrelated)], unrelated.clone()),
+                log("KECCAK256", 2, vec![64.into(), 0.into()], solidity.clone()),
+                log("SLOAD", 2, vec![slot(&solidity)], solidity.clone()),
+                log("SLOAD", 2, vec![slot(&solidity)], solidity.clone()),
+                log("RETURN", 2, vec![], vec![]),
+                log("KECCAK256", 1, vec![64.into(), 0.into()], vyper.clone()),
+                log("SLOAD", 1, vec![slot(&vyper)], vyper.clone()),
+            ],
+        };
+
+        assert_eq!(
+            candidates(&trace, token, holder),
+            vec![
+                Strategy::SolidityMapping { slot: 51.into() },
+                Strategy::VyperMapping { slot: 7.into() },
+            ]
+        );
+    }
+}
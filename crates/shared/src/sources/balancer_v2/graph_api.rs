@@ -14,7 +14,7 @@ use {
     anyhow::Result,
     ethcontract::{H160, H256},
     reqwest::{Client, Url},
-    serde::Deserialize,
+    serde::{Deserialize, Serialize},
     serde_json::json,
     serde_with::{DisplayFromStr, serde_as},
     std::collections::HashMap,
@@ -139,7 +139,7 @@ impl RegisteredPools {
 }
 
 /// Pool data from the Balancer V2 subgraph.
-#[derive(Debug, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PoolData {
     pub pool_type: PoolType,
@@ -151,7 +151,7 @@ pub struct PoolData {
 }
 
 /// Supported pool kinds.
-#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Hash, Serialize)]
 pub enum PoolType {
     Stable,
     Weighted,
@@ -161,7 +161,7 @@ pub enum PoolType {
 
 /// Token data for pools.
 #[serde_as]
-#[derive(Debug, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Token {
     pub address: H160,
     pub decimals: u8,
@@ -12,8 +12,8 @@ use {
         registry::Registry,
     },
     super::{
-        graph_api::{BalancerSubgraphClient, RegisteredPools},
-        pool_init::PoolInitializing,
+        graph_api::{BalancerSubgraphClient, PoolType, RegisteredPools},
+        pool_init::{OnchainPoolInitializer, PoolInitializing, SnapshotPoolInitializer},
         pools::{
             FactoryIndexing,
             Pool,
@@ -28,6 +28,7 @@ use {
     crate::{
         ethrpc::{Web3, Web3Transport},
         recent_block_cache::{Block, CacheConfig},
+        sources::snapshots::Snapshots,
         token_info::TokenInfoFetching,
     },
     anyhow::{Context, Result},
@@ -220,6 +221,34 @@ impl BalancerFactoryKind {
             _ => Default::default(),
         }
     }
+
+    /// Returns the type of the pools created by factories of this kind.
+    pub(super) fn pool_type(&self) -> PoolType {
+        match self {
+            Self::Weighted | Self::WeightedV3 | Self::WeightedV4 | Self::Weighted2Token => {
+                PoolType::Weighted
+            }
+            Self::StableV2 => PoolType::Stable,
+            Self::LiquidityBootstrapping | Self::NoProtocolFeeLiquidityBootstrapping => {
+                PoolType::LiquidityBootstrapping
+            }
+            Self::ComposableStable
+            | Self::ComposableStableV3
+            | Self::ComposableStableV4
+            | Self::ComposableStableV5
+            | Self::ComposableStableV6 => PoolType::ComposableStable,
+        }
+    }
+}
+
+/// How the pools registered before startup are discovered.
+#[derive(Clone, Debug)]
+pub enum PoolDiscovery {
+    /// Query the registered pools from a Balancer subgraph.
+    Subgraph(Url),
+    /// Scan the pool creation events of the factories starting at the
+    /// specified block. Used on chains without a Balancer subgraph.
+    Onchain { start_block: u64 },
 }
 
 /// All balancer related contracts that we expect to exist.
@@ -294,7 +323,7 @@ impl BalancerContracts {
 impl BalancerPoolFetcher {
     #[allow(clippy::too_many_arguments)]
     pub async fn new(
-        subgraph_url: &Url,
+        discovery: PoolDiscovery,
         block_retriever: Arc<dyn BlockRetrieving>,
         token_infos: Arc<dyn TokenInfoFetching>,
         config: CacheConfig,
@@ -303,21 +332,40 @@ impl BalancerPoolFetcher {
         web3: Web3,
         contracts: &BalancerContracts,
         deny_listed_pool_ids: Vec<H256>,
+        snapshots: Option<Arc<Snapshots>>,
     ) -> Result<Self> {
-        let pool_initializer = BalancerSubgraphClient::from_subgraph_url(subgraph_url, client)?;
-        let web3 = ethrpc::instrumented::instrument_with_label(&web3, "balancerV2".into());
-        let fetcher = Arc::new(Cache::new(
-            create_aggregate_pool_fetcher(
-                web3,
-                pool_initializer,
-                block_retriever,
-                token_infos,
+        let pool_initializer: Box<dyn PoolInitializing> = match discovery {
+            PoolDiscovery::Subgraph(subgraph_url) => {
+                let subgraph = BalancerSubgraphClient::from_subgraph_url(&subgraph_url, client)?;
+                match snapshots {
+                    Some(snapshots) => Box::new(SnapshotPoolInitializer::new(
+                        subgraph,
+                        snapshots,
+                        block_retriever.clone(),
+                    )),
+                    None => Box::new(subgraph),
+                }
+            }
+            // The on-chain scan persists its progress in the snapshot store so
+            // that restarts resume where the previous scan stopped.
+            PoolDiscovery::Onchain { start_block } => Box::new(OnchainPoolInitializer::new(
                 contracts,
-            )
-            .await?,
-            config,
-            block_stream,
-        )?);
+                token_infos.clone(),
+                block_retriever.clone(),
+                start_block,
+                snapshots,
+            )),
+        };
+        let web3 = ethrpc::instrumented::instrument_with_label(&web3, "balancerV2".into());
+        let aggregate = create_aggregate_pool_fetcher(
+            web3,
+            pool_initializer.as_ref(),
+            block_retriever,
+            token_infos,
+            contracts,
+        )
+        .await?;
+        let fetcher = Arc::new(Cache::new(aggregate, config, block_stream)?);
 
         Ok(Self {
             fetcher,
@@ -375,7 +423,7 @@ impl BalancerPoolFetching for BalancerPoolFetcher {
 /// Creates an aggregate fetcher for all supported pool factories.
 async fn create_aggregate_pool_fetcher(
     web3: Web3,
-    pool_initializer: impl PoolInitializing,
+    pool_initializer: &dyn PoolInitializing,
     block_retriever: Arc<dyn BlockRetrieving>,
     token_infos: Arc<dyn TokenInfoFetching>,
     contracts: &BalancerContracts,
@@ -4,8 +4,36 @@
 //! with existing data in order to reduce the "cold start" time of the service.
 
 use {
-    super::graph_api::{BalancerSubgraphClient, RegisteredPools},
-    anyhow::Result,
+    super::{
+        graph_api::{BalancerSubgraphClient, PoolData, PoolType, RegisteredPools, Token},
+        pool_fetching::{BalancerContracts, BalancerFactoryKind},
+        swap::fixed_point::Bfp,
+    },
+    crate::{
+        event_handling::MAX_REORG_BLOCK_COUNT,
+        sources::snapshots::{Snapshot, Snapshots},
+        token_info::TokenInfoFetching,
+    },
+    anyhow::{Context, Result},
+    contracts::{
+        BalancerV2BasePool,
+        BalancerV2BasePoolFactory,
+        BalancerV2Vault,
+        BalancerV2WeightedPool,
+        errors::EthcontractErrorType,
+    },
+    ethcontract::{
+        BlockNumber,
+        Bytes,
+        H160,
+        H256,
+        common::DeploymentInformation,
+        dyns::DynInstance,
+        errors::MethodError,
+    },
+    ethrpc::block_stream::BlockRetrieving,
+    futures::{StreamExt, stream},
+    std::{ops::RangeInclusive, sync::Arc},
 };
 
 #[async_trait::async_trait]
@@ -25,3 +53,449 @@ impl PoolInitializing for BalancerSubgraphClient {
         Ok(registered_pools)
     }
 }
+
+/// Initializes the pool registries from a snapshot of the registered pools
+/// instead of the inner initializer whenever possible.
+///
+/// Pool states are always fetched on demand, so the registered pools are all
+/// there is to snapshot. The snapshot is written whenever the pools get fetched
+/// from the inner initializer and is used until it exceeds its maximum age.
+/// Pools created after the snapshot block are indexed by replaying the pool
+/// factory events.
+pub struct SnapshotPoolInitializer<Inner> {
+    inner: Inner,
+    snapshots: Arc<Snapshots>,
+    blocks: Arc<dyn BlockRetrieving>,
+}
+
+impl<Inner> SnapshotPoolInitializer<Inner> {
+    pub fn new(inner: Inner, snapshots: Arc<Snapshots>, blocks: Arc<dyn BlockRetrieving>) -> Self {
+        Self {
+            inner,
+            snapshots,
+            blocks,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<Inner> PoolInitializing for SnapshotPoolInitializer<Inner>
+where
+    Inner: PoolInitializing,
+{
+    async fn initialize_pools(&self) -> Result<RegisteredPools> {
+        if let Some(snapshot) = self
+            .snapshots
+            .load::<Vec<PoolData>>(self.blocks.as_ref())
+            .await
+        {
+            return Ok(RegisteredPools {
+                fetched_block_number: snapshot.block_number,
+                pools: snapshot.state,
+            });
+        }
+
+        let registered_pools = self.inner.initialize_pools().await?;
+        let (_, block_hash) = self
+            .blocks
+            .block(registered_pools.fetched_block_number)
+            .await?;
+        self.snapshots
+            .save(&Snapshot {
+                block_number: registered_pools.fetched_block_number,
+                block_hash,
+                state: &registered_pools.pools,
+            })
+            .await;
+        Ok(registered_pools)
+    }
+}
+
+/// Number of blocks whose pool creation events are queried with a single
+/// request. Node providers commonly limit the block range of log queries.
+const DISCOVERY_CHUNK_SIZE: u64 = 5_000;
+
+/// Maximum number of newly discovered pools whose data is fetched
+/// concurrently.
+const MAX_CONCURRENT_POOL_FETCHES: usize = 16;
+
+/// Discovers the registered pools by scanning the `PoolCreated` events of the
+/// pool factories on-chain. This allows indexing Balancer pools on chains
+/// without a Balancer subgraph (e.g. local test networks).
+///
+/// Events are scanned in chunks from the configured start block up to a reorg
+/// safe block. When a progress store is configured, the pools discovered so
+/// far are persisted periodically so that a restart resumes the scan instead
+/// of starting over.
+pub struct OnchainPoolInitializer {
+    vault: BalancerV2Vault,
+    factories: Vec<(BalancerFactoryKind, DynInstance)>,
+    token_infos: Arc<dyn TokenInfoFetching>,
+    blocks: Arc<dyn BlockRetrieving>,
+    start_block: u64,
+    progress: Option<Arc<Snapshots>>,
+}
+
+impl OnchainPoolInitializer {
+    pub fn new(
+        contracts: &BalancerContracts,
+        token_infos: Arc<dyn TokenInfoFetching>,
+        blocks: Arc<dyn BlockRetrieving>,
+        start_block: u64,
+        progress: Option<Arc<Snapshots>>,
+    ) -> Self {
+        Self {
+            vault: contracts.vault.clone(),
+            factories: contracts.factories.clone(),
+            token_infos,
+            blocks,
+            start_block,
+            progress,
+        }
+    }
+
+    /// Returns the pools created by the specified factory in a block range.
+    async fn scan_factory(
+        &self,
+        kind: BalancerFactoryKind,
+        factory: &DynInstance,
+        range: RangeInclusive<u64>,
+    ) -> Result<Vec<PoolData>> {
+        // Skip ranges from before the factory was deployed.
+        if matches!(
+            factory.deployment_information(),
+            Some(DeploymentInformation::BlockNumber(deployed)) if *range.end() < deployed
+        ) {
+            return Ok(Vec::new());
+        }
+
+        let events = BalancerV2BasePoolFactory::at(&factory.web3(), factory.address())
+            .events()
+            .pool_created()
+            .from_block(BlockNumber::Number((*range.start()).into()))
+            .to_block(BlockNumber::Number((*range.end()).into()))
+            .query()
+            .await
+            .context("failed to query pool creation events")?;
+
+        let pools = stream::iter(events)
+            .map(|event| self.fetch_pool_data(kind, factory.address(), event.data.pool))
+            .buffered(MAX_CONCURRENT_POOL_FETCHES)
+            .collect::<Vec<_>>()
+            .await;
+        pools
+            .into_iter()
+            .filter_map(|pool| match pool {
+                Ok(pool) => Some(Ok(pool)),
+                Err(err) if is_contract_error(&err) => {
+                    tracing::debug!(?err, "skipping pool that can't be indexed");
+                    None
+                }
+                Err(err) => Some(Err(err)),
+            })
+            .collect()
+    }
+
+    async fn fetch_pool_data(
+        &self,
+        kind: BalancerFactoryKind,
+        factory: H160,
+        address: H160,
+    ) -> Result<PoolData> {
+        let web3 = self.vault.raw_instance().web3();
+        let pool_type = kind.pool_type();
+
+        let id = H256(
+            BalancerV2BasePool::at(&web3, address)
+                .methods()
+                .get_pool_id()
+                .call()
+                .await?
+                .0,
+        );
+        let (tokens, _, _) = self
+            .vault
+            .methods()
+            .get_pool_tokens(Bytes(id.0))
+            .call()
+            .await?;
+        let weights = match pool_type {
+            PoolType::Weighted => Some(
+                BalancerV2WeightedPool::at(&web3, address)
+                    .methods()
+                    .get_normalized_weights()
+                    .call()
+                    .await?,
+            ),
+            _ => None,
+        };
+
+        let token_infos = self.token_infos.get_token_infos(&tokens).await;
+        let tokens = tokens
+            .iter()
+            .enumerate()
+            .map(|(i, token)| {
+                Ok(Token {
+                    address: *token,
+                    decimals: token_infos
+                        .get(token)
+                        .and_then(|info| info.decimals)
+                        .with_context(|| format!("missing decimals for token {token:?}"))?,
+                    weight: weights
+                        .as_ref()
+                        .and_then(|weights| weights.get(i))
+                        .copied()
+                        .map(Bfp::from_wei),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(PoolData {
+            pool_type,
+            id,
+            address,
+            factory,
+            // Only used by the subgraph query, the swap status of a pool is
+            // checked whenever its state is fetched.
+            swap_enabled: true,
+            tokens,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl PoolInitializing for OnchainPoolInitializer {
+    async fn initialize_pools(&self) -> Result<RegisteredPools> {
+        let resumed = match &self.progress {
+            Some(progress) => {
+                progress
+                    .load_any_age::<Vec<PoolData>>(self.blocks.as_ref())
+                    .await
+            }
+            None => None,
+        };
+        let (start, mut pools) = match resumed {
+            Some(snapshot) => (snapshot.block_number + 1, snapshot.state),
+            None => (self.start_block, Vec::new()),
+        };
+        let end = self
+            .blocks
+            .current_block()
+            .await?
+            .number
+            .saturating_sub(MAX_REORG_BLOCK_COUNT);
+
+        for range in chunks(start, end, DISCOVERY_CHUNK_SIZE) {
+            for (kind, factory) in &self.factories {
+                pools.extend(self.scan_factory(*kind, factory, range.clone()).await?);
+            }
+            tracing::debug!(
+                block = %range.end(), pools = %pools.len(), target = %end,
+                "scanned Balancer pool creation events",
+            );
+
+            let last = *range.end() == end;
+            if let Some(progress) = self
+                .progress
+                .as_ref()
+                .filter(|progress| last || progress.is_due())
+            {
+                let (_, block_hash) = self.blocks.block(*range.end()).await?;
+                progress
+                    .save(&Snapshot {
+                        block_number: *range.end(),
+                        block_hash,
+                        state: &pools,
+                    })
+                    .await;
+            }
+        }
+
+        tracing::debug!(
+            block = %end, pools = %pools.len(),
+            "initialized registered pools",
+        );
+        Ok(RegisteredPools {
+            fetched_block_number: end,
+            pools,
+        })
+    }
+}
+
+/// Splits the inclusive block range `start..=end` into consecutive ranges of at
+/// most `size` blocks.
+fn chunks(start: u64, end: u64, size: u64) -> impl Iterator<Item = RangeInclusive<u64>> {
+    (start..=end)
+        .step_by(size as usize)
+        .map(move |from| from..=end.min(from + size - 1))
+}
+
+fn is_contract_error(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<MethodError>()
+            .map(EthcontractErrorType::classify),
+        Some(EthcontractErrorType::Contract),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{sources::snapshots::FileSnapshotStore, token_info::MockTokenInfoFetching},
+        contracts::BalancerV2WeightedPoolFactory,
+        ethrpc::block_stream::{BlockInfo, BlockNumberHash},
+        std::{
+            sync::Mutex,
+            time::{Duration, Instant},
+        },
+    };
+
+    #[derive(Debug)]
+    struct Chain(u64);
+
+    #[async_trait::async_trait]
+    impl BlockRetrieving for Chain {
+        async fn current_block(&self) -> Result<BlockInfo> {
+            Ok(BlockInfo {
+                number: self.0,
+                hash: H256::from_low_u64_be(self.0),
+                parent_hash: H256::from_low_u64_be(self.0 - 1),
+                timestamp: 0,
+                gas_limit: Default::default(),
+                gas_price: Default::default(),
+                observed_at: Instant::now(),
+            })
+        }
+
+        async fn block(&self, number: u64) -> Result<BlockNumberHash> {
+            Ok((number, H256::from_low_u64_be(number)))
+        }
+
+        async fn blocks(
+            &self,
+            _: ethrpc::block_stream::RangeInclusive<u64>,
+        ) -> Result<Vec<BlockNumberHash>> {
+            unimplemented!()
+        }
+    }
+
+    fn pool() -> PoolData {
+        PoolData {
+            pool_type: PoolType::Weighted,
+            id: H256([1; 32]),
+            address: H160([2; 20]),
+            factory: H160([3; 20]),
+            swap_enabled: true,
+            tokens: vec![Token {
+                address: H160([4; 20]),
+                decimals: 18,
+                weight: Some(Bfp::exp10(-1)),
+            }],
+        }
+    }
+
+    #[test]
+    fn splits_block_ranges_into_chunks() {
+        assert_eq!(
+            chunks(10, 25, 5).collect::<Vec<_>>(),
+            vec![10..=14, 15..=19, 20..=24, 25..=25],
+        );
+        assert_eq!(chunks(10, 14, 5).collect::<Vec<_>>(), vec![10..=14]);
+        assert_eq!(chunks(10, 10, 5).collect::<Vec<_>>(), vec![10..=10]);
+        assert!(chunks(11, 10, 5).next().is_none());
+    }
+
+    #[tokio::test]
+    async fn onchain_discovery_resumes_from_progress() {
+        // The factory has no pool creation events, so the scanned block ranges
+        // are recorded to check where the scan resumes.
+        let scanned = Arc::new(Mutex::new(Vec::new()));
+        let web3 = ethrpc::mock::web3();
+        web3.transport().mock().expect_execute().returning({
+            let scanned = scanned.clone();
+            move |method, params| {
+                assert_eq!(method, "eth_getLogs");
+                let block = |key: &str| {
+                    let block = params[0][key].as_str().unwrap().trim_start_matches("0x");
+                    u64::from_str_radix(block, 16).unwrap()
+                };
+                scanned
+                    .lock()
+                    .unwrap()
+                    .push(block("fromBlock")..=block("toBlock"));
+                Ok(serde_json::json!([]))
+            }
+        });
+        let contracts = BalancerContracts {
+            vault: BalancerV2Vault::at(&web3, H160([0xba; 20])),
+            factories: vec![(
+                BalancerFactoryKind::Weighted,
+                BalancerV2WeightedPoolFactory::at(&web3, H160([3; 20]))
+                    .raw_instance()
+                    .clone(),
+            )],
+        };
+
+        let directory = tempfile::tempdir().unwrap();
+        let progress = Arc::new(Snapshots::new(
+            Arc::new(FileSnapshotStore::new(directory.path().to_owned())),
+            "balancer-v2".to_string(),
+            crate::sources::snapshots::Config {
+                interval: Duration::from_secs(60),
+                max_age: 100,
+            },
+        ));
+        // The progress is older than the snapshot maximum age but still used.
+        progress
+            .save(&Snapshot {
+                block_number: 7_999,
+                block_hash: H256::from_low_u64_be(7_999),
+                state: vec![pool()],
+            })
+            .await;
+
+        let chain = Arc::new(Chain(10_000 + MAX_REORG_BLOCK_COUNT));
+        let initializer = OnchainPoolInitializer::new(
+            &contracts,
+            Arc::new(MockTokenInfoFetching::new()),
+            chain.clone(),
+            0,
+            Some(progress.clone()),
+        );
+        assert_eq!(
+            initializer.initialize_pools().await.unwrap(),
+            RegisteredPools {
+                fetched_block_number: 10_000,
+                pools: vec![pool()],
+            },
+        );
+        assert_eq!(*scanned.lock().unwrap(), vec![8_000..=10_000]);
+
+        // The progress of the completed scan is saved.
+        let saved = progress
+            .load_any_age::<Vec<PoolData>>(chain.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(saved.block_number, 10_000);
+        assert_eq!(saved.state, vec![pool()]);
+
+        // Without progress the scan starts at the start block.
+        scanned.lock().unwrap().clear();
+        let initializer = OnchainPoolInitializer::new(
+            &contracts,
+            Arc::new(MockTokenInfoFetching::new()),
+            chain,
+            1_000,
+            None,
+        );
+        assert_eq!(
+            initializer.initialize_pools().await.unwrap(),
+            RegisteredPools::empty(10_000),
+        );
+        assert_eq!(
+            *scanned.lock().unwrap(),
+            vec![1_000..=5_999, 6_000..=10_000],
+        );
+    }
+}
@@ -11,7 +11,7 @@ use {
     number::conversions::{big_int_to_u256, u256_to_big_int},
     std::{
         convert::TryFrom,
-        fmt::{self, Debug, Formatter},
+        fmt::{self, Debug, Display, Formatter},
         str::FromStr,
         sync::LazyLock,
     },
@@ -83,6 +83,12 @@ impl FromStr for Bfp {
 }
 
 impl Debug for Bfp {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        Display::fmt(self, formatter)
+    }
+}
+
+impl Display for Bfp {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         write!(
             formatter,
@@ -17,11 +17,11 @@ use {
     std::collections::BTreeMap,
 };
 
-mod error;
+pub(crate) mod error;
 pub mod fixed_point;
 mod math;
-mod stable_math;
-mod weighted_math;
+pub(crate) mod stable_math;
+pub(crate) mod weighted_math;
 
 const WEIGHTED_SWAP_GAS_COST: usize = 100_000;
 // See https://dune.xyz/queries/219641 for cost of pure stable swaps
@@ -292,7 +292,7 @@ impl BaselineSolvable for StablePoolRef<'_> {
 /// enough to buy X tokens, selling the computed amount over the same pool in
 /// the exact same state will yield X-𝛿 tokens. To work around this, for each
 /// hop, we try to converge to some sell amount >= the required buy amount.
-fn converge_in_amount(
+pub(crate) fn converge_in_amount(
     in_amount: U256,
     exact_out_amount: U256,
     get_amount_out: impl Fn(U256) -> Option<U256>,
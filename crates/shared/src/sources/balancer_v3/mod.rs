@@ -0,0 +1,21 @@
+//! Balancer V3 liquidity.
+//!
+//! Unlike Balancer V2, V3 pools are registered with the vault by their address
+//! and the vault, rather than the pools, keeps track of balances, swap fees
+//! and token rates. Pools are discovered by enumerating the pools of the
+//! configured factories once and then indexing the factories' `PoolCreated`
+//! events. Their static information (tokens, weights and hooks) is kept in
+//! memory while their current state is read from the vault on demand.
+//!
+//! Swaps are computed locally by applying the vault's scaling, rate and fee
+//! logic around the pool math. The V3 weighted and stable math is the same as
+//! the latest Balancer V2 math, so it is shared with the [`super::balancer_v2`]
+//! module.
+
+pub mod pool_fetching;
+mod registry;
+mod swap;
+
+pub use self::pool_fetching::{
+    BalancerV3PoolFetcher, BalancerV3PoolFetching, FactoryKind, Pool, PoolKind, TokenState,
+};
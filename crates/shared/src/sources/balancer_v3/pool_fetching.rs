@@ -0,0 +1,430 @@
+//! Pool Fetching retrieves the Balancer V3 pools trading a collection of
+//! `TokenPair`s from the factory registries and attaches their current state
+//! as read from the vault.
+
+use {
+    super::registry::Registry,
+    crate::{
+        ethrpc::Web3,
+        maintenance::Maintaining,
+        recent_block_cache::Block,
+        sources::balancer_v2::{
+            pool_fetching::AmplificationParameter,
+            swap::fixed_point::Bfp,
+        },
+    },
+    anyhow::{Context, Result, ensure},
+    contracts::{
+        BalancerV3BasePoolFactory,
+        BalancerV3StablePool,
+        BalancerV3Vault,
+        BalancerV3WeightedPool,
+        errors::EthcontractErrorType,
+    },
+    ethcontract::{BlockId, H160, U256, errors::MethodError},
+    ethrpc::block_stream::BlockRetrieving,
+    futures::future,
+    model::TokenPair,
+    std::{collections::HashSet, sync::Arc},
+};
+
+/// Median gas used by a swap through the Balancer V3 batch router, including
+/// the Permit2 transfer of the sell token into the vault.
+pub const SWAP_GAS_COST: u64 = 145_000;
+
+/// Additional gas used by swaps through pools calling swap hooks.
+pub const HOOKS_GAS_COST: u64 = 50_000;
+
+#[async_trait::async_trait]
+pub trait BalancerV3PoolFetching: Send + Sync {
+    async fn fetch(&self, token_pairs: &HashSet<TokenPair>, at_block: Block) -> Result<Vec<Pool>>;
+}
+
+/// The kind of pools created by a Balancer V3 factory.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FactoryKind {
+    Weighted,
+    Stable,
+}
+
+/// A Balancer V3 pool along with its most recent state.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pool {
+    pub address: H160,
+    /// The pool tokens, in vault registration order.
+    pub reserves: Vec<TokenState>,
+    pub swap_fee: Bfp,
+    /// The hooks contract that gets called on swaps, if any.
+    pub hooks: Option<H160>,
+    pub kind: PoolKind,
+}
+
+/// The state of a single pool token.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TokenState {
+    pub token: H160,
+    /// The raw token balance of the pool.
+    pub balance: U256,
+    /// The factor `10^(18 - decimals)` scaling raw amounts to 18 decimals.
+    pub scaling_factor: U256,
+    /// The rate of the token as reported by its rate provider, or 1 for tokens
+    /// without one.
+    pub rate: Bfp,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PoolKind {
+    /// A weighted pool with the normalized weights of its tokens, in token
+    /// order.
+    Weighted(Vec<Bfp>),
+    /// A stable pool.
+    Stable(AmplificationParameter),
+}
+
+impl Pool {
+    /// Returns an iterator over the pool tokens.
+    pub fn tokens(&self) -> impl Iterator<Item = H160> + '_ {
+        self.reserves.iter().map(|reserve| reserve.token)
+    }
+
+    /// Returns all token pairs that can be traded with the pool.
+    pub fn token_pairs(&self) -> impl Iterator<Item = TokenPair> + '_ {
+        self.reserves.iter().enumerate().flat_map(|(i, a)| {
+            self.reserves[i + 1..]
+                .iter()
+                .filter_map(|b| TokenPair::new(a.token, b.token))
+        })
+    }
+
+    /// Returns the gas used by a swap through the pool.
+    pub fn swap_gas(&self) -> u64 {
+        match self.hooks {
+            Some(_) => SWAP_GAS_COST + HOOKS_GAS_COST,
+            None => SWAP_GAS_COST,
+        }
+    }
+}
+
+/// The static information of an indexed pool.
+#[derive(Clone, Debug)]
+pub(super) struct PoolInfo {
+    pub address: H160,
+    pub tokens: Vec<H160>,
+    pub hooks: Option<H160>,
+    pub kind: PoolInfoKind,
+    pub block_created: u64,
+}
+
+#[derive(Clone, Debug)]
+pub(super) enum PoolInfoKind {
+    /// Weights of weighted pools are immutable.
+    Weighted(Vec<Bfp>),
+    Stable,
+}
+
+/// Fetches the state of the Balancer V3 pools created by the configured
+/// factories.
+pub struct BalancerV3PoolFetcher {
+    vault: BalancerV3Vault,
+    registries: Vec<Registry>,
+    // Pools with problematic behaviour, such as hooks that revert swaps under
+    // conditions that can't be predicted from the pool state.
+    pool_deny_list: HashSet<H160>,
+}
+
+impl BalancerV3PoolFetcher {
+    /// Creates a new pool fetcher, discovering all existing pools of the
+    /// specified factories and indexing new ones from the current block on.
+    pub async fn new(
+        web3: Web3,
+        block_retriever: Arc<dyn BlockRetrieving>,
+        vault: H160,
+        factories: &[(FactoryKind, H160)],
+        pool_deny_list: Vec<H160>,
+    ) -> Result<Self> {
+        let web3 = ethrpc::instrumented::instrument_with_label(&web3, "balancerV3".into());
+        let vault = BalancerV3Vault::at(&web3, vault);
+        let current_block = block_retriever.current_block().await?;
+
+        let mut registries = Vec::new();
+        for (kind, factory) in factories {
+            let registry = Registry::new(
+                block_retriever.clone(),
+                vault.clone(),
+                *kind,
+                BalancerV3BasePoolFactory::at(&web3, *factory),
+                (current_block.number, current_block.hash),
+            )
+            .await
+            .with_context(|| format!("failed to index {kind:?} factory {factory:?}"))?;
+            registries.push(registry);
+        }
+
+        Ok(Self {
+            vault,
+            registries,
+            pool_deny_list: pool_deny_list.into_iter().collect(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl BalancerV3PoolFetching for BalancerV3PoolFetcher {
+    async fn fetch(&self, token_pairs: &HashSet<TokenPair>, at_block: Block) -> Result<Vec<Pool>> {
+        let block = BlockId::Number(at_block.into());
+        let pool_infos = future::join_all(
+            self.registries
+                .iter()
+                .map(|registry| registry.pools_for_token_pairs(token_pairs)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .filter(|pool| !self.pool_deny_list.contains(&pool.address))
+        .collect::<Vec<_>>();
+
+        let pools = future::join_all(
+            pool_infos
+                .iter()
+                .map(|pool| fetch_pool_state(&self.vault, pool, block)),
+        )
+        .await;
+        collect_pool_results(pools)
+    }
+}
+
+#[async_trait::async_trait]
+impl Maintaining for BalancerV3PoolFetcher {
+    async fn run_maintenance(&self) -> Result<()> {
+        future::try_join_all(
+            self.registries
+                .iter()
+                .map(|registry| registry.run_maintenance()),
+        )
+        .await?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "BalancerV3PoolFetcher"
+    }
+}
+
+/// Fetches the static information of a pool. Returns `None` for pools that
+/// can't be traded because their hooks alter swaps in ways that can't be
+/// computed locally.
+pub(super) async fn fetch_pool_info(
+    vault: &BalancerV3Vault,
+    kind: FactoryKind,
+    pool: H160,
+    block_created: u64,
+) -> Result<Option<PoolInfo>> {
+    let (
+        enable_hook_adjusted_amounts,
+        _,
+        _,
+        should_call_compute_dynamic_swap_fee,
+        should_call_before_swap,
+        should_call_after_swap,
+        _,
+        _,
+        _,
+        _,
+        hooks_contract,
+    ) = vault.get_hooks_config(pool).call().await?;
+    if enable_hook_adjusted_amounts || should_call_compute_dynamic_swap_fee {
+        tracing::debug!(?pool, "ignoring Balancer V3 pool with unsupported hooks");
+        return Ok(None);
+    }
+
+    let tokens = vault.get_pool_tokens(pool).call().await?;
+    let kind = match kind {
+        FactoryKind::Weighted => {
+            let web3 = vault.raw_instance().web3();
+            let weights = BalancerV3WeightedPool::at(&web3, pool)
+                .get_normalized_weights()
+                .call()
+                .await?;
+            ensure!(weights.len() == tokens.len(), "pool weights mismatch");
+            PoolInfoKind::Weighted(weights.into_iter().map(Bfp::from_wei).collect())
+        }
+        FactoryKind::Stable => PoolInfoKind::Stable,
+    };
+
+    Ok(Some(PoolInfo {
+        address: pool,
+        tokens,
+        hooks: (should_call_before_swap || should_call_after_swap).then_some(hooks_contract),
+        kind,
+        block_created,
+    }))
+}
+
+/// Fetches the current state of a pool. Returns `None` for pools that are
+/// paused or not yet initialized.
+async fn fetch_pool_state(
+    vault: &BalancerV3Vault,
+    pool: &PoolInfo,
+    block: BlockId,
+) -> Result<Option<Pool>> {
+    let fetch_paused = vault.is_pool_paused(pool.address).block(block).call();
+    let fetch_initialized = vault.is_pool_initialized(pool.address).block(block).call();
+    let fetch_token_info = vault.get_pool_token_info(pool.address).block(block).call();
+    let fetch_rates = vault.get_pool_token_rates(pool.address).block(block).call();
+    let fetch_swap_fee = vault
+        .get_static_swap_fee_percentage(pool.address)
+        .block(block)
+        .call();
+    let fetch_amplification_parameter = async {
+        match pool.kind {
+            PoolInfoKind::Weighted(_) => Ok(None),
+            PoolInfoKind::Stable => {
+                let web3 = vault.raw_instance().web3();
+                BalancerV3StablePool::at(&web3, pool.address)
+                    .get_amplification_parameter()
+                    .block(block)
+                    .call()
+                    .await
+                    .map(Some)
+            }
+        }
+    };
+
+    let (
+        paused,
+        initialized,
+        (tokens, _, balances, _),
+        (scaling_factors, rates),
+        swap_fee,
+        amplification_parameter,
+    ) = futures::try_join!(
+        fetch_paused,
+        fetch_initialized,
+        fetch_token_info,
+        fetch_rates,
+        fetch_swap_fee,
+        fetch_amplification_parameter,
+    )?;
+    if paused || !initialized {
+        return Ok(None);
+    }
+
+    ensure!(tokens == pool.tokens, "pool token mismatch");
+    ensure!(
+        balances.len() == tokens.len()
+            && scaling_factors.len() == tokens.len()
+            && rates.len() == tokens.len(),
+        "pool token state mismatch"
+    );
+    let reserves = itertools::izip!(tokens, balances, scaling_factors, rates)
+        .map(|(token, balance, scaling_factor, rate)| TokenState {
+            token,
+            balance,
+            scaling_factor,
+            rate: Bfp::from_wei(rate),
+        })
+        .collect();
+
+    let kind = match &pool.kind {
+        PoolInfoKind::Weighted(weights) => PoolKind::Weighted(weights.clone()),
+        PoolInfoKind::Stable => {
+            let (value, _, precision) =
+                amplification_parameter.context("missing amplification parameter")?;
+            PoolKind::Stable(AmplificationParameter::try_new(value, precision)?)
+        }
+    };
+
+    Ok(Some(Pool {
+        address: pool.address,
+        reserves,
+        swap_fee: Bfp::from_wei(swap_fee),
+        hooks: pool.hooks,
+        kind,
+    }))
+}
+
+/// Collects the fetched pools, skipping pools that can't be traded and pools
+/// whose state could not be fetched because of contract errors.
+fn collect_pool_results(pools: Vec<Result<Option<Pool>>>) -> Result<Vec<Pool>> {
+    pools
+        .into_iter()
+        .filter_map(|pool| match pool {
+            Ok(pool) => pool.map(Ok),
+            Err(err) if is_contract_error(&err) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .collect()
+}
+
+fn is_contract_error(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<MethodError>()
+            .map(EthcontractErrorType::classify),
+        Some(EthcontractErrorType::Contract),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        contracts::errors::{testing_contract_error, testing_node_error},
+    };
+
+    fn pool() -> Pool {
+        Pool {
+            address: H160([0x11; 20]),
+            reserves: [1, 2, 3]
+                .into_iter()
+                .map(|i| TokenState {
+                    token: H160([i; 20]),
+                    balance: U256::exp10(18),
+                    scaling_factor: U256::one(),
+                    rate: Bfp::one(),
+                })
+                .collect(),
+            swap_fee: Bfp::zero(),
+            hooks: None,
+            kind: PoolKind::Stable(AmplificationParameter::try_new(100.into(), 1.into()).unwrap()),
+        }
+    }
+
+    #[test]
+    fn enumerates_token_pairs() {
+        assert_eq!(
+            pool().token_pairs().collect::<Vec<_>>(),
+            vec![
+                TokenPair::new(H160([1; 20]), H160([2; 20])).unwrap(),
+                TokenPair::new(H160([1; 20]), H160([3; 20])).unwrap(),
+                TokenPair::new(H160([2; 20]), H160([3; 20])).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn hooks_cost_additional_gas() {
+        let pool = pool();
+        assert_eq!(pool.swap_gas(), SWAP_GAS_COST);
+        let pool = Pool {
+            hooks: Some(H160([0x22; 20])),
+            ..pool
+        };
+        assert_eq!(pool.swap_gas(), SWAP_GAS_COST + HOOKS_GAS_COST);
+    }
+
+    #[test]
+    fn collecting_results_filters_inactive_pools_and_contract_errors() {
+        let results = vec![
+            Ok(Some(pool())),
+            Ok(None),
+            Err(testing_contract_error().into()),
+        ];
+        assert_eq!(collect_pool_results(results).unwrap(), vec![pool()]);
+    }
+
+    #[test]
+    fn collecting_results_forwards_node_error() {
+        let node_err = Err(testing_node_error().into());
+        assert!(collect_pool_results(vec![node_err]).is_err());
+    }
+}
@@ -0,0 +1,307 @@
+//! A pool registry for a single Balancer V3 pool factory.
+//!
+//! The registry is initialized with all pools the factory created up until
+//! some block and then keeps itself up to date by indexing the factory's
+//! `PoolCreated` events.
+
+use {
+    super::pool_fetching::{FactoryKind, PoolInfo, fetch_pool_info},
+    crate::{
+        event_handling::{EventHandler, EventRetrieving, EventStoring},
+        maintenance::Maintaining,
+    },
+    anyhow::{Context, Result},
+    contracts::{
+        BalancerV3BasePoolFactory,
+        BalancerV3Vault,
+        balancer_v3_base_pool_factory::Event as BasePoolFactoryEvent,
+    },
+    ethcontract::{BlockId, Event, H160, dyns::DynAllEventsBuilder},
+    ethrpc::block_stream::{BlockNumberHash, BlockRetrieving, RangeInclusive},
+    futures::future,
+    model::TokenPair,
+    std::{
+        cmp,
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    },
+    tokio::sync::Mutex,
+};
+
+/// The maximum number of pool addresses to request from a factory at once.
+const POOLS_PAGE_SIZE: usize = 500;
+
+pub struct BasePoolFactoryContract(BalancerV3BasePoolFactory);
+
+impl EventRetrieving for BasePoolFactoryContract {
+    type Event = BasePoolFactoryEvent;
+
+    fn get_events(&self) -> DynAllEventsBuilder<Self::Event> {
+        self.0.all_events()
+    }
+}
+
+/// Indexes the pools created by a Balancer V3 factory.
+pub struct Registry {
+    updater: Mutex<EventHandler<BasePoolFactoryContract, PoolStorage>>,
+}
+
+impl Registry {
+    /// Returns a new registry containing all the pools created by the factory
+    /// up until the specified block.
+    pub async fn new(
+        block_retriever: Arc<dyn BlockRetrieving>,
+        vault: BalancerV3Vault,
+        kind: FactoryKind,
+        factory: BalancerV3BasePoolFactory,
+        block: BlockNumberHash,
+    ) -> Result<Self> {
+        let at = BlockId::Number(block.0.into());
+        let count = factory.get_pool_count().block(at).call().await?.as_usize();
+        let mut addresses = Vec::with_capacity(count);
+        for start in (0..count).step_by(POOLS_PAGE_SIZE) {
+            let page = cmp::min(POOLS_PAGE_SIZE, count - start);
+            addresses.extend(
+                factory
+                    .get_pools_in_range(start.into(), page.into())
+                    .block(at)
+                    .call()
+                    .await?,
+            );
+        }
+
+        let pools = future::try_join_all(
+            addresses
+                .into_iter()
+                .map(|pool| fetch_pool_info(&vault, kind, pool, block.0)),
+        )
+        .await?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        tracing::debug!(?kind, pools = pools.len(), "indexed Balancer V3 pools");
+
+        let storage = PoolStorage::new(vault, kind, pools, block.0);
+        Ok(Self {
+            updater: Mutex::new(EventHandler::new(
+                block_retriever,
+                BasePoolFactoryContract(factory),
+                storage,
+                Some(block),
+            )),
+        })
+    }
+
+    /// Returns the pools trading any of the specified token pairs.
+    pub async fn pools_for_token_pairs(&self, token_pairs: &HashSet<TokenPair>) -> Vec<PoolInfo> {
+        self.updater
+            .lock()
+            .await
+            .store()
+            .pools_for_token_pairs(token_pairs)
+    }
+}
+
+#[async_trait::async_trait]
+impl Maintaining for Registry {
+    async fn run_maintenance(&self) -> Result<()> {
+        self.updater.run_maintenance().await
+    }
+
+    fn name(&self) -> &str {
+        "BalancerV3PoolRegistry"
+    }
+}
+
+/// In-memory storage of the static information of the pools created by a
+/// factory.
+pub struct PoolStorage {
+    vault: BalancerV3Vault,
+    kind: FactoryKind,
+    pools: HashMap<H160, PoolInfo>,
+    /// Used for O(1) access to all pools for a given token.
+    pools_by_token: HashMap<H160, HashSet<H160>>,
+    /// The block the initial pools were fetched on. This block is considered
+    /// reorg-safe and pools created prior to it do not get removed.
+    initial_fetched_block: u64,
+}
+
+impl PoolStorage {
+    fn new(
+        vault: BalancerV3Vault,
+        kind: FactoryKind,
+        pools: Vec<PoolInfo>,
+        initial_fetched_block: u64,
+    ) -> Self {
+        let mut storage = Self {
+            vault,
+            kind,
+            pools: Default::default(),
+            pools_by_token: Default::default(),
+            initial_fetched_block,
+        };
+        for pool in pools {
+            storage.insert_pool(pool);
+        }
+        storage
+    }
+
+    fn pools_for_token_pairs(&self, token_pairs: &HashSet<TokenPair>) -> Vec<PoolInfo> {
+        token_pairs
+            .iter()
+            .flat_map(|pair| {
+                let (token0, token1) = pair.get();
+                self.pools_by_token
+                    .get(&token0)
+                    .zip(self.pools_by_token.get(&token1))
+                    .into_iter()
+                    .flat_map(|(pools0, pools1)| pools0.intersection(pools1))
+            })
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|pool| self.pools.get(pool))
+            .cloned()
+            .collect()
+    }
+
+    fn insert_pool(&mut self, pool: PoolInfo) {
+        for token in &pool.tokens {
+            self.pools_by_token
+                .entry(*token)
+                .or_default()
+                .insert(pool.address);
+        }
+        self.pools.insert(pool.address, pool);
+    }
+
+    /// Removes all pools created in or after the specified block.
+    fn remove_pools_newer_than_block(&mut self, block: u64) {
+        let block = cmp::max(block, self.initial_fetched_block + 1);
+        self.pools.retain(|_, pool| pool.block_created < block);
+        for pools in self.pools_by_token.values_mut() {
+            pools.retain(|pool| self.pools.contains_key(pool));
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventStoring<BasePoolFactoryEvent> for PoolStorage {
+    async fn replace_events(
+        &mut self,
+        events: Vec<Event<BasePoolFactoryEvent>>,
+        range: RangeInclusive<u64>,
+    ) -> Result<()> {
+        self.remove_pools_newer_than_block(*range.start());
+        self.append_events(events).await
+    }
+
+    async fn append_events(&mut self, events: Vec<Event<BasePoolFactoryEvent>>) -> Result<()> {
+        for event in events {
+            let block_created = event.meta.context("event missing metadata")?.block_number;
+            let BasePoolFactoryEvent::PoolCreated(pool_created) = event.data;
+            if let Some(pool) =
+                fetch_pool_info(&self.vault, self.kind, pool_created.pool, block_created).await?
+            {
+                self.insert_pool(pool);
+            }
+        }
+        Ok(())
+    }
+
+    async fn last_event_block(&self) -> Result<u64> {
+        Ok(self
+            .pools
+            .values()
+            .map(|pool| pool.block_created)
+            .fold(self.initial_fetched_block, cmp::max))
+    }
+
+    async fn persist_last_indexed_block(&mut self, _block: u64) -> Result<()> {
+        // storage is only in-memory so we don't need to persist anything here
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::sources::balancer_v3::pool_fetching::PoolInfoKind,
+        contracts::dummy_contract,
+    };
+
+    fn pool(address: u64, tokens: &[u64], block_created: u64) -> PoolInfo {
+        PoolInfo {
+            address: H160::from_low_u64_be(address),
+            tokens: tokens.iter().copied().map(H160::from_low_u64_be).collect(),
+            hooks: None,
+            kind: PoolInfoKind::Stable,
+            block_created,
+        }
+    }
+
+    fn storage(pools: Vec<PoolInfo>) -> PoolStorage {
+        PoolStorage::new(
+            dummy_contract!(BalancerV3Vault, H160([0x42; 20])),
+            FactoryKind::Stable,
+            pools,
+            10,
+        )
+    }
+
+    fn addresses(pools: Vec<PoolInfo>) -> HashSet<H160> {
+        pools.into_iter().map(|pool| pool.address).collect()
+    }
+
+    #[test]
+    fn finds_pools_by_token_pairs() {
+        let storage = storage(vec![
+            pool(100, &[1, 2, 3], 10),
+            pool(101, &[2, 3], 10),
+            pool(102, &[3, 4], 10),
+        ]);
+        let pair = |a, b| TokenPair::new(H160::from_low_u64_be(a), H160::from_low_u64_be(b));
+
+        assert_eq!(
+            addresses(storage.pools_for_token_pairs(&HashSet::from([pair(2, 3).unwrap()]))),
+            HashSet::from([H160::from_low_u64_be(100), H160::from_low_u64_be(101)]),
+        );
+        assert_eq!(
+            addresses(
+                storage.pools_for_token_pairs(&HashSet::from([
+                    pair(1, 3).unwrap(),
+                    pair(3, 4).unwrap(),
+                ]))
+            ),
+            HashSet::from([H160::from_low_u64_be(100), H160::from_low_u64_be(102)]),
+        );
+        assert!(
+            storage
+                .pools_for_token_pairs(&HashSet::from([pair(1, 4).unwrap()]))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn removes_pools_newer_than_block_but_keeps_initial_pools() {
+        let mut storage = storage(vec![pool(100, &[1, 2], 10)]);
+        storage.insert_pool(pool(101, &[1, 2], 12));
+        storage.insert_pool(pool(102, &[1, 2], 14));
+
+        storage.remove_pools_newer_than_block(13);
+        assert_eq!(
+            storage.pools.keys().copied().collect::<HashSet<_>>(),
+            HashSet::from([H160::from_low_u64_be(100), H160::from_low_u64_be(101)]),
+        );
+
+        storage.remove_pools_newer_than_block(5);
+        assert_eq!(
+            storage.pools.keys().copied().collect::<HashSet<_>>(),
+            HashSet::from([H160::from_low_u64_be(100)]),
+        );
+        assert_eq!(
+            storage.pools_by_token[&H160::from_low_u64_be(1)],
+            HashSet::from([H160::from_low_u64_be(100)]),
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Module emulating the swap logic of the Balancer V3 vault. The original
+//! contract code can be found at:
+//! https://github.com/balancer/balancer-v3-monorepo/blob/main/pkg/vault/contracts/Vault.sol
+//!
+//! The vault scales amounts to 18 decimals, applies token rates and charges
+//! swap fees, so pools only compute swaps on "live" balances.
+
+use {
+    super::pool_fetching::{Pool, PoolKind, TokenState},
+    crate::{
+        baseline_solver::BaselineSolvable,
+        sources::balancer_v2::swap::{
+            converge_in_amount,
+            fixed_point::Bfp,
+            stable_math::{self, AMP_PRECISION},
+            weighted_math,
+        },
+    },
+    ethcontract::{H160, U256},
+};
+
+/// The minimum swap amount, scaled to 18 decimals, that the vault accepts.
+/// https://github.com/balancer/balancer-v3-monorepo/blob/main/pkg/vault/contracts/VaultCommon.sol
+const MINIMUM_TRADE_AMOUNT: u64 = 1_000_000;
+
+impl TokenState {
+    /// Scales a raw amount to 18 decimals and applies the token rate,
+    /// rounding down.
+    fn to_scaled_18_down(&self, amount: U256) -> Option<Bfp> {
+        Bfp::from_wei(amount.checked_mul(self.scaling_factor)?)
+            .mul_down(self.rate)
+            .ok()
+    }
+
+    /// Scales a raw amount to 18 decimals and applies the token rate,
+    /// rounding up.
+    fn to_scaled_18_up(&self, amount: U256) -> Option<Bfp> {
+        Bfp::from_wei(amount.checked_mul(self.scaling_factor)?)
+            .mul_up(self.rate)
+            .ok()
+    }
+
+    /// Converts an amount scaled to 18 decimals with the token rate applied
+    /// back to a raw amount, rounding down.
+    fn to_raw_down(&self, amount: Bfp) -> Option<U256> {
+        Some(amount.div_down(self.scaled_rate()?).ok()?.as_uint256())
+    }
+
+    /// Converts an amount scaled to 18 decimals with the token rate applied
+    /// back to a raw amount, rounding up.
+    fn to_raw_up(&self, amount: Bfp) -> Option<U256> {
+        Some(amount.div_up(self.scaled_rate()?).ok()?.as_uint256())
+    }
+
+    fn scaled_rate(&self) -> Option<Bfp> {
+        Some(Bfp::from_wei(
+            self.rate.as_uint256().checked_mul(self.scaling_factor)?,
+        ))
+    }
+}
+
+impl Pool {
+    fn indices(&self, in_token: H160, out_token: H160) -> Option<(usize, usize)> {
+        let index = |token| self.reserves.iter().position(|r| r.token == token);
+        let (i, j) = (index(in_token)?, index(out_token)?);
+        (i != j).then_some((i, j))
+    }
+
+    fn live_balances(&self) -> Option<Vec<Bfp>> {
+        self.reserves
+            .iter()
+            .map(|reserve| reserve.to_scaled_18_down(reserve.balance))
+            .collect()
+    }
+
+    /// Computes the amount out for an exact amount in with the pool math.
+    /// Amounts are scaled to 18 decimals with the token rates applied.
+    fn calc_out_given_in(&self, i: usize, j: usize, amount_in: Bfp) -> Option<Bfp> {
+        let mut balances = self.live_balances()?;
+        match &self.kind {
+            PoolKind::Weighted(weights) => weighted_math::calc_out_given_in_v3(
+                balances[i],
+                *weights.get(i)?,
+                balances[j],
+                *weights.get(j)?,
+                amount_in,
+            ),
+            PoolKind::Stable(amplification_parameter) => stable_math::calc_out_given_in(
+                amplification_parameter.with_base(*AMP_PRECISION)?,
+                &mut balances,
+                i,
+                j,
+                amount_in,
+            ),
+        }
+        .ok()
+    }
+
+    /// Computes the amount in for an exact amount out with the pool math.
+    /// Amounts are scaled to 18 decimals with the token rates applied.
+    fn calc_in_given_out(&self, i: usize, j: usize, amount_out: Bfp) -> Option<Bfp> {
+        let mut balances = self.live_balances()?;
+        match &self.kind {
+            PoolKind::Weighted(weights) => weighted_math::calc_in_given_out_v3(
+                balances[i],
+                *weights.get(i)?,
+                balances[j],
+                *weights.get(j)?,
+                amount_out,
+            ),
+            PoolKind::Stable(amplification_parameter) => stable_math::calc_in_given_out(
+                amplification_parameter.with_base(*AMP_PRECISION)?,
+                &mut balances,
+                i,
+                j,
+                amount_out,
+            ),
+        }
+        .ok()
+    }
+}
+
+/// Returns `None` for non-zero amounts below the vault's minimum trade amount.
+fn valid_trade_amount(amount: Bfp) -> Option<Bfp> {
+    (amount.is_zero() || amount.as_uint256() >= MINIMUM_TRADE_AMOUNT.into()).then_some(amount)
+}
+
+impl BaselineSolvable for Pool {
+    fn get_amount_out(&self, out_token: H160, (in_amount, in_token): (U256, H160)) -> Option<U256> {
+        // https://github.com/balancer/balancer-v3-monorepo/blob/main/pkg/vault/contracts/Vault.sol#L380-L400
+        let (i, j) = self.indices(in_token, out_token)?;
+        let amount_in = valid_trade_amount(self.reserves[i].to_scaled_18_down(in_amount)?)?;
+        let fee = amount_in.mul_up(self.swap_fee).ok()?;
+        let amount_out =
+            valid_trade_amount(self.calc_out_given_in(i, j, amount_in.sub(fee).ok()?)?)?;
+        self.reserves[j].to_raw_down(amount_out)
+    }
+
+    fn get_amount_in(&self, in_token: H160, (out_amount, out_token): (U256, H160)) -> Option<U256> {
+        // https://github.com/balancer/balancer-v3-monorepo/blob/main/pkg/vault/contracts/Vault.sol#L400-L420
+        let (i, j) = self.indices(in_token, out_token)?;
+        let amount_out = valid_trade_amount(self.reserves[j].to_scaled_18_up(out_amount)?)?;
+        let amount_in = self.calc_in_given_out(i, j, amount_out)?;
+        // The fee is charged on top of the amount in, such that it is the
+        // configured percentage of the total amount in.
+        let amount_in = valid_trade_amount(amount_in.div_up(self.swap_fee.complement()).ok()?)?;
+        let in_amount = self.reserves[i].to_raw_up(amount_in)?;
+
+        converge_in_amount(in_amount, out_amount, |x| {
+            self.get_amount_out(out_token, (x, in_token))
+        })
+    }
+
+    fn gas_cost(&self) -> usize {
+        self.swap_gas() as _
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::sources::balancer_v2::pool_fetching::AmplificationParameter,
+    };
+
+    fn token(token: u8, balance: U256, scaling_factor: U256, rate: Bfp) -> TokenState {
+        TokenState {
+            token: H160([token; 20]),
+            balance,
+            scaling_factor,
+            rate,
+        }
+    }
+
+    fn pool(reserves: Vec<TokenState>, swap_fee: Bfp, kind: PoolKind) -> Pool {
+        Pool {
+            address: H160([0x11; 20]),
+            reserves,
+            swap_fee,
+            hooks: None,
+            kind,
+        }
+    }
+
+    fn equal_weights() -> PoolKind {
+        PoolKind::Weighted(vec![bfp!("0.5"); 2])
+    }
+
+    #[test]
+    fn weighted_swaps_apply_rates_and_decimals() {
+        // Both pools have live balances of 1000 for either token, so they
+        // quote the same swaps.
+        let plain = pool(
+            vec![
+                token(1, U256::exp10(21), U256::one(), Bfp::one()),
+                token(2, U256::exp10(21), U256::one(), Bfp::one()),
+            ],
+            Bfp::zero(),
+            equal_weights(),
+        );
+        let scaled = pool(
+            vec![
+                // A yield bearing token worth two units.
+                token(1, U256::exp10(21) / 2, U256::one(), bfp!("2")),
+                // A token with 6 decimals.
+                token(2, U256::exp10(9), U256::exp10(12), Bfp::one()),
+            ],
+            Bfp::zero(),
+            equal_weights(),
+        );
+
+        assert_eq!(
+            plain.get_amount_out(H160([2; 20]), (U256::exp10(18), H160([1; 20]))),
+            Some(999_000_999_000_999_000_u128.into()),
+        );
+        assert_eq!(
+            scaled.get_amount_out(H160([2; 20]), (U256::exp10(18) / 2, H160([1; 20]))),
+            Some(999_000.into()),
+        );
+    }
+
+    #[test]
+    fn weighted_swaps_charge_fee_on_amount_in() {
+        let pool = pool(
+            vec![
+                token(1, U256::exp10(21), U256::one(), Bfp::one()),
+                token(2, U256::exp10(21), U256::one(), Bfp::one()),
+            ],
+            bfp!("0.01"),
+            equal_weights(),
+        );
+        let without_fee = Pool {
+            swap_fee: Bfp::zero(),
+            ..pool.clone()
+        };
+
+        // Selling 1.0101.. with a 1% fee is the same as selling 1 without.
+        let amount_in = U256::from(1_010_101_010_101_010_102_u128);
+        assert_eq!(
+            pool.get_amount_out(H160([2; 20]), (amount_in, H160([1; 20]))),
+            without_fee.get_amount_out(H160([2; 20]), (U256::exp10(18), H160([1; 20]))),
+        );
+    }
+
+    #[test]
+    fn amount_in_buys_amount_out() {
+        let pools = [
+            pool(
+                vec![
+                    token(1, U256::exp10(21) / 2, U256::one(), bfp!("2")),
+                    token(2, U256::exp10(9), U256::exp10(12), Bfp::one()),
+                ],
+                bfp!("0.003"),
+                equal_weights(),
+            ),
+            pool(
+                vec![
+                    token(1, U256::exp10(21), U256::one(), bfp!("1.1")),
+                    token(2, U256::exp10(9), U256::exp10(12), Bfp::one()),
+                    token(3, U256::exp10(21), U256::one(), Bfp::one()),
+                ],
+                bfp!("0.0001"),
+                PoolKind::Stable(
+                    AmplificationParameter::try_new(200_000.into(), 1000.into()).unwrap(),
+                ),
+            ),
+        ];
+
+        for pool in pools {
+            let (in_token, out_token) = (H160([1; 20]), H160([2; 20]));
+            let out_amount = U256::from(10_000_000);
+            let in_amount = pool
+                .get_amount_in(in_token, (out_amount, out_token))
+                .unwrap();
+            assert!(
+                pool.get_amount_out(out_token, (in_amount, in_token))
+                    .unwrap()
+                    >= out_amount
+            );
+        }
+    }
+
+    #[test]
+    fn stable_swaps_are_close_to_parity() {
+        let pool = pool(
+            vec![
+                token(1, U256::exp10(21), U256::one(), Bfp::one()),
+                token(2, U256::exp10(9), U256::exp10(12), Bfp::one()),
+            ],
+            Bfp::zero(),
+            PoolKind::Stable(AmplificationParameter::try_new(200_000.into(), 1000.into()).unwrap()),
+        );
+
+        let out = pool
+            .get_amount_out(H160([2; 20]), (U256::exp10(18), H160([1; 20])))
+            .unwrap();
+        assert!(out < U256::exp10(6) && out > U256::exp10(6) * 999 / 1000);
+    }
+
+    #[test]
+    fn rejects_invalid_swaps() {
+        let pool = pool(
+            vec![
+                token(1, U256::exp10(21), U256::one(), Bfp::one()),
+                token(2, U256::exp10(21), U256::one(), Bfp::one()),
+            ],
+            Bfp::zero(),
+            equal_weights(),
+        );
+
+        // Amounts below the vault's minimum trade amount.
+        assert_eq!(
+            pool.get_amount_out(H160([2; 20]), (1000.into(), H160([1; 20]))),
+            None
+        );
+        // Tokens that are not in the pool.
+        assert_eq!(
+            pool.get_amount_out(H160([3; 20]), (U256::exp10(18), H160([1; 20]))),
+            None
+        );
+        assert_eq!(
+            pool.get_amount_in(H160([1; 20]), (U256::exp10(18), H160([1; 20]))),
+            None
+        );
+    }
+}
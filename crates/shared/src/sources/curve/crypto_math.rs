@@ -0,0 +1,352 @@
+//! Module emulating the swap math of the Curve CryptoSwap pools used by two
+//! coin crypto and tricrypto pools. The original contract code can be found
+//! at:
+//! https://github.com/curvefi/curve-crypto-contract/blob/d7d04cd9ae038970e40be850df99de8c1ff7241b/contracts/two/CurveCryptoSwap2ETH.vy
+//! https://github.com/curvefi/curve-crypto-contract/blob/d7d04cd9ae038970e40be850df99de8c1ff7241b/contracts/tricrypto/CurveCryptoSwap.vy
+
+use {
+    super::{abs_diff, ceil_div},
+    ethcontract::U256,
+};
+
+/// The denominator of pool fees.
+pub const FEE_DENOMINATOR: u64 = 10_000_000_000;
+
+/// The precision of prices and the fixed point values used in the pool math.
+pub const PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// The multiplier of the amplification coefficient.
+const A_MULTIPLIER: u64 = 10_000;
+
+/// The maximum number of iterations of the balance approximation.
+const MAX_ITERATIONS: usize = 255;
+
+/// The state of a CryptoSwap pool required for computing swaps.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CryptoPoolState {
+    /// The balances of the pool's coins, in coin index order.
+    pub balances: Vec<U256>,
+    /// The multipliers normalizing each coin's balance to 18 decimals.
+    pub precisions: Vec<U256>,
+    /// The internal prices of all coins but the first in terms of the first
+    /// coin, scaled by [`PRECISION`].
+    pub price_scale: Vec<U256>,
+    /// The amplification coefficient as returned by the pool, which includes
+    /// the `N**N` and [`A_MULTIPLIER`] factors.
+    pub amplification: U256,
+    pub gamma: U256,
+    /// The invariant `D` of the pool.
+    pub d: U256,
+    /// The fee of swaps in a balanced pool, relative to [`FEE_DENOMINATOR`].
+    pub mid_fee: U256,
+    /// The fee of swaps in an imbalanced pool, relative to
+    /// [`FEE_DENOMINATOR`].
+    pub out_fee: U256,
+    /// The parameter controlling how fast the fee changes from `mid_fee` to
+    /// `out_fee` with the pool's imbalance.
+    pub fee_gamma: U256,
+}
+
+impl CryptoPoolState {
+    /// Returns the amount of coin `j` received for `dx` of coin `i`, computed
+    /// exactly like the pool's `get_dy` function.
+    pub fn get_dy(&self, i: usize, j: usize, dx: U256) -> Option<U256> {
+        if !self.is_valid_swap(i, j) {
+            return None;
+        }
+
+        let mut balances = self.balances.clone();
+        balances[i] = balances[i].checked_add(dx)?;
+        let mut xp = self.scale(balances)?;
+        let y = newton_y(self.amplification, self.gamma, &xp, self.d, j)?;
+        let mut dy = xp[j].checked_sub(y)?.checked_sub(1.into())?;
+        xp[j] = y;
+        if j > 0 {
+            dy = dy
+                .checked_mul(PRECISION.into())?
+                .checked_div(self.price_scale[j - 1])?;
+        }
+        dy = dy.checked_div(self.precisions[j])?;
+        dy = dy.checked_sub(self.fee(&xp)?.checked_mul(dy)? / FEE_DENOMINATOR)?;
+        (dy < self.balances[j]).then_some(dy)
+    }
+
+    /// Returns the amount of coin `i` that needs to be sold in order to
+    /// receive at least `dy` of coin `j`.
+    ///
+    /// Pools don't implement this computation, so the amount is estimated by
+    /// inverting the swap math with the lowest possible fee and then refined
+    /// with [`Self::get_dy`].
+    pub fn get_dx(&self, i: usize, j: usize, dy: U256) -> Option<U256> {
+        if !self.is_valid_swap(i, j) {
+            return None;
+        }
+
+        let fee_denominator = U256::from(FEE_DENOMINATOR);
+        let dy_before_fee = ceil_div(
+            dy.checked_mul(fee_denominator)?,
+            fee_denominator.checked_sub(self.mid_fee)?,
+        )?;
+        let mut balances = self.balances.clone();
+        balances[j] = balances[j].checked_sub(dy_before_fee)?;
+        let mut xp = self.scale(balances)?;
+        xp[j] = xp[j].checked_sub(1.into())?;
+        let x = newton_y(self.amplification, self.gamma, &xp, self.d, i)?;
+
+        let mut dx = x.checked_sub(self.scale(self.balances.clone())?[i])?;
+        if i > 0 {
+            dx = ceil_div(dx.checked_mul(PRECISION.into())?, self.price_scale[i - 1])?;
+        }
+        let dx = ceil_div(dx, self.precisions[i])?;
+
+        super::refine_amount_in(dx.checked_add(1.into())?, dy, |dx| self.get_dy(i, j, dx))
+    }
+
+    fn is_valid_swap(&self, i: usize, j: usize) -> bool {
+        i != j && i < self.balances.len() && j < self.balances.len()
+    }
+
+    /// Normalizes balances to 18 decimals and prices them in terms of the
+    /// first coin.
+    fn scale(&self, mut balances: Vec<U256>) -> Option<Vec<U256>> {
+        if self.precisions.len() != balances.len() || self.price_scale.len() + 1 != balances.len() {
+            return None;
+        }
+        for (k, balance) in balances.iter_mut().enumerate() {
+            *balance = balance.checked_mul(self.precisions[k])?;
+            if k > 0 {
+                *balance = balance.checked_mul(self.price_scale[k - 1])? / PRECISION;
+            }
+        }
+        Some(balances)
+    }
+
+    /// Returns the fee for the normalized balances after a swap.
+    fn fee(&self, xp: &[U256]) -> Option<U256> {
+        let precision = U256::from(PRECISION);
+        let n = U256::from(xp.len());
+        let s = xp
+            .iter()
+            .try_fold(U256::zero(), |sum, x| sum.checked_add(*x))?;
+
+        let mut k = precision;
+        for x in xp {
+            k = k.checked_mul(n)?.checked_mul(*x)?.checked_div(s)?;
+        }
+        if !self.fee_gamma.is_zero() {
+            k = self
+                .fee_gamma
+                .checked_mul(precision)?
+                .checked_div(self.fee_gamma.checked_add(precision)?.checked_sub(k)?)?;
+        }
+
+        Some(
+            self.mid_fee
+                .checked_mul(k)?
+                .checked_add(self.out_fee.checked_mul(precision.checked_sub(k)?)?)?
+                / PRECISION,
+        )
+    }
+}
+
+/// Computes the normalized balance of coin `i` such that the invariant `d`
+/// holds for the other normalized balances.
+fn newton_y(amplification: U256, gamma: U256, x: &[U256], d: U256, i: usize) -> Option<U256> {
+    let n = x.len();
+    if n < 2 || i >= n {
+        return None;
+    }
+    let n_coins = U256::from(n);
+    let precision = U256::from(PRECISION);
+
+    let mut x_sorted = x.to_vec();
+    x_sorted[i] = U256::zero();
+    x_sorted.sort_unstable_by(|a, b| b.cmp(a));
+    let convergence_limit = (x_sorted[0] / 100_000_000_000_000_u64)
+        .max(d / 100_000_000_000_000_u64)
+        .max(100.into());
+
+    // The two coin pools start from a slightly different initial value.
+    let (mut y, s_i) = if n == 2 {
+        let y = d
+            .checked_mul(d)?
+            .checked_div(x_sorted[0].checked_mul(n_coins.checked_mul(n_coins)?)?)?;
+        (y, x_sorted[0])
+    } else {
+        let mut y = d / n_coins;
+        let mut s_i = U256::zero();
+        for x_j in x_sorted[..n - 1].iter().rev() {
+            y = y.checked_mul(d)?.checked_div(x_j.checked_mul(n_coins)?)?;
+            s_i = s_i.checked_add(*x_j)?;
+        }
+        (y, s_i)
+    };
+    let mut k0_i = precision;
+    for x_j in &x_sorted[..n - 1] {
+        k0_i = k0_i
+            .checked_mul(*x_j)?
+            .checked_mul(n_coins)?
+            .checked_div(d)?;
+    }
+
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let k0 = k0_i.checked_mul(y)?.checked_mul(n_coins)?.checked_div(d)?;
+        let s = s_i.checked_add(y)?;
+
+        let g1k0 = gamma.checked_add(precision)?;
+        let g1k0 = if g1k0 > k0 {
+            g1k0 - k0 + U256::one()
+        } else {
+            k0 - g1k0 + U256::one()
+        };
+
+        // D / (A * N**N) * g1k0**2 / gamma**2
+        let mul1 = precision
+            .checked_mul(d)?
+            .checked_div(gamma)?
+            .checked_mul(g1k0)?
+            .checked_div(gamma)?
+            .checked_mul(g1k0)?
+            .checked_mul(A_MULTIPLIER.into())?
+            .checked_div(amplification)?;
+        // 2 * K0 / g1k0
+        let mul2 = precision.checked_add(
+            precision
+                .checked_mul(2.into())?
+                .checked_mul(k0)?
+                .checked_div(g1k0)?,
+        )?;
+
+        let yfprime = precision
+            .checked_mul(y)?
+            .checked_add(s.checked_mul(mul2)?)?
+            .checked_add(mul1)?;
+        let dyfprime = d.checked_mul(mul2)?;
+        if yfprime < dyfprime {
+            y = y_prev / 2;
+            continue;
+        }
+        let yfprime = yfprime - dyfprime;
+        let fprime = yfprime.checked_div(y)?;
+
+        let y_minus = mul1.checked_div(fprime)?;
+        let y_plus = yfprime
+            .checked_add(precision.checked_mul(d)?)?
+            .checked_div(fprime)?
+            .checked_add(y_minus.checked_mul(precision)?.checked_div(k0)?)?;
+        let y_minus = y_minus.checked_add(precision.checked_mul(s)?.checked_div(fprime)?)?;
+        y = if y_plus < y_minus {
+            y_prev / 2
+        } else {
+            y_plus - y_minus
+        };
+
+        if abs_diff(y, y_prev) < convergence_limit.max(y / 100_000_000_000_000_u64) {
+            // The pool rejects results outside of the safe range.
+            let frac = y.checked_mul(precision)?.checked_div(d)?;
+            let safe = U256::exp10(16)..=U256::exp10(20);
+            return safe.contains(&frac).then_some(y);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u(value: u128) -> U256 {
+        value.into()
+    }
+
+    fn e(value: u128, decimals: u32) -> U256 {
+        U256::from(value) * U256::exp10(decimals as _)
+    }
+
+    fn dec(value: &str) -> U256 {
+        U256::from_dec_str(value).unwrap()
+    }
+
+    /// A balanced tricrypto-like pool of USDT, WBTC and WETH.
+    fn tricrypto() -> CryptoPoolState {
+        CryptoPoolState {
+            balances: vec![e(30_000_000, 6), e(1_000, 8), e(15_000, 18)],
+            precisions: vec![e(1, 12), e(1, 10), e(1, 0)],
+            price_scale: vec![e(30_000, 18), e(2_000, 18)],
+            amplification: u(1_707_629),
+            gamma: u(11_809_167_828_997),
+            d: e(90_000_000, 18),
+            mid_fee: u(3_000_000),
+            out_fee: u(30_000_000),
+            fee_gamma: u(500_000_000_000_000),
+        }
+    }
+
+    /// A balanced two coin crypto pool of WETH and CRV.
+    fn two_crypto() -> CryptoPoolState {
+        CryptoPoolState {
+            balances: vec![e(10_000, 18), e(20_000_000, 18)],
+            precisions: vec![e(1, 0), e(1, 0)],
+            price_scale: vec![e(5, 14)],
+            amplification: u(400_000),
+            gamma: u(145_000_000_000_000),
+            d: e(20_000, 18),
+            mid_fee: u(26_000_000),
+            out_fee: u(45_000_000),
+            fee_gamma: u(230_000_000_000_000),
+        }
+    }
+
+    #[test]
+    fn fee_of_balanced_pool_is_mid_fee() {
+        let pool = tricrypto();
+        let xp = pool.scale(pool.balances.clone()).unwrap();
+        assert_eq!(pool.fee(&xp), Some(pool.mid_fee));
+    }
+
+    #[test]
+    fn amount_out() {
+        let pool = tricrypto();
+        assert_eq!(
+            pool.get_dy(0, 2, e(1_000_000, 6)),
+            Some(dec("485505951362345578072")),
+        );
+        assert_eq!(pool.get_dy(2, 1, e(100, 18)), Some(u(665_365_706)));
+        assert_eq!(pool.get_dy(1, 0, e(1, 8)), Some(u(29_990_232_871)));
+        assert_eq!(pool.get_dy(1, 1, e(1, 8)), None);
+        assert_eq!(pool.get_dy(1, 3, e(1, 8)), None);
+
+        let pool = two_crypto();
+        assert_eq!(
+            pool.get_dy(0, 1, e(10, 18)),
+            Some(dec("19946873747162337719588")),
+        );
+        assert_eq!(
+            pool.get_dy(1, 0, e(100_000, 18)),
+            Some(dec("49844940509640536500")),
+        );
+        // Swaps moving the pool too far from its price are rejected.
+        assert_eq!(pool.get_dy(0, 1, e(1, 30)), None);
+    }
+
+    #[test]
+    fn amount_in() {
+        for (pool, i, j, dy, dx) in [
+            (tricrypto(), 0, 2, e(100, 18), u(200_392_774_296)),
+            (tricrypto(), 1, 0, e(1_000_000, 6), u(3_436_526_682)),
+            (tricrypto(), 2, 1, e(5, 8), dec("75079203280303612669")),
+            (
+                two_crypto(),
+                0,
+                1,
+                e(50_000, 18),
+                dec("25069670001586650316"),
+            ),
+            (two_crypto(), 1, 0, e(1, 18), dec("2005223296888794278315")),
+        ] {
+            assert_eq!(pool.get_dx(i, j, dy), Some(dx));
+            assert!(pool.get_dy(i, j, dx).unwrap() >= dy);
+        }
+    }
+}
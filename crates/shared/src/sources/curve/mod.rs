@@ -0,0 +1,55 @@
+//! Curve liquidity.
+//!
+//! Pools are discovered through the on-chain Curve registries, which list the
+//! plain and lending StableSwap pools as well as the CryptoSwap pools. The
+//! static information of the pools is read once while their balances and
+//! parameters are fetched with a multicall for every request.
+//!
+//! Swaps are computed locally with ports of the pools' swap math.
+
+pub mod crypto_math;
+pub mod pool_fetching;
+pub mod stable_math;
+
+use ethcontract::U256;
+
+/// The maximum number of rounds for refining an estimated amount in.
+const MAX_REFINEMENT_ROUNDS: usize = 8;
+
+/// Refines an estimated amount in until it buys at least `amount_out`.
+///
+/// Curve pools don't implement computing the amount in for a fixed amount out,
+/// so it gets estimated by the swap math and verified with the pools' exact
+/// amount out computation.
+fn refine_amount_in(
+    mut amount_in: U256,
+    amount_out: U256,
+    get_amount_out: impl Fn(U256) -> Option<U256>,
+) -> Option<U256> {
+    for _ in 0..MAX_REFINEMENT_ROUNDS {
+        let out = get_amount_out(amount_in)?;
+        if out >= amount_out {
+            return Some(amount_in);
+        }
+        // Swaps have diminishing returns, so scaling the amount in by the
+        // missing amount out approaches the required amount from below.
+        amount_in = ceil_div(amount_in.checked_mul(amount_out)?, out)?.checked_add(1.into())?;
+    }
+    None
+}
+
+fn ceil_div(a: U256, b: U256) -> Option<U256> {
+    if b.is_zero() {
+        return None;
+    }
+    let (quotient, remainder) = a.div_mod(b);
+    Some(if remainder.is_zero() {
+        quotient
+    } else {
+        quotient + U256::one()
+    })
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b { a - b } else { b - a }
+}
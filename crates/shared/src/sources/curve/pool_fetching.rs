@@ -0,0 +1,536 @@
+use {
+    super::{crypto_math::CryptoPoolState, stable_math::StablePoolState},
+    crate::{baseline_solver::BaselineSolvable, recent_block_cache::Block},
+    anyhow::{Context, Result, ensure},
+    contracts::{CurveCryptoRegistry, CurveCryptoSwapPool, CurveRegistry, CurveStableSwapPool},
+    ethcontract::{BlockId, H160, U256, errors::ExecutionError, web3::types::Bytes},
+    ethrpc::{
+        Web3,
+        multicall::{Call, MulticallExt, Options},
+    },
+    model::TokenPair,
+    std::collections::HashSet,
+    web3::ethabi::{self, ParamType, Token},
+};
+
+/// Median gas used by swaps through StableSwap pools.
+pub const STABLE_SWAP_GAS_COST: u64 = 130_000;
+
+/// Median gas used by swaps through CryptoSwap pools.
+pub const CRYPTO_SWAP_GAS_COST: u64 = 180_000;
+
+/// The address Curve pools use to represent native Ether.
+const NATIVE_TOKEN: H160 = H160([0xee; 20]);
+
+/// The maximum number of coins of pools listed in the registries.
+const MAX_COINS: usize = 8;
+
+/// The maximum number of calls to batch into a single multicall.
+const MULTICALL_BATCH_SIZE: usize = 100;
+
+#[async_trait::async_trait]
+pub trait PoolFetching: Send + Sync {
+    async fn fetch(&self, token_pairs: &HashSet<TokenPair>, at_block: Block) -> Result<Vec<Pool>>;
+}
+
+/// A Curve pool along with its most recent state.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pool {
+    pub address: H160,
+    /// The coins of the pool in coin index order.
+    pub tokens: Vec<H160>,
+    pub state: PoolState,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PoolState {
+    /// A plain or lending StableSwap pool.
+    Stable(StablePoolState),
+    /// A two coin crypto or tricrypto CryptoSwap pool.
+    Crypto(CryptoPoolState),
+}
+
+impl Pool {
+    /// Returns all token pairs that can be traded with the pool.
+    pub fn token_pairs(&self) -> impl Iterator<Item = TokenPair> + '_ {
+        self.tokens.iter().enumerate().flat_map(|(i, a)| {
+            self.tokens[i + 1..]
+                .iter()
+                .filter_map(|b| TokenPair::new(*a, *b))
+        })
+    }
+
+    /// Returns the coin indices of the input and output tokens.
+    pub fn indices(&self, in_token: H160, out_token: H160) -> Option<(usize, usize)> {
+        let index = |token| self.tokens.iter().position(|t| *t == token);
+        Some((index(in_token)?, index(out_token)?))
+    }
+}
+
+impl BaselineSolvable for Pool {
+    fn get_amount_out(&self, out_token: H160, (in_amount, in_token): (U256, H160)) -> Option<U256> {
+        let (i, j) = self.indices(in_token, out_token)?;
+        match &self.state {
+            PoolState::Stable(state) => state.get_dy(i, j, in_amount),
+            PoolState::Crypto(state) => state.get_dy(i, j, in_amount),
+        }
+    }
+
+    fn get_amount_in(&self, in_token: H160, (out_amount, out_token): (U256, H160)) -> Option<U256> {
+        let (i, j) = self.indices(in_token, out_token)?;
+        match &self.state {
+            PoolState::Stable(state) => state.get_dx(i, j, out_amount),
+            PoolState::Crypto(state) => state.get_dx(i, j, out_amount),
+        }
+    }
+
+    fn gas_cost(&self) -> usize {
+        let gas = match self.state {
+            PoolState::Stable(_) => STABLE_SWAP_GAS_COST,
+            PoolState::Crypto(_) => CRYPTO_SWAP_GAS_COST,
+        };
+        gas as _
+    }
+}
+
+/// A Curve registry to discover pools from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Registry {
+    /// The main registry listing plain and lending StableSwap pools.
+    Stable(H160),
+    /// The registry listing CryptoSwap pools.
+    Crypto(H160),
+}
+
+impl Registry {
+    fn address(&self) -> H160 {
+        match self {
+            Self::Stable(address) | Self::Crypto(address) => *address,
+        }
+    }
+}
+
+/// The static information of a pool listed in a registry.
+#[derive(Clone, Debug)]
+struct RegisteredPool {
+    address: H160,
+    registry: Registry,
+    tokens: Vec<H160>,
+    /// The multipliers normalizing each coin's (underlying) balance to 18
+    /// decimals.
+    precisions: Vec<U256>,
+}
+
+/// Fetches the state of the Curve pools listed in the configured registries.
+pub struct CurvePoolFetcher {
+    web3: Web3,
+    pools: Vec<RegisteredPool>,
+}
+
+impl CurvePoolFetcher {
+    /// Creates a new pool fetcher, discovering all pools listed in the
+    /// specified registries.
+    pub async fn new(web3: Web3, registries: &[Registry]) -> Result<Self> {
+        let mut pools = Vec::new();
+        for registry in registries {
+            let registered = discover(&web3, *registry)
+                .await
+                .with_context(|| format!("failed to discover pools of {registry:?}"))?;
+            tracing::debug!(
+                ?registry,
+                pools = registered.len(),
+                "discovered Curve pools"
+            );
+            pools.extend(registered);
+        }
+        Ok(Self { web3, pools })
+    }
+}
+
+#[async_trait::async_trait]
+impl PoolFetching for CurvePoolFetcher {
+    async fn fetch(&self, token_pairs: &HashSet<TokenPair>, at_block: Block) -> Result<Vec<Pool>> {
+        let pools = self
+            .pools
+            .iter()
+            .filter(|pool| {
+                token_pairs.iter().any(|pair| {
+                    let (a, b) = pair.get();
+                    pool.tokens.contains(&a) && pool.tokens.contains(&b)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let calls = pools
+            .iter()
+            .map(|pool| pool.state_calls(&self.web3))
+            .collect::<Vec<_>>();
+        let mut results = multicall(
+            &self.web3,
+            calls.iter().flatten().cloned().collect(),
+            Some(BlockId::Number(at_block.into())),
+        )
+        .await
+        .into_iter();
+
+        Ok(pools
+            .into_iter()
+            .zip(&calls)
+            .filter_map(|(pool, calls)| {
+                let results = results.by_ref().take(calls.len()).collect();
+                match pool.decode_state(results) {
+                    Ok(state) => Some(Pool {
+                        address: pool.address,
+                        tokens: pool.tokens.clone(),
+                        state,
+                    }),
+                    Err(err) => {
+                        tracing::debug!(pool = ?pool.address, ?err, "failed to fetch Curve pool state");
+                        None
+                    }
+                }
+            })
+            .collect())
+    }
+}
+
+impl RegisteredPool {
+    /// Returns the calls for fetching the current state of the pool.
+    fn state_calls(&self, web3: &Web3) -> Vec<Call> {
+        match self.registry {
+            Registry::Stable(registry) => {
+                let contract = CurveRegistry::at(web3, registry);
+                let pool = CurveStableSwapPool::at(web3, self.address);
+                vec![
+                    call(registry, contract.get_balances(self.address).m.tx.data),
+                    call(registry, contract.get_rates(self.address).m.tx.data),
+                    call(registry, contract.get_amplification(self.address).m.tx.data),
+                    call(registry, contract.get_fees(self.address).m.tx.data),
+                    call(self.address, pool.offpeg_fee_multiplier().m.tx.data),
+                ]
+            }
+            Registry::Crypto(_) => {
+                let pool = CurveCryptoSwapPool::at(web3, self.address);
+                let balances = (0..self.tokens.len())
+                    .map(|k| call(self.address, pool.balances(k.into()).m.tx.data));
+                // Two coin pools only have a single price and don't take an
+                // index.
+                let price_scale = if self.tokens.len() == 2 {
+                    vec![call(self.address, pool.price_scale().m.tx.data)]
+                } else {
+                    (0..self.tokens.len() - 1)
+                        .map(|k| call(self.address, pool.price_scale_at(k.into()).m.tx.data))
+                        .collect()
+                };
+                [
+                    call(self.address, pool.amplification().m.tx.data),
+                    call(self.address, pool.gamma().m.tx.data),
+                    call(self.address, pool.invariant().m.tx.data),
+                    call(self.address, pool.mid_fee().m.tx.data),
+                    call(self.address, pool.out_fee().m.tx.data),
+                    call(self.address, pool.fee_gamma().m.tx.data),
+                ]
+                .into_iter()
+                .chain(balances)
+                .chain(price_scale)
+                .collect()
+            }
+        }
+    }
+
+    /// Decodes the results of the calls returned by [`Self::state_calls`].
+    fn decode_state(&self, results: Vec<Result<Vec<u8>, ExecutionError>>) -> Result<PoolState> {
+        let n = self.tokens.len();
+        let mut results = results.into_iter();
+        let mut next = |kind: ParamType| decode(results.next().context("missing result")?, kind);
+        let uint = || ParamType::Uint(256);
+        let uints = || ParamType::FixedArray(Box::new(uint()), MAX_COINS);
+
+        match self.registry {
+            Registry::Stable(_) => {
+                let balances = into_uints(next(uints())?)?;
+                let rates = into_uints(next(uints())?)?;
+                let amplification = into_uint(next(uint())?)?;
+                let fees = into_uints(next(ParamType::FixedArray(Box::new(uint()), 2))?)?;
+                // Only some lending pools have dynamic fees.
+                let offpeg_fee_multiplier = next(uint()).ok().map(into_uint).transpose()?;
+                Ok(PoolState::Stable(StablePoolState {
+                    balances: balances[..n].to_vec(),
+                    rates: rates[..n]
+                        .iter()
+                        .zip(&self.precisions)
+                        .map(|(rate, precision)| rate.checked_mul(*precision))
+                        .collect::<Option<_>>()
+                        .context("rate overflow")?,
+                    amplification,
+                    fee: fees[0],
+                    offpeg_fee_multiplier,
+                }))
+            }
+            Registry::Crypto(_) => {
+                let amplification = into_uint(next(uint())?)?;
+                let gamma = into_uint(next(uint())?)?;
+                let d = into_uint(next(uint())?)?;
+                let mid_fee = into_uint(next(uint())?)?;
+                let out_fee = into_uint(next(uint())?)?;
+                let fee_gamma = into_uint(next(uint())?)?;
+                let balances = (0..n)
+                    .map(|_| into_uint(next(uint())?))
+                    .collect::<Result<_>>()?;
+                let price_scale = (1..n)
+                    .map(|_| into_uint(next(uint())?))
+                    .collect::<Result<_>>()?;
+                Ok(PoolState::Crypto(CryptoPoolState {
+                    balances,
+                    precisions: self.precisions.clone(),
+                    price_scale,
+                    amplification,
+                    gamma,
+                    d,
+                    mid_fee,
+                    out_fee,
+                    fee_gamma,
+                }))
+            }
+        }
+    }
+}
+
+/// Discovers all pools listed in a registry along with their static
+/// information.
+async fn discover(web3: &Web3, registry: Registry) -> Result<Vec<RegisteredPool>> {
+    // Both registries share the getters used for discovery, except that
+    // StableSwap pools are normalized by the decimals of the underlying coins
+    // of lending pools.
+    let address = registry.address();
+    let contract = CurveCryptoRegistry::at(web3, address);
+    let decimals = |pool| match registry {
+        Registry::Stable(_) => {
+            CurveRegistry::at(web3, address)
+                .get_underlying_decimals(pool)
+                .m
+                .tx
+                .data
+        }
+        Registry::Crypto(_) => contract.get_decimals(pool).m.tx.data,
+    };
+
+    let count = contract.pool_count().call().await?.as_usize();
+    let addresses = multicall(
+        web3,
+        (0..count)
+            .map(|i| call(address, contract.pool_list(i.into()).m.tx.data))
+            .collect(),
+        None,
+    )
+    .await
+    .into_iter()
+    .map(|result| {
+        decode(result, ParamType::Address)?
+            .into_address()
+            .context("expected address")
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+    let calls = addresses
+        .iter()
+        .flat_map(|pool| {
+            [
+                call(address, contract.get_coins(*pool).m.tx.data),
+                call(address, decimals(*pool)),
+            ]
+        })
+        .collect();
+    let mut results = multicall(web3, calls, None).await.into_iter();
+
+    let mut pools = Vec::new();
+    for pool in addresses {
+        let (coins, decimals) = (results.next(), results.next());
+        let coins = decode(
+            coins.context("missing result")?,
+            ParamType::FixedArray(Box::new(ParamType::Address), MAX_COINS),
+        )?;
+        let decimals = decode(
+            decimals.context("missing result")?,
+            ParamType::FixedArray(Box::new(ParamType::Uint(256)), MAX_COINS),
+        )?;
+        match registered_pool(pool, registry, coins, decimals) {
+            Ok(registered) => pools.push(registered),
+            Err(err) => tracing::debug!(?pool, ?err, "ignoring Curve pool"),
+        }
+    }
+    Ok(pools)
+}
+
+fn registered_pool(
+    address: H160,
+    registry: Registry,
+    coins: Token,
+    decimals: Token,
+) -> Result<RegisteredPool> {
+    let tokens = coins
+        .into_fixed_array()
+        .context("expected array")?
+        .into_iter()
+        .map(|coin| coin.into_address().context("expected address"))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .take_while(|coin| !coin.is_zero())
+        .collect::<Vec<_>>();
+    ensure!(tokens.len() >= 2, "pool has less than two coins");
+    ensure!(
+        !tokens.contains(&NATIVE_TOKEN),
+        "pools trading native Ether are not supported"
+    );
+
+    let precisions = into_uints(decimals)?[..tokens.len()]
+        .iter()
+        .map(|decimals| {
+            ensure!(
+                *decimals <= 18.into(),
+                "unsupported coin decimals {decimals}"
+            );
+            Ok(U256::exp10(18 - decimals.as_usize()))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(RegisteredPool {
+        address,
+        registry,
+        tokens,
+        precisions,
+    })
+}
+
+fn call(to: H160, data: Option<Bytes>) -> Call {
+    Call {
+        to,
+        data: data.expect("contract calls have calldata").0,
+        ..Default::default()
+    }
+}
+
+/// Executes the calls in batches of multicalls.
+async fn multicall(
+    web3: &Web3,
+    calls: Vec<Call>,
+    block: Option<BlockId>,
+) -> Vec<Result<Vec<u8>, ExecutionError>> {
+    let eth = web3.eth();
+    let batches = calls
+        .chunks(MULTICALL_BATCH_SIZE)
+        .map(|batch| eth.multicall(batch.to_vec(), Options::default(), block));
+    futures::future::join_all(batches)
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn decode(result: Result<Vec<u8>, ExecutionError>, kind: ParamType) -> Result<Token> {
+    let mut tokens = ethabi::decode(&[kind], &result?)?;
+    tokens.pop().context("missing return value")
+}
+
+fn into_uint(token: Token) -> Result<U256> {
+    token.into_uint().context("expected uint")
+}
+
+fn into_uints(token: Token) -> Result<Vec<U256>> {
+    token
+        .into_fixed_array()
+        .context("expected array")?
+        .into_iter()
+        .map(into_uint)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::sources::curve::stable_math::PRECISION};
+
+    fn pool() -> Pool {
+        Pool {
+            address: H160([0x11; 20]),
+            tokens: vec![H160([1; 20]), H160([2; 20]), H160([3; 20])],
+            state: PoolState::Stable(StablePoolState {
+                balances: vec![U256::exp10(24); 3],
+                rates: vec![PRECISION.into(); 3],
+                amplification: 100.into(),
+                fee: 4_000_000.into(),
+                offpeg_fee_multiplier: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn enumerates_token_pairs() {
+        let pool = pool();
+        let pairs = pool.token_pairs().collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            vec![
+                TokenPair::new(H160([1; 20]), H160([2; 20])).unwrap(),
+                TokenPair::new(H160([1; 20]), H160([3; 20])).unwrap(),
+                TokenPair::new(H160([2; 20]), H160([3; 20])).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn swaps_by_token_address() {
+        let pool = pool();
+        let (a, c) = (H160([1; 20]), H160([3; 20]));
+        let out = pool.get_amount_out(c, (U256::exp10(18), a)).unwrap();
+        assert!(out < U256::exp10(18));
+        let amount_in = pool.get_amount_in(a, (out, c)).unwrap();
+        assert!(pool.get_amount_out(c, (amount_in, a)).unwrap() >= out);
+        assert_eq!(
+            pool.get_amount_out(c, (U256::exp10(18), H160([4; 20]))),
+            None
+        );
+    }
+
+    #[test]
+    fn registers_pools() {
+        let coins = |coins: &[H160]| {
+            Token::FixedArray(
+                (0..MAX_COINS)
+                    .map(|i| Token::Address(coins.get(i).copied().unwrap_or_default()))
+                    .collect(),
+            )
+        };
+        let decimals = |decimals: &[u64]| {
+            Token::FixedArray(
+                (0..MAX_COINS)
+                    .map(|i| Token::Uint(decimals.get(i).copied().unwrap_or_default().into()))
+                    .collect(),
+            )
+        };
+        let registry = Registry::Stable(H160([0x22; 20]));
+
+        let pool = registered_pool(
+            H160([0x11; 20]),
+            registry,
+            coins(&[H160([1; 20]), H160([2; 20]), H160([3; 20])]),
+            decimals(&[18, 6, 6]),
+        )
+        .unwrap();
+        assert_eq!(pool.tokens.len(), 3);
+        assert_eq!(
+            pool.precisions,
+            vec![U256::one(), U256::exp10(12), U256::exp10(12)]
+        );
+
+        // Pools with native Ether are not supported.
+        assert!(
+            registered_pool(
+                H160([0x11; 20]),
+                registry,
+                coins(&[NATIVE_TOKEN, H160([2; 20])]),
+                decimals(&[18, 18]),
+            )
+            .is_err()
+        );
+    }
+}
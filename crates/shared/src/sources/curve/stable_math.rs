@@ -0,0 +1,282 @@
+//! Module emulating the swap math of the Curve StableSwap pools used by plain
+//! and lending pools. The original contract code can be found at:
+//! https://github.com/curvefi/curve-contract/blob/b0bbf77f8f93c9c5f4e415bce9cd71f0cdee960e/contracts/pools/3pool/StableSwap3Pool.vy
+//! https://github.com/curvefi/curve-contract/blob/b0bbf77f8f93c9c5f4e415bce9cd71f0cdee960e/contracts/pools/aave/StableSwapAave.vy
+
+use {
+    super::{abs_diff, ceil_div},
+    ethcontract::U256,
+};
+
+/// The denominator of pool fees.
+pub const FEE_DENOMINATOR: u64 = 10_000_000_000;
+
+/// The precision of the rates used to normalize pool balances.
+pub const PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// The maximum number of iterations of the invariant and balance
+/// approximations.
+const MAX_ITERATIONS: usize = 255;
+
+/// The state of a StableSwap pool required for computing swaps.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StablePoolState {
+    /// The balances of the pool's coins, in coin index order.
+    pub balances: Vec<U256>,
+    /// The rates normalizing each coin's balance to 18 decimals, scaled by
+    /// [`PRECISION`]. For lending pools these include the exchange rate of
+    /// the wrapped coin to its underlying.
+    pub rates: Vec<U256>,
+    /// The amplification coefficient.
+    pub amplification: U256,
+    /// The swap fee, relative to [`FEE_DENOMINATOR`].
+    pub fee: U256,
+    /// The multiplier increasing the fee of swaps when the pool is imbalanced.
+    /// Only some lending pools implement dynamic fees.
+    pub offpeg_fee_multiplier: Option<U256>,
+}
+
+impl StablePoolState {
+    /// Returns the amount of coin `j` received for `dx` of coin `i`, computed
+    /// exactly like the pool's `exchange` function.
+    pub fn get_dy(&self, i: usize, j: usize, dx: U256) -> Option<U256> {
+        let xp = self.xp()?;
+        let x = xp
+            .get(i)?
+            .checked_add(dx.checked_mul(self.rates[i])? / PRECISION)?;
+        let y = get_y(i, j, x, &xp, self.amplification)?;
+        let dy = xp.get(j)?.checked_sub(y)?.checked_sub(1.into())?;
+        let fee = self.dynamic_fee((xp[i] + x) / 2, (xp[j] + y) / 2)?;
+        let dy_fee = dy.checked_mul(fee)? / FEE_DENOMINATOR;
+        let dy = dy
+            .checked_sub(dy_fee)?
+            .checked_mul(PRECISION.into())?
+            .checked_div(self.rates[j])?;
+        (dy < self.balances[j]).then_some(dy)
+    }
+
+    /// Returns the amount of coin `i` that needs to be sold in order to
+    /// receive at least `dy` of coin `j`.
+    ///
+    /// Pools don't implement this computation, so the amount is estimated by
+    /// inverting the swap math with the static fee and then refined with
+    /// [`Self::get_dy`].
+    pub fn get_dx(&self, i: usize, j: usize, dy: U256) -> Option<U256> {
+        let xp = self.xp()?;
+        let (xp_i, xp_j) = (*xp.get(i)?, *xp.get(j)?);
+        let fee_denominator = U256::from(FEE_DENOMINATOR);
+
+        let dy_xp = ceil_div(dy.checked_mul(self.rates[j])?, PRECISION.into())?;
+        let dy_xp = ceil_div(
+            dy_xp.checked_mul(fee_denominator)?,
+            fee_denominator.checked_sub(self.fee)?,
+        )?;
+        let y = xp_j.checked_sub(dy_xp)?.checked_sub(1.into())?;
+        let x = get_y(j, i, y, &xp, self.amplification)?;
+        let dx = ceil_div(
+            x.checked_sub(xp_i)?.checked_mul(PRECISION.into())?,
+            self.rates[i],
+        )?;
+
+        super::refine_amount_in(dx.checked_add(1.into())?, dy, |dx| self.get_dy(i, j, dx))
+    }
+
+    /// Returns the balances normalized to 18 decimals.
+    fn xp(&self) -> Option<Vec<U256>> {
+        if self.balances.len() != self.rates.len() {
+            return None;
+        }
+        self.balances
+            .iter()
+            .zip(&self.rates)
+            .map(|(balance, rate)| Some(balance.checked_mul(*rate)? / PRECISION))
+            .collect()
+    }
+
+    /// Returns the fee of a swap given the average of the normalized balances
+    /// before and after the swap.
+    fn dynamic_fee(&self, xpi: U256, xpj: U256) -> Option<U256> {
+        let fee_denominator = U256::from(FEE_DENOMINATOR);
+        let multiplier = match self.offpeg_fee_multiplier {
+            Some(multiplier) if multiplier > fee_denominator => multiplier,
+            _ => return Some(self.fee),
+        };
+        let xps = xpi.checked_add(xpj)?;
+        let xps2 = xps.checked_mul(xps)?;
+        let imbalance = (multiplier - fee_denominator)
+            .checked_mul(4.into())?
+            .checked_mul(xpi)?
+            .checked_mul(xpj)?
+            .checked_div(xps2)?;
+        multiplier
+            .checked_mul(self.fee)?
+            .checked_div(imbalance.checked_add(fee_denominator)?)
+    }
+}
+
+/// Computes the StableSwap invariant `D` for the normalized balances.
+fn get_d(xp: &[U256], amplification: U256) -> Option<U256> {
+    let n = U256::from(xp.len());
+    let s = xp
+        .iter()
+        .try_fold(U256::zero(), |sum, x| sum.checked_add(*x))?;
+    if s.is_zero() {
+        return Some(s);
+    }
+
+    let mut d = s;
+    let ann = amplification.checked_mul(n)?;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for x in xp {
+            d_p = d_p.checked_mul(d)?.checked_div(x.checked_mul(n)?)?;
+        }
+        let d_prev = d;
+        let numerator = ann
+            .checked_mul(s)?
+            .checked_add(d_p.checked_mul(n)?)?
+            .checked_mul(d)?;
+        let denominator = ann
+            .checked_sub(1.into())?
+            .checked_mul(d)?
+            .checked_add(n.checked_add(1.into())?.checked_mul(d_p)?)?;
+        d = numerator.checked_div(denominator)?;
+        if abs_diff(d, d_prev) <= 1.into() {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// Computes the normalized balance of coin `j` such that the invariant holds
+/// when the normalized balance of coin `i` is `x`.
+fn get_y(i: usize, j: usize, x: U256, xp: &[U256], amplification: U256) -> Option<U256> {
+    if i == j || i >= xp.len() || j >= xp.len() {
+        return None;
+    }
+
+    let n = U256::from(xp.len());
+    let d = get_d(xp, amplification)?;
+    let ann = amplification.checked_mul(n)?;
+    let mut c = d;
+    let mut s = U256::zero();
+    for (k, xp_k) in xp.iter().enumerate() {
+        let x_k = if k == i {
+            x
+        } else if k != j {
+            *xp_k
+        } else {
+            continue;
+        };
+        s = s.checked_add(x_k)?;
+        c = c.checked_mul(d)?.checked_div(x_k.checked_mul(n)?)?;
+    }
+    c = c.checked_mul(d)?.checked_div(ann.checked_mul(n)?)?;
+    let b = s.checked_add(d.checked_div(ann)?)?;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        y = y
+            .checked_mul(y)?
+            .checked_add(c)?
+            .checked_div(y.checked_mul(2.into())?.checked_add(b)?.checked_sub(d)?)?;
+        if abs_diff(y, y_prev) <= 1.into() {
+            return Some(y);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u(value: u128) -> U256 {
+        value.into()
+    }
+
+    fn e(value: u128, decimals: u32) -> U256 {
+        U256::from(value) * U256::exp10(decimals as _)
+    }
+
+    /// A 3pool-like pool of DAI, USDC and USDT.
+    fn three_pool() -> StablePoolState {
+        StablePoolState {
+            balances: vec![e(100_000_000, 18), e(120_000_000, 6), e(80_000_000, 6)],
+            rates: vec![e(1, 18), e(1, 30), e(1, 30)],
+            amplification: u(2_000),
+            fee: u(1_000_000),
+            offpeg_fee_multiplier: None,
+        }
+    }
+
+    #[test]
+    fn invariant() {
+        let pool = three_pool();
+        assert_eq!(
+            get_d(&pool.xp().unwrap(), pool.amplification),
+            Some(U256::from_dec_str("299997917765608652100285788").unwrap()),
+        );
+        assert_eq!(
+            get_d(&[U256::zero(); 3], pool.amplification),
+            Some(U256::zero())
+        );
+    }
+
+    #[test]
+    fn amount_out() {
+        let pool = three_pool();
+        assert_eq!(
+            pool.get_dy(0, 1, e(1_000_000, 18)),
+            Some(u(999_982_288_437))
+        );
+        assert_eq!(
+            pool.get_dy(2, 0, e(1_000_000, 6)),
+            Some(U256::from_dec_str("1000023324339199302880457").unwrap()),
+        );
+        // Swapping more than the pool balance is not possible.
+        assert_eq!(pool.get_dy(0, 1, e(1_000_000_000_000, 18)), None);
+        assert_eq!(pool.get_dy(0, 0, e(1, 18)), None);
+        assert_eq!(pool.get_dy(0, 3, e(1, 18)), None);
+    }
+
+    #[test]
+    fn amount_in() {
+        let pool = three_pool();
+        for (i, j, dy, dx) in [
+            (
+                0,
+                1,
+                e(1_000_000, 6),
+                U256::from_dec_str("1000017711955244392303544").unwrap(),
+            ),
+            (1, 2, e(5_000_000, 6), u(5_001_749_624_406)),
+            (2, 0, e(42, 18), u(41_998_736)),
+        ] {
+            assert_eq!(pool.get_dx(i, j, dy), Some(dx));
+            assert!(pool.get_dy(i, j, dx).unwrap() >= dy);
+        }
+    }
+
+    #[test]
+    fn dynamic_fee() {
+        let pool = StablePoolState {
+            offpeg_fee_multiplier: Some(u(20_000_000_000)),
+            ..three_pool()
+        };
+        // The dynamic fee is the regular fee for balanced pools and increases
+        // with the imbalance of the pool.
+        assert_eq!(pool.dynamic_fee(e(1, 18), e(1, 18)), Some(pool.fee));
+        assert_eq!(pool.dynamic_fee(e(3, 18), e(1, 18)), Some(u(1_142_857)));
+
+        // Swaps in imbalanced pools are charged more fees.
+        let dy = u(999_981_914_106);
+        assert_eq!(pool.get_dy(0, 1, e(1_000_000, 18)), Some(dy));
+        assert!(dy < three_pool().get_dy(0, 1, e(1_000_000, 18)).unwrap());
+        assert_eq!(
+            pool.get_dx(0, 1, dy),
+            Some(U256::from_dec_str("1000000000000112115861302").unwrap()),
+        );
+    }
+}
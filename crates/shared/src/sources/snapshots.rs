@@ -0,0 +1,316 @@
+//! Persistent snapshots of the indexed state of liquidity sources.
+//!
+//! Indexing liquidity sources from scratch (fetching the pools from a subgraph
+//! and then their state from the node) makes restarts slow and RPC heavy. So
+//! sources periodically write a snapshot of their state at a reorg safe block
+//! and, on startup, resume from the snapshot by only replaying the events that
+//! were emitted after it.
+
+use {
+    anyhow::{Context, Result},
+    ethcontract::H256,
+    ethrpc::block_stream::BlockRetrieving,
+    serde::{Deserialize, Serialize, de::DeserializeOwned},
+    std::{
+        path::PathBuf,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+};
+
+/// Persistent storage for liquidity source snapshots. Only the most recent
+/// snapshot of every source is kept.
+#[async_trait::async_trait]
+pub trait SnapshotStoring: Send + Sync + 'static {
+    /// Loads the serialized snapshot of the specified source.
+    async fn load(&self, source: &str) -> Result<Option<Vec<u8>>>;
+    /// Replaces the snapshot of the specified source.
+    async fn save(&self, source: &str, block_number: u64, data: Vec<u8>) -> Result<()>;
+}
+
+/// Stores snapshots as JSON files in a local directory.
+#[derive(Clone, Debug)]
+pub struct FileSnapshotStore {
+    directory: PathBuf,
+}
+
+impl FileSnapshotStore {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    fn path(&self, source: &str) -> PathBuf {
+        self.directory.join(format!("{source}.json"))
+    }
+}
+
+#[async_trait::async_trait]
+impl SnapshotStoring for FileSnapshotStore {
+    async fn load(&self, source: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(source)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).context("failed to read snapshot file"),
+        }
+    }
+
+    async fn save(&self, source: &str, _: u64, data: Vec<u8>) -> Result<()> {
+        tokio::fs::create_dir_all(&self.directory)
+            .await
+            .context("failed to create snapshot directory")?;
+
+        // Write to a temporary file first and then move it in place so that a
+        // crash while writing never leaves a truncated snapshot behind.
+        let path = self.path(source);
+        let temporary = path.with_extension("json.tmp");
+        tokio::fs::write(&temporary, data)
+            .await
+            .context("failed to write snapshot file")?;
+        tokio::fs::rename(&temporary, &path)
+            .await
+            .context("failed to move snapshot file in place")?;
+        Ok(())
+    }
+}
+
+/// The state of a liquidity source at a specific block.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot<T> {
+    pub block_number: u64,
+    /// The hash of the block, used to detect that the snapshot was taken on
+    /// a block that got reorged out.
+    pub block_hash: H256,
+    pub state: T,
+}
+
+/// Snapshot configuration shared by all liquidity sources.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// How often a liquidity source writes a new snapshot.
+    pub interval: Duration,
+    /// Snapshots older than this number of blocks are discarded on startup
+    /// since replaying all the events since then would not be any faster
+    /// than indexing from scratch.
+    pub max_age: u64,
+}
+
+/// Loads and periodically saves the snapshots of a single liquidity source.
+pub struct Snapshots {
+    store: Arc<dyn SnapshotStoring>,
+    source: String,
+    config: Config,
+    last_saved: Mutex<Option<Instant>>,
+}
+
+impl Snapshots {
+    pub fn new(store: Arc<dyn SnapshotStoring>, source: String, config: Config) -> Self {
+        Self {
+            store,
+            source,
+            config,
+            last_saved: Mutex::new(None),
+        }
+    }
+
+    /// Loads the snapshot of the liquidity source. Returns `None` if there is
+    /// no snapshot or it can't be used because it is too old or its block is
+    /// no longer part of the canonical chain.
+    pub async fn load<T: DeserializeOwned>(
+        &self,
+        blocks: &dyn BlockRetrieving,
+    ) -> Option<Snapshot<T>> {
+        self.load_with_max_age(blocks, Some(self.config.max_age))
+            .await
+    }
+
+    /// Loads the snapshot of the liquidity source regardless of its age. This
+    /// is useful for snapshots that record indexing progress where resuming
+    /// is always faster than starting over.
+    pub async fn load_any_age<T: DeserializeOwned>(
+        &self,
+        blocks: &dyn BlockRetrieving,
+    ) -> Option<Snapshot<T>> {
+        self.load_with_max_age(blocks, None).await
+    }
+
+    async fn load_with_max_age<T: DeserializeOwned>(
+        &self,
+        blocks: &dyn BlockRetrieving,
+        max_age: Option<u64>,
+    ) -> Option<Snapshot<T>> {
+        match self.try_load(blocks, max_age).await {
+            Ok(Some(snapshot)) => {
+                tracing::info!(
+                    source = %self.source,
+                    block = snapshot.block_number,
+                    "resuming from liquidity snapshot"
+                );
+                Some(snapshot)
+            }
+            Ok(None) => None,
+            Err(err) => {
+                tracing::warn!(source = %self.source, ?err, "discarding liquidity snapshot");
+                None
+            }
+        }
+    }
+
+    async fn try_load<T: DeserializeOwned>(
+        &self,
+        blocks: &dyn BlockRetrieving,
+        max_age: Option<u64>,
+    ) -> Result<Option<Snapshot<T>>> {
+        let Some(data) = self.store.load(&self.source).await? else {
+            tracing::debug!(source = %self.source, "no liquidity snapshot");
+            return Ok(None);
+        };
+        let snapshot: Snapshot<T> = serde_json::from_slice(&data).context("invalid snapshot")?;
+
+        if let Some(max_age) = max_age {
+            let current_block = blocks.current_block().await?.number;
+            let age = current_block.saturating_sub(snapshot.block_number);
+            anyhow::ensure!(
+                age <= max_age,
+                "snapshot is {age} blocks old, the maximum age is {max_age}"
+            );
+        }
+
+        let (_, hash) = blocks.block(snapshot.block_number).await?;
+        anyhow::ensure!(
+            hash == snapshot.block_hash,
+            "snapshot block {} was reorged",
+            snapshot.block_number
+        );
+
+        Ok(Some(snapshot))
+    }
+
+    /// Returns whether the configured interval passed since the last saved
+    /// snapshot.
+    pub fn is_due(&self) -> bool {
+        self.last_saved
+            .lock()
+            .unwrap()
+            .is_none_or(|last_saved| last_saved.elapsed() >= self.config.interval)
+    }
+
+    /// Saves a new snapshot of the liquidity source. The snapshot block must
+    /// be reorg safe. Failures are only logged since snapshots are purely an
+    /// optimization.
+    pub async fn save<T: Serialize>(&self, snapshot: &Snapshot<T>) {
+        *self.last_saved.lock().unwrap() = Some(Instant::now());
+
+        let start = Instant::now();
+        let result = async {
+            let data = serde_json::to_vec(snapshot).context("failed to serialize snapshot")?;
+            self.store
+                .save(&self.source, snapshot.block_number, data)
+                .await
+        }
+        .await;
+        match result {
+            Ok(()) => tracing::debug!(
+                source = %self.source,
+                block = snapshot.block_number,
+                time = ?start.elapsed(),
+                "saved liquidity snapshot"
+            ),
+            Err(err) => {
+                tracing::warn!(source = %self.source, ?err, "failed to save liquidity snapshot")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        ethrpc::block_stream::{BlockInfo, BlockNumberHash, RangeInclusive},
+    };
+
+    #[derive(Debug)]
+    struct Chain(u64);
+
+    #[async_trait::async_trait]
+    impl BlockRetrieving for Chain {
+        async fn current_block(&self) -> Result<BlockInfo> {
+            Ok(BlockInfo {
+                number: self.0,
+                hash: H256::from_low_u64_be(self.0),
+                parent_hash: H256::from_low_u64_be(self.0 - 1),
+                timestamp: 0,
+                gas_limit: Default::default(),
+                gas_price: Default::default(),
+                observed_at: Instant::now(),
+            })
+        }
+
+        async fn block(&self, number: u64) -> Result<BlockNumberHash> {
+            Ok((number, H256::from_low_u64_be(number)))
+        }
+
+        async fn blocks(&self, _: RangeInclusive<u64>) -> Result<Vec<BlockNumberHash>> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn file_store_roundtrip() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = FileSnapshotStore::new(directory.path().join("snapshots"));
+
+        assert_eq!(store.load("uniswap-v3").await.unwrap(), None);
+        store.save("uniswap-v3", 1, vec![1, 2, 3]).await.unwrap();
+        store.save("uniswap-v3", 2, vec![4, 5]).await.unwrap();
+        assert_eq!(store.load("uniswap-v3").await.unwrap(), Some(vec![4, 5]));
+        assert_eq!(store.load("balancer-v2").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn validates_loaded_snapshots() {
+        let directory = tempfile::tempdir().unwrap();
+        let snapshots = Snapshots::new(
+            Arc::new(FileSnapshotStore::new(directory.path().to_owned())),
+            "test".to_string(),
+            Config {
+                interval: Duration::from_secs(60),
+                max_age: 100,
+            },
+        );
+        assert_eq!(snapshots.load::<u64>(&Chain(1000)).await, None);
+
+        assert!(snapshots.is_due());
+        snapshots
+            .save(&Snapshot {
+                block_number: 950,
+                block_hash: H256::from_low_u64_be(950),
+                state: 42_u64,
+            })
+            .await;
+        assert!(!snapshots.is_due());
+        assert_eq!(
+            snapshots.load(&Chain(1000)).await,
+            Some(Snapshot {
+                block_number: 950,
+                block_hash: H256::from_low_u64_be(950),
+                state: 42_u64,
+            })
+        );
+
+        // too old
+        assert_eq!(snapshots.load::<u64>(&Chain(1051)).await, None);
+        assert!(snapshots.load_any_age::<u64>(&Chain(1051)).await.is_some());
+
+        // reorged
+        snapshots
+            .save(&Snapshot {
+                block_number: 950,
+                block_hash: H256::from_low_u64_be(1),
+                state: 42_u64,
+            })
+            .await;
+        assert_eq!(snapshots.load::<u64>(&Chain(1000)).await, None);
+    }
+}
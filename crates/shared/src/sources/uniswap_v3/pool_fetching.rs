@@ -7,6 +7,7 @@ use {
         event_handling::{EventHandler, EventStoring, MAX_REORG_BLOCK_COUNT},
         maintenance::Maintaining,
         recent_block_cache::Block,
+        sources::snapshots::{Snapshot, Snapshots},
     },
     anyhow::{Context, Result},
     ethcontract::{Event, H160, U256},
@@ -19,7 +20,7 @@ use {
     num::{BigInt, Zero, rational::Ratio},
     number::serialization::HexOrDecimalU256,
     reqwest::{Client, Url},
-    serde::Serialize,
+    serde::{Deserialize, Serialize},
     serde_with::{DisplayFromStr, serde_as},
     std::{
         collections::{BTreeMap, HashMap, HashSet},
@@ -107,6 +108,70 @@ impl TryFrom<PoolData> for PoolInfo {
     }
 }
 
+/// Pool data in the format it is persisted in liquidity snapshots.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PoolSnapshot {
+    address: H160,
+    tokens: Vec<Token>,
+    #[serde_as(as = "HexOrDecimalU256")]
+    sqrt_price: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    liquidity: U256,
+    #[serde_as(as = "DisplayFromStr")]
+    tick: BigInt,
+    #[serde_as(as = "BTreeMap<DisplayFromStr, DisplayFromStr>")]
+    liquidity_net: BTreeMap<BigInt, BigInt>,
+    /// Numerator and denominator of the fee.
+    fee: (u32, u32),
+    #[serde_as(as = "HexOrDecimalU256")]
+    mean_gas: U256,
+}
+
+impl From<&PoolInfo> for PoolSnapshot {
+    fn from(pool: &PoolInfo) -> Self {
+        Self {
+            address: pool.address,
+            tokens: pool.tokens.clone(),
+            sqrt_price: pool.state.sqrt_price,
+            liquidity: pool.state.liquidity,
+            tick: pool.state.tick.clone(),
+            liquidity_net: pool.state.liquidity_net.clone(),
+            fee: (*pool.state.fee.numer(), *pool.state.fee.denom()),
+            mean_gas: pool.gas_stats.mean_gas,
+        }
+    }
+}
+
+impl From<PoolSnapshot> for PoolInfo {
+    fn from(pool: PoolSnapshot) -> Self {
+        Self {
+            address: pool.address,
+            tokens: pool.tokens,
+            state: PoolState {
+                sqrt_price: pool.sqrt_price,
+                liquidity: pool.liquidity,
+                tick: pool.tick,
+                liquidity_net: pool.liquidity_net,
+                fee: Ratio::new_raw(pool.fee.0, pool.fee.1),
+            },
+            gas_stats: PoolStats {
+                mean_gas: pool.mean_gas,
+            },
+        }
+    }
+}
+
+/// The checkpoint in the format it is persisted in liquidity snapshots. The
+/// registered pools are not part of it since they are cheap to fetch and pools
+/// created after the snapshot need to be known.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckpointSnapshot {
+    pools: Vec<PoolSnapshot>,
+}
+
 #[derive(Default)]
 struct PoolsCheckpoint {
     /// Pools state.
@@ -129,11 +194,13 @@ struct PoolsCheckpointHandler {
 impl PoolsCheckpointHandler {
     /// Fetches the list of existing UniswapV3 pools and their metadata (without
     /// state/ticks). Then fetches state/ticks for the most deepest pools
-    /// (subset of all existing pools)
+    /// (subset of all existing pools), or restores them from the snapshot if
+    /// there is one.
     pub async fn new(
         subgraph_url: &Url,
         client: Client,
         max_pools_to_initialize_cache: usize,
+        snapshot: Option<Snapshot<CheckpointSnapshot>>,
     ) -> Result<Self> {
         let graph_api = UniV3SubgraphClient::from_subgraph_url(subgraph_url, client)?;
         let mut registered_pools = graph_api.get_registered_pools().await?;
@@ -149,6 +216,30 @@ impl PoolsCheckpointHandler {
             pools_by_token_pair.entry(pair).or_default().insert(pool.id);
         }
 
+        if let Some(snapshot) = snapshot {
+            // Pools created after the snapshot are fetched like any other
+            // missing pool once they are needed.
+            let pools = snapshot
+                .state
+                .pools
+                .into_iter()
+                .map(|pool| (pool.address, pool.into()))
+                .collect::<HashMap<_, _>>();
+            tracing::debug!(
+                block = %snapshot.block_number, pools = %pools.len(),
+                "initialized pools from snapshot",
+            );
+            return Ok(Self {
+                graph_api,
+                pools_by_token_pair,
+                pools_checkpoint: Mutex::new(PoolsCheckpoint {
+                    pools,
+                    block_number: snapshot.block_number,
+                    ..Default::default()
+                }),
+            });
+        }
+
         // can't fetch the state of all pools in constructor for performance reasons,
         // so let's fetch the top `max_pools_to_initialize_cache` pools with the highest
         // liquidity
@@ -216,6 +307,18 @@ impl PoolsCheckpointHandler {
         }
     }
 
+    /// Returns the state of all pools in the checkpoint together with the
+    /// block it is for.
+    fn snapshot(&self) -> (CheckpointSnapshot, u64) {
+        let checkpoint = self.pools_checkpoint.lock().unwrap();
+        (
+            CheckpointSnapshot {
+                pools: checkpoint.pools.values().map(PoolSnapshot::from).collect(),
+            },
+            checkpoint.block_number,
+        )
+    }
+
     /// Fetches state/ticks for missing pools and moves them from
     /// `missing_pools` to `pools`
     async fn update_missing_pools(&self) -> Result<()> {
@@ -264,6 +367,9 @@ pub struct UniswapV3PoolFetcher {
     /// Recent events used on top of pools_checkpoint to get the `latest_block`
     /// pools state.
     events: tokio::sync::Mutex<EventHandler<UniswapV3PoolEventFetcher, RecentEventsCache>>,
+    block_retriever: Arc<dyn BlockRetrieving>,
+    /// Periodically persisted checkpoints to speed up restarts.
+    snapshots: Option<Arc<Snapshots>>,
 }
 
 impl UniswapV3PoolFetcher {
@@ -273,22 +379,52 @@ impl UniswapV3PoolFetcher {
         client: Client,
         block_retriever: Arc<dyn BlockRetrieving>,
         max_pools_to_initialize: usize,
+        snapshots: Option<Arc<Snapshots>>,
     ) -> Result<Self> {
         let web3 = ethrpc::instrumented::instrument_with_label(&web3, "uniswapV3".into());
+        let snapshot = match &snapshots {
+            Some(snapshots) => snapshots.load(block_retriever.as_ref()).await,
+            None => None,
+        };
         let checkpoint =
-            PoolsCheckpointHandler::new(subgraph_url, client, max_pools_to_initialize).await?;
+            PoolsCheckpointHandler::new(subgraph_url, client, max_pools_to_initialize, snapshot)
+                .await?;
 
         let init_block = checkpoint.pools_checkpoint.lock().unwrap().block_number;
         let init_block = block_retriever.block(init_block).await?;
 
         let events = tokio::sync::Mutex::new(EventHandler::new(
-            block_retriever,
+            block_retriever.clone(),
             UniswapV3PoolEventFetcher(web3),
             RecentEventsCache::default(),
             Some(init_block),
         ));
 
-        Ok(Self { checkpoint, events })
+        Ok(Self {
+            checkpoint,
+            events,
+            block_retriever,
+            snapshots,
+        })
+    }
+
+    /// Persists the checkpoint if a new snapshot is due. The checkpoint is
+    /// always `MAX_REORG_BLOCK_COUNT` blocks behind the chain head, so it is
+    /// considered reorg safe.
+    async fn save_snapshot(&self) -> Result<()> {
+        let Some(snapshots) = self.snapshots.as_ref().filter(|s| s.is_due()) else {
+            return Ok(());
+        };
+        let (state, block_number) = self.checkpoint.snapshot();
+        let (_, block_hash) = self.block_retriever.block(block_number).await?;
+        snapshots
+            .save(&Snapshot {
+                block_number,
+                block_hash,
+                state,
+            })
+            .await;
+        Ok(())
     }
 
     /// Moves the checkpoint to the block `latest_block - MAX_REORG_BLOCK_COUNT`
@@ -484,7 +620,8 @@ impl Maintaining for UniswapV3PoolFetcher {
                 err
             );
         }
-        self.move_checkpoint_to_future().await
+        self.move_checkpoint_to_future().await?;
+        self.save_snapshot().await
     }
 
     fn name(&self) -> &str {
@@ -574,6 +711,91 @@ mod tests {
         assert_json_matches!(json, serialized);
     }
 
+    #[test]
+    fn snapshot_roundtrip() {
+        let pool = PoolInfo {
+            address: H160::from_low_u64_be(1),
+            tokens: vec![
+                Token {
+                    id: H160::from_low_u64_be(2),
+                    decimals: 18,
+                },
+                Token {
+                    id: H160::from_low_u64_be(3),
+                    decimals: 6,
+                },
+            ],
+            state: PoolState {
+                sqrt_price: U256::from_dec_str("792216481398733702759960397").unwrap(),
+                liquidity: U256::from(1_000),
+                tick: BigInt::from(-92110),
+                liquidity_net: BTreeMap::from([
+                    (BigInt::from(-92160), BigInt::from(1_000)),
+                    (BigInt::from(-92100), BigInt::from(-1_000)),
+                ]),
+                fee: Ratio::new(3_000, 1_000_000),
+            },
+            gas_stats: PoolStats {
+                mean_gas: U256::from(108_163),
+            },
+        };
+
+        let json = serde_json::to_vec(&PoolSnapshot::from(&pool)).unwrap();
+        let snapshot: PoolSnapshot = serde_json::from_slice(&json).unwrap();
+        assert_eq!(PoolInfo::from(snapshot), pool);
+    }
+
+    #[test]
+    fn checkpoint_snapshot_roundtrip() {
+        let pool = PoolInfo {
+            address: H160::from_low_u64_be(1),
+            tokens: vec![
+                Token {
+                    id: H160::from_low_u64_be(10),
+                    decimals: 18,
+                },
+                Token {
+                    id: H160::from_low_u64_be(11),
+                    decimals: 18,
+                },
+            ],
+            state: PoolState {
+                fee: Ratio::new(500, 1_000_000),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let checkpoint = PoolsCheckpointHandler {
+            graph_api: UniV3SubgraphClient::from_subgraph_url(
+                &Url::parse("http://localhost/").unwrap(),
+                Client::new(),
+            )
+            .unwrap(),
+            pools_by_token_pair: HashMap::from([(
+                TokenPair::new(H160::from_low_u64_be(10), H160::from_low_u64_be(11)).unwrap(),
+                HashSet::from([H160::from_low_u64_be(1), H160::from_low_u64_be(2)]),
+            )]),
+            pools_checkpoint: Mutex::new(PoolsCheckpoint {
+                pools: HashMap::from([(pool.address, pool.clone())]),
+                block_number: 42,
+                ..Default::default()
+            }),
+        };
+
+        let (snapshot, block_number) = checkpoint.snapshot();
+        let json = serde_json::to_vec(&snapshot).unwrap();
+        let snapshot: CheckpointSnapshot = serde_json::from_slice(&json).unwrap();
+        assert_eq!(block_number, 42);
+        assert_eq!(
+            snapshot
+                .pools
+                .into_iter()
+                .map(PoolInfo::from)
+                .collect::<Vec<_>>(),
+            vec![pool],
+        );
+    }
+
     #[test]
     fn append_events_test_empty() {
         let pools = HashMap::from([(H160::from_low_u64_be(1), Default::default())]);
@@ -0,0 +1,215 @@
+use {
+    crate::event_handling::{EventRetrieving, EventStoring},
+    anyhow::{Context, Result},
+    contracts::{
+        UniswapV4PoolManager,
+        uniswap_v4_pool_manager::event_data::{Initialize, ModifyLiquidity, Swap},
+    },
+    ethcontract::{
+        Event,
+        H160,
+        H256,
+        RawLog,
+        common::abi::Error,
+        contract::ParseLog,
+        dyns::DynAllEventsBuilder,
+        errors::ExecutionError,
+    },
+    ethrpc::{Web3, block_stream::RangeInclusive},
+    hex_literal::hex,
+    std::collections::BTreeMap,
+};
+
+const INITIALIZE_TOPIC: [u8; 32] =
+    hex!("dd466e674ea557f56295e2d0218a125ea4b4f0f6f3307b95f85e6110838d6438");
+const MODIFY_LIQUIDITY_TOPIC: [u8; 32] =
+    hex!("f208f4912782fd25c7f114ca3723a2d5dd6f3bcc3ac8db5af63baa85f711d5ec");
+const SWAP_TOPIC: [u8; 32] =
+    hex!("40e9cecb9f5f1f1c5b9c97dec2917b7ee92e57ba5563708daca94dd84ad7112f");
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UniswapV4Event {
+    Initialize(Initialize),
+    ModifyLiquidity(ModifyLiquidity),
+    Swap(Swap),
+}
+
+impl UniswapV4Event {
+    /// The ID of the pool the event was emitted for.
+    pub fn pool_id(&self) -> H256 {
+        match self {
+            UniswapV4Event::Initialize(initialize) => H256(initialize.id.0),
+            UniswapV4Event::ModifyLiquidity(modify) => H256(modify.id.0),
+            UniswapV4Event::Swap(swap) => H256(swap.id.0),
+        }
+    }
+}
+
+impl ParseLog for UniswapV4Event {
+    fn parse_log(log: RawLog) -> Result<Self, ExecutionError> {
+        let event = |name| {
+            UniswapV4PoolManager::raw_contract()
+                .interface
+                .abi
+                .event(name)
+                .expect("generated event decode")
+        };
+        match log.topics.first().copied() {
+            Some(H256(INITIALIZE_TOPIC)) => {
+                Ok(UniswapV4Event::Initialize(log.decode(event("Initialize"))?))
+            }
+            Some(H256(MODIFY_LIQUIDITY_TOPIC)) => Ok(UniswapV4Event::ModifyLiquidity(
+                log.decode(event("ModifyLiquidity"))?,
+            )),
+            Some(H256(SWAP_TOPIC)) => Ok(UniswapV4Event::Swap(log.decode(event("Swap"))?)),
+            _ => Err(ExecutionError::from(Error::InvalidData)),
+        }
+    }
+}
+
+/// Retrieves the pool events of the singleton Uniswap V4 pool manager.
+pub struct UniswapV4PoolEventFetcher {
+    pub web3: Web3,
+    pub pool_manager: H160,
+}
+
+impl EventRetrieving for UniswapV4PoolEventFetcher {
+    type Event = UniswapV4Event;
+
+    fn get_events(&self) -> DynAllEventsBuilder<Self::Event> {
+        let mut events = DynAllEventsBuilder::new(self.web3.clone(), self.pool_manager, None);
+        let events_signatures = vec![
+            H256(INITIALIZE_TOPIC),
+            H256(MODIFY_LIQUIDITY_TOPIC),
+            H256(SWAP_TOPIC),
+        ];
+        events.filter = events.filter.topic0(events_signatures.into());
+        events
+    }
+}
+
+/// In-memory storage of the pool manager events that are not yet part of the
+/// reorg safe pools checkpoint.
+#[derive(Debug, Default)]
+pub struct RecentEventsCache {
+    /// (block number, event log index) used as a Key
+    events: BTreeMap<(u64, usize), Event<UniswapV4Event>>,
+}
+
+impl RecentEventsCache {
+    /// Removes all events up to the specified block, excluding the specified
+    /// block.
+    pub fn remove_events_older_than_block(&mut self, delete_up_to_block_number: u64) {
+        self.events = self.events.split_off(&(delete_up_to_block_number, 0));
+    }
+
+    /// Removes all events from the specified block, including specified block.
+    fn remove_events_newer_than_block(&mut self, delete_from_block_number: u64) {
+        self.events.split_off(&(delete_from_block_number, 0));
+    }
+
+    pub fn get_events(&self, block_range: RangeInclusive<u64>) -> Vec<Event<UniswapV4Event>> {
+        self.events
+            .range((*block_range.start(), 0)..=(*block_range.end(), usize::MAX))
+            .map(|(_, event)| event)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl EventStoring<UniswapV4Event> for RecentEventsCache {
+    async fn replace_events(
+        &mut self,
+        events: Vec<Event<UniswapV4Event>>,
+        range: RangeInclusive<u64>,
+    ) -> Result<()> {
+        self.remove_events_newer_than_block(*range.start());
+        self.append_events(events).await
+    }
+
+    async fn append_events(&mut self, events: Vec<Event<UniswapV4Event>>) -> Result<()> {
+        for event in events {
+            let event_meta = event.meta.as_ref().context("event meta is empty")?;
+            self.events
+                .insert((event_meta.block_number, event_meta.log_index), event);
+        }
+        Ok(())
+    }
+
+    async fn last_event_block(&self) -> Result<u64> {
+        self.events
+            .keys()
+            .last()
+            .map(|(block_number, _)| block_number)
+            .cloned()
+            .context("no events")
+    }
+
+    async fn persist_last_indexed_block(&mut self, _block: u64) -> Result<()> {
+        // storage is only in-memory so we don't need to persist anything here
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, ethcontract::EventMetadata};
+
+    fn build_event((block_number, log_index): (u64, usize)) -> Event<UniswapV4Event> {
+        Event {
+            data: UniswapV4Event::Swap(Swap::default()),
+            meta: Some(EventMetadata {
+                block_number,
+                log_index,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn replace_events_drops_reorged_blocks() {
+        let keys = [(1, 0), (2, 0), (2, 1), (3, 0)];
+        let events = keys
+            .into_iter()
+            .map(|key| (key, build_event(key)))
+            .collect();
+        let mut cache = RecentEventsCache { events };
+
+        cache
+            .replace_events(
+                vec![build_event((2, 5))],
+                RangeInclusive::try_new(2u64, 3).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            cache.events.keys().cloned().collect::<Vec<_>>(),
+            [(1, 0), (2, 5)]
+        );
+        assert_eq!(cache.last_event_block().await.unwrap(), 2);
+    }
+
+    #[test]
+    fn get_and_remove_events() {
+        let keys = [(1, 0), (1, 1), (2, 0), (3, 0), (3, 1)];
+        let events = keys
+            .into_iter()
+            .map(|key| (key, build_event(key)))
+            .collect();
+        let mut cache = RecentEventsCache { events };
+
+        let events = cache.get_events(RangeInclusive::try_new(2u64, 3).unwrap());
+        assert_eq!(
+            events,
+            keys[2..]
+                .iter()
+                .map(|key| build_event(*key))
+                .collect::<Vec<_>>()
+        );
+
+        cache.remove_events_older_than_block(3);
+        assert_eq!(cache.events.keys().cloned().collect::<Vec<_>>(), keys[3..]);
+    }
+}
@@ -0,0 +1,4 @@
+//! Uniswap V4 liquidity source implementation, indexing the pools of the
+//! singleton pool manager from its events.
+pub mod event_fetching;
+pub mod pool_fetching;
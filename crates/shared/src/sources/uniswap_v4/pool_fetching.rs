@@ -0,0 +1,482 @@
+use {
+    super::event_fetching::{RecentEventsCache, UniswapV4Event, UniswapV4PoolEventFetcher},
+    crate::{
+        event_handling::{EventHandler, MAX_REORG_BLOCK_COUNT},
+        maintenance::Maintaining,
+        recent_block_cache::Block,
+        sources::uniswap_v3::pool_fetching::{PoolState, PoolStats},
+    },
+    anyhow::{Context, Result},
+    ethcontract::{Event, H160, H256, U256},
+    ethrpc::{
+        Web3,
+        block_stream::{BlockRetrieving, RangeInclusive},
+    },
+    model::TokenPair,
+    num::{BigInt, Zero, rational::Ratio},
+    number::conversions::{big_int_to_u256, u256_to_big_int},
+    std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        sync::{Arc, Mutex},
+    },
+};
+
+/// The fee value of a pool key marking that the LP fee of the pool is managed
+/// by its hooks contract.
+pub const DYNAMIC_FEE_FLAG: u32 = 0x800000;
+
+/// The maximum LP fee in hundredths of a bip, i.e. 100%.
+const MAX_LP_FEE: u32 = 1_000_000;
+
+#[async_trait::async_trait]
+pub trait PoolFetching: Send + Sync {
+    async fn fetch(
+        &self,
+        token_pairs: &HashSet<TokenPair>,
+        at_block: Block,
+    ) -> Result<Vec<PoolInfo>>;
+}
+
+/// The key identifying a Uniswap V4 pool within the pool manager. The pool ID
+/// is the hash of the ABI encoded key.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PoolKey {
+    pub currency0: H160,
+    pub currency1: H160,
+    /// The LP fee in hundredths of a bip, or [`DYNAMIC_FEE_FLAG`].
+    pub fee: u32,
+    pub tick_spacing: i32,
+    pub hooks: H160,
+}
+
+impl PoolKey {
+    /// Returns whether or not pools with this key can be used for settling
+    /// orders. Pools trading native ETH can't be settled with the settlement
+    /// contract's WETH and hooks can change swaps arbitrarily, so only pools
+    /// without hooks or with allow-listed hooks are supported.
+    pub fn is_supported(&self, allowed_hooks: &HashSet<H160>) -> bool {
+        !self.currency0.is_zero() && (self.hooks.is_zero() || allowed_hooks.contains(&self.hooks))
+    }
+}
+
+/// Pool data in a format prepared for solvers.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PoolInfo {
+    pub id: H256,
+    pub key: PoolKey,
+    pub state: PoolState,
+    pub gas_stats: PoolStats,
+}
+
+impl PoolInfo {
+    pub fn token_pair(&self) -> Option<TokenPair> {
+        TokenPair::new(self.key.currency0, self.key.currency1)
+    }
+}
+
+#[derive(Default)]
+struct PoolsCheckpoint {
+    /// Pools state.
+    pools: HashMap<H256, PoolInfo>,
+    /// Pool IDs for each token pair.
+    pools_by_token_pair: HashMap<TokenPair, HashSet<H256>>,
+    /// Block number for which `pools` field was populated.
+    block_number: u64,
+}
+
+impl PoolsCheckpoint {
+    /// Returns the checkpointed pools for the given token pairs and the block
+    /// number of the checkpoint.
+    fn get(&self, token_pairs: &HashSet<TokenPair>) -> (HashMap<H256, PoolInfo>, u64) {
+        let pools = token_pairs
+            .iter()
+            .filter_map(|pair| self.pools_by_token_pair.get(pair))
+            .flatten()
+            .filter_map(|id| Some((*id, self.pools.get(id)?.clone())))
+            .collect();
+        (pools, self.block_number)
+    }
+
+    fn append_events(&mut self, events: Vec<Event<UniswapV4Event>>, allowed_hooks: &HashSet<H160>) {
+        let initialized = events
+            .iter()
+            .filter(|event| matches!(event.data, UniswapV4Event::Initialize(_)))
+            .map(|event| event.data.pool_id())
+            .collect::<Vec<_>>();
+        append_events(&mut self.pools, events, allowed_hooks);
+        for id in initialized {
+            if let Some(pair) = self.pools.get(&id).and_then(PoolInfo::token_pair) {
+                self.pools_by_token_pair.entry(pair).or_default().insert(id);
+            }
+        }
+    }
+}
+
+/// Indexes Uniswap V4 pools from the events of the singleton pool manager.
+pub struct UniswapV4PoolFetcher {
+    /// Pools state on a specific block number in history considered reorg safe
+    checkpoint: Mutex<PoolsCheckpoint>,
+    /// Recent events used on top of the checkpoint to get the `latest_block`
+    /// pools state.
+    events: tokio::sync::Mutex<EventHandler<UniswapV4PoolEventFetcher, RecentEventsCache>>,
+    /// Hooks contracts that are considered safe to trade through.
+    allowed_hooks: HashSet<H160>,
+}
+
+impl UniswapV4PoolFetcher {
+    /// Creates a new pool fetcher and indexes the pool manager events since
+    /// its deployment. There is no subgraph to bootstrap the pools from, so
+    /// this can take a while.
+    pub async fn new(
+        web3: Web3,
+        block_retriever: Arc<dyn BlockRetrieving>,
+        pool_manager: H160,
+        deployment_block: u64,
+        allowed_hooks: HashSet<H160>,
+    ) -> Result<Self> {
+        let web3 = ethrpc::instrumented::instrument_with_label(&web3, "uniswapV4".into());
+        let init_block = block_retriever
+            .block(deployment_block.saturating_sub(1))
+            .await?;
+
+        let events = tokio::sync::Mutex::new(EventHandler::new(
+            block_retriever,
+            UniswapV4PoolEventFetcher { web3, pool_manager },
+            RecentEventsCache::default(),
+            Some(init_block),
+        ));
+        let fetcher = Self {
+            checkpoint: Mutex::new(PoolsCheckpoint {
+                block_number: init_block.0,
+                ..Default::default()
+            }),
+            events,
+            allowed_hooks,
+        };
+
+        fetcher
+            .run_maintenance()
+            .await
+            .context("failed to index Uniswap V4 pool manager events")?;
+        Ok(fetcher)
+    }
+
+    /// Moves the checkpoint to the block `last_handled_block -
+    /// MAX_REORG_BLOCK_COUNT`
+    async fn move_checkpoint_to_future(&self) -> Result<()> {
+        let mut events = self.events.lock().await;
+        let Some((last_handled_block, _)) = events.last_handled_block() else {
+            return Ok(());
+        };
+        let old_checkpoint_block = self.checkpoint.lock().unwrap().block_number;
+        let new_checkpoint_block = std::cmp::max(
+            last_handled_block.saturating_sub(MAX_REORG_BLOCK_COUNT),
+            old_checkpoint_block,
+        );
+
+        if new_checkpoint_block > old_checkpoint_block {
+            let block_range =
+                RangeInclusive::try_new(old_checkpoint_block + 1, new_checkpoint_block)?;
+            let checkpoint_events = events.store().get_events(block_range);
+            {
+                let mut checkpoint = self.checkpoint.lock().unwrap();
+                checkpoint.append_events(checkpoint_events, &self.allowed_hooks);
+                checkpoint.block_number = new_checkpoint_block;
+                tracing::debug!(
+                    block = checkpoint.block_number,
+                    pools = checkpoint.pools.len(),
+                    "updated Uniswap V4 checkpoint"
+                );
+            }
+
+            // clear events with block number lower than `new_checkpoint_block`
+            events
+                .store_mut()
+                .remove_events_older_than_block(new_checkpoint_block);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl PoolFetching for UniswapV4PoolFetcher {
+    async fn fetch(
+        &self,
+        token_pairs: &HashSet<TokenPair>,
+        at_block: Block,
+    ) -> Result<Vec<PoolInfo>> {
+        let last_handled_block = self
+            .events
+            .lock()
+            .await
+            .last_handled_block()
+            .unwrap_or_default()
+            .0;
+        let block_number = match at_block {
+            Block::Recent => last_handled_block,
+            Block::Number(number) => number,
+        };
+
+        // sanity check if events are up to date
+        if block_number > last_handled_block {
+            tracing::debug!(
+                "can't get liquidity for block {} since the last handled block is {}",
+                block_number,
+                last_handled_block
+            );
+            if let Err(err) = self.events.run_maintenance().await {
+                tracing::debug!("failed to update events on fetch because {}", err);
+                return Ok(Default::default());
+            }
+        }
+
+        // hold the events lock while reading the checkpoint so that the
+        // maintenance can't move the checkpoint in between
+        let events = self.events.lock().await;
+        let (mut pools, checkpoint_block_number) = self.checkpoint.lock().unwrap().get(token_pairs);
+        if block_number > checkpoint_block_number {
+            let block_range = RangeInclusive::try_new(checkpoint_block_number + 1, block_number)?;
+            append_events(
+                &mut pools,
+                events.store().get_events(block_range),
+                &self.allowed_hooks,
+            );
+        }
+
+        // recent events can initialize pools for any token pair, and only
+        // pools with liquidity at the current tick are useful
+        Ok(pools
+            .into_values()
+            .filter(|pool| {
+                pool.state.liquidity > U256::zero()
+                    && pool
+                        .token_pair()
+                        .is_some_and(|pair| token_pairs.contains(&pair))
+            })
+            .collect())
+    }
+}
+
+/// For a given set of pools, append events to get their new state.
+fn append_events(
+    pools: &mut HashMap<H256, PoolInfo>,
+    events: Vec<Event<UniswapV4Event>>,
+    allowed_hooks: &HashSet<H160>,
+) {
+    for event in events {
+        let id = event.data.pool_id();
+        match event.data {
+            UniswapV4Event::Initialize(initialize) => {
+                let key = PoolKey {
+                    currency0: initialize.currency0,
+                    currency1: initialize.currency1,
+                    fee: initialize.fee,
+                    tick_spacing: initialize.tick_spacing,
+                    hooks: initialize.hooks,
+                };
+                if !key.is_supported(allowed_hooks) {
+                    continue;
+                }
+                // The fee of dynamic fee pools is only known once they are
+                // swapped with, so assume the maximum until then to not
+                // overvalue the pool.
+                let fee = match key.fee {
+                    DYNAMIC_FEE_FLAG => MAX_LP_FEE,
+                    fee => fee,
+                };
+                pools.insert(
+                    id,
+                    PoolInfo {
+                        id,
+                        key,
+                        state: PoolState {
+                            sqrt_price: initialize.sqrt_price_x96,
+                            liquidity: U256::zero(),
+                            tick: BigInt::from(initialize.tick),
+                            liquidity_net: BTreeMap::new(),
+                            fee: Ratio::new(fee, MAX_LP_FEE),
+                        },
+                        gas_stats: PoolStats {
+                            // a swap through the universal router, including
+                            // the unlock, settle and take of the pool manager
+                            mean_gas: U256::from(160_000),
+                        },
+                    },
+                );
+            }
+            UniswapV4Event::ModifyLiquidity(modify) => {
+                let Some(pool) = pools.get_mut(&id).map(|pool| &mut pool.state) else {
+                    continue;
+                };
+                let delta = modify
+                    .liquidity_delta
+                    .to_string()
+                    .parse::<BigInt>()
+                    .expect("signed integers format as decimal numbers");
+                let tick_lower = BigInt::from(modify.tick_lower);
+                let tick_upper = BigInt::from(modify.tick_upper);
+
+                // liquidity tracks the liquidity on recent tick,
+                // only need to update it if the position includes the recent tick.
+                if tick_lower <= pool.tick && pool.tick < tick_upper {
+                    pool.liquidity = big_int_to_u256(&(u256_to_big_int(&pool.liquidity) + &delta))
+                        .unwrap_or_default();
+                }
+
+                update_liquidity_net(&mut pool.liquidity_net, tick_lower, delta.clone());
+                update_liquidity_net(&mut pool.liquidity_net, tick_upper, -delta);
+            }
+            UniswapV4Event::Swap(swap) => {
+                let Some(pool) = pools.get_mut(&id).map(|pool| &mut pool.state) else {
+                    continue;
+                };
+                pool.tick = BigInt::from(swap.tick);
+                pool.liquidity = swap.liquidity.into();
+                pool.sqrt_price = swap.sqrt_price_x96;
+                pool.fee = Ratio::new(swap.fee, MAX_LP_FEE);
+            }
+        }
+    }
+}
+
+/// Adds `delta` to the net liquidity of a tick, removing 0 entries to save
+/// bandwidth.
+fn update_liquidity_net(liquidity_net: &mut BTreeMap<BigInt, BigInt>, tick: BigInt, delta: BigInt) {
+    let net = liquidity_net.entry(tick.clone()).or_default();
+    *net += delta;
+    if net.is_zero() {
+        liquidity_net.remove(&tick);
+    }
+}
+
+#[async_trait::async_trait]
+impl Maintaining for UniswapV4PoolFetcher {
+    async fn run_maintenance(&self) -> Result<()> {
+        self.events.run_maintenance().await?;
+        self.move_checkpoint_to_future().await
+    }
+
+    fn name(&self) -> &str {
+        "UniswapV4PoolFetcher"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        contracts::uniswap_v4_pool_manager::event_data::{Initialize, ModifyLiquidity, Swap},
+        ethcontract::{Bytes, EventMetadata, I256},
+    };
+
+    const POOL_ID: [u8; 32] = [0x01; 32];
+
+    fn event(data: UniswapV4Event) -> Event<UniswapV4Event> {
+        Event {
+            data,
+            meta: Some(EventMetadata::default()),
+        }
+    }
+
+    fn initialize(hooks: H160) -> Event<UniswapV4Event> {
+        event(UniswapV4Event::Initialize(Initialize {
+            id: Bytes(POOL_ID),
+            currency0: H160::from_low_u64_be(1),
+            currency1: H160::from_low_u64_be(2),
+            fee: 3000,
+            tick_spacing: 60,
+            hooks,
+            sqrt_price_x96: 1.into(),
+            tick: 0,
+        }))
+    }
+
+    #[test]
+    fn append_events_test_initialize() {
+        let allowed_hooks = HashSet::from([H160::from_low_u64_be(0x42)]);
+
+        let mut pools = HashMap::new();
+        append_events(&mut pools, vec![initialize(H160::zero())], &allowed_hooks);
+        let pool = &pools[&H256(POOL_ID)];
+        assert_eq!(pool.key.fee, 3000);
+        assert_eq!(pool.state.fee, Ratio::new(3, 1000));
+        assert_eq!(pool.state.sqrt_price, U256::from(1));
+
+        let mut pools = HashMap::new();
+        append_events(
+            &mut pools,
+            vec![initialize(H160::from_low_u64_be(0x42))],
+            &allowed_hooks,
+        );
+        assert!(pools.contains_key(&H256(POOL_ID)));
+
+        let mut pools = HashMap::new();
+        append_events(
+            &mut pools,
+            vec![initialize(H160::from_low_u64_be(0x1337))],
+            &allowed_hooks,
+        );
+        assert!(pools.is_empty());
+    }
+
+    #[test]
+    fn append_events_test_modify_liquidity_and_swap() {
+        let mut pools = HashMap::new();
+        let modify = |tick_lower, tick_upper, delta: i128| {
+            event(UniswapV4Event::ModifyLiquidity(ModifyLiquidity {
+                id: Bytes(POOL_ID),
+                tick_lower,
+                tick_upper,
+                liquidity_delta: I256::from(delta),
+                ..Default::default()
+            }))
+        };
+        append_events(
+            &mut pools,
+            vec![
+                initialize(H160::zero()),
+                modify(-60, 60, 1000),
+                modify(60, 120, 500),
+                modify(-60, 60, -400),
+            ],
+            &HashSet::new(),
+        );
+        let pool = &pools[&H256(POOL_ID)];
+        assert_eq!(pool.state.liquidity, U256::from(600));
+        assert_eq!(
+            pool.state.liquidity_net,
+            BTreeMap::from([
+                (BigInt::from(-60), BigInt::from(600)),
+                (BigInt::from(60), BigInt::from(-100)),
+                (BigInt::from(120), BigInt::from(-500)),
+            ])
+        );
+
+        append_events(
+            &mut pools,
+            vec![
+                event(UniswapV4Event::Swap(Swap {
+                    id: Bytes(POOL_ID),
+                    sqrt_price_x96: 2.into(),
+                    liquidity: 500,
+                    tick: 70,
+                    fee: 500,
+                    ..Default::default()
+                })),
+                modify(-60, 60, -600),
+            ],
+            &HashSet::new(),
+        );
+        let pool = &pools[&H256(POOL_ID)];
+        assert_eq!(pool.state.tick, BigInt::from(70));
+        assert_eq!(pool.state.liquidity, U256::from(500));
+        assert_eq!(pool.state.fee, Ratio::new(1, 2000));
+        assert_eq!(
+            pool.state.liquidity_net,
+            BTreeMap::from([
+                (BigInt::from(60), BigInt::from(500)),
+                (BigInt::from(120), BigInt::from(-500)),
+            ])
+        );
+    }
+}
@@ -85,6 +85,121 @@ impl Default for OrdersQuery {
     }
 }
 
+/// 0x API swap quote query parameters for the allowance holder flow.
+/// https://0x.org/docs/api#tag/Swap/operation/swap::allowanceHolder::getQuote
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SwapQuery {
+    /// The chain ID of the network to swap on.
+    pub chain_id: u64,
+    /// Contract address of the token to sell.
+    pub sell_token: H160,
+    /// Contract address of the token to buy.
+    pub buy_token: H160,
+    /// Amount of the sell token to sell, set in atoms. The API only supports
+    /// quoting exact sell amounts.
+    pub sell_amount: U256,
+    /// The address which will execute the swap.
+    pub taker: H160,
+    /// Maximum acceptable slippage of the buy amount in basis points.
+    pub slippage_bps: u32,
+    /// Liquidity sources to exclude.
+    pub excluded_sources: Vec<String>,
+}
+
+impl SwapQuery {
+    /// Encodes the swap query as a url with parameters.
+    fn format_url(&self, base_url: &Url) -> Url {
+        let mut url = crate::url::join(base_url, "/swap/allowance-holder/quote");
+        url.query_pairs_mut()
+            .append_pair("chainId", &self.chain_id.to_string())
+            .append_pair("sellToken", &addr2str(self.sell_token))
+            .append_pair("buyToken", &addr2str(self.buy_token))
+            .append_pair("sellAmount", &self.sell_amount.to_string())
+            .append_pair("taker", &addr2str(self.taker))
+            .append_pair("slippageBps", &self.slippage_bps.to_string());
+        if !self.excluded_sources.is_empty() {
+            url.query_pairs_mut()
+                .append_pair("excludedSources", &self.excluded_sources.join(","));
+        }
+        url
+    }
+}
+
+/// A 0x API swap quote.
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapResponse {
+    /// The amount of sell token (in atoms) that would be sold in this swap.
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub sell_amount: U256,
+    /// The amount of buy token (in atoms) that would be bought in this swap.
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub buy_amount: U256,
+    /// The transaction executing the swap.
+    pub transaction: SwapTransaction,
+    /// Issues that would prevent the taker from executing the swap.
+    #[serde(default)]
+    pub issues: SwapIssues,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapTransaction {
+    /// The address of the contract to call in order to execute the swap.
+    pub to: H160,
+    /// The swap calldata.
+    #[serde(with = "bytes_hex")]
+    pub data: Vec<u8>,
+    /// The amount of Ether that needs to be sent with the swap.
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub value: U256,
+    /// The estimated gas units used by the swap.
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas: U256,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapIssues {
+    /// Set if the taker has not approved the sell token to the contract
+    /// pulling it.
+    pub allowance: Option<SwapAllowance>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapAllowance {
+    /// The contract that needs an allowance of the sell token.
+    pub spender: H160,
+}
+
+impl SwapResponse {
+    /// The contract that needs an allowance of the sell token to execute the
+    /// swap. In the allowance holder flow this is the contract the swap gets
+    /// executed through, which the API only reports when the allowance is
+    /// missing.
+    pub fn allowance_target(&self) -> H160 {
+        self.issues
+            .allowance
+            .as_ref()
+            .map(|allowance| allowance.spender)
+            .unwrap_or(self.transaction.to)
+    }
+}
+
+/// Swap quotes are only returned if enough liquidity is available.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawSwapResponse {
+    Available(SwapResponse),
+    #[serde(rename_all = "camelCase")]
+    Unavailable {
+        liquidity_available: bool,
+    },
+}
+
 #[serde_as]
 #[derive(Debug, Derivative, Clone, Deserialize, Serialize, Eq, PartialEq)]
 #[derivative(Default)]
@@ -220,6 +335,9 @@ pub trait ZeroExApi: Send + Sync {
         &self,
         query: &OrdersQuery,
     ) -> Result<Vec<OrderRecord>, ZeroExResponseError>;
+
+    /// Quotes a swap of an exact sell amount.
+    async fn get_swap(&self, query: &SwapQuery) -> Result<SwapResponse, ZeroExResponseError>;
 }
 
 /// 0x API Client implementation.
@@ -291,7 +409,7 @@ impl DefaultZeroExApi {
         url.query_pairs_mut()
             .append_pair("page", &page.to_string())
             .append_pair("perPage", &results_per_page.to_string());
-        self.request(url, false).await
+        self.request(url, false, None).await
     }
 }
 
@@ -299,7 +417,15 @@ impl DefaultZeroExApi {
 #[serde(untagged)]
 enum RawResponse<Ok> {
     ResponseOk(Ok),
-    ResponseErr { reason: String, code: u32 },
+    ResponseErr {
+        reason: String,
+        code: u32,
+    },
+    /// Error format of the v2 APIs.
+    ResponseErrV2 {
+        name: String,
+        message: String,
+    },
 }
 
 #[derive(Error, Debug)]
@@ -348,6 +474,21 @@ impl ZeroExApi for DefaultZeroExApi {
         retain_valid_orders(&mut results);
         Ok(results)
     }
+
+    async fn get_swap(&self, query: &SwapQuery) -> Result<SwapResponse, ZeroExResponseError> {
+        let url = query.format_url(&self.base_url);
+        match self.request(url, false, Some("v2")).await? {
+            RawSwapResponse::Available(swap) => Ok(swap),
+            RawSwapResponse::Unavailable {
+                liquidity_available: false,
+            } => Err(ZeroExResponseError::InsufficientLiquidity),
+            RawSwapResponse::Unavailable {
+                liquidity_available: true,
+            } => Err(ZeroExResponseError::UnknownZeroExError(
+                "incomplete swap quote".to_owned(),
+            )),
+        }
+    }
 }
 
 /// Append data of response to results and return whether another page should be
@@ -380,6 +521,7 @@ impl DefaultZeroExApi {
         &self,
         url: Url,
         set_current_block_header: bool,
+        api_version: Option<&str>,
     ) -> Result<T, ZeroExResponseError> {
         tracing::trace!("Querying 0x API: {}", url);
 
@@ -392,6 +534,9 @@ impl DefaultZeroExApi {
                     self.block_stream.borrow().hash.to_string(),
                 );
             };
+            if let Some(version) = api_version {
+                request = request.header("0x-version", version);
+            }
             if let Some(id) = observe::request_id::from_current_span() {
                 request = request.header("X-REQUEST-ID", id);
             }
@@ -417,6 +562,17 @@ impl DefaultZeroExApi {
                     500..=599 => Err(ZeroExResponseError::ServerError(format!("{url:?}"))),
                     _ => Err(ZeroExResponseError::UnknownZeroExError(reason)),
                 },
+                Ok(RawResponse::ResponseErrV2 { name, message }) => match name.as_str() {
+                    "INPUT_INVALID" | "TOKEN_NOT_SUPPORTED" => {
+                        Err(ZeroExResponseError::InsufficientLiquidity)
+                    }
+                    "INTERNAL_SERVER_ERROR" => {
+                        Err(ZeroExResponseError::ServerError(format!("{url:?}")))
+                    }
+                    _ => Err(ZeroExResponseError::UnknownZeroExError(format!(
+                        "{name}: {message}"
+                    ))),
+                },
                 Err(err) => Err(ZeroExResponseError::DeserializeError(
                     err,
                     response_text.parse().unwrap(),
@@ -583,6 +739,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn formats_swap_query() {
+        let query = SwapQuery {
+            chain_id: 1,
+            sell_token: addr!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            buy_token: addr!("6b175474e89094c44da98b954eedeac495271d0f"),
+            sell_amount: U256::exp10(18),
+            taker: addr!("9008d19f58aabd9ed0d60971565aa8510560ab41"),
+            slippage_bps: 100,
+            excluded_sources: vec!["Uniswap_V2".to_owned(), "Curve".to_owned()],
+        };
+        assert_eq!(
+            query
+                .format_url(&DefaultZeroExApi::DEFAULT_URL.parse().unwrap())
+                .as_str(),
+            "https://api.0x.org/swap/allowance-holder/quote?chainId=1\
+             &sellToken=0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2\
+             &buyToken=0x6b175474e89094c44da98b954eedeac495271d0f\
+             &sellAmount=1000000000000000000\
+             &taker=0x9008d19f58aabd9ed0d60971565aa8510560ab41\
+             &slippageBps=100&excludedSources=Uniswap_V2%2CCurve",
+        );
+    }
+
+    #[test]
+    fn deserialize_swap_response() {
+        let swap = serde_json::from_str::<RawResponse<RawSwapResponse>>(
+            r#"{"liquidityAvailable":true,"buyAmount":"1500000000000000000000","sellAmount":"1000000000000000000","minBuyAmount":"1485000000000000000000","issues":{"allowance":{"actual":"0","spender":"0x0000000000001ff3684f28c67538d4d072c22734"},"balance":null,"simulationIncomplete":false},"transaction":{"to":"0x0000000000001ff3684f28c67538d4d072c22734","data":"0x2213bc0b","gas":"111000","gasPrice":"15000000000","value":"0"}}"#,
+        )
+        .unwrap();
+        let RawResponse::ResponseOk(RawSwapResponse::Available(swap)) = swap else {
+            panic!("unexpected response");
+        };
+        assert_eq!(
+            swap,
+            SwapResponse {
+                sell_amount: U256::exp10(18),
+                buy_amount: U256::from(1500) * U256::exp10(18),
+                transaction: SwapTransaction {
+                    to: addr!("0000000000001ff3684f28c67538d4d072c22734"),
+                    data: vec![0x22, 0x13, 0xbc, 0x0b],
+                    value: U256::zero(),
+                    gas: 111_000.into(),
+                },
+                issues: SwapIssues {
+                    allowance: Some(SwapAllowance {
+                        spender: addr!("0000000000001ff3684f28c67538d4d072c22734"),
+                    }),
+                },
+            }
+        );
+
+        let unavailable = serde_json::from_str::<RawResponse<RawSwapResponse>>(
+            r#"{"liquidityAvailable":false,"zid":"0x111111111111111111111111"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            unavailable,
+            RawResponse::ResponseOk(RawSwapResponse::Unavailable {
+                liquidity_available: false
+            })
+        ));
+    }
+
     #[test]
     fn compute_remaining_maker_amount() {
         let bogous_order = OrderRecord::new(
@@ -0,0 +1,74 @@
+use {
+    super::balancer_v2::NEVER,
+    contracts::BalancerV3BatchRouter,
+    ethcontract::{Bytes, H160},
+    shared::{
+        http_solver::model::TokenAmount,
+        interaction::{EncodedInteraction, Interaction},
+    },
+};
+
+/// A single pool exact out swap through the Balancer V3 batch router.
+///
+/// The router pulls the input tokens from the settlement contract with
+/// Permit2, so the settlement contract needs a Permit2 allowance for the
+/// router when this interaction executes.
+#[derive(Clone, Debug)]
+pub struct BalancerV3SwapGivenOutInteraction {
+    pub batch_router: BalancerV3BatchRouter,
+    pub pool: H160,
+    pub asset_in_max: TokenAmount,
+    pub asset_out: TokenAmount,
+    pub user_data: Bytes<Vec<u8>>,
+}
+
+impl BalancerV3SwapGivenOutInteraction {
+    pub fn encode_swap(&self) -> EncodedInteraction {
+        let method = self.batch_router.swap_exact_out(
+            vec![(
+                self.asset_in_max.token,
+                vec![(
+                    self.pool,
+                    self.asset_out.token,
+                    false, // isBuffer
+                )],
+                self.asset_in_max.amount,
+                self.asset_out.amount,
+            )],
+            *NEVER,
+            false, // wethIsEth
+            self.user_data.clone(),
+        );
+        let calldata = method.tx.data.expect("no calldata").0;
+        (self.batch_router.address(), 0.into(), Bytes(calldata))
+    }
+}
+
+impl Interaction for BalancerV3SwapGivenOutInteraction {
+    fn encode(&self) -> EncodedInteraction {
+        self.encode_swap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, contracts::dummy_contract, hex_literal::hex};
+
+    #[test]
+    fn encode_swap_exact_out() {
+        let batch_router = dummy_contract!(BalancerV3BatchRouter, [0x01; 20]);
+        let interaction = BalancerV3SwapGivenOutInteraction {
+            batch_router: batch_router.clone(),
+            pool: H160([0x02; 20]),
+            asset_in_max: TokenAmount::new(H160([0x03; 20]), 1_337_000_000_000_000_000_000u128),
+            asset_out: TokenAmount::new(H160([0x04; 20]), 42_000_000_000_000_000_000u128),
+            user_data: Bytes::default(),
+        };
+
+        let (target, value, calldata) = interaction.encode_swap();
+        assert_eq!(target, batch_router.address());
+        assert_eq!(value, 0.into());
+        // swapExactOut((address,(address,address,bool)[],uint256,uint256)[],uint256,bool,bytes)
+        assert_eq!(calldata.0[..4], hex!("8eb1b65e"));
+    }
+}
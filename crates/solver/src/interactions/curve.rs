@@ -0,0 +1,121 @@
+use {
+    contracts::{CurveCryptoSwapPool, CurveStableSwapPool},
+    ethcontract::Bytes,
+    primitive_types::U256,
+    shared::interaction::{EncodedInteraction, Interaction},
+};
+
+/// The contract of a Curve pool, which depends on the kind of pool.
+#[derive(Clone, Debug)]
+pub enum CurvePoolContract {
+    Stable(CurveStableSwapPool),
+    Crypto(CurveCryptoSwapPool),
+}
+
+/// A swap through a Curve pool.
+///
+/// Curve pools only support swapping exact amounts in, so the maximum input
+/// amount is sold and the exact output amount is enforced as the minimum
+/// amount out.
+#[derive(Clone, Debug)]
+pub struct CurveInteraction {
+    pub pool: CurvePoolContract,
+    /// The coin index of the token being sold.
+    pub i: usize,
+    /// The coin index of the token being bought.
+    pub j: usize,
+    pub amount_in_max: U256,
+    pub amount_out: U256,
+}
+
+impl Interaction for CurveInteraction {
+    fn encode(&self) -> EncodedInteraction {
+        let (target, method) = match &self.pool {
+            CurvePoolContract::Stable(pool) => (
+                pool.address(),
+                pool.exchange(
+                    self.i as i128,
+                    self.j as i128,
+                    self.amount_in_max,
+                    self.amount_out,
+                )
+                .tx,
+            ),
+            CurvePoolContract::Crypto(pool) => (
+                pool.address(),
+                pool.exchange(
+                    self.i.into(),
+                    self.j.into(),
+                    self.amount_in_max,
+                    self.amount_out,
+                )
+                .tx,
+            ),
+        };
+        let calldata = method.data.expect("no calldata").0;
+        (target, 0.into(), Bytes(calldata))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, contracts::dummy_contract, hex_literal::hex, primitive_types::H160};
+
+    fn word(value: u64) -> [u8; 32] {
+        let mut word = [0; 32];
+        U256::from(value).to_big_endian(&mut word);
+        word
+    }
+
+    #[test]
+    fn encode_stable_swap() {
+        let pool = H160([0x42; 20]);
+        let interaction = CurveInteraction {
+            pool: CurvePoolContract::Stable(dummy_contract!(CurveStableSwapPool, pool)),
+            i: 0,
+            j: 2,
+            amount_in_max: 5.into(),
+            amount_out: 4.into(),
+        };
+        let (target, value, calldata) = interaction.encode();
+        assert_eq!(target, pool);
+        assert_eq!(value, 0.into());
+        assert_eq!(
+            calldata.0,
+            [
+                hex!("3df02124").as_slice(),
+                word(0).as_slice(),
+                word(2).as_slice(),
+                word(5).as_slice(),
+                word(4).as_slice(),
+            ]
+            .concat(),
+        );
+    }
+
+    #[test]
+    fn encode_crypto_swap() {
+        let pool = H160([0x42; 20]);
+        let interaction = CurveInteraction {
+            pool: CurvePoolContract::Crypto(dummy_contract!(CurveCryptoSwapPool, pool)),
+            i: 1,
+            j: 0,
+            amount_in_max: 5.into(),
+            amount_out: 4.into(),
+        };
+        let (target, value, calldata) = interaction.encode();
+        assert_eq!(target, pool);
+        assert_eq!(value, 0.into());
+        assert_eq!(
+            calldata.0,
+            [
+                hex!("5b41b908").as_slice(),
+                word(1).as_slice(),
+                word(0).as_slice(),
+                word(5).as_slice(),
+                word(4).as_slice(),
+            ]
+            .concat(),
+        );
+    }
+}
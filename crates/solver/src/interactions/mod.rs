@@ -1,16 +1,24 @@
 pub mod allowances;
 mod balancer_v2;
+mod balancer_v3;
+mod curve;
 mod erc20;
+mod permit2;
 mod uniswap_v2;
 mod uniswap_v3;
+mod uniswap_v4;
 mod weth;
 mod zeroex;
 
 pub use {
     balancer_v2::BalancerSwapGivenOutInteraction,
+    balancer_v3::BalancerV3SwapGivenOutInteraction,
+    curve::{CurveInteraction, CurvePoolContract},
     erc20::Erc20ApproveInteraction,
+    permit2::Permit2ApproveInteraction,
     uniswap_v2::UniswapInteraction,
     uniswap_v3::{ExactOutputSingleParams, UniswapV3Interaction},
+    uniswap_v4::UniswapV4SwapInteraction,
     weth::UnwrapWethInteraction,
     zeroex::ZeroExInteraction,
 };
@@ -0,0 +1,67 @@
+//! Module containing the Permit2 allowance interaction.
+
+use {
+    contracts::Permit2,
+    ethcontract::Bytes,
+    primitive_types::{H160, U256},
+    shared::interaction::{EncodedInteraction, Interaction},
+};
+
+/// Approves a spender to transfer tokens of the settlement contract through
+/// Permit2.
+///
+/// The allowance is only valid for the block the interaction executes in, so
+/// that no dangling allowances are left behind.
+#[derive(Clone, Debug)]
+pub struct Permit2ApproveInteraction {
+    pub permit2: Permit2,
+    pub token: H160,
+    pub spender: H160,
+    pub amount: U256,
+}
+
+impl Permit2ApproveInteraction {
+    pub fn as_encoded(&self) -> EncodedInteraction {
+        // An expiration of 0 makes Permit2 use the current block timestamp.
+        let method = self
+            .permit2
+            .approve(self.token, self.spender, self.amount, 0);
+        let calldata = method.tx.data.expect("no calldata").0;
+        (self.permit2.address(), 0.into(), Bytes(calldata))
+    }
+}
+
+impl Interaction for Permit2ApproveInteraction {
+    fn encode(&self) -> EncodedInteraction {
+        self.as_encoded()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, contracts::dummy_contract, hex_literal::hex};
+
+    #[test]
+    fn encode_permit2_approve() {
+        let approve = Permit2ApproveInteraction {
+            permit2: dummy_contract!(Permit2, [0x01; 20]),
+            token: H160([0x02; 20]),
+            spender: H160([0x03; 20]),
+            amount: 42.into(),
+        };
+
+        let (target, value, calldata) = approve.as_encoded();
+        assert_eq!(target, approve.permit2.address());
+        assert_eq!(value, 0.into());
+        assert_eq!(
+            calldata.0,
+            hex!(
+                "87517c45
+                 0000000000000000000000000202020202020202020202020202020202020202
+                 0000000000000000000000000303030303030303030303030303030303030303
+                 000000000000000000000000000000000000000000000000000000000000002a
+                 0000000000000000000000000000000000000000000000000000000000000000"
+            )
+        );
+    }
+}
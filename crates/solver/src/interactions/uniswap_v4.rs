@@ -0,0 +1,121 @@
+use {
+    super::balancer_v2::NEVER,
+    contracts::UniswapV4UniversalRouter,
+    ethcontract::{
+        Bytes,
+        I256,
+        common::abi::{self, Token},
+    },
+    shared::{
+        http_solver::model::TokenAmount,
+        interaction::{EncodedInteraction, Interaction},
+        sources::uniswap_v4::pool_fetching::PoolKey,
+    },
+};
+
+/// Universal router command executing a sequence of V4 router actions.
+const V4_SWAP: u8 = 0x10;
+/// V4 router actions used for a single pool exact out swap.
+const SWAP_EXACT_OUT_SINGLE: u8 = 0x08;
+const SETTLE_ALL: u8 = 0x0c;
+const TAKE_ALL: u8 = 0x0f;
+
+/// A single pool exact out swap through the Uniswap universal router.
+///
+/// The router unlocks the pool manager, swaps, settles the input tokens by
+/// pulling them from the settlement contract with Permit2 and sends the
+/// output tokens back to the settlement contract. So the settlement contract
+/// needs a Permit2 allowance for the router when this interaction executes.
+#[derive(Clone, Debug)]
+pub struct UniswapV4SwapInteraction {
+    pub router: UniswapV4UniversalRouter,
+    pub pool_key: PoolKey,
+    pub token_amount_in_max: TokenAmount,
+    pub token_amount_out: TokenAmount,
+    pub hook_data: Bytes<Vec<u8>>,
+}
+
+impl UniswapV4SwapInteraction {
+    pub fn encode_swap(&self) -> EncodedInteraction {
+        let key = &self.pool_key;
+        let swap = abi::encode(&[Token::Tuple(vec![
+            Token::Tuple(vec![
+                Token::Address(key.currency0),
+                Token::Address(key.currency1),
+                Token::Uint(key.fee.into()),
+                Token::Int(I256::from(i128::from(key.tick_spacing)).into_raw()),
+                Token::Address(key.hooks),
+            ]),
+            // zeroForOne
+            Token::Bool(self.token_amount_in_max.token == key.currency0),
+            Token::Uint(self.token_amount_out.amount),
+            Token::Uint(self.token_amount_in_max.amount),
+            Token::Bytes(self.hook_data.0.clone()),
+        ])]);
+        let settle = abi::encode(&[
+            Token::Address(self.token_amount_in_max.token),
+            Token::Uint(self.token_amount_in_max.amount),
+        ]);
+        let take = abi::encode(&[
+            Token::Address(self.token_amount_out.token),
+            Token::Uint(self.token_amount_out.amount),
+        ]);
+        let input = abi::encode(&[
+            Token::Bytes(vec![SWAP_EXACT_OUT_SINGLE, SETTLE_ALL, TAKE_ALL]),
+            Token::Array(vec![
+                Token::Bytes(swap),
+                Token::Bytes(settle),
+                Token::Bytes(take),
+            ]),
+        ]);
+
+        let method = self
+            .router
+            .execute(Bytes(vec![V4_SWAP]), vec![Bytes(input)], *NEVER);
+        let calldata = method.tx.data.expect("no calldata").0;
+        (self.router.address(), 0.into(), Bytes(calldata))
+    }
+}
+
+impl Interaction for UniswapV4SwapInteraction {
+    fn encode(&self) -> EncodedInteraction {
+        self.encode_swap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, contracts::dummy_contract, ethcontract::H160, hex_literal::hex};
+
+    #[test]
+    fn encode_swap_exact_out_single() {
+        let router = dummy_contract!(UniswapV4UniversalRouter, [0x01; 20]);
+        let interaction = UniswapV4SwapInteraction {
+            router: router.clone(),
+            pool_key: PoolKey {
+                currency0: H160([0x02; 20]),
+                currency1: H160([0x03; 20]),
+                fee: 3000,
+                tick_spacing: 60,
+                hooks: H160::zero(),
+            },
+            token_amount_in_max: TokenAmount::new(H160([0x03; 20]), 1_337),
+            token_amount_out: TokenAmount::new(H160([0x02; 20]), 42),
+            hook_data: Bytes::default(),
+        };
+
+        let (target, value, calldata) = interaction.encode_swap();
+        assert_eq!(target, router.address());
+        assert_eq!(value, 0.into());
+        // execute(bytes,bytes[],uint256)
+        assert_eq!(calldata.0[..4], hex!("3593564c"));
+        // commands: a single V4_SWAP
+        assert_eq!(
+            calldata.0[100..133],
+            hex!(
+                "0000000000000000000000000000000000000000000000000000000000000001
+                 10"
+            )
+        );
+    }
+}
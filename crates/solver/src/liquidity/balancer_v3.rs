@@ -0,0 +1,222 @@
+//! Module for providing Balancer V3 pool liquidity to the solvers.
+
+use {
+    super::{AmmOrderExecution, BalancerV3PoolOrder, Liquidity, SettlementHandling},
+    crate::{
+        interactions::{
+            BalancerV3SwapGivenOutInteraction,
+            Permit2ApproveInteraction,
+            allowances::{AllowanceManager, AllowanceManaging, Allowances, Approval},
+        },
+        liquidity_collector::LiquidityCollecting,
+        settlement::SettlementEncoder,
+    },
+    anyhow::Result,
+    contracts::{BalancerV3BatchRouter, GPv2Settlement, Permit2},
+    model::TokenPair,
+    primitive_types::H160,
+    shared::{
+        ethrpc::Web3,
+        http_solver::model::TokenAmount,
+        recent_block_cache::Block,
+        sources::balancer_v3::BalancerV3PoolFetching,
+    },
+    std::{collections::HashSet, sync::Arc},
+};
+
+/// A liquidity provider for Balancer V3 weighted and stable pools.
+pub struct BalancerV3Liquidity {
+    pool_fetcher: Arc<dyn BalancerV3PoolFetching>,
+    batch_router: BalancerV3BatchRouter,
+    permit2: Permit2,
+    allowance_manager: Box<dyn AllowanceManaging>,
+}
+
+impl BalancerV3Liquidity {
+    pub fn new(
+        web3: Web3,
+        pool_fetcher: Arc<dyn BalancerV3PoolFetching>,
+        settlement: GPv2Settlement,
+        batch_router: BalancerV3BatchRouter,
+        permit2: Permit2,
+    ) -> Self {
+        let allowance_manager = AllowanceManager::new(web3, settlement.address());
+        Self {
+            pool_fetcher,
+            batch_router,
+            permit2,
+            allowance_manager: Box::new(allowance_manager),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LiquidityCollecting for BalancerV3Liquidity {
+    /// Returns the Balancer V3 pools that can trade any of the token pairs.
+    async fn get_liquidity(
+        &self,
+        pairs: HashSet<TokenPair>,
+        block: Block,
+    ) -> Result<Vec<Liquidity>> {
+        let pools = self.pool_fetcher.fetch(&pairs, block).await?;
+
+        // The batch router pulls the sell tokens through Permit2, so the
+        // ERC20 allowances are always for the Permit2 contract.
+        let tokens = pools.iter().flat_map(|pool| pool.tokens()).collect();
+        let allowances = Arc::new(
+            self.allowance_manager
+                .get_allowances(tokens, self.permit2.address())
+                .await?,
+        );
+
+        Ok(pools
+            .into_iter()
+            .map(|pool| {
+                Liquidity::BalancerV3(BalancerV3PoolOrder {
+                    settlement_handling: Arc::new(BalancerV3SettlementHandler {
+                        pool: pool.address,
+                        batch_router: self.batch_router.clone(),
+                        permit2: self.permit2.clone(),
+                        allowances: allowances.clone(),
+                    }),
+                    pool,
+                })
+            })
+            .collect())
+    }
+}
+
+pub struct BalancerV3SettlementHandler {
+    pool: H160,
+    batch_router: BalancerV3BatchRouter,
+    permit2: Permit2,
+    allowances: Arc<Allowances>,
+}
+
+impl BalancerV3SettlementHandler {
+    pub fn new(
+        pool: H160,
+        batch_router: BalancerV3BatchRouter,
+        permit2: Permit2,
+        allowances: Allowances,
+    ) -> Self {
+        Self {
+            pool,
+            batch_router,
+            permit2,
+            allowances: Arc::new(allowances),
+        }
+    }
+
+    pub fn pool(&self) -> H160 {
+        self.pool
+    }
+
+    pub fn batch_router(&self) -> &BalancerV3BatchRouter {
+        &self.batch_router
+    }
+
+    pub fn permit2(&self) -> &Permit2 {
+        &self.permit2
+    }
+
+    /// Returns the interactions swapping at most `input_max` for `output`:
+    /// the ERC20 approval for Permit2 (if required), the Permit2 allowance
+    /// for the batch router and the swap itself.
+    pub fn settle(
+        &self,
+        input_max: TokenAmount,
+        output: TokenAmount,
+    ) -> (
+        Option<Approval>,
+        Permit2ApproveInteraction,
+        BalancerV3SwapGivenOutInteraction,
+    ) {
+        let approval = self.allowances.approve_token_or_default(input_max.clone());
+        let permit = Permit2ApproveInteraction {
+            permit2: self.permit2.clone(),
+            token: input_max.token,
+            spender: self.batch_router.address(),
+            amount: input_max.amount,
+        };
+        let swap = BalancerV3SwapGivenOutInteraction {
+            batch_router: self.batch_router.clone(),
+            pool: self.pool,
+            asset_in_max: input_max,
+            asset_out: output,
+            user_data: Default::default(),
+        };
+        (approval, permit, swap)
+    }
+}
+
+impl SettlementHandling<BalancerV3PoolOrder> for BalancerV3SettlementHandler {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    // Creates the required interactions to convert the given input into
+    // output. Assumes slippage is already applied to the `input_max` field.
+    fn encode(&self, execution: AmmOrderExecution, encoder: &mut SettlementEncoder) -> Result<()> {
+        let (approval, permit, swap) = self.settle(execution.input_max, execution.output);
+        if let Some(approval) = approval {
+            encoder.append_to_execution_plan_internalizable(
+                Arc::new(approval),
+                execution.internalizable,
+            );
+        }
+        encoder.append_to_execution_plan_internalizable(Arc::new(permit), execution.internalizable);
+        encoder.append_to_execution_plan_internalizable(Arc::new(swap), execution.internalizable);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, contracts::dummy_contract, maplit::hashmap};
+
+    #[test]
+    fn settles_through_permit2() {
+        let permit2 = dummy_contract!(Permit2, H160([0x02; 20]));
+        let batch_router = dummy_contract!(BalancerV3BatchRouter, H160([0x03; 20]));
+        let handler = BalancerV3SettlementHandler::new(
+            H160([0x42; 20]),
+            batch_router.clone(),
+            permit2.clone(),
+            Allowances::new(
+                permit2.address(),
+                hashmap! { H160::from_low_u64_be(1) => 100.into() },
+            ),
+        );
+
+        let (approval, permit, swap) = handler.settle(
+            TokenAmount::new(H160::from_low_u64_be(1), 100),
+            TokenAmount::new(H160::from_low_u64_be(2), 99),
+        );
+        assert_eq!(approval, None);
+        assert_eq!(permit.token, H160::from_low_u64_be(1));
+        assert_eq!(permit.spender, batch_router.address());
+        assert_eq!(permit.amount, 100.into());
+        assert_eq!(swap.pool, H160([0x42; 20]));
+        assert_eq!(
+            swap.asset_in_max,
+            TokenAmount::new(H160::from_low_u64_be(1), 100)
+        );
+        assert_eq!(
+            swap.asset_out,
+            TokenAmount::new(H160::from_low_u64_be(2), 99)
+        );
+
+        let (approval, ..) = handler.settle(
+            TokenAmount::new(H160::from_low_u64_be(2), 100),
+            TokenAmount::new(H160::from_low_u64_be(1), 99),
+        );
+        assert_eq!(
+            approval,
+            Some(Approval {
+                token: H160::from_low_u64_be(2),
+                spender: permit2.address(),
+            })
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Module for providing Curve pool liquidity to the solvers.
+
+use {
+    super::{AmmOrderExecution, CurvePoolOrder, Liquidity, SettlementHandling},
+    crate::{
+        interactions::{
+            CurveInteraction,
+            CurvePoolContract,
+            allowances::{AllowanceManager, AllowanceManaging, Allowances, Approval},
+        },
+        liquidity_collector::LiquidityCollecting,
+        settlement::SettlementEncoder,
+    },
+    anyhow::{Context, Result},
+    contracts::{CurveCryptoSwapPool, CurveStableSwapPool, GPv2Settlement},
+    model::TokenPair,
+    primitive_types::H160,
+    shared::{
+        ethrpc::Web3,
+        http_solver::model::TokenAmount,
+        recent_block_cache::Block,
+        sources::curve::pool_fetching::{Pool, PoolFetching, PoolState},
+    },
+    std::{collections::HashSet, sync::Arc},
+};
+
+/// A liquidity provider for Curve StableSwap and CryptoSwap pools.
+pub struct CurveLiquidity {
+    web3: Web3,
+    pool_fetcher: Arc<dyn PoolFetching>,
+    allowance_manager: Box<dyn AllowanceManaging>,
+}
+
+impl CurveLiquidity {
+    pub fn new(
+        web3: Web3,
+        pool_fetcher: Arc<dyn PoolFetching>,
+        settlement: GPv2Settlement,
+    ) -> Self {
+        let allowance_manager = AllowanceManager::new(web3.clone(), settlement.address());
+        Self {
+            web3,
+            pool_fetcher,
+            allowance_manager: Box::new(allowance_manager),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LiquidityCollecting for CurveLiquidity {
+    /// Returns the Curve pools that can trade any of the token pairs.
+    async fn get_liquidity(
+        &self,
+        pairs: HashSet<TokenPair>,
+        block: Block,
+    ) -> Result<Vec<Liquidity>> {
+        let pools = self.pool_fetcher.fetch(&pairs, block).await?;
+
+        // Curve pools pull the sell tokens themselves, so each pool needs its
+        // own allowances.
+        let allowances = futures::future::try_join_all(pools.iter().map(|pool| {
+            self.allowance_manager
+                .get_allowances(pool.tokens.iter().copied().collect(), pool.address)
+        }))
+        .await?;
+
+        Ok(pools
+            .into_iter()
+            .zip(allowances)
+            .map(|(pool, allowances)| {
+                Liquidity::Curve(CurvePoolOrder {
+                    settlement_handling: Arc::new(CurveSettlementHandler::new(
+                        pool_contract(&self.web3, &pool),
+                        pool.tokens.clone(),
+                        allowances,
+                    )),
+                    pool,
+                })
+            })
+            .collect())
+    }
+}
+
+pub struct CurveSettlementHandler {
+    contract: CurvePoolContract,
+    tokens: Vec<H160>,
+    allowances: Allowances,
+}
+
+impl CurveSettlementHandler {
+    pub fn new(contract: CurvePoolContract, tokens: Vec<H160>, allowances: Allowances) -> Self {
+        Self {
+            contract,
+            tokens,
+            allowances,
+        }
+    }
+
+    /// Returns the interaction swapping at most `input_max` for `output`
+    /// along with the approval it requires, if any.
+    pub fn settle(
+        &self,
+        input_max: TokenAmount,
+        output: TokenAmount,
+    ) -> Result<(Option<Approval>, CurveInteraction)> {
+        let index = |token: H160| {
+            self.tokens
+                .iter()
+                .position(|t| *t == token)
+                .with_context(|| format!("token {token:?} not in Curve pool"))
+        };
+        let i = index(input_max.token)?;
+        let j = index(output.token)?;
+        let approval = self.allowances.approve_token_or_default(input_max.clone());
+
+        Ok((
+            approval,
+            CurveInteraction {
+                pool: self.contract.clone(),
+                i,
+                j,
+                amount_in_max: input_max.amount,
+                amount_out: output.amount,
+            },
+        ))
+    }
+}
+
+impl SettlementHandling<CurvePoolOrder> for CurveSettlementHandler {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    // Creates the required interaction to convert the given input into output.
+    // Assumes slippage is already applied to the `input_max` field.
+    fn encode(&self, execution: AmmOrderExecution, encoder: &mut SettlementEncoder) -> Result<()> {
+        let (approval, swap) = self.settle(execution.input_max, execution.output)?;
+        if let Some(approval) = approval {
+            encoder.append_to_execution_plan_internalizable(
+                Arc::new(approval),
+                execution.internalizable,
+            );
+        }
+        encoder.append_to_execution_plan_internalizable(Arc::new(swap), execution.internalizable);
+        Ok(())
+    }
+}
+
+/// Returns the contract for swapping through the specified pool.
+fn pool_contract(web3: &Web3, pool: &Pool) -> CurvePoolContract {
+    match pool.state {
+        PoolState::Stable(_) => {
+            CurvePoolContract::Stable(CurveStableSwapPool::at(web3, pool.address))
+        }
+        PoolState::Crypto(_) => {
+            CurvePoolContract::Crypto(CurveCryptoSwapPool::at(web3, pool.address))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, contracts::dummy_contract, maplit::hashmap};
+
+    fn handler(allowances: Allowances) -> CurveSettlementHandler {
+        CurveSettlementHandler::new(
+            CurvePoolContract::Stable(dummy_contract!(CurveStableSwapPool, H160([0x42; 20]))),
+            vec![
+                H160::from_low_u64_be(1),
+                H160::from_low_u64_be(2),
+                H160::from_low_u64_be(3),
+            ],
+            allowances,
+        )
+    }
+
+    #[test]
+    fn settles_by_coin_index() {
+        let pool = H160([0x42; 20]);
+        let handler = handler(Allowances::new(
+            pool,
+            hashmap! { H160::from_low_u64_be(3) => 100.into() },
+        ));
+
+        let (approval, swap) = handler
+            .settle(
+                TokenAmount::new(H160::from_low_u64_be(3), 100),
+                TokenAmount::new(H160::from_low_u64_be(1), 99),
+            )
+            .unwrap();
+        assert_eq!(approval, None);
+        assert_eq!((swap.i, swap.j), (2, 0));
+        assert_eq!(swap.amount_in_max, 100.into());
+        assert_eq!(swap.amount_out, 99.into());
+
+        let (approval, swap) = handler
+            .settle(
+                TokenAmount::new(H160::from_low_u64_be(2), 100),
+                TokenAmount::new(H160::from_low_u64_be(3), 99),
+            )
+            .unwrap();
+        assert_eq!(
+            approval,
+            Some(Approval {
+                token: H160::from_low_u64_be(2),
+                spender: pool,
+            })
+        );
+        assert_eq!((swap.i, swap.j), (1, 2));
+
+        assert!(
+            handler
+                .settle(
+                    TokenAmount::new(H160::from_low_u64_be(4), 100),
+                    TokenAmount::new(H160::from_low_u64_be(1), 99),
+                )
+                .is_err()
+        );
+    }
+}
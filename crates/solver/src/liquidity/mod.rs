@@ -1,8 +1,11 @@
 pub mod balancer_v2;
+pub mod balancer_v3;
+pub mod curve;
 pub mod order_converter;
 pub mod slippage;
 pub mod uniswap_v2;
 pub mod uniswap_v3;
+pub mod uniswap_v4;
 pub mod zeroex;
 
 #[cfg(test)]
@@ -28,8 +31,11 @@ use {
                 },
                 swap::fixed_point::Bfp,
             },
+            balancer_v3::Pool as BalancerV3Pool,
+            curve::pool_fetching::Pool as CurvePool,
             uniswap_v2::pool_fetching::Pool,
             uniswap_v3::pool_fetching::PoolInfo,
+            uniswap_v4::pool_fetching::PoolInfo as UniswapV4Pool,
         },
     },
     std::{collections::BTreeMap, sync::Arc},
@@ -45,6 +51,9 @@ pub enum Liquidity {
     BalancerStable(StablePoolOrder),
     LimitOrder(LimitOrder),
     Concentrated(ConcentratedLiquidity),
+    Curve(CurvePoolOrder),
+    BalancerV3(BalancerV3PoolOrder),
+    UniswapV4(UniswapV4PoolOrder),
 }
 
 /// A trait associating some liquidity model to how it is executed and encoded
@@ -393,6 +402,78 @@ impl Settleable for ConcentratedLiquidity {
     }
 }
 
+/// Curve StableSwap or CryptoSwap pool liquidity.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Derivative))]
+#[cfg_attr(test, derivative(PartialEq))]
+pub struct CurvePoolOrder {
+    pub pool: CurvePool,
+    #[cfg_attr(test, derivative(PartialEq = "ignore"))]
+    pub settlement_handling: Arc<dyn SettlementHandling<Self>>,
+}
+
+impl std::fmt::Debug for CurvePoolOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Curve pool {:?}", self.pool)
+    }
+}
+
+impl Settleable for CurvePoolOrder {
+    type Execution = AmmOrderExecution;
+
+    fn settlement_handling(&self) -> &dyn SettlementHandling<Self> {
+        &*self.settlement_handling
+    }
+}
+
+/// Balancer V3 weighted or stable pool liquidity.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Derivative))]
+#[cfg_attr(test, derivative(PartialEq))]
+pub struct BalancerV3PoolOrder {
+    pub pool: BalancerV3Pool,
+    #[cfg_attr(test, derivative(PartialEq = "ignore"))]
+    pub settlement_handling: Arc<dyn SettlementHandling<Self>>,
+}
+
+impl std::fmt::Debug for BalancerV3PoolOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Balancer V3 pool {:?}", self.pool)
+    }
+}
+
+impl Settleable for BalancerV3PoolOrder {
+    type Execution = AmmOrderExecution;
+
+    fn settlement_handling(&self) -> &dyn SettlementHandling<Self> {
+        &*self.settlement_handling
+    }
+}
+
+/// Uniswap V4 pool manager concentrated liquidity.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Derivative))]
+#[cfg_attr(test, derivative(PartialEq))]
+pub struct UniswapV4PoolOrder {
+    pub pool: UniswapV4Pool,
+    #[cfg_attr(test, derivative(PartialEq = "ignore"))]
+    pub settlement_handling: Arc<dyn SettlementHandling<Self>>,
+}
+
+impl std::fmt::Debug for UniswapV4PoolOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Uniswap V4 pool {:?}", self.pool)
+    }
+}
+
+impl Settleable for UniswapV4PoolOrder {
+    type Execution = AmmOrderExecution;
+
+    fn settlement_handling(&self) -> &dyn SettlementHandling<Self> {
+        &*self.settlement_handling
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use {super::*, maplit::btreemap, std::sync::Mutex};
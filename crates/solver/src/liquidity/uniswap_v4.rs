@@ -0,0 +1,231 @@
+//! Module for providing Uniswap V4 pool liquidity to the solvers.
+
+use {
+    super::{AmmOrderExecution, Liquidity, SettlementHandling, UniswapV4PoolOrder},
+    crate::{
+        interactions::{
+            Permit2ApproveInteraction,
+            UniswapV4SwapInteraction,
+            allowances::{AllowanceManager, AllowanceManaging, Allowances, Approval},
+        },
+        liquidity_collector::LiquidityCollecting,
+        settlement::SettlementEncoder,
+    },
+    anyhow::Result,
+    contracts::{GPv2Settlement, Permit2, UniswapV4UniversalRouter},
+    model::TokenPair,
+    shared::{
+        ethrpc::Web3,
+        http_solver::model::TokenAmount,
+        recent_block_cache::Block,
+        sources::uniswap_v4::pool_fetching::{PoolFetching, PoolKey},
+    },
+    std::{collections::HashSet, sync::Arc},
+};
+
+/// A liquidity provider for the pools of the Uniswap V4 pool manager.
+pub struct UniswapV4Liquidity {
+    pool_fetcher: Arc<dyn PoolFetching>,
+    router: UniswapV4UniversalRouter,
+    permit2: Permit2,
+    allowance_manager: Box<dyn AllowanceManaging>,
+}
+
+impl UniswapV4Liquidity {
+    pub fn new(
+        web3: Web3,
+        pool_fetcher: Arc<dyn PoolFetching>,
+        settlement: GPv2Settlement,
+        router: UniswapV4UniversalRouter,
+        permit2: Permit2,
+    ) -> Self {
+        let allowance_manager = AllowanceManager::new(web3, settlement.address());
+        Self {
+            pool_fetcher,
+            router,
+            permit2,
+            allowance_manager: Box::new(allowance_manager),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LiquidityCollecting for UniswapV4Liquidity {
+    /// Returns the Uniswap V4 pools that can trade any of the token pairs.
+    async fn get_liquidity(
+        &self,
+        pairs: HashSet<TokenPair>,
+        block: Block,
+    ) -> Result<Vec<Liquidity>> {
+        let pools = self.pool_fetcher.fetch(&pairs, block).await?;
+
+        // The universal router settles the sell tokens through Permit2, so
+        // the ERC20 allowances are always for the Permit2 contract.
+        let tokens = pools
+            .iter()
+            .flat_map(|pool| [pool.key.currency0, pool.key.currency1])
+            .collect();
+        let allowances = Arc::new(
+            self.allowance_manager
+                .get_allowances(tokens, self.permit2.address())
+                .await?,
+        );
+
+        Ok(pools
+            .into_iter()
+            .map(|pool| {
+                Liquidity::UniswapV4(UniswapV4PoolOrder {
+                    settlement_handling: Arc::new(UniswapV4SettlementHandler {
+                        pool_key: pool.key,
+                        router: self.router.clone(),
+                        permit2: self.permit2.clone(),
+                        allowances: allowances.clone(),
+                    }),
+                    pool,
+                })
+            })
+            .collect())
+    }
+}
+
+pub struct UniswapV4SettlementHandler {
+    pool_key: PoolKey,
+    router: UniswapV4UniversalRouter,
+    permit2: Permit2,
+    allowances: Arc<Allowances>,
+}
+
+impl UniswapV4SettlementHandler {
+    pub fn new(
+        pool_key: PoolKey,
+        router: UniswapV4UniversalRouter,
+        permit2: Permit2,
+        allowances: Allowances,
+    ) -> Self {
+        Self {
+            pool_key,
+            router,
+            permit2,
+            allowances: Arc::new(allowances),
+        }
+    }
+
+    pub fn pool_key(&self) -> &PoolKey {
+        &self.pool_key
+    }
+
+    pub fn router(&self) -> &UniswapV4UniversalRouter {
+        &self.router
+    }
+
+    pub fn permit2(&self) -> &Permit2 {
+        &self.permit2
+    }
+
+    /// Returns the interactions swapping at most `input_max` for `output`:
+    /// the ERC20 approval for Permit2 (if required), the Permit2 allowance
+    /// for the universal router and the swap itself.
+    pub fn settle(
+        &self,
+        input_max: TokenAmount,
+        output: TokenAmount,
+    ) -> (
+        Option<Approval>,
+        Permit2ApproveInteraction,
+        UniswapV4SwapInteraction,
+    ) {
+        let approval = self.allowances.approve_token_or_default(input_max.clone());
+        let permit = Permit2ApproveInteraction {
+            permit2: self.permit2.clone(),
+            token: input_max.token,
+            spender: self.router.address(),
+            amount: input_max.amount,
+        };
+        let swap = UniswapV4SwapInteraction {
+            router: self.router.clone(),
+            pool_key: self.pool_key,
+            token_amount_in_max: input_max,
+            token_amount_out: output,
+            hook_data: Default::default(),
+        };
+        (approval, permit, swap)
+    }
+}
+
+impl SettlementHandling<UniswapV4PoolOrder> for UniswapV4SettlementHandler {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    // Creates the required interactions to convert the given input into
+    // output. Assumes slippage is already applied to the `input_max` field.
+    fn encode(&self, execution: AmmOrderExecution, encoder: &mut SettlementEncoder) -> Result<()> {
+        let (approval, permit, swap) = self.settle(execution.input_max, execution.output);
+        if let Some(approval) = approval {
+            encoder.append_to_execution_plan_internalizable(
+                Arc::new(approval),
+                execution.internalizable,
+            );
+        }
+        encoder.append_to_execution_plan_internalizable(Arc::new(permit), execution.internalizable);
+        encoder.append_to_execution_plan_internalizable(Arc::new(swap), execution.internalizable);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, contracts::dummy_contract, maplit::hashmap, primitive_types::H160};
+
+    #[test]
+    fn settles_through_permit2() {
+        let permit2 = dummy_contract!(Permit2, H160([0x02; 20]));
+        let router = dummy_contract!(UniswapV4UniversalRouter, H160([0x03; 20]));
+        let pool_key = PoolKey {
+            currency0: H160::from_low_u64_be(1),
+            currency1: H160::from_low_u64_be(2),
+            fee: 500,
+            tick_spacing: 10,
+            hooks: H160::zero(),
+        };
+        let handler = UniswapV4SettlementHandler::new(
+            pool_key,
+            router.clone(),
+            permit2.clone(),
+            Allowances::new(
+                permit2.address(),
+                hashmap! { H160::from_low_u64_be(1) => 100.into() },
+            ),
+        );
+
+        let (approval, permit, swap) = handler.settle(
+            TokenAmount::new(H160::from_low_u64_be(1), 100),
+            TokenAmount::new(H160::from_low_u64_be(2), 99),
+        );
+        assert_eq!(approval, None);
+        assert_eq!(permit.token, H160::from_low_u64_be(1));
+        assert_eq!(permit.spender, router.address());
+        assert_eq!(permit.amount, 100.into());
+        assert_eq!(swap.pool_key, pool_key);
+        assert_eq!(
+            swap.token_amount_in_max,
+            TokenAmount::new(H160::from_low_u64_be(1), 100)
+        );
+        assert_eq!(
+            swap.token_amount_out,
+            TokenAmount::new(H160::from_low_u64_be(2), 99)
+        );
+
+        let (approval, ..) = handler.settle(
+            TokenAmount::new(H160::from_low_u64_be(2), 100),
+            TokenAmount::new(H160::from_low_u64_be(1), 99),
+        );
+        assert_eq!(
+            approval,
+            Some(Approval {
+                token: H160::from_low_u64_be(2),
+                spender: permit2.address(),
+            })
+        );
+    }
+}
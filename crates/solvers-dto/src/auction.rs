@@ -156,6 +156,10 @@ pub struct Token {
     #[serde_as(as = "HexOrDecimalU256")]
     pub available_balance: U256,
     pub trusted: bool,
+    /// Fee in basis points the token takes on every transfer. Only the
+    /// remaining amount arrives at the recipient.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_fee_bps: Option<u32>,
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -167,6 +171,12 @@ pub enum Liquidity {
     Stable(StablePool),
     ConcentratedLiquidity(ConcentratedLiquidityPool),
     LimitOrder(ForeignLimitOrder),
+    CowAmm(CowAmmPool),
+    CurveStable(CurveStablePool),
+    CurveCrypto(CurveCryptoPool),
+    BalancerV3Weighted(BalancerV3WeightedPool),
+    BalancerV3Stable(BalancerV3StablePool),
+    UniswapV4(UniswapV4Pool),
 }
 
 #[serde_as]
@@ -284,6 +294,179 @@ pub struct ForeignLimitOrder {
     pub taker_token_fee_amount: U256,
 }
 
+/// A CoW AMM offering to trade the order its helper contract computed to
+/// rebalance the pool to the auction's reference prices. The pool already
+/// signed the order, so it can only be settled as-is as a JIT order.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CowAmmPool {
+    pub id: String,
+    pub address: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_estimate: U256,
+    pub order: CowAmmOrder,
+}
+
+/// A sell order of a CoW AMM that is not partially fillable.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CowAmmOrder {
+    pub sell_token: H160,
+    pub buy_token: H160,
+    pub receiver: Option<H160>,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub sell_amount: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub buy_amount: U256,
+    pub valid_to: u32,
+    pub app_data: AppDataHash,
+    /// The EIP-1271 signature of the order without the pool address.
+    #[serde_as(as = "serialize::Hex")]
+    pub signature: Vec<u8>,
+    /// Interactions that need to be executed before the order can be
+    /// settled, e.g. committing the pool to the order.
+    pub pre_interactions: Vec<InteractionData>,
+    pub post_interactions: Vec<InteractionData>,
+}
+
+/// A Curve plain or lending StableSwap pool. Fees are relative to `1e10`, as
+/// in the pool contracts.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurveStablePool {
+    pub id: String,
+    pub address: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_estimate: U256,
+    /// The coins of the pool in coin index order.
+    pub tokens: Vec<H160>,
+    #[serde_as(as = "Vec<HexOrDecimalU256>")]
+    pub balances: Vec<U256>,
+    /// The rates normalizing the balances to 18 decimals, scaled by `1e18`.
+    #[serde_as(as = "Vec<HexOrDecimalU256>")]
+    pub rates: Vec<U256>,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub amplification_parameter: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub fee: U256,
+    #[serde_as(as = "Option<HexOrDecimalU256>")]
+    #[serde(default)]
+    pub offpeg_fee_multiplier: Option<U256>,
+}
+
+/// A Curve two coin crypto or tricrypto CryptoSwap pool. Fees are relative to
+/// `1e10`, as in the pool contracts.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurveCryptoPool {
+    pub id: String,
+    pub address: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_estimate: U256,
+    /// The coins of the pool in coin index order.
+    pub tokens: Vec<H160>,
+    #[serde_as(as = "Vec<HexOrDecimalU256>")]
+    pub balances: Vec<U256>,
+    /// The multipliers normalizing the balances to 18 decimals.
+    #[serde_as(as = "Vec<HexOrDecimalU256>")]
+    pub precisions: Vec<U256>,
+    /// The prices of all coins but the first relative to the first, scaled
+    /// by `1e18`.
+    #[serde_as(as = "Vec<HexOrDecimalU256>")]
+    pub price_scale: Vec<U256>,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub amplification_parameter: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gamma: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub d: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub mid_fee: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub out_fee: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub fee_gamma: U256,
+}
+
+/// A Balancer V3 weighted pool. Token rates and scaling factors are applied by
+/// the vault before the pool math.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalancerV3WeightedPool {
+    pub id: String,
+    pub address: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_estimate: U256,
+    pub tokens: HashMap<H160, BalancerV3WeightedReserve>,
+    pub fee: BigDecimal,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalancerV3WeightedReserve {
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub balance: U256,
+    pub scaling_factor: BigDecimal,
+    pub rate: BigDecimal,
+    pub weight: BigDecimal,
+}
+
+/// A Balancer V3 stable pool. Token rates and scaling factors are applied by
+/// the vault before the pool math.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalancerV3StablePool {
+    pub id: String,
+    pub address: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_estimate: U256,
+    pub tokens: HashMap<H160, BalancerV3StableReserve>,
+    pub amplification_parameter: BigDecimal,
+    pub fee: BigDecimal,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalancerV3StableReserve {
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub balance: U256,
+    pub scaling_factor: BigDecimal,
+    pub rate: BigDecimal,
+}
+
+/// A Uniswap V4 concentrated liquidity pool. V4 pools live in the singleton
+/// pool manager, so they are identified by their pool ID instead of an
+/// address.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UniswapV4Pool {
+    pub id: String,
+    pub pool_id: H256,
+    pub router: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_estimate: U256,
+    pub tokens: Vec<H160>,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub sqrt_price: U256,
+    #[serde_as(as = "DisplayFromStr")]
+    pub liquidity: u128,
+    pub tick: i32,
+    #[serde_as(as = "HashMap<DisplayFromStr, DisplayFromStr>")]
+    pub liquidity_net: HashMap<i32, i128>,
+    pub fee: BigDecimal,
+    pub tick_spacing: i32,
+    pub hooks: H160,
+}
+
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -50,6 +50,7 @@ pub enum Kind {
         tokens: BTreeSet<H160>,
     },
     SolverAccountInsufficientBalance {
+        account: H160,
         #[serde_as(as = "HexOrDecimalU256")]
         required: U256,
     },
@@ -66,6 +67,11 @@ pub enum Kind {
     Expired,
     Fail,
     PostprocessingTimedOut,
+    SimulatorDisagreement {
+        block: BlockNo,
+        tx: Tx,
+        reason: String,
+    },
     Banned {
         reason: BanReason,
         until: DateTime<Utc>,
@@ -2,9 +2,10 @@ use serde::Serialize;
 
 mod healthz;
 mod metrics;
+mod notify;
 mod solve;
 
-pub(super) use {healthz::healthz, metrics::metrics, solve::solve};
+pub(super) use {healthz::healthz, metrics::metrics, notify::notify, solve::solve};
 
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
@@ -0,0 +1,3 @@
+pub mod notification;
+
+pub use solvers_dto::notification::Notification;
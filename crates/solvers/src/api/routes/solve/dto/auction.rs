@@ -31,6 +31,9 @@ pub fn to_domain(auction: &Auction) -> Result<auction::Auction, Error> {
                                 .map(auction::Price),
                             available_balance: token.available_balance,
                             trusted: token.trusted,
+                            transfer_fee: token
+                                .transfer_fee_bps
+                                .and_then(auction::TransferFee::from_bps),
                         },
                     )
                 })
@@ -84,6 +87,16 @@ pub fn to_domain(auction: &Auction) -> Result<auction::Auction, Error> {
                     concentrated_liquidity_pool::to_domain(liquidity)
                 }
                 Liquidity::LimitOrder(liquidity) => Ok(foreign_limit_order::to_domain(liquidity)),
+                Liquidity::CowAmm(liquidity) => cow_amm_pool::to_domain(liquidity),
+                Liquidity::CurveStable(liquidity) => curve_pool::stable_to_domain(liquidity),
+                Liquidity::CurveCrypto(liquidity) => curve_pool::crypto_to_domain(liquidity),
+                Liquidity::BalancerV3Weighted(liquidity) => {
+                    balancer_v3_pool::weighted_to_domain(liquidity)
+                }
+                Liquidity::BalancerV3Stable(liquidity) => {
+                    balancer_v3_pool::stable_to_domain(liquidity)
+                }
+                Liquidity::UniswapV4(liquidity) => uniswap_v4_pool::to_domain(liquidity),
             })
             .try_collect()?,
         gas_price: auction::GasPrice(eth::Ether(auction.effective_gas_price)),
@@ -243,6 +256,247 @@ mod concentrated_liquidity_pool {
     }
 }
 
+mod cow_amm_pool {
+    use super::*;
+
+    pub fn to_domain(pool: &CowAmmPool) -> Result<liquidity::Liquidity, Error> {
+        if pool.order.sell_token == pool.order.buy_token {
+            return Err("CoW AMM order trades identical tokens".into());
+        }
+        let interactions = |interactions: &[InteractionData]| -> Vec<_> {
+            interactions
+                .iter()
+                .map(|interaction| eth::Interaction {
+                    target: eth::Address(interaction.target),
+                    value: eth::Ether(interaction.value),
+                    calldata: interaction.call_data.clone(),
+                })
+                .collect()
+        };
+
+        Ok(liquidity::Liquidity {
+            id: liquidity::Id(pool.id.clone()),
+            address: pool.address,
+            gas: eth::Gas(pool.gas_estimate),
+            state: liquidity::State::CowAmm(liquidity::cow_amm::Pool {
+                order: liquidity::cow_amm::Order {
+                    sell: eth::Asset {
+                        token: eth::TokenAddress(pool.order.sell_token),
+                        amount: pool.order.sell_amount,
+                    },
+                    buy: eth::Asset {
+                        token: eth::TokenAddress(pool.order.buy_token),
+                        amount: pool.order.buy_amount,
+                    },
+                    receiver: pool.order.receiver.map(eth::Address),
+                    valid_to: pool.order.valid_to,
+                    app_data: order::AppData(pool.order.app_data.0),
+                    signature: pool.order.signature.clone(),
+                    pre_interactions: interactions(&pool.order.pre_interactions),
+                    post_interactions: interactions(&pool.order.post_interactions),
+                },
+            }),
+        })
+    }
+}
+
+mod curve_pool {
+    use super::*;
+
+    pub fn stable_to_domain(pool: &CurveStablePool) -> Result<liquidity::Liquidity, Error> {
+        if pool.rates.len() != pool.tokens.len() {
+            return Err("invalid number of Curve pool rates".into());
+        }
+        to_domain(
+            &pool.id,
+            pool.address,
+            pool.gas_estimate,
+            &pool.tokens,
+            liquidity::curve::Kind::Stable(liquidity::curve::Stable {
+                balances: pool.balances.clone(),
+                rates: pool.rates.clone(),
+                amplification_parameter: pool.amplification_parameter,
+                fee: pool.fee,
+                offpeg_fee_multiplier: pool.offpeg_fee_multiplier,
+            }),
+        )
+    }
+
+    pub fn crypto_to_domain(pool: &CurveCryptoPool) -> Result<liquidity::Liquidity, Error> {
+        if pool.precisions.len() != pool.tokens.len()
+            || pool.price_scale.len() + 1 != pool.tokens.len()
+        {
+            return Err("invalid number of Curve pool precisions or prices".into());
+        }
+        to_domain(
+            &pool.id,
+            pool.address,
+            pool.gas_estimate,
+            &pool.tokens,
+            liquidity::curve::Kind::Crypto(liquidity::curve::Crypto {
+                balances: pool.balances.clone(),
+                precisions: pool.precisions.clone(),
+                price_scale: pool.price_scale.clone(),
+                amplification_parameter: pool.amplification_parameter,
+                gamma: pool.gamma,
+                d: pool.d,
+                mid_fee: pool.mid_fee,
+                out_fee: pool.out_fee,
+                fee_gamma: pool.fee_gamma,
+            }),
+        )
+    }
+
+    fn to_domain(
+        id: &str,
+        address: eth::H160,
+        gas_estimate: eth::U256,
+        tokens: &[eth::H160],
+        kind: liquidity::curve::Kind,
+    ) -> Result<liquidity::Liquidity, Error> {
+        let tokens = tokens.iter().copied().map(eth::TokenAddress).collect();
+        Ok(liquidity::Liquidity {
+            id: liquidity::Id(id.to_owned()),
+            address,
+            gas: eth::Gas(gas_estimate),
+            state: liquidity::State::Curve(
+                liquidity::curve::Pool::new(tokens, kind).ok_or("invalid Curve pool tokens")?,
+            ),
+        })
+    }
+}
+
+mod balancer_v3_pool {
+    use super::*;
+
+    pub fn weighted_to_domain(
+        pool: &BalancerV3WeightedPool,
+    ) -> Result<liquidity::Liquidity, Error> {
+        let (reserves, weights) = pool
+            .tokens
+            .iter()
+            .map(|(address, token)| {
+                Ok((
+                    reserve(*address, token.balance, &token.scaling_factor, &token.rate)?,
+                    conv::decimal_to_rational(&token.weight).ok_or("invalid token weight")?,
+                ))
+            })
+            .collect::<Result<(Vec<_>, Vec<_>), Error>>()?;
+        to_domain(
+            &pool.id,
+            pool.address,
+            pool.gas_estimate,
+            reserves,
+            &pool.fee,
+            liquidity::balancer_v3::Kind::Weighted(weights),
+        )
+    }
+
+    pub fn stable_to_domain(pool: &BalancerV3StablePool) -> Result<liquidity::Liquidity, Error> {
+        let reserves = pool
+            .tokens
+            .iter()
+            .map(|(address, token)| {
+                reserve(*address, token.balance, &token.scaling_factor, &token.rate)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        to_domain(
+            &pool.id,
+            pool.address,
+            pool.gas_estimate,
+            reserves,
+            &pool.fee,
+            liquidity::balancer_v3::Kind::Stable {
+                amplification_parameter: conv::decimal_to_rational(&pool.amplification_parameter)
+                    .ok_or("invalid amplification parameter")?,
+            },
+        )
+    }
+
+    fn reserve(
+        address: eth::H160,
+        balance: eth::U256,
+        scaling_factor: &bigdecimal::BigDecimal,
+        rate: &bigdecimal::BigDecimal,
+    ) -> Result<liquidity::balancer_v3::Reserve, Error> {
+        Ok(liquidity::balancer_v3::Reserve {
+            asset: eth::Asset {
+                token: eth::TokenAddress(address),
+                amount: balance,
+            },
+            scale: conv::decimal_to_rational(scaling_factor)
+                .and_then(liquidity::ScalingFactor::new)
+                .ok_or("invalid token scaling factor")?,
+            rate: conv::decimal_to_rational(rate).ok_or("invalid token rate")?,
+        })
+    }
+
+    fn to_domain(
+        id: &str,
+        address: eth::H160,
+        gas_estimate: eth::U256,
+        reserves: Vec<liquidity::balancer_v3::Reserve>,
+        fee: &bigdecimal::BigDecimal,
+        kind: liquidity::balancer_v3::Kind,
+    ) -> Result<liquidity::Liquidity, Error> {
+        let fee = conv::decimal_to_rational(fee).ok_or("invalid Balancer V3 pool fee")?;
+        Ok(liquidity::Liquidity {
+            id: liquidity::Id(id.to_owned()),
+            address,
+            gas: eth::Gas(gas_estimate),
+            state: liquidity::State::BalancerV3(
+                liquidity::balancer_v3::Pool::new(reserves, fee, kind)
+                    .ok_or("invalid Balancer V3 pool tokens")?,
+            ),
+        })
+    }
+}
+
+mod uniswap_v4_pool {
+    use {super::*, itertools::Itertools};
+
+    /// Uniswap V4 pools share the concentrated liquidity math of Uniswap V3
+    /// pools. They are swapped through the universal router, so the router
+    /// is used as the liquidity address.
+    pub fn to_domain(pool: &UniswapV4Pool) -> Result<liquidity::Liquidity, Error> {
+        let tokens = {
+            let (a, b) = pool
+                .tokens
+                .iter()
+                .copied()
+                .map(eth::TokenAddress)
+                .collect_tuple()
+                .ok_or("invalid number of Uniswap V4 pool tokens")?;
+            liquidity::TokenPair::new(a, b).ok_or("duplicate Uniswap V4 pool token address")?
+        };
+
+        Ok(liquidity::Liquidity {
+            id: liquidity::Id(pool.id.clone()),
+            address: pool.router,
+            gas: eth::Gas(pool.gas_estimate),
+            state: liquidity::State::Concentrated(liquidity::concentrated::Pool {
+                tokens,
+                sqrt_price: liquidity::concentrated::SqrtPrice(pool.sqrt_price),
+                liquidity: liquidity::concentrated::Amount(pool.liquidity),
+                tick: liquidity::concentrated::Tick(pool.tick),
+                liquidity_net: pool
+                    .liquidity_net
+                    .iter()
+                    .map(|(tick, liquidity)| {
+                        (
+                            liquidity::concentrated::Tick(*tick),
+                            liquidity::concentrated::LiquidityNet(*liquidity),
+                        )
+                    })
+                    .collect(),
+                fee: liquidity::concentrated::Fee(
+                    conv::decimal_to_rational(&pool.fee).ok_or("invalid Uniswap V4 pool fee")?,
+                ),
+            }),
+        })
+    }
+}
+
 mod foreign_limit_order {
     use super::*;
 
@@ -3,7 +3,7 @@
 use {
     crate::{
         boundary,
-        domain::{eth, liquidity, order, solver},
+        domain::{eth, liquidity, order, solver::baseline},
     },
     ethereum_types::{H160, U256},
     model::TokenPair,
@@ -33,7 +33,11 @@ impl<'a> Solver<'a> {
         }
     }
 
-    pub fn route(&self, request: solver::Request, max_hops: usize) -> Option<solver::Route<'a>> {
+    pub fn route(
+        &self,
+        request: baseline::Request,
+        max_hops: usize,
+    ) -> Option<baseline::Route<'a>> {
         let candidates = self.base_tokens.path_candidates_with_hops(
             request.sell.token.0,
             request.buy.token.0,
@@ -91,7 +95,7 @@ impl<'a> Solver<'a> {
                 .max_by_key(|(_, buy)| buy.value)?,
         };
 
-        solver::Route::new(segments)
+        baseline::Route::new(segments)
     }
 
     fn traverse_path(
@@ -99,7 +103,7 @@ impl<'a> Solver<'a> {
         path: &[&OnchainLiquidity],
         mut sell_token: H160,
         mut sell_amount: U256,
-    ) -> Option<Vec<solver::Segment<'a>>> {
+    ) -> Option<Vec<baseline::Segment<'a>>> {
         let mut segments = Vec::new();
         for liquidity in path {
             let reference_liquidity = self
@@ -112,23 +116,38 @@ impl<'a> Solver<'a> {
                 .other(&sell_token)
                 .expect("Inconsistent path");
             let buy_amount = liquidity.get_amount_out(buy_token, (sell_amount, sell_token))?;
+            let input = eth::Asset {
+                token: eth::TokenAddress(sell_token),
+                amount: sell_amount,
+            };
+            let gas = match &liquidity.source {
+                LiquiditySource::Concentrated(pool) => pool.swap_gas(&input)?,
+                LiquiditySource::CowAmm(pool) => pool.gas,
+                _ => eth::Gas(liquidity.gas_cost().into()),
+            };
 
-            segments.push(solver::Segment {
+            segments.push(baseline::Segment {
                 liquidity: reference_liquidity,
-                input: eth::Asset {
-                    token: eth::TokenAddress(sell_token),
-                    amount: sell_amount,
-                },
+                input,
                 output: eth::Asset {
                     token: eth::TokenAddress(buy_token),
                     amount: buy_amount,
                 },
-                gas: eth::Gas(liquidity.gas_cost().into()),
+                gas,
             });
 
             sell_token = buy_token;
             sell_amount = buy_amount;
         }
+
+        // CoW AMM orders are settled at the uniform clearing prices of the
+        // solution, which only exist for the tokens of the order being solved.
+        let has_cow_amm = segments
+            .iter()
+            .any(|segment| matches!(segment.liquidity.state, liquidity::State::CowAmm(_)));
+        if has_cow_amm && segments.len() > 1 {
+            return None;
+        }
         Some(segments)
     }
 }
@@ -192,6 +211,73 @@ fn to_boundary_liquidity(
                         }
                     }
                 }
+                liquidity::State::Concentrated(pool) => {
+                    let token_pair = to_boundary_token_pair(&pool.tokens);
+                    onchain_liquidity
+                        .entry(token_pair)
+                        .or_default()
+                        .push(OnchainLiquidity {
+                            id: liquidity.id.clone(),
+                            token_pair,
+                            source: LiquiditySource::Concentrated(
+                                boundary::liquidity::concentrated::Pool {
+                                    pool: pool.clone(),
+                                    gas: liquidity.gas,
+                                },
+                            ),
+                        });
+                }
+                liquidity::State::CowAmm(pool) => {
+                    if let Some(boundary_pool) =
+                        boundary::liquidity::cow_amm::to_boundary_pool(liquidity.gas, pool)
+                    {
+                        let token_pair = boundary_pool.tokens;
+                        onchain_liquidity
+                            .entry(token_pair)
+                            .or_default()
+                            .push(OnchainLiquidity {
+                                id: liquidity.id.clone(),
+                                token_pair,
+                                source: LiquiditySource::CowAmm(boundary_pool),
+                            });
+                    }
+                }
+                liquidity::State::Curve(pool) => {
+                    let boundary_pool = boundary::liquidity::curve::to_boundary_pool(
+                        liquidity.address,
+                        liquidity.gas,
+                        pool,
+                    );
+                    for pair in pool.token_pairs() {
+                        let token_pair = to_boundary_token_pair(&pair);
+                        onchain_liquidity
+                            .entry(token_pair)
+                            .or_default()
+                            .push(OnchainLiquidity {
+                                id: liquidity.id.clone(),
+                                token_pair,
+                                source: LiquiditySource::Curve(boundary_pool.clone()),
+                            });
+                    }
+                }
+                liquidity::State::BalancerV3(pool) => {
+                    if let Some(boundary_pool) = boundary::liquidity::balancer_v3::to_boundary_pool(
+                        liquidity.address,
+                        liquidity.gas,
+                        pool,
+                    ) {
+                        for pair in pool.token_pairs() {
+                            let token_pair = to_boundary_token_pair(&pair);
+                            onchain_liquidity.entry(token_pair).or_default().push(
+                                OnchainLiquidity {
+                                    id: liquidity.id.clone(),
+                                    token_pair,
+                                    source: LiquiditySource::BalancerV3(boundary_pool.clone()),
+                                },
+                            );
+                        }
+                    }
+                }
                 liquidity::State::LimitOrder(limit_order) => {
                     if let Some(token_pair) =
                         TokenPair::new(limit_order.maker.token.0, limit_order.taker.token.0)
@@ -206,8 +292,6 @@ fn to_boundary_liquidity(
                             })
                     }
                 }
-                // The baseline solver does not currently support other AMMs.
-                _ => {}
             };
             onchain_liquidity
         })
@@ -225,7 +309,11 @@ enum LiquiditySource {
     ConstantProduct(boundary::liquidity::constant_product::Pool),
     WeightedProduct(boundary::liquidity::weighted_product::Pool),
     Stable(boundary::liquidity::stable::Pool),
+    Concentrated(boundary::liquidity::concentrated::Pool),
     LimitOrder(liquidity::limit_order::LimitOrder),
+    CowAmm(boundary::liquidity::cow_amm::Pool),
+    Curve(boundary::liquidity::curve::Pool),
+    BalancerV3(boundary::liquidity::balancer_v3::Pool),
 }
 
 impl BaselineSolvable for OnchainLiquidity {
@@ -234,9 +322,13 @@ impl BaselineSolvable for OnchainLiquidity {
             LiquiditySource::ConstantProduct(pool) => pool.get_amount_out(out_token, input),
             LiquiditySource::WeightedProduct(pool) => pool.get_amount_out(out_token, input),
             LiquiditySource::Stable(pool) => pool.get_amount_out(out_token, input),
+            LiquiditySource::Concentrated(pool) => pool.get_amount_out(out_token, input),
             LiquiditySource::LimitOrder(limit_order) => {
                 limit_order.get_amount_out(out_token, input)
             }
+            LiquiditySource::CowAmm(pool) => pool.get_amount_out(out_token, input),
+            LiquiditySource::Curve(pool) => pool.get_amount_out(out_token, input),
+            LiquiditySource::BalancerV3(pool) => pool.get_amount_out(out_token, input),
         }
     }
 
@@ -245,7 +337,11 @@ impl BaselineSolvable for OnchainLiquidity {
             LiquiditySource::ConstantProduct(pool) => pool.get_amount_in(in_token, out),
             LiquiditySource::WeightedProduct(pool) => pool.get_amount_in(in_token, out),
             LiquiditySource::Stable(pool) => pool.get_amount_in(in_token, out),
+            LiquiditySource::Concentrated(pool) => pool.get_amount_in(in_token, out),
             LiquiditySource::LimitOrder(limit_order) => limit_order.get_amount_in(in_token, out),
+            LiquiditySource::CowAmm(pool) => pool.get_amount_in(in_token, out),
+            LiquiditySource::Curve(pool) => pool.get_amount_in(in_token, out),
+            LiquiditySource::BalancerV3(pool) => pool.get_amount_in(in_token, out),
         }
     }
 
@@ -254,7 +350,11 @@ impl BaselineSolvable for OnchainLiquidity {
             LiquiditySource::ConstantProduct(pool) => pool.gas_cost(),
             LiquiditySource::WeightedProduct(pool) => pool.gas_cost(),
             LiquiditySource::Stable(pool) => pool.gas_cost(),
+            LiquiditySource::Concentrated(pool) => pool.gas_cost(),
             LiquiditySource::LimitOrder(limit_order) => limit_order.gas_cost(),
+            LiquiditySource::CowAmm(pool) => pool.gas_cost(),
+            LiquiditySource::Curve(pool) => pool.gas_cost(),
+            LiquiditySource::BalancerV3(pool) => pool.gas_cost(),
         }
     }
 }
@@ -0,0 +1,90 @@
+use {
+    crate::domain::{eth, liquidity},
+    ethereum_types::{H160, U256},
+    shared::{
+        baseline_solver::BaselineSolvable,
+        sources::{
+            balancer_v2::{pool_fetching::AmplificationParameter, swap::fixed_point::Bfp},
+            balancer_v3::{self, PoolKind, TokenState},
+        },
+    },
+};
+
+/// A Balancer V3 pool together with the gas estimate for a swap.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub pool: balancer_v3::Pool,
+    pub gas: eth::Gas,
+}
+
+impl BaselineSolvable for Pool {
+    fn get_amount_out(&self, out_token: H160, input: (U256, H160)) -> Option<U256> {
+        self.pool.get_amount_out(out_token, input)
+    }
+
+    fn get_amount_in(&self, in_token: H160, out: (U256, H160)) -> Option<U256> {
+        self.pool.get_amount_in(in_token, out)
+    }
+
+    fn gas_cost(&self) -> usize {
+        usize::try_from(self.gas.0).unwrap_or(usize::MAX)
+    }
+}
+
+/// Converts a domain pool into a [`shared`] Balancer V3 pool. Returns `None`
+/// if the domain pool cannot be represented as a boundary pool.
+pub fn to_boundary_pool(
+    address: H160,
+    gas: eth::Gas,
+    pool: &liquidity::balancer_v3::Pool,
+) -> Option<Pool> {
+    let reserves = pool
+        .reserves
+        .iter()
+        .map(|reserve| {
+            let scale = reserve.scale.get();
+            Some(TokenState {
+                token: reserve.asset.token.0,
+                balance: reserve.asset.amount,
+                scaling_factor: scale.numer().checked_div(*scale.denom())?,
+                rate: to_fixed_point(&reserve.rate)?,
+            })
+        })
+        .collect::<Option<_>>()?;
+    let kind = match &pool.kind {
+        liquidity::balancer_v3::Kind::Weighted(weights) => {
+            PoolKind::Weighted(weights.iter().map(to_fixed_point).collect::<Option<_>>()?)
+        }
+        liquidity::balancer_v3::Kind::Stable {
+            amplification_parameter,
+        } => PoolKind::Stable(
+            AmplificationParameter::try_new(
+                *amplification_parameter.numer(),
+                *amplification_parameter.denom(),
+            )
+            .ok()?,
+        ),
+    };
+
+    Some(Pool {
+        pool: balancer_v3::Pool {
+            address,
+            reserves,
+            swap_fee: to_fixed_point(&pool.fee)?,
+            // Hooks only affect the gas estimate, which is provided separately.
+            hooks: None,
+            kind,
+        },
+        gas,
+    })
+}
+
+/// Converts a rational to a Balancer fixed point number.
+fn to_fixed_point(ratio: &eth::Rational) -> Option<Bfp> {
+    let base = U256::exp10(18);
+    let wei = ratio
+        .numer()
+        .checked_mul(base)?
+        .checked_div(*ratio.denom())?;
+    Some(Bfp::from_wei(wei))
+}
@@ -0,0 +1,43 @@
+use {
+    crate::domain::{eth, liquidity::concentrated},
+    ethereum_types::{H160, U256},
+    shared::baseline_solver::BaselineSolvable,
+};
+
+/// A concentrated liquidity pool together with the gas estimate for a swap
+/// that does not cross any initialized ticks.
+#[derive(Debug)]
+pub struct Pool {
+    pub pool: concentrated::Pool,
+    pub gas: eth::Gas,
+}
+
+impl Pool {
+    /// Returns the gas needed to swap `input` through the pool, including the
+    /// cost of all initialized ticks the swap crosses.
+    pub fn swap_gas(&self, input: &eth::Asset) -> Option<eth::Gas> {
+        Some(self.pool.exact_input(input)?.gas(self.gas))
+    }
+}
+
+impl BaselineSolvable for Pool {
+    fn get_amount_out(&self, out_token: H160, (in_amount, in_token): (U256, H160)) -> Option<U256> {
+        let swap = self.pool.exact_input(&eth::Asset {
+            token: eth::TokenAddress(in_token),
+            amount: in_amount,
+        })?;
+        (swap.output.token.0 == out_token).then_some(swap.output.amount)
+    }
+
+    fn get_amount_in(&self, in_token: H160, (out_amount, out_token): (U256, H160)) -> Option<U256> {
+        let swap = self.pool.exact_output(&eth::Asset {
+            token: eth::TokenAddress(out_token),
+            amount: out_amount,
+        })?;
+        (swap.input.token.0 == in_token).then_some(swap.input.amount)
+    }
+
+    fn gas_cost(&self) -> usize {
+        usize::try_from(self.gas.0).unwrap_or(usize::MAX)
+    }
+}
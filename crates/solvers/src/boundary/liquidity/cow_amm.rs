@@ -0,0 +1,49 @@
+use {
+    crate::domain::{eth, liquidity::cow_amm},
+    ethereum_types::{H160, U256},
+    model::TokenPair,
+    shared::baseline_solver::BaselineSolvable,
+};
+
+/// A CoW AMM that only trades its fixed rebalancing order. The order is
+/// settled at uniform clearing prices, so a route can only use it if it trades
+/// exactly the order's amounts.
+#[derive(Debug)]
+pub struct Pool {
+    pub tokens: TokenPair,
+    pub sell: eth::Asset,
+    pub buy: eth::Asset,
+    pub gas: eth::Gas,
+}
+
+impl BaselineSolvable for Pool {
+    fn get_amount_out(&self, out_token: H160, (in_amount, in_token): (U256, H160)) -> Option<U256> {
+        (out_token == self.sell.token.0
+            && in_token == self.buy.token.0
+            && in_amount == self.buy.amount)
+            .then_some(self.sell.amount)
+    }
+
+    fn get_amount_in(&self, in_token: H160, (out_amount, out_token): (U256, H160)) -> Option<U256> {
+        (in_token == self.buy.token.0
+            && out_token == self.sell.token.0
+            && out_amount == self.sell.amount)
+            .then_some(self.buy.amount)
+    }
+
+    fn gas_cost(&self) -> usize {
+        usize::try_from(self.gas.0).unwrap_or(usize::MAX)
+    }
+}
+
+/// Converts a domain CoW AMM into a boundary pool that can be used for path
+/// finding. Returns `None` if the pool cannot be represented as a boundary
+/// pool.
+pub fn to_boundary_pool(gas: eth::Gas, pool: &cow_amm::Pool) -> Option<Pool> {
+    Some(Pool {
+        tokens: TokenPair::new(pool.order.sell.token.0, pool.order.buy.token.0)?,
+        sell: pool.order.sell,
+        buy: pool.order.buy,
+        gas,
+    })
+}
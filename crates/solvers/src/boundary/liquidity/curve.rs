@@ -0,0 +1,66 @@
+use {
+    crate::domain::{eth, liquidity::curve},
+    ethereum_types::{H160, U256},
+    shared::{
+        baseline_solver::BaselineSolvable,
+        sources::curve::{
+            crypto_math::CryptoPoolState,
+            pool_fetching::{self, PoolState},
+            stable_math::StablePoolState,
+        },
+    },
+};
+
+/// A Curve pool together with the gas estimate for a swap.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub pool: pool_fetching::Pool,
+    pub gas: eth::Gas,
+}
+
+impl BaselineSolvable for Pool {
+    fn get_amount_out(&self, out_token: H160, input: (U256, H160)) -> Option<U256> {
+        self.pool.get_amount_out(out_token, input)
+    }
+
+    fn get_amount_in(&self, in_token: H160, out: (U256, H160)) -> Option<U256> {
+        self.pool.get_amount_in(in_token, out)
+    }
+
+    fn gas_cost(&self) -> usize {
+        usize::try_from(self.gas.0).unwrap_or(usize::MAX)
+    }
+}
+
+/// Converts a domain pool into a [`shared`] Curve pool.
+pub fn to_boundary_pool(address: H160, gas: eth::Gas, pool: &curve::Pool) -> Pool {
+    let state = match &pool.kind {
+        curve::Kind::Stable(state) => PoolState::Stable(StablePoolState {
+            balances: state.balances.clone(),
+            rates: state.rates.clone(),
+            amplification: state.amplification_parameter,
+            fee: state.fee,
+            offpeg_fee_multiplier: state.offpeg_fee_multiplier,
+        }),
+        curve::Kind::Crypto(state) => PoolState::Crypto(CryptoPoolState {
+            balances: state.balances.clone(),
+            precisions: state.precisions.clone(),
+            price_scale: state.price_scale.clone(),
+            amplification: state.amplification_parameter,
+            gamma: state.gamma,
+            d: state.d,
+            mid_fee: state.mid_fee,
+            out_fee: state.out_fee,
+            fee_gamma: state.fee_gamma,
+        }),
+    };
+
+    Pool {
+        pool: pool_fetching::Pool {
+            address,
+            tokens: pool.tokens.iter().map(|token| token.0).collect(),
+            state,
+        },
+        gas,
+    }
+}
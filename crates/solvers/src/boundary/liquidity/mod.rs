@@ -1,4 +1,8 @@
+pub mod balancer_v3;
+pub mod concentrated;
 pub mod constant_product;
+pub mod cow_amm;
+pub mod curve;
 mod limit_order;
 pub mod stable;
 pub mod weighted_product;
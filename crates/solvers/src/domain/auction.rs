@@ -60,8 +60,12 @@ pub struct Token {
     pub reference_price: Option<Price>,
     pub available_balance: U256,
     pub trusted: bool,
+    /// The fee the token takes on every transfer.
+    pub transfer_fee: Option<TransferFee>,
 }
 
+pub use shared::bad_token::TransferFee;
+
 /// The price of a token in wei. This represents how much wei is needed to buy
 /// 10**18 of another token.
 #[derive(Clone, Copy, Debug)]
@@ -0,0 +1,178 @@
+//! Modelling swaps returned by DEX aggregator APIs.
+
+use {
+    crate::domain::{auction, eth, order, solution},
+    ethereum_types::{U256, U512},
+};
+
+/// An order to route through a DEX aggregator.
+#[derive(Clone, Debug)]
+pub struct Order {
+    pub sell: eth::TokenAddress,
+    pub buy: eth::TokenAddress,
+    pub side: order::Side,
+    /// The fixed amount of the order. This is the sell amount for sell orders
+    /// and the buy amount for buy orders.
+    pub amount: U256,
+}
+
+impl Order {
+    pub fn new(order: &order::Order) -> Self {
+        Self {
+            sell: order.sell.token,
+            buy: order.buy.token,
+            side: order.side,
+            amount: match order.side {
+                order::Side::Buy => order.buy.amount,
+                order::Side::Sell => order.sell.amount,
+            },
+        }
+    }
+}
+
+/// A relative slippage tolerance in basis points.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Slippage(u32);
+
+impl Slippage {
+    const BPS: u32 = 10_000;
+
+    /// Creates a new slippage tolerance. Returns `None` if the tolerance is
+    /// 100% or more.
+    pub fn from_bps(bps: u32) -> Option<Self> {
+        (bps < Self::BPS).then_some(Self(bps))
+    }
+
+    pub fn bps(&self) -> u32 {
+        self.0
+    }
+
+    /// Adds the slippage tolerance to an amount, rounding up.
+    pub fn add(&self, amount: U256) -> U256 {
+        amount.saturating_add(self.tolerance(amount))
+    }
+
+    /// Subtracts the slippage tolerance from an amount, rounding down.
+    pub fn sub(&self, amount: U256) -> U256 {
+        amount.saturating_sub(self.tolerance(amount))
+    }
+
+    fn tolerance(&self, amount: U256) -> U256 {
+        let bps = U512::from(Self::BPS);
+        let tolerance = (amount.full_mul(self.0.into()) + bps - U512::one()) / bps;
+        tolerance.try_into().unwrap_or(U256::MAX)
+    }
+}
+
+/// A call to the contract executing a swap.
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub to: eth::ContractAddress,
+    pub calldata: Vec<u8>,
+}
+
+/// An allowance the settlement contract needs to grant to execute a swap.
+#[derive(Clone, Debug)]
+pub struct Allowance {
+    pub spender: eth::ContractAddress,
+    pub amount: U256,
+}
+
+/// A swap quoted by a DEX aggregator.
+#[derive(Clone, Debug)]
+pub struct Swap {
+    pub call: Call,
+    pub value: eth::Ether,
+    pub input: eth::Asset,
+    pub output: eth::Asset,
+    pub allowance: Allowance,
+    pub gas: eth::Gas,
+}
+
+impl Swap {
+    /// Applies the slippage tolerance to the quoted amounts. The fixed amount
+    /// of the order stays the same while the other amount gets the worst
+    /// price the aggregator may execute the swap at.
+    pub fn with_slippage(self, side: order::Side, slippage: &Slippage) -> Self {
+        let (input, output) = match side {
+            order::Side::Buy => (
+                eth::Asset {
+                    amount: slippage.add(self.input.amount),
+                    ..self.input
+                },
+                self.output,
+            ),
+            order::Side::Sell => (
+                self.input,
+                eth::Asset {
+                    amount: slippage.sub(self.output.amount),
+                    ..self.output
+                },
+            ),
+        };
+        Self {
+            allowance: Allowance {
+                amount: self.allowance.amount.max(input.amount),
+                ..self.allowance
+            },
+            input,
+            output,
+            ..self
+        }
+    }
+
+    /// Converts the swap into a solution for the order. Returns `None` if the
+    /// swap does not satisfy the order's limit price.
+    pub fn into_solution(
+        self,
+        order: order::Order,
+        gas_price: auction::GasPrice,
+        sell_token_price: auction::Price,
+        gas_offset: eth::SignedGas,
+    ) -> Option<solution::Solution> {
+        let gas = self.gas + gas_offset;
+        let fee = sell_token_price
+            .ether_value(eth::Ether(gas.0.checked_mul(gas_price.0.0)?))?
+            .into();
+
+        solution::Single {
+            order,
+            input: self.input,
+            output: self.output,
+            interactions: vec![solution::Interaction::Custom(solution::CustomInteraction {
+                target: self.call.to.0,
+                value: self.value,
+                calldata: self.call.calldata,
+                internalize: false,
+                inputs: vec![self.input],
+                outputs: vec![self.output],
+                allowances: vec![solution::Allowance {
+                    spender: self.allowance.spender.0,
+                    asset: eth::Asset {
+                        token: self.input.token,
+                        amount: self.allowance.amount,
+                    },
+                }],
+            })],
+            gas,
+        }
+        .into_solution(fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slippage_rounds_against_the_user() {
+        let slippage = Slippage::from_bps(100).unwrap();
+        assert_eq!(slippage.bps(), 100);
+        assert_eq!(slippage.add(1_000.into()), 1_010.into());
+        assert_eq!(slippage.sub(1_000.into()), 990.into());
+        assert_eq!(slippage.add(1_001.into()), 1_012.into());
+        assert_eq!(slippage.sub(1_001.into()), 990.into());
+        assert_eq!(slippage.add(U256::MAX), U256::MAX);
+        assert_eq!(Slippage::from_bps(10_000), None);
+    }
+}
@@ -82,7 +82,7 @@ pub struct Tx {
 
 /// An arbitrary ethereum interaction that is required for the settlement
 /// execution.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Interaction {
     pub target: Address,
     pub value: Ether,
@@ -0,0 +1,359 @@
+//! Feedback about proposed solutions received through notifications.
+//!
+//! The solver remembers which liquidity every proposed solution used.
+//! Liquidity that is repeatedly implicated in failing simulations or reverting
+//! settlements gets avoided for a while, so a single broken pool does not make
+//! the solver propose failing solutions auction after auction.
+//!
+//! Failures are deliberately not attributed to tokens: almost every solution
+//! routes through WETH or another base token, so one broken pool would quickly
+//! make the solver avoid the most important tokens altogether.
+
+use {
+    crate::domain::{auction, liquidity, notification, solution},
+    std::{
+        collections::{HashMap, HashSet},
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// How long proposed solutions are remembered to attribute notifications to
+/// them.
+const SOLUTION_RETENTION: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Number of failures after which liquidity gets avoided.
+    pub failure_threshold: u32,
+    /// For how long liquidity gets avoided.
+    pub cooldown: Duration,
+}
+
+/// Liquidity that should currently not be used.
+#[derive(Debug, Default)]
+pub struct Avoided(pub HashSet<liquidity::Id>);
+
+impl Avoided {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether the liquidity should be avoided.
+    pub fn contains(&self, liquidity: &liquidity::Liquidity) -> bool {
+        self.0.contains(&liquidity.id)
+    }
+}
+
+pub struct Feedback {
+    config: Config,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    solutions: HashMap<(i64, u64), Proposed>,
+    liquidity: Failures,
+}
+
+/// Liquidity used by a proposed solution.
+struct Proposed {
+    liquidity: HashSet<liquidity::Id>,
+    at: Instant,
+}
+
+/// Consecutive failures of liquidity and until when it is avoided.
+#[derive(Default)]
+struct Failures(HashMap<liquidity::Id, Record>);
+
+#[derive(Default)]
+struct Record {
+    failures: u32,
+    avoided_until: Option<Instant>,
+}
+
+impl Failures {
+    fn failed<'a>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'a liquidity::Id>,
+        config: &Config,
+        now: Instant,
+    ) {
+        for key in keys {
+            let record = self.0.entry(key.clone()).or_default();
+            record.failures += 1;
+            if record.failures >= config.failure_threshold {
+                record.failures = 0;
+                record.avoided_until = Some(now + config.cooldown);
+            }
+        }
+    }
+
+    fn succeeded<'a>(&mut self, keys: impl IntoIterator<Item = &'a liquidity::Id>) {
+        for key in keys {
+            if let Some(record) = self.0.get_mut(key) {
+                record.failures = 0;
+            }
+        }
+    }
+
+    fn avoided(&mut self, now: Instant) -> HashSet<liquidity::Id> {
+        self.0.retain(|_, record| {
+            record.failures > 0 || record.avoided_until.is_some_and(|until| until > now)
+        });
+        self.0
+            .iter()
+            .filter(|(_, record)| record.avoided_until.is_some_and(|until| until > now))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+}
+
+/// How a notification affects the liquidity of a solution.
+enum Outcome {
+    Failure,
+    Success,
+}
+
+impl Feedback {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            state: Default::default(),
+        }
+    }
+
+    /// Remembers the liquidity used by the proposed solutions.
+    pub fn proposed(&self, auction: auction::Id, solutions: &[solution::Solution]) {
+        self.proposed_at(auction, solutions, Instant::now())
+    }
+
+    /// Updates the failure counts of the liquidity used by the solutions the
+    /// notification is about.
+    pub fn notify(&self, notification: &notification::Notification) {
+        self.notify_at(notification, Instant::now())
+    }
+
+    /// Returns the liquidity that should currently be avoided.
+    pub fn avoided(&self) -> Avoided {
+        self.avoided_at(Instant::now())
+    }
+
+    fn proposed_at(&self, auction: auction::Id, solutions: &[solution::Solution], now: Instant) {
+        let auction::Id::Solve(auction) = auction else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        state
+            .solutions
+            .retain(|_, proposed| now.saturating_duration_since(proposed.at) < SOLUTION_RETENTION);
+        for solution in solutions {
+            let liquidity = solution
+                .interactions
+                .iter()
+                .filter_map(|interaction| match interaction {
+                    solution::Interaction::Liquidity(interaction) => {
+                        Some(interaction.liquidity.id.clone())
+                    }
+                    solution::Interaction::Custom(_) => None,
+                })
+                .collect();
+            state
+                .solutions
+                .insert((auction, solution.id.0), Proposed { liquidity, at: now });
+        }
+    }
+
+    fn notify_at(&self, notification: &notification::Notification, now: Instant) {
+        let outcome = match &notification.kind {
+            notification::Kind::SimulationFailed(..)
+            | notification::Kind::Settled(
+                notification::Settlement::Revert(_) | notification::Settlement::SimulationRevert,
+            ) => Outcome::Failure,
+            notification::Kind::Settled(notification::Settlement::Success(_)) => Outcome::Success,
+            _ => return,
+        };
+        let auction::Id::Solve(auction) = notification.auction_id else {
+            return;
+        };
+        let ids = match &notification.solution_id {
+            Some(notification::Id::Single(id)) => vec![id.0],
+            Some(notification::Id::Merged(ids)) => ids.clone(),
+            None => return,
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let State {
+            solutions,
+            liquidity,
+        } = &mut *state;
+        for proposed in ids.iter().filter_map(|id| solutions.get(&(auction, *id))) {
+            match outcome {
+                Outcome::Failure => liquidity.failed(&proposed.liquidity, &self.config, now),
+                Outcome::Success => liquidity.succeeded(&proposed.liquidity),
+            }
+        }
+    }
+
+    fn avoided_at(&self, now: Instant) -> Avoided {
+        Avoided(self.state.lock().unwrap().liquidity.avoided(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::domain::{eth, liquidity::limit_order},
+        ethereum_types::H160,
+    };
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::TokenAddress(H160::repeat_byte(byte))
+    }
+
+    fn liquidity(id: &str, a: u8, b: u8) -> liquidity::Liquidity {
+        liquidity::Liquidity {
+            id: liquidity::Id(id.to_owned()),
+            address: H160::zero(),
+            gas: eth::Gas(Default::default()),
+            state: liquidity::State::LimitOrder(limit_order::LimitOrder {
+                maker: eth::Asset {
+                    token: token(a),
+                    amount: 1.into(),
+                },
+                taker: eth::Asset {
+                    token: token(b),
+                    amount: 1.into(),
+                },
+                fee: limit_order::TakerAmount(0.into()),
+            }),
+        }
+    }
+
+    fn solution(id: u64, liquidity: &[liquidity::Liquidity]) -> solution::Solution {
+        solution::Solution {
+            id: solution::Id(id),
+            interactions: liquidity
+                .iter()
+                .map(|liquidity| {
+                    let (input, output) = match &liquidity.state {
+                        liquidity::State::LimitOrder(order) => (order.taker, order.maker),
+                        _ => unreachable!(),
+                    };
+                    solution::Interaction::Liquidity(solution::LiquidityInteraction {
+                        liquidity: liquidity.clone(),
+                        input,
+                        output,
+                        internalize: false,
+                    })
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn notification(
+        auction: i64,
+        id: notification::Id,
+        kind: notification::Kind,
+    ) -> notification::Notification {
+        notification::Notification {
+            auction_id: auction::Id::Solve(auction),
+            solution_id: Some(id),
+            kind,
+        }
+    }
+
+    fn revert(auction: i64, id: u64) -> notification::Notification {
+        notification(
+            auction,
+            notification::Id::Single(solution::Id(id)),
+            notification::Kind::Settled(notification::Settlement::Revert(Default::default())),
+        )
+    }
+
+    #[test]
+    fn avoids_repeatedly_failing_liquidity() {
+        let feedback = Feedback::new(Config {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(60),
+        });
+        let bad = liquidity("bad", 1, 2);
+        let good = liquidity("good", 3, 4);
+        let now = Instant::now();
+
+        feedback.proposed_at(
+            auction::Id::Solve(1),
+            &[solution(0, &[bad.clone()]), solution(1, &[good.clone()])],
+            now,
+        );
+        feedback.notify_at(&revert(1, 0), now);
+        assert!(feedback.avoided_at(now).is_empty());
+
+        // Merged solutions attribute the failure to all their parts.
+        feedback.proposed_at(auction::Id::Solve(2), &[solution(0, &[bad.clone()])], now);
+        feedback.notify_at(
+            &notification(
+                2,
+                notification::Id::Merged(vec![0, 1]),
+                notification::Kind::SimulationFailed(0, tx(), false),
+            ),
+            now,
+        );
+        let avoided = feedback.avoided_at(now);
+        assert_eq!(avoided.0, HashSet::from([bad.id.clone()]));
+        assert!(avoided.contains(&bad));
+        assert!(!avoided.contains(&good));
+        // Other liquidity trading the same tokens is still used.
+        assert!(!avoided.contains(&liquidity("other", 1, 2)));
+
+        // Liquidity is used again after the cooldown.
+        assert!(
+            feedback
+                .avoided_at(now + Duration::from_secs(61))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn success_resets_failures() {
+        let feedback = Feedback::new(Config {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(60),
+        });
+        let pool = liquidity("pool", 1, 2);
+        let now = Instant::now();
+
+        feedback.proposed_at(auction::Id::Solve(1), &[solution(0, &[pool.clone()])], now);
+        feedback.notify_at(&revert(1, 0), now);
+        feedback.notify_at(
+            &notification(
+                1,
+                notification::Id::Single(solution::Id(0)),
+                notification::Kind::Settled(notification::Settlement::Success(Default::default())),
+            ),
+            now,
+        );
+        feedback.notify_at(&revert(1, 0), now);
+        assert!(feedback.avoided_at(now).is_empty());
+
+        // Notifications for unknown solutions and quotes are ignored.
+        feedback.proposed_at(auction::Id::Quote, &[solution(0, &[pool.clone()])], now);
+        feedback.notify_at(&revert(2, 0), now);
+        assert!(feedback.avoided_at(now).is_empty());
+
+        feedback.notify_at(&revert(1, 0), now);
+        assert!(!feedback.avoided_at(now).is_empty());
+    }
+
+    fn tx() -> eth::Tx {
+        eth::Tx {
+            from: Default::default(),
+            to: Default::default(),
+            value: eth::Ether(Default::default()),
+            input: Default::default(),
+            access_list: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Balancer V3 pools.
+//!
+//! The V3 vault scales balances to 18 decimals and applies token rates before
+//! running the weighted or stable pool math, so reserves carry both a scaling
+//! factor and a rate.
+
+use {
+    crate::domain::{eth, liquidity},
+    itertools::Itertools as _,
+};
+
+/// The state of a Balancer V3 pool.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub reserves: Vec<Reserve>,
+    pub fee: eth::Rational,
+    pub kind: Kind,
+}
+
+/// A Balancer V3 pool token reserve.
+#[derive(Clone, Debug)]
+pub struct Reserve {
+    pub asset: eth::Asset,
+    /// The factor `10^(18 - decimals)` scaling raw amounts to 18 decimals.
+    pub scale: liquidity::ScalingFactor,
+    pub rate: eth::Rational,
+}
+
+#[derive(Clone, Debug)]
+pub enum Kind {
+    /// A weighted pool with the normalized weights of its tokens, in reserve
+    /// order.
+    Weighted(Vec<eth::Rational>),
+    Stable {
+        amplification_parameter: eth::Rational,
+    },
+}
+
+impl Pool {
+    /// Returns a new pool for the specified reserves. Returns `None` if the
+    /// pool has duplicate tokens or, for weighted pools, does not have a
+    /// weight for every token.
+    pub fn new(reserves: Vec<Reserve>, fee: eth::Rational, kind: Kind) -> Option<Self> {
+        let weights_match = match &kind {
+            Kind::Weighted(weights) => weights.len() == reserves.len(),
+            Kind::Stable { .. } => true,
+        };
+        if reserves.len() < 2
+            || !reserves.iter().map(|r| r.asset.token).all_unique()
+            || !weights_match
+        {
+            return None;
+        }
+        Some(Self {
+            reserves,
+            fee,
+            kind,
+        })
+    }
+
+    /// Returns all token pairs that can be traded with the pool.
+    pub fn token_pairs(&self) -> impl Iterator<Item = liquidity::TokenPair> + '_ {
+        self.reserves
+            .iter()
+            .tuple_combinations()
+            .map(|(a, b)| liquidity::TokenPair::new(a.asset.token, b.asset.token).expect("a != b"))
+    }
+}
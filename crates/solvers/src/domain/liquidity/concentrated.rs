@@ -1,6 +1,6 @@
 use {
     crate::domain::{eth, liquidity},
-    ethereum_types::U256,
+    ethereum_types::{U256, U512},
     std::collections::BTreeMap,
 };
 
@@ -15,6 +15,180 @@ pub struct Pool {
     pub fee: Fee,
 }
 
+impl Pool {
+    /// Simulates swapping exactly `input` for the other token of the pool.
+    /// Returns `None` if the token is not traded by the pool or if the pool
+    /// does not have enough liquidity to fully execute the swap.
+    pub fn exact_input(&self, input: &eth::Asset) -> Option<Swap> {
+        let (output_token, zero_for_one) = self.other(input.token)?;
+        let result = self.swap(zero_for_one, Specified::Input(input.amount))?;
+        Some(Swap {
+            input: *input,
+            output: eth::Asset {
+                token: output_token,
+                amount: result.calculated,
+            },
+            ticks_crossed: result.ticks_crossed,
+        })
+    }
+
+    /// Simulates swapping the other token of the pool for exactly `output`.
+    /// Returns `None` if the token is not traded by the pool or if the pool
+    /// does not have enough liquidity to fully execute the swap.
+    pub fn exact_output(&self, output: &eth::Asset) -> Option<Swap> {
+        let (input_token, output_is_token0) = self.other(output.token)?;
+        let result = self.swap(!output_is_token0, Specified::Output(output.amount))?;
+        Some(Swap {
+            input: eth::Asset {
+                token: input_token,
+                amount: result.calculated,
+            },
+            output: *output,
+            ticks_crossed: result.ticks_crossed,
+        })
+    }
+
+    /// Returns the other token of the pool and whether `token` is token 0.
+    fn other(&self, token: eth::TokenAddress) -> Option<(eth::TokenAddress, bool)> {
+        let (token0, token1) = self.tokens.get();
+        match token {
+            token if token == token0 => Some((token1, true)),
+            token if token == token1 => Some((token0, false)),
+            _ => None,
+        }
+    }
+
+    /// Port of the swap loop of the Uniswap V3 pool contract. Instead of
+    /// searching the tick bitmap word by word, the loop directly steps to the
+    /// next initialized tick, which can cause differences of a few wei due to
+    /// rounding.
+    fn swap(&self, zero_for_one: bool, specified: Specified) -> Option<SwapResult> {
+        let (mut remaining, exact_input) = match specified {
+            Specified::Input(amount) => (amount, true),
+            Specified::Output(amount) => (amount, false),
+        };
+        // The pool contract takes the amount as a signed integer.
+        if remaining.is_zero() || remaining.bit(255) {
+            return None;
+        }
+        let fee = self.fee.pips()?;
+        let price_limit = if zero_for_one {
+            math::MIN_SQRT_RATIO + 1
+        } else {
+            math::MAX_SQRT_RATIO - 1
+        };
+
+        let mut sqrt_price = self.sqrt_price.0;
+        let mut tick = self.tick.0;
+        let mut liquidity = self.liquidity.0;
+        let mut calculated = U256::zero();
+        let mut ticks_crossed = 0;
+
+        while !remaining.is_zero() {
+            if sqrt_price == price_limit {
+                // Not enough liquidity to fully execute the swap.
+                return None;
+            }
+
+            let next = if zero_for_one {
+                self.liquidity_net.range(..=Tick(tick)).next_back()
+            } else {
+                self.liquidity_net.range(Tick(tick + 1)..).next()
+            };
+            let tick_next = next
+                .map(|(next, _)| next.0)
+                .unwrap_or(if zero_for_one {
+                    math::MIN_TICK
+                } else {
+                    math::MAX_TICK
+                })
+                .clamp(math::MIN_TICK, math::MAX_TICK);
+            let sqrt_price_next = math::sqrt_ratio_at_tick(tick_next)?;
+            let target = if (zero_for_one && sqrt_price_next < price_limit)
+                || (!zero_for_one && sqrt_price_next > price_limit)
+            {
+                price_limit
+            } else {
+                sqrt_price_next
+            };
+
+            let step = math::compute_swap_step(
+                sqrt_price,
+                target,
+                liquidity,
+                remaining,
+                exact_input,
+                fee,
+            )?;
+            sqrt_price = step.sqrt_price_next;
+            if exact_input {
+                remaining = remaining.checked_sub(step.amount_in.checked_add(step.fee_amount)?)?;
+                calculated = calculated.checked_add(step.amount_out)?;
+            } else {
+                remaining = remaining.checked_sub(step.amount_out)?;
+                calculated =
+                    calculated.checked_add(step.amount_in.checked_add(step.fee_amount)?)?;
+            }
+
+            if sqrt_price == sqrt_price_next {
+                if let Some((_, net)) = next.filter(|(next, _)| next.0 == tick_next) {
+                    let net = if zero_for_one {
+                        net.0.checked_neg()?
+                    } else {
+                        net.0
+                    };
+                    liquidity = math::add_delta(liquidity, net)?;
+                    ticks_crossed += 1;
+                }
+                tick = if zero_for_one {
+                    tick_next - 1
+                } else {
+                    tick_next
+                };
+            }
+        }
+
+        Some(SwapResult {
+            calculated,
+            ticks_crossed,
+        })
+    }
+}
+
+/// The amount of a swap that is fixed.
+#[derive(Clone, Copy, Debug)]
+enum Specified {
+    Input(U256),
+    Output(U256),
+}
+
+struct SwapResult {
+    /// The output amount for exact input swaps and the input amount including
+    /// fees for exact output swaps.
+    calculated: U256,
+    ticks_crossed: u32,
+}
+
+/// A simulated swap through a concentrated liquidity pool.
+#[derive(Clone, Copy, Debug)]
+pub struct Swap {
+    pub input: eth::Asset,
+    pub output: eth::Asset,
+    /// The number of initialized ticks the swap crosses.
+    pub ticks_crossed: u32,
+}
+
+/// Approximate gas cost of crossing an initialized tick during a swap.
+pub const GAS_PER_TICK_CROSSED: u64 = 25_000;
+
+impl Swap {
+    /// Returns the estimated gas cost of the swap given the estimate for a
+    /// swap that stays within the current tick.
+    pub fn gas(&self, base: eth::Gas) -> eth::Gas {
+        eth::Gas(base.0 + U256::from(GAS_PER_TICK_CROSSED) * self.ticks_crossed)
+    }
+}
+
 /// A compressed representation of the current exchange rate between the tokens
 /// belonging to a pool.
 ///
@@ -48,3 +222,433 @@ pub struct LiquidityNet(pub i128);
 /// added by the uniswap DAO.
 #[derive(Clone, Debug)]
 pub struct Fee(pub eth::Rational);
+
+impl Fee {
+    /// Returns the fee in hundredths of a basis point as used by the pool
+    /// contract. Returns `None` for fees of 100% or more.
+    fn pips(&self) -> Option<u32> {
+        let pips = self.0.numer().checked_mul(math::PIPS.into())? / self.0.denom();
+        (pips < math::PIPS.into()).then(|| pips.as_u32())
+    }
+}
+
+/// Ports of the Uniswap V3 `TickMath`, `SqrtPriceMath` and `SwapMath`
+/// libraries. Reverts in the contracts are represented by returning `None`.
+mod math {
+    use super::*;
+
+    pub const MIN_TICK: i32 = -887272;
+    pub const MAX_TICK: i32 = 887272;
+    pub const MIN_SQRT_RATIO: U256 = U256([4295128739, 0, 0, 0]);
+    pub const MAX_SQRT_RATIO: U256 = U256([0x5d951d5263988d26, 0xefd1fc6a50648849, 0xfffd8963, 0]);
+    /// The fee denominator.
+    pub const PIPS: u32 = 1_000_000;
+    const RESOLUTION: usize = 96;
+
+    /// Returns the sqrt price as a Q64.96 for the given tick.
+    pub fn sqrt_ratio_at_tick(tick: i32) -> Option<U256> {
+        const FACTORS: [(u32, u128); 19] = [
+            (0x2, 0xfff97272373d413259a46990580e213a),
+            (0x4, 0xfff2e50f5f656932ef12357cf3c7fdcc),
+            (0x8, 0xffe5caca7e10e4e61c3624eaa0941cd0),
+            (0x10, 0xffcb9843d60f6159c9db58835c926644),
+            (0x20, 0xff973b41fa98c081472e6896dfb254c0),
+            (0x40, 0xff2ea16466c96a3843ec78b326b52861),
+            (0x80, 0xfe5dee046a99a2a811c461f1969c3053),
+            (0x100, 0xfcbe86c7900a88aedcffc83b479aa3a4),
+            (0x200, 0xf987a7253ac413176f2b074cf7815e54),
+            (0x400, 0xf3392b0822b70005940c7a398e4b70f3),
+            (0x800, 0xe7159475a2c29b7443b29c7fa6e889d9),
+            (0x1000, 0xd097f3bdfd2022b8845ad8f792aa5825),
+            (0x2000, 0xa9f746462d870fdf8a65dc1f90e061e5),
+            (0x4000, 0x70d869a156d2a1b890bb3df62baf32f7),
+            (0x8000, 0x31be135f97d08fd981231505542fcfa6),
+            (0x10000, 0x9aa508b5b7a84e1c677de54f3e99bc9),
+            (0x20000, 0x5d6af8dedb81196699c329225ee604),
+            (0x40000, 0x2216e584f5fa1ea926041bedfe98),
+            (0x80000, 0x48a170391f7dc42444e8fa2),
+        ];
+
+        let abs_tick = tick.unsigned_abs();
+        if abs_tick > MAX_TICK.unsigned_abs() {
+            return None;
+        }
+
+        let mut ratio = if abs_tick & 0x1 != 0 {
+            U256::from(0xfffcb933bd6fad37aa2d162d1a594001_u128)
+        } else {
+            U256::one() << 128
+        };
+        for (bit, factor) in FACTORS {
+            if abs_tick & bit != 0 {
+                ratio = (ratio * U256::from(factor)) >> 128;
+            }
+        }
+        if tick > 0 {
+            ratio = U256::MAX / ratio;
+        }
+
+        // Round up to make sure that the resulting price is always at least
+        // the price of the tick.
+        let rounding = U256::from(!(ratio % (U256::one() << 32)).is_zero() as u8);
+        Some((ratio >> 32) + rounding)
+    }
+
+    pub struct SwapStep {
+        pub sqrt_price_next: U256,
+        pub amount_in: U256,
+        pub amount_out: U256,
+        pub fee_amount: U256,
+    }
+
+    /// Computes the result of swapping some amount in or out within a single
+    /// tick range.
+    pub fn compute_swap_step(
+        sqrt_price_current: U256,
+        sqrt_price_target: U256,
+        liquidity: u128,
+        amount_remaining: U256,
+        exact_input: bool,
+        fee_pips: u32,
+    ) -> Option<SwapStep> {
+        let zero_for_one = sqrt_price_current >= sqrt_price_target;
+        let (fee, pips) = (U256::from(fee_pips), U256::from(PIPS));
+
+        let sqrt_price_next;
+        let mut amount_in = U256::zero();
+        let mut amount_out = U256::zero();
+        if exact_input {
+            let amount_remaining_less_fee = mul_div(amount_remaining, pips - fee, pips)?;
+            amount_in = if zero_for_one {
+                amount0_delta(sqrt_price_target, sqrt_price_current, liquidity, true)?
+            } else {
+                amount1_delta(sqrt_price_current, sqrt_price_target, liquidity, true)?
+            };
+            sqrt_price_next = if amount_remaining_less_fee >= amount_in {
+                sqrt_price_target
+            } else {
+                next_sqrt_price_from_input(
+                    sqrt_price_current,
+                    liquidity,
+                    amount_remaining_less_fee,
+                    zero_for_one,
+                )?
+            };
+        } else {
+            amount_out = if zero_for_one {
+                amount1_delta(sqrt_price_target, sqrt_price_current, liquidity, false)?
+            } else {
+                amount0_delta(sqrt_price_current, sqrt_price_target, liquidity, false)?
+            };
+            sqrt_price_next = if amount_remaining >= amount_out {
+                sqrt_price_target
+            } else {
+                next_sqrt_price_from_output(
+                    sqrt_price_current,
+                    liquidity,
+                    amount_remaining,
+                    zero_for_one,
+                )?
+            };
+        }
+
+        // Amounts for reaching the target price were already computed above.
+        let max = sqrt_price_target == sqrt_price_next;
+        let (keep_in, keep_out) = (max && exact_input, max && !exact_input);
+        if zero_for_one {
+            if !keep_in {
+                amount_in = amount0_delta(sqrt_price_next, sqrt_price_current, liquidity, true)?;
+            }
+            if !keep_out {
+                amount_out = amount1_delta(sqrt_price_next, sqrt_price_current, liquidity, false)?;
+            }
+        } else {
+            if !keep_in {
+                amount_in = amount1_delta(sqrt_price_current, sqrt_price_next, liquidity, true)?;
+            }
+            if !keep_out {
+                amount_out = amount0_delta(sqrt_price_current, sqrt_price_next, liquidity, false)?;
+            }
+        }
+
+        // Cap the output amount to not exceed the remaining output amount.
+        if !exact_input && amount_out > amount_remaining {
+            amount_out = amount_remaining;
+        }
+
+        let fee_amount = if exact_input && sqrt_price_next != sqrt_price_target {
+            // Take the remainder of the maximum input as fee.
+            amount_remaining.checked_sub(amount_in)?
+        } else {
+            mul_div_rounding_up(amount_in, fee, pips - fee)?
+        };
+
+        Some(SwapStep {
+            sqrt_price_next,
+            amount_in,
+            amount_out,
+            fee_amount,
+        })
+    }
+
+    /// Adds a signed liquidity delta to the liquidity.
+    pub fn add_delta(liquidity: u128, delta: i128) -> Option<u128> {
+        if delta < 0 {
+            liquidity.checked_sub(delta.unsigned_abs())
+        } else {
+            liquidity.checked_add(delta.unsigned_abs())
+        }
+    }
+
+    fn next_sqrt_price_from_input(
+        sqrt_price: U256,
+        liquidity: u128,
+        amount_in: U256,
+        zero_for_one: bool,
+    ) -> Option<U256> {
+        if sqrt_price.is_zero() || liquidity == 0 {
+            return None;
+        }
+        if zero_for_one {
+            next_sqrt_price_from_amount0_rounding_up(sqrt_price, liquidity, amount_in, true)
+        } else {
+            next_sqrt_price_from_amount1_rounding_down(sqrt_price, liquidity, amount_in, true)
+        }
+    }
+
+    fn next_sqrt_price_from_output(
+        sqrt_price: U256,
+        liquidity: u128,
+        amount_out: U256,
+        zero_for_one: bool,
+    ) -> Option<U256> {
+        if sqrt_price.is_zero() || liquidity == 0 {
+            return None;
+        }
+        if zero_for_one {
+            next_sqrt_price_from_amount1_rounding_down(sqrt_price, liquidity, amount_out, false)
+        } else {
+            next_sqrt_price_from_amount0_rounding_up(sqrt_price, liquidity, amount_out, false)
+        }
+    }
+
+    fn next_sqrt_price_from_amount0_rounding_up(
+        sqrt_price: U256,
+        liquidity: u128,
+        amount: U256,
+        add: bool,
+    ) -> Option<U256> {
+        if amount.is_zero() {
+            return Some(sqrt_price);
+        }
+        let numerator = U256::from(liquidity) << RESOLUTION;
+        let product = amount.checked_mul(sqrt_price);
+        if add {
+            let denominator = product.and_then(|product| numerator.checked_add(product));
+            match denominator {
+                Some(denominator) => mul_div_rounding_up(numerator, sqrt_price, denominator),
+                None => div_rounding_up(numerator, (numerator / sqrt_price).checked_add(amount)?),
+            }
+        } else {
+            let denominator = numerator.checked_sub(product?).filter(|d| !d.is_zero())?;
+            to_uint160(mul_div_rounding_up(numerator, sqrt_price, denominator)?)
+        }
+    }
+
+    fn next_sqrt_price_from_amount1_rounding_down(
+        sqrt_price: U256,
+        liquidity: u128,
+        amount: U256,
+        add: bool,
+    ) -> Option<U256> {
+        let q96 = U256::one() << RESOLUTION;
+        if add {
+            let quotient = mul_div(amount, q96, liquidity.into())?;
+            to_uint160(sqrt_price.checked_add(quotient)?)
+        } else {
+            let quotient = mul_div_rounding_up(amount, q96, liquidity.into())?;
+            sqrt_price
+                .checked_sub(quotient)
+                .filter(|price| !price.is_zero())
+        }
+    }
+
+    /// Returns the amount of token 0 between two prices.
+    fn amount0_delta(
+        sqrt_price_a: U256,
+        sqrt_price_b: U256,
+        liquidity: u128,
+        round_up: bool,
+    ) -> Option<U256> {
+        let (lower, upper) = (
+            sqrt_price_a.min(sqrt_price_b),
+            sqrt_price_a.max(sqrt_price_b),
+        );
+        if lower.is_zero() {
+            return None;
+        }
+        let numerator1 = U256::from(liquidity) << RESOLUTION;
+        let numerator2 = upper - lower;
+        if round_up {
+            div_rounding_up(mul_div_rounding_up(numerator1, numerator2, upper)?, lower)
+        } else {
+            Some(mul_div(numerator1, numerator2, upper)? / lower)
+        }
+    }
+
+    /// Returns the amount of token 1 between two prices.
+    fn amount1_delta(
+        sqrt_price_a: U256,
+        sqrt_price_b: U256,
+        liquidity: u128,
+        round_up: bool,
+    ) -> Option<U256> {
+        let (lower, upper) = (
+            sqrt_price_a.min(sqrt_price_b),
+            sqrt_price_a.max(sqrt_price_b),
+        );
+        let q96 = U256::one() << RESOLUTION;
+        if round_up {
+            mul_div_rounding_up(liquidity.into(), upper - lower, q96)
+        } else {
+            mul_div(liquidity.into(), upper - lower, q96)
+        }
+    }
+
+    fn to_uint160(value: U256) -> Option<U256> {
+        (value.bits() <= 160).then_some(value)
+    }
+
+    fn mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+        if denominator.is_zero() {
+            return None;
+        }
+        (a.full_mul(b) / U512::from(denominator)).try_into().ok()
+    }
+
+    fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> Option<U256> {
+        if denominator.is_zero() {
+            return None;
+        }
+        let product = a.full_mul(b);
+        let denominator = U512::from(denominator);
+        let rounding = U512::from(!(product % denominator).is_zero() as u8);
+        (product / denominator + rounding).try_into().ok()
+    }
+
+    fn div_rounding_up(a: U256, denominator: U256) -> Option<U256> {
+        if denominator.is_zero() {
+            return None;
+        }
+        let rounding = U256::from(!(a % denominator).is_zero() as u8);
+        Some(a / denominator + rounding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, ethereum_types::H160};
+
+    #[test]
+    fn sqrt_ratio_at_tick_bounds() {
+        assert_eq!(
+            math::sqrt_ratio_at_tick(math::MIN_TICK),
+            Some(math::MIN_SQRT_RATIO)
+        );
+        assert_eq!(
+            math::sqrt_ratio_at_tick(math::MAX_TICK),
+            Some(math::MAX_SQRT_RATIO)
+        );
+        assert_eq!(math::sqrt_ratio_at_tick(0), Some(U256::one() << 96));
+        assert_eq!(math::sqrt_ratio_at_tick(math::MAX_TICK + 1), None);
+    }
+
+    fn token(n: u64) -> eth::TokenAddress {
+        eth::TokenAddress(H160::from_low_u64_be(n))
+    }
+
+    fn asset(n: u64, amount: u128) -> eth::Asset {
+        eth::Asset {
+            token: token(n),
+            amount: amount.into(),
+        }
+    }
+
+    /// A pool at price 1 with positions in the tick ranges [-60, 60] and
+    /// [-120, 120].
+    fn pool() -> Pool {
+        Pool {
+            tokens: liquidity::TokenPair::new(token(1), token(2)).unwrap(),
+            sqrt_price: SqrtPrice(U256::one() << 96),
+            liquidity: Amount(1_500_000_000_000_000_000_000),
+            tick: Tick(0),
+            liquidity_net: [
+                (-120, 500_000_000_000_000_000_000),
+                (-60, 1_000_000_000_000_000_000_000),
+                (60, -1_000_000_000_000_000_000_000),
+                (120, -500_000_000_000_000_000_000),
+            ]
+            .into_iter()
+            .map(|(tick, net)| (Tick(tick), LiquidityNet(net)))
+            .collect(),
+            fee: Fee(eth::Rational::new(3.into(), 1000.into())),
+        }
+    }
+
+    #[test]
+    fn swaps_within_tick_range() {
+        let pool = pool();
+
+        let swap = pool
+            .exact_input(&asset(1, 1_000_000_000_000_000_000))
+            .unwrap();
+        assert_eq!(swap.output.token, token(2));
+        assert_eq!(swap.output.amount, 996_337_767_497_203_525_u128.into());
+        assert_eq!(swap.ticks_crossed, 0);
+
+        let swap = pool
+            .exact_output(&asset(2, 1_000_000_000_000_000_000))
+            .unwrap();
+        assert_eq!(swap.input.token, token(1));
+        assert_eq!(swap.input.amount, 1_003_678_145_845_140_493_u128.into());
+        assert_eq!(swap.ticks_crossed, 0);
+    }
+
+    #[test]
+    fn swaps_across_ticks() {
+        let pool = pool();
+
+        for (input, output) in [(1, 2), (2, 1)] {
+            let swap = pool
+                .exact_input(&asset(input, 5_000_000_000_000_000_000))
+                .unwrap();
+            assert_eq!(swap.output.token, token(output));
+            assert_eq!(swap.output.amount, 4_968_185_933_846_166_093_u128.into());
+            assert_eq!(swap.ticks_crossed, 1);
+        }
+
+        let swap = pool
+            .exact_output(&asset(2, 5_000_000_000_000_000_000))
+            .unwrap();
+        assert_eq!(swap.input.amount, 5_032_165_159_007_440_364_u128.into());
+        assert_eq!(swap.ticks_crossed, 1);
+        assert_eq!(
+            swap.gas(eth::Gas(100_000.into())).0,
+            (100_000 + GAS_PER_TICK_CROSSED).into()
+        );
+    }
+
+    #[test]
+    fn rejects_swaps_exceeding_liquidity() {
+        let pool = pool();
+        assert!(
+            pool.exact_input(&asset(1, 10_000_000_000_000_000_000))
+                .is_none()
+        );
+        assert!(
+            pool.exact_output(&asset(2, 10_000_000_000_000_000_000))
+                .is_none()
+        );
+        assert!(pool.exact_input(&asset(3, 1)).is_none());
+    }
+}
@@ -0,0 +1,38 @@
+//! CoW AMM pool.
+//!
+//! CoW AMMs do not get called through an interaction like regular AMMs.
+//! Instead their helper contract computes a just-in-time CoW Protocol order
+//! that rebalances the pool to the auction's reference prices. The pool
+//! already signed this order, so it can only be settled as-is.
+
+use crate::domain::{eth, liquidity, order};
+
+/// CoW AMM pool state.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub order: Order,
+}
+
+impl Pool {
+    /// Returns the pool's token pair.
+    pub fn tokens(&self) -> liquidity::TokenPair {
+        liquidity::TokenPair::new(self.order.sell.token, self.order.buy.token)
+            .expect("CoW AMM order trades different tokens")
+    }
+}
+
+/// The fill-or-kill sell order offered by the pool.
+#[derive(Clone, Debug)]
+pub struct Order {
+    pub sell: eth::Asset,
+    pub buy: eth::Asset,
+    pub receiver: Option<eth::Address>,
+    pub valid_to: u32,
+    pub app_data: order::AppData,
+    /// The EIP-1271 signature of the order without the pool address.
+    pub signature: Vec<u8>,
+    /// Interactions that need to be executed before the order can be
+    /// settled, e.g. committing the pool to the order.
+    pub pre_interactions: Vec<eth::Interaction>,
+    pub post_interactions: Vec<eth::Interaction>,
+}
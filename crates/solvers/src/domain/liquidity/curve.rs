@@ -0,0 +1,76 @@
+//! Curve pools.
+//!
+//! Curve swaps are computed with the same fixed point math as the pool
+//! contracts, which is why the pool parameters are kept in their on-chain
+//! representation. Fees are relative to `1e10`.
+
+use {
+    crate::domain::{eth, liquidity},
+    ethereum_types::U256,
+    itertools::Itertools as _,
+};
+
+/// The state of a Curve pool.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    /// The coins of the pool in coin index order.
+    pub tokens: Vec<eth::TokenAddress>,
+    pub kind: Kind,
+}
+
+#[derive(Clone, Debug)]
+pub enum Kind {
+    /// A plain or lending StableSwap pool.
+    Stable(Stable),
+    /// A two coin crypto or tricrypto CryptoSwap pool.
+    Crypto(Crypto),
+}
+
+#[derive(Clone, Debug)]
+pub struct Stable {
+    pub balances: Vec<U256>,
+    /// The rates normalizing the balances to 18 decimals, scaled by `1e18`.
+    pub rates: Vec<U256>,
+    pub amplification_parameter: U256,
+    pub fee: U256,
+    pub offpeg_fee_multiplier: Option<U256>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Crypto {
+    pub balances: Vec<U256>,
+    /// The multipliers normalizing the balances to 18 decimals.
+    pub precisions: Vec<U256>,
+    /// The prices of all coins but the first relative to the first, scaled
+    /// by `1e18`.
+    pub price_scale: Vec<U256>,
+    pub amplification_parameter: U256,
+    pub gamma: U256,
+    pub d: U256,
+    pub mid_fee: U256,
+    pub out_fee: U256,
+    pub fee_gamma: U256,
+}
+
+impl Pool {
+    /// Returns a new pool for the specified coins. Returns `None` if the pool
+    /// has duplicate coins or does not have a balance for every coin.
+    pub fn new(tokens: Vec<eth::TokenAddress>, kind: Kind) -> Option<Self> {
+        let balances = match &kind {
+            Kind::Stable(state) => &state.balances,
+            Kind::Crypto(state) => &state.balances,
+        };
+        if tokens.len() < 2 || tokens.len() != balances.len() || !tokens.iter().all_unique() {
+            return None;
+        }
+        Some(Self { tokens, kind })
+    }
+
+    /// Returns all token pairs that can be traded with the pool.
+    pub fn token_pairs(&self) -> impl Iterator<Item = liquidity::TokenPair> + '_ {
+        self.tokens
+            .iter()
+            .tuple_combinations()
+            .map(|(a, b)| liquidity::TokenPair::new(*a, *b).expect("a != b"))
+    }
+}
@@ -1,7 +1,10 @@
 //! Modelling on-chain liquidity.
 
+pub mod balancer_v3;
 pub mod concentrated;
 pub mod constant_product;
+pub mod cow_amm;
+pub mod curve;
 pub mod limit_order;
 pub mod stable;
 pub mod weighted_product;
@@ -29,6 +32,9 @@ pub enum State {
     Stable(stable::Pool),
     Concentrated(concentrated::Pool),
     LimitOrder(limit_order::LimitOrder),
+    CowAmm(cow_amm::Pool),
+    Curve(curve::Pool),
+    BalancerV3(balancer_v3::Pool),
 }
 
 /// An ordered token pair.
@@ -1,7 +1,9 @@
 //! Core solver engine logic.
 
 pub mod auction;
+pub mod dex;
 pub mod eth;
+pub mod feedback;
 pub mod liquidity;
 pub mod notification;
 pub mod order;
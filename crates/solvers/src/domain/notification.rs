@@ -4,6 +4,7 @@ use {
         eth::{self, Ether, TokenAddress},
         solution::{self},
     },
+    chrono::{DateTime, Utc},
     std::collections::BTreeSet,
 };
 
@@ -42,6 +43,8 @@ pub enum Kind {
     Settled(Settlement),
     DriverError(String),
     PostprocessingTimedOut,
+    SimulatorDisagreement(BlockNo, Transaction, String),
+    Banned(BanReason, DateTime<Utc>),
 }
 
 /// The result of winning solver trying to settle the transaction onchain.
@@ -50,6 +53,7 @@ pub enum Settlement {
     Success(TransactionHash),
     Revert(TransactionHash),
     SimulationRevert,
+    Expired,
     Fail,
 }
 
@@ -59,3 +63,10 @@ pub enum ScoreKind {
     InvalidExecutedAmount,
     MissingPrice(TokenAddress),
 }
+
+/// Why the solver got banned from participating in auctions.
+#[derive(Debug)]
+pub enum BanReason {
+    UnsettledConsecutiveAuctions,
+    HighSettleFailureRate,
+}
@@ -3,7 +3,7 @@ use {
         domain::{auction, eth, liquidity, order},
         util,
     },
-    ethereum_types::{Address, U256},
+    ethereum_types::{Address, U256, U512},
     std::{collections::HashMap, slice},
 };
 
@@ -120,18 +120,14 @@ impl Single {
     /// Creates a full solution for a single order solution given gas and sell
     /// token prices.
     pub fn into_solution(self, fee: eth::SellTokenAmount) -> Option<Solution> {
+        let (sell, buy) = self.executed_amounts(fee)?;
         let Self {
             order,
-            input,
-            output,
             interactions,
             gas,
+            ..
         } = self;
 
-        if (order.sell.token, order.buy.token) != (input.token, output.token) {
-            return None;
-        }
-
         let fee = if order.solver_determines_fee() {
             Fee::Surplus(fee)
         } else {
@@ -139,30 +135,6 @@ impl Single {
         };
         let surplus_fee = fee.surplus().unwrap_or_default();
 
-        // Compute total executed sell and buy amounts accounting for solver
-        // fees. That is, the total amount of sell tokens transferred into the
-        // contract and the total buy tokens transferred out of the contract.
-        let (sell, buy) = match order.side {
-            order::Side::Buy => (input.amount.checked_add(surplus_fee)?, output.amount),
-            order::Side::Sell => {
-                // We want to collect fees in the sell token, so we need to sell
-                // `fee` more than the DEX swap. However, we don't allow
-                // transferring more than `order.sell.amount` (guaranteed by the
-                // Smart Contract), so we need to cap our executed amount to the
-                // order's limit sell amount and compute the executed buy amount
-                // accordingly.
-                let sell = input
-                    .amount
-                    .checked_add(surplus_fee)?
-                    .min(order.sell.amount);
-                let buy = util::math::div_ceil(
-                    sell.checked_sub(surplus_fee)?.checked_mul(output.amount)?,
-                    input.amount,
-                )?;
-                (sell, buy)
-            }
-        };
-
         // Check order's limit price is satisfied accounting for solver
         // specified fees.
         if order.sell.amount.checked_mul(buy)? < order.buy.amount.checked_mul(sell)? {
@@ -197,6 +169,60 @@ impl Single {
             trades: vec![Trade::Fulfillment(Fulfillment::new(order, executed, fee)?)],
         })
     }
+
+    /// Returns the surplus of the solution relative to the order's limit
+    /// price, scaled by the order's sell amount. This allows comparing
+    /// different solutions for the same order.
+    pub fn surplus(&self, fee: eth::SellTokenAmount) -> Option<U512> {
+        let (sell, buy) = self.executed_amounts(fee)?;
+        Some(
+            buy.full_mul(self.order.sell.amount)
+                .saturating_sub(sell.full_mul(self.order.buy.amount)),
+        )
+    }
+
+    /// Computes total executed sell and buy amounts accounting for solver
+    /// fees. That is, the total amount of sell tokens transferred into the
+    /// contract and the total buy tokens transferred out of the contract.
+    fn executed_amounts(&self, fee: eth::SellTokenAmount) -> Option<(U256, U256)> {
+        let Self {
+            order,
+            input,
+            output,
+            ..
+        } = self;
+
+        if (order.sell.token, order.buy.token) != (input.token, output.token) {
+            return None;
+        }
+
+        let surplus_fee = if order.solver_determines_fee() {
+            fee.0
+        } else {
+            U256::zero()
+        };
+
+        match order.side {
+            order::Side::Buy => Some((input.amount.checked_add(surplus_fee)?, output.amount)),
+            order::Side::Sell => {
+                // We want to collect fees in the sell token, so we need to sell
+                // `fee` more than the DEX swap. However, we don't allow
+                // transferring more than `order.sell.amount` (guaranteed by the
+                // Smart Contract), so we need to cap our executed amount to the
+                // order's limit sell amount and compute the executed buy amount
+                // accordingly.
+                let sell = input
+                    .amount
+                    .checked_add(surplus_fee)?
+                    .min(order.sell.amount);
+                let buy = util::math::div_ceil(
+                    sell.checked_sub(surplus_fee)?.checked_mul(output.amount)?,
+                    input.amount,
+                )?;
+                Some((sell, buy))
+            }
+        }
+    }
 }
 
 /// A set of uniform clearing prices. They are represented as a mapping of token
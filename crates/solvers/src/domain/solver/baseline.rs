@@ -0,0 +1,589 @@
+//! "Baseline" solver implementation.
+//!
+//! The baseline solver is a simple solver implementation that finds the best
+//! path of at most length `max_hops + 1` over a set of on-chain liquidity. It
+//! **does not** try to split large orders into multiple parts and route them
+//! over separate paths.
+
+use {
+    crate::{
+        boundary,
+        domain::{
+            auction,
+            eth,
+            feedback,
+            liquidity,
+            notification,
+            order::{self, Order},
+            solution,
+        },
+        infra::metrics,
+    },
+    ethereum_types::{H160, U256, U512},
+    std::{
+        cmp,
+        collections::{BTreeMap, HashSet},
+        sync::Arc,
+    },
+};
+
+pub struct Baseline(Arc<Inner>);
+
+pub struct Config {
+    pub weth: eth::WethAddress,
+    pub base_tokens: Vec<eth::TokenAddress>,
+    pub max_hops: usize,
+    pub max_partial_attempts: usize,
+    pub solution_gas_offset: eth::SignedGas,
+    pub native_token_price_estimation_amount: eth::U256,
+    pub feedback: Option<feedback::Config>,
+    pub anytime_search: bool,
+}
+
+struct Inner {
+    weth: eth::WethAddress,
+
+    /// Set of tokens to additionally consider as intermediary hops when
+    /// path-finding. This allows paths of the kind `TOKEN1 -> WETH -> TOKEN2`
+    /// to be considered.
+    base_tokens: HashSet<eth::TokenAddress>,
+
+    /// Maximum number of hops that can be considered in a trading path. A hop
+    /// is an intermediary token within a trading path. For example:
+    /// - A value of 0 indicates that only a direct trade is allowed: `A -> B`
+    /// - A value of 1 indicates that a single intermediary token can appear
+    ///   within a trading path: `A -> B -> C`
+    /// - A value of 2 indicates: `A -> B -> C -> D`
+    /// - etc.
+    max_hops: usize,
+
+    /// The maximum number of attempts to solve a partially fillable order.
+    /// Basically we continuously halve the amount to execute until we find a
+    /// valid solution or exceed this count.
+    max_partial_attempts: usize,
+
+    /// Units of gas that get added to the gas estimate for executing a
+    /// computed trade route to arrive at a gas estimate for a whole settlement.
+    solution_gas_offset: eth::SignedGas,
+
+    /// The amount of the native token to use to estimate native price of a
+    /// token
+    native_token_price_estimation_amount: eth::U256,
+
+    /// Outcomes of previously proposed solutions used to avoid liquidity that
+    /// keeps causing failures. Failing liquidity is not avoided if unset.
+    feedback: Option<feedback::Feedback>,
+
+    /// Whether to first find direct routes for all orders and then improve
+    /// them with deeper searches while the deadline allows, instead of fully
+    /// solving one order after another.
+    anytime_search: bool,
+}
+
+impl Baseline {
+    /// Creates a new baseline solver for the specified configuration.
+    pub fn new(config: Config) -> Self {
+        Self(Arc::new(Inner {
+            weth: config.weth,
+            base_tokens: config.base_tokens.into_iter().collect(),
+            max_hops: config.max_hops,
+            max_partial_attempts: config.max_partial_attempts,
+            solution_gas_offset: config.solution_gas_offset,
+            native_token_price_estimation_amount: config.native_token_price_estimation_amount,
+            feedback: config.feedback.map(feedback::Feedback::new),
+            anytime_search: config.anytime_search,
+        }))
+    }
+
+    /// Solves the specified auction, returning a vector of all possible
+    /// solutions.
+    pub async fn solve(&self, mut auction: auction::Auction) -> Vec<solution::Solution> {
+        metrics::solve(&auction);
+        let auction_id = auction.id;
+        let deadline = auction.deadline.clone();
+        let avoided = self
+            .0
+            .feedback
+            .as_ref()
+            .map(feedback::Feedback::avoided)
+            .unwrap_or_default();
+        if !avoided.is_empty() {
+            tracing::debug!(?avoided, "avoiding liquidity of failed solutions");
+            auction
+                .liquidity
+                .retain(|liquidity| !avoided.contains(liquidity));
+        }
+        // Make sure to push the CPU-heavy code to a separate thread in order to
+        // not lock up the [`tokio`] runtime and cause it to slow down handling
+        // the real async things. For larger settlements, this can block in the
+        // 100s of ms.
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let remaining = auction
+            .deadline
+            .clone()
+            .reduce(super::DEADLINE_SLACK)
+            .remaining()
+            .unwrap_or_default();
+
+        let inner = self.0.clone();
+        let span = tracing::Span::current();
+        let background_work = async move {
+            let _entered = span.enter();
+            inner.solve(auction, sender);
+        };
+
+        if tokio::time::timeout(remaining, tokio::spawn(background_work))
+            .await
+            .is_err()
+        {
+            tracing::debug!("reached timeout while solving orders");
+        }
+
+        // Solutions for the same order replace earlier ones since they are
+        // only sent if they improve on them.
+        let mut solutions = BTreeMap::new();
+        while let Ok(solution) = receiver.try_recv() {
+            solutions.insert(solution.id.0, solution);
+        }
+        let solutions: Vec<_> = solutions.into_values().collect();
+        metrics::solved(&deadline, &solutions);
+        if let Some(feedback) = &self.0.feedback {
+            feedback.proposed(auction_id, &solutions);
+        }
+        solutions
+    }
+
+    /// Processes a notification about a previously proposed solution.
+    pub fn notify(&self, notification: notification::Notification) {
+        tracing::debug!(?notification, "received notification");
+        if let Some(feedback) = &self.0.feedback {
+            feedback.notify(&notification);
+        }
+    }
+}
+
+impl Inner {
+    fn solve(
+        &self,
+        auction: auction::Auction,
+        sender: tokio::sync::mpsc::UnboundedSender<solution::Solution>,
+    ) {
+        if self.anytime_search {
+            self.solve_anytime(auction, sender);
+            return;
+        }
+
+        let boundary_solver =
+            boundary::baseline::Solver::new(&self.weth, &self.base_tokens, &auction.liquidity);
+
+        for (i, order) in auction.orders.iter().enumerate() {
+            let Some(sell_token_price) = self.sell_token_price(&auction, &boundary_solver, order)
+            else {
+                continue;
+            };
+            let solution = self.solve_order(
+                &auction,
+                &boundary_solver,
+                self.max_hops,
+                sell_token_price,
+                i,
+                order,
+            );
+            if let Some((solution, _)) = solution {
+                if sender.send(solution).is_err() {
+                    tracing::debug!("deadline hit, receiver dropped");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Solves the auction in stages of increasing search depth. The first
+    /// stage only considers direct routes so every order quickly gets a
+    /// solution. Later stages consider more hops and base tokens and only
+    /// send a solution if it improves on the best one found so far for the
+    /// order. The receiver keeps the latest solution per order, so whatever
+    /// is found when the deadline is reached can be returned immediately.
+    fn solve_anytime(
+        &self,
+        auction: auction::Auction,
+        sender: tokio::sync::mpsc::UnboundedSender<solution::Solution>,
+    ) {
+        let weth_only =
+            boundary::baseline::Solver::new(&self.weth, &HashSet::new(), &auction.liquidity);
+        let full =
+            boundary::baseline::Solver::new(&self.weth, &self.base_tokens, &auction.liquidity);
+        let has_base_tokens = self
+            .base_tokens
+            .iter()
+            .any(|token| *token != self.weth.0.into());
+
+        let mut stages = vec![(&weth_only, 0)];
+        for hops in 1..=self.max_hops {
+            stages.push((&weth_only, hops));
+            if has_base_tokens {
+                stages.push((&full, hops));
+            }
+        }
+
+        let mut prices = vec![None; auction.orders.len()];
+        let mut best = vec![None; auction.orders.len()];
+        for (stage, (boundary_solver, max_hops)) in stages.into_iter().enumerate() {
+            tracing::trace!(stage, max_hops, "starting search stage");
+            for (i, order) in auction.orders.iter().enumerate() {
+                // Native prices are always estimated with the full search
+                // depth so fees are comparable across stages.
+                let Some(sell_token_price) =
+                    *prices[i].get_or_insert_with(|| self.sell_token_price(&auction, &full, order))
+                else {
+                    continue;
+                };
+                let Some((solution, surplus)) = self.solve_order(
+                    &auction,
+                    boundary_solver,
+                    max_hops,
+                    sell_token_price,
+                    i,
+                    order,
+                ) else {
+                    continue;
+                };
+                if best[i].is_some_and(|best| best >= surplus) {
+                    continue;
+                }
+                best[i] = Some(surplus);
+                if sender.send(solution).is_err() {
+                    tracing::debug!(stage, "deadline hit, receiver dropped");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns the price of the order's sell token in the native token or
+    /// `None` if the order can't be solved.
+    fn sell_token_price(
+        &self,
+        auction: &auction::Auction,
+        boundary_solver: &boundary::baseline::Solver,
+        order: &Order,
+    ) -> Option<auction::Price> {
+        let sell_token = order.sell.token;
+        match auction.tokens.reference_price(&sell_token) {
+            Some(price) => Some(price),
+            None if sell_token == self.weth.0.into() => {
+                // Early return if the sell token is native token
+                Some(auction::Price(eth::Ether(eth::U256::exp10(18))))
+            }
+            None => {
+                // Estimate the price of the sell token in the native token
+                let native_price_request = self.native_price_request(order);
+                match boundary_solver.route(native_price_request, self.max_hops) {
+                    Some(route) => {
+                        // how many units of buy_token are bought for one unit of sell_token
+                        // (buy_amount / sell_amount).
+                        let price = self.native_token_price_estimation_amount.to_f64_lossy()
+                            / route.input().amount.to_f64_lossy();
+                        let price = to_normalized_price(price)?;
+
+                        Some(auction::Price(eth::Ether(price)))
+                    }
+                    _ => {
+                        // This is to allow quotes to be generated for tokens for which the sell
+                        // token price is not available, so we default to fee=0
+                        Some(auction::Price(eth::Ether(eth::U256::MAX)))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finds a solution for a single order using routes of at most `max_hops`
+    /// hops. Returns the solution together with its surplus.
+    fn solve_order(
+        &self,
+        auction: &auction::Auction,
+        boundary_solver: &boundary::baseline::Solver,
+        max_hops: usize,
+        sell_token_price: auction::Price,
+        i: usize,
+        order: &Order,
+    ) -> Option<(solution::Solution, U512)> {
+        let transfer_fees = TransferFees::new(&auction.tokens, order);
+        self.requests_for_order(order).find_map(|request| {
+            tracing::trace!(order =% order.uid, ?request, "finding route");
+
+            let route = boundary_solver.route(transfer_fees.route(request)?, max_hops)?;
+            let mut interactions = Vec::new();
+            let mut cow_amms = Vec::new();
+            for segment in &route.segments {
+                match &segment.liquidity.state {
+                    liquidity::State::CowAmm(pool) => {
+                        cow_amms.push((segment.liquidity.address, pool));
+                    }
+                    _ => interactions.push(solution::Interaction::Liquidity(
+                        solution::LiquidityInteraction {
+                            liquidity: segment.liquidity.clone(),
+                            input: segment.input,
+                            output: segment.output,
+                            // TODO does the baseline solver know about this optimization?
+                            internalize: false,
+                        },
+                    )),
+                }
+            }
+
+            // The baseline solver generates a path with swapping
+            // for exact output token amounts. This leads to
+            // potential rounding errors for buy orders, where we
+            // can buy slightly more than intended. Fix this by
+            // capping the output amount to the order's buy amount
+            // for buy orders.
+            let mut output = transfer_fees.received(route.output())?;
+            if let order::Side::Buy = order.side {
+                output.amount = cmp::min(output.amount, order.buy.amount);
+            }
+
+            let gas = route.gas() + self.solution_gas_offset;
+            let fee = sell_token_price
+                .ether_value(eth::Ether(gas.0.checked_mul(auction.gas_price.0.0)?))?
+                .into();
+
+            let single = solution::Single {
+                order: order.clone(),
+                input: transfer_fees.sent(route.input())?,
+                output,
+                interactions,
+                gas,
+            };
+            let surplus = single.surplus(fee)?;
+            let mut solution = single.into_solution(fee)?;
+            for (address, pool) in cow_amms {
+                let trade = cow_amm_trade(address, pool, &solution.prices)?;
+                solution.trades.push(trade);
+                solution
+                    .pre_interactions
+                    .extend(pool.order.pre_interactions.iter().cloned());
+                solution
+                    .post_interactions
+                    .extend(pool.order.post_interactions.iter().cloned());
+            }
+            let solution = solution
+                .with_id(solution::Id(i as u64))
+                .with_buffers_internalizations(&auction.tokens);
+            Some((solution, surplus))
+        })
+    }
+
+    fn requests_for_order(&self, order: &Order) -> impl Iterator<Item = Request> + use<> {
+        let order::Order {
+            sell, buy, side, ..
+        } = order.clone();
+
+        let n = if order.partially_fillable {
+            self.max_partial_attempts
+        } else {
+            1
+        };
+
+        (0..n)
+            .map(move |i| {
+                let divisor = U256::one() << i;
+                Request {
+                    sell: eth::Asset {
+                        token: sell.token,
+                        amount: sell.amount / divisor,
+                    },
+                    buy: eth::Asset {
+                        token: buy.token,
+                        amount: buy.amount / divisor,
+                    },
+                    side,
+                }
+            })
+            .filter(|r| !r.sell.amount.is_zero() && !r.buy.amount.is_zero())
+    }
+
+    fn native_price_request(&self, order: &Order) -> Request {
+        let sell = eth::Asset {
+            token: order.sell.token,
+            // Note that we intentionally do not use [`eth::U256::max_value()`]
+            // as an order with this would cause overflows with the smart
+            // contract, so buy orders requiring excessively large sell amounts
+            // would not work anyway. Instead we use `2 ** 144`, the rationale
+            // being that Uniswap V2 pool reserves are 112-bit integers. Noting
+            // that `256 - 112 = 144`, this means that we can use it to trade a full
+            // `type(uint112).max` without overflowing a `uint256` on the smart
+            // contract level. Requiring to trade more than `type(uint112).max`
+            // is unlikely and would not work with Uniswap V2 anyway.
+            amount: eth::U256::one() << 144,
+        };
+
+        let buy = eth::Asset {
+            token: self.weth.0.into(),
+            amount: self.native_token_price_estimation_amount,
+        };
+
+        Request {
+            sell,
+            buy,
+            side: order::Side::Buy,
+        }
+    }
+}
+
+fn to_normalized_price(price: f64) -> Option<U256> {
+    let uint_max = 2.0_f64.powi(256);
+
+    let price_in_eth = 1e18 * price;
+    if price_in_eth.is_normal() && price_in_eth >= 1. && price_in_eth < uint_max {
+        Some(U256::from_f64_lossy(price_in_eth))
+    } else {
+        None
+    }
+}
+
+/// Returns the JIT trade settling the rebalancing order of the CoW AMM at
+/// `address` in full, or `None` if the single order solution's clearing
+/// prices don't balance it.
+///
+/// The traded order receives exactly the amount the pool sells if the price of
+/// the pool's buy token is the pool's sell amount, as the price of an order's
+/// sell token in a single order solution is the executed buy amount. The pool
+/// then receives the price of its sell token, which has to cover its limit.
+fn cow_amm_trade(
+    address: H160,
+    pool: &liquidity::cow_amm::Pool,
+    prices: &solution::ClearingPrices,
+) -> Option<solution::Trade> {
+    let order = &pool.order;
+    if *prices.0.get(&order.buy.token)? != order.sell.amount
+        || *prices.0.get(&order.sell.token)? < order.buy.amount
+    {
+        return None;
+    }
+
+    let mut signature = address.as_bytes().to_vec();
+    signature.extend_from_slice(&order.signature);
+    Some(solution::Trade::Jit(solution::JitTrade {
+        order: order::JitOrder {
+            owner: address,
+            signature: order::Signature::Eip1271(signature),
+            sell: order.sell,
+            buy: order.buy,
+            side: order::Side::Sell,
+            class: order::Class::Limit,
+            partially_fillable: false,
+            valid_to: order.valid_to,
+            app_data: order.app_data,
+            receiver: order
+                .receiver
+                .map(|receiver| receiver.0)
+                .unwrap_or_default(),
+        },
+        executed: order.sell.amount,
+        fee: eth::SellTokenAmount(U256::zero()),
+    }))
+}
+
+/// A baseline routing request.
+#[derive(Debug)]
+pub struct Request {
+    pub sell: eth::Asset,
+    pub buy: eth::Asset,
+    pub side: order::Side,
+}
+
+/// The fees fee-on-transfer tokens of an order take. Only the amount left
+/// after the fee arrives in the settlement contract, both when the user sends
+/// the sell token and when the liquidity sends the buy token.
+struct TransferFees {
+    sell: Option<auction::TransferFee>,
+    buy: Option<auction::TransferFee>,
+}
+
+impl TransferFees {
+    fn new(tokens: &auction::Tokens, order: &Order) -> Self {
+        let fee =
+            |token: &eth::TokenAddress| tokens.get(token).and_then(|token| token.transfer_fee);
+        Self {
+            sell: fee(&order.sell.token),
+            buy: fee(&order.buy.token),
+        }
+    }
+
+    /// The request to route through the liquidity so that the order's amounts
+    /// are met after the transfer fees.
+    fn route(&self, mut request: Request) -> Option<Request> {
+        if let Some(fee) = self.sell {
+            request.sell.amount = fee.apply(request.sell.amount)?;
+        }
+        if let Some(fee) = self.buy {
+            request.buy.amount = fee.gross_up(request.buy.amount)?;
+        }
+        Some(request)
+    }
+
+    /// The amount the user has to send for the route's input to arrive.
+    fn sent(&self, mut input: eth::Asset) -> Option<eth::Asset> {
+        if let Some(fee) = self.sell {
+            input.amount = fee.gross_up(input.amount)?;
+        }
+        Some(input)
+    }
+
+    /// The amount arriving in the settlement contract for the route's output.
+    fn received(&self, mut output: eth::Asset) -> Option<eth::Asset> {
+        if let Some(fee) = self.buy {
+            output.amount = fee.apply(output.amount)?;
+        }
+        Some(output)
+    }
+}
+
+/// A trading route.
+#[derive(Debug)]
+pub struct Route<'a> {
+    segments: Vec<Segment<'a>>,
+}
+
+/// A segment in a trading route.
+#[derive(Debug)]
+pub struct Segment<'a> {
+    pub liquidity: &'a liquidity::Liquidity,
+    // TODO: There is no type-level guarantee here that both `input.token` and
+    // `output.token` are valid for the liquidity in this segment. This is
+    // unfortunate because this type leaks out of this module (currently into
+    // the `boundary::baseline` module) but should no longer need to be `pub`
+    // once the `boundary::baseline` module gets refactored into the domain
+    // logic, so I think it is fine for now.
+    pub input: eth::Asset,
+    pub output: eth::Asset,
+    pub gas: eth::Gas,
+}
+
+impl<'a> Route<'a> {
+    pub fn new(segments: Vec<Segment<'a>>) -> Option<Self> {
+        if segments.is_empty() {
+            return None;
+        }
+        Some(Self { segments })
+    }
+
+    fn input(&self) -> eth::Asset {
+        self.segments[0].input
+    }
+
+    fn output(&self) -> eth::Asset {
+        self.segments
+            .last()
+            .expect("route has at least one segment by construction")
+            .output
+    }
+
+    fn gas(&self) -> eth::Gas {
+        eth::Gas(self.segments.iter().fold(U256::zero(), |acc, segment| {
+            acc.saturating_add(segment.gas.0)
+        }))
+    }
+}
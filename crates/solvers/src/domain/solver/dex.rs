@@ -0,0 +1,131 @@
+//! A solver engine that routes individual orders through a DEX aggregator
+//! API.
+
+use {
+    crate::{
+        domain::{auction, dex, eth, notification, order, solution},
+        infra::{self, metrics},
+    },
+    futures::{StreamExt, stream},
+    std::num::NonZeroUsize,
+};
+
+pub struct Config {
+    /// The relative slippage tolerance to apply to quoted swaps.
+    pub slippage: dex::Slippage,
+    /// The number of orders to concurrently request swaps for.
+    pub concurrent_requests: NonZeroUsize,
+    /// Units of gas that get added to the gas estimate of a swap to arrive at
+    /// a gas estimate for a whole settlement.
+    pub solution_gas_offset: eth::SignedGas,
+}
+
+pub struct Dex {
+    aggregator: Box<dyn infra::dex::Aggregator>,
+    slippage: dex::Slippage,
+    concurrent_requests: NonZeroUsize,
+    solution_gas_offset: eth::SignedGas,
+}
+
+impl Dex {
+    pub fn new(aggregator: Box<dyn infra::dex::Aggregator>, config: Config) -> Self {
+        Self {
+            aggregator,
+            slippage: config.slippage,
+            concurrent_requests: config.concurrent_requests,
+            solution_gas_offset: config.solution_gas_offset,
+        }
+    }
+
+    /// Solves the specified auction by requesting a swap for every order.
+    /// Returns the solutions found before the deadline.
+    pub async fn solve(&self, auction: auction::Auction) -> Vec<solution::Solution> {
+        metrics::solve(&auction);
+        let remaining = auction
+            .deadline
+            .clone()
+            .reduce(super::DEADLINE_SLACK)
+            .remaining()
+            .unwrap_or_default();
+
+        let mut solutions = Vec::new();
+        let solve_orders = async {
+            let mut swaps = stream::iter(auction.orders.iter().enumerate())
+                .map(|(i, order)| self.solve_order(&auction, i, order))
+                .buffer_unordered(self.concurrent_requests.get());
+            while let Some(solution) = swaps.next().await {
+                solutions.extend(solution);
+            }
+        };
+        if tokio::time::timeout(remaining, solve_orders).await.is_err() {
+            tracing::debug!("reached timeout while solving orders");
+        }
+
+        solutions.sort_by_key(|solution| solution.id.0);
+        metrics::solved(&auction.deadline, &solutions);
+        solutions
+    }
+
+    async fn solve_order(
+        &self,
+        auction: &auction::Auction,
+        i: usize,
+        order: &order::Order,
+    ) -> Option<solution::Solution> {
+        let sell_token_price = match auction.tokens.reference_price(&order.sell.token) {
+            Some(price) => price,
+            // The fee is not used for orders that don't need a solver
+            // determined fee, so default to fee=0.
+            None if !order.solver_determines_fee() => auction::Price(eth::Ether(eth::U256::MAX)),
+            None => {
+                tracing::debug!(order = %order.uid, "missing sell token price");
+                return None;
+            }
+        };
+
+        let swap = match self
+            .aggregator
+            .swap(&dex::Order::new(order), &self.slippage)
+            .await
+        {
+            Ok(swap) => swap,
+            Err(infra::dex::Error::NotFound) => {
+                tracing::debug!(order = %order.uid, "no swap found");
+                return None;
+            }
+            Err(infra::dex::Error::OrderNotSupported) => {
+                tracing::debug!(order = %order.uid, "order not supported by the aggregator");
+                return None;
+            }
+            Err(err) => {
+                tracing::warn!(order = %order.uid, ?err, "failed to get swap");
+                metrics::solve_error(err.label());
+                return None;
+            }
+        };
+
+        let solution = swap
+            .with_slippage(order.side, &self.slippage)
+            .into_solution(
+                order.clone(),
+                auction.gas_price,
+                sell_token_price,
+                self.solution_gas_offset,
+            );
+        let Some(solution) = solution else {
+            tracing::debug!(order = %order.uid, "swap does not satisfy the limit price");
+            return None;
+        };
+        Some(
+            solution
+                .with_id(solution::Id(i as u64))
+                .with_buffers_internalizations(&auction.tokens),
+        )
+    }
+
+    /// Notifications are only logged since aggregator swaps don't depend on
+    /// previous results.
+    pub fn notify(&self, notification: notification::Notification) {
+        tracing::debug!(?notification, "received notification");
+    }
+}
@@ -0,0 +1,39 @@
+//! Solver engines.
+
+use crate::domain::{auction, notification, solution};
+
+pub mod baseline;
+pub mod dex;
+
+pub use self::{baseline::Baseline, dex::Dex};
+
+/// The amount of time we aim the solver to finish before the final deadline is
+/// reached.
+const DEADLINE_SLACK: chrono::Duration = chrono::Duration::milliseconds(500);
+
+/// The solver engine to serve.
+pub enum Solver {
+    /// Routes orders over the on-chain liquidity included in the auction.
+    Baseline(Baseline),
+    /// Routes orders through an external DEX aggregator API.
+    Dex(Dex),
+}
+
+impl Solver {
+    /// Solves the specified auction, returning a vector of all possible
+    /// solutions.
+    pub async fn solve(&self, auction: auction::Auction) -> Vec<solution::Solution> {
+        match self {
+            Solver::Baseline(solver) => solver.solve(auction).await,
+            Solver::Dex(solver) => solver.solve(auction).await,
+        }
+    }
+
+    /// Processes a notification about a previously proposed solution.
+    pub fn notify(&self, notification: notification::Notification) {
+        match self {
+            Solver::Baseline(solver) => solver.notify(notification),
+            Solver::Dex(solver) => solver.notify(notification),
+        }
+    }
+}
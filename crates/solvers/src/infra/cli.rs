@@ -39,4 +39,9 @@ pub enum Command {
         #[clap(long, env)]
         config: PathBuf,
     },
+    /// solve individual orders by routing them through the 0x swap API
+    ZeroEx {
+        #[clap(long, env)]
+        config: PathBuf,
+    },
 }
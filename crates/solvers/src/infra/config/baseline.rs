@@ -1,6 +1,6 @@
 use {
     crate::{
-        domain::{eth, solver},
+        domain::{eth, feedback, solver::baseline},
         infra::contracts,
         util::serialize,
     },
@@ -8,8 +8,7 @@ use {
     ethereum_types::H160,
     serde::Deserialize,
     serde_with::serde_as,
-    shared::price_estimation::gas::SETTLEMENT_OVERHEAD,
-    std::{fmt::Debug, path::Path},
+    std::{path::Path, time::Duration},
     tokio::fs,
 };
 
@@ -41,13 +40,30 @@ struct Config {
 
     /// Units of gas that get added to the gas estimate for executing a
     /// computed trade route to arrive at a gas estimate for a whole settlement.
-    #[serde(default = "default_gas_offset")]
+    #[serde(default = "super::default_gas_offset")]
     solution_gas_offset: i64,
 
     /// The amount of the native token to use to estimate native price of a
     /// token
     #[serde_as(as = "serialize::U256")]
     native_token_price_estimation_amount: eth::U256,
+
+    /// Number of failed simulations or reverted settlements a liquidity
+    /// source needs to be involved in before the solver stops using it. The
+    /// solver keeps using failing liquidity if not specified.
+    failure_threshold: Option<u32>,
+
+    /// For how long the solver stops using liquidity sources that reached the
+    /// failure threshold.
+    #[serde(with = "humantime_serde", default = "default_failure_cooldown")]
+    failure_cooldown: Duration,
+
+    /// Whether to quickly find direct routes for all orders first and then
+    /// improve them with deeper searches until the deadline is reached.
+    /// Otherwise orders get fully solved one after another, which can leave
+    /// orders of big auctions unsolved.
+    #[serde(default)]
+    anytime_search: bool,
 }
 
 /// Load the driver configuration from a TOML file.
@@ -55,12 +71,12 @@ struct Config {
 /// # Panics
 ///
 /// This method panics if the config is invalid or on I/O errors.
-pub async fn load(path: &Path) -> solver::Config {
+pub async fn load(path: &Path) -> baseline::Config {
     let data = fs::read_to_string(path)
         .await
         .unwrap_or_else(|e| panic!("I/O error while reading {path:?}: {e:?}"));
     // Not printing detailed error because it could potentially leak secrets.
-    let config = unwrap_or_log(toml::de::from_str::<Config>(&data), &path);
+    let config = super::unwrap_or_log(toml::de::from_str::<Config>(&data), &path);
     let weth = match (config.chain_id, config.weth) {
         (Some(chain_id), None) => contracts::Contracts::for_chain(chain_id).weth,
         (None, Some(weth)) => eth::WethAddress(weth),
@@ -73,7 +89,7 @@ pub async fn load(path: &Path) -> solver::Config {
         ),
     };
 
-    solver::Config {
+    baseline::Config {
         weth,
         base_tokens: config
             .base_tokens
@@ -84,29 +100,16 @@ pub async fn load(path: &Path) -> solver::Config {
         max_partial_attempts: config.max_partial_attempts,
         solution_gas_offset: config.solution_gas_offset.into(),
         native_token_price_estimation_amount: config.native_token_price_estimation_amount,
+        feedback: config
+            .failure_threshold
+            .map(|failure_threshold| feedback::Config {
+                failure_threshold,
+                cooldown: config.failure_cooldown,
+            }),
+        anytime_search: config.anytime_search,
     }
 }
 
-/// Unwraps result or logs a `TOML` parsing error.
-fn unwrap_or_log<T, E, P>(result: Result<T, E>, path: &P) -> T
-where
-    E: Debug,
-    P: Debug,
-{
-    result.unwrap_or_else(|err| {
-        if std::env::var("TOML_TRACE_ERROR").is_ok_and(|v| v == "1") {
-            panic!("failed to parse TOML config at {path:?}: {err:#?}")
-        } else {
-            panic!(
-                "failed to parse TOML config at: {path:?}. Set TOML_TRACE_ERROR=1 to print \
-                 parsing error but this may leak secrets."
-            )
-        }
-    })
-}
-
-/// Returns minimum gas used for settling a single order.
-/// (not accounting for the cost of additional interactions)
-fn default_gas_offset() -> i64 {
-    SETTLEMENT_OVERHEAD.try_into().unwrap()
+fn default_failure_cooldown() -> Duration {
+    Duration::from_secs(10 * 60)
 }
@@ -0,0 +1,85 @@
+use {
+    crate::{
+        domain::{dex, solver::dex as dex_solver},
+        infra::{contracts, dex::zeroex},
+    },
+    chain::Chain,
+    serde::Deserialize,
+    std::{num::NonZeroUsize, path::Path},
+    tokio::fs,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Config {
+    /// The chain ID of the network to swap on. This is also used to determine
+    /// the address of the settlement contract executing the swaps.
+    chain_id: Chain,
+
+    /// The base URL for the 0x API.
+    #[serde(default = "default_endpoint")]
+    endpoint: String,
+
+    /// 0x liquidity sources to exclude from swaps.
+    #[serde(default)]
+    excluded_sources: Vec<String>,
+
+    /// The API key to use.
+    api_key: Option<String>,
+
+    /// The relative slippage tolerance in basis points.
+    #[serde(default = "default_slippage_bps")]
+    slippage_bps: u32,
+
+    /// The number of orders to concurrently request swaps for.
+    #[serde(default = "default_concurrent_requests")]
+    concurrent_requests: NonZeroUsize,
+
+    /// Units of gas that get added to the gas estimate of a swap to arrive at
+    /// a gas estimate for a whole settlement.
+    #[serde(default = "super::default_gas_offset")]
+    solution_gas_offset: i64,
+}
+
+/// Load the 0x solver configuration from a TOML file.
+///
+/// # Panics
+///
+/// This method panics if the config is invalid or on I/O errors.
+pub async fn load(path: &Path) -> (zeroex::Config, dex_solver::Config) {
+    let data = fs::read_to_string(path)
+        .await
+        .unwrap_or_else(|e| panic!("I/O error while reading {path:?}: {e:?}"));
+    // Not printing detailed error because it could potentially leak secrets.
+    let config = super::unwrap_or_log(toml::de::from_str::<Config>(&data), &path);
+
+    let zeroex = zeroex::Config {
+        endpoint: config
+            .endpoint
+            .parse()
+            .expect("invalid configuration: `endpoint` is not a valid URL"),
+        chain_id: config.chain_id.id(),
+        excluded_sources: config.excluded_sources,
+        settlement: contracts::Contracts::for_chain(config.chain_id).settlement,
+        api_key: config.api_key,
+    };
+    let solver = dex_solver::Config {
+        slippage: dex::Slippage::from_bps(config.slippage_bps)
+            .expect("invalid configuration: `slippage-bps` must be less than 10000"),
+        concurrent_requests: config.concurrent_requests,
+        solution_gas_offset: config.solution_gas_offset.into(),
+    };
+    (zeroex, solver)
+}
+
+fn default_endpoint() -> String {
+    shared::zeroex_api::DefaultZeroExApi::DEFAULT_URL.to_owned()
+}
+
+fn default_slippage_bps() -> u32 {
+    100
+}
+
+fn default_concurrent_requests() -> NonZeroUsize {
+    NonZeroUsize::new(1).unwrap()
+}
@@ -0,0 +1,41 @@
+//! DEX aggregator APIs that solver engines can route orders through.
+
+use crate::domain::dex;
+
+pub mod zeroex;
+
+/// An adapter for a DEX aggregator API.
+#[async_trait::async_trait]
+pub trait Aggregator: Send + Sync {
+    /// Quotes a swap executing the order with at most the specified slippage.
+    /// The returned swap contains the quoted amounts without any slippage
+    /// applied.
+    async fn swap(&self, order: &dex::Order, slippage: &dex::Slippage) -> Result<dex::Swap, Error>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("no valid swap found")]
+    NotFound,
+    #[error("order type is not supported")]
+    OrderNotSupported,
+    #[error("rate limited")]
+    RateLimited,
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("API error: {0}")]
+    Api(String),
+}
+
+impl Error {
+    /// Returns a short label describing the error for metrics.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Error::NotFound => "not_found",
+            Error::OrderNotSupported => "order_not_supported",
+            Error::RateLimited => "rate_limited",
+            Error::Http(_) => "http",
+            Error::Api(_) => "api",
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use {
+    crate::{
+        domain::{dex, eth, order},
+        infra::dex::{Aggregator, Error},
+    },
+    ethrpc::block_stream::BlockInfo,
+    shared::zeroex_api::{self, DefaultZeroExApi, ZeroExApi, ZeroExResponseError},
+    std::sync::Arc,
+};
+
+/// Bindings to the 0x swap API.
+pub struct ZeroEx {
+    api: Arc<dyn ZeroExApi>,
+    defaults: zeroex_api::SwapQuery,
+}
+
+pub struct Config {
+    /// The base URL for the 0x API.
+    pub endpoint: reqwest::Url,
+
+    /// The chain ID of the network to swap on.
+    pub chain_id: u64,
+
+    /// 0x liquidity sources to exclude from swaps.
+    pub excluded_sources: Vec<String>,
+
+    /// The address of the settlement contract executing the swaps.
+    pub settlement: eth::ContractAddress,
+
+    /// The API key to use.
+    pub api_key: Option<String>,
+}
+
+impl ZeroEx {
+    pub fn new(config: Config) -> Result<Self, Error> {
+        // The current block is only sent along with limit order requests which
+        // this solver does not make.
+        let (_, block_stream) = tokio::sync::watch::channel(BlockInfo::default());
+        let api = DefaultZeroExApi::new(
+            reqwest::Client::builder(),
+            config.endpoint,
+            config.api_key,
+            block_stream,
+        )
+        .map_err(|err| Error::Api(format!("{err:#}")))?;
+        Ok(Self::with_api(
+            Arc::new(api),
+            zeroex_api::SwapQuery {
+                chain_id: config.chain_id,
+                taker: config.settlement.0,
+                excluded_sources: config.excluded_sources,
+                ..Default::default()
+            },
+        ))
+    }
+
+    fn with_api(api: Arc<dyn ZeroExApi>, defaults: zeroex_api::SwapQuery) -> Self {
+        Self { api, defaults }
+    }
+}
+
+#[async_trait::async_trait]
+impl Aggregator for ZeroEx {
+    async fn swap(&self, order: &dex::Order, slippage: &dex::Slippage) -> Result<dex::Swap, Error> {
+        // The 0x swap API only quotes exact sell amounts.
+        if order.side != order::Side::Sell {
+            return Err(Error::OrderNotSupported);
+        }
+        let query = zeroex_api::SwapQuery {
+            sell_token: order.sell.0,
+            buy_token: order.buy.0,
+            sell_amount: order.amount,
+            slippage_bps: slippage.bps(),
+            ..self.defaults.clone()
+        };
+        let quote = self.api.get_swap(&query).await?;
+        Ok(swap(order, quote))
+    }
+}
+
+fn swap(order: &dex::Order, quote: zeroex_api::SwapResponse) -> dex::Swap {
+    let spender = quote.allowance_target();
+    dex::Swap {
+        call: dex::Call {
+            to: eth::ContractAddress(quote.transaction.to),
+            calldata: quote.transaction.data,
+        },
+        value: eth::Ether(quote.transaction.value),
+        input: eth::Asset {
+            token: order.sell,
+            amount: quote.sell_amount,
+        },
+        output: eth::Asset {
+            token: order.buy,
+            amount: quote.buy_amount,
+        },
+        allowance: dex::Allowance {
+            spender: eth::ContractAddress(spender),
+            amount: quote.sell_amount,
+        },
+        gas: eth::Gas(quote.transaction.gas),
+    }
+}
+
+impl From<ZeroExResponseError> for Error {
+    fn from(err: ZeroExResponseError) -> Self {
+        match err {
+            ZeroExResponseError::InsufficientLiquidity => Self::NotFound,
+            ZeroExResponseError::RateLimited => Self::RateLimited,
+            ZeroExResponseError::Send(err) | ZeroExResponseError::TextFetch(err) => Self::Http(err),
+            err => Self::Api(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, ethereum_types::H160, shared::zeroex_api::MockZeroExApi};
+
+    #[tokio::test]
+    async fn converts_quote_into_swap() {
+        let settlement = H160::repeat_byte(0x90);
+        let holder = H160::repeat_byte(0xaa);
+        let mut api = MockZeroExApi::new();
+        api.expect_get_swap()
+            .withf(move |query| {
+                *query
+                    == zeroex_api::SwapQuery {
+                        chain_id: 1,
+                        sell_token: H160::repeat_byte(1),
+                        buy_token: H160::repeat_byte(2),
+                        sell_amount: eth::U256::exp10(18),
+                        taker: settlement,
+                        slippage_bps: 100,
+                        excluded_sources: vec!["Uniswap_V2".to_owned()],
+                    }
+            })
+            .returning(move |_| {
+                Ok(zeroex_api::SwapResponse {
+                    sell_amount: eth::U256::exp10(18),
+                    buy_amount: eth::U256::from(1500) * eth::U256::exp10(18),
+                    transaction: zeroex_api::SwapTransaction {
+                        to: holder,
+                        data: vec![0x22, 0x13, 0xbc, 0x0b],
+                        value: 0.into(),
+                        gas: 111_000.into(),
+                    },
+                    issues: Default::default(),
+                })
+            });
+        let zeroex = ZeroEx::with_api(
+            Arc::new(api),
+            zeroex_api::SwapQuery {
+                chain_id: 1,
+                taker: settlement,
+                excluded_sources: vec!["Uniswap_V2".to_owned()],
+                ..Default::default()
+            },
+        );
+        let slippage = dex::Slippage::from_bps(100).unwrap();
+        let order = dex::Order {
+            sell: eth::TokenAddress(H160::repeat_byte(1)),
+            buy: eth::TokenAddress(H160::repeat_byte(2)),
+            side: order::Side::Sell,
+            amount: eth::U256::exp10(18),
+        };
+
+        let swap = zeroex.swap(&order, &slippage).await.unwrap();
+        assert_eq!(swap.call.to.0, holder);
+        assert_eq!(swap.call.calldata, vec![0x22, 0x13, 0xbc, 0x0b]);
+        assert_eq!(swap.input.amount, eth::U256::exp10(18));
+        assert_eq!(
+            swap.output.amount,
+            eth::U256::from(1500) * eth::U256::exp10(18)
+        );
+        assert_eq!(swap.allowance.spender.0, holder);
+        assert_eq!(swap.allowance.amount, eth::U256::exp10(18));
+        assert_eq!(swap.gas.0, 111_000.into());
+
+        let buy = dex::Order {
+            side: order::Side::Buy,
+            ..order
+        };
+        assert!(matches!(
+            zeroex.swap(&buy, &slippage).await,
+            Err(Error::OrderNotSupported)
+        ));
+    }
+}
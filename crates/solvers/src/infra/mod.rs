@@ -1,4 +1,5 @@
 pub mod cli;
 pub mod config;
 pub mod contracts;
+pub mod dex;
 pub mod metrics;
@@ -3,7 +3,7 @@ use tokio::signal::unix::{self, SignalKind};
 use {
     crate::{
         domain::solver,
-        infra::{cli, config},
+        infra::{cli, config, dex::zeroex},
     },
     clap::Parser,
     std::net::SocketAddr,
@@ -30,8 +30,13 @@ async fn run_with(args: cli::Args, bind: Option<oneshot::Sender<SocketAddr>>) {
 
     let solver = match args.command {
         cli::Command::Baseline { config } => {
-            let config = config::load(&config).await;
-            solver::Solver::new(config)
+            let config = config::baseline::load(&config).await;
+            solver::Solver::Baseline(solver::Baseline::new(config))
+        }
+        cli::Command::ZeroEx { config } => {
+            let (zeroex, config) = config::zeroex::load(&config).await;
+            let zeroex = zeroex::ZeroEx::new(zeroex).expect("failed to create 0x API client");
+            solver::Solver::Dex(solver::Dex::new(Box::new(zeroex), config))
         }
     };
 
@@ -0,0 +1,178 @@
+//! Test case that verifies that the anytime search replaces a quickly found
+//! direct route with a better route found in a deeper search stage.
+
+use {crate::tests, serde_json::json};
+
+#[tokio::test]
+async fn test() {
+    let engine = tests::SolverEngine::new(
+        "baseline",
+        tests::Config::String(
+            r#"
+                chain-id = "1"
+                base-tokens = []
+                max-hops = 1
+                max-partial-attempts = 5
+                native-token-price-estimation-amount = "100000000000000000"
+                anytime-search = true
+            "#
+            .to_owned(),
+        ),
+    )
+    .await;
+
+    let solution = engine
+        .solve(json!({
+            "id": "1",
+            "tokens": {
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                    "decimals": 18,
+                    "symbol": "WETH",
+                    "referencePrice": "1000000000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                },
+                "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                    "decimals": 18,
+                    "symbol": "COW",
+                    "referencePrice": "21313588604228",
+                    "availableBalance": "0",
+                    "trusted": false
+                },
+                "0x6B175474E89094C44Da98b954EedeAC495271d0F": {
+                    "decimals": 18,
+                    "symbol": "DAI",
+                    "referencePrice": "500000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                }
+            },
+            "orders": [
+                {
+                    "uid": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a",
+                    "sellToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                    "buyToken": "0x6B175474E89094C44Da98b954EedeAC495271d0F",
+                    "sellAmount": "1000000000000000000000",
+                    "fullSellAmount": "1000000000000000000000",
+                    "buyAmount": "5000000000000000000",
+                    "fullBuyAmount": "5000000000000000000",
+                    "feePolicies": [],
+                    "validTo": 0,
+                    "kind": "sell",
+                    "owner": "0x5b1e2c2762667331bc91648052f646d1b0d35984",
+                    "partiallyFillable": false,
+                    "preInteractions": [],
+                    "postInteractions": [],
+                    "sellTokenSource": "erc20",
+                    "buyTokenDestination": "erc20",
+                    "class": "market",
+                    "appData": "0x6000000000000000000000000000000000000000000000000000000000000007",
+                    "signingScheme": "presign",
+                    "signature": "0x",
+                }
+            ],
+            "liquidity": [
+                {
+                    "kind": "constantProduct",
+                    "tokens": {
+                        "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                            "balance": "1000000000000000000000000"
+                        },
+                        "0x6B175474E89094C44Da98b954EedeAC495271d0F": {
+                            "balance": "10000000000000000000000"
+                        }
+                    },
+                    "fee": "0.003",
+                    "id": "0",
+                    "address": "0x0000000000000000000000000000000000000001",
+                    "router": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                    "gasEstimate": "110000"
+                },
+                {
+                    "kind": "constantProduct",
+                    "tokens": {
+                        "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                            "balance": "3828187314911751990"
+                        },
+                        "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                            "balance": "179617892578796375604692"
+                        }
+                    },
+                    "fee": "0.003",
+                    "id": "1",
+                    "address": "0x97b744df0b59d93A866304f97431D8EfAd29a08d",
+                    "router": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                    "gasEstimate": "110000"
+                },
+                {
+                    "kind": "constantProduct",
+                    "tokens": {
+                        "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                            "balance": "1000000000000000000000"
+                        },
+                        "0x6B175474E89094C44Da98b954EedeAC495271d0F": {
+                            "balance": "2000000000000000000000000"
+                        }
+                    },
+                    "fee": "0.003",
+                    "id": "2",
+                    "address": "0x0000000000000000000000000000000000000002",
+                    "router": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                    "gasEstimate": "110000"
+                }
+            ],
+            "effectiveGasPrice": "15000000000",
+            "deadline": "2106-01-01T00:00:00.000Z",
+            "surplusCapturingJitOrderOwners": []
+        }))
+        .await;
+
+    // The direct route found in the first stage only buys ~9.96 DAI and gets
+    // replaced by the route over WETH.
+    assert_eq!(
+        solution,
+        json!({
+            "solutions": [{
+                "id": 0,
+                "prices": {
+                    "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab": "42135755493700815822",
+                    "0x6b175474e89094c44da98b954eedeac495271d0f": "1000000000000000000000"
+                },
+                "trades": [
+                    {
+                        "kind": "fulfillment",
+                        "order": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                                    2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                                    2a2a2a2a",
+                        "executedAmount": "1000000000000000000000"
+                    }
+                ],
+                "preInteractions": [],
+                "interactions": [
+                    {
+                        "kind": "liquidity",
+                        "internalize": false,
+                        "id": "1",
+                        "inputToken": "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+                        "outputToken": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                        "inputAmount": "1000000000000000000000",
+                        "outputAmount": "21131716761960335"
+                    },
+                    {
+                        "kind": "liquidity",
+                        "internalize": false,
+                        "id": "2",
+                        "inputToken": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                        "outputToken": "0x6b175474e89094c44da98b954eedeac495271d0f",
+                        "inputAmount": "21131716761960335",
+                        "outputAmount": "42135755493700815822"
+                    }
+                ],
+                "postInteractions": [],
+                "gas": 276391,
+            }]
+        }),
+    );
+}
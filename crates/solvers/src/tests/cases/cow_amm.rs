@@ -0,0 +1,516 @@
+//! Test cases that verify that the baseline solver settles an order with a
+//! CoW AMM by settling the pool's rebalancing order as a JIT order when it
+//! offers a better price than regular AMMs, and only if the order trades
+//! exactly the pool's amounts on a direct route.
+
+use {crate::tests, serde_json::json};
+
+#[tokio::test]
+async fn sell_order() {
+    let engine = tests::SolverEngine::new(
+        "baseline",
+        tests::Config::File("config/example.baseline.toml".into()),
+    )
+    .await;
+
+    let solution = engine
+        .solve(json!({
+            "id": "1",
+            "tokens": {
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                    "decimals": 18,
+                    "symbol": "WETH",
+                    "referencePrice": "1000000000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                },
+                "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                    "decimals": 18,
+                    "symbol": "COW",
+                    "referencePrice": "20000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                }
+            },
+            "orders": [
+                {
+                    "uid": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a",
+                    "sellToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                    "buyToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                    "sellAmount": "1000000000000000000",
+                    "fullSellAmount": "1000000000000000000",
+                    "buyAmount": "30000000000000000000000",
+                    "fullBuyAmount": "30000000000000000000000",
+                    "feePolicies": [],
+                    "validTo": 0,
+                    "kind": "sell",
+                    "owner": "0x5b1e2c2762667331bc91648052f646d1b0d35984",
+                    "partiallyFillable": false,
+                    "preInteractions": [],
+                    "postInteractions": [],
+                    "sellTokenSource": "erc20",
+                    "buyTokenDestination": "erc20",
+                    "class": "market",
+                    "appData": "0x6000000000000000000000000000000000000000000000000000000000000007",
+                    "signingScheme": "presign",
+                    "signature": "0x",
+                }
+            ],
+            "liquidity": [
+                {
+                    "kind": "constantProduct",
+                    "tokens": {
+                        "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                            "balance": "3828187314911751990"
+                        },
+                        "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                            "balance": "179617892578796375604692"
+                        }
+                    },
+                    "fee": "0.003",
+                    "id": "0",
+                    "address": "0x97b744df0b59d93A866304f97431D8EfAd29a08d",
+                    "router": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                    "gasEstimate": "110000"
+                },
+                {
+                    "kind": "cowAmm",
+                    "order": {
+                        "sellToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                        "buyToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                        "receiver": null,
+                        "sellAmount": "50000000000000000000000",
+                        "buyAmount": "1000000000000000000",
+                        "validTo": 1700000000,
+                        "appData": "0x362e5182440b52aa8fffe70a251550fbbcbca424740fe5a14f59bf0c1b06fe1d",
+                        "signature": "0xc0ffee",
+                        "preInteractions": [
+                            {
+                                "target": "0xBEEF5AfE88eF73337e5070aB2855d37dBF5493A4",
+                                "value": "0",
+                                "callData": "0xf14fcbc8\
+                                             84c5a51e7ba11aa017d72a9bf7febfe62e2f432027826d46d2786581032590af"
+                            }
+                        ],
+                        "postInteractions": []
+                    },
+                    "id": "1",
+                    "address": "0xBEEF5AfE88eF73337e5070aB2855d37dBF5493A4",
+                    "gasEstimate": "50000"
+                }
+            ],
+            "effectiveGasPrice": "15000000000",
+            "deadline": "2106-01-01T00:00:00.000Z",
+            "surplusCapturingJitOrderOwners": []
+        }))
+        .await;
+
+    assert_eq!(
+        solution,
+        json!({
+            "solutions": [{
+                "id": 0,
+                "prices": {
+                    "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2": "50000000000000000000000",
+                    "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab": "1000000000000000000"
+                },
+                "trades": [
+                    {
+                        "kind": "fulfillment",
+                        "order": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                                    2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                                    2a2a2a2a",
+                        "executedAmount": "1000000000000000000"
+                    },
+                    {
+                        "kind": "jit",
+                        "order": {
+                            "sellToken": "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+                            "buyToken": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                            "receiver": "0x0000000000000000000000000000000000000000",
+                            "sellAmount": "50000000000000000000000",
+                            "buyAmount": "1000000000000000000",
+                            "partiallyFillable": false,
+                            "validTo": 1700000000,
+                            "appData": "0x362e5182440b52aa8fffe70a251550fbbcbca424740fe5a14f59bf0c1b06fe1d",
+                            "kind": "sell",
+                            "sellTokenBalance": "erc20",
+                            "buyTokenBalance": "erc20",
+                            "signingScheme": "eip1271",
+                            "signature": "0xbeef5afe88ef73337e5070ab2855d37dbf5493a4c0ffee"
+                        },
+                        "executedAmount": "50000000000000000000000",
+                        "fee": "0"
+                    }
+                ],
+                "preInteractions": [
+                    {
+                        "kind": "Call",
+                        "target": "0xbeef5afe88ef73337e5070ab2855d37dbf5493a4",
+                        "value": "0x0",
+                        "callData": "0xf14fcbc8\
+                                   84c5a51e7ba11aa017d72a9bf7febfe62e2f432027826d46d2786581032590af"
+                    }
+                ],
+                "interactions": [],
+                "postInteractions": [],
+                "gas": 156391,
+            }]
+        }),
+    );
+}
+
+#[tokio::test]
+async fn buy_order() {
+    let engine = tests::SolverEngine::new(
+        "baseline",
+        tests::Config::File("config/example.baseline.toml".into()),
+    )
+    .await;
+
+    let solution = engine
+        .solve(json!({
+            "id": "1",
+            "tokens": {
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                    "decimals": 18,
+                    "symbol": "WETH",
+                    "referencePrice": "1000000000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                },
+                "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                    "decimals": 18,
+                    "symbol": "COW",
+                    "referencePrice": "20000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                }
+            },
+            "orders": [
+                {
+                    "uid": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a",
+                    "sellToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                    "buyToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                    "sellAmount": "1100000000000000000",
+                    "fullSellAmount": "1100000000000000000",
+                    "buyAmount": "50000000000000000000000",
+                    "fullBuyAmount": "50000000000000000000000",
+                    "feePolicies": [],
+                    "validTo": 0,
+                    "kind": "buy",
+                    "owner": "0x5b1e2c2762667331bc91648052f646d1b0d35984",
+                    "partiallyFillable": false,
+                    "preInteractions": [],
+                    "postInteractions": [],
+                    "sellTokenSource": "erc20",
+                    "buyTokenDestination": "erc20",
+                    "class": "market",
+                    "appData": "0x6000000000000000000000000000000000000000000000000000000000000007",
+                    "signingScheme": "presign",
+                    "signature": "0x",
+                }
+            ],
+            "liquidity": [
+                {
+                    "kind": "cowAmm",
+                    "order": {
+                        "sellToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                        "buyToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                        "receiver": null,
+                        "sellAmount": "50000000000000000000000",
+                        "buyAmount": "1000000000000000000",
+                        "validTo": 1700000000,
+                        "appData": "0x362e5182440b52aa8fffe70a251550fbbcbca424740fe5a14f59bf0c1b06fe1d",
+                        "signature": "0xc0ffee",
+                        "preInteractions": [
+                            {
+                                "target": "0xBEEF5AfE88eF73337e5070aB2855d37dBF5493A4",
+                                "value": "0",
+                                "callData": "0xf14fcbc8\
+                                             84c5a51e7ba11aa017d72a9bf7febfe62e2f432027826d46d2786581032590af"
+                            }
+                        ],
+                        "postInteractions": []
+                    },
+                    "id": "1",
+                    "address": "0xBEEF5AfE88eF73337e5070aB2855d37dBF5493A4",
+                    "gasEstimate": "50000"
+                }
+            ],
+            "effectiveGasPrice": "15000000000",
+            "deadline": "2106-01-01T00:00:00.000Z",
+            "surplusCapturingJitOrderOwners": []
+        }))
+        .await;
+
+    assert_eq!(
+        solution,
+        json!({
+            "solutions": [{
+                "id": 0,
+                "prices": {
+                    "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2": "50000000000000000000000",
+                    "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab": "1000000000000000000"
+                },
+                "trades": [
+                    {
+                        "kind": "fulfillment",
+                        "order": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                                    2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                                    2a2a2a2a",
+                        "executedAmount": "50000000000000000000000"
+                    },
+                    {
+                        "kind": "jit",
+                        "order": {
+                            "sellToken": "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+                            "buyToken": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                            "receiver": "0x0000000000000000000000000000000000000000",
+                            "sellAmount": "50000000000000000000000",
+                            "buyAmount": "1000000000000000000",
+                            "partiallyFillable": false,
+                            "validTo": 1700000000,
+                            "appData": "0x362e5182440b52aa8fffe70a251550fbbcbca424740fe5a14f59bf0c1b06fe1d",
+                            "kind": "sell",
+                            "sellTokenBalance": "erc20",
+                            "buyTokenBalance": "erc20",
+                            "signingScheme": "eip1271",
+                            "signature": "0xbeef5afe88ef73337e5070ab2855d37dbf5493a4c0ffee"
+                        },
+                        "executedAmount": "50000000000000000000000",
+                        "fee": "0"
+                    }
+                ],
+                "preInteractions": [
+                    {
+                        "kind": "Call",
+                        "target": "0xbeef5afe88ef73337e5070ab2855d37dbf5493a4",
+                        "value": "0x0",
+                        "callData": "0xf14fcbc8\
+                                   84c5a51e7ba11aa017d72a9bf7febfe62e2f432027826d46d2786581032590af"
+                    }
+                ],
+                "interactions": [],
+                "postInteractions": [],
+                "gas": 156391,
+            }]
+        }),
+    );
+}
+
+#[tokio::test]
+async fn buy_order_for_less_than_the_pool_sells() {
+    let engine = tests::SolverEngine::new(
+        "baseline",
+        tests::Config::File("config/example.baseline.toml".into()),
+    )
+    .await;
+
+    let solution = engine
+        .solve(json!({
+            "id": "1",
+            "tokens": {
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                    "decimals": 18,
+                    "symbol": "WETH",
+                    "referencePrice": "1000000000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                },
+                "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                    "decimals": 18,
+                    "symbol": "COW",
+                    "referencePrice": "20000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                }
+            },
+            "orders": [
+                {
+                    "uid": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a",
+                    "sellToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                    "buyToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                    "sellAmount": "1100000000000000000",
+                    "fullSellAmount": "1100000000000000000",
+                    "buyAmount": "40000000000000000000000",
+                    "fullBuyAmount": "40000000000000000000000",
+                    "feePolicies": [],
+                    "validTo": 0,
+                    "kind": "buy",
+                    "owner": "0x5b1e2c2762667331bc91648052f646d1b0d35984",
+                    "partiallyFillable": false,
+                    "preInteractions": [],
+                    "postInteractions": [],
+                    "sellTokenSource": "erc20",
+                    "buyTokenDestination": "erc20",
+                    "class": "market",
+                    "appData": "0x6000000000000000000000000000000000000000000000000000000000000007",
+                    "signingScheme": "presign",
+                    "signature": "0x",
+                }
+            ],
+            "liquidity": [
+                {
+                    "kind": "cowAmm",
+                    "order": {
+                        "sellToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                        "buyToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                        "receiver": null,
+                        "sellAmount": "50000000000000000000000",
+                        "buyAmount": "1000000000000000000",
+                        "validTo": 1700000000,
+                        "appData": "0x362e5182440b52aa8fffe70a251550fbbcbca424740fe5a14f59bf0c1b06fe1d",
+                        "signature": "0xc0ffee",
+                        "preInteractions": [
+                            {
+                                "target": "0xBEEF5AfE88eF73337e5070aB2855d37dBF5493A4",
+                                "value": "0",
+                                "callData": "0xf14fcbc8\
+                                             84c5a51e7ba11aa017d72a9bf7febfe62e2f432027826d46d2786581032590af"
+                            }
+                        ],
+                        "postInteractions": []
+                    },
+                    "id": "1",
+                    "address": "0xBEEF5AfE88eF73337e5070aB2855d37dBF5493A4",
+                    "gasEstimate": "50000"
+                }
+            ],
+            "effectiveGasPrice": "15000000000",
+            "deadline": "2106-01-01T00:00:00.000Z",
+            "surplusCapturingJitOrderOwners": []
+        }))
+        .await;
+
+    assert_eq!(solution, json!({ "solutions": [] }));
+}
+
+#[tokio::test]
+async fn multi_hop() {
+    let engine = tests::SolverEngine::new(
+        "baseline",
+        tests::Config::String(
+            r#"
+                chain-id = "1"
+                base-tokens = ["0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB"]
+                max-hops = 1
+                max-partial-attempts = 5
+                native-token-price-estimation-amount = "100000000000000000"
+            "#
+            .to_owned(),
+        ),
+    )
+    .await;
+
+    // The route WETH -> COW -> USDC would use the whole rebalancing order, but
+    // there would be no clearing price for COW to settle it at.
+    let solution = engine
+        .solve(json!({
+            "id": "1",
+            "tokens": {
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                    "decimals": 18,
+                    "symbol": "WETH",
+                    "referencePrice": "1000000000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                },
+                "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                    "decimals": 18,
+                    "symbol": "COW",
+                    "referencePrice": "20000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                },
+                "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48": {
+                    "decimals": 6,
+                    "symbol": "USDC",
+                    "referencePrice": "400000000000000000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                }
+            },
+            "orders": [
+                {
+                    "uid": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a",
+                    "sellToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                    "buyToken": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+                    "sellAmount": "1000000000000000000",
+                    "fullSellAmount": "1000000000000000000",
+                    "buyAmount": "1000000000",
+                    "fullBuyAmount": "1000000000",
+                    "feePolicies": [],
+                    "validTo": 0,
+                    "kind": "sell",
+                    "owner": "0x5b1e2c2762667331bc91648052f646d1b0d35984",
+                    "partiallyFillable": false,
+                    "preInteractions": [],
+                    "postInteractions": [],
+                    "sellTokenSource": "erc20",
+                    "buyTokenDestination": "erc20",
+                    "class": "market",
+                    "appData": "0x6000000000000000000000000000000000000000000000000000000000000007",
+                    "signingScheme": "presign",
+                    "signature": "0x",
+                }
+            ],
+            "liquidity": [
+                {
+                    "kind": "constantProduct",
+                    "tokens": {
+                        "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                            "balance": "179617892578796375604692"
+                        },
+                        "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48": {
+                            "balance": "10000000000000"
+                        }
+                    },
+                    "fee": "0.003",
+                    "id": "0",
+                    "address": "0x97b744df0b59d93A866304f97431D8EfAd29a08d",
+                    "router": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                    "gasEstimate": "110000"
+                },
+                {
+                    "kind": "cowAmm",
+                    "order": {
+                        "sellToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                        "buyToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                        "receiver": null,
+                        "sellAmount": "50000000000000000000000",
+                        "buyAmount": "1000000000000000000",
+                        "validTo": 1700000000,
+                        "appData": "0x362e5182440b52aa8fffe70a251550fbbcbca424740fe5a14f59bf0c1b06fe1d",
+                        "signature": "0xc0ffee",
+                        "preInteractions": [
+                            {
+                                "target": "0xBEEF5AfE88eF73337e5070aB2855d37dBF5493A4",
+                                "value": "0",
+                                "callData": "0xf14fcbc8\
+                                             84c5a51e7ba11aa017d72a9bf7febfe62e2f432027826d46d2786581032590af"
+                            }
+                        ],
+                        "postInteractions": []
+                    },
+                    "id": "1",
+                    "address": "0xBEEF5AfE88eF73337e5070aB2855d37dBF5493A4",
+                    "gasEstimate": "50000"
+                }
+            ],
+            "effectiveGasPrice": "15000000000",
+            "deadline": "2106-01-01T00:00:00.000Z",
+            "surplusCapturingJitOrderOwners": []
+        }))
+        .await;
+
+    assert_eq!(solution, json!({ "solutions": [] }));
+}
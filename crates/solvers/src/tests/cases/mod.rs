@@ -1,8 +1,12 @@
-//! Baseline solver test cases.
+//! Solver engine test cases.
 
+mod anytime_search;
 mod bal_liquidity;
 mod buy_order_rounding;
+mod cow_amm;
 mod direct_swap;
+mod fee_on_transfer;
 mod internalization;
 mod limit_order_quoting;
 mod partial_fill;
+mod zeroex;
@@ -0,0 +1,187 @@
+//! Test case that verifies that the 0x solver engine settles an order with a
+//! swap quoted by the 0x API.
+
+use {
+    crate::tests,
+    axum::{
+        Json,
+        extract::Query,
+        http::HeaderMap,
+        routing::get,
+    },
+    serde_json::{Value, json},
+    std::collections::HashMap,
+};
+
+/// Serves a fixed 0x swap quote for the expected query.
+async fn quote(headers: HeaderMap, Query(query): Query<HashMap<String, String>>) -> Json<Value> {
+    assert_eq!(headers["0x-version"], "v2");
+    assert_eq!(headers["0x-api-key"], "secret");
+    assert_eq!(
+        query,
+        HashMap::from(
+            [
+                ("chainId", "1"),
+                ("sellToken", "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+                ("buyToken", "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab"),
+                ("sellAmount", "1000000000000000000"),
+                ("taker", "0x9008d19f58aabd9ed0d60971565aa8510560ab41"),
+                ("slippageBps", "100"),
+                ("excludedSources", "Uniswap_V2"),
+            ]
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        ),
+    );
+    Json(json!({
+        "liquidityAvailable": true,
+        "sellAmount": "1000000000000000000",
+        "buyAmount": "1500000000000000000000",
+        "minBuyAmount": "1485000000000000000000",
+        "issues": {
+            "allowance": {
+                "actual": "0",
+                "spender": "0x0000000000001ff3684f28c67538d4d072c22734"
+            },
+            "balance": null,
+            "simulationIncomplete": false
+        },
+        "transaction": {
+            "to": "0x0000000000001ff3684f28c67538d4d072c22734",
+            "data": "0x2213bc0b",
+            "gas": "111000",
+            "gasPrice": "15000000000",
+            "value": "0"
+        }
+    }))
+}
+
+#[tokio::test]
+async fn test() {
+    let api = axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(
+        axum::Router::new()
+            .route("/swap/allowance-holder/quote", get(quote))
+            .into_make_service(),
+    );
+    let endpoint = format!("http://{}/", api.local_addr());
+    tokio::spawn(api);
+
+    let engine = tests::SolverEngine::new(
+        "zeroex",
+        tests::Config::String(format!(
+            r#"
+chain-id = "1"
+endpoint = "{endpoint}"
+api-key = "secret"
+excluded-sources = ["Uniswap_V2"]
+slippage-bps = 100
+solution-gas-offset = 50000
+            "#,
+        )),
+    )
+    .await;
+
+    let solution = engine
+        .solve(json!({
+            "id": "1",
+            "tokens": {
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                    "decimals": 18,
+                    "symbol": "WETH",
+                    "referencePrice": "1000000000000000000",
+                    "availableBalance": "0",
+                    "trusted": false
+                },
+                "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                    "decimals": 18,
+                    "symbol": "COW",
+                    "referencePrice": "666666666666666",
+                    "availableBalance": "0",
+                    "trusted": false
+                }
+            },
+            "orders": [
+                {
+                    "uid": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                              2a2a2a2a",
+                    "sellToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                    "buyToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                    "sellAmount": "1000000000000000000",
+                    "fullSellAmount": "1000000000000000000",
+                    "buyAmount": "1400000000000000000000",
+                    "fullBuyAmount": "1400000000000000000000",
+                    "feePolicies": [],
+                    "validTo": 0,
+                    "kind": "sell",
+                    "owner": "0x5b1e2c2762667331bc91648052f646d1b0d35984",
+                    "partiallyFillable": false,
+                    "preInteractions": [],
+                    "postInteractions": [],
+                    "sellTokenSource": "erc20",
+                    "buyTokenDestination": "erc20",
+                    "class": "market",
+                    "appData": "0x6000000000000000000000000000000000000000000000000000000000000007",
+                    "signingScheme": "presign",
+                    "signature": "0x",
+                }
+            ],
+            "liquidity": [],
+            "effectiveGasPrice": "15000000000",
+            "deadline": "2106-01-01T00:00:00.000Z",
+            "surplusCapturingJitOrderOwners": []
+        }))
+        .await;
+
+    assert_eq!(
+        solution,
+        json!({
+            "solutions": [{
+                "id": 0,
+                "prices": {
+                    "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2": "1485000000000000000000",
+                    "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab": "1000000000000000000"
+                },
+                "trades": [
+                    {
+                        "kind": "fulfillment",
+                        "order": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                                    2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                                    2a2a2a2a",
+                        "executedAmount": "1000000000000000000"
+                    }
+                ],
+                "preInteractions": [],
+                "interactions": [
+                    {
+                        "kind": "custom",
+                        "internalize": false,
+                        "target": "0x0000000000001ff3684f28c67538d4d072c22734",
+                        "value": "0",
+                        "callData": "0x2213bc0b",
+                        "allowances": [
+                            {
+                                "token": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                                "spender": "0x0000000000001ff3684f28c67538d4d072c22734",
+                                "amount": "1000000000000000000"
+                            }
+                        ],
+                        "inputs": [
+                            {
+                                "token": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                                "amount": "1000000000000000000"
+                            }
+                        ],
+                        "outputs": [
+                            {
+                                "token": "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+                                "amount": "1485000000000000000000"
+                            }
+                        ]
+                    }
+                ],
+                "postInteractions": [],
+                "gas": 161000,
+            }]
+        }),
+    );
+}
@@ -0,0 +1,11 @@
+-- Strategies to override token balances in simulations (e.g. for quote
+-- verification) that were discovered automatically.
+CREATE TYPE BalanceOverrideStrategy AS ENUM ('solidity_mapping', 'vyper_mapping', 'solady_mapping');
+
+CREATE TABLE token_balance_overrides
+(
+    token       bytea                   PRIMARY KEY,
+    strategy    BalanceOverrideStrategy NOT NULL,
+    slot        bytea,
+    detected_at timestamptz             NOT NULL
+);
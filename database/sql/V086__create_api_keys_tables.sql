@@ -0,0 +1,19 @@
+-- Keys identifying API clients of the orderbook together with their request
+-- limits. Only the hash of the secret key gets stored.
+CREATE TABLE api_keys
+(
+    name              text        PRIMARY KEY,
+    key_hash          bytea       NOT NULL UNIQUE,
+    quotes_per_minute integer,
+    orders_per_minute integer,
+    created_at        timestamptz NOT NULL
+);
+
+-- Attribution of orders to the API key that was used to create them.
+CREATE TABLE order_api_keys
+(
+    order_uid bytea PRIMARY KEY,
+    api_key   text  NOT NULL
+);
+
+CREATE INDEX order_api_keys_api_key ON order_api_keys USING BTREE (api_key);
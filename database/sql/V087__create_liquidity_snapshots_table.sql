@@ -0,0 +1,9 @@
+-- Periodic snapshots of the indexed state of liquidity sources so that
+-- services can resume indexing from them instead of starting from scratch.
+CREATE TABLE liquidity_snapshots
+(
+    source       text        PRIMARY KEY,
+    block_number bigint      NOT NULL,
+    data         bytea       NOT NULL,
+    created_at   timestamptz NOT NULL
+);